@@ -1,17 +1,31 @@
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 use egui::load::SizedTexture;
-use egui::{Event, Image, ImageSource, Sense, TextureId, Ui, Vec2};
+use egui::{
+    Event, EventFilter, Image, ImageSource, Key, MouseWheelUnit, Pos2, Rect, Response, Sense,
+    TextureId, TouchPhase, Ui, Vec2, WidgetInfo, WidgetType,
+};
 use egui_wgpu::wgpu::{FilterMode, TextureView};
 use egui_wgpu::RenderState;
 use galileo::control::{
-    EventProcessor, MapController, MouseButton, RawUserEvent, UserEventHandler,
+    EventProcessor, InputFilter, MapController, MouseButton, RawUserEvent, TouchEvent,
+    UserEventHandler,
 };
+use galileo::file_drop::{DroppedFile, FileDropHandler};
 use galileo::galileo_types::cartesian::{Point2d, Size};
 use galileo::galileo_types::geo::impls::GeoPoint2d;
 use galileo::render::WgpuRenderer;
-use galileo::{Map, Messenger};
+use galileo::{Color, Map, Messenger};
+
+/// Number of points scrolled by one line of a mouse wheel. Same as the default `line_scroll_speed` of egui.
+const POINTS_PER_SCROLL_LINE: f32 = 40.0;
+/// Distance in points the map is moved by a single arrow key press.
+const KEYBOARD_PAN_STEP: f64 = 100.0;
+/// Resolution multiplier for a single zoom key press.
+const KEYBOARD_ZOOM_STEP: f64 = 0.5;
+const KEYBOARD_ZOOM_DURATION: Duration = Duration::from_millis(150);
 
 pub struct EguiMap<'a> {
     state: &'a mut EguiMapState,
@@ -71,9 +85,14 @@ pub struct EguiMapState {
     egui_render_state: RenderState,
     renderer: WgpuRenderer,
     requires_redraw: Arc<AtomicBool>,
+    repaint_requests: Arc<AtomicBool>,
     texture_id: TextureId,
     texture_view: TextureView,
     event_processor: EventProcessor,
+    input_filter: InputFilter,
+    file_drop_handler: Option<FileDropHandler>,
+    has_focus: bool,
+    focus_requested: bool,
 }
 
 impl EguiMapState {
@@ -84,9 +103,11 @@ impl EguiMapState {
         handlers: impl IntoIterator<Item = Box<dyn UserEventHandler>>,
     ) -> Self {
         let requires_redraw = Arc::new(AtomicBool::new(true));
+        let repaint_requests = Arc::new(AtomicBool::new(true));
         let messenger = MapStateMessenger {
             context: ctx.clone(),
             requires_redraw: requires_redraw.clone(),
+            repaint_requests: repaint_requests.clone(),
         };
 
         map.set_messenger(Some(messenger.clone()));
@@ -117,82 +138,235 @@ impl EguiMapState {
         for handler in handlers {
             event_processor.add_handler_boxed(handler);
         }
-        event_processor.add_handler(MapController::default());
+        event_processor.add_named_handler(
+            EventProcessor::MAP_CONTROLLER,
+            MapController::default(),
+            EventProcessor::LOW_PRIORITY,
+        );
 
         Self {
             map,
             egui_render_state: render_state,
             renderer,
             requires_redraw,
+            repaint_requests,
             texture_id,
             texture_view: texture,
             event_processor,
+            input_filter: InputFilter::default(),
+            file_drop_handler: None,
+            has_focus: false,
+            focus_requested: false,
         }
     }
 
+    /// Sets the background color of the map. Use [`Color::TRANSPARENT`] to show the UI behind the map through the
+    /// areas not covered by layers.
+    pub fn set_background(&mut self, color: Color) {
+        self.renderer.set_background(color);
+        self.map.redraw();
+    }
+
+    /// Enables or disables moving and zooming the map with the keyboard (arrow keys, `+` and `-`) when the map widget
+    /// has keyboard focus. Enabled by default.
+    pub fn set_keyboard_navigation(&mut self, enabled: bool) {
+        self.input_filter.keyboard = enabled;
+    }
+
+    /// Sets the classes of input events consumed by the map. Events of disabled classes are left to other widgets,
+    /// e.g. scrolling over the map scrolls the containing scroll area if [`InputFilter::scroll`] is disabled.
+    ///
+    /// By default all events are consumed.
+    pub fn set_input_filter(&mut self, filter: InputFilter) {
+        self.input_filter = filter;
+    }
+
+    /// Classes of input events consumed by the map.
+    pub fn input_filter(&self) -> InputFilter {
+        self.input_filter
+    }
+
+    /// Returns true if the map widget had keyboard focus during the last frame.
+    pub fn has_focus(&self) -> bool {
+        self.has_focus
+    }
+
+    /// Moves keyboard focus to the map widget on the next frame.
+    pub fn request_focus(&mut self) {
+        self.focus_requested = true;
+    }
+
+    /// Sets the handler that opens the files dropped onto the window as new layers on top of the map. By default
+    /// dropped files are ignored by the map.
+    pub fn set_file_drop_handler(&mut self, handler: Option<FileDropHandler>) {
+        self.file_drop_handler = handler;
+    }
+
     pub fn request_redraw(&self) {
         self.map.redraw();
     }
 
+    /// Enables or disables repaint requests to egui from the map. Enabled by default.
+    ///
+    /// When enabled, the map requests a repaint of the UI only when it has to be redrawn: a layer loaded new data,
+    /// the view was changed or an animation is running. Applications that control the repaint rate themselves (e.g.
+    /// to limit the frame rate on battery) can disable the requests and schedule repaints using
+    /// [`EguiMapState::needs_repaint`] and [`EguiMapState::next_repaint_in`].
+    pub fn set_repaint_requests(&mut self, enabled: bool) {
+        self.repaint_requests.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Returns true if the map has changed since it was drawn last time, or an animation is in progress. The map is
+    /// drawn by [`EguiMapState::render`] only when this method returns true, so other frames of the UI cost almost
+    /// nothing for the map.
+    pub fn needs_repaint(&self) -> bool {
+        !self.map.is_paused()
+            && (self.requires_redraw.load(Ordering::Relaxed) || self.map.is_animating())
+    }
+
+    /// Returns the time after which the UI should be repainted for the map, or `None` if the map doesn't need a
+    /// repaint until a new input event or a change of data. This value can be given to
+    /// `egui::Context::request_repaint_after` when [repaint requests](EguiMapState::set_repaint_requests) are disabled.
+    pub fn next_repaint_in(&self) -> Option<Duration> {
+        if self.needs_repaint() {
+            return Some(Duration::ZERO);
+        }
+
+        self.event_processor.next_update_in()
+    }
+
     pub fn render(&mut self, ui: &mut egui::Ui) {
         let available_size = ui.available_size();
-        let map_size = self.renderer.size().cast::<f32>();
 
         let (rect, response) = ui.allocate_exact_size(available_size, Sense::click_and_drag());
+        response.widget_info(|| WidgetInfo::labeled(WidgetType::Other, ui.is_enabled(), "Map"));
+
+        // Nothing can be drawn into a zero size area (e.g. collapsed panel or minimized window). The map keeps its
+        // last size and render target, and continues when the area gets non-zero size again.
+        let target_size = Size::new(
+            available_size.x.round() as u32,
+            available_size.y.round() as u32,
+        );
+        if target_size.width() == 0 || target_size.height() == 0 {
+            return;
+        }
+
+        self.update_focus(ui, &response);
 
-        if self.event_processor.is_dragging() || response.contains_pointer() {
+        // Other widgets or areas drawn above the map, or dragged over it, get the pointer events, unless the drag
+        // started on the map
+        let other_is_dragged = ui
+            .ctx()
+            .dragged_id()
+            .is_some_and(|dragged| dragged != response.id);
+        let owns_pointer = self.event_processor.is_dragging()
+            || response.is_pointer_button_down_on()
+            || (response.contains_pointer() && !other_is_dragged);
+
+        if owns_pointer {
             let events = ui.input(|input_state| input_state.events.clone());
-            self.process_events(&events);
+            self.process_events(&events, rect);
+
+            if response.contains_pointer() && self.input_filter.scroll {
+                // Scroll over the map is used for zooming, so scroll areas containing the map must not scroll
+                ui.input_mut(|input_state| {
+                    input_state.raw_scroll_delta = Vec2::ZERO;
+                    input_state.smooth_scroll_delta = Vec2::ZERO;
+                });
+            }
+        }
+
+        if self.has_focus && self.input_filter.keyboard {
+            self.process_keyboard(ui);
+        }
+
+        self.open_dropped_files(ui.ctx());
+
+        self.event_processor.update(&mut self.map);
+        if self.repaint_requests.load(Ordering::Relaxed) {
+            if let Some(delay) = self.event_processor.next_update_in() {
+                ui.ctx().request_repaint_after(delay);
+            }
         }
 
         self.map.animate();
 
-        if available_size[0] != map_size.width() || available_size[1] != map_size.height() {
-            self.resize_map(available_size);
+        if target_size != self.renderer.size().cast() {
+            self.resize_map(target_size);
         }
 
-        if self.requires_redraw.swap(false, Ordering::Relaxed) {
+        if !self.map.is_paused() && self.requires_redraw.swap(false, Ordering::Relaxed) {
             self.draw();
         }
 
         Image::new(ImageSource::Texture(SizedTexture::new(
             self.texture_id,
-            Vec2::new(map_size.width(), map_size.height()),
+            rect.size(),
         )))
         .paint_at(ui, rect);
     }
 
-    fn resize_map(&mut self, size: Vec2) {
+    fn resize_map(&mut self, size: Size<u32>) {
         log::trace!("Resizing map to size: {size:?}");
 
-        let size = Size::new(size.x as f64, size.y as f64);
-        self.map.set_size(size);
-
-        let size = Size::new(size.width() as u32, size.height() as u32);
+        self.map.set_size(size.cast());
         self.renderer.resize(size);
 
-        // After renderer is resized, a new texture is created, so we need to update its id that we
-        // use in UI.
+        // After renderer is resized, a new texture is created, so the texture registered in UI must be updated. The
+        // texture id stays the same, so the old texture is released by egui.
         let texture = self
             .renderer
             .get_target_texture_view()
             .expect("failed to get map texture");
-        let texture_id = self
-            .egui_render_state
+        self.egui_render_state
             .renderer
             .write()
-            .register_native_texture(
+            .update_egui_texture_from_wgpu_texture(
                 &self.egui_render_state.device,
                 &texture,
                 FilterMode::Nearest,
+                self.texture_id,
             );
-
-        self.texture_id = texture_id;
         self.texture_view = texture;
 
         self.map.redraw();
     }
 
+    /// Moves the map to a different render state, e.g. after the graphics device was recreated or when the map is
+    /// moved to a viewport rendered with a different device.
+    ///
+    /// The render target of the map is recreated, the data the layers have packed for the previous device is dropped
+    /// (see [`Map::clear_render_cache`]), and the map is redrawn at the next frame.
+    pub fn set_render_state(&mut self, render_state: RenderState) {
+        self.egui_render_state
+            .renderer
+            .write()
+            .free_texture(&self.texture_id);
+
+        let size = self.renderer.size().cast();
+        let mut renderer = WgpuRenderer::new_with_device_and_texture(
+            render_state.device.clone(),
+            render_state.queue.clone(),
+            size,
+        );
+        renderer.set_background(self.renderer.background());
+
+        let texture = renderer
+            .get_target_texture_view()
+            .expect("failed to get map texture");
+        self.texture_id = render_state.renderer.write().register_native_texture(
+            &render_state.device,
+            &texture,
+            FilterMode::Nearest,
+        );
+        self.texture_view = texture;
+        self.renderer = renderer;
+        self.egui_render_state = render_state;
+
+        // Bundles packed by the previous device cannot be drawn with the new one
+        self.map.clear_render_cache();
+    }
+
     fn draw(&mut self) {
         log::trace!("Redrawing the map");
         self.map.load_layers();
@@ -200,15 +374,135 @@ impl EguiMapState {
             .render_to_texture_view(&self.map, &self.texture_view);
     }
 
-    fn process_events(&mut self, events: &[Event]) {
+    fn update_focus(&mut self, ui: &Ui, response: &Response) {
+        if self.focus_requested || response.clicked() || response.drag_started() {
+            response.request_focus();
+            self.focus_requested = false;
+        }
+
+        self.has_focus = response.has_focus();
+        if self.has_focus && self.input_filter.keyboard {
+            // Arrow keys are used to move the map instead of moving the focus to other widgets
+            ui.memory_mut(|memory| {
+                memory.set_focus_lock_filter(
+                    response.id,
+                    EventFilter {
+                        horizontal_arrows: true,
+                        vertical_arrows: true,
+                        ..Default::default()
+                    },
+                )
+            });
+        }
+    }
+
+    fn open_dropped_files(&mut self, ctx: &egui::Context) {
+        let Some(handler) = &self.file_drop_handler else {
+            return;
+        };
+
+        let dropped_files = ctx.input(|input| input.raw.dropped_files.clone());
+        if dropped_files.is_empty() {
+            return;
+        }
+
+        let mut files = vec![];
+        for dropped in dropped_files {
+            // Browsers give the contents of the file, native platforms give the path
+            match (&dropped.bytes, &dropped.path) {
+                (Some(bytes), _) => {
+                    files.push(DroppedFile::new(dropped.name.clone(), bytes.to_vec()))
+                }
+                (None, Some(path)) => match DroppedFile::read(path) {
+                    Ok(file) => files.push(file),
+                    Err(err) => log::warn!("Failed to read dropped file {path:?}: {err}"),
+                },
+                (None, None) => {}
+            }
+        }
+
+        // Images are opened together with the world files dropped with them
+        for (name, result) in handler.open_all(files) {
+            let mut layer = match result {
+                Ok(layer) => layer,
+                Err(err) => {
+                    log::warn!("Failed to open dropped file {name}: {err}");
+                    continue;
+                }
+            };
+
+            layer.set_messenger(Box::new(MapStateMessenger {
+                context: ctx.clone(),
+                requires_redraw: self.requires_redraw.clone(),
+                repaint_requests: self.repaint_requests.clone(),
+            }));
+            self.map.layers_mut().push_boxed(layer);
+            self.map.redraw();
+        }
+    }
+
+    fn process_keyboard(&mut self, ui: &Ui) {
+        let (left, right, up, down, zoom_in, zoom_out) = ui.input(|input| {
+            (
+                input.key_pressed(Key::ArrowLeft),
+                input.key_pressed(Key::ArrowRight),
+                input.key_pressed(Key::ArrowUp),
+                input.key_pressed(Key::ArrowDown),
+                input.key_pressed(Key::Plus) || input.key_pressed(Key::Equals),
+                input.key_pressed(Key::Minus),
+            )
+        });
+
+        let dx = (left as i8 - right as i8) as f64 * KEYBOARD_PAN_STEP;
+        let dy = (up as i8 - down as i8) as f64 * KEYBOARD_PAN_STEP;
+        if dx != 0.0 || dy != 0.0 {
+            self.map.pan_by_pixels(dx, dy);
+        }
+
+        let size = self.map.view().size();
+        let center = Point2d::new(size.width() / 2.0, size.height() / 2.0);
+        if zoom_in {
+            self.map
+                .zoom_around(center, KEYBOARD_ZOOM_STEP, KEYBOARD_ZOOM_DURATION);
+        }
+        if zoom_out {
+            self.map
+                .zoom_around(center, 1.0 / KEYBOARD_ZOOM_STEP, KEYBOARD_ZOOM_DURATION);
+        }
+    }
+
+    fn process_events(&mut self, events: &[Event], rect: Rect) {
+        // Egui duplicates touches as pointer events for the widgets that do not handle touches. The map handles
+        // touches itself (including multi-touch gestures), so these pointer events must be skipped.
+        let is_touch_input = events
+            .iter()
+            .any(|event| matches!(event, Event::Touch { .. }));
+
         for event in events {
-            if let Some(raw_event) = Self::convert_event(event) {
+            if is_touch_input
+                && matches!(
+                    event,
+                    Event::PointerMoved(_) | Event::PointerButton { .. } | Event::PointerGone
+                )
+            {
+                continue;
+            }
+
+            if let Some(raw_event) = Self::convert_event(event, rect)
+                .filter(|raw_event| self.input_filter.accepts(raw_event))
+            {
                 self.event_processor.handle(raw_event, &mut self.map);
             }
         }
     }
 
-    fn convert_event(event: &Event) -> Option<RawUserEvent> {
+    fn convert_event(event: &Event, rect: Rect) -> Option<RawUserEvent> {
+        // Galileo expects positions relative to the top left corner of the map
+        let to_map_position = |position: &Pos2| {
+            let position = *position - rect.min;
+            Point2d::new(position.x as f64, position.y as f64)
+        };
+
         match event {
             Event::PointerButton {
                 button, pressed, ..
@@ -226,19 +520,41 @@ impl EguiMapState {
                 })
             }
             Event::PointerMoved(position) => {
-                let scale = 1.0;
-                let pointer_position =
-                    Point2d::new(position.x as f64 / scale, position.y as f64 / scale);
-                Some(RawUserEvent::PointerMoved(pointer_position))
+                Some(RawUserEvent::PointerMoved(to_map_position(position)))
             }
-            Event::MouseWheel { delta, .. } => {
-                let zoom = delta[1] as f64;
+            Event::MouseWheel { unit, delta, .. } => {
+                // Touch pads and kinetic scrolling give the delta in points
+                let lines = match unit {
+                    MouseWheelUnit::Line => delta.y,
+                    MouseWheelUnit::Point => delta.y / POINTS_PER_SCROLL_LINE,
+                    MouseWheelUnit::Page => delta.y * rect.height() / POINTS_PER_SCROLL_LINE,
+                };
+                let zoom = lines as f64;
                 if zoom.abs() < 0.0001 {
                     return None;
                 }
 
                 Some(RawUserEvent::Scroll(zoom))
             }
+            Event::Zoom(factor) => {
+                if *factor <= 0.0 || (*factor - 1.0).abs() < 0.0001 {
+                    return None;
+                }
+
+                Some(RawUserEvent::Zoom(1.0 / *factor as f64))
+            }
+            Event::Touch { id, phase, pos, .. } => {
+                let touch = TouchEvent {
+                    touch_id: id.0,
+                    position: to_map_position(pos),
+                };
+
+                Some(match phase {
+                    TouchPhase::Start => RawUserEvent::TouchStart(touch),
+                    TouchPhase::Move => RawUserEvent::TouchMove(touch),
+                    TouchPhase::End | TouchPhase::Cancel => RawUserEvent::TouchEnd(touch),
+                })
+            }
 
             _ => None,
         }
@@ -248,13 +564,18 @@ impl EguiMapState {
 #[derive(Debug, Clone)]
 pub struct MapStateMessenger {
     pub requires_redraw: Arc<AtomicBool>,
+    /// If false, only marks the map as requiring redraw without requesting a repaint from egui. See
+    /// [`EguiMapState::set_repaint_requests`].
+    pub repaint_requests: Arc<AtomicBool>,
     pub context: egui::Context,
 }
 
 impl Messenger for MapStateMessenger {
     fn request_redraw(&self) {
         log::trace!("Redraw requested");
-        if !self.requires_redraw.swap(true, Ordering::Relaxed) {
+        if !self.requires_redraw.swap(true, Ordering::Relaxed)
+            && self.repaint_requests.load(Ordering::Relaxed)
+        {
             self.context.request_repaint();
         }
     }
@@ -0,0 +1,100 @@
+//! Benchmarks of vector tile decoding.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use galileo_mvt::MvtTile;
+use geozero::mvt::tile::{Feature, GeomType, Layer};
+use geozero::mvt::{Message, Tile};
+
+const FEATURES_COUNT: u32 = 10_000;
+const LINE_POINTS: u32 = 32;
+
+fn command(id: u32, count: u32) -> u32 {
+    (count << 3) | id
+}
+
+fn zigzag(value: i32) -> u32 {
+    ((value << 1) ^ (value >> 31)) as u32
+}
+
+/// Creates a tile with a single layer of `FEATURES_COUNT` features of the given type.
+fn synthetic_tile(geom_type: GeomType) -> Vec<u8> {
+    let features = (0..FEATURES_COUNT)
+        .map(|i| {
+            let start = (i % 4000) as i32;
+            let geometry = match geom_type {
+                GeomType::Point => vec![command(1, 1), zigzag(start), zigzag(start)],
+                GeomType::Linestring => {
+                    let mut geometry = vec![command(1, 1), zigzag(start), zigzag(start)];
+                    geometry.push(command(2, LINE_POINTS));
+                    for j in 0..LINE_POINTS {
+                        let step = if j % 2 == 0 { 3 } else { -1 };
+                        geometry.extend([zigzag(step), zigzag(2)]);
+                    }
+                    geometry
+                }
+                _ => vec![
+                    command(1, 1),
+                    zigzag(start),
+                    zigzag(start),
+                    command(2, 3),
+                    zigzag(10),
+                    zigzag(0),
+                    zigzag(0),
+                    zigzag(10),
+                    zigzag(-10),
+                    zigzag(0),
+                    command(7, 1),
+                ],
+            };
+
+            Feature {
+                id: Some(i as u64),
+                tags: vec![],
+                r#type: Some(geom_type as i32),
+                geometry,
+            }
+        })
+        .collect();
+
+    let tile = Tile {
+        layers: vec![Layer {
+            version: 2,
+            name: "benchmark".into(),
+            features,
+            keys: vec![],
+            values: vec![],
+            extent: Some(4096),
+        }],
+    };
+
+    tile.encode_to_vec()
+}
+
+fn decode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("decode");
+
+    let sample = include_bytes!("../test-data/vt.mvt");
+    group.throughput(Throughput::Bytes(sample.len() as u64));
+    group.bench_function("sample_tile", |b| {
+        b.iter(|| MvtTile::decode(black_box(&sample[..]), false))
+    });
+
+    group.throughput(Throughput::Elements(FEATURES_COUNT as u64));
+    for (name, geom_type) in [
+        ("points_10k", GeomType::Point),
+        ("lines_10k", GeomType::Linestring),
+        ("polygons_10k", GeomType::Polygon),
+    ] {
+        let data = synthetic_tile(geom_type);
+        group.bench_function(name, |b| {
+            b.iter(|| MvtTile::decode(black_box(&data[..]), false))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, decode);
+criterion_main!(benches);
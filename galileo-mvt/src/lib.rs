@@ -60,6 +60,20 @@ impl Display for MvtValue {
     }
 }
 
+impl MvtValue {
+    /// Returns the value as a number, if it is numeric or a string that can be parsed as a number.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            MvtValue::String(v) => v.parse().ok(),
+            MvtValue::Float(v) => Some(*v as f64),
+            MvtValue::Double(v) => Some(*v),
+            MvtValue::Int64(v) => Some(*v as f64),
+            MvtValue::Uint64(v) => Some(*v as f64),
+            MvtValue::Bool(_) | MvtValue::Unknown => None,
+        }
+    }
+}
+
 impl DisplayStr for MvtValue {
     fn display_str(&self, f: &mut strfmt::Formatter) -> strfmt::Result<()> {
         f.str(&self.to_string())?;
@@ -255,7 +269,7 @@ impl MvtFeature {
         } = pb_feature;
         let pb_type = opt_number_to_geomtype(r#type);
         let properties = Self::decode_properties(tags, keys, values)?;
-        let geometry = Self::decode_geometry(pb_type, geometry, extent)?;
+        let geometry = Self::decode_geometry(pb_type, &geometry, extent)?;
 
         Ok(MvtFeature {
             id,
@@ -291,28 +305,33 @@ impl MvtFeature {
 
     fn decode_geometry(
         geom_type: GeomType,
-        commands: Vec<u32>,
+        commands: &[u32],
         extent: u32,
     ) -> Result<MvtGeometry, GalileoMvtError> {
+        let reader = CommandReader::new(commands, extent);
         Ok(match geom_type {
             GeomType::Unknown => {
                 return Err(GalileoMvtError::Generic("Unknown geometry type".into()))
             }
-            GeomType::Point => MvtGeometry::Point(Self::decode_point(commands, extent)?),
-            GeomType::Linestring => MvtGeometry::LineString(Self::decode_line(commands, extent)?),
-            GeomType::Polygon => MvtGeometry::Polygon(Self::decode_polygon(commands, extent)?),
+            GeomType::Point => MvtGeometry::Point(Self::decode_point(reader)?),
+            GeomType::Linestring => MvtGeometry::LineString(Self::decode_line(reader)?),
+            GeomType::Polygon => MvtGeometry::Polygon(Self::decode_polygon(reader)?),
         })
     }
 
-    fn decode_point(commands: Vec<u32>, extent: u32) -> Result<Vec<Point>, GalileoMvtError> {
-        let mut points = Vec::with_capacity(commands.len() / 2);
-        for command in Self::decode_commands(commands, extent) {
+    fn decode_point(mut reader: CommandReader) -> Result<Vec<Point>, GalileoMvtError> {
+        let mut points = Vec::with_capacity(reader.remaining() / 2);
+        while let Some(command) = reader.next_command() {
             match command? {
-                MvtGeomCommand::MoveTo(p) => points.push(p),
-                _ => {
-                    return Err(GalileoMvtError::Generic(
-                        "Point geometry cannot have {:?} command".into(),
-                    ))
+                (MOVE_TO, count) => {
+                    for _ in 0..count {
+                        points.push(reader.read_point()?);
+                    }
+                }
+                (id, _) => {
+                    return Err(GalileoMvtError::Generic(format!(
+                        "Point geometry cannot have command {id}"
+                    )))
                 }
             }
         }
@@ -320,17 +339,14 @@ impl MvtFeature {
         Ok(points)
     }
 
-    fn decode_line(
-        commands: Vec<u32>,
-        extent: u32,
-    ) -> Result<Vec<Contour<Point>>, GalileoMvtError> {
-        let mut contours = Vec::with_capacity(64);
+    fn decode_line(mut reader: CommandReader) -> Result<Vec<Contour<Point>>, GalileoMvtError> {
+        let mut contours = Vec::new();
         let mut current_contour: Option<Vec<Point>> = None;
         let mut first_point = None;
 
-        for command in Self::decode_commands(commands, extent) {
+        while let Some(command) = reader.next_command() {
             match command? {
-                MvtGeomCommand::MoveTo(p) => {
+                (MOVE_TO, count) => {
                     if let Some(curr) = current_contour.take() {
                         if curr.len() < 2 {
                             return Err(GalileoMvtError::Generic(
@@ -341,31 +357,45 @@ impl MvtFeature {
                         contours.push(Contour::open(curr));
                     }
 
-                    first_point = Some(p);
+                    for _ in 0..count {
+                        first_point = Some(reader.read_point()?);
+                    }
                 }
-                MvtGeomCommand::LineTo(p, count) => {
-                    if let Some(curr) = &mut current_contour {
+                (LINE_TO, count) => {
+                    let curr = match (current_contour.take(), first_point) {
+                        (Some(mut curr), _) => {
+                            curr.reserve(count as usize);
+                            curr
+                        }
+                        (None, Some(first)) => {
+                            let mut curr = Vec::with_capacity(count as usize + 1);
+                            curr.push(first);
+                            curr
+                        }
+                        (None, None) => {
+                            return Err(GalileoMvtError::Generic(
+                                "First command in the line cannot be MoveTo".into(),
+                            ));
+                        }
+                    };
+                    let curr = current_contour.insert(curr);
+
+                    for _ in 0..count {
+                        let p = reader.read_point()?;
                         // todo: make this less hacky
-                        if curr[curr.len() - 1].taxicab_distance(&p) < 1.0 / 1024.0 {
+                        if curr.len() > 1
+                            && curr[curr.len() - 1].taxicab_distance(&p) < 1.0 / 1024.0
+                        {
                             continue;
                         }
 
                         curr.push(p);
-                    } else if let Some(first) = first_point {
-                        let mut curr = Vec::with_capacity(count);
-                        curr.push(first);
-                        curr.push(p);
-                        current_contour = Some(curr);
-                    } else {
-                        return Err(GalileoMvtError::Generic(
-                            "First command in the line cannot be MoveTo".into(),
-                        ));
                     }
                 }
-                _ => {
-                    return Err(GalileoMvtError::Generic(
-                        "Linestring geometry cannot have {:?} command".into(),
-                    ))
+                (id, _) => {
+                    return Err(GalileoMvtError::Generic(format!(
+                        "Linestring geometry cannot have command {id}"
+                    )))
                 }
             }
         }
@@ -383,41 +413,49 @@ impl MvtFeature {
         Ok(contours)
     }
 
-    fn decode_polygon(
-        commands: Vec<u32>,
-        extent: u32,
-    ) -> Result<Vec<Polygon<Point>>, GalileoMvtError> {
-        let mut polygons = Vec::with_capacity(64);
+    fn decode_polygon(mut reader: CommandReader) -> Result<Vec<Polygon<Point>>, GalileoMvtError> {
+        let mut polygons = Vec::new();
         let mut curr_polygon = None;
         let mut curr_contour: Option<Vec<Point>> = None;
         let mut first_point = None;
 
-        for command in Self::decode_commands(commands, extent) {
+        while let Some(command) = reader.next_command() {
             match command? {
-                MvtGeomCommand::MoveTo(p) => {
+                (MOVE_TO, count) => {
                     if curr_contour.is_some() {
                         return Err(GalileoMvtError::Generic(
                             "Polygon cannot have unclosed contours".into(),
                         ));
                     }
 
-                    first_point = Some(p);
+                    for _ in 0..count {
+                        first_point = Some(reader.read_point()?);
+                    }
                 }
-                MvtGeomCommand::LineTo(p, count) => {
-                    if let Some(curr) = &mut curr_contour {
-                        curr.push(p)
-                    } else if let Some(first) = first_point {
-                        let mut curr = Vec::with_capacity(count);
-                        curr.push(first);
-                        curr.push(p);
-                        curr_contour = Some(curr);
-                    } else {
-                        return Err(GalileoMvtError::Generic(
-                            "Contour must start with move to command".into(),
-                        ));
+                (LINE_TO, count) => {
+                    let curr = match (curr_contour.take(), first_point) {
+                        (Some(mut curr), _) => {
+                            curr.reserve(count as usize);
+                            curr
+                        }
+                        (None, Some(first)) => {
+                            let mut curr = Vec::with_capacity(count as usize + 1);
+                            curr.push(first);
+                            curr
+                        }
+                        (None, None) => {
+                            return Err(GalileoMvtError::Generic(
+                                "Contour must start with move to command".into(),
+                            ));
+                        }
+                    };
+                    let curr = curr_contour.insert(curr);
+
+                    for _ in 0..count {
+                        curr.push(reader.read_point()?);
                     }
                 }
-                MvtGeomCommand::ClosePath => {
+                (CLOSE_PATH, _) => {
                     let Some(curr) = curr_contour.take() else {
                         return Err(GalileoMvtError::Generic(
                             "No opened polygon, cannot close path".into(),
@@ -456,6 +494,9 @@ impl MvtFeature {
                         });
                     }
                 }
+                (id, _) => {
+                    return Err(GalileoMvtError::Generic(format!("Unknown command id {id}")))
+                }
             }
         }
 
@@ -465,73 +506,83 @@ impl MvtFeature {
 
         Ok(polygons)
     }
-
-    fn decode_commands(
-        commands: Vec<u32>,
-        extent: u32,
-    ) -> impl Iterator<Item = Result<MvtGeomCommand, GalileoMvtError>> {
-        CommandIterator::new(commands.into_iter(), extent)
-    }
 }
 
-struct CommandIterator<T: Iterator<Item = u32>> {
-    inner: T,
-    extent: u32,
-    current_command: Option<(u32, u32)>,
-    can_continue: bool,
-    cursor: Point,
+const MOVE_TO: u32 = 1;
+const LINE_TO: u32 = 2;
+const CLOSE_PATH: u32 = 7;
+
+/// Reads geometry commands directly from the encoded command buffer.
+///
+/// The cursor is accumulated in integer tile coordinates, and points are converted into the `0..1` range only when
+/// they are read, so no precision is lost on long geometries.
+struct CommandReader<'a> {
+    commands: &'a [u32],
+    position: usize,
+    extent: f32,
+    cursor: [i32; 2],
 }
 
-impl<T: Iterator<Item = u32>> CommandIterator<T> {
-    fn new(inner: T, extent: u32) -> Self {
+impl<'a> CommandReader<'a> {
+    fn new(commands: &'a [u32], extent: u32) -> Self {
         Self {
-            inner,
-            extent,
-            current_command: None,
-            can_continue: true,
-            cursor: Point::default(),
+            commands,
+            position: 0,
+            extent: extent as f32,
+            cursor: [0, 0],
         }
     }
 
-    fn read_move_to(&mut self) -> Result<MvtGeomCommand, GalileoMvtError> {
-        self.cursor = self.read_point()?;
-        Ok(MvtGeomCommand::MoveTo(self.cursor))
+    /// Number of integers that are not read yet.
+    fn remaining(&self) -> usize {
+        self.commands.len() - self.position
     }
 
-    fn read_line_to(&mut self, command_count: u32) -> Result<MvtGeomCommand, GalileoMvtError> {
-        self.cursor = self.read_point()?;
-        Ok(MvtGeomCommand::LineTo(
-            self.cursor,
-            command_count as usize + 1,
-        ))
-    }
+    /// Reads the next command integer and returns the id and the count of the command. The parameters of the command
+    /// must be read by the caller before reading the next command.
+    fn next_command(&mut self) -> Option<Result<(u32, u32), GalileoMvtError>> {
+        let command_integer = *self.commands.get(self.position)?;
+        self.position += 1;
 
-    fn read_point(&mut self) -> Result<Point, GalileoMvtError> {
-        let vals = self.read_vals::<2>()?;
-        Ok(Point::new(
-            self.decode_sint_coord(vals[0]) + self.cursor.x(),
-            self.decode_sint_coord(vals[1]) + self.cursor.y(),
-        ))
-    }
+        let command_id = command_integer & 0x7;
+        let command_count = command_integer >> 3;
 
-    fn decode_sint_coord(&mut self, val: u32) -> f32 {
-        sint_to_int(val) as f32 / self.extent as f32
+        Some(match (command_id, command_count) {
+            (_, 0) => {
+                self.position = self.commands.len();
+                Err(GalileoMvtError::Generic("Command count cannot be 0".into()))
+            }
+            (CLOSE_PATH, count) if count != 1 => {
+                self.position = self.commands.len();
+                Err(GalileoMvtError::Generic(format!(
+                    "ClosePath command must have count 0, but has {count}"
+                )))
+            }
+            (MOVE_TO | LINE_TO | CLOSE_PATH, _) => Ok((command_id, command_count)),
+            _ => {
+                self.position = self.commands.len();
+                Err(GalileoMvtError::Generic(format!(
+                    "Unknown command id {command_id}"
+                )))
+            }
+        })
     }
 
-    fn read_vals<const COUNT: usize>(&mut self) -> Result<[u32; COUNT], GalileoMvtError> {
-        let mut result = [0; COUNT];
-        for val in result.iter_mut() {
-            *val = match self.inner.next() {
-                Some(v) => v,
-                None => {
-                    return Err(GalileoMvtError::Generic(
-                        "Expected value to be present, but found end of data".into(),
-                    ));
-                }
-            };
-        }
+    fn read_point(&mut self) -> Result<Point, GalileoMvtError> {
+        let Some(&[dx, dy]) = self.commands.get(self.position..self.position + 2) else {
+            return Err(GalileoMvtError::Generic(
+                "Expected value to be present, but found end of data".into(),
+            ));
+        };
+        self.position += 2;
 
-        Ok(result)
+        self.cursor[0] = self.cursor[0].wrapping_add(sint_to_int(dx));
+        self.cursor[1] = self.cursor[1].wrapping_add(sint_to_int(dy));
+
+        Ok(Point::new(
+            self.cursor[0] as f32 / self.extent,
+            self.cursor[1] as f32 / self.extent,
+        ))
     }
 }
 
@@ -548,65 +599,6 @@ fn sint_to_int(sint: u32) -> i32 {
     }
 }
 
-impl<T: Iterator<Item = u32>> Iterator for CommandIterator<T> {
-    type Item = Result<MvtGeomCommand, GalileoMvtError>;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        if !self.can_continue {
-            return None;
-        }
-
-        let (command_id, command_count) = match self.current_command {
-            Some((id, count)) => (id, count),
-            None => {
-                let command_integer = self.inner.next()?;
-                let command_id = command_integer & 0x7;
-                let command_count = command_integer >> 3;
-
-                (command_id, command_count)
-            }
-        };
-
-        self.current_command = match command_count {
-            0 => {
-                self.can_continue = false;
-                return Some(Err(GalileoMvtError::Generic(
-                    "Command count cannot be 0".into(),
-                )));
-            }
-            1 => None,
-            v => Some((command_id, v - 1)),
-        };
-
-        Some(match command_id {
-            1 => self.read_move_to(),
-            2 => self.read_line_to(command_count),
-            7 => {
-                if command_count != 1 {
-                    self.can_continue = false;
-                    Err(GalileoMvtError::Generic(format!(
-                        "ClosePath command must have count 0, but has {command_count}"
-                    )))
-                } else {
-                    Ok(MvtGeomCommand::ClosePath)
-                }
-            }
-            _ => {
-                self.can_continue = false;
-                Err(GalileoMvtError::Generic(format!(
-                    "Unknown command id {command_id}"
-                )))
-            }
-        })
-    }
-}
-
-enum MvtGeomCommand {
-    MoveTo(Point),
-    LineTo(Point, usize),
-    ClosePath,
-}
-
 #[cfg(test)]
 mod tests {
     use std::io::Cursor;
@@ -628,4 +620,43 @@ mod tests {
         let vt = include_bytes!("../test-data/vt.mvt");
         let _tile = MvtTile::decode(&mut Cursor::new(&vt), false).unwrap();
     }
+
+    #[test]
+    fn decode_geometry_commands() {
+        // Examples from the vector tile specification
+        let MvtGeometry::Point(points) =
+            MvtFeature::decode_geometry(GeomType::Point, &[9, 50, 34], 4096).unwrap()
+        else {
+            panic!("expected point geometry");
+        };
+        assert_eq!(points, vec![Point::new(25.0 / 4096.0, 17.0 / 4096.0)]);
+
+        let MvtGeometry::LineString(lines) = MvtFeature::decode_geometry(
+            GeomType::Linestring,
+            &[9, 4, 4, 18, 0, 16, 16, 0, 9, 17, 17, 10, 4, 8],
+            16,
+        )
+        .unwrap() else {
+            panic!("expected line geometry");
+        };
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0],
+            Contour::open(vec![
+                Point::new(2.0 / 16.0, 2.0 / 16.0),
+                Point::new(2.0 / 16.0, 10.0 / 16.0),
+                Point::new(10.0 / 16.0, 10.0 / 16.0),
+            ])
+        );
+        assert_eq!(
+            lines[1],
+            Contour::open(vec![
+                Point::new(1.0 / 16.0, 1.0 / 16.0),
+                Point::new(3.0 / 16.0, 5.0 / 16.0),
+            ])
+        );
+
+        assert!(MvtFeature::decode_geometry(GeomType::Linestring, &[10, 4, 4], 16).is_err());
+        assert!(MvtFeature::decode_geometry(GeomType::Point, &[9, 50], 4096).is_err());
+    }
 }
@@ -255,7 +255,7 @@ impl<N: Num + Copy + PartialOrd + Scalar + FromPrimitive> Rect<N> {
     pub fn intersects(&self, other: Rect<N>) -> bool {
         self.x_max >= other.x_min
             && self.x_min <= other.x_max
-            && self.y_max >= other.y_max
+            && self.y_max >= other.y_min
             && self.y_min <= other.y_max
     }
 }
@@ -273,3 +273,19 @@ impl<N: Num + Copy + PartialOrd + Scalar + FromPrimitive> FromIterator<Rect<N>>
         Some(prev)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intersects() {
+        let rect = Rect::new(0.0, 0.0, 10.0, 10.0);
+        assert!(rect.intersects(Rect::new(5.0, 5.0, 15.0, 15.0)));
+        assert!(rect.intersects(Rect::new(5.0, -5.0, 15.0, 5.0)));
+        assert!(rect.intersects(Rect::new(2.0, 2.0, 3.0, 3.0)));
+        assert!(!rect.intersects(Rect::new(5.0, 11.0, 15.0, 15.0)));
+        assert!(!rect.intersects(Rect::new(5.0, -15.0, 15.0, -5.0)));
+        assert!(!rect.intersects(Rect::new(11.0, 0.0, 15.0, 10.0)));
+    }
+}
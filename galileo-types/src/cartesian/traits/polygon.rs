@@ -1,10 +1,22 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::fmt::Debug;
+
 use nalgebra::Point2;
+use num_traits::{Bounded, Float, FromPrimitive, One, Zero};
 
 use crate::cartesian::traits::cartesian_point::CartesianPoint2d;
-use crate::contour::ClosedContour;
+use crate::contour::{ClosedContour, Contour};
+use crate::geometry::Geom;
+use crate::multi_contour::MultiContour;
+use crate::multi_point::MultiPoint;
+use crate::multi_polygon::MultiPolygon;
 use crate::polygon::Polygon;
 use crate::segment::Segment;
 
+/// Maximum number of cells checked by the [`CartesianPolygon::representative_point`] algorithm.
+const MAX_POLYLABEL_CELLS: usize = 10_000;
+
 /// Polygon in 2d cartesian coordinates. This trait is auto-implemented for all illegible types.
 pub trait CartesianPolygon {
     /// Type of the points of the polygon.
@@ -14,6 +26,46 @@ pub trait CartesianPolygon {
     fn contains_point<P>(&self, point: &P) -> bool
     where
         P: CartesianPoint2d<Num = <Self::Point as CartesianPoint2d>::Num>;
+
+    /// Center of mass of the polygon area, taking holes into account.
+    ///
+    /// The centroid can be outside of the polygon for concave shapes. To get a point that is guaranteed to be inside
+    /// the polygon (e.g. to place a label), use [`CartesianPolygon::representative_point`].
+    ///
+    /// Returns `None` if the polygon has zero area.
+    fn centroid(&self) -> Option<Point2<<Self::Point as CartesianPoint2d>::Num>>
+    where
+        <Self::Point as CartesianPoint2d>::Num: Float;
+
+    /// Point inside the polygon that is the farthest from its boundary (*pole of inaccessibility*), calculated with
+    /// the [polylabel](https://github.com/mapbox/polylabel) algorithm. This is usually the best position to place a
+    /// label or an icon of the polygon.
+    ///
+    /// The point is found with the given `precision` (in the units of the polygon coordinates).
+    ///
+    /// Returns `None` if the polygon has no points.
+    fn representative_point(
+        &self,
+        precision: <Self::Point as CartesianPoint2d>::Num,
+    ) -> Option<Point2<<Self::Point as CartesianPoint2d>::Num>>
+    where
+        <Self::Point as CartesianPoint2d>::Num: Float;
+
+    /// Returns true if the `geometry` has at least one common point with the polygon.
+    ///
+    /// The check compares every segment of the geometry with every segment of the polygon, so it is meant for
+    /// selection-like tasks with relatively small geometries rather than for heavy geometry processing.
+    fn intersects_geometry<P>(&self, geometry: &Geom<P>) -> bool
+    where
+        P: CartesianPoint2d<Num = <Self::Point as CartesianPoint2d>::Num>;
+
+    /// Returns true if all the points of the `geometry` are inside the polygon, and the geometry does not cross the
+    /// boundary of the polygon. Geometries that touch the boundary from inside are not considered contained.
+    ///
+    /// Empty geometries are never contained.
+    fn contains_geometry<P>(&self, geometry: &Geom<P>) -> bool
+    where
+        P: CartesianPoint2d<Num = <Self::Point as CartesianPoint2d>::Num>;
 }
 
 impl<P, C, T> CartesianPolygon for T
@@ -25,38 +77,353 @@ where
     type Point = P;
 
     fn contains_point<Point: CartesianPoint2d<Num = P::Num>>(&self, point: &Point) -> bool {
-        let mut wn = 0i64;
-        let x = point.x();
-        let y = point.y();
+        winding_number(self.iter_segments(), point) != 0
+    }
+
+    fn centroid(&self) -> Option<Point2<P::Num>>
+    where
+        P::Num: Float,
+    {
+        // Calculate relative to the first point to reduce the loss of precision for large coordinates
+        let origin = self.outer_contour().iter_points().next()?;
+        let (ox, oy) = (origin.x(), origin.y());
 
-        for segment in self.iter_segments() {
-            if segment.0.x() < x && segment.1.x() < x {
+        let zero = P::Num::zero();
+        let six = P::Num::from_u8(6)?;
+        let (mut area_sum, mut x_sum, mut y_sum) = (zero, zero, zero);
+
+        for (index, contour) in self.iter_contours().enumerate() {
+            let mut area = zero;
+            let mut x_moment = zero;
+            let mut y_moment = zero;
+
+            let mut points = contour.iter_points_closing();
+            let Some(first) = points.next() else {
                 continue;
+            };
+            let mut prev = (first.x() - ox, first.y() - oy);
+            for p in points {
+                let curr = (p.x() - ox, p.y() - oy);
+                let cross = prev.0 * curr.1 - curr.0 * prev.1;
+                area = area + cross;
+                x_moment = x_moment + (prev.0 + curr.0) * cross;
+                y_moment = y_moment + (prev.1 + curr.1) * cross;
+                prev = curr;
             }
 
-            let is_to_right = segment.0.x() > x && segment.1.x() > x || {
-                let x_max = if segment.0.x() > segment.1.x() {
-                    segment.0.x()
-                } else {
-                    segment.1.x()
-                };
-                let ray_p1 = Point2::new(x, y);
-                let ray_p2 = Point2::new(x_max, y);
-                let ray = Segment(&ray_p1, &ray_p2);
-
-                segment.intersects(&ray)
+            // Holes are subtracted regardless of the winding of the contours
+            let sign = if index == 0 {
+                P::Num::one()
+            } else {
+                -P::Num::one()
             };
+            let orientation = area.signum();
+            area_sum = area_sum + sign * area.abs();
+            x_sum = x_sum + sign * orientation * x_moment;
+            y_sum = y_sum + sign * orientation * y_moment;
+        }
+
+        // `area_sum` is the doubled area, and the moments are `6 * area * centroid`
+        let denominator = area_sum * six / (P::Num::one() + P::Num::one());
+        if denominator == zero {
+            return None;
+        }
+
+        Some(Point2::new(
+            ox + x_sum / denominator,
+            oy + y_sum / denominator,
+        ))
+    }
+
+    fn representative_point(&self, precision: P::Num) -> Option<Point2<P::Num>>
+    where
+        P::Num: Float,
+    {
+        let mut points = self.outer_contour().iter_points();
+        let first = points.next()?;
+        let (mut x_min, mut y_min, mut x_max, mut y_max) =
+            (first.x(), first.y(), first.x(), first.y());
+        for p in points {
+            x_min = x_min.min(p.x());
+            y_min = y_min.min(p.y());
+            x_max = x_max.max(p.x());
+            y_max = y_max.max(p.y());
+        }
+
+        let two = P::Num::one() + P::Num::one();
+        let cell_size = (x_max - x_min).min(y_max - y_min);
+        if cell_size == P::Num::zero() {
+            return Some(Point2::new(x_min, y_min));
+        }
+
+        let half = cell_size / two;
+        let mut cells = BinaryHeap::new();
+        let mut x = x_min;
+        while x < x_max {
+            let mut y = y_min;
+            while y < y_max {
+                cells.push(PolylabelCell::new(x + half, y + half, half, self));
+                y = y + cell_size;
+            }
+            x = x + cell_size;
+        }
+
+        let mut best = PolylabelCell::new(
+            (x_min + x_max) / two,
+            (y_min + y_max) / two,
+            P::Num::zero(),
+            self,
+        );
+        if let Some(centroid) = self.centroid() {
+            let centroid_cell = PolylabelCell::new(centroid.x, centroid.y, P::Num::zero(), self);
+            if centroid_cell.distance > best.distance {
+                best = centroid_cell;
+            }
+        }
+
+        let mut checked = 0;
+        while let Some(cell) = cells.pop() {
+            checked += 1;
+            if cell.distance > best.distance {
+                best = cell;
+            }
+
+            if cell.max_distance - best.distance <= precision || checked > MAX_POLYLABEL_CELLS {
+                continue;
+            }
 
-            if is_to_right {
-                if segment.0.y() < y && segment.1.y() >= y {
-                    wn += 1;
-                } else if segment.0.y() > y && segment.1.y() <= y {
-                    wn -= 1;
-                }
+            let half = cell.half_size / two;
+            for (dx, dy) in [(-half, -half), (half, -half), (-half, half), (half, half)] {
+                cells.push(PolylabelCell::new(cell.x + dx, cell.y + dy, half, self));
             }
         }
 
-        wn != 0
+        Some(Point2::new(best.x, best.y))
+    }
+
+    fn intersects_geometry<Point>(&self, geometry: &Geom<Point>) -> bool
+    where
+        Point: CartesianPoint2d<Num = P::Num>,
+    {
+        if geom_points(geometry).any(|point| covers_point(self, point)) || crosses(self, geometry) {
+            return true;
+        }
+
+        // The polygon can be completely inside of the geometry
+        let Some(point) = self.outer_contour().iter_points().next() else {
+            return false;
+        };
+        match geometry {
+            Geom::Polygon(polygon) => covers_point(polygon, point),
+            Geom::MultiPolygon(multi_polygon) => multi_polygon
+                .polygons()
+                .any(|polygon| covers_point(polygon, point)),
+            _ => false,
+        }
+    }
+
+    fn contains_geometry<Point>(&self, geometry: &Geom<Point>) -> bool
+    where
+        Point: CartesianPoint2d<Num = P::Num>,
+    {
+        let mut points = geom_points(geometry).peekable();
+        if points.peek().is_none()
+            || !points.all(|point| covers_point(self, point))
+            || crosses(self, geometry)
+        {
+            return false;
+        }
+
+        // A hole of the polygon can be completely inside of the geometry
+        let hole_inside = |polygon: &crate::impls::Polygon<Point>| {
+            self.inner_contours().any(|contour| {
+                contour
+                    .iter_points()
+                    .next()
+                    .is_some_and(|point| covers_point(polygon, point))
+            })
+        };
+        match geometry {
+            Geom::Polygon(polygon) => !hole_inside(polygon),
+            Geom::MultiPolygon(multi_polygon) => !multi_polygon.polygons().any(hole_inside),
+            _ => true,
+        }
+    }
+}
+
+/// Iterates over all the vertices of the geometry.
+fn geom_points<P>(geometry: &Geom<P>) -> Box<dyn Iterator<Item = &P> + '_> {
+    match geometry {
+        Geom::Point(point) => Box::new(std::iter::once(point)),
+        Geom::MultiPoint(multi_point) => Box::new(multi_point.iter_points()),
+        Geom::Contour(contour) => Box::new(contour.iter_points()),
+        Geom::MultiContour(multi_contour) => Box::new(
+            multi_contour
+                .contours()
+                .flat_map(|contour| contour.iter_points()),
+        ),
+        Geom::Polygon(polygon) => Box::new(polygon.iter_contours().flat_map(Contour::iter_points)),
+        Geom::MultiPolygon(multi_polygon) => Box::new(
+            multi_polygon
+                .polygons()
+                .flat_map(|polygon| polygon.iter_contours())
+                .flat_map(Contour::iter_points),
+        ),
+    }
+}
+
+/// Iterates over all the segments of the geometry. Point geometries do not have segments.
+fn geom_segments<P>(geometry: &Geom<P>) -> Box<dyn Iterator<Item = Segment<'_, P>> + '_> {
+    match geometry {
+        Geom::Point(_) | Geom::MultiPoint(_) => Box::new(std::iter::empty()),
+        Geom::Contour(contour) => Box::new(contour.iter_segments()),
+        Geom::MultiContour(multi_contour) => Box::new(
+            multi_contour
+                .contours()
+                .flat_map(|contour| contour.iter_segments()),
+        ),
+        Geom::Polygon(polygon) => Box::new(polygon.iter_segments()),
+        Geom::MultiPolygon(multi_polygon) => Box::new(
+            multi_polygon
+                .polygons()
+                .flat_map(|polygon| polygon.iter_segments()),
+        ),
+    }
+}
+
+/// Winding number of the closed line formed by the `segments` around the `point`. Non-zero value means that the point
+/// is inside the line.
+fn winding_number<'a, P, Point>(
+    segments: impl Iterator<Item = Segment<'a, P>>,
+    point: &Point,
+) -> i64
+where
+    P: CartesianPoint2d + 'a,
+    Point: CartesianPoint2d<Num = P::Num>,
+{
+    let mut wn = 0i64;
+    let x = point.x();
+    let y = point.y();
+
+    for segment in segments {
+        if segment.0.x() < x && segment.1.x() < x {
+            continue;
+        }
+
+        let is_to_right = segment.0.x() > x && segment.1.x() > x || {
+            let x_max = if segment.0.x() > segment.1.x() {
+                segment.0.x()
+            } else {
+                segment.1.x()
+            };
+            let ray_p1 = Point2::new(x, y);
+            let ray_p2 = Point2::new(x_max, y);
+            let ray = Segment(&ray_p1, &ray_p2);
+
+            segment.intersects(&ray)
+        };
+
+        if is_to_right {
+            if segment.0.y() < y && segment.1.y() >= y {
+                wn += 1;
+            } else if segment.0.y() > y && segment.1.y() <= y {
+                wn -= 1;
+            }
+        }
+    }
+
+    wn
+}
+
+/// Returns true if the `point` is inside the outer contour of the polygon and not inside any of its holes. Unlike
+/// [`CartesianPolygon::contains_point`], this does not depend on the winding of the holes.
+fn covers_point<P, C, T, Point>(polygon: &T, point: &Point) -> bool
+where
+    P: CartesianPoint2d,
+    C: ClosedContour<Point = P>,
+    T: Polygon<Contour = C>,
+    Point: CartesianPoint2d<Num = P::Num>,
+{
+    winding_number(polygon.outer_contour().iter_segments(), point) != 0
+        && !polygon
+            .inner_contours()
+            .any(|hole| winding_number(hole.iter_segments(), point) != 0)
+}
+
+/// Returns true if any segment of the geometry has a common point with the boundary of the polygon.
+fn crosses<P, C, T, Point>(polygon: &T, geometry: &Geom<Point>) -> bool
+where
+    P: CartesianPoint2d,
+    C: ClosedContour<Point = P>,
+    T: Polygon<Contour = C>,
+    Point: CartesianPoint2d<Num = P::Num>,
+{
+    geom_segments(geometry).any(|segment| {
+        polygon
+            .iter_segments()
+            .any(|polygon_segment| polygon_segment.intersects(&segment))
+    })
+}
+
+/// Square cell used by the polylabel algorithm.
+#[derive(Clone, Copy)]
+struct PolylabelCell<N> {
+    x: N,
+    y: N,
+    half_size: N,
+    /// Signed distance from the center of the cell to the polygon boundary, positive inside the polygon.
+    distance: N,
+    /// Maximum possible distance to the boundary for a point inside the cell.
+    max_distance: N,
+}
+
+impl<N: Float + Bounded + FromPrimitive + Debug + 'static> PolylabelCell<N> {
+    fn new<P, Poly>(x: N, y: N, half_size: N, polygon: &Poly) -> Self
+    where
+        P: CartesianPoint2d<Num = N>,
+        Poly: Polygon + CartesianPolygon<Point = P>,
+        Poly::Contour: Contour<Point = P>,
+    {
+        let center = Point2::new(x, y);
+        let distance_sq = polygon
+            .iter_segments()
+            .map(|segment| segment.distance_to_point_sq(&center))
+            .fold(N::infinity(), N::min);
+        let distance = if polygon.contains_point(&center) {
+            distance_sq.sqrt()
+        } else {
+            -distance_sq.sqrt()
+        };
+
+        Self {
+            x,
+            y,
+            half_size,
+            distance,
+            max_distance: distance + half_size * (N::one() + N::one()).sqrt(),
+        }
+    }
+}
+
+impl<N: Float> PartialEq for PolylabelCell<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<N: Float> Eq for PolylabelCell<N> {}
+
+impl<N: Float> PartialOrd for PolylabelCell<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N: Float> Ord for PolylabelCell<N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.max_distance
+            .partial_cmp(&other.max_distance)
+            .unwrap_or(Ordering::Equal)
     }
 }
 
@@ -86,4 +453,130 @@ mod tests {
         assert!(!polygon.contains_point(&Point2d::new(0.2, -0.3)));
         assert!(!polygon.contains_point(&Point2d::new(1.1, 0.0)));
     }
+
+    fn square_with_hole() -> crate::impls::Polygon<Point2d> {
+        crate::impls::Polygon {
+            outer_contour: crate::impls::ClosedContour {
+                points: vec![
+                    Point2d::new(0.0, 0.0),
+                    Point2d::new(0.0, 4.0),
+                    Point2d::new(4.0, 4.0),
+                    Point2d::new(4.0, 0.0),
+                ],
+            },
+            inner_contours: vec![crate::impls::ClosedContour {
+                points: vec![
+                    Point2d::new(2.0, 0.0),
+                    Point2d::new(4.0, 0.0),
+                    Point2d::new(4.0, 4.0),
+                    Point2d::new(2.0, 4.0),
+                ],
+            }],
+        }
+    }
+
+    #[test]
+    fn centroid() {
+        let polygon = square_with_hole();
+        let centroid = polygon.centroid().unwrap();
+        assert!((centroid.x - 1.0).abs() < 1e-9);
+        assert!((centroid.y - 2.0).abs() < 1e-9);
+
+        let degenerate = crate::impls::Polygon::<Point2d> {
+            outer_contour: crate::impls::ClosedContour {
+                points: vec![Point2d::new(0.0, 0.0), Point2d::new(1.0, 1.0)],
+            },
+            inner_contours: vec![],
+        };
+        assert!(degenerate.centroid().is_none());
+    }
+
+    #[test]
+    fn representative_point() {
+        // U-shaped polygon, centroid of which is outside of the polygon
+        let polygon = crate::impls::Polygon {
+            outer_contour: crate::impls::ClosedContour {
+                points: vec![
+                    Point2d::new(0.0, 0.0),
+                    Point2d::new(0.0, 10.0),
+                    Point2d::new(2.0, 10.0),
+                    Point2d::new(2.0, 2.0),
+                    Point2d::new(8.0, 2.0),
+                    Point2d::new(8.0, 10.0),
+                    Point2d::new(10.0, 10.0),
+                    Point2d::new(10.0, 0.0),
+                ],
+            },
+            inner_contours: vec![],
+        };
+
+        let centroid = polygon.centroid().unwrap();
+        assert!(!polygon.contains_point(&centroid));
+
+        let point = polygon.representative_point(0.01).unwrap();
+        assert!(polygon.contains_point(&point));
+
+        let point = square_with_hole().representative_point(0.01).unwrap();
+        assert!((point.x - 1.0).abs() < 0.1);
+    }
+
+    /// Square with a square hole in the middle.
+    fn frame() -> crate::impls::Polygon<Point2d> {
+        let square = |min: f64, max: f64| crate::impls::ClosedContour {
+            points: vec![
+                Point2d::new(min, min),
+                Point2d::new(min, max),
+                Point2d::new(max, max),
+                Point2d::new(max, min),
+            ],
+        };
+        crate::impls::Polygon::new(square(0.0, 4.0), vec![square(1.0, 3.0)])
+    }
+
+    fn line(points: &[(f64, f64)]) -> Geom<Point2d> {
+        Geom::Contour(crate::impls::Contour::open(
+            points.iter().map(|&(x, y)| Point2d::new(x, y)).collect(),
+        ))
+    }
+
+    fn rect(min: f64, max: f64) -> Geom<Point2d> {
+        Geom::Polygon(
+            vec![
+                Point2d::new(min, min),
+                Point2d::new(min, max),
+                Point2d::new(max, max),
+                Point2d::new(max, min),
+            ]
+            .into(),
+        )
+    }
+
+    #[test]
+    fn intersects_geometry() {
+        let polygon = frame();
+
+        assert!(polygon.intersects_geometry(&Geom::Point(Point2d::new(0.5, 0.5))));
+        assert!(!polygon.intersects_geometry(&Geom::Point(Point2d::new(2.0, 2.0))));
+        assert!(!polygon.intersects_geometry(&Geom::Point(Point2d::new(5.0, 1.0))));
+        assert!(polygon.intersects_geometry(&line(&[(-1.0, 2.0), (5.0, 2.0)])));
+        assert!(!polygon.intersects_geometry(&line(&[(-1.0, 5.0), (5.0, 5.0)])));
+        assert!(!polygon.intersects_geometry(&rect(1.5, 2.5)));
+        assert!(polygon.intersects_geometry(&rect(-1.0, 5.0)));
+    }
+
+    #[test]
+    fn contains_geometry() {
+        let polygon = frame();
+
+        assert!(polygon.contains_geometry(&Geom::Point(Point2d::new(0.5, 0.5))));
+        assert!(polygon.contains_geometry(&line(&[(0.5, 0.5), (0.5, 3.5), (3.5, 3.5)])));
+        // Both ends are inside, but the line goes through the hole
+        assert!(!polygon.contains_geometry(&line(&[(0.5, 2.0), (3.5, 2.0)])));
+        assert!(!polygon.contains_geometry(&line(&[(0.5, 0.5), (5.0, 0.5)])));
+        assert!(!polygon.contains_geometry(&Geom::MultiPoint(Vec::<Point2d>::new().into())));
+
+        // The hole is inside the polygon geometry
+        assert!(!polygon.contains_geometry(&rect(0.5, 3.5)));
+        assert!(!polygon.contains_geometry(&rect(-1.0, 5.0)));
+    }
 }
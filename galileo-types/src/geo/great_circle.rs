@@ -0,0 +1,281 @@
+use nalgebra::Vector3;
+
+use crate::cartesian::{Point2d, Point3d};
+use crate::geo::impls::GeoPoint2d;
+use crate::geo::{Datum, GeoPoint, NewGeoPoint, Projection};
+use crate::impls::{Contour, MultiContour};
+
+/// Default maximum length of a segment of the generated contour in meters.
+const DEFAULT_MAX_SEGMENT_LENGTH: f64 = 100_000.0;
+
+/// Generator of the contour along the great circle arc (the shortest path on the surface of the Earth) between two
+/// points, e.g. for visualizing flight routes.
+///
+/// In most projections a great circle arc is a curve, so it is densified: the arc is split into segments, each not
+/// longer than [`max segment length`](GreatCircleContour::with_max_segment_length). The Earth is approximated by a
+/// sphere with the radius of the WGS84 semimajor axis.
+///
+/// The path between two antipodal points is not defined, so one of the possible paths is chosen in this case.
+///
+/// ```
+/// use galileo_types::geo::GreatCircleContour;
+/// use galileo_types::latlon;
+/// use galileo_types::MultiContour;
+///
+/// let route = GreatCircleContour::new(&latlon!(52.31, 4.76), &latlon!(40.64, -73.78))
+///     .with_max_segment_length(50_000.0);
+/// assert!(route.distance() > 5_800_000.0);
+///
+/// let geometry = route.to_multi_contour();
+/// assert_eq!(geometry.contours().count(), 1);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GreatCircleContour {
+    start: GeoPoint2d,
+    end: GeoPoint2d,
+    max_segment_length: f64,
+}
+
+impl GreatCircleContour {
+    /// Creates a new arc between the two points.
+    pub fn new(start: &impl GeoPoint<Num = f64>, end: &impl GeoPoint<Num = f64>) -> Self {
+        Self {
+            start: GeoPoint2d::from(start),
+            end: GeoPoint2d::from(end),
+            max_segment_length: DEFAULT_MAX_SEGMENT_LENGTH,
+        }
+    }
+
+    /// Sets the maximum length of a segment of the generated contours in meters. Default value is 100 km.
+    ///
+    /// To make the arc look smooth, the segments should be shorter than a few pixels at the resolution the arc is
+    /// displayed with.
+    pub fn with_max_segment_length(mut self, max_segment_length: f64) -> Self {
+        self.max_segment_length = max_segment_length;
+        self
+    }
+
+    /// Start point of the arc.
+    pub fn start(&self) -> &GeoPoint2d {
+        &self.start
+    }
+
+    /// End point of the arc.
+    pub fn end(&self) -> &GeoPoint2d {
+        &self.end
+    }
+
+    /// Length of the arc in meters.
+    pub fn distance(&self) -> f64 {
+        self.angle() * Datum::WGS84.semimajor()
+    }
+
+    /// Returns the point on the arc. `fraction` of `0.0` corresponds to the start of the arc, and `1.0` to its end.
+    pub fn point_at(&self, fraction: f64) -> GeoPoint2d {
+        let a = to_vector(&self.start);
+        let b = to_vector(&self.end);
+        let angle = self.angle();
+
+        // Unit vector perpendicular to `a` in the plane of the great circle
+        let perpendicular = (b - a * a.dot(&b))
+            .try_normalize(f64::EPSILON)
+            .or_else(|| a.cross(&Vector3::z()).try_normalize(f64::EPSILON))
+            .or_else(|| a.cross(&Vector3::x()).try_normalize(f64::EPSILON))
+            .unwrap_or_else(Vector3::zeros);
+
+        to_point(a * (angle * fraction).cos() + perpendicular * (angle * fraction).sin())
+    }
+
+    /// Returns the points of the densified arc, including the start and end points.
+    ///
+    /// When the arc crosses the antimeridian, longitudes of the points jump between `180` and `-180`. Use
+    /// [`GreatCircleContour::to_multi_contour`] to get the arc split at the antimeridian.
+    pub fn points(&self) -> Vec<GeoPoint2d> {
+        self.fractions().map(|t| self.point_at(t)).collect()
+    }
+
+    /// Returns the densified arc, split into separate contours at the antimeridian, so that it is drawn correctly in
+    /// projections with the antimeridian at the edge of the map (e.g. Web Mercator).
+    pub fn to_multi_contour(&self) -> MultiContour<GeoPoint2d> {
+        self.parts()
+            .into_iter()
+            .map(|part| Contour::open(part.into_iter().map(|(point, _)| point).collect()))
+            .collect::<Vec<_>>()
+            .into()
+    }
+
+    /// Projects the densified arc with the given projection and lifts it above the surface, so that it is displayed
+    /// as a bulge in 3D views. The elevation of the arc is zero at its ends and `altitude` at its middle, in the
+    /// units of the projection.
+    ///
+    /// Like with [`GreatCircleContour::to_multi_contour`], the arc is split at the antimeridian. Returns `None` if
+    /// any of the points cannot be projected.
+    pub fn project_3d(
+        &self,
+        projection: &(impl Projection<InPoint = GeoPoint2d, OutPoint = Point2d> + ?Sized),
+        altitude: f64,
+    ) -> Option<MultiContour<Point3d>> {
+        let mut contours = vec![];
+        for part in self.parts() {
+            let points = part
+                .into_iter()
+                .map(|(point, t)| {
+                    let projected = projection.project(&point)?;
+                    let z = altitude * (std::f64::consts::PI * t).sin();
+                    Some(Point3d::new(projected.x, projected.y, z))
+                })
+                .collect::<Option<Vec<_>>>()?;
+            contours.push(Contour::open(points));
+        }
+
+        Some(contours.into())
+    }
+
+    fn angle(&self) -> f64 {
+        let a = to_vector(&self.start);
+        let b = to_vector(&self.end);
+        a.cross(&b).norm().atan2(a.dot(&b))
+    }
+
+    fn fractions(&self) -> impl Iterator<Item = f64> {
+        let segments = if self.max_segment_length > 0.0 {
+            (self.distance() / self.max_segment_length).ceil().max(1.0) as usize
+        } else {
+            1
+        };
+
+        (0..=segments).map(move |i| i as f64 / segments as f64)
+    }
+
+    /// Points of the arc with their fractions, split at the antimeridian.
+    fn parts(&self) -> Vec<Vec<(GeoPoint2d, f64)>> {
+        let mut parts = vec![];
+        let mut current: Vec<(GeoPoint2d, f64)> = vec![];
+
+        for t in self.fractions() {
+            let point = self.point_at(t);
+            if let Some(&(prev, prev_t)) = current.last() {
+                let d_lon = point.lon() - prev.lon();
+                if d_lon.abs() > 180.0 {
+                    // Longitude of the antimeridian on the side of the previous point
+                    let edge = 180f64.copysign(prev.lon());
+                    let unwrapped_lon = point.lon() + 360f64.copysign(prev.lon());
+                    let k = (edge - prev.lon()) / (unwrapped_lon - prev.lon());
+                    let lat = prev.lat() + (point.lat() - prev.lat()) * k;
+                    let crossing_t = prev_t + (t - prev_t) * k;
+
+                    current.push((GeoPoint2d::latlon(lat, edge), crossing_t));
+                    parts.push(std::mem::take(&mut current));
+                    current.push((GeoPoint2d::latlon(lat, -edge), crossing_t));
+                }
+            }
+
+            current.push((point, t));
+        }
+
+        parts.push(current);
+        parts
+    }
+}
+
+fn to_vector(point: &GeoPoint2d) -> Vector3<f64> {
+    let (lat, lon) = (point.lat_rad(), point.lon_rad());
+    Vector3::new(lat.cos() * lon.cos(), lat.cos() * lon.sin(), lat.sin())
+}
+
+fn to_point(vector: Vector3<f64>) -> GeoPoint2d {
+    GeoPoint2d::latlon(
+        vector.z.clamp(-1.0, 1.0).asin().to_degrees(),
+        vector.y.atan2(vector.x).to_degrees(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contour::Contour as _;
+    use crate::latlon;
+    use crate::multi_contour::MultiContour as _;
+
+    fn assert_close(a: f64, b: f64, epsilon: f64) {
+        assert!((a - b).abs() < epsilon, "{a} != {b}");
+    }
+
+    #[test]
+    fn points_along_equator() {
+        let arc = GreatCircleContour::new(&latlon!(0.0, 0.0), &latlon!(0.0, 90.0))
+            .with_max_segment_length(1_000_000.0);
+
+        assert_close(arc.distance(), 10_018_754.17, 1.0);
+
+        let points = arc.points();
+        assert_eq!(points.len(), 12);
+        assert_close(points[0].lon(), 0.0, 1e-9);
+        assert_close(points[11].lon(), 90.0, 1e-9);
+        assert!(points.iter().all(|p| p.lat().abs() < 1e-9));
+    }
+
+    #[test]
+    fn arc_goes_north() {
+        // The shortest path between the points at the same northern latitude goes closer to the pole
+        let arc = GreatCircleContour::new(&latlon!(50.0, -60.0), &latlon!(50.0, 60.0));
+        let middle = arc.point_at(0.5);
+
+        assert_close(middle.lon(), 0.0, 1e-9);
+        assert!(middle.lat() > 60.0);
+    }
+
+    #[test]
+    fn split_at_antimeridian() {
+        let arc = GreatCircleContour::new(&latlon!(10.0, 170.0), &latlon!(10.0, -170.0))
+            .with_max_segment_length(100_000.0);
+        let contours: Vec<Vec<GeoPoint2d>> = arc
+            .to_multi_contour()
+            .contours()
+            .map(|contour| contour.iter_points().copied().collect())
+            .collect();
+
+        assert_eq!(contours.len(), 2);
+        let first_end = contours[0].last().unwrap();
+        let second_start = contours[1].first().unwrap();
+        assert_eq!(first_end.lon(), 180.0);
+        assert_eq!(second_start.lon(), -180.0);
+        assert_eq!(first_end.lat(), second_start.lat());
+        assert!(contours[0].iter().all(|p| p.lon() > 0.0));
+        assert!(contours[1].iter().all(|p| p.lon() < 0.0));
+    }
+
+    #[test]
+    fn projected_bulge() {
+        struct Equirectangular;
+        impl Projection for Equirectangular {
+            type InPoint = GeoPoint2d;
+            type OutPoint = Point2d;
+
+            fn project(&self, input: &GeoPoint2d) -> Option<Point2d> {
+                Some(Point2d::new(input.lon(), input.lat()))
+            }
+
+            fn unproject(&self, input: &Point2d) -> Option<GeoPoint2d> {
+                Some(GeoPoint2d::latlon(input.y, input.x))
+            }
+        }
+
+        // 4 segments, so that the middle of the arc is one of the points
+        let arc = GreatCircleContour::new(&latlon!(0.0, 0.0), &latlon!(0.0, 20.0))
+            .with_max_segment_length(600_000.0);
+        let projected = arc.project_3d(&Equirectangular, 10.0).unwrap();
+        let points: Vec<Point3d> = projected
+            .contours()
+            .next()
+            .unwrap()
+            .iter_points()
+            .copied()
+            .collect();
+
+        assert_close(points.first().unwrap().z, 0.0, 1e-9);
+        assert_close(points.last().unwrap().z, 0.0, 1e-9);
+        assert_close(points[points.len() / 2].z, 10.0, 1e-9);
+        assert_close(points.last().unwrap().x, 20.0, 1e-9);
+    }
+}
@@ -3,10 +3,12 @@
 
 mod crs;
 mod datum;
+mod great_circle;
 pub mod impls;
 mod traits;
 
 pub use crs::{Crs, ProjectionType};
 pub use datum::Datum;
+pub use great_circle::GreatCircleContour;
 pub use traits::point::{GeoPoint, NewGeoPoint};
 pub use traits::projection::{ChainProjection, InvertedProjection, Projection};
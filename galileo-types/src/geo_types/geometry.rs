@@ -0,0 +1,73 @@
+use geo_types::{Coord, CoordNum};
+use nalgebra::Scalar;
+use num_traits::{Bounded, FromPrimitive};
+
+use crate::geo::Projection;
+use crate::geometry::{Geom, Geometry};
+use crate::impls::{Contour, MultiPoint};
+
+/// `geo_types::Geometry` can contain any type of geometry, so its coordinate space cannot be set by the type system.
+/// The points of the geometry are projected as `Coord` values, which can be interpreted both as cartesian and
+/// geographic points, depending on the projection used.
+///
+/// Geometry collections are not supported and cannot be projected.
+impl<T: CoordNum + Bounded + Scalar + FromPrimitive> Geometry for geo_types::Geometry<T> {
+    type Point = Coord<T>;
+
+    fn project<Proj>(&self, projection: &Proj) -> Option<Geom<Proj::OutPoint>>
+    where
+        Proj: Projection<InPoint = Coord<T>> + ?Sized,
+    {
+        match self {
+            geo_types::Geometry::Point(point) => Some(Geom::Point(projection.project(&point.0)?)),
+            geo_types::Geometry::Line(line) => Some(Geom::Contour(Contour::open(vec![
+                projection.project(&line.start)?,
+                projection.project(&line.end)?,
+            ]))),
+            geo_types::Geometry::LineString(line_string) => line_string.project(projection),
+            geo_types::Geometry::Polygon(polygon) => polygon.project(projection),
+            geo_types::Geometry::MultiPoint(points) => Some(Geom::MultiPoint(MultiPoint::from(
+                points
+                    .iter()
+                    .map(|point| projection.project(&point.0))
+                    .collect::<Option<Vec<_>>>()?,
+            ))),
+            geo_types::Geometry::MultiLineString(lines) => lines.project(projection),
+            geo_types::Geometry::MultiPolygon(polygons) => polygons.project(projection),
+            geo_types::Geometry::Rect(rect) => rect.to_polygon().project(projection),
+            geo_types::Geometry::Triangle(triangle) => triangle.to_polygon().project(projection),
+            geo_types::Geometry::GeometryCollection(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use geo_types::{line_string, point, polygon};
+
+    use super::*;
+    use crate::cartesian::Point2d;
+    use crate::geo::impls::projection::IdentityProjection;
+    use crate::geometry_type::CartesianSpace2d;
+
+    #[test]
+    fn project_geometry_enum() {
+        let projection = IdentityProjection::<Coord<f64>, Point2d, CartesianSpace2d>::new();
+
+        let point: geo_types::Geometry = point!(x: 1.0, y: 2.0).into();
+        assert_eq!(
+            point.project(&projection),
+            Some(Geom::Point(Point2d::new(1.0, 2.0)))
+        );
+
+        let line: geo_types::Geometry = line_string![(x: 0.0, y: 0.0), (x: 1.0, y: 1.0)].into();
+        assert!(matches!(line.project(&projection), Some(Geom::Contour(_))));
+
+        let polygon: geo_types::Geometry =
+            polygon![(x: 0.0, y: 0.0), (x: 1.0, y: 0.0), (x: 1.0, y: 1.0)].into();
+        assert!(matches!(
+            polygon.project(&projection),
+            Some(Geom::Polygon(_))
+        ));
+    }
+}
@@ -1,4 +1,5 @@
 mod coord;
+mod geometry;
 mod linestring;
 mod multi_linestring;
 mod multi_point;
@@ -0,0 +1,146 @@
+//! This example shows how to implement a custom layer using only the stable layer SDK (`galileo::sdk`), as it would
+//! be done in a separate crate. The layer draws a graticule (lines of constant latitude and longitude) over the
+//! OSM basemap.
+
+use galileo::layer::Basemap;
+use galileo::{Map, MapBuilder, MapView};
+use galileo_types::latlon;
+use graticule::GraticuleLayer;
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    run()
+}
+
+pub(crate) fn run() {
+    galileo_egui::init(create_map(), []).expect("failed to initialize");
+}
+
+fn create_map() -> Map {
+    let basemap = MapBuilder::create_basemap_layer(&Basemap::OpenStreetMap);
+    let graticule = GraticuleLayer::new(10.0);
+
+    Map::new(
+        MapView::new(
+            &latlon!(45.0, 10.0),
+            basemap
+                .tile_schema()
+                .lod_resolution(3)
+                .expect("invalid tile schema"),
+        ),
+        vec![Box::new(basemap), Box::new(graticule)],
+        None,
+    )
+}
+
+/// This module would be the whole contents of a layer crate.
+mod graticule {
+    use std::any::Any;
+
+    use galileo::sdk::galileo_types::cartesian::{Point2d, Point3d};
+    use galileo::sdk::galileo_types::geo::impls::GeoPoint2d;
+    use galileo::sdk::galileo_types::geo::NewGeoPoint;
+    use galileo::sdk::galileo_types::impls::{Contour, Polygon};
+    use galileo::sdk::{
+        Canvas, Color, Layer, LineCap, LinePaint, MapView, Messenger, RenderOptions,
+        RenderPrimitive, SDK_VERSION,
+    };
+
+    const _: () = assert!(SDK_VERSION == 1, "unsupported version of galileo");
+
+    /// Web Mercator cannot project the poles.
+    const MAX_LAT: f64 = 85.0;
+    /// Step between the points of the lines in degrees.
+    const POINT_STEP: f64 = 1.0;
+
+    pub struct GraticuleLayer {
+        step: f64,
+        paint: LinePaint,
+    }
+
+    impl GraticuleLayer {
+        /// Creates a layer with the lines every `step` degrees.
+        pub fn new(step: f64) -> Self {
+            Self {
+                step,
+                paint: LinePaint {
+                    color: Color::rgba(0, 0, 0, 128),
+                    width: 1.0,
+                    offset: 0.0,
+                    line_cap: LineCap::Butt,
+                    width_scale: None,
+                    crisp: true,
+                },
+            }
+        }
+
+        fn lines(&self) -> Vec<Vec<GeoPoint2d>> {
+            let range = |from: f64, to: f64, step: f64| {
+                let count = ((to - from) / step).floor() as usize;
+                (0..=count).map(move |i| from + i as f64 * step)
+            };
+
+            let parallels = range(-MAX_LAT, MAX_LAT, self.step).map(|lat| {
+                range(-180.0, 180.0, POINT_STEP)
+                    .map(|lon| GeoPoint2d::latlon(lat, lon))
+                    .collect()
+            });
+            let meridians = range(-180.0, 180.0, self.step).map(|lon| {
+                range(-MAX_LAT, MAX_LAT, POINT_STEP)
+                    .map(|lat| GeoPoint2d::latlon(lat, lon))
+                    .collect()
+            });
+
+            parallels.chain(meridians).collect()
+        }
+    }
+
+    impl Layer for GraticuleLayer {
+        fn render(&self, view: &MapView, canvas: &mut dyn Canvas) {
+            let Some(projection) = view.crs().get_projection::<GeoPoint2d, Point2d>() else {
+                return;
+            };
+
+            let mut bundle = canvas.create_bundle();
+            for line in self.lines() {
+                let points: Option<Vec<Point3d>> = line
+                    .iter()
+                    .map(|point| {
+                        projection
+                            .project(point)
+                            .map(|point| Point3d::new(point.x, point.y, 0.0))
+                    })
+                    .collect();
+
+                if let Some(points) = points {
+                    bundle.add(
+                        RenderPrimitive::<f64, Point3d, Contour<Point3d>, Polygon<Point3d>>::new_contour(
+                            Contour::open(points),
+                            self.paint,
+                        ),
+                        view.resolution(),
+                    );
+                }
+            }
+
+            let packed = canvas.pack_bundle(&bundle);
+            canvas.draw_bundles(&[&*packed], RenderOptions::default());
+        }
+
+        fn prepare(&self, _view: &MapView) {
+            // The layer does not load any data
+        }
+
+        fn set_messenger(&mut self, _messenger: Box<dyn Messenger>) {
+            // The layer never changes, so it never needs to request redraw
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn Any {
+            self
+        }
+    }
+}
@@ -1,12 +1,16 @@
 //! This exmample shows how to use geometries from `geo` crate as inputs for feature layers.
+//!
+//! The example requires the `geo-types` feature of Galileo:
+//!
+//! ```shell
+//! cargo run --example georust --features geo-types
+//! ```
 
 use galileo::layer::feature_layer::{FeatureLayer, FeatureLayerOptions};
 use galileo::symbol::ImagePointSymbol;
 use galileo::tile_scheme::TileSchema;
 use galileo::{Map, MapBuilder, MapView};
-use galileo_types::geo::Crs;
-use galileo_types::geometry_type::GeoSpace2d;
-use galileo_types::{latlon, Disambig, Disambiguate};
+use galileo_types::latlon;
 use geozero::geojson::GeoJson;
 use geozero::ToGeo;
 use nalgebra::Vector2;
@@ -20,28 +24,21 @@ pub(crate) fn run() {
     galileo_egui::init(create_map(), []).expect("failed to initialize");
 }
 
-fn load_points() -> Vec<Disambig<geo_types::Point, GeoSpace2d>> {
+fn load_points() -> Vec<geo_types::Geometry> {
     let json = include_str!("./data/Museums 2021.geojson");
     let geojson = GeoJson(json);
     match geojson.to_geo().expect("invalid geojson") {
-        geo_types::Geometry::GeometryCollection(points) => points
-            .iter()
-            .map(|p| match p {
-                geo_types::Geometry::Point(p) => p.to_geo2d(),
-                _ => panic!("not points"),
-            })
-            .collect(),
+        geo_types::Geometry::GeometryCollection(points) => points.0,
         _ => panic!("not geometry collection"),
     }
 }
 
 fn create_map() -> Map {
     let symbol_image = include_bytes!("data/pin-yellow.png");
-    let point_layer = FeatureLayer::new(
+    let point_layer = FeatureLayer::from_geo_types(
         load_points(),
         ImagePointSymbol::from_bytes(symbol_image, Vector2::new(0.5, 1.0), 0.5)
             .expect("invalid image file"),
-        Crs::WGS84,
     )
     .with_options(FeatureLayerOptions {
         sort_by_depth: true,
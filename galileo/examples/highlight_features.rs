@@ -127,7 +127,7 @@ fn create_mouse_handler(
         if let UserEvent::PointerMoved(event) = ev {
             let mut layer = feature_layer.write();
 
-            let Some(position) = map.view().screen_to_map(event.screen_pointer_position) else {
+            let Some(position) = event.map_pointer_position else {
                 return EventPropagation::Stop;
             };
 
@@ -57,7 +57,7 @@ fn create_mouse_handler(
             let mut layer = feature_layer.write();
 
             let mut new_selected = usize::MAX;
-            let Some(position) = map.view().screen_to_map(event.screen_pointer_position) else {
+            let Some(position) = event.map_pointer_position else {
                 return EventPropagation::Stop;
             };
 
@@ -1,6 +1,6 @@
 //! This exmpales shows how to set a simple map with a single raster tile layer.
 
-use galileo::tile_scheme::TileSchema;
+use galileo::layer::Basemap;
 use galileo::{Map, MapBuilder, MapView};
 use galileo_types::latlon;
 
@@ -14,15 +14,7 @@ pub(crate) fn run() {
 }
 
 fn create_map() -> Map {
-    let layer = MapBuilder::create_raster_tile_layer(
-        |index| {
-            format!(
-                "https://tile.openstreetmap.org/{}/{}/{}.png",
-                index.z, index.x, index.y
-            )
-        },
-        TileSchema::web(18),
-    );
+    let layer = MapBuilder::create_basemap_layer(&Basemap::OpenStreetMap);
 
     Map::new(
         MapView::new(
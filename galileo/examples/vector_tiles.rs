@@ -39,10 +39,7 @@ pub(crate) fn run() {
     let handler = move |ev: &UserEvent, map: &mut Map| match ev {
         UserEvent::Click(MouseButton::Left, mouse_event) => {
             let view = map.view().clone();
-            if let Some(position) = map
-                .view()
-                .screen_to_map(mouse_event.screen_pointer_position)
-            {
+            if let Some(position) = mouse_event.map_pointer_position {
                 let features = layer_copy.read().get_features_at(&position, &view);
 
                 for (layer, feature) in features {
@@ -1,17 +1,19 @@
-//! This examples shows how to render labels for vector tile points.
+//! This examples shows how to render labels for vector tile points, including labels with a background.
 
 use bytes::Bytes;
 use galileo::layer::vector_tile_layer::style::{
-    VectorTileDefaultSymbol, VectorTileLabelSymbol, VectorTileStyle,
+    StyleRule, VectorTileDefaultSymbol, VectorTileLabelSymbol, VectorTileStyle, VectorTileSymbol,
 };
 use galileo::layer::vector_tile_layer::VectorTileLayer;
+use galileo::render::point_paint::LabelBackground;
 use galileo::render::text::font_service::FontService;
-use galileo::render::text::TextStyle;
+use galileo::render::text::{TextStyle, VerticalAlignment};
 use galileo::tile_scheme::{TileIndex, TileSchema, VerticalDirection};
 use galileo::{Color, Lod, Map, MapBuilder, MapView};
 use galileo_types::cartesian::{Point2d, Rect};
 use galileo_types::geo::Crs;
 use galileo_types::latlon;
+use nalgebra::Vector2;
 
 #[cfg(not(target_arch = "wasm32"))]
 fn main() {
@@ -45,7 +47,29 @@ pub(crate) fn run() {
         VectorTileLayer::new(tile_provider.clone(), default_style(), tile_schema());
 
     let labels_style = VectorTileStyle {
-        rules: vec![],
+        rules: vec![StyleRule {
+            layer_name: Some("housenumber".into()),
+            symbol: VectorTileSymbol::Label(VectorTileLabelSymbol {
+                pattern: "{housenumber}".into(),
+                text_style: TextStyle {
+                    font_name: "Noto Sans".to_string(),
+                    font_size: 11.0,
+                    font_color: Color::WHITE,
+                    horizontal_alignment: Default::default(),
+                    vertical_alignment: VerticalAlignment::Middle,
+                    max_width: None,
+                    line_spacing: 1.0,
+                    line_alignment: None,
+                },
+                priority: Default::default(),
+                background: Some(
+                    LabelBackground::rect(Color::rgba(40, 90, 160, 255), 3.0)
+                        .with_outline(Color::WHITE, 1.0)
+                        .with_padding(Vector2::new(4.0, 2.0)),
+                ),
+            }),
+            ..Default::default()
+        }],
         default_symbol: VectorTileDefaultSymbol {
             label: Some(VectorTileLabelSymbol {
                 pattern: "{name_en}".into(),
@@ -55,11 +79,17 @@ pub(crate) fn run() {
                     font_color: Color::BLACK,
                     horizontal_alignment: Default::default(),
                     vertical_alignment: Default::default(),
+                    max_width: None,
+                    line_spacing: 1.0,
+                    line_alignment: None,
                 },
+                priority: Default::default(),
+                background: None,
             }),
             ..Default::default()
         },
         background: Default::default(),
+        ..Default::default()
     };
 
     let label_layer = VectorTileLayer::new(tile_provider, labels_style, tile_schema());
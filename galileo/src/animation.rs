@@ -0,0 +1,509 @@
+//! Animations of arbitrary values, e.g. opacity or color of a symbol.
+//!
+//! An [`Animation`] describes how a value changes over time. To run it, add it to the map with
+//! [`Map::add_animation`](crate::Map::add_animation) together with a function that applies the current value. The map
+//! advances all its animations in [`Map::animate`](crate::Map::animate) and requests redraws until they are finished.
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use galileo::animation::Animation;
+//! use galileo::Map;
+//!
+//! # fn create_map() -> Map { unimplemented!() }
+//! let mut map = create_map();
+//!
+//! // Blink the layer 3 times
+//! let blink = Animation::new(true, false, Duration::from_millis(300))
+//!     .then(true, Duration::from_millis(300))
+//!     .repeat(3);
+//! map.add_animation(blink, |layers, visible| {
+//!     if visible {
+//!         layers.show(0);
+//!     } else {
+//!         layers.hide(0);
+//!     }
+//! });
+//! ```
+
+use std::time::Duration;
+
+use maybe_sync::{MaybeSend, MaybeSync};
+use web_time::Instant;
+
+use crate::map::LayerCollection;
+use crate::Color;
+
+/// Values that can be animated.
+pub trait Interpolate: Clone {
+    /// Returns the value between `self` and `target`. `k` is in the range `[0.0, 1.0]`, with `0.0` corresponding to
+    /// `self` and `1.0` to `target`.
+    fn interpolate(&self, target: &Self, k: f64) -> Self;
+}
+
+impl Interpolate for f64 {
+    fn interpolate(&self, target: &Self, k: f64) -> Self {
+        self + (target - self) * k
+    }
+}
+
+impl Interpolate for f32 {
+    fn interpolate(&self, target: &Self, k: f64) -> Self {
+        self + (target - self) * k as f32
+    }
+}
+
+impl Interpolate for bool {
+    /// Boolean values switch to the target at the end of the step.
+    fn interpolate(&self, target: &Self, k: f64) -> Self {
+        if k >= 1.0 {
+            *target
+        } else {
+            *self
+        }
+    }
+}
+
+impl Interpolate for Color {
+    fn interpolate(&self, target: &Self, k: f64) -> Self {
+        let channel = |from: u8, to: u8| (from as f64).interpolate(&(to as f64), k).round() as u8;
+        Color::rgba(
+            channel(self.r(), target.r()),
+            channel(self.g(), target.g()),
+            channel(self.b(), target.b()),
+            channel(self.a(), target.a()),
+        )
+    }
+}
+
+/// Easing function of an animation step or a view animation (see
+/// [`Map::animate_to_with_easing`](crate::Map::animate_to_with_easing)).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Easing {
+    /// Constant speed.
+    #[default]
+    Linear,
+    /// Starts slowly and accelerates.
+    EaseIn,
+    /// Starts fast and decelerates.
+    EaseOut,
+    /// Accelerates in the first half of the step and decelerates in the second.
+    EaseInOut,
+    /// Cubic Bézier curve, same as the `cubic-bezier()` function of CSS. This allows using the easing curves of
+    /// application design systems.
+    CubicBezier(CubicBezier),
+    /// Motion of a critically damped spring, which moves fast at the start and smoothly settles at the target
+    /// without oscillations.
+    Spring(Spring),
+}
+
+/// Parameters of the [`Easing::CubicBezier`] easing: a cubic Bézier curve from `(0, 0)` to `(1, 1)` with control
+/// points `(x1, y1)` and `(x2, y2)`.
+///
+/// Parameters are compared by their bit representation, so that [`Easing`] can implement `Eq`.
+#[derive(Debug, Clone, Copy)]
+pub struct CubicBezier {
+    /// `x` coordinate of the first control point. Must be in the range `[0.0, 1.0]`, and is clamped to it.
+    pub x1: f64,
+    /// `y` coordinate of the first control point.
+    pub y1: f64,
+    /// `x` coordinate of the second control point. Must be in the range `[0.0, 1.0]`, and is clamped to it.
+    pub x2: f64,
+    /// `y` coordinate of the second control point.
+    pub y2: f64,
+}
+
+impl CubicBezier {
+    /// Creates a new curve with the control points `(x1, y1)` and `(x2, y2)`.
+    pub fn new(x1: f64, y1: f64, x2: f64, y2: f64) -> Self {
+        Self { x1, y1, x2, y2 }
+    }
+
+    fn to_bits(self) -> [u64; 4] {
+        [self.x1, self.y1, self.x2, self.y2].map(f64::to_bits)
+    }
+}
+
+impl PartialEq for CubicBezier {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_bits() == other.to_bits()
+    }
+}
+
+impl Eq for CubicBezier {}
+
+/// Parameters of the [`Easing::Spring`] easing.
+///
+/// The motion is scaled so that the target is reached exactly at the end of the step. Parameters are compared by
+/// their bit representation, so that [`Easing`] can implement `Eq`.
+#[derive(Debug, Clone, Copy)]
+pub struct Spring {
+    /// Natural frequency of the spring in radians per step duration: the larger it is, the earlier the value comes
+    /// close to the target. Values around `8.0` give a natural-looking motion.
+    pub frequency: f64,
+}
+
+impl Spring {
+    /// Creates a new spring with the given natural frequency.
+    pub fn new(frequency: f64) -> Self {
+        Self { frequency }
+    }
+}
+
+impl PartialEq for Spring {
+    fn eq(&self, other: &Self) -> bool {
+        self.frequency.to_bits() == other.frequency.to_bits()
+    }
+}
+
+impl Eq for Spring {}
+
+impl Easing {
+    /// Converts the portion of the step time passed (in the range `[0.0, 1.0]`) into the interpolation coefficient.
+    pub fn apply(&self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+            Easing::CubicBezier(CubicBezier { x1, y1, x2, y2 }) => {
+                cubic_bezier(*x1, *y1, *x2, *y2, t)
+            }
+            Easing::Spring(Spring { frequency }) => {
+                let frequency = frequency.max(f64::EPSILON);
+                let spring = |t: f64| 1.0 - (1.0 + frequency * t) * (-frequency * t).exp();
+                spring(t) / spring(1.0)
+            }
+        }
+    }
+}
+
+/// Returns `y` coordinate of the cubic Bézier curve point with the `x` coordinate equal to `t`.
+fn cubic_bezier(x1: f64, y1: f64, x2: f64, y2: f64, t: f64) -> f64 {
+    // Coordinate of the curve point at the parameter `s` for the control values `p1` and `p2`
+    let curve = |p1: f64, p2: f64, s: f64| {
+        let r = 1.0 - s;
+        3.0 * r * r * s * p1 + 3.0 * r * s * s * p2 + s * s * s
+    };
+    let x1 = x1.clamp(0.0, 1.0);
+    let x2 = x2.clamp(0.0, 1.0);
+
+    // With x1 and x2 in [0, 1] the x coordinate grows monotonically with the parameter, so bisection always finds it
+    let (mut low, mut high) = (0.0, 1.0);
+    let mut s = t;
+    for _ in 0..64 {
+        let x = curve(x1, x2, s);
+        if (x - t).abs() < 1e-9 {
+            break;
+        }
+
+        if x < t {
+            low = s;
+        } else {
+            high = s;
+        }
+        s = (low + high) / 2.0;
+    }
+
+    curve(y1, y2, s)
+}
+
+/// Describes how a value changes over time.
+///
+/// An animation consists of one or more steps, each of which changes the value from the end value of the previous
+/// step to its target value. The whole sequence of steps can be repeated.
+#[derive(Debug, Clone)]
+pub struct Animation<T> {
+    start: T,
+    steps: Vec<Step<T>>,
+    repeat: Option<u32>,
+}
+
+#[derive(Debug, Clone)]
+struct Step<T> {
+    target: T,
+    duration: Duration,
+    easing: Easing,
+}
+
+impl<T: Interpolate> Animation<T> {
+    /// Creates a new animation changing the value from `from` to `to` with linear easing.
+    pub fn new(from: T, to: T, duration: Duration) -> Self {
+        Self {
+            start: from,
+            steps: vec![Step {
+                target: to,
+                duration,
+                easing: Easing::Linear,
+            }],
+            repeat: Some(1),
+        }
+    }
+
+    /// Sets the easing of the last step of the animation.
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        if let Some(step) = self.steps.last_mut() {
+            step.easing = easing;
+        }
+
+        self
+    }
+
+    /// Adds a step changing the value from the end value of the last step to `to`.
+    pub fn then(mut self, to: T, duration: Duration) -> Self {
+        self.steps.push(Step {
+            target: to,
+            duration,
+            easing: Easing::Linear,
+        });
+        self
+    }
+
+    /// Adds a step that keeps the end value of the last step unchanged for the given duration.
+    pub fn then_wait(self, duration: Duration) -> Self {
+        let value = self.end_value().clone();
+        self.then(value, duration)
+    }
+
+    /// Plays all the steps of the animation `count` times.
+    pub fn repeat(mut self, count: u32) -> Self {
+        self.repeat = Some(count);
+        self
+    }
+
+    /// Repeats the animation until it is removed from the map.
+    pub fn repeat_forever(mut self) -> Self {
+        self.repeat = None;
+        self
+    }
+
+    /// Duration of one cycle of the animation.
+    pub fn cycle_duration(&self) -> Duration {
+        self.steps.iter().map(|step| step.duration).sum()
+    }
+
+    /// Total duration of the animation. `None` if the animation is repeated forever.
+    pub fn duration(&self) -> Option<Duration> {
+        self.repeat.map(|count| self.cycle_duration() * count)
+    }
+
+    /// Returns true if the animation is finished after the `elapsed` time since its start.
+    pub fn is_finished(&self, elapsed: Duration) -> bool {
+        self.duration().is_some_and(|duration| elapsed >= duration)
+    }
+
+    /// Value of the animated property after the `elapsed` time since the start of the animation.
+    pub fn value_at(&self, elapsed: Duration) -> T {
+        let cycle = self.cycle_duration();
+        if self.is_finished(elapsed) || cycle.is_zero() {
+            return self.end_value().clone();
+        }
+
+        let mut in_cycle = Duration::from_nanos((elapsed.as_nanos() % cycle.as_nanos()) as u64);
+        let mut from = &self.start;
+        for step in &self.steps {
+            if in_cycle < step.duration {
+                let t = in_cycle.as_secs_f64() / step.duration.as_secs_f64();
+                return from.interpolate(&step.target, step.easing.apply(t));
+            }
+
+            in_cycle -= step.duration;
+            from = &step.target;
+        }
+
+        self.end_value().clone()
+    }
+
+    fn end_value(&self) -> &T {
+        self.steps
+            .last()
+            .map(|step| &step.target)
+            .unwrap_or(&self.start)
+    }
+}
+
+/// Identifier of an animation added to a map.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct AnimationId(u64);
+
+/// Animation running on a map, type-erased so animations of different values can be stored together.
+pub(crate) trait RunningAnimation: MaybeSend + MaybeSync {
+    /// Applies the value of the animation at the `now` moment. Returns false if the animation is finished.
+    fn advance(&mut self, now: Instant, layers: &mut LayerCollection) -> bool;
+    /// Shifts the start of the animation by the given duration, e.g. to not count the time the map was paused.
+    fn delay(&mut self, duration: Duration);
+}
+
+pub(crate) struct AnimationTask<T, F> {
+    animation: Animation<T>,
+    apply: F,
+    start_time: Instant,
+}
+
+impl<T, F> AnimationTask<T, F> {
+    pub(crate) fn new(animation: Animation<T>, apply: F, start_time: Instant) -> Self {
+        Self {
+            animation,
+            apply,
+            start_time,
+        }
+    }
+}
+
+impl<T, F> RunningAnimation for AnimationTask<T, F>
+where
+    T: Interpolate + MaybeSend + MaybeSync,
+    F: FnMut(&mut LayerCollection, T) + MaybeSend + MaybeSync,
+{
+    fn advance(&mut self, now: Instant, layers: &mut LayerCollection) -> bool {
+        let elapsed = now.saturating_duration_since(self.start_time);
+        (self.apply)(layers, self.animation.value_at(elapsed));
+        !self.animation.is_finished(elapsed)
+    }
+
+    fn delay(&mut self, duration: Duration) {
+        self.start_time += duration;
+    }
+}
+
+pub(crate) struct AnimationSet {
+    next_id: u64,
+    animations: Vec<(AnimationId, Box<dyn RunningAnimation>)>,
+}
+
+impl AnimationSet {
+    pub(crate) fn new() -> Self {
+        Self {
+            next_id: 0,
+            animations: vec![],
+        }
+    }
+
+    pub(crate) fn add(&mut self, animation: Box<dyn RunningAnimation>) -> AnimationId {
+        let id = AnimationId(self.next_id);
+        self.next_id += 1;
+        self.animations.push((id, animation));
+        id
+    }
+
+    pub(crate) fn remove(&mut self, id: AnimationId) -> bool {
+        let len = self.animations.len();
+        self.animations
+            .retain(|(animation_id, _)| *animation_id != id);
+        self.animations.len() != len
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.animations.is_empty()
+    }
+
+    /// Shifts the start of all the animations by the given duration.
+    pub(crate) fn delay(&mut self, duration: Duration) {
+        for (_, animation) in &mut self.animations {
+            animation.delay(duration);
+        }
+    }
+
+    /// Advances all the animations, removing the finished ones.
+    pub(crate) fn advance(&mut self, now: Instant, layers: &mut LayerCollection) {
+        self.animations
+            .retain_mut(|(_, animation)| animation.advance(now, layers));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn easing() {
+        for easing in [
+            Easing::Linear,
+            Easing::EaseIn,
+            Easing::EaseOut,
+            Easing::EaseInOut,
+        ] {
+            assert_eq!(easing.apply(0.0), 0.0);
+            assert_eq!(easing.apply(1.0), 1.0);
+        }
+
+        assert!(Easing::EaseIn.apply(0.5) < 0.5);
+        assert!(Easing::EaseOut.apply(0.5) > 0.5);
+        assert_eq!(Easing::EaseInOut.apply(0.5), 0.5);
+    }
+
+    #[test]
+    fn cubic_bezier_easing() {
+        let linear = Easing::CubicBezier(CubicBezier::new(0.25, 0.25, 0.75, 0.75));
+        let ease = Easing::CubicBezier(CubicBezier::new(0.25, 0.1, 0.25, 1.0));
+        for easing in [linear, ease] {
+            assert!(easing.apply(0.0).abs() < 1e-6);
+            assert!((easing.apply(1.0) - 1.0).abs() < 1e-6);
+        }
+
+        assert!((linear.apply(0.3) - 0.3).abs() < 1e-6);
+        // CSS `ease` curve value at the middle
+        assert!((ease.apply(0.5) - 0.8024).abs() < 1e-3);
+
+        assert_eq!(
+            ease,
+            Easing::CubicBezier(CubicBezier::new(0.25, 0.1, 0.25, 1.0))
+        );
+        assert_ne!(ease, linear);
+    }
+
+    #[test]
+    fn spring_easing() {
+        let spring = Easing::Spring(Spring::new(8.0));
+        assert_eq!(spring.apply(0.0), 0.0);
+        assert_eq!(spring.apply(1.0), 1.0);
+        assert!(spring.apply(0.5) > 0.9);
+
+        let mut previous = 0.0;
+        for i in 1..=100 {
+            let value = spring.apply(i as f64 / 100.0);
+            assert!(value >= previous);
+            previous = value;
+        }
+    }
+
+    #[test]
+    fn chained_steps() {
+        let animation = Animation::new(0.0, 1.0, Duration::from_millis(100))
+            .then_wait(Duration::from_millis(100))
+            .then(0.0, Duration::from_millis(200));
+
+        assert_eq!(animation.duration(), Some(Duration::from_millis(400)));
+        assert_eq!(animation.value_at(Duration::ZERO), 0.0);
+        assert_eq!(animation.value_at(Duration::from_millis(50)), 0.5);
+        assert_eq!(animation.value_at(Duration::from_millis(150)), 1.0);
+        assert_eq!(animation.value_at(Duration::from_millis(300)), 0.5);
+        assert_eq!(animation.value_at(Duration::from_millis(1000)), 0.0);
+        assert!(animation.is_finished(Duration::from_millis(400)));
+    }
+
+    #[test]
+    fn repeated_animation() {
+        let animation = Animation::new(0.0, 1.0, Duration::from_millis(100)).repeat(2);
+        assert_eq!(animation.value_at(Duration::from_millis(150)), 0.5);
+        assert!(!animation.is_finished(Duration::from_millis(150)));
+        assert!(animation.is_finished(Duration::from_millis(200)));
+
+        let forever = animation.repeat_forever();
+        assert_eq!(forever.duration(), None);
+        assert!(!forever.is_finished(Duration::from_secs(1000)));
+    }
+
+    #[test]
+    fn color_interpolation() {
+        let color = Color::BLACK.interpolate(&Color::rgba(200, 100, 0, 255), 0.5);
+        assert_eq!(color, Color::rgba(100, 50, 0, 255));
+    }
+}
@@ -1,7 +1,32 @@
+use std::collections::HashMap;
 use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
 
+#[cfg(not(target_arch = "wasm32"))]
+use lazy_static::lazy_static;
 #[cfg(not(target_arch = "wasm32"))]
 use maybe_sync::MaybeSend;
+use parking_lot::Mutex;
+
+#[cfg(not(target_arch = "wasm32"))]
+lazy_static! {
+    /// Runtime used to run the tasks of the library when they are spawned outside of a tokio runtime, e.g. from unit
+    /// tests or command line tools that do not set up a runtime themselves. Created on the first use.
+    static ref FALLBACK_RUNTIME: tokio::runtime::Runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .thread_name("galileo-runtime")
+        .build()
+        .expect("failed to create async runtime");
+}
+
+/// Handle of the current tokio runtime, or of the fallback runtime if the current thread is not in a runtime context.
+#[cfg(not(target_arch = "wasm32"))]
+fn runtime_handle() -> tokio::runtime::Handle {
+    tokio::runtime::Handle::try_current().unwrap_or_else(|_| FALLBACK_RUNTIME.handle().clone())
+}
 
 #[cfg(not(target_arch = "wasm32"))]
 pub fn spawn<T>(future: T)
@@ -9,7 +34,50 @@ where
     T: Future + MaybeSend + 'static,
     T::Output: MaybeSend + 'static,
 {
-    tokio::spawn(future);
+    runtime_handle().spawn(future);
+}
+
+/// Runs the blocking function on a thread of the current (or fallback) runtime dedicated to blocking operations.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn spawn_blocking<F, R>(f: F) -> tokio::task::JoinHandle<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    runtime_handle().spawn_blocking(f)
+}
+
+/// Runs the future to completion on the current thread, blocking until it is done.
+///
+/// This allows using the asynchronous parts of the library (e.g. loading of layers, or
+/// [`TileRenderer`](crate::render::TileRenderer)) from synchronous code, like unit tests or simple command line tools,
+/// without setting up an async runtime. The tasks spawned by the library while the future is running are executed
+/// by an internal runtime created on the first use.
+///
+/// # Panics
+///
+/// Panics if called from within an asynchronous context (e.g. inside a tokio runtime). Use `.await` there instead.
+///
+/// ```no_run
+/// let value = galileo::block_on(async { 42 });
+/// assert_eq!(value, 42);
+/// ```
+#[cfg(not(target_arch = "wasm32"))]
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    FALLBACK_RUNTIME.block_on(future)
+}
+
+/// Waits for the given duration without blocking the thread.
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn sleep(duration: web_time::Duration) {
+    tokio::time::sleep(duration).await
+}
+
+/// Waits for the given duration without blocking the thread.
+#[cfg(target_arch = "wasm32")]
+pub async fn sleep(duration: web_time::Duration) {
+    crate::platform::web::map_builder::sleep(duration.as_millis().min(i32::MAX as u128) as i32)
+        .await
 }
 
 #[cfg(target_arch = "wasm32")]
@@ -22,3 +90,176 @@ where
         future.await;
     });
 }
+
+/// Spawns the future that is aborted as soon as the `token` is cancelled.
+///
+/// Cancellation is cooperative: the future is dropped the next time the executor polls it, which
+/// happens right after [`CancellationToken::cancel`] is called.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn spawn_cancellable<T>(token: &CancellationToken, future: T)
+where
+    T: Future + MaybeSend + 'static,
+    T::Output: MaybeSend + 'static,
+{
+    spawn(Cancellable::new(token.clone(), future));
+}
+
+/// Spawns the future that is aborted as soon as the `token` is cancelled.
+///
+/// Cancellation is cooperative: the future is dropped the next time the executor polls it, which
+/// happens right after [`CancellationToken::cancel`] is called.
+#[cfg(target_arch = "wasm32")]
+pub fn spawn_cancellable<T>(token: &CancellationToken, future: T)
+where
+    T: Future + 'static,
+    T::Output: 'static,
+{
+    spawn(Cancellable::new(token.clone(), future));
+}
+
+/// Token shared between the owner of asynchronous tasks (e.g. a layer) and the tasks themselves.
+///
+/// When the token is cancelled, all tasks spawned with [`spawn_cancellable`] using this token
+/// stop at their next await point.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    inner: Arc<CancellationState>,
+}
+
+#[derive(Debug, Default)]
+struct CancellationState {
+    cancelled: AtomicBool,
+    next_task_id: AtomicU64,
+    wakers: Mutex<HashMap<u64, Waker>>,
+}
+
+impl CancellationToken {
+    /// Creates a new, not cancelled token.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels all the tasks associated with the token.
+    pub fn cancel(&self) {
+        if self.inner.cancelled.swap(true, Ordering::AcqRel) {
+            return;
+        }
+
+        let wakers: Vec<_> = self.inner.wakers.lock().drain().map(|(_, w)| w).collect();
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+
+    /// Returns true if the token was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::Acquire)
+    }
+
+    fn next_task_id(&self) -> u64 {
+        self.inner.next_task_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    fn register(&self, task_id: u64, waker: &Waker) {
+        let mut wakers = self.inner.wakers.lock();
+        match wakers.get_mut(&task_id) {
+            Some(stored) if stored.will_wake(waker) => {}
+            Some(stored) => *stored = waker.clone(),
+            None => {
+                wakers.insert(task_id, waker.clone());
+            }
+        }
+    }
+
+    fn unregister(&self, task_id: u64) {
+        self.inner.wakers.lock().remove(&task_id);
+    }
+}
+
+/// Future wrapper that resolves to `None` when the token is cancelled before the inner future
+/// completes.
+struct Cancellable<F> {
+    token: CancellationToken,
+    task_id: u64,
+    future: Pin<Box<F>>,
+}
+
+impl<F: Future> Cancellable<F> {
+    fn new(token: CancellationToken, future: F) -> Self {
+        Self {
+            task_id: token.next_task_id(),
+            token,
+            future: Box::pin(future),
+        }
+    }
+}
+
+impl<F: Future> Future for Cancellable<F> {
+    type Output = Option<F::Output>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        if this.token.is_cancelled() {
+            return Poll::Ready(None);
+        }
+
+        this.token.register(this.task_id, cx.waker());
+
+        // The token might have been cancelled between the check and the registration of the
+        // waker, in which case the waker would never be called.
+        if this.token.is_cancelled() {
+            return Poll::Ready(None);
+        }
+
+        this.future.as_mut().poll(cx).map(Some)
+    }
+}
+
+impl<F> Drop for Cancellable<F> {
+    fn drop(&mut self) {
+        self.token.unregister(self.task_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn cancelled_task_is_not_completed() {
+        let token = CancellationToken::new();
+        let completed = Arc::new(AtomicBool::new(false));
+
+        let completed_clone = completed.clone();
+        let handle = tokio::spawn(Cancellable::new(token.clone(), async move {
+            std::future::pending::<()>().await;
+            completed_clone.store(true, Ordering::Relaxed);
+        }));
+
+        tokio::task::yield_now().await;
+        token.cancel();
+
+        assert_eq!(handle.await.expect("task panicked"), None);
+        assert!(!completed.load(Ordering::Relaxed));
+        assert!(token.inner.wakers.lock().is_empty());
+    }
+
+    #[test]
+    fn spawn_without_runtime() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        spawn(async move {
+            let value = spawn_blocking(|| 42).await.expect("task panicked");
+            tx.send(value).expect("receiver is dropped");
+        });
+
+        assert_eq!(rx.recv_timeout(std::time::Duration::from_secs(5)), Ok(42));
+        assert_eq!(block_on(async { 7 }), 7);
+    }
+
+    #[tokio::test]
+    async fn not_cancelled_task_completes() {
+        let token = CancellationToken::new();
+        let result = Cancellable::new(token.clone(), async { 42 }).await;
+        assert_eq!(result, Some(42));
+    }
+}
@@ -53,6 +53,13 @@ impl Color {
         ]
     }
 
+    /// Converts the color into f32 array with the color channels multiplied by the alpha channel (premultiplied
+    /// alpha).
+    pub fn to_premultiplied_f32_array(&self) -> [f32; 4] {
+        let [r, g, b, a] = self.to_f32_array();
+        [r * a, g * a, b * a, a]
+    }
+
     /// Converts the color into u8 array (RGBA).
     pub fn to_u8_array(&self) -> [u8; 4] {
         [self.r, self.g, self.b, self.a]
@@ -184,4 +191,20 @@ mod tests {
 
         assert_eq!(Color::from_hex(hex), color);
     }
+
+    #[test]
+    fn premultiplied_alpha() {
+        assert_eq!(
+            Color::rgba(255, 0, 255, 0).to_premultiplied_f32_array(),
+            [0.0, 0.0, 0.0, 0.0]
+        );
+        assert_eq!(
+            Color::rgba(255, 0, 0, 51).to_premultiplied_f32_array(),
+            [0.2, 0.0, 0.0, 0.2]
+        );
+        assert_eq!(
+            Color::WHITE.to_premultiplied_f32_array(),
+            Color::WHITE.to_f32_array()
+        );
+    }
 }
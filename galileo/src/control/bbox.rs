@@ -0,0 +1,458 @@
+use std::sync::Arc;
+
+use galileo_types::cartesian::{Point2d, Point3d, Rect};
+use galileo_types::geo::impls::GeoPoint2d;
+use galileo_types::geo::{Crs, GeoPoint};
+use parking_lot::Mutex;
+
+use crate::control::{EventPropagation, MouseButton, UserEvent, UserEventHandler};
+use crate::layer::{AnnotationLayer, CoordinateSpace};
+use crate::map::Map;
+use crate::view::MapView;
+use crate::Color;
+
+/// Function called with the new bounding box when the user finishes changing it.
+type BboxChangedFn = Arc<dyn Fn(Rect) + Send + Sync>;
+
+/// Event handler for selecting a rectangular area of the map, e.g. the area to export or a spatial filter.
+///
+/// While the controller is active:
+/// * dragging with the left mouse button outside of the bounding box creates a new one,
+/// * dragging inside of the bounding box moves it,
+/// * dragging one of the handles at the corners and in the middle of the sides resizes it.
+///
+/// The bounding box is drawn into the [`AnnotationLayer`] at the given index in the map's layer collection. The
+/// controller clears the layer every time the bounding box is changed, so the layer must not be used for other
+/// annotations.
+///
+/// The controller must be added to the event processor before the [`MapController`](super::MapController), so that
+/// it receives the drag events first. When the controller is inactive, all the events are propagated to the next
+/// handlers, but the bounding box stays on the map.
+///
+/// Clones of the controller share the same state, so a clone can be kept by the application to get the selected
+/// extent with [`BoundingBoxController::bbox`] or [`BoundingBoxController::geo_bbox`].
+#[derive(Clone)]
+pub struct BoundingBoxController {
+    layer_index: usize,
+    state: Arc<Mutex<BboxState>>,
+    fill_color: Color,
+    outline_color: Color,
+    outline_width: f64,
+    handle_size: f64,
+    on_change: Option<BboxChangedFn>,
+}
+
+#[derive(Debug, Default)]
+struct BboxState {
+    is_active: bool,
+    bbox: Option<Rect>,
+    drag: Option<DragMode>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum DragMode {
+    /// A new bounding box is created from the given corner.
+    Create { anchor: Point2d },
+    /// The bounding box is moved with the pointer.
+    Move { grab: Point2d, original: Rect },
+    /// The bounding box is resized by the handle.
+    Resize(Handle),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Side {
+    Min,
+    Max,
+}
+
+/// Resize handle of the bounding box. A side that is `None` is not changed by the handle, so corner handles have both
+/// sides set, and handles in the middle of the edges have only one.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Handle {
+    x: Option<Side>,
+    y: Option<Side>,
+}
+
+impl Handle {
+    const ALL: [Handle; 8] = [
+        Handle::new(Some(Side::Min), Some(Side::Min)),
+        Handle::new(Some(Side::Max), Some(Side::Min)),
+        Handle::new(Some(Side::Max), Some(Side::Max)),
+        Handle::new(Some(Side::Min), Some(Side::Max)),
+        Handle::new(None, Some(Side::Min)),
+        Handle::new(Some(Side::Max), None),
+        Handle::new(None, Some(Side::Max)),
+        Handle::new(Some(Side::Min), None),
+    ];
+
+    const fn new(x: Option<Side>, y: Option<Side>) -> Self {
+        Self { x, y }
+    }
+
+    fn position(&self, bbox: &Rect) -> Point2d {
+        let coord = |side: Option<Side>, min: f64, max: f64| match side {
+            Some(Side::Min) => min,
+            Some(Side::Max) => max,
+            None => (min + max) / 2.0,
+        };
+
+        Point2d::new(
+            coord(self.x, bbox.x_min(), bbox.x_max()),
+            coord(self.y, bbox.y_min(), bbox.y_max()),
+        )
+    }
+
+    /// Moves the sides of the handle to the point. If the box is turned inside out, returns the handle at the same
+    /// place of the normalized box, so that the drag continues with the right sides.
+    fn resize(&self, bbox: &Rect, point: Point2d) -> (Rect, Handle) {
+        let resize = |side: Option<Side>, min: f64, max: f64, value: f64| match side {
+            Some(Side::Min) if value > max => (max, value, Some(Side::Max)),
+            Some(Side::Min) => (value, max, side),
+            Some(Side::Max) if value < min => (value, min, Some(Side::Min)),
+            Some(Side::Max) => (min, value, side),
+            None => (min, max, None),
+        };
+
+        let (x_min, x_max, x) = resize(self.x, bbox.x_min(), bbox.x_max(), point.x);
+        let (y_min, y_max, y) = resize(self.y, bbox.y_min(), bbox.y_max(), point.y);
+        (Rect::new(x_min, y_min, x_max, y_max), Handle::new(x, y))
+    }
+}
+
+impl BoundingBoxController {
+    /// Creates a new inactive controller that draws the bounding box into the [`AnnotationLayer`] at the given index
+    /// in the map's layer collection.
+    pub fn new(layer_index: usize) -> Self {
+        Self {
+            layer_index,
+            state: Default::default(),
+            fill_color: Color::rgba(0, 120, 255, 40),
+            outline_color: Color::rgba(0, 120, 255, 255),
+            outline_width: 2.0,
+            handle_size: 10.0,
+            on_change: None,
+        }
+    }
+
+    /// Sets the colors of the bounding box fill and of its outline and handles.
+    pub fn with_colors(mut self, fill: Color, outline: Color) -> Self {
+        self.fill_color = fill;
+        self.outline_color = outline;
+        self
+    }
+
+    /// Sets the diameter of the resize handles in pixels. Default value is 10 pixels.
+    pub fn with_handle_size(mut self, size: f64) -> Self {
+        self.handle_size = size;
+        self
+    }
+
+    /// Sets the function that is called with the new bounding box in map coordinates every time the user finishes
+    /// creating, moving or resizing it.
+    pub fn with_on_change(mut self, on_change: impl Fn(Rect) + Send + Sync + 'static) -> Self {
+        self.on_change = Some(Arc::new(on_change));
+        self
+    }
+
+    /// Index of the annotation layer the bounding box is drawn into.
+    pub fn layer_index(&self) -> usize {
+        self.layer_index
+    }
+
+    /// Returns true if the controller handles the user input.
+    pub fn is_active(&self) -> bool {
+        self.state.lock().is_active
+    }
+
+    /// Switches the controller on or off.
+    pub fn set_active(&self, is_active: bool) {
+        let mut state = self.state.lock();
+        state.is_active = is_active;
+        state.drag = None;
+    }
+
+    /// Returns true while the user is dragging the bounding box or its handles.
+    pub fn is_editing(&self) -> bool {
+        self.state.lock().drag.is_some()
+    }
+
+    /// Current bounding box in the map coordinates.
+    pub fn bbox(&self) -> Option<Rect> {
+        self.state.lock().bbox
+    }
+
+    /// Current bounding box in geographic coordinates for a map with the given CRS. The `x` coordinates of the
+    /// returned rectangle are longitudes, and `y` coordinates are latitudes.
+    ///
+    /// Returns `None` if there is no bounding box, or it cannot be projected into geographic coordinates.
+    pub fn geo_bbox(&self, crs: &Crs) -> Option<Rect> {
+        let bbox = self.bbox()?;
+        let projection = crs.get_projection::<GeoPoint2d, Point2d>()?;
+
+        // Lines of constant map coordinates are not always lines of constant latitude or longitude, so the middle
+        // points of the edges are included too
+        let points = Handle::ALL
+            .iter()
+            .map(|handle| projection.unproject(&handle.position(&bbox)))
+            .collect::<Option<Vec<_>>>()?;
+        let (first, rest) = points.split_first()?;
+
+        Some(rest.iter().fold(
+            Rect::new(first.lon(), first.lat(), first.lon(), first.lat()),
+            |rect, point| {
+                rect.merge(Rect::new(
+                    point.lon(),
+                    point.lat(),
+                    point.lon(),
+                    point.lat(),
+                ))
+            },
+        ))
+    }
+
+    /// Replaces the bounding box and redraws it. Setting `None` removes the bounding box from the map.
+    pub fn set_bbox(&self, map: &mut Map, bbox: Option<Rect>) {
+        {
+            let mut state = self.state.lock();
+            state.bbox = bbox;
+            state.drag = None;
+        }
+
+        self.draw(map, bbox);
+    }
+
+    fn draw(&self, map: &mut Map, bbox: Option<Rect>) {
+        let Some(layer) = map
+            .layers_mut()
+            .get_mut(self.layer_index)
+            .and_then(|layer| layer.as_any_mut().downcast_mut::<AnnotationLayer>())
+        else {
+            log::warn!(
+                "Layer {} is not an annotation layer, cannot draw the bounding box",
+                self.layer_index
+            );
+            return;
+        };
+
+        layer.clear();
+        if let Some(bbox) = bbox {
+            layer.draw_rect(
+                CoordinateSpace::Map,
+                bbox,
+                Some(self.fill_color),
+                Some((self.outline_color, self.outline_width)),
+            );
+
+            for handle in Handle::ALL {
+                layer.draw_circle(
+                    CoordinateSpace::Map,
+                    handle.position(&bbox),
+                    self.handle_size as f32,
+                    self.outline_color,
+                );
+            }
+        }
+
+        map.redraw();
+    }
+
+    /// Chooses what the drag started at the screen point does with the bounding box.
+    fn drag_mode(
+        &self,
+        view: &MapView,
+        bbox: Option<Rect>,
+        screen_point: Point2d,
+    ) -> Option<DragMode> {
+        let map_point = view.screen_to_map(screen_point)?;
+        let Some(bbox) = bbox else {
+            return Some(DragMode::Create { anchor: map_point });
+        };
+
+        let tolerance = (self.handle_size / 2.0).max(MIN_HIT_TOLERANCE);
+        let handle = Handle::ALL
+            .iter()
+            .filter_map(|handle| {
+                let position = handle.position(&bbox);
+                let on_screen = view.map_to_screen(&Point3d::new(position.x, position.y, 0.0))?;
+                Some((*handle, (on_screen - screen_point).norm()))
+            })
+            .filter(|(_, distance)| *distance <= tolerance)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        Some(match handle {
+            Some((handle, _)) => DragMode::Resize(handle),
+            None if bbox.contains(&map_point) => DragMode::Move {
+                grab: map_point,
+                original: bbox,
+            },
+            None => DragMode::Create { anchor: map_point },
+        })
+    }
+}
+
+/// Minimum distance in pixels from a handle at which the handle can be grabbed.
+const MIN_HIT_TOLERANCE: f64 = 8.0;
+
+impl UserEventHandler for BoundingBoxController {
+    fn handle(&self, event: &UserEvent, map: &mut Map) -> EventPropagation {
+        match event {
+            UserEvent::DragStarted(MouseButton::Left, e) => {
+                let mut state = self.state.lock();
+                if !state.is_active {
+                    return EventPropagation::Propagate;
+                }
+
+                state.drag = self.drag_mode(map.view(), state.bbox, e.screen_pointer_position);
+                match state.drag {
+                    Some(_) => EventPropagation::Consume,
+                    None => EventPropagation::Propagate,
+                }
+            }
+            UserEvent::Drag(MouseButton::Left, _, e) => {
+                let bbox = {
+                    let mut state = self.state.lock();
+                    let (Some(drag), Some(point)) = (state.drag, e.map_pointer_position) else {
+                        return EventPropagation::Propagate;
+                    };
+
+                    let bbox = match drag {
+                        DragMode::Create { anchor } => {
+                            Rect::new(anchor.x, anchor.y, point.x, point.y)
+                        }
+                        DragMode::Move { grab, original } => {
+                            let delta = point - grab;
+                            Rect::new(
+                                original.x_min() + delta.x,
+                                original.y_min() + delta.y,
+                                original.x_max() + delta.x,
+                                original.y_max() + delta.y,
+                            )
+                        }
+                        DragMode::Resize(handle) => {
+                            let Some(bbox) = state.bbox else {
+                                return EventPropagation::Stop;
+                            };
+
+                            let (bbox, handle) = handle.resize(&bbox, point);
+                            state.drag = Some(DragMode::Resize(handle));
+                            bbox
+                        }
+                    };
+
+                    state.bbox = Some(bbox);
+                    bbox
+                };
+
+                self.draw(map, Some(bbox));
+                EventPropagation::Stop
+            }
+            UserEvent::DragEnded(MouseButton::Left, _) => {
+                let bbox = {
+                    let mut state = self.state.lock();
+                    if state.drag.take().is_none() {
+                        return EventPropagation::Propagate;
+                    }
+
+                    state.bbox
+                };
+
+                if let (Some(bbox), Some(on_change)) = (bbox, &self.on_change) {
+                    on_change(bbox);
+                }
+
+                EventPropagation::Stop
+            }
+            _ => EventPropagation::Propagate,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+    use galileo_types::cartesian::Size;
+
+    use super::*;
+    use crate::control::{EventProcessor, RawUserEvent};
+    use crate::layer::Layer;
+
+    fn test_map() -> Map {
+        let view =
+            MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0).with_size(Size::new(200.0, 200.0));
+        let layers: Vec<Box<dyn Layer>> = vec![Box::new(AnnotationLayer::new())];
+        Map::new(view, layers, None)
+    }
+
+    fn drag(processor: &mut EventProcessor, map: &mut Map, from: Point2d, to: Point2d) {
+        processor.handle(RawUserEvent::PointerMoved(from), map);
+        processor.handle(RawUserEvent::ButtonPressed(MouseButton::Left), map);
+        processor.handle(RawUserEvent::PointerMoved(to), map);
+        processor.handle(RawUserEvent::ButtonReleased(MouseButton::Left), map);
+    }
+
+    #[test]
+    fn resize_handle_flips() {
+        let bbox = Rect::new(0.0, 0.0, 10.0, 10.0);
+        let handle = Handle::new(Some(Side::Max), None);
+        let (resized, handle) = handle.resize(&bbox, Point2d::new(-5.0, 100.0));
+
+        assert_eq!(resized, Rect::new(-5.0, 0.0, 0.0, 10.0));
+        assert_eq!(handle, Handle::new(Some(Side::Min), None));
+    }
+
+    #[test]
+    fn create_move_and_resize() {
+        let mut map = test_map();
+        let controller = BoundingBoxController::new(0);
+        controller.set_active(true);
+
+        let mut processor = EventProcessor::default();
+        processor.add_handler(controller.clone());
+
+        // Screen y axis points down, map y axis points up
+        drag(
+            &mut processor,
+            &mut map,
+            Point2d::new(100.0, 100.0),
+            Point2d::new(150.0, 50.0),
+        );
+        assert_eq!(controller.bbox(), Some(Rect::new(0.0, 0.0, 50.0, 50.0)));
+        assert!(!map
+            .layers()
+            .get(0)
+            .expect("layer exists")
+            .as_any()
+            .downcast_ref::<AnnotationLayer>()
+            .expect("annotation layer")
+            .is_empty());
+
+        drag(
+            &mut processor,
+            &mut map,
+            Point2d::new(120.0, 80.0),
+            Point2d::new(130.0, 80.0),
+        );
+        assert_eq!(controller.bbox(), Some(Rect::new(10.0, 0.0, 60.0, 50.0)));
+
+        drag(
+            &mut processor,
+            &mut map,
+            Point2d::new(160.0, 50.0),
+            Point2d::new(170.0, 40.0),
+        );
+        assert_eq!(controller.bbox(), Some(Rect::new(10.0, 0.0, 70.0, 60.0)));
+
+        let geo_bbox = controller.geo_bbox(&Crs::EPSG3857).expect("valid bbox");
+        assert!(geo_bbox.x_min() > 0.0 && geo_bbox.x_max() < 0.001);
+        assert_abs_diff_eq!(geo_bbox.y_min(), 0.0, epsilon = 1e-9);
+
+        controller.set_active(false);
+        drag(
+            &mut processor,
+            &mut map,
+            Point2d::new(10.0, 10.0),
+            Point2d::new(20.0, 20.0),
+        );
+        assert_eq!(controller.bbox(), Some(Rect::new(10.0, 0.0, 70.0, 60.0)));
+    }
+}
@@ -1,29 +1,58 @@
+use std::collections::VecDeque;
+
 use galileo_types::cartesian::{CartesianPoint2d, Point2d};
 use web_time::SystemTime;
 
 use crate::control::{
-    EventPropagation, MouseButton, MouseButtonsState, MouseEvent, RawUserEvent, TouchId, UserEvent,
-    UserEventHandler,
+    ContextMenuEvent, EventPropagation, Gesture, MouseButton, MouseButtonsState, MouseEvent,
+    RawUserEvent, TouchId, UserEvent, UserEventHandler,
 };
 use crate::map::Map;
 
 const DRAG_THRESHOLD: f64 = 3.0;
-const CLICK_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(200);
-const DBL_CLICK_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(500);
+const CLICK_MAX_DURATION: std::time::Duration = std::time::Duration::from_millis(200);
+const DOUBLE_CLICK_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+const LONG_PRESS_DURATION: std::time::Duration = std::time::Duration::from_millis(500);
+const LONG_PRESS_TOLERANCE: f64 = 10.0;
+
+/// Identifier of a handler registered in the [`EventProcessor`]. Can be used to remove or disable the handler.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HandlerId(u64);
+
+struct HandlerEntry {
+    id: HandlerId,
+    name: Option<String>,
+    priority: i32,
+    is_enabled: bool,
+    handler: Box<dyn UserEventHandler>,
+}
 
 struct TouchInfo {
     id: TouchId,
     start_position: Point2d,
-    _start_time: SystemTime,
+    start_time: SystemTime,
     prev_position: Point2d,
+    /// The touch cannot be a long press anymore, because it moved too far or the long press was already reported.
+    long_press_cancelled: bool,
 }
 
 /// Stores input state, converts [`RawUserEvent`] into [`UserEvent`] and manages a list of event handlers.
 ///
 /// When an even is called, the `EventProcessor` will go through event handlers one by one until a handler returns
 /// [`EventPropagation::Consume`] or [`EventPropagation::Stop`]. At this point the event is considered to be handled.
+///
+/// Handlers are called in the order of their priorities, from the highest to the lowest. Handlers with the same
+/// priority are called in the order they were added. Map builders register the [`MapController`](super::MapController)
+/// with [`EventProcessor::LOW_PRIORITY`] under the name [`EventProcessor::MAP_CONTROLLER`], so handlers added with the
+/// default priority receive the events before it.
+///
+/// A [`UserEvent::ContextMenu`] is fired on a click with the right mouse button, or when a single touch is held without
+/// moving for [`EventProcessor::set_long_press_duration`]. Since long press is detected by time rather than by an
+/// input event, the application should call [`EventProcessor::update`] after [`EventProcessor::next_update_in`]
+/// elapses. The events of the gestures played with [`EventProcessor::play_gesture`] are fed the same way.
 pub struct EventProcessor {
-    handlers: Vec<Box<dyn UserEventHandler>>,
+    handlers: Vec<HandlerEntry>,
+    next_handler_id: u64,
     pointer_position: Point2d,
     pointer_pressed_position: Point2d,
     touches: Vec<TouchInfo>,
@@ -33,13 +62,25 @@ pub struct EventProcessor {
     last_pressed_time: SystemTime,
     last_click_time: SystemTime,
 
-    drag_target: Option<usize>,
+    drag_target: Option<HandlerId>,
+
+    click_max_duration: std::time::Duration,
+    double_click_interval: std::time::Duration,
+    drag_threshold: f64,
+    dpi_scale_factor: f64,
+    long_press_duration: std::time::Duration,
+    long_press_tolerance: f64,
+
+    /// Events of the played gestures with the time they are due.
+    gesture_events: VecDeque<(SystemTime, RawUserEvent)>,
+    gestures_end: SystemTime,
 }
 
 impl Default for EventProcessor {
     fn default() -> Self {
         Self {
             handlers: vec![],
+            next_handler_id: 0,
             pointer_position: Default::default(),
             pointer_pressed_position: Default::default(),
             touches: Vec::new(),
@@ -47,19 +88,237 @@ impl Default for EventProcessor {
             last_pressed_time: SystemTime::UNIX_EPOCH,
             last_click_time: SystemTime::UNIX_EPOCH,
             drag_target: None,
+            click_max_duration: CLICK_MAX_DURATION,
+            double_click_interval: DOUBLE_CLICK_INTERVAL,
+            drag_threshold: DRAG_THRESHOLD,
+            dpi_scale_factor: 1.0,
+            long_press_duration: LONG_PRESS_DURATION,
+            long_press_tolerance: LONG_PRESS_TOLERANCE,
+            gesture_events: VecDeque::new(),
+            gestures_end: SystemTime::UNIX_EPOCH,
         }
     }
 }
 
 impl EventProcessor {
-    /// Adds a new handler to the end of the handler list.
-    pub fn add_handler(&mut self, handler: impl UserEventHandler + 'static) {
-        self.handlers.push(Box::new(handler));
+    /// Priority of the handlers added without explicitly specified priority.
+    pub const DEFAULT_PRIORITY: i32 = 0;
+    /// Priority of the handlers that should run after all other handlers, like the map controller.
+    pub const LOW_PRIORITY: i32 = -100;
+    /// Name under which the map builders register the [`MapController`](super::MapController).
+    pub const MAP_CONTROLLER: &'static str = "map_controller";
+
+    /// Adds a new handler with the default priority after all the handlers with the same priority.
+    pub fn add_handler(&mut self, handler: impl UserEventHandler + 'static) -> HandlerId {
+        self.insert_handler(None, Box::new(handler), Self::DEFAULT_PRIORITY)
+    }
+
+    /// Adds a new handler with the default priority after all the handlers with the same priority.
+    pub fn add_handler_boxed(&mut self, handler: Box<dyn UserEventHandler>) -> HandlerId {
+        self.insert_handler(None, handler, Self::DEFAULT_PRIORITY)
+    }
+
+    /// Adds a new handler with the given priority. Handlers with higher priority receive events first.
+    pub fn add_handler_with_priority(
+        &mut self,
+        handler: impl UserEventHandler + 'static,
+        priority: i32,
+    ) -> HandlerId {
+        self.insert_handler(None, Box::new(handler), priority)
+    }
+
+    /// Adds a new handler with the given name and priority. The name can later be used to find the handler with
+    /// [`EventProcessor::handler_id`].
+    ///
+    /// If a handler with the same name is already registered, it is replaced by the new one.
+    pub fn add_named_handler(
+        &mut self,
+        name: impl Into<String>,
+        handler: impl UserEventHandler + 'static,
+        priority: i32,
+    ) -> HandlerId {
+        let name = name.into();
+        if let Some(id) = self.handler_id(&name) {
+            self.remove_handler(id);
+        }
+
+        self.insert_handler(Some(name), Box::new(handler), priority)
+    }
+
+    fn insert_handler(
+        &mut self,
+        name: Option<String>,
+        handler: Box<dyn UserEventHandler>,
+        priority: i32,
+    ) -> HandlerId {
+        let id = HandlerId(self.next_handler_id);
+        self.next_handler_id += 1;
+
+        let position = self
+            .handlers
+            .iter()
+            .position(|entry| entry.priority < priority)
+            .unwrap_or(self.handlers.len());
+        self.handlers.insert(
+            position,
+            HandlerEntry {
+                id,
+                name,
+                priority,
+                is_enabled: true,
+                handler,
+            },
+        );
+
+        id
+    }
+
+    /// Returns the id of the handler registered with the given name.
+    pub fn handler_id(&self, name: &str) -> Option<HandlerId> {
+        self.handlers
+            .iter()
+            .find(|entry| entry.name.as_deref() == Some(name))
+            .map(|entry| entry.id)
+    }
+
+    /// Removes the handler from the processor. Returns the removed handler, or `None` if there is no handler with
+    /// the given id.
+    pub fn remove_handler(&mut self, id: HandlerId) -> Option<Box<dyn UserEventHandler>> {
+        let index = self.handlers.iter().position(|entry| entry.id == id)?;
+        if self.drag_target == Some(id) {
+            self.drag_target = None;
+        }
+
+        Some(self.handlers.remove(index).handler)
+    }
+
+    /// Enables or disables the handler. Disabled handlers are skipped when processing events, e.g. the map controller
+    /// can be disabled while the user is drawing on the map.
+    ///
+    /// Returns false if there is no handler with the given id.
+    pub fn set_handler_enabled(&mut self, id: HandlerId, is_enabled: bool) -> bool {
+        let Some(entry) = self.handlers.iter_mut().find(|entry| entry.id == id) else {
+            return false;
+        };
+
+        entry.is_enabled = is_enabled;
+        true
+    }
+
+    /// Returns true if the handler is enabled, or `None` if there is no handler with the given id.
+    pub fn is_handler_enabled(&self, id: HandlerId) -> Option<bool> {
+        self.handlers
+            .iter()
+            .find(|entry| entry.id == id)
+            .map(|entry| entry.is_enabled)
+    }
+
+    /// Sets the maximum time between pressing and releasing a mouse button for the release to be reported as a
+    /// [`UserEvent::Click`]. Default value is 200 ms.
+    pub fn set_click_max_duration(&mut self, duration: std::time::Duration) {
+        self.click_max_duration = duration;
     }
 
-    /// Adds a new handler to the end of the handler list.
-    pub fn add_handler_boxed(&mut self, handler: Box<dyn UserEventHandler>) {
-        self.handlers.push(handler);
+    /// Sets the maximum time between two clicks for the second one to be also reported as a
+    /// [`UserEvent::DoubleClick`]. Default value is 500 ms.
+    ///
+    /// Setting it to zero disables double click events.
+    pub fn set_double_click_interval(&mut self, interval: std::time::Duration) {
+        self.double_click_interval = interval;
+    }
+
+    /// Sets the distance the pointer (or a touch) must move with a pressed button for the
+    /// [`UserEvent::DragStarted`] event to be fired. Default value is 3 pixels.
+    ///
+    /// The distance is given in logical pixels, and is multiplied by the
+    /// [DPI scale factor](crate::MapView::dpi_scale_factor) of the map when the events are processed.
+    pub fn set_drag_threshold(&mut self, threshold: f64) {
+        self.drag_threshold = threshold;
+    }
+
+    /// Sets the time a single touch must be held for the [`UserEvent::ContextMenu`] event to be fired. Default value
+    /// is 500 ms.
+    pub fn set_long_press_duration(&mut self, duration: std::time::Duration) {
+        self.long_press_duration = duration;
+    }
+
+    /// Sets the distance in pixels a touch can move from its start position and still be considered a long press.
+    /// Default value is 10 pixels.
+    ///
+    /// If the touch starts dragging the map, it is not considered a long press regardless of this value.
+    pub fn set_long_press_tolerance(&mut self, tolerance: f64) {
+        self.long_press_tolerance = tolerance;
+    }
+
+    /// Returns the time after which [`EventProcessor::update`] should be called to fire time-based events (like a long
+    /// press or the next event of a played gesture). Returns `None` if no such events are pending.
+    pub fn next_update_in(&self) -> Option<std::time::Duration> {
+        let now = SystemTime::now();
+        let long_press = self.long_press_candidate().map(|touch| {
+            let elapsed = now.duration_since(touch.start_time).unwrap_or_default();
+            self.long_press_duration.saturating_sub(elapsed)
+        });
+        let gesture = self
+            .gesture_events
+            .front()
+            .map(|(time, _)| time.duration_since(now).unwrap_or_default());
+
+        long_press.into_iter().chain(gesture).min()
+    }
+
+    /// Fires the events that are triggered by time rather than by user input, like a long press of a touch or the
+    /// events of played gestures.
+    pub fn update(&mut self, map: &mut Map) {
+        let now = SystemTime::now();
+        while self
+            .gesture_events
+            .front()
+            .is_some_and(|(time, _)| *time <= now)
+        {
+            if let Some((_, event)) = self.gesture_events.pop_front() {
+                self.handle(event, map);
+            }
+        }
+
+        if let Some(event) = self.check_long_press(now) {
+            self.dispatch(vec![event], map);
+        }
+    }
+
+    /// Plays a scripted gesture. The events of the gesture are given to the handlers by [`EventProcessor::update`]
+    /// when they are due, so the application must call it as described in the [`EventProcessor`] documentation.
+    ///
+    /// If another gesture is still playing, the new one starts after it ends. Real user input given to the processor
+    /// while a gesture is playing may interfere with it.
+    pub fn play_gesture(&mut self, gesture: Gesture) {
+        let start = self.gestures_end.max(SystemTime::now());
+        self.gestures_end = start + gesture.duration();
+        self.gesture_events.extend(
+            gesture
+                .into_events()
+                .into_iter()
+                .map(|(offset, event)| (start + offset, event)),
+        );
+    }
+
+    /// Returns true if there are gesture events that are not played yet.
+    pub fn is_playing_gesture(&self) -> bool {
+        !self.gesture_events.is_empty()
+    }
+
+    /// Stops all played gestures. Pressed buttons and touches of the gestures are released immediately, so that the
+    /// input state is consistent.
+    pub fn stop_gestures(&mut self, map: &mut Map) {
+        self.gestures_end = SystemTime::UNIX_EPOCH;
+        let pending = std::mem::take(&mut self.gesture_events);
+        for (_, event) in pending {
+            if matches!(
+                event,
+                RawUserEvent::ButtonReleased(_) | RawUserEvent::TouchEnd(_)
+            ) {
+                self.handle(event, map);
+            }
+        }
     }
 
     /// Returns true if the processor is currently tracking dgragging by the pointer.
@@ -69,53 +328,58 @@ impl EventProcessor {
 
     /// Handles the event.
     pub fn handle(&mut self, event: RawUserEvent, map: &mut Map) {
+        self.dpi_scale_factor = map.view().dpi_scale_factor();
         if let Some(user_events) = self.process(event) {
-            for user_event in user_events {
-                let mut drag_start_target = None;
-
-                if let UserEvent::Click(
-                    _,
-                    MouseEvent {
-                        screen_pointer_position,
-                        ..
-                    },
-                ) = user_event
+            self.dispatch(user_events, map);
+        }
+    }
+
+    fn dispatch(&mut self, user_events: Vec<UserEvent>, map: &mut Map) {
+        for mut user_event in user_events {
+            let mut drag_start_target = None;
+
+            if let UserEvent::ContextMenu(event) = &mut user_event {
+                event.map_position = map.view().screen_to_map(event.screen_position);
+                event.geo_position = map.view().screen_to_map_geo(event.screen_position);
+            }
+
+            if let Some(event) = user_event.mouse_event_mut() {
+                event.map_pointer_position =
+                    map.view().screen_to_map(event.screen_pointer_position);
+                event.geo_pointer_position =
+                    map.view().screen_to_map_geo(event.screen_pointer_position);
+            }
+
+            for entry in &self.handlers {
+                if !entry.is_enabled {
+                    continue;
+                }
+
+                if matches!(user_event, UserEvent::Drag(..) | UserEvent::DragEnded(..))
+                    && self.drag_target != Some(entry.id)
                 {
-                    let map_position = map.view().screen_to_map(screen_pointer_position);
-                    log::info!("click position: {map_position:?}");
+                    continue;
                 }
 
-                for (index, handler) in self.handlers.iter_mut().enumerate() {
-                    if matches!(user_event, UserEvent::Drag(..) | UserEvent::DragEnded(..)) {
-                        if let Some(target) = &self.drag_target {
-                            if index != *target {
-                                continue;
-                            }
-                        } else {
-                            continue;
+                match entry.handler.handle(&user_event, map) {
+                    EventPropagation::Propagate => {}
+                    EventPropagation::Stop => break,
+                    EventPropagation::Consume => {
+                        if let UserEvent::DragStarted(..) = user_event {
+                            drag_start_target = Some(entry.id);
                         }
-                    }
 
-                    match handler.handle(&user_event, map) {
-                        EventPropagation::Propagate => {}
-                        EventPropagation::Stop => break,
-                        EventPropagation::Consume => {
-                            if let UserEvent::DragStarted(..) = user_event {
-                                drag_start_target = Some(index);
-                            }
-
-                            break;
-                        }
+                        break;
                     }
                 }
+            }
 
-                if drag_start_target.is_some() {
-                    self.drag_target = drag_start_target;
-                }
+            if drag_start_target.is_some() {
+                self.drag_target = drag_start_target;
+            }
 
-                if matches!(user_event, UserEvent::DragEnded(..)) {
-                    self.drag_target = None;
-                }
+            if matches!(user_event, UserEvent::DragEnded(..)) {
+                self.drag_target = None;
             }
         }
     }
@@ -137,13 +401,14 @@ impl EventProcessor {
                 self.buttons_state.set_released(button);
                 let mut events = vec![UserEvent::ButtonReleased(button, self.get_mouse_event())];
 
-                if (now.duration_since(self.last_pressed_time)).unwrap_or_default() < CLICK_TIMEOUT
+                if (now.duration_since(self.last_pressed_time)).unwrap_or_default()
+                    < self.click_max_duration
                 {
                     log::info!("click position: {:?}", self.pointer_position);
                     events.push(UserEvent::Click(button, self.get_mouse_event()));
 
                     if (now.duration_since(self.last_click_time)).unwrap_or_default()
-                        < DBL_CLICK_TIMEOUT
+                        < self.double_click_interval
                     {
                         events.push(UserEvent::DoubleClick(button, self.get_mouse_event()));
                     }
@@ -151,6 +416,15 @@ impl EventProcessor {
                     self.last_click_time = now;
                 }
 
+                if button == MouseButton::Right
+                    && self
+                        .pointer_position
+                        .taxicab_distance(&self.pointer_pressed_position)
+                        <= self.drag_threshold_px()
+                {
+                    events.push(Self::context_menu_event(self.pointer_position, false));
+                }
+
                 if self.drag_target.take().is_some() {
                     events.push(UserEvent::DragEnded(button, self.get_mouse_event()));
                 }
@@ -166,7 +440,7 @@ impl EventProcessor {
                     let mut is_dragging = self.drag_target.is_some();
                     if self.drag_target.is_none()
                         && position.taxicab_distance(&self.pointer_pressed_position)
-                            > DRAG_THRESHOLD
+                            > self.drag_threshold_px()
                     {
                         events.push(UserEvent::DragStarted(
                             button,
@@ -190,6 +464,7 @@ impl EventProcessor {
             RawUserEvent::Scroll(delta) => {
                 Some(vec![UserEvent::Scroll(delta, self.get_mouse_event())])
             }
+            RawUserEvent::Zoom(zoom) => Some(vec![UserEvent::Zoom(zoom, self.pointer_position)]),
             RawUserEvent::TouchStart(touch) => {
                 for i in 0..self.touches.len() {
                     if self.touches[i].id == touch.touch_id {
@@ -199,25 +474,38 @@ impl EventProcessor {
                     }
                 }
 
+                // Multi-touch gestures are not long presses
+                let is_multi_touch = !self.touches.is_empty();
+                for touch_info in &mut self.touches {
+                    touch_info.long_press_cancelled = true;
+                }
+
                 self.touches.push(TouchInfo {
                     id: touch.touch_id,
                     start_position: touch.position,
-                    _start_time: now,
+                    start_time: now,
                     prev_position: touch.position,
+                    long_press_cancelled: is_multi_touch,
                 });
 
                 None
             }
             RawUserEvent::TouchMove(touch) => {
-                let touch_info = self.touches.iter().find(|t| t.id == touch.touch_id)?;
+                let long_press_tolerance = self.long_press_tolerance;
+                let touch_info = self.touches.iter_mut().find(|t| t.id == touch.touch_id)?;
                 let position = touch.position;
+                if position.taxicab_distance(&touch_info.start_position) > long_press_tolerance {
+                    touch_info.long_press_cancelled = true;
+                }
 
-                let mut events = vec![];
+                let mut events: Vec<_> = self.check_long_press(now).into_iter().collect();
+                let touch_info = self.touches.iter().find(|t| t.id == touch.touch_id)?;
 
                 if self.touches.len() == 1 {
                     let mut is_dragging = self.drag_target.is_some();
                     if self.drag_target.is_none()
-                        && position.taxicab_distance(&touch_info.start_position) > DRAG_THRESHOLD
+                        && position.taxicab_distance(&touch_info.start_position)
+                            > self.drag_threshold_px()
                     {
                         events.push(UserEvent::DragStarted(
                             MouseButton::Other,
@@ -258,6 +546,8 @@ impl EventProcessor {
                 Some(events)
             }
             RawUserEvent::TouchEnd(touch) => {
+                let mut events: Vec<_> = self.check_long_press(now).into_iter().collect();
+
                 for i in 0..self.touches.len() {
                     if self.touches[i].id == touch.touch_id {
                         self.touches.remove(i);
@@ -265,10 +555,8 @@ impl EventProcessor {
                     }
                 }
 
-                let mut events = vec![];
-
+                // Drag target is cleared by `dispatch` after the handler receives the event
                 if self.drag_target.is_some() && self.touches.is_empty() {
-                    self.drag_target = None;
                     events.push(UserEvent::DragEnded(
                         MouseButton::Other,
                         self.get_mouse_event_pos(touch.position),
@@ -280,14 +568,298 @@ impl EventProcessor {
         }
     }
 
+    /// Drag threshold in physical pixels.
+    fn drag_threshold_px(&self) -> f64 {
+        self.drag_threshold * self.dpi_scale_factor
+    }
+
+    /// Returns the touch that can become a long press.
+    fn long_press_candidate(&self) -> Option<&TouchInfo> {
+        match &self.touches[..] {
+            [touch] if !touch.long_press_cancelled && self.drag_target.is_none() => Some(touch),
+            _ => None,
+        }
+    }
+
+    fn check_long_press(&mut self, now: SystemTime) -> Option<UserEvent> {
+        let touch = self.long_press_candidate()?;
+        if now.duration_since(touch.start_time).unwrap_or_default() < self.long_press_duration {
+            return None;
+        }
+
+        let position = touch.prev_position;
+        for touch in &mut self.touches {
+            touch.long_press_cancelled = true;
+        }
+
+        Some(Self::context_menu_event(position, true))
+    }
+
+    /// Creates a context menu event. Map positions are resolved when the event is dispatched.
+    fn context_menu_event(screen_position: Point2d, is_long_press: bool) -> UserEvent {
+        UserEvent::ContextMenu(ContextMenuEvent {
+            screen_position,
+            map_position: None,
+            geo_position: None,
+            is_long_press,
+        })
+    }
+
     fn get_mouse_event(&self) -> MouseEvent {
         self.get_mouse_event_pos(self.pointer_position)
     }
 
+    /// Creates a mouse event. Map positions are resolved when the event is dispatched.
     fn get_mouse_event_pos(&self, screen_pointer_position: Point2d) -> MouseEvent {
         MouseEvent {
             screen_pointer_position,
+            map_pointer_position: None,
+            geo_pointer_position: None,
             buttons: self.buttons_state,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use galileo_types::cartesian::Size;
+    use galileo_types::geo::impls::GeoPoint2d;
+    use galileo_types::geo::NewGeoPoint;
+    use parking_lot::Mutex;
+
+    use super::*;
+    use crate::control::TouchEvent;
+    use crate::view::MapView;
+
+    fn recorder(
+        calls: &Arc<Mutex<Vec<&'static str>>>,
+        name: &'static str,
+    ) -> impl UserEventHandler + 'static {
+        let calls = calls.clone();
+        move |_: &UserEvent, _: &mut Map| {
+            calls.lock().push(name);
+            EventPropagation::Propagate
+        }
+    }
+
+    #[test]
+    fn handlers_order_and_state() {
+        let view =
+            MapView::new(&GeoPoint2d::latlon(0.0, 0.0), 10.0).with_size(Size::new(100.0, 100.0));
+        let mut map = Map::new(view, vec![], None);
+        let calls = Arc::new(Mutex::new(vec![]));
+
+        let mut processor = EventProcessor::default();
+        processor.add_named_handler(
+            EventProcessor::MAP_CONTROLLER,
+            recorder(&calls, "map"),
+            EventProcessor::LOW_PRIORITY,
+        );
+        let first = processor.add_handler(recorder(&calls, "first"));
+        processor.add_handler(recorder(&calls, "second"));
+        processor.add_handler_with_priority(recorder(&calls, "urgent"), 10);
+
+        processor.handle(RawUserEvent::Scroll(1.0), &mut map);
+        assert_eq!(
+            std::mem::take(&mut *calls.lock()),
+            vec!["urgent", "first", "second", "map"]
+        );
+
+        let map_controller = processor
+            .handler_id(EventProcessor::MAP_CONTROLLER)
+            .expect("no map controller");
+        assert!(processor.set_handler_enabled(map_controller, false));
+        assert_eq!(processor.is_handler_enabled(map_controller), Some(false));
+        assert!(processor.remove_handler(first).is_some());
+        assert!(processor.remove_handler(first).is_none());
+
+        processor.handle(RawUserEvent::Scroll(1.0), &mut map);
+        assert_eq!(std::mem::take(&mut *calls.lock()), vec!["urgent", "second"]);
+    }
+
+    #[test]
+    fn context_menu_events() {
+        let view =
+            MapView::new(&GeoPoint2d::latlon(0.0, 0.0), 10.0).with_size(Size::new(100.0, 100.0));
+        let mut map = Map::new(view, vec![], None);
+        let events = Arc::new(Mutex::new(vec![]));
+
+        let mut processor = EventProcessor::default();
+        let events_clone = events.clone();
+        processor.add_handler(move |event: &UserEvent, _: &mut Map| {
+            if let UserEvent::ContextMenu(event) = event {
+                events_clone.lock().push(event.clone());
+            }
+            EventPropagation::Propagate
+        });
+
+        processor.handle(
+            RawUserEvent::PointerMoved(Point2d::new(60.0, 50.0)),
+            &mut map,
+        );
+        processor.handle(RawUserEvent::ButtonPressed(MouseButton::Right), &mut map);
+        processor.handle(RawUserEvent::ButtonReleased(MouseButton::Right), &mut map);
+
+        let event = events.lock().pop().expect("no context menu event");
+        assert!(!event.is_long_press);
+        assert_eq!(event.screen_position, Point2d::new(60.0, 50.0));
+        let map_position = event.map_position.expect("no map position");
+        assert!((map_position.x() - 100.0).abs() < 1e-6);
+        assert!(event.geo_position.is_some());
+
+        processor.set_long_press_duration(std::time::Duration::ZERO);
+        let touch = TouchEvent {
+            touch_id: 1,
+            position: Point2d::new(10.0, 10.0),
+        };
+        processor.handle(RawUserEvent::TouchStart(touch.clone()), &mut map);
+        assert_eq!(processor.next_update_in(), Some(std::time::Duration::ZERO));
+        processor.update(&mut map);
+        processor.handle(RawUserEvent::TouchEnd(touch), &mut map);
+
+        let events = std::mem::take(&mut *events.lock());
+        assert_eq!(events.len(), 1);
+        assert!(events[0].is_long_press);
+        assert_eq!(processor.next_update_in(), None);
+    }
+
+    #[test]
+    fn mouse_events_have_map_positions() {
+        let view = MapView::new(&GeoPoint2d::latlon(0.0, 0.0), 10.0)
+            .with_size(Size::new(100.0, 100.0))
+            .with_rotation_z(1.0)
+            .with_rotation_x(0.5);
+        let expected = view
+            .screen_to_map(Point2d::new(60.0, 70.0))
+            .expect("point is on the map");
+        let mut map = Map::new(view, vec![], None);
+        let events = Arc::new(Mutex::new(vec![]));
+
+        let mut processor = EventProcessor::default();
+        let events_clone = events.clone();
+        processor.add_handler(move |event: &UserEvent, _: &mut Map| {
+            if let Some(event) = event.mouse_event() {
+                events_clone.lock().push(event.clone());
+            }
+            EventPropagation::Propagate
+        });
+
+        processor.handle(
+            RawUserEvent::PointerMoved(Point2d::new(60.0, 70.0)),
+            &mut map,
+        );
+        processor.handle(RawUserEvent::ButtonPressed(MouseButton::Left), &mut map);
+
+        let events = std::mem::take(&mut *events.lock());
+        assert_eq!(events.len(), 2);
+        for event in events {
+            let position = event.map_pointer_position.expect("no map position");
+            assert!((position.x() - expected.x()).abs() < 1e-6);
+            assert!((position.y() - expected.y()).abs() < 1e-6);
+            assert!(event.geo_pointer_position.is_some());
+        }
+    }
+
+    #[test]
+    fn configurable_click_and_drag() {
+        let view = MapView::new(&GeoPoint2d::latlon(0.0, 0.0), 10.0)
+            .with_size(Size::new(100.0, 100.0))
+            .with_dpi_scale_factor(2.0);
+        let mut map = Map::new(view, vec![], None);
+        let events = Arc::new(Mutex::new(vec![]));
+
+        let mut processor = EventProcessor::default();
+        let events_clone = events.clone();
+        processor.add_handler(move |event: &UserEvent, _: &mut Map| {
+            let name = match event {
+                UserEvent::Click(..) => "click",
+                UserEvent::DoubleClick(..) => "double_click",
+                UserEvent::DragStarted(..) => "drag_started",
+                _ => return EventPropagation::Propagate,
+            };
+            events_clone.lock().push(name);
+            EventPropagation::Propagate
+        });
+
+        let click = |processor: &mut EventProcessor, map: &mut Map| {
+            processor.handle(RawUserEvent::ButtonPressed(MouseButton::Left), map);
+            processor.handle(RawUserEvent::ButtonReleased(MouseButton::Left), map);
+        };
+
+        click(&mut processor, &mut map);
+        click(&mut processor, &mut map);
+        assert_eq!(
+            std::mem::take(&mut *events.lock()),
+            vec!["click", "click", "double_click"]
+        );
+
+        processor.set_double_click_interval(std::time::Duration::ZERO);
+        click(&mut processor, &mut map);
+        click(&mut processor, &mut map);
+        assert_eq!(std::mem::take(&mut *events.lock()), vec!["click", "click"]);
+
+        processor.set_click_max_duration(std::time::Duration::ZERO);
+        click(&mut processor, &mut map);
+        assert!(events.lock().is_empty());
+
+        // Threshold of 4 logical pixels is 8 physical pixels with the scale factor of 2
+        processor.set_drag_threshold(4.0);
+        processor.handle(RawUserEvent::ButtonPressed(MouseButton::Left), &mut map);
+        processor.handle(RawUserEvent::PointerMoved(Point2d::new(6.0, 0.0)), &mut map);
+        assert!(events.lock().is_empty());
+        processor.handle(RawUserEvent::PointerMoved(Point2d::new(9.0, 0.0)), &mut map);
+        assert_eq!(std::mem::take(&mut *events.lock()), vec!["drag_started"]);
+    }
+
+    #[test]
+    fn played_gestures() {
+        let view =
+            MapView::new(&GeoPoint2d::latlon(0.0, 0.0), 10.0).with_size(Size::new(100.0, 100.0));
+        let mut map = Map::new(view, vec![], None);
+        let events = Arc::new(Mutex::new(vec![]));
+
+        let mut processor = EventProcessor::default();
+        let events_clone = events.clone();
+        processor.add_handler(move |event: &UserEvent, _: &mut Map| {
+            match event {
+                UserEvent::Scroll(..) => events_clone.lock().push("scroll"),
+                UserEvent::DragEnded(..) => events_clone.lock().push("drag_ended"),
+                _ => {}
+            }
+            EventPropagation::Consume
+        });
+
+        processor.play_gesture(
+            Gesture::new().event(std::time::Duration::ZERO, RawUserEvent::Scroll(1.0)),
+        );
+        assert!(processor.is_playing_gesture());
+        assert_eq!(processor.next_update_in(), Some(std::time::Duration::ZERO));
+        processor.update(&mut map);
+        assert!(!processor.is_playing_gesture());
+        assert_eq!(std::mem::take(&mut *events.lock()), vec!["scroll"]);
+
+        // Stopping a gesture releases its touch
+        processor.play_gesture(Gesture::pan(
+            Point2d::new(10.0, 10.0),
+            Point2d::new(90.0, 10.0),
+            std::time::Duration::from_secs(60),
+        ));
+        processor.update(&mut map);
+        // Move the synthetic touch of the gesture as if the next frame was played
+        processor.handle(
+            RawUserEvent::TouchMove(TouchEvent {
+                touch_id: TouchId::MAX - 1,
+                position: Point2d::new(50.0, 10.0),
+            }),
+            &mut map,
+        );
+        assert!(processor.is_dragging());
+
+        processor.stop_gestures(&mut map);
+        assert!(!processor.is_playing_gesture());
+        assert!(!processor.is_dragging());
+        assert_eq!(std::mem::take(&mut *events.lock()), vec!["drag_ended"]);
+    }
+}
@@ -0,0 +1,254 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use galileo_types::cartesian::{Point2d, Point3d};
+use galileo_types::geo::impls::GeoPoint2d;
+use galileo_types::geo::GeoPoint;
+use nalgebra::Vector2;
+
+use crate::control::{EventPropagation, MouseButton, UserEvent, UserEventHandler};
+use crate::map::{normalize_angle, Map};
+use crate::view::MapView;
+
+const DEFAULT_ANIMATION_DURATION: Duration = Duration::from_millis(500);
+
+/// Keeps a moving position (e.g. the location of the device in a navigation app) at a fixed point of the screen.
+///
+/// The application feeds the position updates with [`FollowController::update_position`], and the controller
+/// smoothly moves the map so that the position stays in the center of the screen (or at the configured
+/// [offset](FollowController::with_screen_offset) from the center). If the heading of the movement is given, the map
+/// can also be rotated so that the heading points up.
+///
+/// Small changes of the position and heading (within the dead bands) are ignored to avoid constant jittering of the
+/// map caused by the noise of positioning systems.
+///
+/// When the controller is added to the event processor (before the [`MapController`](super::MapController)), it
+/// switches off the follow mode when the user starts dragging the map, so that the user can look around. The mode
+/// can be switched back on with [`FollowController::set_active`]. Clones of the controller share the same active
+/// state.
+#[derive(Debug, Clone)]
+pub struct FollowController {
+    is_active: Arc<AtomicBool>,
+    screen_offset: Vector2<f64>,
+    rotate_to_heading: bool,
+    dead_band: f64,
+    heading_dead_band: f64,
+    animation_duration: Duration,
+}
+
+impl Default for FollowController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FollowController {
+    /// Creates a new active controller that keeps the position in the center of the screen without rotating the map.
+    pub fn new() -> Self {
+        Self {
+            is_active: Arc::new(AtomicBool::new(true)),
+            screen_offset: Vector2::zeros(),
+            rotate_to_heading: false,
+            dead_band: 1.0,
+            heading_dead_band: 1f64.to_radians(),
+            animation_duration: DEFAULT_ANIMATION_DURATION,
+        }
+    }
+
+    /// Sets the offset in pixels of the followed position from the center of the screen. Positive `y` moves the
+    /// position down, e.g. `Vector2::new(0.0, height / 4.0)` shows more of the map ahead of the position when the map
+    /// is rotated to the heading.
+    pub fn with_screen_offset(mut self, offset: Vector2<f64>) -> Self {
+        self.screen_offset = offset;
+        self
+    }
+
+    /// If set to true, the map is rotated so that the heading given to [`FollowController::update_position`] points
+    /// to the top of the screen.
+    pub fn with_rotate_to_heading(mut self, rotate_to_heading: bool) -> Self {
+        self.rotate_to_heading = rotate_to_heading;
+        self
+    }
+
+    /// Sets the distance in pixels the position must move on the screen before the map follows it.
+    pub fn with_dead_band(mut self, dead_band: f64) -> Self {
+        self.dead_band = dead_band;
+        self
+    }
+
+    /// Sets the change of the heading in radians needed for the map to be rotated.
+    pub fn with_heading_dead_band(mut self, heading_dead_band: f64) -> Self {
+        self.heading_dead_band = heading_dead_band;
+        self
+    }
+
+    /// Sets the duration of the transition of the map to a new position. Usually it should be close to the interval
+    /// between the position updates, so that the map moves continuously. With zero duration the map jumps to the
+    /// new position.
+    pub fn with_animation_duration(mut self, duration: Duration) -> Self {
+        self.animation_duration = duration;
+        self
+    }
+
+    /// Returns true if the controller follows the position updates.
+    pub fn is_active(&self) -> bool {
+        self.is_active.load(Ordering::Relaxed)
+    }
+
+    /// Switches the follow mode on or off. When the mode is switched on, the map is moved to the position on the
+    /// next call to [`FollowController::update_position`].
+    pub fn set_active(&self, is_active: bool) {
+        self.is_active.store(is_active, Ordering::Relaxed);
+    }
+
+    /// Moves the map to the new followed position. `heading` is the direction of the movement in radians clockwise
+    /// from the north, it is only used if the controller is set to
+    /// [rotate the map](FollowController::with_rotate_to_heading).
+    ///
+    /// Does nothing if the controller is not active.
+    pub fn update_position(
+        &self,
+        map: &mut Map,
+        position: &impl GeoPoint<Num = f64>,
+        heading: Option<f64>,
+    ) {
+        if !self.is_active() {
+            return;
+        }
+
+        let projected: Option<Point2d> = map
+            .view()
+            .crs()
+            .get_projection()
+            .and_then(|projection| projection.project(&GeoPoint2d::from(position)));
+        let Some(projected) = projected else {
+            log::debug!("Cannot project followed position to the map CRS");
+            return;
+        };
+
+        let Some(target) = self.target_view(map.target_view(), position, projected, heading) else {
+            return;
+        };
+
+        if self.animation_duration.is_zero() {
+            map.set_view(target);
+        } else {
+            map.animate_to(target, self.animation_duration);
+            map.redraw();
+        }
+    }
+
+    /// Returns `None` if the changes of the position and the heading are within the dead bands.
+    fn target_view(
+        &self,
+        view: &MapView,
+        position: &impl GeoPoint<Num = f64>,
+        projected: Point2d,
+        heading: Option<f64>,
+    ) -> Option<MapView> {
+        let rotation_z = match heading {
+            Some(heading) if self.rotate_to_heading => {
+                // Rotate the shortest way from the current rotation
+                view.rotation_z() + normalize_angle(heading - view.rotation_z())
+            }
+            _ => view.rotation_z(),
+        };
+
+        let size = view.size();
+        let anchor = Point2d::new(
+            size.half_width() + self.screen_offset.x,
+            size.half_height() + self.screen_offset.y,
+        );
+
+        let rotation_changed = (rotation_z - view.rotation_z()).abs() >= self.heading_dead_band;
+        let current_screen_position =
+            view.map_to_screen(&Point3d::new(projected.x, projected.y, 0.0));
+        let position_changed = current_screen_position
+            .map(|p| (p - anchor).norm() >= self.dead_band)
+            .unwrap_or(true);
+
+        if !rotation_changed && !position_changed {
+            return None;
+        }
+
+        let target = view
+            .with_rotation_z(if rotation_changed {
+                rotation_z
+            } else {
+                view.rotation_z()
+            })
+            .with_position(position);
+
+        Some(target.pan_by_pixels(self.screen_offset.x, self.screen_offset.y))
+    }
+}
+
+impl UserEventHandler for FollowController {
+    fn handle(&self, event: &UserEvent, _map: &mut Map) -> EventPropagation {
+        if let UserEvent::DragStarted(MouseButton::Left | MouseButton::Other, _) = event {
+            self.set_active(false);
+        }
+
+        EventPropagation::Propagate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+    use galileo_types::cartesian::Size;
+    use galileo_types::geo::NewGeoPoint;
+
+    use super::*;
+
+    fn test_map() -> Map {
+        let view =
+            MapView::new(&GeoPoint2d::latlon(0.0, 0.0), 10.0).with_size(Size::new(200.0, 100.0));
+        Map::new(view, vec![], None)
+    }
+
+    #[test]
+    fn keeps_position_at_offset() {
+        let mut map = test_map();
+        let controller = FollowController::new()
+            .with_screen_offset(Vector2::new(0.0, 25.0))
+            .with_animation_duration(Duration::ZERO);
+
+        let position = GeoPoint2d::latlon(1.0, 1.0);
+        controller.update_position(&mut map, &position, None);
+
+        let projected: Point2d = map
+            .view()
+            .crs()
+            .get_projection()
+            .and_then(|projection| projection.project(&position))
+            .expect("projection failed");
+        let screen = map
+            .view()
+            .map_to_screen(&Point3d::new(projected.x, projected.y, 0.0))
+            .expect("point is not on the screen");
+        assert_abs_diff_eq!(screen.x, 100.0, epsilon = 1e-6);
+        assert_abs_diff_eq!(screen.y, 75.0, epsilon = 1e-6);
+
+        // Movement within the dead band is ignored
+        let prev_position = map.view().position().expect("no position");
+        controller.update_position(&mut map, &GeoPoint2d::latlon(1.0, 1.00001), None);
+        assert_eq!(map.view().position(), Some(prev_position));
+    }
+
+    #[test]
+    fn rotates_to_heading() {
+        let mut map = test_map();
+        let controller = FollowController::new()
+            .with_rotate_to_heading(true)
+            .with_animation_duration(Duration::ZERO);
+
+        controller.update_position(&mut map, &GeoPoint2d::latlon(0.0, 0.0), Some(0.5));
+        assert_abs_diff_eq!(map.view().rotation_z(), 0.5, epsilon = 1e-9);
+
+        controller.set_active(false);
+        controller.update_position(&mut map, &GeoPoint2d::latlon(0.0, 0.0), Some(1.0));
+        assert_abs_diff_eq!(map.view().rotation_z(), 0.5, epsilon = 1e-9);
+    }
+}
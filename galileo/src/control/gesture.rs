@@ -0,0 +1,228 @@
+use std::time::Duration;
+
+use galileo_types::cartesian::Point2d;
+use nalgebra::Vector2;
+
+use crate::animation::Easing;
+use crate::control::{RawUserEvent, TouchEvent, TouchId};
+
+/// Interval between the synthetic events of continuous gestures, roughly one frame at 60 FPS.
+const FRAME_INTERVAL: Duration = Duration::from_millis(16);
+/// Distance in pixels between the fingers at the start of a pinch.
+const PINCH_START_DISTANCE: f64 = 100.0;
+
+// Synthetic touches use the ids that are very unlikely to be given to real touches.
+const FIRST_TOUCH_ID: TouchId = TouchId::MAX - 1;
+const SECOND_TOUCH_ID: TouchId = TouchId::MAX;
+
+/// Scripted user gesture, e.g. for onboarding tours or demo modes.
+///
+/// A gesture is a sequence of [`RawUserEvent`]s with the times they happen at. When it is played with
+/// [`EventProcessor::play_gesture`](super::EventProcessor::play_gesture), the events go through the same handlers as
+/// real user input, so the map moves with the same dynamics as if the user made the gesture.
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use galileo::control::{EventProcessor, Gesture};
+/// use galileo::galileo_types::cartesian::Point2d;
+///
+/// # let mut event_processor = EventProcessor::default();
+/// let center = Point2d::new(400.0, 300.0);
+/// let gesture = Gesture::flick(center, Point2d::new(200.0, 300.0), Duration::from_millis(300))
+///     .wait(Duration::from_millis(500))
+///     .then(Gesture::pinch(center, 0.25, Duration::from_secs(1)));
+///
+/// event_processor.play_gesture(gesture);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Gesture {
+    events: Vec<(Duration, RawUserEvent)>,
+    duration: Duration,
+}
+
+impl Gesture {
+    /// Creates an empty gesture.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Total duration of the gesture.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Adds an event that happens `delay` after the end of the gesture.
+    pub fn event(mut self, delay: Duration, event: RawUserEvent) -> Self {
+        self.duration += delay;
+        self.events.push((self.duration, event));
+        self
+    }
+
+    /// Adds a pause to the end of the gesture.
+    pub fn wait(mut self, duration: Duration) -> Self {
+        self.duration += duration;
+        self
+    }
+
+    /// Adds the events of the `next` gesture after the end of this gesture.
+    pub fn then(mut self, next: Gesture) -> Self {
+        let start = self.duration;
+        self.events.extend(
+            next.events
+                .into_iter()
+                .map(|(time, event)| (start + time, event)),
+        );
+        self.duration += next.duration;
+        self
+    }
+
+    /// Moves a finger over the screen from one point to another, accelerating at the start and decelerating at the
+    /// end.
+    pub fn pan(from: Point2d, to: Point2d, duration: Duration) -> Self {
+        Self::touch_drag(from, to, duration, Easing::EaseInOut)
+    }
+
+    /// Moves a finger fast from one point to another, decelerating to the end.
+    pub fn flick(from: Point2d, to: Point2d, duration: Duration) -> Self {
+        Self::touch_drag(from, to, duration, Easing::EaseOut)
+    }
+
+    /// Pinches with two fingers around the `center` screen point. `zoom` is the multiplier for the map resolution,
+    /// so values less than `1.0` zoom in.
+    ///
+    /// One finger is held at the center while the other one moves, so the point under the center stays in place.
+    pub fn pinch(center: Point2d, zoom: f64, duration: Duration) -> Self {
+        let start_distance = PINCH_START_DISTANCE;
+        let end_distance = PINCH_START_DISTANCE / zoom.max(f64::EPSILON);
+        let finger_at =
+            |distance: f64| center + Vector2::new(distance / 2f64.sqrt(), distance / 2f64.sqrt());
+
+        let mut gesture = Self::new()
+            .event(Duration::ZERO, touch_start(FIRST_TOUCH_ID, center))
+            .event(
+                Duration::ZERO,
+                touch_start(SECOND_TOUCH_ID, finger_at(start_distance)),
+            );
+
+        let frames = frame_count(duration);
+        for frame in 1..=frames {
+            let k = Easing::EaseInOut.apply(frame as f64 / frames as f64);
+            let distance = start_distance + (end_distance - start_distance) * k;
+            gesture = gesture.event(
+                duration / frames,
+                touch_move(SECOND_TOUCH_ID, finger_at(distance)),
+            );
+        }
+
+        gesture
+            .event(
+                Duration::ZERO,
+                touch_end(SECOND_TOUCH_ID, finger_at(end_distance)),
+            )
+            .event(Duration::ZERO, touch_end(FIRST_TOUCH_ID, center))
+    }
+
+    fn touch_drag(from: Point2d, to: Point2d, duration: Duration, easing: Easing) -> Self {
+        let mut gesture = Self::new().event(Duration::ZERO, touch_start(FIRST_TOUCH_ID, from));
+
+        let frames = frame_count(duration);
+        for frame in 1..=frames {
+            let k = easing.apply(frame as f64 / frames as f64);
+            gesture = gesture.event(
+                duration / frames,
+                touch_move(FIRST_TOUCH_ID, from + (to - from) * k),
+            );
+        }
+
+        gesture.event(Duration::ZERO, touch_end(FIRST_TOUCH_ID, to))
+    }
+
+    pub(crate) fn into_events(self) -> Vec<(Duration, RawUserEvent)> {
+        self.events
+    }
+}
+
+fn frame_count(duration: Duration) -> u32 {
+    duration
+        .as_nanos()
+        .div_ceil(FRAME_INTERVAL.as_nanos())
+        .max(1) as u32
+}
+
+fn touch_start(touch_id: TouchId, position: Point2d) -> RawUserEvent {
+    RawUserEvent::TouchStart(TouchEvent { touch_id, position })
+}
+
+fn touch_move(touch_id: TouchId, position: Point2d) -> RawUserEvent {
+    RawUserEvent::TouchMove(TouchEvent { touch_id, position })
+}
+
+fn touch_end(touch_id: TouchId, position: Point2d) -> RawUserEvent {
+    RawUserEvent::TouchEnd(TouchEvent { touch_id, position })
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+    use galileo_types::cartesian::Size;
+    use galileo_types::geo::impls::GeoPoint2d;
+    use galileo_types::geo::NewGeoPoint;
+    use parking_lot::Mutex;
+
+    use super::*;
+    use crate::control::{EventProcessor, EventPropagation, UserEvent};
+    use crate::map::Map;
+    use crate::view::MapView;
+
+    #[test]
+    fn sequence_timing() {
+        let gesture = Gesture::pan(
+            Point2d::new(0.0, 0.0),
+            Point2d::new(100.0, 0.0),
+            Duration::from_millis(160),
+        )
+        .wait(Duration::from_millis(100))
+        .then(Gesture::new().event(Duration::from_millis(50), RawUserEvent::Scroll(1.0)));
+
+        assert_eq!(gesture.duration(), Duration::from_millis(310));
+
+        let events = gesture.into_events();
+        // Start, 10 frames, end and scroll
+        assert_eq!(events.len(), 13);
+        assert!(events.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+        assert!(matches!(
+            events[11],
+            (time, RawUserEvent::TouchEnd(TouchEvent { position, .. }))
+                if time == Duration::from_millis(160) && position == Point2d::new(100.0, 0.0)
+        ));
+        assert_eq!(events[12].0, Duration::from_millis(310));
+    }
+
+    #[test]
+    fn pinch_zooms_around_center() {
+        let view =
+            MapView::new(&GeoPoint2d::latlon(0.0, 0.0), 10.0).with_size(Size::new(400.0, 400.0));
+        let mut map = Map::new(view, vec![], None);
+        let zooms = std::sync::Arc::new(Mutex::new(vec![]));
+
+        let mut processor = EventProcessor::default();
+        let recorded = zooms.clone();
+        processor.add_handler(move |event: &UserEvent, _: &mut Map| {
+            if let UserEvent::Zoom(zoom, center) = event {
+                recorded.lock().push((*zoom, *center));
+            }
+            EventPropagation::Stop
+        });
+
+        let center = Point2d::new(200.0, 200.0);
+        for (_, event) in Gesture::pinch(center, 0.5, Duration::from_millis(200)).into_events() {
+            processor.handle(event, &mut map);
+        }
+
+        let zooms = zooms.lock();
+        assert!(zooms.iter().all(|(_, zoom_center)| *zoom_center == center));
+        let total: f64 = zooms.iter().map(|(zoom, _)| zoom).product();
+        assert_abs_diff_eq!(total, 0.5, epsilon = 1e-9);
+    }
+}
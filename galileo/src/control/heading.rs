@@ -0,0 +1,265 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use nalgebra::Vector2;
+use parking_lot::Mutex;
+
+use crate::control::{EventPropagation, MouseButton, UserEvent, UserEventHandler};
+use crate::map::{normalize_angle, Map};
+
+const DEFAULT_ANIMATION_DURATION: Duration = Duration::from_millis(200);
+
+/// Orientation of the map controlled by a [`HeadingController`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum OrientationMode {
+    /// North is at the top of the screen.
+    #[default]
+    NorthUp,
+    /// The map is rotated so that the current heading points to the top of the screen.
+    HeadingUp,
+    /// The map is rotated by the user, heading updates do not change the rotation.
+    Free,
+}
+
+/// Rotates the map by the heading of the device, e.g. taken from the compass of a phone (see
+/// `platform::web::device_heading::DeviceHeading` for browsers) or from a user-supplied stream of headings.
+///
+/// Compass readings are noisy, so the headings are smoothed with an exponential filter before they are applied, and
+/// changes smaller than the dead band are ignored.
+///
+/// The controller has three [modes](OrientationMode). In the [`OrientationMode::HeadingUp`] mode the map follows
+/// the heading. When the controller is added to the event processor (before the
+/// [`MapController`](super::MapController)), it switches to the [`OrientationMode::Free`] mode when the user starts
+/// rotating the map, so that the gesture is not fought by the heading updates. Applications usually toggle between
+/// the north-up and heading-up modes with a compass button using [`HeadingController::toggle_mode`]. Clones of the
+/// controller share the same state.
+#[derive(Debug, Clone)]
+pub struct HeadingController {
+    state: Arc<Mutex<HeadingState>>,
+    smoothing: f64,
+    dead_band: f64,
+    animation_duration: Duration,
+}
+
+#[derive(Debug, Default)]
+struct HeadingState {
+    mode: OrientationMode,
+    /// Smoothed heading as a unit vector, so that averaging works across the north direction.
+    heading: Option<Vector2<f64>>,
+}
+
+impl Default for HeadingController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HeadingController {
+    /// Creates a new controller in the north-up mode.
+    pub fn new() -> Self {
+        Self {
+            state: Default::default(),
+            smoothing: 0.3,
+            dead_band: 2f64.to_radians(),
+            animation_duration: DEFAULT_ANIMATION_DURATION,
+        }
+    }
+
+    /// Sets the weight of a new heading reading in the smoothed heading, from `0.0` (readings are ignored) to `1.0`
+    /// (no smoothing). Default value is `0.3`.
+    pub fn with_smoothing(mut self, smoothing: f64) -> Self {
+        self.smoothing = smoothing.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets the change of the heading in radians needed for the map to be rotated. Default value is 2 degrees.
+    pub fn with_dead_band(mut self, dead_band: f64) -> Self {
+        self.dead_band = dead_band;
+        self
+    }
+
+    /// Sets the duration of the rotation of the map to a new heading. With zero duration the map is rotated
+    /// immediately.
+    pub fn with_animation_duration(mut self, duration: Duration) -> Self {
+        self.animation_duration = duration;
+        self
+    }
+
+    /// Current orientation mode.
+    pub fn mode(&self) -> OrientationMode {
+        self.state.lock().mode
+    }
+
+    /// Smoothed heading in radians clockwise from the north, or `None` if no headings were received yet.
+    pub fn heading(&self) -> Option<f64> {
+        self.state.lock().heading.map(to_angle)
+    }
+
+    /// Changes the orientation mode and rotates the map accordingly: to the north in the north-up mode, and to the
+    /// last known heading in the heading-up mode.
+    pub fn set_mode(&self, map: &mut Map, mode: OrientationMode) {
+        let heading = {
+            let mut state = self.state.lock();
+            state.mode = mode;
+            state.heading.map(to_angle)
+        };
+
+        match mode {
+            OrientationMode::NorthUp => self.rotate_map(map, 0.0),
+            OrientationMode::HeadingUp => {
+                if let Some(heading) = heading {
+                    self.rotate_map(map, heading);
+                }
+            }
+            OrientationMode::Free => {}
+        }
+    }
+
+    /// Switches to the heading-up mode from the north-up or free mode, and to the north-up mode from the heading-up
+    /// mode.
+    pub fn toggle_mode(&self, map: &mut Map) {
+        let mode = match self.mode() {
+            OrientationMode::HeadingUp => OrientationMode::NorthUp,
+            OrientationMode::NorthUp | OrientationMode::Free => OrientationMode::HeadingUp,
+        };
+        self.set_mode(map, mode);
+    }
+
+    /// Feeds a new heading reading in radians clockwise from the north. In the heading-up mode the map is rotated to
+    /// the smoothed heading.
+    pub fn update_heading(&self, map: &mut Map, heading: f64) {
+        let (mode, heading) = {
+            let mut state = self.state.lock();
+            let reading = Vector2::new(heading.sin(), heading.cos());
+            let smoothed = match state.heading {
+                Some(prev) => {
+                    let mixed = prev * (1.0 - self.smoothing) + reading * self.smoothing;
+                    // Opposite readings cancel out, keep the previous heading then
+                    if mixed.norm() > 1e-9 {
+                        mixed.normalize()
+                    } else {
+                        prev
+                    }
+                }
+                None => reading,
+            };
+
+            state.heading = Some(smoothed);
+            (state.mode, to_angle(smoothed))
+        };
+
+        if mode != OrientationMode::HeadingUp {
+            return;
+        }
+
+        let current = map.target_view().rotation_z();
+        if normalize_angle(heading - current).abs() >= self.dead_band {
+            self.rotate_map(map, heading);
+        }
+    }
+
+    fn rotate_map(&self, map: &mut Map, rotation_z: f64) {
+        let view = map.target_view();
+        // Rotate the shortest way from the current rotation
+        let rotation_z = view.rotation_z() + normalize_angle(rotation_z - view.rotation_z());
+        let target = view.with_rotation_z(rotation_z);
+
+        if self.animation_duration.is_zero() {
+            map.set_view(target);
+        } else {
+            map.animate_to(target, self.animation_duration);
+            map.redraw();
+        }
+    }
+}
+
+fn to_angle(direction: Vector2<f64>) -> f64 {
+    direction.x.atan2(direction.y)
+}
+
+impl UserEventHandler for HeadingController {
+    fn handle(&self, event: &UserEvent, _map: &mut Map) -> EventPropagation {
+        if let UserEvent::DragStarted(MouseButton::Right, _) = event {
+            let mut state = self.state.lock();
+            if state.mode == OrientationMode::HeadingUp {
+                state.mode = OrientationMode::Free;
+            }
+        }
+
+        EventPropagation::Propagate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+    use galileo_types::cartesian::{Point2d, Size};
+    use galileo_types::geo::impls::GeoPoint2d;
+    use galileo_types::geo::NewGeoPoint;
+
+    use super::*;
+    use crate::control::MouseEvent;
+    use crate::view::MapView;
+
+    fn test_map() -> Map {
+        let view =
+            MapView::new(&GeoPoint2d::latlon(0.0, 0.0), 10.0).with_size(Size::new(200.0, 100.0));
+        Map::new(view, vec![], None)
+    }
+
+    fn controller() -> HeadingController {
+        HeadingController::new()
+            .with_smoothing(0.5)
+            .with_animation_duration(Duration::ZERO)
+    }
+
+    #[test]
+    fn smooths_across_north() {
+        let mut map = test_map();
+        let controller = controller();
+
+        controller.update_heading(&mut map, 350f64.to_radians());
+        controller.update_heading(&mut map, 10f64.to_radians());
+        let heading = controller.heading().expect("heading is set");
+        assert_abs_diff_eq!(normalize_angle(heading), 0.0, epsilon = 1e-9);
+
+        // North-up mode does not rotate the map
+        assert_eq!(map.view().rotation_z(), 0.0);
+    }
+
+    #[test]
+    fn heading_up_mode() {
+        let mut map = test_map();
+        let controller = controller();
+
+        controller.update_heading(&mut map, 0.5);
+        controller.set_mode(&mut map, OrientationMode::HeadingUp);
+        assert_abs_diff_eq!(map.view().rotation_z(), 0.5, epsilon = 1e-9);
+
+        // Change within the dead band is ignored
+        controller.update_heading(&mut map, 0.52);
+        assert_abs_diff_eq!(map.view().rotation_z(), 0.5, epsilon = 1e-9);
+
+        // User rotation switches to the free mode
+        controller.handle(
+            &UserEvent::DragStarted(
+                MouseButton::Right,
+                MouseEvent {
+                    screen_pointer_position: Point2d::new(0.0, 0.0),
+                    map_pointer_position: None,
+                    geo_pointer_position: None,
+                    buttons: Default::default(),
+                },
+            ),
+            &mut map,
+        );
+        assert_eq!(controller.mode(), OrientationMode::Free);
+        controller.update_heading(&mut map, 1.5);
+        assert_abs_diff_eq!(map.view().rotation_z(), 0.5, epsilon = 1e-9);
+
+        controller.toggle_mode(&mut map);
+        assert_eq!(controller.mode(), OrientationMode::HeadingUp);
+        controller.toggle_mode(&mut map);
+        assert_abs_diff_eq!(map.view().rotation_z(), 0.0, epsilon = 1e-9);
+    }
+}
@@ -0,0 +1,88 @@
+use crate::control::RawUserEvent;
+
+/// Classes of input events that the map consumes.
+///
+/// Integrations ([`WinitInputHandler`](crate::winit::WinitInputHandler) and the `egui` map widget) skip the events of
+/// disabled classes, so that they are left to the rest of the application, e.g. when the map is embedded into an app
+/// with text inputs or its own scrollable areas. Text input, IME composition and clipboard events are never consumed by
+/// the map.
+///
+/// By default all classes are enabled.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct InputFilter {
+    /// Mouse buttons and pointer movement.
+    pub pointer: bool,
+    /// Mouse wheel, touch pad scrolling and zoom gestures.
+    pub scroll: bool,
+    /// Touch screen events.
+    pub touch: bool,
+    /// Keyboard navigation (moving and zooming the map with the keys) in integrations that support it.
+    pub keyboard: bool,
+}
+
+impl Default for InputFilter {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+impl InputFilter {
+    /// Filter that lets the map consume all supported events.
+    pub fn all() -> Self {
+        Self {
+            pointer: true,
+            scroll: true,
+            touch: true,
+            keyboard: true,
+        }
+    }
+
+    /// Filter that passes all events through to the application, making the map non-interactive.
+    pub fn none() -> Self {
+        Self {
+            pointer: false,
+            scroll: false,
+            touch: false,
+            keyboard: false,
+        }
+    }
+
+    /// Returns true if the map should consume the event.
+    pub fn accepts(&self, event: &RawUserEvent) -> bool {
+        match event {
+            RawUserEvent::ButtonPressed(_)
+            | RawUserEvent::ButtonReleased(_)
+            | RawUserEvent::PointerMoved(_) => self.pointer,
+            RawUserEvent::Scroll(_) | RawUserEvent::Zoom(_) => self.scroll,
+            RawUserEvent::TouchStart(_)
+            | RawUserEvent::TouchMove(_)
+            | RawUserEvent::TouchEnd(_) => self.touch,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use galileo_types::cartesian::Point2d;
+
+    use super::*;
+    use crate::control::{MouseButton, TouchEvent};
+
+    #[test]
+    fn filters_event_classes() {
+        let filter = InputFilter {
+            scroll: false,
+            ..Default::default()
+        };
+
+        assert!(filter.accepts(&RawUserEvent::ButtonPressed(MouseButton::Left)));
+        assert!(filter.accepts(&RawUserEvent::TouchStart(TouchEvent {
+            touch_id: 0,
+            position: Point2d::new(0.0, 0.0),
+        })));
+        assert!(!filter.accepts(&RawUserEvent::Scroll(1.0)));
+        assert!(!filter.accepts(&RawUserEvent::Zoom(0.5)));
+
+        assert!(!InputFilter::none().accepts(&RawUserEvent::PointerMoved(Point2d::new(1.0, 1.0))));
+    }
+}
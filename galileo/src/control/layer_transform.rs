@@ -0,0 +1,136 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use galileo_types::cartesian::Point2d;
+
+use crate::control::{EventPropagation, MouseButton, UserEvent, UserEventHandler};
+use crate::map::{LayerTransform, Map};
+
+/// Event handler that adjusts the [transform](LayerTransform) of a single layer of the map with the pointer.
+///
+/// While the controller is active:
+/// * dragging with the left mouse button moves the layer,
+/// * dragging with the right mouse button rotates the layer around the center of the screen,
+/// * scrolling scales the layer around the pointer position.
+///
+/// The controller must be added to the event processor before the [`MapController`](super::MapController), so that
+/// it receives the events first. When the controller is inactive, all the events are propagated to the next handlers.
+///
+/// Clones of the controller share the same active state, so a clone can be kept by the application to switch the
+/// adjustment mode on and off after the controller is added to the event processor.
+#[derive(Debug, Clone)]
+pub struct LayerTransformController {
+    layer_index: usize,
+    is_active: Arc<AtomicBool>,
+    rotation_speed: f64,
+    scale_speed: f64,
+}
+
+impl LayerTransformController {
+    /// Creates a new inactive controller for the layer at the given index in the map's layer collection.
+    pub fn new(layer_index: usize) -> Self {
+        Self {
+            layer_index,
+            is_active: Arc::new(AtomicBool::new(false)),
+            rotation_speed: 0.005,
+            scale_speed: 0.05,
+        }
+    }
+
+    /// Sets the rotation angle in radians per pixel of the pointer movement.
+    pub fn with_rotation_speed(mut self, rotation_speed: f64) -> Self {
+        self.rotation_speed = rotation_speed;
+        self
+    }
+
+    /// Sets the change of the scale per line of scrolling.
+    pub fn with_scale_speed(mut self, scale_speed: f64) -> Self {
+        self.scale_speed = scale_speed;
+        self
+    }
+
+    /// Index of the layer the controller adjusts.
+    pub fn layer_index(&self) -> usize {
+        self.layer_index
+    }
+
+    /// Returns true if the controller handles the user input.
+    pub fn is_active(&self) -> bool {
+        self.is_active.load(Ordering::Relaxed)
+    }
+
+    /// Switches the controller on or off.
+    pub fn set_active(&self, is_active: bool) {
+        self.is_active.store(is_active, Ordering::Relaxed);
+    }
+
+    fn update_transform(&self, map: &mut Map, update: impl FnOnce(&mut LayerTransform)) {
+        let layers = map.layers_mut();
+        if self.layer_index >= layers.len() {
+            log::warn!(
+                "Layer {} is not present in the map, cannot update its transform",
+                self.layer_index
+            );
+            return;
+        }
+
+        let mut transform = layers
+            .transform(self.layer_index)
+            .copied()
+            .unwrap_or_default();
+        update(&mut transform);
+        layers.set_transform(self.layer_index, Some(transform));
+        map.redraw();
+    }
+}
+
+impl UserEventHandler for LayerTransformController {
+    fn handle(&self, event: &UserEvent, map: &mut Map) -> EventPropagation {
+        if !self.is_active() {
+            return EventPropagation::Propagate;
+        }
+
+        match event {
+            UserEvent::DragStarted(MouseButton::Left | MouseButton::Right, _) => {
+                EventPropagation::Consume
+            }
+            UserEvent::Drag(MouseButton::Left, delta, e) => {
+                let current_position = e.screen_pointer_position;
+                let view = map.view();
+                let (Some(from), Some(to)) = (
+                    view.screen_to_map(current_position - delta),
+                    view.screen_to_map(current_position),
+                ) else {
+                    return EventPropagation::Stop;
+                };
+
+                self.update_transform(map, |transform| transform.translate(to - from));
+                EventPropagation::Stop
+            }
+            UserEvent::Drag(MouseButton::Right, delta, _) => {
+                let size = map.view().size();
+                let Some(center) = map
+                    .view()
+                    .screen_to_map(Point2d::new(size.half_width(), size.half_height()))
+                else {
+                    return EventPropagation::Stop;
+                };
+
+                // Moving the pointer to the right rotates the layer clockwise
+                let angle = -delta.x * self.rotation_speed;
+                self.update_transform(map, |transform| transform.rotate_around(center, angle));
+                EventPropagation::Stop
+            }
+            UserEvent::Scroll(delta, e) => {
+                let Some(center) = e.map_pointer_position else {
+                    return EventPropagation::Stop;
+                };
+
+                let factor = (1.0 + self.scale_speed).powf(*delta);
+                self.update_transform(map, |transform| transform.scale_around(center, factor));
+                EventPropagation::Stop
+            }
+            _ => EventPropagation::Propagate,
+        }
+    }
+}
@@ -1,9 +1,11 @@
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 
+use galileo_types::cartesian::Point2d;
 use nalgebra::Vector2;
 
 use crate::control::{EventPropagation, MouseButton, UserEvent, UserEventHandler};
-use crate::map::Map;
+use crate::map::{normalize_angle, Map};
 use crate::view::MapView;
 
 const DEFAULT_ZOOM_DURATION: Duration = Duration::from_millis(50);
@@ -12,6 +14,8 @@ const DEFAULT_ZOOM_DURATION: Duration = Duration::from_millis(50);
 #[derive(Default)]
 pub struct MapController {
     parameters: MapControllerParameters,
+    /// Set when the controller takes ownership of a drag with the right button, i.e. the map is being rotated.
+    is_rotating: AtomicBool,
 }
 
 pub struct MapControllerParameters {
@@ -22,8 +26,16 @@ pub struct MapControllerParameters {
 
     rotation_speed: f64,
     max_rotation_x: f64,
+    north_snap_threshold: Option<f64>,
+    double_click_zoom: bool,
+
+    min_camera_height: Option<f64>,
+    ground_elevation: Option<GroundElevationFn>,
 }
 
+/// Function returning the elevation of the ground at the given point in map coordinates.
+type GroundElevationFn = Box<dyn Fn(Point2d) -> Option<f64> + Send + Sync>;
+
 impl Default for MapControllerParameters {
     fn default() -> Self {
         Self {
@@ -33,6 +45,10 @@ impl Default for MapControllerParameters {
             min_resolution: 156543.03392800014 / 8.0 / 2.0f64.powi(16),
             rotation_speed: 0.005,
             max_rotation_x: 80f64.to_radians(),
+            north_snap_threshold: None,
+            double_click_zoom: false,
+            min_camera_height: None,
+            ground_elevation: None,
         }
     }
 }
@@ -45,6 +61,8 @@ impl UserEventHandler for MapController {
                     || *button == MouseButton::Right
                     || *button == MouseButton::Other =>
             {
+                self.is_rotating
+                    .store(*button == MouseButton::Right, Ordering::Relaxed);
                 EventPropagation::Consume
             }
             UserEvent::Drag(button, delta, e) => match button {
@@ -64,18 +82,30 @@ impl UserEventHandler for MapController {
                 }
                 _ => EventPropagation::Propagate,
             },
+            UserEvent::DragEnded(MouseButton::Right, _) => {
+                if self.is_rotating.swap(false, Ordering::Relaxed) {
+                    self.snap_to_north(map);
+                    EventPropagation::Stop
+                } else {
+                    EventPropagation::Propagate
+                }
+            }
             UserEvent::Scroll(delta, mouse_event) => {
-                let zoom = self.get_zoom(*delta, map.view().resolution());
-                let target = map
-                    .target_view()
-                    .zoom(zoom, mouse_event.screen_pointer_position);
-                map.animate_to(target, self.parameters.zoom_duration);
+                self.zoom_around(map, mouse_event.screen_pointer_position, *delta);
+
+                EventPropagation::Stop
+            }
+            UserEvent::DoubleClick(MouseButton::Left, mouse_event)
+                if self.parameters.double_click_zoom =>
+            {
+                let zoom = self.limit_zoom(0.5, map.target_view().resolution());
+                self.zoom_map(map, mouse_event.screen_pointer_position, zoom);
 
                 EventPropagation::Stop
             }
             UserEvent::Zoom(zoom, center) => {
                 let target = map.view().zoom(*zoom, *center);
-                map.set_view(target);
+                map.set_view(self.limit_rotation_x(target));
 
                 EventPropagation::Stop
             }
@@ -85,8 +115,113 @@ impl UserEventHandler for MapController {
 }
 
 impl MapController {
+    /// Makes the controller rotate the map back to the north-up position at the end of a rotation gesture, if the
+    /// map is rotated by less than `threshold` radians from the north.
+    ///
+    /// ```
+    /// use galileo::control::MapController;
+    ///
+    /// let controller = MapController::default().with_north_snap(10f64.to_radians());
+    /// ```
+    pub fn with_north_snap(mut self, threshold: f64) -> Self {
+        self.parameters.north_snap_threshold = Some(threshold);
+        self
+    }
+
+    /// Enables or disables zooming in twice around the pointer on a double click with the left mouse button. Disabled
+    /// by default, so that fast consecutive clicks in applications that use clicks for selection or editing do not
+    /// change the view.
+    ///
+    /// ```
+    /// use galileo::control::MapController;
+    ///
+    /// let controller = MapController::default().with_double_click_zoom(true);
+    /// ```
+    pub fn with_double_click_zoom(mut self, is_enabled: bool) -> Self {
+        self.parameters.double_click_zoom = is_enabled;
+        self
+    }
+
+    /// Limits the tilt of the map, so that the camera stays at least `height` map units above the ground.
+    ///
+    /// The limit is checked both when the map is tilted and when it is zoomed in, as zooming in moves the camera closer
+    /// to the ground.
+    ///
+    /// The elevation of the ground under the camera is taken from the function set with
+    /// [`MapController::with_ground_elevation`], or is `0.0` if the function is not set.
+    ///
+    /// ```
+    /// use galileo::control::MapController;
+    ///
+    /// let controller = MapController::default()
+    ///     .with_min_camera_height(50.0)
+    ///     .with_ground_elevation(|point| Some(if point.x > 0.0 { 300.0 } else { 0.0 }));
+    /// ```
+    pub fn with_min_camera_height(mut self, height: f64) -> Self {
+        self.parameters.min_camera_height = Some(height);
+        self
+    }
+
+    /// Sets the function that returns the elevation of the ground at the given point in map coordinates, e.g. sampled
+    /// from a digital elevation model. Points for which the function returns `None` are considered to be at `0.0`
+    /// elevation.
+    ///
+    /// The elevation is used to keep the camera above the ground (see [`MapController::with_min_camera_height`]).
+    pub fn with_ground_elevation(
+        mut self,
+        elevation: impl Fn(Point2d) -> Option<f64> + Send + Sync + 'static,
+    ) -> Self {
+        self.parameters.ground_elevation = Some(Box::new(elevation));
+        self
+    }
+
+    /// Zooms the map around the given screen point the same way as a scroll event with the given `delta` would do.
+    ///
+    /// Resolution limits and zoom animation parameters of the controller are applied. This can be used to drive the
+    /// map from custom UI elements (e.g. zoom buttons) consistently with user input.
+    pub fn zoom_around(&self, map: &mut Map, screen_point: Point2d, delta: f64) {
+        let zoom = self.get_zoom(delta, map.target_view().resolution());
+        self.zoom_map(map, screen_point, zoom);
+    }
+
+    /// Moves the map by the given number of pixels the same way as a drag by the pointer would do.
+    pub fn pan_by_pixels(&self, map: &mut Map, dx: f64, dy: f64) {
+        map.pan_by_pixels(dx, dy);
+    }
+
+    fn snap_to_north(&self, map: &mut Map) {
+        let Some(threshold) = self.parameters.north_snap_threshold else {
+            return;
+        };
+
+        let rotation_z = normalize_angle(map.view().rotation_z());
+        if rotation_z != 0.0 && rotation_z.abs() <= threshold {
+            map.reset_rotation(true);
+        }
+    }
+
+    fn zoom_map(&self, map: &mut Map, screen_point: Point2d, zoom: f64) {
+        let duration = self.parameters.zoom_duration;
+        map.zoom_around(screen_point, zoom, duration);
+
+        // Zooming in moves the camera closer to the ground, so the tilt might need to be decreased
+        let target = map.target_view();
+        let limited = self.limit_rotation_x(target.clone());
+        if limited.rotation_x() < target.rotation_x() {
+            if duration.is_zero() {
+                map.set_view(limited);
+            } else {
+                map.animate_to(limited, duration);
+            }
+        }
+    }
+
     fn get_zoom(&self, delta: f64, current_resolution: f64) -> f64 {
         let zoom = (self.parameters.zoom_speed + 1.0).powf(-delta);
+        self.limit_zoom(zoom, current_resolution)
+    }
+
+    fn limit_zoom(&self, zoom: f64, current_resolution: f64) -> f64 {
         let target_resolution = current_resolution * zoom;
         if target_resolution > self.parameters.max_resolution {
             self.parameters.max_resolution / current_resolution
@@ -109,6 +244,72 @@ impl MapController {
             rotation_x = self.parameters.max_rotation_x;
         };
 
-        curr_view.with_rotation(rotation_x, rotation_z)
+        self.limit_rotation_x(curr_view.with_rotation(rotation_x, rotation_z))
+    }
+
+    /// Decreases the tilt of the view if the camera is lower than the minimum camera height above the ground.
+    fn limit_rotation_x(&self, view: MapView) -> MapView {
+        match self.max_rotation_x_above_ground(&view) {
+            Some(max_rotation_x) if view.rotation_x() > max_rotation_x => {
+                view.with_rotation_x(max_rotation_x)
+            }
+            _ => view,
+        }
+    }
+
+    /// Maximum tilt of the view at which the camera stays above the ground by the minimum camera height.
+    ///
+    /// The elevation is sampled under the camera of the given view, so the limit is approximate when the elevation
+    /// changes a lot between the camera positions with different tilts.
+    fn max_rotation_x_above_ground(&self, view: &MapView) -> Option<f64> {
+        let min_height = self.parameters.min_camera_height?;
+        let camera = view.camera_position()?;
+        let elevation = self
+            .parameters
+            .ground_elevation
+            .as_ref()
+            .and_then(|elevation| elevation(Point2d::new(camera.x, camera.y)))
+            .unwrap_or(0.0);
+
+        view.max_rotation_x_for_height(elevation, min_height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+    use galileo_types::cartesian::Size;
+
+    use super::*;
+
+    fn tilted_map() -> Map {
+        // Camera is 100 units away from the map and 100 * cos(1.0) = 54 units above the ground
+        let view = MapView::new_projected(&Point2d::new(0.0, 0.0), 2.0)
+            .with_size(Size::new(100.0, 100.0))
+            .with_rotation_x(1.0);
+        Map::new(view, vec![], None)
+    }
+
+    #[test]
+    fn zoom_keeps_camera_above_ground() {
+        let controller = MapController::default().with_min_camera_height(50.0);
+
+        let mut map = tilted_map();
+        let delta = 2f64.ln() / 1.2f64.ln();
+        controller.zoom_around(&mut map, Point2d::new(50.0, 50.0), delta);
+        assert_abs_diff_eq!(map.target_view().resolution(), 1.0, epsilon = 1e-9);
+        assert_abs_diff_eq!(map.target_view().rotation_x(), 0.0, epsilon = 1e-9);
+
+        let mut map = tilted_map();
+        controller.handle(&UserEvent::Zoom(0.75, Point2d::new(50.0, 50.0)), &mut map);
+        assert_abs_diff_eq!(
+            map.view().rotation_x(),
+            (50.0f64 / 75.0).acos(),
+            epsilon = 1e-9
+        );
+
+        let mut map = tilted_map();
+        controller.handle(&UserEvent::Zoom(2.0, Point2d::new(50.0, 50.0)), &mut map);
+        assert_abs_diff_eq!(map.view().rotation_x(), 1.0, epsilon = 1e-9);
     }
 }
@@ -12,15 +12,28 @@
 //! to the `EventProcessor` handler list.
 
 use galileo_types::cartesian::Point2d;
+use galileo_types::geo::impls::GeoPoint2d;
 use maybe_sync::{MaybeSend, MaybeSync};
 use nalgebra::Vector2;
 
 use crate::map::Map;
 
+mod bbox;
 mod event_processor;
+mod follow;
+mod gesture;
+mod heading;
+mod input_filter;
+mod layer_transform;
 mod map;
 
-pub use event_processor::EventProcessor;
+pub use bbox::BoundingBoxController;
+pub use event_processor::{EventProcessor, HandlerId};
+pub use follow::FollowController;
+pub use gesture::Gesture;
+pub use heading::{HeadingController, OrientationMode};
+pub use input_filter::InputFilter;
+pub use layer_transform::LayerTransformController;
 pub use map::MapController;
 
 /// User input handler.
@@ -42,6 +55,7 @@ where
 /// by the application. It does not provide any state information, as not all supported platforms give this information
 /// together with the event. Instead, the input state information is stored in the [`EventProcessor`] struct, which
 /// can combine `RawUserEvent` with the state to produce [`UserEvent`] which is then given to the application.
+#[derive(Debug, Clone)]
 pub enum RawUserEvent {
     /// A mouse button was pressed.
     ButtonPressed(MouseButton),
@@ -58,6 +72,12 @@ pub enum RawUserEvent {
     TouchMove(TouchEvent),
     /// Existing touch was released.
     TouchEnd(TouchEvent),
+    /// Zoom gesture (e.g. pinch on a touch pad) around the current pointer position. The value is the multiplier
+    /// for the map resolution, so values less than `1.0` zoom in.
+    ///
+    /// Multi-touch gestures on touch screens are given as separate touch events instead, and are converted into zoom
+    /// by the [`EventProcessor`].
+    Zoom(f64),
 }
 
 /// User interaction event. This is the main type that the application would use through [`UserEventHandler`]s.
@@ -94,6 +114,57 @@ pub enum UserEvent {
     /// Zoom is called around a point. This is different from [`UserEvent::Scroll`], as it is not produced by a mouse
     /// but rather by multi-tough gestures. The first parameter is zoom delta value.
     Zoom(f64, Point2d),
+
+    /// Context menu is requested by a right mouse button click or by a long press of a single touch (configured in
+    /// [`EventProcessor`]).
+    ContextMenu(ContextMenuEvent),
+}
+
+impl UserEvent {
+    /// State of the mouse at the moment of the event, if the event is produced by a mouse.
+    pub fn mouse_event(&self) -> Option<&MouseEvent> {
+        match self {
+            UserEvent::ButtonPressed(_, event)
+            | UserEvent::ButtonReleased(_, event)
+            | UserEvent::Click(_, event)
+            | UserEvent::DoubleClick(_, event)
+            | UserEvent::PointerMoved(event)
+            | UserEvent::DragStarted(_, event)
+            | UserEvent::Drag(_, _, event)
+            | UserEvent::DragEnded(_, event)
+            | UserEvent::Scroll(_, event) => Some(event),
+            UserEvent::Zoom(..) | UserEvent::ContextMenu(_) => None,
+        }
+    }
+
+    pub(crate) fn mouse_event_mut(&mut self) -> Option<&mut MouseEvent> {
+        match self {
+            UserEvent::ButtonPressed(_, event)
+            | UserEvent::ButtonReleased(_, event)
+            | UserEvent::Click(_, event)
+            | UserEvent::DoubleClick(_, event)
+            | UserEvent::PointerMoved(event)
+            | UserEvent::DragStarted(_, event)
+            | UserEvent::Drag(_, _, event)
+            | UserEvent::DragEnded(_, event)
+            | UserEvent::Scroll(_, event) => Some(event),
+            UserEvent::Zoom(..) | UserEvent::ContextMenu(_) => None,
+        }
+    }
+}
+
+/// Details of a [`UserEvent::ContextMenu`] event.
+#[derive(Debug, Clone)]
+pub struct ContextMenuEvent {
+    /// Position of the pointer or the touch on the screen in pixels from the top-left corner.
+    pub screen_position: Point2d,
+    /// Position in the projected coordinates of the map. `None` if the point is outside of the map (e.g. above the
+    /// horizon of a tilted map).
+    pub map_position: Option<Point2d>,
+    /// Geographic position of the point. `None` if the point is outside of the map or cannot be projected.
+    pub geo_position: Option<GeoPoint2d>,
+    /// True if the event was triggered by a long press of a touch, false if by a mouse click.
+    pub is_long_press: bool,
 }
 
 /// Value returned by an [`UserEventHandler`] to indicate the status of the event.
@@ -126,6 +197,12 @@ pub enum MouseButton {
 pub struct MouseEvent {
     /// Pointer position on the screen in pixels from the top-left corner.
     pub screen_pointer_position: Point2d,
+    /// Pointer position in the projected coordinates of the map, taking into account rotation and tilt of the view.
+    /// `None` if the pointer is outside of the map (e.g. above the horizon of a tilted map).
+    pub map_pointer_position: Option<Point2d>,
+    /// Geographic position of the pointer. `None` if the pointer is outside of the map or the position cannot be
+    /// projected.
+    pub geo_pointer_position: Option<GeoPoint2d>,
     /// State of the mouse buttons.
     pub buttons: MouseButtonsState,
 }
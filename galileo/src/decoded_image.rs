@@ -27,10 +27,21 @@ impl DecodedImage {
     ///
     /// Attempts to guess the format of the image from the data. Non-RGBA images
     /// will be converted to RGBA.
+    ///
+    /// PNG and JPEG formats are always supported. WebP images can be decoded if the `webp` feature is enabled.
+    ///
+    /// AVIF decoding requires the `dav1d` library to be installed in the system, so it is not a feature of this crate
+    /// (it would break builds with `--all-features`). An application can opt in by enabling the `avif-decoder`
+    /// feature of its own `image` dependency of the same version, which turns it on for this crate as well.
     #[cfg(feature = "image")]
     pub fn decode(bytes: &[u8]) -> Result<Self, GalileoError> {
         use image::GenericImageView;
-        let decoded = image::load_from_memory(bytes).map_err(|_| GalileoError::ImageDecode)?;
+
+        crate::trace::span!("image_decode", size = bytes.len());
+        let decoded = image::load_from_memory(bytes).map_err(|err| {
+            log::debug!("Failed to decode image: {err}");
+            GalileoError::ImageDecode
+        })?;
         let bytes = decoded.to_rgba8();
         let dimensions = decoded.dimensions();
 
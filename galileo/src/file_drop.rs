@@ -0,0 +1,515 @@
+//! Opening files dropped onto the map window as layers.
+//!
+//! [`FileDropHandler`] chooses a [`FileLoader`] by the format of the file and creates a layer from it. The map
+//! integrations ([`MapBuilder::with_file_drop_handler`](crate::MapBuilder::with_file_drop_handler) for `winit` and
+//! the `egui` map widget) call the handler for every dropped file and add the created layer on top of the map.
+//!
+//! Supported formats depend on the enabled features:
+//! * GeoJSON (`.geojson` and `.json` files) with the `geojson` feature,
+//! * FlatGeobuf (`.fgb` files) with the `flatgeobuf` feature,
+//! * GPX (`.gpx` files) with the `gpx` feature,
+//! * KML (`.kml` files) with the `kml` feature,
+//! * PNG and JPEG images with [world files](https://en.wikipedia.org/wiki/World_file) with the `image` feature.
+//!
+//! A raster image is opened together with its world file, which is either dropped together with the image (see
+//! [`FileDropHandler::open_all`]), or found next to the image by [`DroppedFile::read`].
+//!
+//! Applications can support other formats by adding their own loaders with [`FileDropHandler::with_loader`], and
+//! change the styling of the created layers with [`FileDropHandler::with_on_layer`]:
+//!
+//! ```no_run
+//! use galileo::file_drop::FileDropHandler;
+//! use galileo::MapBuilder;
+//!
+//! let handler = FileDropHandler::new().with_on_layer(|file, _layer| {
+//!     log::info!("Opened {} as a new layer", file.name);
+//! });
+//! let map = MapBuilder::new().with_file_drop_handler(handler);
+//! ```
+
+use std::path::Path;
+
+use maybe_sync::{MaybeSend, MaybeSync};
+
+use crate::error::GalileoError;
+use crate::layer::Layer;
+
+/// Function called with every layer created by a [`FileDropHandler`] before it is added to the map.
+type LayerCreatedFn = Box<dyn Fn(&DroppedFile, &mut Box<dyn Layer>) + Send + Sync>;
+
+/// Name of a dropped file with the result of opening it.
+type OpenedFile = (String, Result<Box<dyn Layer>, GalileoError>);
+
+/// Extensions of the raster images that can be georeferenced with world files.
+const IMAGE_EXTENSIONS: [&str; 5] = ["png", "jpg", "jpeg", "tif", "tiff"];
+
+/// File dropped onto the map.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DroppedFile {
+    /// Name of the file including the extension.
+    pub name: String,
+    /// Contents of the file.
+    pub bytes: Vec<u8>,
+    /// Files needed to open this file, e.g. the world file of a raster image.
+    pub sidecars: Vec<DroppedFile>,
+}
+
+impl DroppedFile {
+    /// Creates a new dropped file with the given name and contents.
+    pub fn new(name: impl Into<String>, bytes: Vec<u8>) -> Self {
+        Self {
+            name: name.into(),
+            bytes,
+            sidecars: vec![],
+        }
+    }
+
+    /// Adds a sidecar file.
+    pub fn with_sidecar(mut self, sidecar: DroppedFile) -> Self {
+        self.sidecars.push(sidecar);
+        self
+    }
+
+    /// Reads the file from the file system. If the file is a raster image, its world file is read as a sidecar if it
+    /// exists next to the image.
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, GalileoError> {
+        let path = path.as_ref();
+        let mut file = Self::new(file_name(path), std::fs::read(path)?);
+
+        if let Some(extension) = file.extension() {
+            let world_file_path = world_file_extensions(&extension)
+                .into_iter()
+                .flat_map(|extension| [extension.to_uppercase(), extension])
+                .map(|extension| path.with_extension(extension))
+                .find(|path| path.is_file());
+            if let Some(world_file_path) = world_file_path {
+                let bytes = std::fs::read(&world_file_path)?;
+                file.sidecars
+                    .push(Self::new(file_name(&world_file_path), bytes));
+            }
+        }
+
+        Ok(file)
+    }
+
+    /// Extension of the file name in lower case, or `None` if the name has no extension.
+    pub fn extension(&self) -> Option<String> {
+        Path::new(&self.name)
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+    }
+
+    /// Returns true if the file is a world file of a raster image, e.g. `.pgw` file of a PNG image or `.wld` file.
+    pub fn is_world_file(&self) -> bool {
+        self.extension().is_some_and(|extension| {
+            IMAGE_EXTENSIONS
+                .iter()
+                .any(|image| world_file_extensions(image).contains(&extension))
+        })
+    }
+
+    /// The first world file among the sidecars of the file.
+    pub fn world_file(&self) -> Option<&DroppedFile> {
+        self.sidecars.iter().find(|sidecar| sidecar.is_world_file())
+    }
+
+    /// Name of the file without the extension in lower case.
+    fn stem(&self) -> String {
+        Path::new(&self.name)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_lowercase())
+            .unwrap_or_default()
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Possible extensions of the world file of an image with the given extension: the first and the last letters of the
+/// image extension followed by `w` (e.g. `.pgw`), the image extension followed by `w` (e.g. `.pngw`), and `.wld`.
+fn world_file_extensions(image_extension: &str) -> Vec<String> {
+    let mut extensions = vec![];
+    let mut chars = image_extension.chars();
+    if let (Some(first), Some(last)) = (chars.next(), chars.last()) {
+        extensions.push(format!("{first}{last}w"));
+    }
+    extensions.push(format!("{image_extension}w"));
+    extensions.push("wld".to_string());
+
+    extensions
+}
+
+/// Creates layers from files of some format.
+pub trait FileLoader: MaybeSend + MaybeSync {
+    /// Returns true if the file has the format supported by the loader. This is usually decided by the extension of
+    /// the file.
+    fn can_load(&self, file: &DroppedFile) -> bool;
+
+    /// Creates a layer from the file.
+    fn load(&self, file: &DroppedFile) -> Result<Box<dyn Layer>, GalileoError>;
+}
+
+/// Creates layers from the files dropped onto the map. See [module documentation](self) for details.
+pub struct FileDropHandler {
+    loaders: Vec<Box<dyn FileLoader>>,
+    on_layer: Option<LayerCreatedFn>,
+}
+
+impl Default for FileDropHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FileDropHandler {
+    /// Creates a handler with the loaders of all formats supported with the enabled features.
+    // Loaders are pushed one by one, since each of them depends on a feature
+    #[allow(clippy::vec_init_then_push)]
+    pub fn new() -> Self {
+        #[allow(unused_mut)]
+        let mut loaders: Vec<Box<dyn FileLoader>> = vec![];
+
+        #[cfg(feature = "geojson")]
+        loaders.push(Box::new(GeoJsonLoader));
+        #[cfg(feature = "flatgeobuf")]
+        loaders.push(Box::new(FlatGeobufLoader));
+        #[cfg(feature = "gpx")]
+        loaders.push(Box::new(GpxLoader));
+        #[cfg(feature = "kml")]
+        loaders.push(Box::new(KmlLoader));
+        #[cfg(feature = "image")]
+        loaders.push(Box::new(WorldFileImageLoader::default()));
+
+        Self {
+            loaders,
+            on_layer: None,
+        }
+    }
+
+    /// Creates a handler without any loaders.
+    pub fn empty() -> Self {
+        Self {
+            loaders: vec![],
+            on_layer: None,
+        }
+    }
+
+    /// Adds a loader. Loaders added later take precedence over the ones added before, so a built-in loader can be
+    /// replaced by a custom one for the same format.
+    pub fn with_loader(mut self, loader: impl FileLoader + 'static) -> Self {
+        self.loaders.insert(0, Box::new(loader));
+        self
+    }
+
+    /// Sets the function that is called with every created layer before it is added to the map. The function can
+    /// change the layer (e.g. set a different symbol after downcasting it with [`Layer::as_any_mut`]) or replace it.
+    pub fn with_on_layer(
+        mut self,
+        on_layer: impl Fn(&DroppedFile, &mut Box<dyn Layer>) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_layer = Some(Box::new(on_layer));
+        self
+    }
+
+    /// Creates a layer from the file with the first loader that supports it.
+    pub fn open(&self, file: &DroppedFile) -> Result<Box<dyn Layer>, GalileoError> {
+        let loader = self
+            .loaders
+            .iter()
+            .find(|loader| loader.can_load(file))
+            .ok_or_else(|| {
+                GalileoError::Generic(format!("format of the file {} is not supported", file.name))
+            })?;
+
+        let mut layer = loader.load(file)?;
+        if let Some(on_layer) = &self.on_layer {
+            on_layer(file, &mut layer);
+        }
+
+        Ok(layer)
+    }
+
+    /// Creates layers from several files dropped at once.
+    ///
+    /// World files are attached as [sidecars](DroppedFile::sidecars) to the images with the same name instead of
+    /// being opened as separate files. World files without an image are skipped. Returns the name of every opened file
+    /// with the result of opening it.
+    pub fn open_all(&self, files: Vec<DroppedFile>) -> Vec<OpenedFile> {
+        let (world_files, mut files): (Vec<_>, Vec<_>) =
+            files.into_iter().partition(DroppedFile::is_world_file);
+
+        for world_file in world_files {
+            let stem = world_file.stem();
+            match files
+                .iter_mut()
+                .find(|file| file.stem() == stem && file.world_file().is_none())
+            {
+                Some(file) => file.sidecars.push(world_file),
+                None => log::debug!("Image for the world file {} is not found", world_file.name),
+            }
+        }
+
+        files
+            .into_iter()
+            .map(|file| {
+                let result = self.open(&file);
+                (file.name, result)
+            })
+            .collect()
+    }
+}
+
+/// Loads GeoJSON feature collections into a [`FeatureLayer`](crate::layer::FeatureLayer) with
+/// [`ArbitraryGeometrySymbol`](crate::symbol::ArbitraryGeometrySymbol).
+#[cfg(feature = "geojson")]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct GeoJsonLoader;
+
+#[cfg(feature = "geojson")]
+impl FileLoader for GeoJsonLoader {
+    fn can_load(&self, file: &DroppedFile) -> bool {
+        matches!(file.extension().as_deref(), Some("geojson" | "json"))
+    }
+
+    fn load(&self, file: &DroppedFile) -> Result<Box<dyn Layer>, GalileoError> {
+        let geojson: geojson::GeoJson = serde_json::from_slice(&file.bytes)
+            .map_err(|err| GalileoError::Generic(format!("invalid GeoJSON: {err}")))?;
+        let collection = geojson::FeatureCollection::try_from(geojson)
+            .map_err(|err| GalileoError::Generic(format!("invalid GeoJSON: {err}")))?;
+
+        Ok(geojson_layer(collection))
+    }
+}
+
+/// Loads FlatGeobuf files into a [`FeatureLayer`](crate::layer::FeatureLayer) with
+/// [`ArbitraryGeometrySymbol`](crate::symbol::ArbitraryGeometrySymbol). The data is expected to be in WGS84.
+#[cfg(feature = "flatgeobuf")]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct FlatGeobufLoader;
+
+#[cfg(feature = "flatgeobuf")]
+impl FileLoader for FlatGeobufLoader {
+    fn can_load(&self, file: &DroppedFile) -> bool {
+        file.extension().as_deref() == Some("fgb")
+    }
+
+    fn load(&self, file: &DroppedFile) -> Result<Box<dyn Layer>, GalileoError> {
+        let collection = crate::layer::feature_layer::flatgeobuf::read_features(
+            std::io::Cursor::new(&file.bytes),
+        )?;
+
+        Ok(geojson_layer(collection))
+    }
+}
+
+/// Loads GPX waypoints, routes and tracks into a [`FeatureLayer`](crate::layer::FeatureLayer) with
+/// [`ArbitraryGeometrySymbol`](crate::symbol::ArbitraryGeometrySymbol). See
+/// [`gpx`](crate::layer::feature_layer::gpx) module for details.
+#[cfg(feature = "gpx")]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct GpxLoader;
+
+#[cfg(feature = "gpx")]
+impl FileLoader for GpxLoader {
+    fn can_load(&self, file: &DroppedFile) -> bool {
+        file.extension().as_deref() == Some("gpx")
+    }
+
+    fn load(&self, file: &DroppedFile) -> Result<Box<dyn Layer>, GalileoError> {
+        let collection = crate::layer::feature_layer::gpx::read_features(file.bytes.as_slice())?;
+        Ok(geojson_layer(collection))
+    }
+}
+
+/// Loads KML placemarks into a [`FeatureLayer`](crate::layer::FeatureLayer) with
+/// [`ArbitraryGeometrySymbol`](crate::symbol::ArbitraryGeometrySymbol). See
+/// [`kml`](crate::layer::feature_layer::kml) module for details.
+#[cfg(feature = "kml")]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct KmlLoader;
+
+#[cfg(feature = "kml")]
+impl FileLoader for KmlLoader {
+    fn can_load(&self, file: &DroppedFile) -> bool {
+        file.extension().as_deref() == Some("kml")
+    }
+
+    fn load(&self, file: &DroppedFile) -> Result<Box<dyn Layer>, GalileoError> {
+        let collection = crate::layer::feature_layer::kml::read_features(file.bytes.as_slice())?;
+        Ok(geojson_layer(collection))
+    }
+}
+
+/// Loads PNG and JPEG images georeferenced with a world file into an [`ImageLayer`](crate::layer::ImageLayer). The
+/// world file must be a [sidecar](DroppedFile::sidecars) of the image.
+///
+/// World files do not specify the CRS of the coordinates, so all images are placed in the CRS of the loader, which is
+/// Web Mercator by default. Use [`FileDropHandler::with_loader`] to add a loader with a different CRS.
+#[cfg(feature = "image")]
+#[derive(Debug, Clone)]
+pub struct WorldFileImageLoader {
+    crs: galileo_types::geo::Crs,
+}
+
+#[cfg(feature = "image")]
+impl WorldFileImageLoader {
+    /// Creates a loader that places the images in the given CRS.
+    pub fn new(crs: galileo_types::geo::Crs) -> Self {
+        Self { crs }
+    }
+}
+
+#[cfg(feature = "image")]
+impl Default for WorldFileImageLoader {
+    fn default() -> Self {
+        Self::new(galileo_types::geo::Crs::EPSG3857)
+    }
+}
+
+#[cfg(feature = "image")]
+impl FileLoader for WorldFileImageLoader {
+    fn can_load(&self, file: &DroppedFile) -> bool {
+        matches!(file.extension().as_deref(), Some("png" | "jpg" | "jpeg"))
+    }
+
+    fn load(&self, file: &DroppedFile) -> Result<Box<dyn Layer>, GalileoError> {
+        let world_file = file.world_file().ok_or_else(|| {
+            GalileoError::Generic(format!(
+                "world file of the image {} is not found",
+                file.name
+            ))
+        })?;
+        let world_file = std::str::from_utf8(&world_file.bytes)
+            .map_err(|err| GalileoError::Generic(format!("invalid world file: {err}")))?;
+        let image = crate::decoded_image::DecodedImage::decode(&file.bytes)?;
+
+        Ok(Box::new(crate::layer::ImageLayer::from_world_file(
+            image,
+            world_file,
+            self.crs.clone(),
+        )?))
+    }
+}
+
+#[cfg(feature = "geojson")]
+fn geojson_layer(collection: geojson::FeatureCollection) -> Box<dyn Layer> {
+    Box::new(crate::layer::FeatureLayer::from_geojson(
+        collection,
+        crate::symbol::ArbitraryGeometrySymbol::default(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_extension() {
+        assert_eq!(
+            DroppedFile::new("Tracks.GeoJSON", vec![]).extension(),
+            Some("geojson".to_string())
+        );
+        assert_eq!(DroppedFile::new("README", vec![]).extension(), None);
+    }
+
+    #[test]
+    fn unsupported_format() {
+        let handler = FileDropHandler::empty();
+        assert!(handler
+            .open(&DroppedFile::new("track.gpx", vec![]))
+            .is_err());
+    }
+
+    #[test]
+    fn world_files() {
+        assert!(DroppedFile::new("map.pgw", vec![]).is_world_file());
+        assert!(DroppedFile::new("map.JGW", vec![]).is_world_file());
+        assert!(DroppedFile::new("map.tfw", vec![]).is_world_file());
+        assert!(DroppedFile::new("map.wld", vec![]).is_world_file());
+        assert!(!DroppedFile::new("map.png", vec![]).is_world_file());
+        assert!(!DroppedFile::new("map.gpx", vec![]).is_world_file());
+    }
+
+    struct SidecarCountLoader;
+
+    impl FileLoader for SidecarCountLoader {
+        fn can_load(&self, _file: &DroppedFile) -> bool {
+            true
+        }
+
+        fn load(&self, file: &DroppedFile) -> Result<Box<dyn Layer>, GalileoError> {
+            Err(GalileoError::Generic(file.sidecars.len().to_string()))
+        }
+    }
+
+    #[test]
+    fn world_files_are_attached_to_images() {
+        let handler = FileDropHandler::empty().with_loader(SidecarCountLoader);
+        let results = handler.open_all(vec![
+            DroppedFile::new("a.pgw", vec![]),
+            DroppedFile::new("b.png", vec![]),
+            DroppedFile::new("A.png", vec![]),
+            DroppedFile::new("c.wld", vec![]),
+        ]);
+
+        let sidecar_counts: Vec<_> = results
+            .into_iter()
+            .map(|(name, result)| match result {
+                Err(GalileoError::Generic(count)) => (name, count),
+                _ => panic!("unexpected result"),
+            })
+            .collect();
+        assert_eq!(
+            sidecar_counts,
+            vec![
+                ("b.png".to_string(), "0".to_string()),
+                ("A.png".to_string(), "1".to_string())
+            ]
+        );
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn open_image_with_world_file() {
+        let mut png = vec![];
+        image::DynamicImage::ImageRgba8(image::RgbaImage::new(2, 2))
+            .write_to(
+                &mut std::io::Cursor::new(&mut png),
+                image::ImageOutputFormat::Png,
+            )
+            .expect("failed to encode image");
+
+        let handler = FileDropHandler::new();
+        let image = DroppedFile::new("map.png", png);
+        assert!(handler.open(&image).is_err());
+
+        let world_file = DroppedFile::new("map.pgw", b"10\n0\n0\n-10\n5\n-5\n".to_vec());
+        let layer = handler
+            .open(&image.with_sidecar(world_file))
+            .expect("failed to open image");
+        assert_eq!(
+            layer.extent(&galileo_types::geo::Crs::EPSG3857),
+            Some(galileo_types::cartesian::Rect::new(0.0, -20.0, 20.0, 0.0))
+        );
+    }
+
+    #[cfg(feature = "geojson")]
+    #[test]
+    fn open_geojson() {
+        let geojson = r#"{"type": "FeatureCollection", "features": [
+            {"type": "Feature", "properties": {}, "geometry": {"type": "Point", "coordinates": [10.0, 20.0]}}
+        ]}"#;
+        let handler = FileDropHandler::new().with_on_layer(|file, _layer| {
+            assert_eq!(file.name, "points.geojson");
+        });
+
+        let file = DroppedFile::new("points.geojson", geojson.as_bytes().to_vec());
+        assert!(handler.open(&file).is_ok());
+        assert!(handler
+            .open(&DroppedFile::new("broken.geojson", b"{".to_vec()))
+            .is_err());
+    }
+}
@@ -12,13 +12,15 @@ use winit::event::WindowEvent;
 use winit::event_loop::EventLoop;
 use winit::window::Window;
 
-use crate::control::{EventProcessor, EventPropagation, UserEvent};
+use crate::control::{EventProcessor, EventPropagation, InputFilter, UserEvent};
+use crate::file_drop::{DroppedFile, FileDropHandler};
 use crate::layer::data_provider::UrlSource;
 use crate::layer::vector_tile_layer::style::VectorTileStyle;
-use crate::layer::Layer;
+use crate::layer::{Basemap, Layer, VectorBasemap};
 use crate::map::Map;
 #[cfg(target_arch = "wasm32")]
 use crate::platform::web::map_builder::sleep;
+use crate::render::horizon::HorizonOptions;
 use crate::render::WgpuRenderer;
 use crate::tile_scheme::{TileIndex, TileSchema};
 use crate::view::MapView;
@@ -39,6 +41,13 @@ pub struct GalileoMap {
     pub(crate) input_handler: WinitInputHandler,
     pub(crate) event_loop: Option<EventLoop<()>>,
     pub(crate) init_size: Size<u32>,
+    /// Window has zero size (e.g. it is minimized).
+    pub(crate) is_minimized: bool,
+    /// Window is fully hidden by other windows.
+    pub(crate) is_occluded: bool,
+    /// Messenger set by the application with [`MapBuilder::with_messenger`].
+    pub(crate) app_messenger: Option<Arc<dyn Messenger>>,
+    pub(crate) file_drop_handler: Option<FileDropHandler>,
 
     #[cfg(target_arch = "wasm32")]
     pub(crate) dom_container: Option<web_sys::HtmlElement>,
@@ -110,18 +119,35 @@ impl ApplicationHandler for GalileoMap {
             }
 
             *backend.write() = Some(renderer);
-            map.write()
-                .set_size(Size::new(size.width as f64, size.height as f64));
+            let mut map = map.write();
+            // The renderer could be recreated with a new device after the application was suspended
+            map.clear_render_cache();
+            map.set_size(Size::new(size.width as f64, size.height as f64));
+            map.set_dpi_scale_factor(window.scale_factor());
+            map.set_paused(false);
+            drop(map);
             window.request_redraw();
         });
     }
 
     fn suspended(&mut self, _event_loop: &winit::event_loop::ActiveEventLoop) {
         *self.backend.write() = None;
+        self.map.write().set_paused(true);
     }
 
-    fn about_to_wait(&mut self, _event_loop: &winit::event_loop::ActiveEventLoop) {
-        self.map.write().animate();
+    fn about_to_wait(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
+        let mut map = self.map.write();
+        self.event_processor.update(&mut map);
+        map.animate();
+
+        // Wake up the event loop when a pending gesture (e.g. long press) is due
+        let control_flow = match self.event_processor.next_update_in() {
+            Some(delay) => {
+                winit::event_loop::ControlFlow::WaitUntil(web_time::Instant::now() + delay)
+            }
+            None => winit::event_loop::ControlFlow::Wait,
+        };
+        event_loop.set_control_flow(control_flow);
     }
 
     fn window_event(
@@ -136,16 +162,37 @@ impl ApplicationHandler for GalileoMap {
             }
             WindowEvent::Resized(size) => {
                 log::info!("Window resized to: {size:?}");
-                if let Some(backend) = self.backend.write().as_mut() {
-                    backend.resize(Size::new(size.width, size.height));
 
-                    let mut map = self.map.write();
-                    map.set_size(Size::new(size.width as f64, size.height as f64));
+                // Nothing can be rendered to a zero size window, so the map is paused until the window is restored,
+                // keeping the last non-zero size
+                self.is_minimized = size.width == 0 || size.height == 0;
+                if !self.is_minimized {
+                    if let Some(backend) = self.backend.write().as_mut() {
+                        backend.resize(Size::new(size.width, size.height));
+
+                        let mut map = self.map.write();
+                        map.set_size(Size::new(size.width as f64, size.height as f64));
+                    }
                 }
+
+                self.update_paused();
+            }
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                self.map.write().set_dpi_scale_factor(scale_factor);
             }
+            WindowEvent::Occluded(occluded) => {
+                // Window is minimized or hidden, so there is no need to load data and render the map
+                self.is_occluded = occluded;
+                self.update_paused();
+            }
+            WindowEvent::DroppedFile(path) => self.open_dropped_file(&path),
             WindowEvent::RedrawRequested => {
                 if let Some(backend) = self.backend.read().as_ref() {
                     let map = self.map.read();
+                    if map.is_paused() {
+                        return;
+                    }
+
                     map.load_layers();
                     if let Err(err) = backend.render(&map) {
                         log::error!("Render error: {err:?}");
@@ -174,15 +221,57 @@ impl ApplicationHandler for GalileoMap {
 
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
 impl GalileoMap {
-    fn set_messenger(&mut self, messenger: Option<WinitMessenger>) {
+    /// Pauses the map while the window is not visible or there is no renderer to draw it with.
+    fn update_paused(&self) {
+        let is_hidden = self.is_minimized || self.is_occluded || self.backend.read().is_none();
         let mut map = self.map.write();
-        map.set_messenger(messenger.clone());
+        if map.is_paused() != is_hidden {
+            map.set_paused(is_hidden);
+        }
+    }
+
+    /// Adds the layer created from the dropped file to the top of the map.
+    fn open_dropped_file(&self, path: &std::path::Path) {
+        let Some(handler) = &self.file_drop_handler else {
+            return;
+        };
 
-        if let Some(messenger) = messenger {
-            for layer in map.layers_mut().iter_mut() {
-                let boxed: Box<dyn Messenger> = Box::new(messenger.clone());
-                layer.set_messenger(boxed);
+        let file = match DroppedFile::read(path) {
+            Ok(file) => file,
+            Err(err) => {
+                log::warn!("Failed to read dropped file {path:?}: {err}");
+                return;
             }
+        };
+
+        // World files are read together with their images, so they are skipped when dropped separately
+        for (name, result) in handler.open_all(vec![file]) {
+            let mut layer = match result {
+                Ok(layer) => layer,
+                Err(err) => {
+                    log::warn!("Failed to open dropped file {name}: {err}");
+                    continue;
+                }
+            };
+
+            let window_messenger = self.window.clone().map(WinitMessenger::new);
+            if let Some(messenger) =
+                combine_messengers(window_messenger, self.app_messenger.clone())
+            {
+                layer.set_messenger(Box::new(messenger));
+            }
+
+            let mut map = self.map.write();
+            map.layers_mut().push_boxed(layer);
+            map.redraw();
+        }
+    }
+
+    fn set_messenger(&mut self, messenger: Option<WinitMessenger>) {
+        let mut map = self.map.write();
+        match combine_messengers(messenger, self.app_messenger.clone()) {
+            Some(messenger) => map.install_messenger(messenger),
+            None => map.set_messenger(None::<WinitMessenger>),
         }
     }
 
@@ -209,6 +298,10 @@ pub struct MapBuilder {
     pub(crate) window: Option<Window>,
     pub(crate) event_loop: Option<EventLoop<()>>,
     pub(crate) size: Option<Size<u32>>,
+    pub(crate) messenger: Option<Arc<dyn Messenger>>,
+    pub(crate) input_filter: InputFilter,
+    pub(crate) file_drop_handler: Option<FileDropHandler>,
+    pub(crate) horizon: Option<HorizonOptions>,
 
     #[cfg(target_arch = "wasm32")]
     pub(crate) dom_container: Option<web_sys::HtmlElement>,
@@ -234,17 +327,23 @@ impl MapBuilder {
 
         let backend = Arc::new(RwLock::new(None));
 
-        let input_handler = WinitInputHandler::default();
+        let input_handler = WinitInputHandler::with_filter(self.input_filter);
 
         let mut event_processor = EventProcessor::default();
         for handler in self.event_handlers.drain(..) {
             event_processor.add_handler(handler);
         }
-        event_processor.add_handler(crate::control::MapController::default());
+        event_processor.add_named_handler(
+            EventProcessor::MAP_CONTROLLER,
+            crate::control::MapController::default(),
+            EventProcessor::LOW_PRIORITY,
+        );
         let init_size = self.size.unwrap_or_else(|| Size::new(1024, 1024));
 
         #[cfg(target_arch = "wasm32")]
         let dom_container = self.dom_container.clone();
+        let app_messenger = self.messenger.clone();
+        let file_drop_handler = self.file_drop_handler.take();
 
         GalileoMap {
             window: None,
@@ -254,12 +353,35 @@ impl MapBuilder {
             input_handler,
             event_loop: Some(event_loop),
             init_size,
+            is_minimized: false,
+            is_occluded: false,
+            app_messenger,
+            file_drop_handler,
 
             #[cfg(target_arch = "wasm32")]
             dom_container,
         }
     }
 
+    /// Add a raster tile layer with one of the well-known basemaps to the layer list.
+    ///
+    /// Note that the attribution of the basemap ([`Basemap::attribution`]) must be displayed by the application.
+    pub fn with_basemap(mut self, basemap: Basemap) -> Self {
+        self.layers
+            .push(Box::new(Self::create_basemap_layer(&basemap)));
+        self
+    }
+
+    /// Add a vector tile layer with one of the well-known vector basemaps and its default style to the layer list.
+    ///
+    /// Note that the attribution of the basemap ([`VectorBasemap::attribution`]) must be displayed by the application,
+    /// and the labels are drawn only if the [`BASEMAP_FONT`](crate::layer::vector_tile_layer::BASEMAP_FONT) is loaded.
+    pub fn with_vector_basemap(mut self, basemap: VectorBasemap) -> Self {
+        self.layers
+            .push(Box::new(Self::create_vector_basemap_layer(&basemap)));
+        self
+    }
+
     /// Use the given window instead of creating a default one.
     pub fn with_window(mut self, window: Window) -> Self {
         self.window = Some(window);
@@ -326,23 +448,65 @@ impl MapBuilder {
         self
     }
 
-    pub(crate) fn build_map(mut self, messenger: Option<WinitMessenger>) -> Arc<RwLock<Map>> {
-        if let Some(ref messenger) = messenger {
-            for layer in self.layers.iter_mut() {
-                layer.set_messenger(Box::new(messenger.clone()))
-            }
-        }
+    /// Sets a messenger that is notified of all redraw requests and view rotation changes of the map and its layers,
+    /// in addition to the window of the map.
+    ///
+    /// This allows applications built around a message loop to react to map updates, e.g. with a
+    /// [`ChannelMessenger`](crate::ChannelMessenger):
+    ///
+    /// ```ignore
+    /// let (messenger, receiver) = ChannelMessenger::channel();
+    /// let map = MapBuilder::new()
+    ///     .with_basemap(Basemap::OpenStreetMap)
+    ///     .with_messenger(messenger)
+    ///     .build();
+    /// ```
+    pub fn with_messenger(mut self, messenger: impl Messenger + 'static) -> Self {
+        self.messenger = Some(Arc::new(messenger));
+        self
+    }
+
+    /// Sets the classes of input events consumed by the map. By default all events are consumed.
+    pub fn with_input_filter(mut self, filter: InputFilter) -> Self {
+        self.input_filter = filter;
+        self
+    }
 
+    /// Opens the files dropped onto the map window as new layers with the given handler.
+    pub fn with_file_drop_handler(mut self, handler: FileDropHandler) -> Self {
+        self.file_drop_handler = Some(handler);
+        self
+    }
+
+    /// Draws the sky and haze at the horizon when the map is tilted. See [`Map::set_horizon`].
+    pub fn with_horizon(mut self, horizon: HorizonOptions) -> Self {
+        self.horizon = Some(horizon);
+        self
+    }
+
+    pub(crate) fn build_map(self, messenger: Option<WinitMessenger>) -> Arc<RwLock<Map>> {
         let view = self
             .view
             .unwrap_or_else(|| MapView::new(&self.position, self.resolution));
 
-        let map = Map::new(
-            view,
-            self.layers,
-            messenger.map(|m| Box::new(m) as Box<dyn Messenger>),
-        );
+        let mut map = Map::new(view, self.layers, None);
+        map.set_horizon(self.horizon);
+        if let Some(messenger) = combine_messengers(messenger, self.messenger) {
+            map.install_messenger(messenger);
+        }
 
         Arc::new(RwLock::new(map))
     }
 }
+
+/// Combines the messenger of the window with the messenger set by the application, so that both are notified.
+fn combine_messengers(
+    window: Option<WinitMessenger>,
+    app: Option<Arc<dyn Messenger>>,
+) -> Option<Arc<dyn Messenger>> {
+    match (window, app) {
+        (Some(window), Some(app)) => Some(Arc::new((window, app))),
+        (Some(window), None) => Some(Arc::new(window)),
+        (None, app) => app,
+    }
+}
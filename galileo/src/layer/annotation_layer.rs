@@ -0,0 +1,329 @@
+use std::any::Any;
+
+use galileo_types::cartesian::{CartesianPoint2d, Point2d, Point3d, Rect};
+use galileo_types::impls::{ClosedContour, Contour, Polygon};
+
+use crate::layer::Layer;
+use crate::messenger::Messenger;
+use crate::render::point_paint::PointPaint;
+use crate::render::render_bundle::RenderPrimitive;
+use crate::render::text::TextStyle;
+use crate::render::{Canvas, LineCap, LinePaint, PolygonPaint, RenderOptions};
+use crate::view::MapView;
+use crate::Color;
+
+/// Coordinate space of the points of an annotation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoordinateSpace {
+    /// Projected coordinates of the map CRS. Annotations move together with the map.
+    Map,
+    /// Screen coordinates in pixels from the top-left corner of the map. Annotations stay at the same place on the
+    /// screen when the map is moved.
+    Screen,
+}
+
+#[derive(Debug, Clone)]
+enum Shape {
+    Point(Point2d, PointPaint<'static>),
+    Line(Vec<Point2d>, bool, LinePaint),
+    Polygon(Vec<Point2d>, PolygonPaint),
+}
+
+#[derive(Debug, Clone)]
+struct Annotation {
+    space: CoordinateSpace,
+    shape: Shape,
+}
+
+/// Layer for transient graphics, like debug overlays, cursors or selection boxes, with an immediate-mode drawing API.
+///
+/// Instead of creating features and symbols, the application calls drawing methods (e.g.
+/// [`AnnotationLayer::draw_circle`]) to add shapes to the layer, and [`AnnotationLayer::clear`] to remove all of them,
+/// usually every time the graphics change. The shapes are tessellated every time the layer is rendered, so the layer
+/// is intended for a small number of shapes.
+///
+/// Changing the annotations does not redraw the map by itself. If they are changed outside of the render loop (e.g.
+/// in an event handler), call [`AnnotationLayer::request_redraw`] or [`Map::redraw`](crate::Map::redraw).
+///
+/// ```no_run
+/// use galileo::galileo_types::cartesian::Point2d;
+/// use galileo::layer::{AnnotationLayer, CoordinateSpace};
+/// use galileo::Color;
+///
+/// let mut layer = AnnotationLayer::new();
+/// layer.draw_circle(CoordinateSpace::Screen, Point2d::new(10.0, 10.0), 8.0, Color::RED);
+/// layer.draw_polyline(
+///     CoordinateSpace::Map,
+///     vec![Point2d::new(0.0, 0.0), Point2d::new(1000.0, 1000.0)],
+///     Color::BLUE,
+///     2.0,
+/// );
+/// ```
+#[derive(Default)]
+pub struct AnnotationLayer {
+    annotations: Vec<Annotation>,
+    messenger: Option<Box<dyn Messenger>>,
+}
+
+impl AnnotationLayer {
+    /// Creates an empty layer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Removes all annotations.
+    pub fn clear(&mut self) {
+        self.annotations.clear();
+    }
+
+    /// Returns true if the layer has no annotations.
+    pub fn is_empty(&self) -> bool {
+        self.annotations.is_empty()
+    }
+
+    /// Number of annotations in the layer.
+    pub fn len(&self) -> usize {
+        self.annotations.len()
+    }
+
+    /// Requests the map to be redrawn with the current annotations.
+    pub fn request_redraw(&self) {
+        if let Some(messenger) = &self.messenger {
+            messenger.request_redraw();
+        }
+    }
+
+    /// Draws a point with the given paint.
+    pub fn draw_point(
+        &mut self,
+        space: CoordinateSpace,
+        position: Point2d,
+        paint: PointPaint<'static>,
+    ) {
+        self.push(space, Shape::Point(position, paint));
+    }
+
+    /// Draws a filled circle. The diameter is given in pixels.
+    pub fn draw_circle(
+        &mut self,
+        space: CoordinateSpace,
+        center: Point2d,
+        diameter: f32,
+        color: Color,
+    ) {
+        self.draw_point(space, center, PointPaint::circle(color, diameter));
+    }
+
+    /// Draws a text label with its anchor at the given position.
+    pub fn draw_text(
+        &mut self,
+        space: CoordinateSpace,
+        position: Point2d,
+        text: impl Into<String>,
+        style: TextStyle,
+    ) {
+        self.draw_point(space, position, PointPaint::label_owned(text.into(), style));
+    }
+
+    /// Draws a line through the given points. The width is given in pixels.
+    pub fn draw_polyline(
+        &mut self,
+        space: CoordinateSpace,
+        points: Vec<Point2d>,
+        color: Color,
+        width: f64,
+    ) {
+        self.draw_line_with_paint(space, points, false, line_paint(color, width));
+    }
+
+    /// Draws a line through the given points with the given paint. If `is_closed` is true, the last point is
+    /// connected to the first one.
+    pub fn draw_line_with_paint(
+        &mut self,
+        space: CoordinateSpace,
+        points: Vec<Point2d>,
+        is_closed: bool,
+        paint: LinePaint,
+    ) {
+        self.push(space, Shape::Line(points, is_closed, paint));
+    }
+
+    /// Draws a filled polygon with the given outer contour.
+    pub fn draw_polygon(&mut self, space: CoordinateSpace, points: Vec<Point2d>, color: Color) {
+        self.push(
+            space,
+            Shape::Polygon(
+                points,
+                PolygonPaint {
+                    color,
+                    hatching: None,
+                },
+            ),
+        );
+    }
+
+    /// Draws a rectangle, e.g. a selection box, with optional fill and outline. The outline width is given in pixels.
+    pub fn draw_rect(
+        &mut self,
+        space: CoordinateSpace,
+        rect: Rect,
+        fill: Option<Color>,
+        outline: Option<(Color, f64)>,
+    ) {
+        let points = vec![
+            Point2d::new(rect.x_min(), rect.y_min()),
+            Point2d::new(rect.x_max(), rect.y_min()),
+            Point2d::new(rect.x_max(), rect.y_max()),
+            Point2d::new(rect.x_min(), rect.y_max()),
+        ];
+
+        if let Some(color) = fill {
+            self.draw_polygon(space, points.clone(), color);
+        }
+
+        if let Some((color, width)) = outline {
+            self.draw_line_with_paint(space, points, true, line_paint(color, width));
+        }
+    }
+
+    fn push(&mut self, space: CoordinateSpace, shape: Shape) {
+        self.annotations.push(Annotation { space, shape });
+    }
+}
+
+fn line_paint(color: Color, width: f64) -> LinePaint {
+    LinePaint {
+        color,
+        width,
+        offset: 0.0,
+        line_cap: LineCap::Butt,
+        width_scale: None,
+        crisp: false,
+    }
+}
+
+/// Converts the points of the annotation into the map coordinates of the view. Returns `None` if any of the screen
+/// points is outside of the map (e.g. above the horizon of a tilted map).
+fn to_map(view: &MapView, space: CoordinateSpace, points: &[Point2d]) -> Option<Vec<Point3d>> {
+    points
+        .iter()
+        .map(|point| {
+            let point = match space {
+                CoordinateSpace::Map => *point,
+                CoordinateSpace::Screen => view.screen_to_map(*point)?,
+            };
+            Some(Point3d::new(point.x(), point.y(), 0.0))
+        })
+        .collect()
+}
+
+impl Layer for AnnotationLayer {
+    fn render(&self, view: &MapView, canvas: &mut dyn Canvas) {
+        if self.annotations.is_empty() {
+            return;
+        }
+
+        let mut bundle = canvas.create_bundle();
+        let resolution = view.resolution();
+        for annotation in &self.annotations {
+            let primitive: RenderPrimitive<f64, Point3d, Contour<Point3d>, Polygon<Point3d>> =
+                match &annotation.shape {
+                    Shape::Point(position, paint) => {
+                        let Some(points) = to_map(view, annotation.space, &[*position]) else {
+                            continue;
+                        };
+                        RenderPrimitive::new_point(points[0], paint.clone())
+                    }
+                    Shape::Line(points, is_closed, paint) => {
+                        let Some(points) = to_map(view, annotation.space, points) else {
+                            continue;
+                        };
+                        RenderPrimitive::new_contour(Contour::new(points, *is_closed), *paint)
+                    }
+                    Shape::Polygon(points, paint) => {
+                        let Some(points) = to_map(view, annotation.space, points) else {
+                            continue;
+                        };
+                        RenderPrimitive::new_polygon(ClosedContour::new(points).into(), *paint)
+                    }
+                };
+
+            bundle.add(primitive, resolution);
+        }
+
+        let packed = canvas.pack_bundle(&bundle);
+        canvas.draw_bundles(&[&*packed], RenderOptions::default());
+    }
+
+    fn prepare(&self, _view: &MapView) {
+        // do nothing
+    }
+
+    fn set_messenger(&mut self, messenger: Box<dyn Messenger>) {
+        self.messenger = Some(messenger);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use galileo_types::cartesian::Size;
+
+    use super::*;
+
+    #[test]
+    fn screen_points_are_projected_to_map() {
+        let view = MapView::new_projected(&Point2d::new(100.0, 100.0), 2.0)
+            .with_size(Size::new(100.0, 100.0));
+
+        let points = to_map(
+            &view,
+            CoordinateSpace::Screen,
+            &[Point2d::new(50.0, 50.0), Point2d::new(60.0, 50.0)],
+        )
+        .expect("points are on the map");
+        assert!((points[0].x - 100.0).abs() < 1e-6);
+        assert!((points[1].x - 120.0).abs() < 1e-6);
+
+        let points = to_map(&view, CoordinateSpace::Map, &[Point2d::new(5.0, 7.0)])
+            .expect("map points are always valid");
+        assert_eq!(points[0], Point3d::new(5.0, 7.0, 0.0));
+    }
+
+    #[test]
+    fn drawing_and_clearing() {
+        let mut layer = AnnotationLayer::new();
+        layer.draw_rect(
+            CoordinateSpace::Screen,
+            Rect::new(0.0, 0.0, 10.0, 10.0),
+            Some(Color::RED),
+            Some((Color::BLACK, 1.0)),
+        );
+        layer.draw_text(
+            CoordinateSpace::Map,
+            Point2d::new(0.0, 0.0),
+            "label",
+            TextStyle {
+                font_name: "Noto Sans".into(),
+                font_size: 12.0,
+                font_color: Color::BLACK,
+                horizontal_alignment: Default::default(),
+                vertical_alignment: Default::default(),
+                max_width: None,
+                line_spacing: 1.0,
+                line_alignment: None,
+            },
+        );
+        assert_eq!(layer.len(), 3);
+
+        layer.clear();
+        assert!(layer.is_empty());
+    }
+}
@@ -0,0 +1,241 @@
+//! Loading of tiles from local [MBTiles](https://github.com/mapbox/mbtiles-spec) files.
+//!
+//! MBTiles is an SQLite database with a tileset, so a map can be shown without network access, e.g. in desktop and
+//! mobile applications working offline. [`MbtilesTileLoader`] can be used both as a provider of a
+//! [`RasterTileLayer`](crate::layer::RasterTileLayer) and as a loader of a
+//! [`VectorTileLayer`](crate::layer::VectorTileLayer):
+//!
+//! ```no_run
+//! use galileo::layer::data_provider::MbtilesTileLoader;
+//! use galileo::layer::RasterTileLayer;
+//!
+//! let loader = MbtilesTileLoader::open("data/osm.mbtiles").expect("failed to open tileset");
+//! let tile_schema = loader.tile_schema();
+//! let layer = RasterTileLayer::new(tile_schema, loader, None);
+//! ```
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use bytes::Bytes;
+use galileo_mvt::MvtTile;
+use galileo_types::cartesian::Rect;
+use parking_lot::Mutex;
+use rusqlite::{Connection, OpenFlags, OptionalExtension};
+
+use crate::decoded_image::DecodedImage;
+use crate::error::GalileoError;
+use crate::layer::data_provider::DataProvider;
+use crate::layer::vector_tile_layer::tile_provider::compression::decompress;
+use crate::layer::vector_tile_layer::tile_provider::loader::{TileLoadError, VectorTileLoader};
+use crate::tile_scheme::{TileIndex, TileSchema};
+
+/// Zoom levels of a tileset without `maxzoom` metadata.
+const DEFAULT_MAX_ZOOM: u32 = 18;
+
+/// Values of the `metadata` table of an MBTiles file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MbtilesMetadata {
+    /// Name of the tileset.
+    pub name: Option<String>,
+    /// Format of the tile data: `pbf` for vector tiles, `png`, `jpg` or `webp` for raster tiles.
+    pub format: Option<String>,
+    /// Lowest zoom level of the tileset.
+    pub min_zoom: Option<u32>,
+    /// Highest zoom level of the tileset.
+    pub max_zoom: Option<u32>,
+    /// Extent of the tileset as longitude and latitude in WGS84.
+    pub bounds: Option<Rect>,
+    /// All values of the metadata table, including the ones parsed into the fields above.
+    pub values: HashMap<String, String>,
+}
+
+impl MbtilesMetadata {
+    fn from_values(values: HashMap<String, String>) -> Self {
+        let zoom = |key: &str| values.get(key).and_then(|value| value.trim().parse().ok());
+        let bounds = values.get("bounds").and_then(|value| {
+            let coords: Vec<f64> = value
+                .split(',')
+                .map(|coord| coord.trim().parse().ok())
+                .collect::<Option<_>>()?;
+            match coords[..] {
+                [x_min, y_min, x_max, y_max] => Some(Rect::new(x_min, y_min, x_max, y_max)),
+                _ => None,
+            }
+        });
+
+        Self {
+            name: values.get("name").cloned(),
+            format: values.get("format").cloned(),
+            min_zoom: zoom("minzoom"),
+            max_zoom: zoom("maxzoom"),
+            bounds,
+            values,
+        }
+    }
+
+    /// Returns true if the tileset contains vector tiles.
+    pub fn is_vector(&self) -> bool {
+        self.format.as_deref() == Some("pbf")
+    }
+}
+
+/// Loads raster or vector tiles from a local MBTiles file. See [module documentation](self) for details.
+pub struct MbtilesTileLoader {
+    connection: Mutex<Connection>,
+    metadata: MbtilesMetadata,
+}
+
+impl MbtilesTileLoader {
+    /// Opens the MBTiles file for reading and reads its metadata.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, GalileoError> {
+        let connection = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )
+        .map_err(sqlite_error)?;
+
+        Self::from_connection(connection)
+    }
+
+    fn from_connection(connection: Connection) -> Result<Self, GalileoError> {
+        let values = {
+            let mut statement = connection
+                .prepare("SELECT name, value FROM metadata")
+                .map_err(sqlite_error)?;
+            let rows = statement
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+                .map_err(sqlite_error)?;
+            rows.collect::<Result<HashMap<String, String>, _>>()
+                .map_err(sqlite_error)?
+        };
+
+        Ok(Self {
+            connection: Mutex::new(connection),
+            metadata: MbtilesMetadata::from_values(values),
+        })
+    }
+
+    /// Metadata of the tileset.
+    pub fn metadata(&self) -> &MbtilesMetadata {
+        &self.metadata
+    }
+
+    /// Creates a Web Mercator tile schema with the zoom levels of the tileset.
+    ///
+    /// MBTiles files are always in Web Mercator, but do not store the size of the tiles, so 256 pixel tiles are
+    /// assumed. If the tiles of the file have different size, change [`TileSchema::tile_width`] and
+    /// [`TileSchema::tile_height`] of the returned schema.
+    pub fn tile_schema(&self) -> TileSchema {
+        let max_zoom = self.metadata.max_zoom.unwrap_or(DEFAULT_MAX_ZOOM);
+        let min_zoom = self.metadata.min_zoom.unwrap_or(0).min(max_zoom);
+
+        let mut schema = TileSchema::web(max_zoom + 1);
+        schema.lods.retain(|lod| lod.z_index() >= min_zoom);
+        schema
+    }
+
+    /// Reads the raw data of the tile. Returns `None` if the tileset does not contain the tile.
+    pub fn read_tile(&self, index: TileIndex) -> Result<Option<Bytes>, GalileoError> {
+        if index.x < 0 || index.y < 0 || index.z > 31 {
+            return Ok(None);
+        }
+
+        // Rows of MBTiles are counted from the bottom of the map
+        let row = (1i64 << index.z) - 1 - index.y as i64;
+        self.connection
+            .lock()
+            .query_row(
+                "SELECT tile_data FROM tiles WHERE zoom_level = ?1 AND tile_column = ?2 AND tile_row = ?3",
+                (index.z, index.x, row),
+                |row| row.get::<_, Vec<u8>>(0),
+            )
+            .optional()
+            .map(|data| data.map(Bytes::from))
+            .map_err(sqlite_error)
+    }
+}
+
+fn sqlite_error(err: rusqlite::Error) -> GalileoError {
+    GalileoError::Generic(format!("failed to read MBTiles file: {err}"))
+}
+
+#[cfg(feature = "image")]
+impl DataProvider<TileIndex, DecodedImage, ()> for MbtilesTileLoader {
+    async fn load_raw(&self, key: &TileIndex) -> Result<Bytes, GalileoError> {
+        self.read_tile(*key)?.ok_or(GalileoError::NotFound)
+    }
+
+    fn decode(&self, bytes: Bytes, _context: ()) -> Result<DecodedImage, GalileoError> {
+        DecodedImage::decode(&bytes)
+    }
+}
+
+#[async_trait::async_trait]
+impl VectorTileLoader for MbtilesTileLoader {
+    async fn load(&self, index: TileIndex) -> Result<MvtTile, TileLoadError> {
+        let bytes = match self.read_tile(index) {
+            Ok(Some(bytes)) => bytes,
+            Ok(None) => return Err(TileLoadError::DoesNotExist),
+            Err(err) => {
+                log::warn!("Failed to read tile {index:?}: {err}");
+                return Err(TileLoadError::Network);
+            }
+        };
+
+        // Vector tiles in MBTiles files are usually compressed with gzip
+        let bytes = decompress(bytes).map_err(|err| {
+            log::warn!("Failed to decompress tile: {err}");
+            TileLoadError::Decoding
+        })?;
+        MvtTile::decode(bytes, false).map_err(|_| TileLoadError::Decoding)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_loader() -> MbtilesTileLoader {
+        let connection = Connection::open_in_memory().unwrap();
+        connection
+            .execute_batch(
+                "CREATE TABLE metadata (name TEXT, value TEXT);
+                 CREATE TABLE tiles (zoom_level INTEGER, tile_column INTEGER, tile_row INTEGER, tile_data BLOB);
+                 INSERT INTO metadata VALUES ('name', 'test'), ('format', 'png'), ('minzoom', '1'),
+                     ('maxzoom', '3'), ('bounds', '-10.0, 40.0, 10.5, 50.0');
+                 INSERT INTO tiles VALUES (2, 1, 3, x'0102');",
+            )
+            .unwrap();
+
+        MbtilesTileLoader::from_connection(connection).unwrap()
+    }
+
+    #[test]
+    fn metadata() {
+        let loader = test_loader();
+        let metadata = loader.metadata();
+        assert_eq!(metadata.name.as_deref(), Some("test"));
+        assert_eq!(metadata.min_zoom, Some(1));
+        assert_eq!(metadata.max_zoom, Some(3));
+        assert_eq!(metadata.bounds, Some(Rect::new(-10.0, 40.0, 10.5, 50.0)));
+        assert!(!metadata.is_vector());
+
+        let schema = loader.tile_schema();
+        let z_indices: Vec<u32> = schema.lods.iter().map(|lod| lod.z_index()).collect();
+        assert_eq!(z_indices.len(), 3);
+        assert!(z_indices.iter().all(|z| (1..=3).contains(z)));
+    }
+
+    #[test]
+    fn read_tile() {
+        let loader = test_loader();
+        // Row 3 counted from the bottom is row 0 counted from the top at zoom level 2
+        assert_eq!(
+            loader.read_tile(TileIndex::new(1, 0, 2)).unwrap(),
+            Some(Bytes::from_static(&[1, 2]))
+        );
+        assert_eq!(loader.read_tile(TileIndex::new(1, 3, 2)).unwrap(), None);
+        assert_eq!(loader.read_tile(TileIndex::new(-1, 0, 2)).unwrap(), None);
+    }
+}
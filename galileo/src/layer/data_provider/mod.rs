@@ -1,11 +1,15 @@
 //! Data sources for layers.
 
+mod stats;
 mod url_image_provider;
 
+pub use stats::{TileSourceStats, TileSourceStatsCollector};
 pub use url_image_provider::UrlImageProvider;
 
 #[cfg(not(target_arch = "wasm32"))]
 mod file_cache;
+#[cfg(all(feature = "mbtiles", not(target_arch = "wasm32")))]
+mod mbtiles;
 
 use std::future::Future;
 
@@ -13,6 +17,8 @@ use bytes::Bytes;
 #[cfg(not(target_arch = "wasm32"))]
 pub use file_cache::FileCacheController;
 use maybe_sync::{MaybeSend, MaybeSync};
+#[cfg(all(feature = "mbtiles", not(target_arch = "wasm32")))]
+pub use mbtiles::{MbtilesMetadata, MbtilesTileLoader};
 
 use crate::error::GalileoError;
 
@@ -47,6 +53,11 @@ where
             self.decode(raw, context)
         }
     }
+
+    /// Statistics of the requests made by the provider, if the provider collects them.
+    fn stats(&self) -> Option<TileSourceStats> {
+        None
+    }
 }
 
 /// Data processors are used to decode raw loaded data into something useful by a layer.
@@ -0,0 +1,197 @@
+use std::iter::Sum;
+use std::ops::{Add, AddAssign};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Statistics of requests made by a tile source.
+///
+/// A snapshot of the statistics can be obtained from a tile layer (e.g.
+/// [`RasterTileLayer::source_stats`](crate::layer::RasterTileLayer::source_stats)) or aggregated over all
+/// layers of a map with [`Map::tile_source_stats`](crate::Map::tile_source_stats).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TileSourceStats {
+    /// Total number of tile requests, including the ones served from the cache.
+    pub requests: u64,
+    /// Number of requests that were served from the persistent cache.
+    pub cache_hits: u64,
+    /// Number of requests that failed.
+    pub errors: u64,
+    /// Total number of bytes downloaded from the network.
+    pub bytes_downloaded: u64,
+    /// Total time spent waiting for the network requests.
+    pub total_latency: Duration,
+}
+
+impl TileSourceStats {
+    /// Number of requests that were sent to the network (not served from the cache).
+    pub fn network_requests(&self) -> u64 {
+        self.requests.saturating_sub(self.cache_hits)
+    }
+
+    /// Portion of the requests that failed, in the range `[0.0, 1.0]`.
+    pub fn error_rate(&self) -> f64 {
+        ratio(self.errors, self.requests)
+    }
+
+    /// Portion of the requests that were served from the cache, in the range `[0.0, 1.0]`.
+    pub fn cache_hit_rate(&self) -> f64 {
+        ratio(self.cache_hits, self.requests)
+    }
+
+    /// Average duration of a network request.
+    pub fn average_latency(&self) -> Duration {
+        match self.network_requests() {
+            0 => Duration::ZERO,
+            count => Duration::from_nanos((self.total_latency.as_nanos() / count as u128) as u64),
+        }
+    }
+}
+
+fn ratio(value: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        value as f64 / total as f64
+    }
+}
+
+impl Add for TileSourceStats {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self {
+            requests: self.requests + rhs.requests,
+            cache_hits: self.cache_hits + rhs.cache_hits,
+            errors: self.errors + rhs.errors,
+            bytes_downloaded: self.bytes_downloaded + rhs.bytes_downloaded,
+            total_latency: self.total_latency + rhs.total_latency,
+        }
+    }
+}
+
+impl AddAssign for TileSourceStats {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sum for TileSourceStats {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), |acc, v| acc + v)
+    }
+}
+
+/// Thread safe counters of [`TileSourceStats`], used by data providers to record their requests.
+///
+/// Clones of the collector share the same counters.
+#[derive(Debug, Default, Clone)]
+pub struct TileSourceStatsCollector {
+    inner: Arc<StatsCounters>,
+}
+
+#[derive(Debug, Default)]
+struct StatsCounters {
+    requests: AtomicU64,
+    cache_hits: AtomicU64,
+    errors: AtomicU64,
+    bytes_downloaded: AtomicU64,
+    latency_micros: AtomicU64,
+}
+
+impl TileSourceStatsCollector {
+    /// Creates a new collector with all counters set to zero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a request served from the cache.
+    pub fn record_cache_hit(&self) {
+        self.inner.requests.fetch_add(1, Ordering::Relaxed);
+        self.inner.cache_hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records a successful network request.
+    pub fn record_download(&self, bytes: usize, latency: Duration) {
+        self.inner.requests.fetch_add(1, Ordering::Relaxed);
+        self.inner
+            .bytes_downloaded
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+        self.add_latency(latency);
+    }
+
+    /// Records a failed network request.
+    pub fn record_error(&self, latency: Duration) {
+        self.inner.requests.fetch_add(1, Ordering::Relaxed);
+        self.inner.errors.fetch_add(1, Ordering::Relaxed);
+        self.add_latency(latency);
+    }
+
+    /// Returns current values of the counters.
+    pub fn snapshot(&self) -> TileSourceStats {
+        TileSourceStats {
+            requests: self.inner.requests.load(Ordering::Relaxed),
+            cache_hits: self.inner.cache_hits.load(Ordering::Relaxed),
+            errors: self.inner.errors.load(Ordering::Relaxed),
+            bytes_downloaded: self.inner.bytes_downloaded.load(Ordering::Relaxed),
+            total_latency: Duration::from_micros(self.inner.latency_micros.load(Ordering::Relaxed)),
+        }
+    }
+
+    /// Sets all the counters to zero.
+    pub fn reset(&self) {
+        self.inner.requests.store(0, Ordering::Relaxed);
+        self.inner.cache_hits.store(0, Ordering::Relaxed);
+        self.inner.errors.store(0, Ordering::Relaxed);
+        self.inner.bytes_downloaded.store(0, Ordering::Relaxed);
+        self.inner.latency_micros.store(0, Ordering::Relaxed);
+    }
+
+    fn add_latency(&self, latency: Duration) {
+        self.inner
+            .latency_micros
+            .fetch_add(latency.as_micros() as u64, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collects_stats() {
+        let collector = TileSourceStatsCollector::new();
+        collector.record_cache_hit();
+        collector.record_download(100, Duration::from_millis(30));
+        collector.record_download(50, Duration::from_millis(10));
+        collector.record_error(Duration::from_millis(20));
+
+        let stats = collector.snapshot();
+        assert_eq!(stats.requests, 4);
+        assert_eq!(stats.network_requests(), 3);
+        assert_eq!(stats.bytes_downloaded, 150);
+        assert_eq!(stats.error_rate(), 0.25);
+        assert_eq!(stats.cache_hit_rate(), 0.25);
+        assert_eq!(stats.average_latency(), Duration::from_millis(20));
+
+        collector.reset();
+        assert_eq!(collector.snapshot(), TileSourceStats::default());
+    }
+
+    #[test]
+    fn aggregates_stats() {
+        let stats = TileSourceStats {
+            requests: 2,
+            cache_hits: 1,
+            errors: 0,
+            bytes_downloaded: 10,
+            total_latency: Duration::from_millis(5),
+        };
+
+        let total: TileSourceStats = [stats, stats].into_iter().sum();
+        assert_eq!(total.requests, 4);
+        assert_eq!(total.bytes_downloaded, 20);
+        assert_eq!(total.average_latency(), Duration::from_millis(5));
+        assert_eq!(TileSourceStats::default().error_rate(), 0.0);
+    }
+}
@@ -8,8 +8,11 @@ use maybe_sync::{MaybeSend, MaybeSync};
 use crate::decoded_image::DecodedImage;
 use crate::error::GalileoError;
 use crate::layer::data_provider::dummy::DummyCacheController;
-use crate::layer::data_provider::{DataProvider, PersistentCacheController, UrlSource};
+use crate::layer::data_provider::{
+    DataProvider, PersistentCacheController, TileSourceStats, TileSourceStatsCollector, UrlSource,
+};
 use crate::platform::{PlatformService, PlatformServiceImpl};
+use crate::trace::instrument;
 
 /// Loads an image from Internet and uses `Cache` persistent cache controller to save it locally.
 #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
@@ -18,6 +21,7 @@ pub struct UrlImageProvider<Key, Cache = DummyCacheController> {
     cache: Option<Cache>,
     platform_service: PlatformServiceImpl,
     offline_mode: bool,
+    stats: TileSourceStatsCollector,
     _phantom_key: PhantomData<Key>,
 }
 
@@ -29,6 +33,7 @@ impl<Key> UrlImageProvider<Key, DummyCacheController> {
             cache: None,
             platform_service: PlatformServiceImpl::new(),
             offline_mode: false,
+            stats: TileSourceStatsCollector::new(),
             _phantom_key: Default::default(),
         }
     }
@@ -42,6 +47,7 @@ impl<Key, Cache> UrlImageProvider<Key, Cache> {
             cache: Some(cache),
             platform_service: PlatformServiceImpl::new(),
             offline_mode: false,
+            stats: TileSourceStatsCollector::new(),
             _phantom_key: Default::default(),
         }
     }
@@ -58,6 +64,11 @@ impl<Key, Cache> UrlImageProvider<Key, Cache> {
         self.offline_mode = enabled;
     }
 
+    /// Collector of the request statistics of the provider.
+    pub fn stats_collector(&self) -> &TileSourceStatsCollector {
+        &self.stats
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
     fn check_offline_mode(&self) -> Result<(), GalileoError> {
         if self.offline_mode {
@@ -79,6 +90,7 @@ where
 
         if let Some(cache) = &self.cache {
             if let Some(data) = cache.get(&url) {
+                self.stats.record_cache_hit();
                 return Ok(data);
             }
         }
@@ -86,7 +98,21 @@ where
         self.check_offline_mode()?;
 
         log::info!("Loading {url}");
-        let data = self.platform_service.load_bytes_from_url(&url).await?;
+        let start = web_time::Instant::now();
+        let data = match instrument!(
+            self.platform_service.load_bytes_from_url(&url),
+            "raster_tile_load",
+            url = %url
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(err) => {
+                self.stats.record_error(start.elapsed());
+                return Err(err);
+            }
+        };
+        self.stats.record_download(data.len(), start.elapsed());
 
         if let Some(cache) = &self.cache {
             if let Err(error) = cache.insert(&url, &data) {
@@ -100,6 +126,10 @@ where
     fn decode(&self, bytes: Bytes, _context: ()) -> Result<DecodedImage, GalileoError> {
         DecodedImage::decode(&bytes)
     }
+
+    fn stats(&self) -> Option<TileSourceStats> {
+        Some(self.stats.snapshot())
+    }
 }
 
 #[cfg(target_arch = "wasm32")]
@@ -121,6 +151,24 @@ where
 
     async fn load(&self, key: &Key, _context: ()) -> Result<DecodedImage, GalileoError> {
         let url = (self.url_source)(key);
-        self.platform_service.load_image_url(&url).await
+        let start = web_time::Instant::now();
+        let result = instrument!(
+            self.platform_service.load_image_url(&url),
+            "raster_tile_load",
+            url = %url
+        )
+        .await;
+
+        // The browser loads the image directly, so the number of downloaded bytes is unknown.
+        match &result {
+            Ok(_) => self.stats.record_download(0, start.elapsed()),
+            Err(_) => self.stats.record_error(start.elapsed()),
+        }
+
+        result
+    }
+
+    fn stats(&self) -> Option<TileSourceStats> {
+        Some(self.stats.snapshot())
     }
 }
@@ -0,0 +1,267 @@
+use std::any::Any;
+
+use galileo_types::cartesian::{Point2d, Point3d};
+use galileo_types::geo::impls::GeoPoint2d;
+use galileo_types::geo::{GeoPoint, NewGeoPoint};
+use galileo_types::impls::{ClosedContour, Contour, Polygon};
+use web_time::{SystemTime, UNIX_EPOCH};
+
+use crate::layer::Layer;
+use crate::messenger::Messenger;
+use crate::render::point_paint::PointPaint;
+use crate::render::render_bundle::RenderPrimitive;
+use crate::render::{Canvas, PolygonPaint, RenderOptions};
+use crate::view::MapView;
+use crate::Color;
+
+/// Latitude limit of the night polygon. Web Mercator cannot project the poles.
+const MAX_LAT: f64 = 85.0;
+/// Step between the vertices of the terminator line in degrees of longitude.
+const LON_STEP: f64 = 2.0;
+
+const DEFAULT_NIGHT_COLOR: Color = Color::rgba(0, 0, 32, 96);
+const DEFAULT_SUN_COLOR: Color = Color::rgba(255, 200, 0, 255);
+
+/// Overlay layer that shades the night side of the Earth and marks the sub-solar point (the point where the sun is
+/// directly overhead) for the given time.
+///
+/// The layer does not follow the clock by itself. Applications showing the current state (e.g. flight tracking or
+/// operations dashboards) should call [`DayNightLayer::set_time`] periodically, and applications with a time slider
+/// should call it when the selected time changes.
+///
+/// The sun position is computed with an approximation that is accurate to about a degree, which is more than enough
+/// for visualization.
+///
+/// ```no_run
+/// use galileo::layer::DayNightLayer;
+/// use web_time::SystemTime;
+///
+/// let mut layer = DayNightLayer::new(SystemTime::now());
+///
+/// // later, e.g. once a minute
+/// layer.set_time(SystemTime::now());
+/// ```
+pub struct DayNightLayer {
+    time: SystemTime,
+    night_color: Color,
+    sun_paint: Option<PointPaint<'static>>,
+    messenger: Option<Box<dyn Messenger>>,
+}
+
+impl DayNightLayer {
+    /// Creates a new layer showing the day and night sides for the given time.
+    pub fn new(time: SystemTime) -> Self {
+        Self {
+            time,
+            night_color: DEFAULT_NIGHT_COLOR,
+            sun_paint: Some(PointPaint::circle(DEFAULT_SUN_COLOR, 12.0)),
+            messenger: None,
+        }
+    }
+
+    /// Sets the color of the night side polygon. It should be translucent for the map to be visible under it.
+    pub fn with_night_color(mut self, color: Color) -> Self {
+        self.night_color = color;
+        self
+    }
+
+    /// Sets the paint of the sub-solar point marker. If `None`, the marker is not drawn.
+    pub fn with_sun_paint(mut self, paint: Option<PointPaint<'static>>) -> Self {
+        self.sun_paint = paint;
+        self
+    }
+
+    /// Time for which the day and night sides are shown.
+    pub fn time(&self) -> SystemTime {
+        self.time
+    }
+
+    /// Changes the time for which the day and night sides are shown, and requests the map to be redrawn.
+    pub fn set_time(&mut self, time: SystemTime) {
+        self.time = time;
+        if let Some(messenger) = &self.messenger {
+            messenger.request_redraw();
+        }
+    }
+
+    /// Point on the Earth surface where the sun is in zenith at the current time of the layer.
+    pub fn subsolar_point(&self) -> GeoPoint2d {
+        subsolar_point(self.time)
+    }
+
+    /// Points of the polygon covering the night side of the Earth at the current time of the layer.
+    ///
+    /// The polygon is limited by the latitudes of `±85` degrees, so it can be projected into Web Mercator.
+    pub fn night_polygon(&self) -> Vec<GeoPoint2d> {
+        night_polygon(&self.subsolar_point())
+    }
+}
+
+/// Computes the sub-solar point for the given time.
+fn subsolar_point(time: SystemTime) -> GeoPoint2d {
+    let unix_seconds = match time.duration_since(UNIX_EPOCH) {
+        Ok(duration) => duration.as_secs_f64(),
+        Err(err) => -err.duration().as_secs_f64(),
+    };
+
+    // Days since J2000.0 epoch
+    let days = unix_seconds / 86400.0 + 2440587.5 - 2451545.0;
+
+    let mean_anomaly = (357.529 + 0.98560028 * days).to_radians();
+    let mean_longitude = 280.459 + 0.98564736 * days;
+    let ecliptic_longitude =
+        (mean_longitude + 1.915 * mean_anomaly.sin() + 0.020 * (2.0 * mean_anomaly).sin())
+            .to_radians();
+    let obliquity = (23.439 - 0.00000036 * days).to_radians();
+
+    let declination = (obliquity.sin() * ecliptic_longitude.sin()).asin();
+    let right_ascension =
+        (obliquity.cos() * ecliptic_longitude.sin()).atan2(ecliptic_longitude.cos());
+
+    let sidereal_time_hours = 18.697374558 + 24.06570982441908 * days;
+    let lon = right_ascension.to_degrees() - sidereal_time_hours * 15.0;
+
+    GeoPoint2d::latlon(declination.to_degrees(), normalize_lon(lon))
+}
+
+fn normalize_lon(lon: f64) -> f64 {
+    (lon + 180.0).rem_euclid(360.0) - 180.0
+}
+
+/// Builds the polygon of the night side from the terminator line and the pole that is in darkness.
+fn night_polygon(subsolar_point: &GeoPoint2d) -> Vec<GeoPoint2d> {
+    // At equinoxes the terminator goes through the poles, avoid division by zero
+    let declination = subsolar_point.lat().to_radians();
+    let tan_declination = if declination.abs() < 1e-6 {
+        1e-6f64.copysign(declination)
+    } else {
+        declination.tan()
+    };
+
+    let steps = (360.0 / LON_STEP) as usize;
+    let mut points: Vec<GeoPoint2d> = (0..=steps)
+        .map(|step| {
+            let lon = -180.0 + step as f64 * LON_STEP;
+            let hour_angle = (lon - subsolar_point.lon()).to_radians();
+            let lat = (-hour_angle.cos() / tan_declination).atan().to_degrees();
+            GeoPoint2d::latlon(lat.clamp(-MAX_LAT, MAX_LAT), lon)
+        })
+        .collect();
+
+    let dark_pole = if tan_declination > 0.0 {
+        -MAX_LAT
+    } else {
+        MAX_LAT
+    };
+    points.push(GeoPoint2d::latlon(dark_pole, 180.0));
+    points.push(GeoPoint2d::latlon(dark_pole, -180.0));
+
+    points
+}
+
+impl Layer for DayNightLayer {
+    fn render(&self, view: &MapView, canvas: &mut dyn Canvas) {
+        let Some(projection) = view.crs().get_projection::<GeoPoint2d, Point2d>() else {
+            log::warn!("Cannot render day/night layer: the map CRS cannot be projected");
+            return;
+        };
+
+        let project = |point: &GeoPoint2d| {
+            projection
+                .project(point)
+                .map(|point: Point2d| Point3d::new(point.x, point.y, 0.0))
+        };
+
+        let mut bundle = canvas.create_bundle();
+        let resolution = view.resolution();
+
+        let night: Option<Vec<Point3d>> = self.night_polygon().iter().map(project).collect();
+        if let Some(night) = night {
+            bundle.add(
+                RenderPrimitive::<f64, Point3d, Contour<Point3d>, Polygon<Point3d>>::new_polygon(
+                    ClosedContour::new(night).into(),
+                    PolygonPaint {
+                        color: self.night_color,
+                        hatching: None,
+                    },
+                ),
+                resolution,
+            );
+        }
+
+        if let Some(paint) = &self.sun_paint {
+            if let Some(position) = project(&self.subsolar_point()) {
+                bundle.add(
+                    RenderPrimitive::<f64, Point3d, Contour<Point3d>, Polygon<Point3d>>::new_point(
+                        position,
+                        paint.clone(),
+                    ),
+                    resolution,
+                );
+            }
+        }
+
+        let packed = canvas.pack_bundle(&bundle);
+        canvas.draw_bundles(&[&*packed], RenderOptions::default());
+    }
+
+    fn prepare(&self, _view: &MapView) {
+        // do nothing
+    }
+
+    fn set_messenger(&mut self, messenger: Box<dyn Messenger>) {
+        self.messenger = Some(messenger);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn time(unix_seconds: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(unix_seconds)
+    }
+
+    #[test]
+    fn subsolar_point_at_solstice_and_equinox() {
+        // 2024-06-20T12:00:00Z
+        let point = subsolar_point(time(1718884800));
+        assert!((point.lat() - 23.43).abs() < 0.2, "{point:?}");
+        assert!(point.lon().abs() < 2.0, "{point:?}");
+
+        // 2024-03-20T00:00:00Z, sun is above the Pacific
+        let point = subsolar_point(time(1710892800));
+        assert!(point.lat().abs() < 0.5, "{point:?}");
+        assert!((point.lon().abs() - 178.0).abs() < 3.0, "{point:?}");
+    }
+
+    #[test]
+    fn night_polygon_covers_the_dark_pole() {
+        let polygon = night_polygon(&GeoPoint2d::latlon(23.0, 0.0));
+        let terminator = &polygon[..polygon.len() - 2];
+
+        // Terminator is the farthest north on the opposite side of the Earth from the sun
+        let at_antimeridian = terminator[0].lat();
+        let at_meridian = terminator[terminator.len() / 2].lat();
+        assert!((at_antimeridian - 67.0).abs() < 0.01);
+        assert!((at_meridian + 67.0).abs() < 0.01);
+
+        assert_eq!(polygon[polygon.len() - 1].lat(), -MAX_LAT);
+        assert_eq!(
+            night_polygon(&GeoPoint2d::latlon(-10.0, 0.0))
+                .last()
+                .map(|point| point.lat()),
+            Some(MAX_LAT)
+        );
+    }
+}
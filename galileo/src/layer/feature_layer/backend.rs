@@ -0,0 +1,157 @@
+//! External storage of the features of a [`FeatureLayer`](super::FeatureLayer).
+
+use galileo_types::cartesian::Rect;
+use maybe_sync::{MaybeSend, MaybeSync};
+
+use crate::error::GalileoError;
+
+/// Identifier of a feature in a [`FeatureBackend`].
+pub type FeatureId = u64;
+
+/// External storage of features, e.g. a database table with a spatial index or a large indexed file, from which a
+/// [`FeatureLayer`](super::FeatureLayer) loads only the features of the area it needs.
+///
+/// A layer created with [`FeatureLayer::with_backend`](super::FeatureLayer::with_backend) does not keep all the
+/// features in memory. Instead, [`FeatureLayer::load_area`](super::FeatureLayer::load_area) finds the features of an
+/// area with a single [bounding box query](FeatureBackend::query_bbox), [loads](FeatureBackend::load) the ones that
+/// are not in the layer yet, and drops the loaded features outside of the area. So the geometries of the features are
+/// only read when the area they are in is requested.
+///
+/// All rectangles are set in the CRS of the layer. For layers with geographic coordinates, `x` is longitude and `y`
+/// is latitude.
+pub trait FeatureBackend<F>: MaybeSend + MaybeSync {
+    /// Bounding rectangle of all the features of the backend. Returns `None` if the backend is empty or the extent
+    /// is unknown.
+    fn extent(&self) -> Option<Rect>;
+
+    /// Returns identifiers of all features, which bounding rectangles intersect the `bbox`.
+    ///
+    /// This method is called every time a new area is loaded, so it should use the index of the storage and not read
+    /// the geometries of the features.
+    fn query_bbox(&self, bbox: Rect) -> Result<Vec<FeatureId>, GalileoError>;
+
+    /// Loads the features with the given identifiers. Identifiers that are not found in the storage are skipped.
+    fn load(&self, ids: &[FeatureId]) -> Result<Vec<(FeatureId, F)>, GalileoError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use approx::{assert_abs_diff_eq, assert_relative_eq};
+    use galileo_types::cartesian::Point2d;
+    use galileo_types::geo::impls::GeoPoint2d;
+    use galileo_types::geo::{Crs, GeoPoint, NewGeoPoint};
+    use galileo_types::geometry_type::GeoSpace2d;
+    use parking_lot::Mutex;
+
+    use super::*;
+    use crate::layer::feature_layer::symbol::CirclePointSymbol;
+    use crate::layer::{FeatureLayer, Layer};
+    use crate::Color;
+
+    type TestLayer = FeatureLayer<GeoPoint2d, GeoPoint2d, CirclePointSymbol, GeoSpace2d>;
+
+    /// Points along the equator every 10 degrees, with the log of the loaded identifiers.
+    struct TestBackend {
+        points: Vec<GeoPoint2d>,
+        loaded: Arc<Mutex<Vec<FeatureId>>>,
+    }
+
+    impl FeatureBackend<GeoPoint2d> for TestBackend {
+        fn extent(&self) -> Option<Rect> {
+            let points: Vec<_> = self
+                .points
+                .iter()
+                .map(|p| Point2d::new(p.lon(), p.lat()))
+                .collect();
+            Rect::from_points(points.iter())
+        }
+
+        fn query_bbox(&self, bbox: Rect) -> Result<Vec<FeatureId>, GalileoError> {
+            Ok(self
+                .points
+                .iter()
+                .enumerate()
+                .filter(|(_, p)| bbox.contains(&Point2d::new(p.lon(), p.lat())))
+                .map(|(id, _)| id as FeatureId)
+                .collect())
+        }
+
+        fn load(&self, ids: &[FeatureId]) -> Result<Vec<(FeatureId, GeoPoint2d)>, GalileoError> {
+            self.loaded.lock().extend_from_slice(ids);
+            Ok(ids
+                .iter()
+                .filter_map(|&id| Some((id, *self.points.get(id as usize)?)))
+                .collect())
+        }
+    }
+
+    fn test_layer() -> (TestLayer, Arc<Mutex<Vec<FeatureId>>>) {
+        let loaded = Arc::new(Mutex::new(vec![]));
+        let backend = TestBackend {
+            points: (0..10)
+                .map(|i| GeoPoint2d::latlon(0.0, i as f64 * 10.0))
+                .collect(),
+            loaded: loaded.clone(),
+        };
+        let layer = FeatureLayer::with_backend(
+            backend,
+            CirclePointSymbol::new(Color::BLACK, 1.0),
+            Crs::WGS84,
+        );
+
+        (layer, loaded)
+    }
+
+    fn source_ids(layer: &TestLayer) -> Vec<Option<FeatureId>> {
+        let features = layer.features();
+        features
+            .iter()
+            .map(|f| features.source_id(f.index()))
+            .collect()
+    }
+
+    #[test]
+    fn load_area() {
+        let (mut layer, loaded) = test_layer();
+        assert!(source_ids(&layer).is_empty());
+
+        layer
+            .load_area(Rect::new(-5.0, -5.0, 25.0, 5.0))
+            .expect("failed to load");
+        assert_eq!(source_ids(&layer), vec![Some(0), Some(1), Some(2)]);
+
+        // Features added directly are kept
+        layer.features_mut().insert(GeoPoint2d::latlon(50.0, 50.0));
+
+        // Only the new features are loaded, and the features outside of the area are dropped
+        layer
+            .load_area(Rect::new(15.0, -5.0, 45.0, 5.0))
+            .expect("failed to load");
+        assert_eq!(source_ids(&layer), vec![Some(2), None, Some(3), Some(4)]);
+        assert_eq!(*loaded.lock(), vec![0, 1, 2, 3, 4]);
+        assert_eq!(layer.loaded_area(), Some(Rect::new(15.0, -5.0, 45.0, 5.0)));
+    }
+
+    #[test]
+    fn extent_includes_features_not_loaded() {
+        let (layer, loaded) = test_layer();
+        let extent = layer.extent(&Crs::EPSG3857).expect("no extent");
+        assert_abs_diff_eq!(extent.x_min(), 0.0);
+        assert_abs_diff_eq!(extent.y_min(), 0.0, epsilon = 1e-6);
+        assert_relative_eq!(extent.x_max(), 10_018_754.171394622, max_relative = 1e-9);
+        assert_abs_diff_eq!(extent.y_max(), 0.0, epsilon = 1e-6);
+        assert!(loaded.lock().is_empty());
+    }
+
+    #[test]
+    fn load_without_backend() {
+        let mut layer = TestLayer::new(
+            vec![],
+            CirclePointSymbol::new(Color::BLACK, 1.0),
+            Crs::WGS84,
+        );
+        assert!(layer.load_area(Rect::new(0.0, 0.0, 1.0, 1.0)).is_err());
+    }
+}
@@ -0,0 +1,195 @@
+//! Limiting the number of features drawn in an area of the screen.
+
+use std::collections::{HashMap, HashSet};
+
+use galileo_types::cartesian::{Point3d, Rect};
+use galileo_types::geometry::Geom;
+use galileo_types::{Contour, MultiContour, MultiPoint, MultiPolygon, Polygon};
+use maybe_sync::{MaybeSend, MaybeSync};
+
+/// Limit on the number of features of a [`FeatureLayer`](super::FeatureLayer) drawn in an area of the screen.
+///
+/// Dense point layers (e.g. labels or markers of points of interest) become unreadable when the map is zoomed out.
+/// With the density limit, the screen is split into square cells, and at most
+/// [`max_per_cell`](DensityLimit::max_per_cell) features with the highest priority are drawn in each cell. The
+/// features are assigned to the cells by the centers of their bounding rectangles.
+///
+/// The limit is applied to the [levels of detail](super::FeatureLayer::with_lods) with the resolution not less than
+/// [`min_resolution`](DensityLimit::min_resolution), using the resolution of the level to calculate the size of the
+/// cells. So the layer must be created with levels of detail for the limit to depend on the zoom level:
+///
+/// ```no_run
+/// use galileo::layer::feature_layer::{DensityLimit, Feature, Properties, Symbol};
+/// use galileo::layer::FeatureLayer;
+/// use galileo_types::geo::{Crs, NewGeoPoint};
+/// use galileo_types::geometry::Geometry;
+/// use galileo_types::geometry_type::GeoSpace2d;
+///
+/// fn create_layer<P, F, S>(features: Vec<F>, symbol: S) -> FeatureLayer<P, F, S, GeoSpace2d>
+/// where
+///     P: NewGeoPoint,
+///     F: Feature + Properties,
+///     F::Geom: Geometry<Point = P>,
+///     S: Symbol<F>,
+/// {
+///     // Levels of detail for the zoom levels of the Web Mercator tile schema
+///     let lods: Vec<f64> = (0..18).map(|z| 156543.03392800014 / 2f64.powi(z)).collect();
+///
+///     // At most 3 features per 100x100 pixels at zoom levels below 10, prioritized by the `rank` property
+///     FeatureLayer::with_lods(features, symbol, Crs::WGS84, &lods).with_density_limit(
+///         DensityLimit {
+///             cell_size: 100.0,
+///             max_per_cell: 3,
+///             min_resolution: lods[9],
+///         },
+///         |feature: &F| {
+///             feature
+///                 .property("rank")
+///                 .and_then(|value| value.as_f64())
+///                 .unwrap_or_default()
+///         },
+///     )
+/// }
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct DensityLimit {
+    /// Size of the side of a cell in pixels.
+    pub cell_size: f64,
+    /// Maximum number of features drawn in a cell.
+    pub max_per_cell: usize,
+    /// Minimum resolution of the levels of detail the limit is applied to.
+    pub min_resolution: f64,
+}
+
+impl DensityLimit {
+    /// Returns true if the limit applies to the level of detail with the given minimum resolution.
+    pub(super) fn applies_to(&self, lod_resolution: f64) -> bool {
+        lod_resolution >= self.min_resolution
+    }
+
+    /// Selects the features to draw at the given resolution. Candidates are given as the feature index, the anchor
+    /// point of the feature and its priority. Features with higher priority are selected first, and of the features
+    /// with the same priority, the ones given first.
+    pub(super) fn select(
+        &self,
+        candidates: impl IntoIterator<Item = (usize, [f64; 2], f64)>,
+        resolution: f64,
+    ) -> HashSet<usize> {
+        let cell_size = self.cell_size * resolution;
+
+        let mut candidates: Vec<_> = candidates.into_iter().collect();
+        candidates.sort_by(|(_, _, a), (_, _, b)| b.total_cmp(a));
+
+        let mut counts: HashMap<(i64, i64), usize> = HashMap::new();
+        candidates
+            .into_iter()
+            .filter(|(_, [x, y], _)| {
+                let cell = (
+                    (x / cell_size).floor() as i64,
+                    (y / cell_size).floor() as i64,
+                );
+                let count = counts.entry(cell).or_default();
+                *count += 1;
+                *count <= self.max_per_cell
+            })
+            .map(|(index, ..)| index)
+            .collect()
+    }
+}
+
+/// Priority of a feature for a [`DensityLimit`]. Features with higher priority are drawn first.
+///
+/// The trait is implemented for closures `Fn(&F) -> f64`.
+pub trait FeaturePriority<F>: MaybeSend + MaybeSync {
+    /// Returns the priority of the feature.
+    fn priority(&self, feature: &F) -> f64;
+}
+
+impl<F, T> FeaturePriority<F> for T
+where
+    T: Fn(&F) -> f64 + MaybeSend + MaybeSync,
+{
+    fn priority(&self, feature: &F) -> f64 {
+        self(feature)
+    }
+}
+
+/// Center of the bounding rectangle of the projected geometry.
+pub(super) fn anchor_point(geometry: &Geom<Point3d>) -> Option<[f64; 2]> {
+    let rect = match geometry {
+        Geom::Point(point) => return Some([point.x, point.y]),
+        Geom::MultiPoint(points) => points_rect(points.iter_points()),
+        Geom::Contour(contour) => points_rect(contour.iter_points()),
+        Geom::MultiContour(contours) => points_rect(
+            contours
+                .contours()
+                .flat_map(|contour| contour.iter_points()),
+        ),
+        Geom::Polygon(polygon) => points_rect(polygon.outer_contour().iter_points()),
+        Geom::MultiPolygon(polygons) => points_rect(
+            polygons
+                .polygons()
+                .flat_map(|polygon| polygon.outer_contour().iter_points()),
+        ),
+    }?;
+
+    Some([
+        (rect.x_min() + rect.x_max()) / 2.0,
+        (rect.y_min() + rect.y_max()) / 2.0,
+    ])
+}
+
+fn points_rect<'a>(points: impl Iterator<Item = &'a Point3d>) -> Option<Rect> {
+    points.fold(None, |rect: Option<Rect>, point| {
+        let point_rect = Rect::new(point.x, point.y, point.x, point.y);
+        Some(match rect {
+            Some(rect) => rect.merge(point_rect),
+            None => point_rect,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn select_by_priority() {
+        let limit = DensityLimit {
+            cell_size: 100.0,
+            max_per_cell: 2,
+            min_resolution: 10.0,
+        };
+
+        // With resolution 10 the cells are 1000 map units wide
+        let candidates = [
+            (0, [100.0, 100.0], 1.0),
+            (1, [200.0, 200.0], 3.0),
+            (2, [300.0, 300.0], 2.0),
+            (3, [1100.0, 100.0], 0.0),
+            (4, [-100.0, 100.0], 0.0),
+        ];
+        let selected = limit.select(candidates, 10.0);
+        assert_eq!(selected, HashSet::from([1, 2, 3, 4]));
+
+        // With resolution 1 all features are in different cells
+        let selected = limit.select(candidates, 1.0);
+        assert_eq!(selected.len(), 5);
+
+        assert!(limit.applies_to(20.0));
+        assert!(!limit.applies_to(5.0));
+    }
+
+    #[test]
+    fn anchor_of_contour() {
+        let contour = galileo_types::impls::Contour::open(vec![
+            Point3d::new(0.0, 0.0, 0.0),
+            Point3d::new(10.0, 4.0, 0.0),
+        ]);
+        assert_eq!(anchor_point(&Geom::Contour(contour)), Some([5.0, 2.0]));
+        assert_eq!(
+            anchor_point(&Geom::Point(Point3d::new(1.0, 2.0, 3.0))),
+            Some([1.0, 2.0])
+        );
+    }
+}
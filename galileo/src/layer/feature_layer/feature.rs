@@ -52,5 +52,17 @@ where
     }
 }
 
+#[cfg(feature = "geo-types")]
+impl<T> Feature for geo_types::Geometry<T>
+where
+    T: geo_types::CoordNum + num_traits::Bounded + nalgebra::Scalar + num_traits::FromPrimitive,
+{
+    type Geom = Self;
+
+    fn geometry(&self) -> &Self::Geom {
+        self
+    }
+}
+
 #[cfg(feature = "geojson")]
 mod geojson;
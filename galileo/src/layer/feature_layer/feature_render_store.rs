@@ -1,9 +1,9 @@
 use std::collections::{HashMap, HashSet};
 
-use galileo_types::cartesian::Point3d;
+use galileo_types::cartesian::{Point3d, Rect};
 use galileo_types::impls::{Contour, Polygon};
 
-use crate::render::render_bundle::{RenderBundle, RenderPrimitive};
+use crate::render::render_bundle::{BundleMemoryUsage, RenderBundle, RenderPrimitive};
 use crate::render::{Canvas, PackedBundle, PrimitiveId};
 
 pub(super) struct FeatureRenderStore {
@@ -14,7 +14,11 @@ pub(super) struct FeatureRenderStore {
     feature_render_map: HashMap<usize, RenderMapEntry>,
     buffer_size_limit: usize,
     bundle_indices_to_pack: HashSet<usize>,
+    /// Bundles with moved primitives, which packed bundles can be updated instead of packing them again.
+    bundle_indices_to_update: HashSet<usize>,
     next_index: usize,
+    /// Area in which the features are rendered when view culling is enabled. `None` if the store was not used yet.
+    render_area: Option<Rect>,
 }
 
 struct RenderMapEntry {
@@ -32,7 +36,9 @@ impl FeatureRenderStore {
             packed_bundles: vec![],
             feature_render_map: HashMap::new(),
             bundle_indices_to_pack: HashSet::new(),
+            bundle_indices_to_update: HashSet::new(),
             next_index: 0,
+            render_area: None,
         }
     }
 
@@ -44,6 +50,14 @@ impl FeatureRenderStore {
         self.min_resolution
     }
 
+    pub fn render_area(&self) -> Option<Rect> {
+        self.render_area
+    }
+
+    pub fn set_render_area(&mut self, area: Option<Rect>) {
+        self.render_area = area;
+    }
+
     pub fn set_buffer_size_limit(&mut self, limit: usize) {
         self.buffer_size_limit = limit;
     }
@@ -113,34 +127,112 @@ impl FeatureRenderStore {
         self.render_bundles.len() - 1
     }
 
+    /// Updates the primitives of the render in place. Returns false if the render cannot be updated this way, e.g. if
+    /// the shape of a point has changed, in which case it must be rendered again.
     pub fn update_renders(
         &mut self,
         render_index: usize,
         primitives: Vec<RenderPrimitive<f64, Point3d, Contour<Point3d>, Polygon<Point3d>>>,
-    ) {
-        let RenderMapEntry {
+    ) -> bool {
+        let Some(RenderMapEntry {
             bundle_index,
             primitive_ids,
-        } = &self.feature_render_map[&render_index];
+        }) = self.feature_render_map.get(&render_index)
+        else {
+            return false;
+        };
+
         if primitive_ids.len() != primitives.len() {
-            log::error!("Cannot update feature style. The number of primitives is not equal to what it was.")
+            return false;
         }
 
+        let mut is_updated = true;
         for (id, primitive) in primitive_ids.iter().zip(primitives.into_iter()) {
             if let Err(err) = self.render_bundles[*bundle_index].update(*id, primitive) {
-                log::warn!("Failed to update feature style: {err:?}");
+                log::debug!("Failed to update feature style in place: {err:?}");
+                is_updated = false;
             }
         }
 
         self.bundle_indices_to_pack.insert(*bundle_index);
+        is_updated
+    }
+
+    /// Moves the point primitives of the render to the anchors of the given primitives and applies their transforms
+    /// without tessellating them again. Returns false if the render cannot be moved this way, e.g. if it contains
+    /// primitives other than points, in which case it must be rendered again.
+    pub fn move_renders(
+        &mut self,
+        render_index: usize,
+        primitives: Vec<RenderPrimitive<f64, Point3d, Contour<Point3d>, Polygon<Point3d>>>,
+    ) -> bool {
+        let Some(RenderMapEntry {
+            bundle_index,
+            primitive_ids,
+        }) = self.feature_render_map.get(&render_index)
+        else {
+            return false;
+        };
+
+        if primitive_ids.len() != primitives.len() {
+            return false;
+        }
+
+        let bundle = &mut self.render_bundles[*bundle_index];
+        for (id, primitive) in primitive_ids.iter().zip(primitives) {
+            let RenderPrimitive::Point(anchor, _) = &primitive else {
+                return false;
+            };
+
+            let anchor = **anchor;
+            if let Err(err) = bundle
+                .move_point(*id, &anchor)
+                .and_then(|_| bundle.update(*id, primitive))
+            {
+                log::debug!("Feature render cannot be moved: {err:?}");
+                return false;
+            }
+        }
+
+        self.bundle_indices_to_update.insert(*bundle_index);
+        true
     }
 
     pub fn pack(&mut self, canvas: &dyn Canvas) {
+        for index in self.bundle_indices_to_update.drain() {
+            if self.bundle_indices_to_pack.contains(&index) {
+                continue;
+            }
+
+            let is_updated = self.packed_bundles[index].as_ref().is_some_and(|packed| {
+                canvas.update_packed_bundle(&**packed, &self.render_bundles[index])
+            });
+            if !is_updated {
+                self.bundle_indices_to_pack.insert(index);
+            }
+        }
+
         for index in self.bundle_indices_to_pack.drain() {
             self.packed_bundles[index] = Some(canvas.pack_bundle(&self.render_bundles[index]));
         }
     }
 
+    /// Drops the packed bundles, so that all bundles are packed again by the next [`FeatureRenderStore::pack`] call.
+    pub fn clear_packed(&mut self) {
+        self.bundle_indices_to_update.clear();
+        for (index, packed) in self.packed_bundles.iter_mut().enumerate() {
+            *packed = None;
+            self.bundle_indices_to_pack.insert(index);
+        }
+    }
+
+    pub fn memory_usage(&self) -> BundleMemoryUsage {
+        self.render_bundles
+            .iter()
+            .map(|bundle| bundle.memory_usage())
+            .sum()
+    }
+
     pub fn bundles(&self) -> Vec<&dyn PackedBundle> {
         self.packed_bundles
             .iter()
@@ -1,6 +1,15 @@
+use std::collections::HashSet;
 use std::sync::Arc;
 
+use galileo_types::cartesian::{Point2d, Rect};
+use galileo_types::geo::Crs;
 use parking_lot::Mutex;
+use rstar::primitives::{GeomWithData, Rectangle};
+use rstar::{RTree, AABB};
+
+use super::backend::FeatureId;
+use super::hit_region::HitRegion;
+use crate::view::MapView;
 
 /// Feature storage of a [FeatureLayer](super::FeatureLayer).
 ///
@@ -13,6 +22,7 @@ use parking_lot::Mutex;
 pub struct FeatureStore<F> {
     features: Vec<FeatureEntry<F>>,
     pending_updates: Arc<Mutex<Vec<FeatureUpdate>>>,
+    extent: Arc<Mutex<ExtentCache>>,
 }
 
 /// Immutable container for a feature in a [FeatureLayer](super::FeatureLayer).
@@ -43,7 +53,9 @@ pub struct FeatureContainerMut<'a, F> {
     entry: &'a mut FeatureEntry<F>,
     feature_index: usize,
     is_updated: bool,
+    is_geometry_updated: bool,
     pending_updates: Arc<Mutex<Vec<FeatureUpdate>>>,
+    extent: Arc<Mutex<ExtentCache>>,
 }
 
 impl<'a, F> FeatureContainerMut<'a, F> {
@@ -73,6 +85,33 @@ impl<'a, F> FeatureContainerMut<'a, F> {
         &mut self.entry.feature
     }
 
+    /// Notifies the layer that after the feature is modified, only the position of its point geometry and the
+    /// transform of its point symbols (e.g. rotation) will change, like for a moving vehicle marker.
+    ///
+    /// The symbols of such feature are moved to the new position without tessellating them again, which is much
+    /// cheaper than the full update made after [container.as_mut()](AsMut::as_mut). Features that are not drawn with
+    /// point symbols are fully updated.
+    pub fn edit_position(mut self) -> &'a mut F {
+        self.geometry_changed();
+
+        if !self.is_updated {
+            self.pending_updates.lock().push(FeatureUpdate::Move {
+                feature_index: self.feature_index,
+            });
+        }
+
+        &mut self.entry.feature
+    }
+
+    fn geometry_changed(&mut self) {
+        if !self.is_geometry_updated {
+            self.extent
+                .lock()
+                .feature_changed(self.feature_index, self.entry.extent());
+            self.is_geometry_updated = true;
+        }
+    }
+
     /// Hides the feature from the map, but leaves it in the features list.
     pub fn hide(&mut self) {
         if self.is_hidden() {
@@ -120,6 +159,8 @@ impl<F> AsRef<F> for FeatureContainerMut<'_, F> {
 
 impl<F> AsMut<F> for FeatureContainerMut<'_, F> {
     fn as_mut(&mut self) -> &mut F {
+        self.geometry_changed();
+
         if !self.is_updated {
             self.pending_updates.lock().push(FeatureUpdate::Update {
                 feature_index: self.feature_index,
@@ -135,6 +176,7 @@ impl<F> AsMut<F> for FeatureContainerMut<'_, F> {
 pub(super) enum FeatureUpdate {
     Update { feature_index: usize },
     UpdateStyle { feature_index: usize },
+    Move { feature_index: usize },
     Delete { render_indices: Vec<Option<usize>> },
 }
 
@@ -150,6 +192,7 @@ impl<F> FeatureStore<F> {
                     .map(|feature_index| FeatureUpdate::Update { feature_index })
                     .collect(),
             )),
+            extent: Default::default(),
         }
     }
 
@@ -157,6 +200,7 @@ impl<F> FeatureStore<F> {
     pub fn insert(&mut self, feature: F) {
         let feature_index = self.features.len();
         self.features.push(FeatureEntry::new(feature));
+        self.extent.lock().feature_added(feature_index);
         self.pending_updates
             .lock()
             .push(FeatureUpdate::Update { feature_index })
@@ -164,7 +208,9 @@ impl<F> FeatureStore<F> {
 
     /// Adds a new hidden feature to the store at the end of the list.
     pub fn insert_hidden(&mut self, feature: F) {
+        let feature_index = self.features.len();
         self.features.push(FeatureEntry::hidden(feature));
+        self.extent.lock().feature_added(feature_index);
     }
 
     /// Returns a reference to the feature. Returns `None` if a feature with the given `index` does not exist.
@@ -178,7 +224,9 @@ impl<F> FeatureStore<F> {
             entry: f,
             feature_index: index,
             is_updated: false,
+            is_geometry_updated: false,
             pending_updates: self.pending_updates.clone(),
+            extent: self.extent.clone(),
         })
     }
 
@@ -192,7 +240,13 @@ impl<F> FeatureStore<F> {
             feature,
             is_hidden: _is_hidden,
             render_indices,
+            hit_regions: _hit_regions,
+            extent,
+            source_id: _source_id,
         } = self.features.remove(index);
+        self.extent
+            .lock()
+            .feature_removed(index, extent.into_inner());
         self.pending_updates.lock().push(FeatureUpdate::Delete {
             render_indices: render_indices.into_inner(),
         });
@@ -200,13 +254,116 @@ impl<F> FeatureStore<F> {
         feature
     }
 
+    /// Returns the identifier of the feature in the [backend](super::FeatureBackend) of the layer, if the feature was
+    /// loaded from it. Returns `None` if a feature with the given `index` does not exist or was added to the store
+    /// directly.
+    pub fn source_id(&self, index: usize) -> Option<FeatureId> {
+        self.features.get(index).and_then(|f| f.source_id)
+    }
+
+    /// Adds a feature loaded from the backend of the layer to the end of the list.
+    pub(super) fn insert_loaded(&mut self, source_id: FeatureId, feature: F) {
+        let feature_index = self.features.len();
+        let mut entry = FeatureEntry::new(feature);
+        entry.source_id = Some(source_id);
+        self.features.push(entry);
+        self.extent.lock().feature_added(feature_index);
+        self.pending_updates
+            .lock()
+            .push(FeatureUpdate::Update { feature_index })
+    }
+
+    /// Returns the backend identifiers of all features loaded from the backend of the layer.
+    pub(super) fn source_ids(&self) -> HashSet<FeatureId> {
+        self.features.iter().filter_map(|f| f.source_id).collect()
+    }
+
+    /// Removes all features for which `predicate` returns true. Returns the number of removed features.
+    ///
+    /// Unlike calling [`FeatureStore::remove`] for every feature, the indices of the following features and the
+    /// extent cache are updated only once.
+    pub(super) fn remove_where(&mut self, predicate: impl Fn(&FeatureEntry<F>) -> bool) -> usize {
+        let mut new_indices = Vec::with_capacity(self.features.len());
+        let mut kept = 0;
+        for entry in &self.features {
+            if predicate(entry) {
+                new_indices.push(None);
+            } else {
+                new_indices.push(Some(kept));
+                kept += 1;
+            }
+        }
+
+        let removed = self.features.len() - kept;
+        if removed == 0 {
+            return 0;
+        }
+
+        let mut updates = self.pending_updates.lock();
+        let mut remapped: Vec<_> = std::mem::take(&mut *updates)
+            .into_iter()
+            .filter_map(|update| {
+                let remap =
+                    |feature_index: usize| new_indices.get(feature_index).copied().flatten();
+                Some(match update {
+                    FeatureUpdate::Update { feature_index } => FeatureUpdate::Update {
+                        feature_index: remap(feature_index)?,
+                    },
+                    FeatureUpdate::UpdateStyle { feature_index } => FeatureUpdate::UpdateStyle {
+                        feature_index: remap(feature_index)?,
+                    },
+                    FeatureUpdate::Move { feature_index } => FeatureUpdate::Move {
+                        feature_index: remap(feature_index)?,
+                    },
+                    delete @ FeatureUpdate::Delete { .. } => delete,
+                })
+            })
+            .collect();
+
+        let features = std::mem::take(&mut self.features);
+        for (entry, new_index) in features.into_iter().zip(&new_indices) {
+            if new_index.is_some() {
+                self.features.push(entry);
+            } else {
+                remapped.push(FeatureUpdate::Delete {
+                    render_indices: entry.render_indices.into_inner(),
+                });
+            }
+        }
+        *updates = remapped;
+
+        // Extents of the remaining features are recalculated on the next request
+        *self.extent.lock() = ExtentCache::default();
+
+        removed
+    }
+
     pub(super) fn get_entry(&self, index: usize) -> Option<&FeatureEntry<F>> {
         self.features.get(index)
     }
 
+    pub(super) fn get_with_entry(
+        &self,
+        index: usize,
+    ) -> Option<(FeatureContainer<'_, F>, &FeatureEntry<F>)> {
+        self.features.get(index).map(|f| {
+            (
+                FeatureContainer {
+                    feature: &f.feature,
+                    feature_index: index,
+                },
+                f,
+            )
+        })
+    }
+
+    pub(super) fn extent_cache(&self) -> &Mutex<ExtentCache> {
+        &self.extent
+    }
+
     pub(super) fn drain_updates(&self) -> Vec<FeatureUpdate> {
         let mut updates = self.pending_updates.lock();
-        std::mem::take(&mut *updates)
+        merge_updates(std::mem::take(&mut *updates))
     }
 
     /// Iterates over immutable containers of the features.
@@ -220,6 +377,21 @@ impl<F> FeatureStore<F> {
             })
     }
 
+    /// Iterates over immutable containers of the features together with their entries.
+    pub(super) fn iter_entries(
+        &self,
+    ) -> impl Iterator<Item = (FeatureContainer<'_, F>, &FeatureEntry<F>)> {
+        self.features.iter().enumerate().map(|(feature_index, f)| {
+            (
+                FeatureContainer {
+                    feature: &f.feature,
+                    feature_index,
+                },
+                f,
+            )
+        })
+    }
+
     /// Iterates over mutable containers of the features.
     pub fn iter_mut(&mut self) -> impl Iterator<Item = FeatureContainerMut<F>> {
         self.features
@@ -229,15 +401,243 @@ impl<F> FeatureStore<F> {
                 entry: f,
                 feature_index: index,
                 is_updated: false,
+                is_geometry_updated: false,
                 pending_updates: self.pending_updates.clone(),
+                extent: self.extent.clone(),
             })
     }
 }
 
+/// Removes repeated updates of the same feature, so that every feature is rendered at most once.
+///
+/// Full update of a feature supersedes moves and style updates of the same feature, and a move supersedes style
+/// updates, since it updates the style of the moved symbols too. Deletions are always kept, since they refer to the
+/// renders rather than to the features.
+fn merge_updates(updates: Vec<FeatureUpdate>) -> Vec<FeatureUpdate> {
+    if updates.len() < 2 {
+        return updates;
+    }
+
+    let mut full_updates = HashSet::new();
+    let mut moves = HashSet::new();
+    for update in &updates {
+        match update {
+            FeatureUpdate::Update { feature_index } => {
+                full_updates.insert(*feature_index);
+            }
+            FeatureUpdate::Move { feature_index } => {
+                moves.insert(*feature_index);
+            }
+            _ => {}
+        }
+    }
+
+    let mut processed = HashSet::new();
+    updates
+        .into_iter()
+        .filter(|update| match update {
+            FeatureUpdate::Update { feature_index } => processed.insert((*feature_index, 0)),
+            FeatureUpdate::Move { feature_index } => {
+                !full_updates.contains(feature_index) && processed.insert((*feature_index, 1))
+            }
+            FeatureUpdate::UpdateStyle { feature_index } => {
+                !full_updates.contains(feature_index)
+                    && !moves.contains(feature_index)
+                    && processed.insert((*feature_index, 2))
+            }
+            FeatureUpdate::Delete { .. } => true,
+        })
+        .collect()
+}
+
+/// Extent of a feature with the index of the feature in the store.
+type IndexedExtent = GeomWithData<Rectangle<[f64; 2]>, usize>;
+
+/// Cached extent of the features of the store in the CRS it was last requested for.
+///
+/// Adding features extends the cached extent. Removing or changing the geometry of a feature only invalidates the
+/// cache if the feature touches the boundary of the extent, since otherwise the extent stays the same.
+///
+/// The cache can also keep a spatial index (R-tree) of the extents of the features. The index is built on the first
+/// query and then updated together with the extents of the changed features.
+#[derive(Debug, Default)]
+pub(super) struct ExtentCache {
+    crs: Option<Crs>,
+    extent: Option<Rect>,
+    is_valid: bool,
+    pending: Vec<usize>,
+    index: Option<RTree<IndexedExtent>>,
+}
+
+impl ExtentCache {
+    /// Returns true if the cached value can be updated for the given CRS without recalculating extents of all
+    /// features.
+    pub fn is_valid_for(&self, crs: &Crs) -> bool {
+        self.is_valid && self.crs.as_ref() == Some(crs)
+    }
+
+    /// Returns true if the extents of the features stored in the entries are calculated for the given CRS (except
+    /// for the [pending](Self::take_pending) features).
+    pub fn has_crs(&self, crs: &Crs) -> bool {
+        self.crs.as_ref() == Some(crs)
+    }
+
+    /// Replaces the cached value with the extent calculated for all features.
+    pub fn reset(&mut self, crs: &Crs, extent: Option<Rect>) {
+        self.crs = Some(crs.clone());
+        self.extent = extent;
+        self.is_valid = true;
+        self.pending.clear();
+        self.index = None;
+    }
+
+    /// Replaces the cached value with the extent recalculated from the stored extents of the features.
+    pub fn revalidate(&mut self, extent: Option<Rect>) {
+        self.extent = extent;
+        self.is_valid = true;
+    }
+
+    /// Returns indices of the features that were added or changed since the last update.
+    pub fn take_pending(&mut self) -> Vec<usize> {
+        std::mem::take(&mut self.pending)
+    }
+
+    /// Extends the cached value with the extent of a feature.
+    pub fn extend(&mut self, feature_extent: Rect) {
+        self.extent = Some(match self.extent {
+            Some(extent) => extent.merge(feature_extent),
+            None => feature_extent,
+        });
+    }
+
+    pub fn extent(&self) -> Option<Rect> {
+        self.extent
+    }
+
+    /// Replaces the extent of the feature in the spatial index, if the index is built.
+    pub fn update_indexed(&mut self, feature_index: usize, old: Option<Rect>, new: Option<Rect>) {
+        let Some(index) = &mut self.index else {
+            return;
+        };
+
+        if let Some(old) = old {
+            index.remove(&indexed_extent(old, feature_index));
+        }
+        if let Some(new) = new {
+            index.insert(indexed_extent(new, feature_index));
+        }
+    }
+
+    /// Extent of all features in the spatial index. Returns `None` if the index is not built.
+    pub fn indexed_extent(&self) -> Option<Option<Rect>> {
+        let index = self.index.as_ref()?;
+        if index.size() == 0 {
+            return Some(None);
+        }
+
+        let envelope = index.root().envelope();
+        let ([x_min, y_min], [x_max, y_max]) = (envelope.lower(), envelope.upper());
+        Some(Some(Rect::new(x_min, y_min, x_max, y_max)))
+    }
+
+    /// Returns indices of the features, which extents intersect the `bbox`, in ascending order. If the spatial index is
+    /// not built yet, it is built from the `extents` of the features.
+    pub fn query(
+        &mut self,
+        bbox: Rect,
+        extents: impl FnOnce() -> Vec<(usize, Rect)>,
+    ) -> Vec<usize> {
+        let index = self.index.get_or_insert_with(|| {
+            RTree::bulk_load(
+                extents()
+                    .into_iter()
+                    .map(|(feature_index, extent)| indexed_extent(extent, feature_index))
+                    .collect(),
+            )
+        });
+
+        let envelope =
+            AABB::from_corners([bbox.x_min(), bbox.y_min()], [bbox.x_max(), bbox.y_max()]);
+        let mut indices: Vec<usize> = index
+            .locate_in_envelope_intersecting(&envelope)
+            .map(|item| item.data)
+            .collect();
+        indices.sort_unstable();
+        indices
+    }
+
+    fn feature_added(&mut self, feature_index: usize) {
+        self.pending.push(feature_index);
+    }
+
+    fn feature_changed(&mut self, feature_index: usize, old_extent: Option<Rect>) {
+        self.invalidate_if_on_boundary(old_extent);
+        if !self.pending.contains(&feature_index) {
+            self.pending.push(feature_index);
+        }
+    }
+
+    fn feature_removed(&mut self, feature_index: usize, old_extent: Option<Rect>) {
+        self.invalidate_if_on_boundary(old_extent);
+        self.pending.retain(|&index| index != feature_index);
+        for index in &mut self.pending {
+            if *index > feature_index {
+                *index -= 1;
+            }
+        }
+
+        // Indices of all the following features are shifted, so the index is rebuilt with the stored extents
+        if let Some(index) = self.index.take() {
+            let items = index
+                .iter()
+                .filter(|item| item.data != feature_index)
+                .map(|item| {
+                    let shifted = if item.data > feature_index {
+                        item.data - 1
+                    } else {
+                        item.data
+                    };
+                    IndexedExtent::new(*item.geom(), shifted)
+                })
+                .collect();
+            self.index = Some(RTree::bulk_load(items));
+        }
+    }
+
+    fn invalidate_if_on_boundary(&mut self, feature_extent: Option<Rect>) {
+        let (Some(extent), Some(feature_extent)) = (self.extent, feature_extent) else {
+            return;
+        };
+
+        if feature_extent.x_min() <= extent.x_min()
+            || feature_extent.y_min() <= extent.y_min()
+            || feature_extent.x_max() >= extent.x_max()
+            || feature_extent.y_max() >= extent.y_max()
+        {
+            self.is_valid = false;
+        }
+    }
+}
+
+fn indexed_extent(extent: Rect, feature_index: usize) -> IndexedExtent {
+    IndexedExtent::new(
+        Rectangle::from_corners(
+            [extent.x_min(), extent.y_min()],
+            [extent.x_max(), extent.y_max()],
+        ),
+        feature_index,
+    )
+}
+
 pub(super) struct FeatureEntry<F> {
     feature: F,
     is_hidden: bool,
     render_indices: Mutex<Vec<Option<usize>>>,
+    hit_regions: Mutex<Vec<HitRegion>>,
+    /// Projected extent of the feature, calculated together with the [`ExtentCache`].
+    extent: Mutex<Option<Rect>>,
+    /// Identifier of the feature in the backend of the layer, if the feature was loaded from it.
+    source_id: Option<FeatureId>,
 }
 
 impl<F> FeatureEntry<F> {
@@ -246,6 +646,9 @@ impl<F> FeatureEntry<F> {
             feature,
             is_hidden: false,
             render_indices: Mutex::new(vec![]),
+            hit_regions: Mutex::new(vec![]),
+            extent: Mutex::new(None),
+            source_id: None,
         }
     }
 
@@ -254,6 +657,9 @@ impl<F> FeatureEntry<F> {
             feature,
             is_hidden: true,
             render_indices: Mutex::new(vec![]),
+            hit_regions: Mutex::new(vec![]),
+            extent: Mutex::new(None),
+            source_id: None,
         }
     }
 
@@ -261,6 +667,34 @@ impl<F> FeatureEntry<F> {
         &self.feature
     }
 
+    pub fn is_hidden(&self) -> bool {
+        self.is_hidden
+    }
+
+    pub fn source_id(&self) -> Option<FeatureId> {
+        self.source_id
+    }
+
+    pub fn extent(&self) -> Option<Rect> {
+        *self.extent.lock()
+    }
+
+    pub fn set_extent(&self, extent: Option<Rect>) {
+        *self.extent.lock() = extent;
+    }
+
+    pub fn set_hit_regions(&self, hit_regions: Vec<HitRegion>) {
+        *self.hit_regions.lock() = hit_regions;
+    }
+
+    /// Returns true if any of the screen-space regions of the rendered feature contains the point.
+    pub fn hit_test(&self, screen_point: Point2d, view: &MapView) -> bool {
+        self.hit_regions
+            .lock()
+            .iter()
+            .any(|region| region.contains(screen_point, view))
+    }
+
     pub fn render_index(&self, render_store_id: usize) -> Option<usize> {
         self.render_indices
             .lock()
@@ -278,6 +712,12 @@ impl<F> FeatureEntry<F> {
 
         render_indices[render_store_id] = Some(render_index)
     }
+
+    pub fn clear_render_index(&self, render_store_id: usize) {
+        if let Some(render_index) = self.render_indices.lock().get_mut(render_store_id) {
+            *render_index = None;
+        }
+    }
 }
 
 #[cfg(test)]
@@ -310,4 +750,183 @@ mod tests {
 
         assert_eq!(store.get(0).expect("no feature"), &"F12".to_string());
     }
+
+    #[test]
+    fn repeated_updates_are_merged() {
+        let mut store = FeatureStore::new(["F1".to_string(), "F2".to_string()].into_iter());
+        store.drain_updates();
+
+        for _ in 0..10 {
+            store.get_mut(0).expect("no feature").edit_style().push('!');
+            store.get_mut(1).expect("no feature").edit_style().push('!');
+        }
+        store.get_mut(1).expect("no feature").as_mut().push('?');
+
+        let pending_updates = store.drain_updates();
+        assert_eq!(pending_updates.len(), 2);
+        assert_matches!(
+            pending_updates[0],
+            FeatureUpdate::UpdateStyle { feature_index: 0 }
+        );
+        assert_matches!(
+            pending_updates[1],
+            FeatureUpdate::Update { feature_index: 1 }
+        );
+    }
+
+    #[test]
+    fn moves_are_merged() {
+        let mut store = FeatureStore::new(["F1".to_string(), "F2".to_string()].into_iter());
+        store.drain_updates();
+
+        for _ in 0..10 {
+            store
+                .get_mut(0)
+                .expect("no feature")
+                .edit_position()
+                .push('!');
+            store.get_mut(0).expect("no feature").edit_style().push('!');
+            store
+                .get_mut(1)
+                .expect("no feature")
+                .edit_position()
+                .push('!');
+        }
+        store.get_mut(1).expect("no feature").as_mut().push('?');
+
+        let pending_updates = store.drain_updates();
+        assert_eq!(pending_updates.len(), 2);
+        assert_matches!(pending_updates[0], FeatureUpdate::Move { feature_index: 0 });
+        assert_matches!(
+            pending_updates[1],
+            FeatureUpdate::Update { feature_index: 1 }
+        );
+    }
+
+    #[test]
+    fn remove_where_remaps_updates() {
+        let mut store = FeatureStore::default();
+        store.insert_loaded(10, "F1");
+        store.insert("F2");
+        store.insert_loaded(30, "F3");
+        store
+            .get_entry(0)
+            .expect("no feature")
+            .set_render_index(5, 0);
+
+        let removed = store.remove_where(|entry| entry.source_id() == Some(10));
+        assert_eq!(removed, 1);
+        assert_eq!(store.source_id(0), None);
+        assert_eq!(store.source_id(1), Some(30));
+        assert_eq!(store.source_ids(), HashSet::from([30]));
+
+        let pending_updates = store.drain_updates();
+        assert_eq!(pending_updates.len(), 3);
+        assert_matches!(
+            pending_updates[0],
+            FeatureUpdate::Update { feature_index: 0 }
+        );
+        assert_matches!(
+            pending_updates[1],
+            FeatureUpdate::Update { feature_index: 1 }
+        );
+        assert_matches!(&pending_updates[2], FeatureUpdate::Delete { render_indices } if render_indices == &[Some(5)]);
+    }
+
+    #[test]
+    fn extent_cache_invalidation() {
+        let mut store = FeatureStore::new(["F1", "F2", "F3"].into_iter());
+        let extents = [
+            Rect::new(0.0, 0.0, 10.0, 10.0),
+            Rect::new(2.0, 2.0, 3.0, 3.0),
+            Rect::new(5.0, 5.0, 6.0, 6.0),
+        ];
+        for (index, extent) in extents.iter().enumerate() {
+            store
+                .get_entry(index)
+                .expect("no feature")
+                .set_extent(Some(*extent));
+        }
+        store
+            .extent_cache()
+            .lock()
+            .reset(&Crs::EPSG3857, Some(extents[0]));
+
+        // Feature inside the extent does not change it
+        store.remove(1);
+        assert!(store.extent_cache().lock().is_valid_for(&Crs::EPSG3857));
+        assert!(!store.extent_cache().lock().is_valid_for(&Crs::WGS84));
+
+        // Added features are only marked for the update
+        store.insert("F4");
+        store.insert("F5");
+        store.get_mut(1).expect("no feature").as_mut();
+        assert_eq!(store.extent_cache().lock().take_pending(), vec![2, 3, 1]);
+
+        store.insert("F6");
+        store.remove(2);
+        assert_eq!(store.extent_cache().lock().take_pending(), vec![3]);
+        assert!(store.extent_cache().lock().is_valid_for(&Crs::EPSG3857));
+
+        // Feature on the boundary may shrink the extent
+        store.get_mut(0).expect("no feature").as_mut();
+        assert!(!store.extent_cache().lock().is_valid_for(&Crs::EPSG3857));
+    }
+
+    #[test]
+    fn extent_index_query() {
+        let mut store = FeatureStore::new(["F1", "F2", "F3"].into_iter());
+        let extents = [
+            Rect::new(0.0, 0.0, 1.0, 1.0),
+            Rect::new(2.0, 2.0, 3.0, 3.0),
+            Rect::new(0.5, 0.5, 2.5, 2.5),
+        ];
+        let stored_extents = |store: &FeatureStore<&str>| {
+            store
+                .iter_entries()
+                .filter_map(|(container, entry)| Some((container.index(), entry.extent()?)))
+                .collect()
+        };
+
+        for (index, extent) in extents.iter().enumerate() {
+            store
+                .get_entry(index)
+                .expect("no feature")
+                .set_extent(Some(*extent));
+        }
+        store.extent_cache().lock().reset(&Crs::EPSG3857, None);
+
+        let bbox = Rect::new(0.8, 0.8, 0.9, 0.9);
+        let query = |store: &FeatureStore<&str>, bbox| {
+            store
+                .extent_cache()
+                .lock()
+                .query(bbox, || stored_extents(store))
+        };
+        assert_eq!(query(&store, bbox), vec![0, 2]);
+        assert_eq!(
+            store.extent_cache().lock().indexed_extent(),
+            Some(Some(Rect::new(0.0, 0.0, 3.0, 3.0)))
+        );
+
+        // Indices of the following features are shifted after removal
+        store.remove(0);
+        assert_eq!(query(&store, bbox), vec![1]);
+        assert_eq!(query(&store, Rect::new(2.9, 2.9, 4.0, 4.0)), vec![0]);
+
+        store.extent_cache().lock().update_indexed(
+            0,
+            Some(extents[1]),
+            Some(Rect::new(10.0, 10.0, 11.0, 11.0)),
+        );
+        assert!(query(&store, Rect::new(2.9, 2.9, 4.0, 4.0)).is_empty());
+        assert_eq!(
+            store.extent_cache().lock().indexed_extent(),
+            Some(Some(Rect::new(0.5, 0.5, 11.0, 11.0)))
+        );
+
+        // Reset drops the index
+        store.extent_cache().lock().reset(&Crs::WGS84, None);
+        assert_eq!(store.extent_cache().lock().indexed_extent(), None);
+    }
 }
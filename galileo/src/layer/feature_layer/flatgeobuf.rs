@@ -0,0 +1,177 @@
+//! Loading of features from [FlatGeobuf](https://flatgeobuf.org) files.
+//!
+//! FlatGeobuf files can contain a spatial index, which allows reading only the features in the given area without
+//! reading the whole file. When the file is loaded over HTTP, only the parts of the file containing the index and the
+//! selected features are requested with HTTP range requests, so even very large datasets can be displayed by loading
+//! only the area of the current map view.
+//!
+//! Features are returned as GeoJSON features, so they can be displayed with
+//! [`FeatureLayer::from_geojson`](super::FeatureLayer::from_geojson). Geometries are returned in the CRS of the
+//! file (see [`crs`]), which is usually WGS84.
+
+use std::io::{Read, Seek};
+
+use flatgeobuf::{FallibleStreamingIterator, FgbFeature, FgbReader};
+use galileo_types::cartesian::Rect;
+use galileo_types::geo::Crs;
+use geozero::{ColumnValue, FeatureProperties, PropertyProcessor, ToGeo};
+use serde_json::{Map, Value};
+
+use crate::error::GalileoError;
+
+/// Reads all features from the FlatGeobuf data.
+pub fn read_features(reader: impl Read + Seek) -> Result<geojson::FeatureCollection, GalileoError> {
+    let mut features = FgbReader::open(reader)
+        .map_err(fgb_error)?
+        .select_all()
+        .map_err(fgb_error)?;
+
+    let mut collection = vec![];
+    while let Some(feature) = features.next().map_err(fgb_error)? {
+        collection.push(to_geojson(feature)?);
+    }
+
+    Ok(collection.into_iter().collect())
+}
+
+/// Reads the features which bounding rectangles intersect the `bbox` from the FlatGeobuf data.
+///
+/// The `bbox` must be given in the CRS of the file. If the file contains a spatial index, only the index and the
+/// selected features are read.
+pub fn read_features_in_bbox(
+    reader: impl Read + Seek,
+    bbox: Rect,
+) -> Result<geojson::FeatureCollection, GalileoError> {
+    let mut features = FgbReader::open(reader)
+        .map_err(fgb_error)?
+        .select_bbox(bbox.x_min(), bbox.y_min(), bbox.x_max(), bbox.y_max())
+        .map_err(fgb_error)?;
+
+    let mut collection = vec![];
+    while let Some(feature) = features.next().map_err(fgb_error)? {
+        collection.push(to_geojson(feature)?);
+    }
+
+    Ok(collection.into_iter().collect())
+}
+
+/// Loads the features which bounding rectangles intersect the `bbox` from the FlatGeobuf file at the given `url`.
+///
+/// The file must contain a spatial index. Only the header, the index and the selected features are downloaded using
+/// HTTP range requests, so the server must support them.
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn load_features_in_bbox(
+    url: &str,
+    bbox: Rect,
+) -> Result<geojson::FeatureCollection, GalileoError> {
+    let mut features = flatgeobuf::HttpFgbReader::open(url)
+        .await
+        .map_err(fgb_error)?
+        .select_bbox(bbox.x_min(), bbox.y_min(), bbox.x_max(), bbox.y_max())
+        .await
+        .map_err(fgb_error)?;
+
+    let mut collection = vec![];
+    while let Some(feature) = features.next().await.map_err(fgb_error)? {
+        collection.push(to_geojson(feature)?);
+    }
+
+    Ok(collection.into_iter().collect())
+}
+
+/// Returns the CRS of the FlatGeobuf data if it is set in the file header and is supported by Galileo.
+pub fn crs(reader: impl Read) -> Result<Option<Crs>, GalileoError> {
+    let reader = FgbReader::open(reader).map_err(fgb_error)?;
+    let crs = reader
+        .header()
+        .crs()
+        .and_then(|crs| match (crs.org(), crs.code()) {
+            (Some("EPSG") | None, 4326) => Some(Crs::WGS84),
+            (Some("EPSG") | None, 3857) => Some(Crs::EPSG3857),
+            _ => None,
+        });
+
+    Ok(crs)
+}
+
+fn to_geojson(feature: &FgbFeature) -> Result<geojson::Feature, GalileoError> {
+    let geometry = feature
+        .to_geo()
+        .map_err(|err| GalileoError::Generic(format!("invalid FlatGeobuf geometry: {err}")))?;
+
+    let mut properties = JsonProperties::default();
+    feature
+        .process_properties(&mut properties)
+        .map_err(|err| GalileoError::Generic(format!("invalid FlatGeobuf properties: {err}")))?;
+
+    Ok(geojson::Feature {
+        bbox: None,
+        geometry: Some(geojson::Geometry::new(geojson::Value::from(&geometry))),
+        id: None,
+        properties: Some(properties.0),
+        foreign_members: None,
+    })
+}
+
+fn fgb_error(err: flatgeobuf::Error) -> GalileoError {
+    GalileoError::Generic(format!("failed to read FlatGeobuf data: {err}"))
+}
+
+/// Collects properties of a FlatGeobuf feature into a JSON object, keeping the types of the values.
+#[derive(Default)]
+struct JsonProperties(Map<String, Value>);
+
+impl PropertyProcessor for JsonProperties {
+    fn property(
+        &mut self,
+        _index: usize,
+        name: &str,
+        value: &ColumnValue,
+    ) -> geozero::error::Result<bool> {
+        let value = match *value {
+            ColumnValue::Byte(v) => Value::from(v),
+            ColumnValue::UByte(v) => Value::from(v),
+            ColumnValue::Bool(v) => Value::from(v),
+            ColumnValue::Short(v) => Value::from(v),
+            ColumnValue::UShort(v) => Value::from(v),
+            ColumnValue::Int(v) => Value::from(v),
+            ColumnValue::UInt(v) => Value::from(v),
+            ColumnValue::Long(v) => Value::from(v),
+            ColumnValue::ULong(v) => Value::from(v),
+            ColumnValue::Float(v) => Value::from(v),
+            ColumnValue::Double(v) => Value::from(v),
+            ColumnValue::String(v) | ColumnValue::DateTime(v) => Value::from(v),
+            ColumnValue::Json(v) => serde_json::from_str(v).unwrap_or_else(|_| Value::from(v)),
+            // Binary values cannot be represented in JSON, and are not used for styling anyway
+            ColumnValue::Binary(_) => Value::Null,
+        };
+
+        self.0.insert(name.to_string(), value);
+
+        // `false` means that the rest of the properties should be processed as well
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_properties() {
+        let mut properties = JsonProperties::default();
+        properties
+            .property(0, "name", &ColumnValue::String("Berlin"))
+            .expect("valid property");
+        properties
+            .property(1, "population", &ColumnValue::ULong(3_850_000))
+            .expect("valid property");
+        properties
+            .property(2, "tags", &ColumnValue::Json(r#"["capital"]"#))
+            .expect("valid property");
+
+        assert_eq!(properties.0["name"], Value::from("Berlin"));
+        assert_eq!(properties.0["population"], Value::from(3_850_000u64));
+        assert_eq!(properties.0["tags"], serde_json::json!(["capital"]));
+    }
+}
@@ -0,0 +1,236 @@
+//! Loading of features from [GeoJSON](https://geojson.org/) documents.
+//!
+//! GeoJSON features are read into [`GeoJsonFeature`]s, which properties are parsed into a map, so the features can be
+//! drawn and styled without defining a feature type for the data:
+//!
+//! ```no_run
+//! use galileo::layer::FeatureLayer;
+//! # use galileo::symbol::ArbitraryGeometrySymbol;
+//! # let symbol = ArbitraryGeometrySymbol::default();
+//!
+//! let layer = FeatureLayer::from_geojson_file("data/museums.geojson", symbol)
+//!     .expect("failed to read GeoJSON");
+//! for feature in layer.features().iter() {
+//!     println!("{:?}", feature.as_ref().properties.get("name"));
+//! }
+//! ```
+//!
+//! The loader accepts a feature collection, a single feature or a bare geometry. Coordinates are always treated as
+//! longitude and latitude in WGS84, as required by the GeoJSON specification.
+
+use std::collections::HashMap;
+
+use galileo_types::geo::Crs;
+use galileo_types::geometry::Geometry;
+use galileo_types::geometry_type::GeoSpace2d;
+use geojson::GeoJson;
+
+use crate::error::GalileoError;
+use crate::layer::feature_layer::{Feature, FeatureLayer, Properties, PropertyValue, Symbol};
+use crate::platform::{PlatformService, PlatformServiceImpl};
+
+/// A feature of a GeoJSON document with parsed properties.
+#[derive(Debug, Clone)]
+pub struct GeoJsonFeature {
+    /// Identifier of the feature, if set in the document.
+    pub id: Option<geojson::feature::Id>,
+    /// Geometry of the feature.
+    pub geometry: geojson::Geometry,
+    /// Properties of the feature. Nested objects and arrays are stored as their JSON representation.
+    pub properties: HashMap<String, PropertyValue<'static>>,
+}
+
+impl Feature for GeoJsonFeature {
+    type Geom = geojson::Geometry;
+
+    fn geometry(&self) -> &Self::Geom {
+        &self.geometry
+    }
+}
+
+impl Properties for GeoJsonFeature {
+    fn property(&self, key: &str) -> Option<PropertyValue<'_>> {
+        self.properties.get(key).cloned()
+    }
+
+    fn iter_properties(&self) -> Box<dyn Iterator<Item = (&str, PropertyValue<'_>)> + '_> {
+        Box::new(
+            self.properties
+                .iter()
+                .map(|(key, value)| (key.as_str(), value.clone())),
+        )
+    }
+}
+
+impl TryFrom<geojson::Feature> for GeoJsonFeature {
+    type Error = GalileoError;
+
+    /// Fails if the feature has no geometry or has a geometry collection, which cannot be drawn.
+    fn try_from(feature: geojson::Feature) -> Result<Self, Self::Error> {
+        let geometry = match feature.geometry {
+            Some(geometry) if !matches!(geometry.value, geojson::Value::GeometryCollection(_)) => {
+                geometry
+            }
+            Some(_) => {
+                return Err(GalileoError::Generic(
+                    "geometry collections are not supported".into(),
+                ))
+            }
+            None => return Err(GalileoError::Generic("feature has no geometry".into())),
+        };
+
+        let properties = feature
+            .properties
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(key, value)| (key, PropertyValue::from(&value).into_owned()))
+            .collect();
+
+        Ok(Self {
+            id: feature.id,
+            geometry,
+            properties,
+        })
+    }
+}
+
+/// Parses a GeoJSON document.
+///
+/// Features without geometry and features with geometry collections are skipped.
+pub fn parse_geojson(json: &str) -> Result<Vec<GeoJsonFeature>, GalileoError> {
+    let geojson: GeoJson = json
+        .parse()
+        .map_err(|err| GalileoError::Generic(format!("invalid GeoJSON: {err}")))?;
+
+    let features = match geojson {
+        GeoJson::FeatureCollection(collection) => collection.features,
+        GeoJson::Feature(feature) => vec![feature],
+        GeoJson::Geometry(geometry) => vec![geojson::Feature {
+            bbox: None,
+            geometry: Some(geometry),
+            id: None,
+            properties: None,
+            foreign_members: None,
+        }],
+    };
+
+    let count = features.len();
+    let features: Vec<GeoJsonFeature> = features
+        .into_iter()
+        .filter_map(|feature| GeoJsonFeature::try_from(feature).ok())
+        .collect();
+
+    if features.len() < count {
+        log::debug!(
+            "{} GeoJSON features without drawable geometry are skipped",
+            count - features.len()
+        );
+    }
+
+    Ok(features)
+}
+
+/// Reads the GeoJSON file.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read_geojson(
+    path: impl AsRef<std::path::Path>,
+) -> Result<Vec<GeoJsonFeature>, GalileoError> {
+    let json = std::fs::read_to_string(path)?;
+    parse_geojson(&json)
+}
+
+/// Downloads the GeoJSON document from the given URL.
+pub async fn load_geojson(url: &str) -> Result<Vec<GeoJsonFeature>, GalileoError> {
+    let bytes = PlatformServiceImpl::new().load_bytes_from_url(url).await?;
+    let json = std::str::from_utf8(&bytes)
+        .map_err(|err| GalileoError::Generic(format!("invalid GeoJSON: {err}")))?;
+    parse_geojson(json)
+}
+
+impl<P, S> FeatureLayer<P, GeoJsonFeature, S, GeoSpace2d>
+where
+    geojson::Geometry: Geometry<Point = P>,
+    S: Symbol<GeoJsonFeature>,
+{
+    /// Creates a new layer from a GeoJSON string. See [module documentation](self) for details.
+    pub fn from_geojson_str(json: &str, style: S) -> Result<Self, GalileoError> {
+        Ok(Self::new(parse_geojson(json)?, style, Crs::WGS84))
+    }
+
+    /// Creates a new layer from a GeoJSON file. See [module documentation](self) for details.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_geojson_file(
+        path: impl AsRef<std::path::Path>,
+        style: S,
+    ) -> Result<Self, GalileoError> {
+        Ok(Self::new(read_geojson(path)?, style, Crs::WGS84))
+    }
+
+    /// Creates a new layer from a GeoJSON document downloaded from the given URL. See
+    /// [module documentation](self) for details.
+    pub async fn from_geojson_url(url: &str, style: S) -> Result<Self, GalileoError> {
+        Ok(Self::new(load_geojson(url).await?, style, Crs::WGS84))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symbol::ArbitraryGeometrySymbol;
+
+    const COLLECTION: &str = r#"{
+        "type": "FeatureCollection",
+        "features": [
+            {
+                "type": "Feature",
+                "id": 1,
+                "geometry": { "type": "Point", "coordinates": [10.0, 50.0] },
+                "properties": { "name": "Museum", "visitors": 1200, "open": true, "tags": ["art"] }
+            },
+            { "type": "Feature", "geometry": null, "properties": { "name": "Nowhere" } },
+            {
+                "type": "Feature",
+                "geometry": { "type": "LineString", "coordinates": [[0.0, 0.0], [1.0, 1.0]] },
+                "properties": null
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn parse_collection() {
+        let features = parse_geojson(COLLECTION).unwrap();
+        assert_eq!(features.len(), 2);
+
+        let museum = &features[0];
+        assert_eq!(museum.id, Some(geojson::feature::Id::Number(1.into())));
+        assert_eq!(
+            museum.property("name"),
+            Some(PropertyValue::String("Museum".into()))
+        );
+        assert_eq!(museum.property("visitors"), Some(PropertyValue::Int(1200)));
+        assert_eq!(museum.property("open"), Some(PropertyValue::Bool(true)));
+        assert_eq!(
+            museum.property("tags"),
+            Some(PropertyValue::String(r#"["art"]"#.into()))
+        );
+
+        assert!(features[1].properties.is_empty());
+    }
+
+    #[test]
+    fn parse_geometry() {
+        let features =
+            parse_geojson(r#"{ "type": "Point", "coordinates": [10.0, 50.0] }"#).unwrap();
+        assert_eq!(features.len(), 1);
+        assert!(features[0].properties.is_empty());
+
+        assert!(parse_geojson("{").is_err());
+    }
+
+    #[test]
+    fn layer_from_str() {
+        let layer =
+            FeatureLayer::from_geojson_str(COLLECTION, ArbitraryGeometrySymbol::default()).unwrap();
+        assert_eq!(layer.features().iter().count(), 2);
+    }
+}
@@ -0,0 +1,169 @@
+//! Loading of waypoints, routes and tracks from [GPX](https://www.topografix.com/gpx.asp) files.
+//!
+//! Features are returned as GeoJSON features in WGS84, so they can be displayed with
+//! [`FeatureLayer::from_geojson`](super::FeatureLayer::from_geojson):
+//! * waypoints (`wpt` elements) as points,
+//! * routes (`rte` elements) as line strings,
+//! * tracks (`trk` elements) as multi line strings with a line for every track segment.
+//!
+//! The `name`, `desc` and `type` child elements are stored as the properties of the features with the same names,
+//! and the name of the GPX element (`wpt`, `rte` or `trk`) is stored as the `gpx_element` property.
+
+use std::io::BufRead;
+
+use geojson::{Feature, FeatureCollection, Geometry, Value};
+use serde_json::Map;
+
+use super::xml::XmlElement;
+use crate::error::GalileoError;
+
+/// Reads all waypoints, routes and tracks from the GPX document.
+pub fn read_features(reader: impl BufRead) -> Result<FeatureCollection, GalileoError> {
+    let root = XmlElement::read(reader)?;
+    if root.name() != "gpx" {
+        return Err(GalileoError::Generic(format!(
+            "invalid GPX: unexpected root element {}",
+            root.name()
+        )));
+    }
+
+    let mut features = vec![];
+    for element in root.children() {
+        let value = match element.name() {
+            "wpt" => Value::Point(position(element)?),
+            "rte" => {
+                let line = line(element.children_named("rtept"))?;
+                if line.len() < 2 {
+                    continue;
+                }
+                Value::LineString(line)
+            }
+            "trk" => {
+                let segments = element
+                    .children_named("trkseg")
+                    .map(|segment| line(segment.children_named("trkpt")))
+                    .filter(|line| !matches!(line, Ok(line) if line.len() < 2))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if segments.is_empty() {
+                    continue;
+                }
+                Value::MultiLineString(segments)
+            }
+            _ => continue,
+        };
+
+        features.push(Feature {
+            bbox: None,
+            geometry: Some(Geometry::new(value)),
+            id: None,
+            properties: Some(properties(element)),
+            foreign_members: None,
+        });
+    }
+
+    Ok(features.into_iter().collect())
+}
+
+fn line<'a>(points: impl Iterator<Item = &'a XmlElement>) -> Result<Vec<Vec<f64>>, GalileoError> {
+    points.map(position).collect()
+}
+
+/// Longitude and latitude of a waypoint, route point or track point.
+fn position(point: &XmlElement) -> Result<Vec<f64>, GalileoError> {
+    let coordinate = |name| {
+        point
+            .attribute(name)
+            .and_then(|value| value.trim().parse::<f64>().ok())
+            .ok_or_else(|| {
+                GalileoError::Generic(format!(
+                    "invalid GPX: missing or invalid {name} of a {} element",
+                    point.name()
+                ))
+            })
+    };
+
+    Ok(vec![coordinate("lon")?, coordinate("lat")?])
+}
+
+fn properties(element: &XmlElement) -> Map<String, serde_json::Value> {
+    let mut properties = Map::new();
+    properties.insert("gpx_element".into(), element.name().into());
+    for name in ["name", "desc", "type"] {
+        if let Some(text) = element.child_text(name) {
+            properties.insert(name.into(), text.into());
+        }
+    }
+
+    properties
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GPX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <gpx version="1.1" creator="test" xmlns="http://www.topografix.com/GPX/1/1">
+            <wpt lat="52.5" lon="13.4"><name>Berlin</name></wpt>
+            <rte>
+                <name>Route</name>
+                <rtept lat="1.0" lon="2.0"/>
+                <rtept lat="3.0" lon="4.0"/>
+            </rte>
+            <trk>
+                <name>Track</name>
+                <type>cycling</type>
+                <trkseg>
+                    <trkpt lat="1.0" lon="2.0"><ele>100</ele></trkpt>
+                    <trkpt lat="1.5" lon="2.5"><ele>110</ele></trkpt>
+                </trkseg>
+                <trkseg>
+                    <trkpt lat="5.0" lon="6.0"/>
+                </trkseg>
+            </trk>
+        </gpx>"#;
+
+    #[test]
+    fn read_gpx() {
+        let collection = read_features(GPX.as_bytes()).expect("invalid GPX");
+        assert_eq!(collection.features.len(), 3);
+
+        let waypoint = &collection.features[0];
+        assert_eq!(
+            waypoint.geometry.as_ref().map(|g| &g.value),
+            Some(&Value::Point(vec![13.4, 52.5]))
+        );
+        assert_eq!(
+            waypoint.property("name").and_then(|v| v.as_str()),
+            Some("Berlin")
+        );
+        assert_eq!(
+            waypoint.property("gpx_element").and_then(|v| v.as_str()),
+            Some("wpt")
+        );
+
+        assert_eq!(
+            collection.features[1].geometry.as_ref().map(|g| &g.value),
+            Some(&Value::LineString(vec![vec![2.0, 1.0], vec![4.0, 3.0]]))
+        );
+
+        // Segments with a single point are skipped
+        let track = &collection.features[2];
+        assert_eq!(
+            track.geometry.as_ref().map(|g| &g.value),
+            Some(&Value::MultiLineString(vec![vec![
+                vec![2.0, 1.0],
+                vec![2.5, 1.5]
+            ]]))
+        );
+        assert_eq!(
+            track.property("type").and_then(|v| v.as_str()),
+            Some("cycling")
+        );
+    }
+
+    #[test]
+    fn invalid_gpx() {
+        assert!(read_features(r#"<kml></kml>"#.as_bytes()).is_err());
+        assert!(read_features(r#"<gpx><wpt lat="1.0"/></gpx>"#.as_bytes()).is_err());
+    }
+}
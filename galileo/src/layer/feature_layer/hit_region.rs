@@ -0,0 +1,87 @@
+use galileo_types::cartesian::{Point2d, Point3d, Rect};
+use nalgebra::{Point2, Rotation2, Vector2};
+
+use crate::render::point_paint::PointPaint;
+use crate::view::MapView;
+
+/// Area on the screen covered by a rendered point primitive (e.g. a marker image).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct HitRegion {
+    anchor: Point3d,
+    bounds: Rect<f32>,
+    map_aligned: bool,
+}
+
+impl HitRegion {
+    /// Creates the hit region for the point primitive. Returns `None` if the size of the rendered object is unknown.
+    pub fn new(anchor: Point3d, paint: &PointPaint) -> Option<Self> {
+        Some(Self {
+            anchor,
+            bounds: paint.screen_bounds()?,
+            map_aligned: paint.is_map_aligned(),
+        })
+    }
+
+    /// Returns true if the given point on the screen is inside the region when the map is displayed with the `view`.
+    pub fn contains(&self, screen_point: Point2d, view: &MapView) -> bool {
+        let Some(anchor) = view.map_to_screen(&self.anchor) else {
+            return false;
+        };
+
+        // Bounds are set with the y axis directed up, while the screen y axis is directed down
+        let mut delta = Vector2::new(screen_point.x - anchor.x, anchor.y - screen_point.y);
+        if self.map_aligned {
+            delta = Rotation2::new(-view.rotation_z()) * delta;
+        }
+
+        self.bounds
+            .contains(&Point2::new(delta.x as f32, delta.y as f32))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use galileo_types::cartesian::Size;
+
+    use super::*;
+    use crate::decoded_image::DecodedImage;
+    use crate::render::point_paint::RotationAlignment;
+    use crate::Color;
+
+    fn view() -> MapView {
+        MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0).with_size(Size::new(200.0, 200.0))
+    }
+
+    #[test]
+    fn offset_marker() {
+        let paint = PointPaint::square(Color::RED, 10.0).with_offset(Vector2::new(20.0, 0.0));
+        let region = HitRegion::new(Point3d::new(0.0, 0.0, 0.0), &paint).expect("no bounds");
+
+        // Center of the screen is the anchor point, while the square is drawn to the right of it
+        assert!(!region.contains(Point2d::new(100.0, 100.0), &view()));
+        assert!(region.contains(Point2d::new(120.0, 104.0), &view()));
+        assert!(!region.contains(Point2d::new(120.0, 110.0), &view()));
+    }
+
+    #[test]
+    fn map_aligned_image() {
+        let image = Arc::new(
+            DecodedImage::from_raw(vec![0; 4], galileo_types::cartesian::Size::new(1, 1))
+                .expect("invalid image"),
+        );
+        let paint = PointPaint::image(image, Vector2::new(0.5, 1.0), 20.0)
+            .with_rotation_alignment(RotationAlignment::Map);
+        let region = HitRegion::new(Point3d::new(0.0, 0.0, 0.0), &paint).expect("no bounds");
+
+        // The image is above the anchor point
+        assert!(region.contains(Point2d::new(100.0, 90.0), &view()));
+        assert!(!region.contains(Point2d::new(80.0, 100.0), &view()));
+
+        // After rotating the map by 90 degrees counterclockwise the image is to the left of the anchor point
+        let rotated = view().with_rotation_z(std::f64::consts::FRAC_PI_2);
+        assert!(!region.contains(Point2d::new(100.0, 80.0), &rotated));
+        assert!(region.contains(Point2d::new(90.0, 100.0), &rotated));
+    }
+}
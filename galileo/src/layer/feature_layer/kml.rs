@@ -0,0 +1,274 @@
+//! Loading of placemarks from [KML](https://www.ogc.org/standard/kml/) files.
+//!
+//! Features are returned as GeoJSON features in WGS84, so they can be displayed with
+//! [`FeatureLayer::from_geojson`](super::FeatureLayer::from_geojson). Every `Placemark` of the document (including
+//! the ones in folders) with `Point`, `LineString`, `LinearRing` or `Polygon` geometry is returned as a feature.
+//! Geometries of a `MultiGeometry` are combined into a single multi-geometry if they are of the same type, and
+//! returned as separate features with the same properties otherwise.
+//!
+//! The `name` and `description` of the placemarks and the values of their `ExtendedData` are stored as the
+//! properties of the features. Styles, altitudes and compressed KMZ files are not supported.
+
+use std::io::BufRead;
+
+use geojson::{Feature, FeatureCollection, Geometry, Value};
+use serde_json::Map;
+
+use super::xml::XmlElement;
+use crate::error::GalileoError;
+
+/// Reads all placemarks from the KML document.
+pub fn read_features(reader: impl BufRead) -> Result<FeatureCollection, GalileoError> {
+    let root = XmlElement::read(reader)?;
+    if root.name() != "kml" {
+        return Err(GalileoError::Generic(format!(
+            "invalid KML: unexpected root element {}",
+            root.name()
+        )));
+    }
+
+    let mut placemarks = vec![];
+    root.descendants("Placemark", &mut placemarks);
+
+    let mut features = vec![];
+    for placemark in placemarks {
+        let mut geometries = vec![];
+        for element in placemark.children() {
+            read_geometries(element, &mut geometries)?;
+        }
+
+        let properties = properties(placemark);
+        for value in combine(geometries) {
+            features.push(Feature {
+                bbox: None,
+                geometry: Some(Geometry::new(value)),
+                id: None,
+                properties: Some(properties.clone()),
+                foreign_members: None,
+            });
+        }
+    }
+
+    Ok(features.into_iter().collect())
+}
+
+/// Reads the geometries of the element, if it is a geometry element.
+fn read_geometries(element: &XmlElement, geometries: &mut Vec<Value>) -> Result<(), GalileoError> {
+    match element.name() {
+        "Point" => {
+            if let Some(position) = coordinates(element)?.into_iter().next() {
+                geometries.push(Value::Point(position));
+            }
+        }
+        "LineString" | "LinearRing" => geometries.push(Value::LineString(coordinates(element)?)),
+        "Polygon" => {
+            let mut rings = vec![];
+            for boundary in ["outerBoundaryIs", "innerBoundaryIs"] {
+                for boundary in element.children_named(boundary) {
+                    for ring in boundary.children_named("LinearRing") {
+                        rings.push(coordinates(ring)?);
+                    }
+                }
+            }
+
+            if !rings.is_empty() {
+                geometries.push(Value::Polygon(rings));
+            }
+        }
+        "MultiGeometry" => {
+            for child in element.children() {
+                read_geometries(child, geometries)?;
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Combines the geometries into a multi-geometry if all of them are of the same type.
+fn combine(geometries: Vec<Value>) -> Vec<Value> {
+    if geometries.len() < 2 {
+        return geometries;
+    }
+
+    let mut points = vec![];
+    let mut lines = vec![];
+    let mut polygons = vec![];
+    for geometry in &geometries {
+        match geometry {
+            Value::Point(point) => points.push(point.clone()),
+            Value::LineString(line) => lines.push(line.clone()),
+            Value::Polygon(polygon) => polygons.push(polygon.clone()),
+            _ => {}
+        }
+    }
+
+    if points.len() == geometries.len() {
+        vec![Value::MultiPoint(points)]
+    } else if lines.len() == geometries.len() {
+        vec![Value::MultiLineString(lines)]
+    } else if polygons.len() == geometries.len() {
+        vec![Value::MultiPolygon(polygons)]
+    } else {
+        geometries
+    }
+}
+
+/// Reads the `coordinates` child of the element: whitespace separated tuples of comma separated longitude, latitude
+/// and optional altitude. Altitudes are dropped.
+fn coordinates(element: &XmlElement) -> Result<Vec<Vec<f64>>, GalileoError> {
+    let Some(text) = element.child_text("coordinates") else {
+        return Ok(vec![]);
+    };
+
+    text.split_whitespace()
+        .map(|tuple| {
+            let values = tuple
+                .split(',')
+                .map(|value| value.parse::<f64>())
+                .collect::<Result<Vec<_>, _>>();
+            match values.as_deref() {
+                Ok([lon, lat, ..]) => Ok(vec![*lon, *lat]),
+                _ => Err(GalileoError::Generic(format!(
+                    "invalid KML: invalid coordinates {tuple}"
+                ))),
+            }
+        })
+        .collect()
+}
+
+fn properties(placemark: &XmlElement) -> Map<String, serde_json::Value> {
+    let mut properties = Map::new();
+    for name in ["name", "description"] {
+        if let Some(text) = placemark.child_text(name) {
+            properties.insert(name.into(), text.into());
+        }
+    }
+
+    for extended_data in placemark.children_named("ExtendedData") {
+        for data in extended_data.children_named("Data") {
+            if let (Some(name), Some(value)) = (data.attribute("name"), data.child_text("value")) {
+                properties.insert(name.into(), value.into());
+            }
+        }
+
+        for schema_data in extended_data.children_named("SchemaData") {
+            for data in schema_data.children_named("SimpleData") {
+                if let Some(name) = data.attribute("name") {
+                    properties.insert(name.into(), data.text().into());
+                }
+            }
+        }
+    }
+
+    properties
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <kml xmlns="http://www.opengis.net/kml/2.2">
+            <Document>
+                <Placemark>
+                    <name>Point</name>
+                    <ExtendedData>
+                        <Data name="rank"><value>1</value></Data>
+                    </ExtendedData>
+                    <Point><coordinates>13.4,52.5,0</coordinates></Point>
+                </Placemark>
+                <Folder>
+                    <Placemark>
+                        <name>Area</name>
+                        <Polygon>
+                            <outerBoundaryIs><LinearRing><coordinates>
+                                0,0 10,0 10,10 0,0
+                            </coordinates></LinearRing></outerBoundaryIs>
+                            <innerBoundaryIs><LinearRing><coordinates>
+                                1,1 2,1 2,2 1,1
+                            </coordinates></LinearRing></innerBoundaryIs>
+                        </Polygon>
+                    </Placemark>
+                </Folder>
+                <Placemark>
+                    <MultiGeometry>
+                        <LineString><coordinates>0,0 1,1</coordinates></LineString>
+                        <LineString><coordinates>2,2 3,3</coordinates></LineString>
+                    </MultiGeometry>
+                </Placemark>
+                <Placemark>
+                    <name>Mixed</name>
+                    <MultiGeometry>
+                        <Point><coordinates>0,0</coordinates></Point>
+                        <LineString><coordinates>2,2 3,3</coordinates></LineString>
+                    </MultiGeometry>
+                </Placemark>
+            </Document>
+        </kml>"#;
+
+    fn value(feature: &Feature) -> Option<&Value> {
+        feature.geometry.as_ref().map(|geometry| &geometry.value)
+    }
+
+    #[test]
+    fn read_kml() {
+        let collection = read_features(KML.as_bytes()).expect("invalid KML");
+        let features = &collection.features;
+        assert_eq!(features.len(), 5);
+
+        assert_eq!(value(&features[0]), Some(&Value::Point(vec![13.4, 52.5])));
+        assert_eq!(
+            features[0].property("name").and_then(|v| v.as_str()),
+            Some("Point")
+        );
+        assert_eq!(
+            features[0].property("rank").and_then(|v| v.as_str()),
+            Some("1")
+        );
+
+        assert_eq!(
+            value(&features[1]),
+            Some(&Value::Polygon(vec![
+                vec![
+                    vec![0.0, 0.0],
+                    vec![10.0, 0.0],
+                    vec![10.0, 10.0],
+                    vec![0.0, 0.0]
+                ],
+                vec![
+                    vec![1.0, 1.0],
+                    vec![2.0, 1.0],
+                    vec![2.0, 2.0],
+                    vec![1.0, 1.0]
+                ],
+            ]))
+        );
+
+        assert_eq!(
+            value(&features[2]),
+            Some(&Value::MultiLineString(vec![
+                vec![vec![0.0, 0.0], vec![1.0, 1.0]],
+                vec![vec![2.0, 2.0], vec![3.0, 3.0]],
+            ]))
+        );
+
+        // Geometries of different types are split into separate features
+        assert_eq!(value(&features[3]), Some(&Value::Point(vec![0.0, 0.0])));
+        assert_eq!(
+            features[4].property("name").and_then(|v| v.as_str()),
+            Some("Mixed")
+        );
+    }
+
+    #[test]
+    fn invalid_kml() {
+        assert!(read_features(r#"<gpx></gpx>"#.as_bytes()).is_err());
+        assert!(read_features(
+            r#"<kml><Placemark><Point><coordinates>a,b</coordinates></Point></Placemark></kml>"#
+                .as_bytes()
+        )
+        .is_err());
+    }
+}
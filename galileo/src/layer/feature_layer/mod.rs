@@ -1,34 +1,63 @@
 //! [`FeatureLayer`] stores features in a [`FeatureStore`] and renders them with a [`Symbol`].
 
 use std::any::Any;
+use std::collections::HashSet;
 use std::marker::PhantomData;
 use std::ops::Deref;
 
 use feature_render_store::FeatureRenderStore;
 use galileo_types::cartesian::{
-    CartesianPoint2d, NewCartesianPoint2d, NewCartesianPoint3d, Point2d, Point3d, Rect,
+    CartesianPoint2d, CartesianPolygon, NewCartesianPoint2d, NewCartesianPoint3d, Point2d, Point3d,
+    Rect,
 };
 use galileo_types::geo::impls::projection::{AddDimensionProjection, IdentityProjection};
 use galileo_types::geo::impls::GeoPoint2d;
-use galileo_types::geo::{ChainProjection, Crs, InvertedProjection, NewGeoPoint, Projection};
+use galileo_types::geo::{
+    ChainProjection, Crs, GeoPoint, InvertedProjection, NewGeoPoint, Projection,
+};
 use galileo_types::geometry::{CartesianGeometry2d, Geom, Geometry};
 use galileo_types::geometry_type::{CartesianSpace2d, CartesianSpace3d, GeoSpace2d};
+use galileo_types::impls::{Contour, Polygon};
+use hit_region::HitRegion;
 use maybe_sync::{MaybeSend, MaybeSync};
 use num_traits::AsPrimitive;
 use parking_lot::{Mutex, RwLock};
 
+use crate::error::GalileoError;
 use crate::layer::Layer;
 use crate::messenger::Messenger;
-use crate::render::{Canvas, RenderOptions};
+use crate::render::lighting::{polygon_normal, Lighting};
+use crate::render::render_bundle::{BundleMemoryUsage, RenderPrimitive};
+use crate::render::{Canvas, RenderOptions, ScreenRefOrder};
 use crate::view::MapView;
 
+mod backend;
+mod density;
 mod feature;
 mod feature_render_store;
 mod feature_store;
+#[cfg(feature = "flatgeobuf")]
+pub mod flatgeobuf;
+#[cfg(feature = "geojson")]
+pub mod geojson_reader;
+#[cfg(feature = "gpx")]
+pub mod gpx;
+mod hit_region;
+#[cfg(feature = "kml")]
+pub mod kml;
+mod properties;
+#[cfg(all(feature = "shapefile", not(target_arch = "wasm32")))]
+pub mod shapefile;
+pub mod spiderfy;
 pub mod symbol;
+#[cfg(any(feature = "gpx", feature = "kml"))]
+mod xml;
 
+pub use backend::{FeatureBackend, FeatureId};
+pub use density::{DensityLimit, FeaturePriority};
 pub use feature::Feature;
 pub use feature_store::*;
+pub use properties::{Properties, PropertyValue};
 pub use symbol::Symbol;
 
 /// Feature layers render a set of [features](Feature) using [symbols](Symbol).
@@ -44,6 +73,8 @@ pub use symbol::Symbol;
 ///
 /// Feature layer can render features differently at different resolutions. See [`FeatureLayer::with_lods`] for
 /// details.
+///
+/// Large data sets that do not fit into memory can be kept in external storage. See [`FeatureBackend`] for details.
 pub struct FeatureLayer<P, F, S, Space>
 where
     F: Feature,
@@ -55,6 +86,9 @@ where
     lods: Vec<Lod>,
     messenger: RwLock<Option<Box<dyn Messenger>>>,
     options: FeatureLayerOptions,
+    density: Option<(DensityLimit, Box<dyn FeaturePriority<F>>)>,
+    backend: Option<Box<dyn FeatureBackend<F>>>,
+    loaded_area: Option<Rect>,
 
     space: PhantomData<Space>,
 }
@@ -83,6 +117,60 @@ pub struct FeatureLayerOptions {
     /// If set to true, the layer will be rendered with anti-aliasing. It makes rendered lines look smoother but is a
     /// little less performant.
     pub use_antialiasing: bool,
+
+    /// Soft limit on the total GPU memory (in bytes) used by the render buffers of the layer. The layer does not
+    /// drop any data when the budget is exceeded, but logs a warning, so that runaway memory usage can be detected.
+    /// Current usage can be checked with [`FeatureLayer::memory_usage`].
+    ///
+    /// If set to `None` (default), memory usage is not checked.
+    pub memory_budget: Option<usize>,
+
+    /// Directional lighting applied to the polygons of the layer. When set, fill color of every polygon is shaded
+    /// according to its orientation relative to the light source, which makes 3D shapes (e.g.
+    /// [extruded](crate::render::lighting::extrude) buildings) look solid. Lines and points are not affected.
+    ///
+    /// The colors are shaded on the CPU when the features are rendered, so changing the lighting has no effect on
+    /// features that are already rendered. See [`lighting`](crate::render::lighting) module for details.
+    ///
+    /// If set to `None` (default), polygons are drawn with their original colors.
+    pub lighting: Option<Lighting>,
+
+    /// If set, only the features intersecting the visible area of the map are rendered. The value is the margin
+    /// around the visible area as a portion of its size, e.g. with `0.5` the features are rendered in the area twice
+    /// as large as the view in each direction, so small movements of the map do not require rendering of new
+    /// features.
+    ///
+    /// When the view moves out of the rendered area, the features that entered it are rendered, and the features that
+    /// left it are removed from the GPU buffers. The area is tracked separately for every level of detail. This keeps
+    /// GPU memory usage of large layers (e.g. with features of a whole continent) proportional to the visible part.
+    ///
+    /// The features are selected by their projected bounding rectangles, the same as in
+    /// [`FeatureLayer::get_features_in_bbox`]. Culling is only supported for layers with geographic coordinates:
+    /// other layers, as well as views without a bounding rectangle (e.g. strongly tilted ones), render all features.
+    /// If set to `None` (default), all features are rendered.
+    pub view_culling_margin: Option<f64>,
+
+    /// If set to true, all lines of the layer are aligned to the pixel grid, as if their paints had
+    /// [`crisp`](crate::render::LinePaint::crisp) set. Use this for layers with thin horizontal and vertical lines,
+    /// e.g. grids and graticules.
+    pub crisp_lines: bool,
+
+    /// If set to true, the layer keeps a spatial index (R-tree) of the bounding rectangles of the features, which is
+    /// used to find the features at a point on the screen ([`FeatureLayer::get_features_at_screen_point`]) or in an area ([`FeatureLayer::get_features_in_bbox`])
+    /// without checking every feature of the layer. Enable it for layers with many features that are queried often,
+    /// e.g. for hit-testing on every mouse move.
+    ///
+    /// The index is built on the first query and then kept up to date when the features are added, changed or
+    /// removed. Removing a feature rebuilds the index from the stored rectangles, so removing many features one by one
+    /// from a large layer is slow.
+    pub spatial_index: bool,
+
+    /// Order of the point symbols (markers and labels) relative to the lines and polygons of the layer. Features of a
+    /// large layer are split between several render bundles, and with the default
+    /// [`ScreenRefOrder::WithBundle`] the markers of the features in one bundle can be covered by the polygons of the
+    /// features in the next ones. Use [`ScreenRefOrder::AfterBundles`] to draw all markers of the layer above its
+    /// polygons.
+    pub screen_ref_order: ScreenRefOrder,
 }
 
 impl Default for FeatureLayerOptions {
@@ -91,6 +179,32 @@ impl Default for FeatureLayerOptions {
             sort_by_depth: false,
             buffer_size_limit: 10_000_000,
             use_antialiasing: true,
+            memory_budget: None,
+            lighting: None,
+            view_culling_margin: None,
+            crisp_lines: false,
+            spatial_index: false,
+            screen_ref_order: ScreenRefOrder::WithBundle,
+        }
+    }
+}
+
+/// Spatial relation used to select features with a rectangle or a polygon, e.g. with
+/// [`FeatureLayer::get_features_in_polygon`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum SelectionMode {
+    /// Select features that have at least one common point with the selection area.
+    #[default]
+    Intersects,
+    /// Select only features that are completely inside the selection area.
+    Contains,
+}
+
+impl SelectionMode {
+    fn matches(&self, area: &Polygon<Point2d>, geometry: &Geom<Point2d>) -> bool {
+        match self {
+            Self::Intersects => area.intersects_geometry(geometry),
+            Self::Contains => area.contains_geometry(geometry),
         }
     }
 }
@@ -129,6 +243,9 @@ where
             messenger: RwLock::new(None),
             lods: vec![Lod::new(0, 1.0, options.buffer_size_limit)],
             options,
+            density: None,
+            backend: None,
+            loaded_area: None,
             space: Default::default(),
         }
     }
@@ -152,10 +269,21 @@ where
             messenger: RwLock::new(None),
             lods,
             options,
+            density: None,
+            backend: None,
+            loaded_area: None,
             space: Default::default(),
         }
     }
 
+    /// Creates a new layer, which features are loaded from the `backend` only for the requested areas. The layer is
+    /// empty until [`FeatureLayer::load_area`] is called.
+    pub fn with_backend(backend: impl FeatureBackend<F> + 'static, style: S, crs: Crs) -> Self {
+        let mut layer = Self::new(vec![], style, crs);
+        layer.backend = Some(Box::new(backend));
+        layer
+    }
+
     /// Set the rendering options for the layer.
     pub fn with_options(mut self, options: FeatureLayerOptions) -> Self {
         self.options = options;
@@ -168,6 +296,17 @@ where
         self
     }
 
+    /// Limits the number of features drawn in an area of the screen at low zoom levels. See [`DensityLimit`] for
+    /// details.
+    pub fn with_density_limit(
+        mut self,
+        limit: DensityLimit,
+        priority: impl FeaturePriority<F> + 'static,
+    ) -> Self {
+        self.density = Some((limit, Box::new(priority)));
+        self
+    }
+
     /// Returns a reference to the feature store.
     pub fn features(&self) -> &FeatureStore<F> {
         &self.features
@@ -178,10 +317,223 @@ where
         &mut self.features
     }
 
+    /// Applies a batch of changes to the features of the layer.
+    ///
+    /// All changes made in the `edit` function are collected and applied to the render bundles at the next render
+    /// of the layer at once: each changed feature is rendered only once, no matter how many times it was changed,
+    /// and only the bundles containing changed features are repacked. After the function returns, a single redraw
+    /// of the map is requested.
+    ///
+    /// ```ignore
+    /// layer.edit(|features| {
+    ///     for mut feature in features.iter_mut() {
+    ///         feature.edit_style().is_selected = false;
+    ///     }
+    /// });
+    /// ```
+    pub fn edit<R>(&mut self, edit: impl FnOnce(&mut FeatureStore<F>) -> R) -> R {
+        let result = edit(&mut self.features);
+
+        if let Some(messenger) = &*self.messenger.read() {
+            messenger.request_redraw();
+        }
+
+        result
+    }
+
+    /// Moves the feature with the given index, e.g. to animate a vehicle marker. The `edit` function changes the
+    /// position of the point geometry of the feature and, if needed, its style (like the heading of the marker).
+    ///
+    /// Point symbols of the feature are moved without tessellating them again and only the changed vertices are
+    /// written to the GPU, so this can be called every frame. See [`FeatureContainerMut::edit_position`] for details.
+    /// Returns `None` if there is no feature with the given index.
+    pub fn move_feature<R>(&mut self, index: usize, edit: impl FnOnce(&mut F) -> R) -> Option<R> {
+        let result = edit(self.features.get_mut(index)?.edit_position());
+
+        if let Some(messenger) = &*self.messenger.read() {
+            messenger.request_redraw();
+        }
+
+        Some(result)
+    }
+
+    /// Returns features that have point symbols (e.g. marker images) drawn at the given point on the screen.
+    ///
+    /// Unlike [`FeatureLayer::get_features_at_screen_point`], this method checks the area covered by the rendered
+    /// symbols rather than the geometry of the features, so a click anywhere on a 32 pixel icon hits the feature
+    /// even if the icon is drawn with an offset from the point. Only the symbols with known size are taken into
+    /// account (i.e. not dots and labels), and only after the feature was rendered at least once.
+    ///
+    /// Features are returned in the reverse order, so that the features drawn on top of others come first.
+    pub fn hit_test_markers(
+        &self,
+        screen_point: Point2d,
+        view: &MapView,
+    ) -> Vec<FeatureContainer<'_, F>> {
+        let mut features: Vec<_> = self
+            .features
+            .iter_entries()
+            .filter(|(_, entry)| !entry.is_hidden() && entry.hit_test(screen_point, view))
+            .map(|(container, _)| container)
+            .collect();
+        features.reverse();
+        features
+    }
+
     /// Returns the CRS of the layer.
     pub fn crs(&self) -> &Crs {
         &self.crs
     }
+
+    /// Returns the amount of memory used by the render buffers of the layer (all levels of detail).
+    pub fn memory_usage(&self) -> BundleMemoryUsage {
+        self.lods
+            .iter()
+            .map(|lod| lod.contents.lock().memory_usage())
+            .sum()
+    }
+
+    fn check_memory_budget(&self) {
+        let Some(budget) = self.options.memory_budget else {
+            return;
+        };
+
+        let usage = self.memory_usage();
+        if usage.total() > budget {
+            log::warn!(
+                "Feature layer memory usage ({} bytes) exceeds the budget of {budget} bytes: {usage:?}",
+                usage.total()
+            );
+        }
+    }
+
+    fn clear_packed_bundles(&self) {
+        for lod in &self.lods {
+            lod.contents.lock().clear_packed();
+        }
+    }
+}
+
+impl<P, F, S, Space> FeatureLayer<P, F, S, Space>
+where
+    F: Feature,
+    F::Geom: Geometry<Point = P>,
+{
+    /// Loads the features of the `bbox` (in the CRS of the layer) from the [backend](FeatureLayer::with_backend) of the
+    /// layer, and drops the loaded features outside of it.
+    ///
+    /// Only the features that are not loaded yet are requested from the backend. Features added to the layer
+    /// directly are never dropped. Note that the changes made to the loaded features (including hiding them) are lost
+    /// when the features are dropped.
+    ///
+    /// If the backend returns an error, the features of the layer are not changed.
+    pub fn load_area(&mut self, bbox: Rect) -> Result<(), GalileoError> {
+        let Some(backend) = &self.backend else {
+            return Err(GalileoError::Generic(
+                "feature layer has no backend".to_string(),
+            ));
+        };
+
+        let ids = backend.query_bbox(bbox)?;
+        let loaded = self.features.source_ids();
+        let to_load: Vec<_> = ids
+            .iter()
+            .copied()
+            .filter(|id| !loaded.contains(id))
+            .collect();
+        let new_features = if to_load.is_empty() {
+            vec![]
+        } else {
+            backend.load(&to_load)?
+        };
+
+        let ids: HashSet<_> = ids.into_iter().collect();
+        self.features
+            .remove_where(|entry| entry.source_id().is_some_and(|id| !ids.contains(&id)));
+        for (id, feature) in new_features {
+            self.features.insert_loaded(id, feature);
+        }
+
+        self.loaded_area = Some(bbox);
+        if let Some(messenger) = &*self.messenger.read() {
+            messenger.request_redraw();
+        }
+
+        Ok(())
+    }
+
+    /// Area last loaded with [`FeatureLayer::load_area`].
+    pub fn loaded_area(&self) -> Option<Rect> {
+        self.loaded_area
+    }
+
+    /// Updates the stored extents of the features calculated with `extent_of` and returns the extent of the layer.
+    ///
+    /// Only the features added or modified since the previous call are updated, unless the `crs` is different from
+    /// the previous call.
+    fn update_extents(&self, crs: &Crs, extent_of: impl Fn(&F) -> Option<Rect>) -> Option<Rect> {
+        let update = |entry: &FeatureEntry<F>| {
+            let extent = extent_of(entry.feature());
+            entry.set_extent(extent);
+            extent
+        };
+
+        let mut cache = self.features.extent_cache().lock();
+        if !cache.has_crs(crs) {
+            let extent = self
+                .features
+                .iter_entries()
+                .filter_map(|(_, entry)| update(entry))
+                .collect();
+            cache.reset(crs, extent);
+            return extent;
+        }
+
+        for index in cache.take_pending() {
+            let Some(entry) = self.features.get_entry(index) else {
+                continue;
+            };
+
+            let old_extent = entry.extent();
+            let extent = update(entry);
+            cache.update_indexed(index, old_extent, extent);
+            if let Some(extent) = extent {
+                cache.extend(extent);
+            }
+        }
+
+        if !cache.is_valid_for(crs) {
+            // Extents of the features are up to date, so there is no need to calculate them again
+            let extent = cache.indexed_extent().unwrap_or_else(|| {
+                self.features
+                    .iter_entries()
+                    .filter_map(|(_, entry)| entry.extent())
+                    .collect()
+            });
+            cache.revalidate(extent);
+        }
+
+        cache.extent()
+    }
+
+    /// Returns indices of the features, which stored extents intersect the `bbox`, in ascending order. The extents
+    /// must be updated with [`FeatureLayer::update_extents`] before calling this method.
+    fn features_in_extent(&self, bbox: Rect) -> Vec<usize> {
+        if self.options.spatial_index {
+            return self.features.extent_cache().lock().query(bbox, || {
+                self.features
+                    .iter_entries()
+                    .filter_map(|(container, entry)| Some((container.index(), entry.extent()?)))
+                    .collect()
+            });
+        }
+
+        self.features
+            .iter_entries()
+            .filter(|(_, entry)| entry.extent().is_some_and(|extent| extent.intersects(bbox)))
+            .map(|(container, _)| container.index())
+            .collect()
+    }
 }
 
 impl<P, F, S> FeatureLayer<P, F, S, GeoSpace2d>
@@ -194,14 +546,168 @@ where
     ///
     /// If the layer doesn't contain any features, or if at least one of them cannot be projected into the given
     /// CRS, `None` will be returned.
+    ///
+    /// The extent is cached, so repeated calls with the same CRS only project the features that were added or
+    /// modified since the previous call. Requesting the extent in a different CRS recalculates it for all features.
+    /// If the [spatial index](FeatureLayerOptions::spatial_index) is built, the extent is taken from it when the
+    /// features at the boundary of the extent are removed or changed.
     pub fn extent_projected(&self, crs: &Crs) -> Option<Rect> {
         let projection = crs.get_projection::<P, Point2d>()?;
-        self.features
-            .iter()
-            .filter_map(|f| f.as_ref().geometry().project(&*projection))
-            .filter_map(|g| g.bounding_rectangle())
+        self.update_extents(crs, |feature| {
+            feature
+                .geometry()
+                .project(&*projection)
+                .and_then(|g| g.bounding_rectangle())
+        })
+    }
+
+    /// Returns all visible features, which bounding rectangles projected into the given CRS intersect the `bbox`.
+    ///
+    /// The projected extents of the features are shared with [`FeatureLayer::extent_projected`], so repeated queries
+    /// in the same CRS only project the features that were added or modified since the previous call. This makes it
+    /// cheap to get the features of a single area (e.g. the visible part of the map or a tile) from a large layer.
+    /// With the [spatial index](FeatureLayerOptions::spatial_index) the features are found without checking every
+    /// feature of the layer.
+    ///
+    /// Only the bounding rectangles are compared, so the result may include features which geometries do not
+    /// intersect the `bbox`. Use [`FeatureLayer::get_features_in_rect`] to check the geometries too.
+    pub fn get_features_in_bbox(&self, bbox: Rect, crs: &Crs) -> Vec<FeatureContainer<'_, F>> {
+        self.extent_projected(crs);
+        if !self.features.extent_cache().lock().is_valid_for(crs) {
+            // Features cannot be projected into the CRS, so the stored extents are not relevant
+            return vec![];
+        }
+
+        self.features_in_extent(bbox)
+            .into_iter()
+            .filter_map(|index| self.features.get_with_entry(index))
+            .filter(|(_, entry)| !entry.is_hidden())
+            .map(|(container, _)| container)
             .collect()
     }
+
+    /// Returns indices of all visible features, which geometries projected into the given CRS intersect or are
+    /// contained by the `polygon`, depending on the `mode`. Use it for lasso selection or spatial filtering.
+    ///
+    /// Only the features which bounding rectangles intersect the polygon are checked (see
+    /// [`FeatureLayer::get_features_in_bbox`]).
+    pub fn get_features_in_polygon(
+        &self,
+        polygon: &Polygon<Point2d>,
+        crs: &Crs,
+        mode: SelectionMode,
+    ) -> Vec<usize> {
+        let Some(bbox) = polygon.bounding_rectangle() else {
+            return vec![];
+        };
+        let Some(projection) = crs.get_projection::<P, Point2d>() else {
+            return vec![];
+        };
+
+        self.get_features_in_bbox(bbox, crs)
+            .into_iter()
+            .filter(|feature| {
+                feature
+                    .as_ref()
+                    .geometry()
+                    .project(&*projection)
+                    .is_some_and(|geometry| mode.matches(polygon, &geometry))
+            })
+            .map(|feature| feature.index())
+            .collect()
+    }
+
+    /// Returns indices of all visible features, which geometries projected into the given CRS intersect or are
+    /// contained by the `rect`, depending on the `mode`. Use it for box selection.
+    pub fn get_features_in_rect(&self, rect: Rect, crs: &Crs, mode: SelectionMode) -> Vec<usize> {
+        self.get_features_in_polygon(&rect.into_contour().into(), crs, mode)
+    }
+
+    /// Loads the features around the visible area of the `view` from the [backend](FeatureLayer::with_backend) of the
+    /// layer. See [`FeatureLayer::load_area`] for details.
+    ///
+    /// The features are loaded for the area around the view, which is larger than the view by `margin` of its size in
+    /// each direction, so small movements of the map do not require loading new features. Returns `false` without
+    /// requesting the backend if the view is still inside the [last loaded area](FeatureLayer::loaded_area). Call this
+    /// method after the view of the map is changed, e.g. from an event handler.
+    pub fn load_view(&mut self, view: &MapView, margin: f64) -> Result<bool, GalileoError> {
+        let Some(view_bbox) = view.get_bbox() else {
+            return Ok(false);
+        };
+        let Some(projection) = view.crs().get_projection::<GeoPoint2d, Point2d>() else {
+            return Err(GalileoError::Generic(format!(
+                "view CRS {:?} does not support geographic coordinates",
+                view.crs()
+            )));
+        };
+
+        let corners: Option<Vec<_>> = rect_corners(view_bbox)
+            .iter()
+            .map(|corner| {
+                projection
+                    .unproject(corner)
+                    .map(|point| Point2d::new(point.lon(), point.lat()))
+            })
+            .collect();
+        let Some(bbox) = corners.and_then(|corners| Rect::from_points(corners.iter())) else {
+            return Ok(false);
+        };
+
+        if self
+            .loaded_area
+            .is_some_and(|area| contains_rect(area, bbox))
+        {
+            return Ok(false);
+        }
+
+        self.load_area(bbox.magnify(1.0 + 2.0 * margin.max(0.0)))?;
+        Ok(true)
+    }
+
+    /// Extent of all the features of the backend of the layer, projected into the given CRS.
+    fn backend_extent_projected(&self, crs: &Crs) -> Option<Rect> {
+        let extent = self.backend.as_ref()?.extent()?;
+        let projection = crs.get_projection::<GeoPoint2d, Point2d>()?;
+        let corners: Option<Vec<_>> = rect_corners(extent)
+            .iter()
+            .map(|corner| projection.project(&GeoPoint2d::latlon(corner.y(), corner.x())))
+            .collect();
+
+        Rect::from_points(corners?.iter())
+    }
+}
+
+#[cfg(feature = "geo-types")]
+impl<P, S> FeatureLayer<P, geo_types::Geometry<f64>, S, GeoSpace2d>
+where
+    geo_types::Geometry<f64>: Geometry<Point = P>,
+    S: Symbol<geo_types::Geometry<f64>>,
+{
+    /// Creates a new layer from a list of `geo-types` geometries.
+    ///
+    /// Coordinates of the geometries are treated as longitude (`x`) and latitude (`y`) in WGS84 CRS.
+    pub fn from_geo_types(geometries: Vec<geo_types::Geometry<f64>>, style: S) -> Self {
+        Self::new(geometries, style, Crs::WGS84)
+    }
+}
+
+#[cfg(feature = "geojson")]
+impl<P, S> FeatureLayer<P, geojson::Feature, S, GeoSpace2d>
+where
+    geojson::Geometry: Geometry<Point = P>,
+    S: Symbol<geojson::Feature>,
+{
+    /// Creates a new layer from a GeoJSON feature collection.
+    ///
+    /// GeoJSON geometries are always in WGS84 CRS, so the layer uses it. Features without geometry are skipped.
+    pub fn from_geojson(collection: geojson::FeatureCollection, style: S) -> Self {
+        let features = collection
+            .features
+            .into_iter()
+            .filter(|feature| feature.geometry.is_some())
+            .collect();
+        Self::new(features, style, Crs::WGS84)
+    }
 }
 
 impl<P, F, S> FeatureLayer<P, F, S, CartesianSpace2d>
@@ -215,7 +721,8 @@ where
     ///
     /// At this moment this method just iterates over all features checking for each one if it is at the point. But
     /// in future it may be changed into using geo-index to make this more efficient. So this method should be preferred
-    /// to manually checking every feature.
+    /// to manually checking every feature. [`FeatureLayer::get_features_at_screen_point`] uses the
+    /// [spatial index](FeatureLayerOptions::spatial_index) if it is enabled.
     pub fn get_features_at<'a>(
         &'a self,
         point: &'a impl CartesianPoint2d<Num = P::Num>,
@@ -232,9 +739,7 @@ where
     /// Returns a mutable iterator of features that are within `tolerance` units from the `point`. Note that the `point` is
     /// expected to be set in the layer's CRS.
     ///
-    /// At this moment this method just iterates over all features checking for each one if it is at the point. But
-    /// in future it may be changed into using geo-index to make this more efficient. So this method should be preferred
-    /// to manually checking every feature.
+    /// See [`FeatureLayer::get_features_at`] for details.
     pub fn get_features_at_mut<'a>(
         &'a mut self,
         point: &'a impl CartesianPoint2d<Num = P::Num>,
@@ -247,6 +752,202 @@ where
             .iter_mut()
             .filter(move |f| f.as_ref().geometry().is_point_inside(point, tolerance))
     }
+
+    /// Returns indices of the features, which bounding rectangles are within `tolerance` from the `point`, using the
+    /// spatial index. Returns `None` if the spatial index is not enabled for the layer.
+    fn indexed_candidates(
+        &self,
+        point: &impl CartesianPoint2d<Num = P::Num>,
+        tolerance: P::Num,
+    ) -> Option<Vec<usize>>
+    where
+        F::Geom: CartesianGeometry2d<P>,
+        P::Num: AsPrimitive<f64>,
+    {
+        if !self.options.spatial_index {
+            return None;
+        }
+
+        self.update_extents(&self.crs, |feature| {
+            let rect = feature.geometry().bounding_rectangle()?;
+            Some(Rect::new(
+                rect.x_min().as_(),
+                rect.y_min().as_(),
+                rect.x_max().as_(),
+                rect.y_max().as_(),
+            ))
+        });
+
+        let (x, y, tolerance) = (point.x().as_(), point.y().as_(), tolerance.as_());
+        Some(self.features_in_extent(Rect::new(
+            x - tolerance,
+            y - tolerance,
+            x + tolerance,
+            y + tolerance,
+        )))
+    }
+}
+
+/// Returns true if the feature is in the sorted list of `candidates`, or if there is no such list.
+fn is_candidate(candidates: Option<&[usize]>, feature_index: usize) -> bool {
+    match candidates {
+        Some(candidates) => candidates.binary_search(&feature_index).is_ok(),
+        None => true,
+    }
+}
+
+impl<P, F, S> FeatureLayer<P, F, S, CartesianSpace2d>
+where
+    P: NewCartesianPoint2d + 'static,
+    F: Feature,
+    F::Geom: Geometry<Point = P>,
+{
+    /// Returns indices of all visible features that intersect or are contained by the `polygon`, depending on the
+    /// `mode`. Use it for lasso selection or spatial filtering. Note that the `polygon` is expected to be set in the
+    /// layer's CRS.
+    ///
+    /// With the [spatial index](FeatureLayerOptions::spatial_index) only the features which bounding rectangles
+    /// intersect the polygon are checked.
+    pub fn get_features_in_polygon(
+        &self,
+        polygon: &Polygon<Point2d>,
+        mode: SelectionMode,
+    ) -> Vec<usize>
+    where
+        F::Geom: CartesianGeometry2d<P>,
+    {
+        let Some(bbox) = polygon.bounding_rectangle() else {
+            return vec![];
+        };
+
+        let candidates = self.options.spatial_index.then(|| {
+            self.update_extents(&self.crs, |feature| feature.geometry().bounding_rectangle());
+            self.features_in_extent(bbox)
+        });
+
+        let projection = IdentityProjection::<P, Point2d, CartesianSpace2d>::new();
+        self.features
+            .iter_entries()
+            .filter(|(container, entry)| {
+                !entry.is_hidden() && is_candidate(candidates.as_deref(), container.index())
+            })
+            .filter(|(container, _)| {
+                container
+                    .as_ref()
+                    .geometry()
+                    .project(&projection)
+                    .is_some_and(|geometry| mode.matches(polygon, &geometry))
+            })
+            .map(|(container, _)| container.index())
+            .collect()
+    }
+
+    /// Returns indices of all visible features that intersect or are contained by the `rect`, depending on the
+    /// `mode`. Use it for box selection. Note that the `rect` is expected to be set in the layer's CRS.
+    pub fn get_features_in_rect(&self, rect: Rect, mode: SelectionMode) -> Vec<usize>
+    where
+        F::Geom: CartesianGeometry2d<P>,
+    {
+        self.get_features_in_polygon(&rect.into_contour().into(), mode)
+    }
+}
+
+impl<P, F, S> FeatureLayer<P, F, S, CartesianSpace2d>
+where
+    P: CartesianPoint2d<Num = f64>,
+    F: Feature,
+    F::Geom: Geometry<Point = P>,
+{
+    /// Returns features that are within `tolerance_px` logical pixels from the given point on the screen.
+    ///
+    /// The tolerance is converted into map units with [`MapView::screen_distance_to_map`], so it takes into account
+    /// the resolution, tilt and DPI scale factor of the view. The CRS of the layer is expected to be the same as the
+    /// CRS of the `view`.
+    pub fn get_features_at_screen_point(
+        &self,
+        screen_point: Point2d,
+        view: &MapView,
+        tolerance_px: f64,
+    ) -> Vec<FeatureContainer<'_, F>>
+    where
+        F::Geom: CartesianGeometry2d<P>,
+    {
+        let Some((point, tolerance)) = screen_hit_area(screen_point, view, tolerance_px) else {
+            return vec![];
+        };
+
+        let candidates = self.indexed_candidates(&point, tolerance);
+        self.features
+            .iter()
+            .filter(|f| {
+                is_candidate(candidates.as_deref(), f.index())
+                    && f.as_ref().geometry().is_point_inside(&point, tolerance)
+            })
+            .collect()
+    }
+
+    /// Mutable version of [`FeatureLayer::get_features_at_screen_point`].
+    pub fn get_features_at_screen_point_mut(
+        &mut self,
+        screen_point: Point2d,
+        view: &MapView,
+        tolerance_px: f64,
+    ) -> Vec<FeatureContainerMut<'_, F>>
+    where
+        F::Geom: CartesianGeometry2d<P>,
+    {
+        let Some((point, tolerance)) = screen_hit_area(screen_point, view, tolerance_px) else {
+            return vec![];
+        };
+
+        let candidates = self.indexed_candidates(&point, tolerance);
+        self.features
+            .iter_mut()
+            .filter(|f| {
+                is_candidate(candidates.as_deref(), f.index())
+                    && f.as_ref().geometry().is_point_inside(&point, tolerance)
+            })
+            .collect()
+    }
+}
+
+fn hit_regions(
+    primitives: &[RenderPrimitive<'_, f64, Point3d, Contour<Point3d>, Polygon<Point3d>>],
+) -> Vec<HitRegion> {
+    primitives
+        .iter()
+        .filter_map(|primitive| match primitive {
+            RenderPrimitive::Point(point, paint) => HitRegion::new(**point, paint),
+            _ => None,
+        })
+        .collect()
+}
+
+fn rect_corners(rect: Rect) -> [Point2d; 4] {
+    [
+        Point2d::new(rect.x_min(), rect.y_min()),
+        Point2d::new(rect.x_min(), rect.y_max()),
+        Point2d::new(rect.x_max(), rect.y_min()),
+        Point2d::new(rect.x_max(), rect.y_max()),
+    ]
+}
+
+/// Returns true if the `inner` rectangle is fully inside the `outer` one.
+fn contains_rect(outer: Rect, inner: Rect) -> bool {
+    outer.x_min() <= inner.x_min()
+        && outer.y_min() <= inner.y_min()
+        && outer.x_max() >= inner.x_max()
+        && outer.y_max() >= inner.y_max()
+}
+
+fn screen_hit_area(
+    screen_point: Point2d,
+    view: &MapView,
+    tolerance_px: f64,
+) -> Option<(Point2d, f64)> {
+    let point = view.screen_to_map(screen_point)?;
+    let tolerance = view.screen_distance_to_map(screen_point, tolerance_px)?;
+    Some((point, tolerance))
 }
 
 impl<P, F, S, Space> FeatureLayer<P, F, S, Space>
@@ -267,27 +968,116 @@ where
         &self.lods[self.lods.len() - 1].contents
     }
 
+    /// Renders the layer. If `view_bbox` is set, only the features with projected extents intersecting the area
+    /// around it are rendered (see [`FeatureLayerOptions::view_culling_margin`]).
     fn render_with_projection<Proj: Projection<InPoint = P, OutPoint = Point3d> + ?Sized>(
         &self,
         view: &MapView,
         canvas: &mut dyn Canvas,
         projection: impl Deref<Target = Proj>,
+        view_bbox: Option<Rect>,
     ) {
         let updates = self.features.drain_updates();
         if !updates.is_empty() {
-            self.update_feature_renders(canvas, projection, &updates);
+            self.update_feature_renders(canvas, &*projection, &updates);
+            self.check_memory_budget();
         }
 
-        let lod = self.select_lod(view.resolution()).lock();
+        let lod = self.select_lod(view.resolution());
+
+        if let Some(margin) = self.options.view_culling_margin {
+            let mut contents = lod.lock();
+            // Without the view bbox (e.g. for a strongly tilted view) all the features are rendered
+            let is_up_to_date = match (contents.render_area(), view_bbox) {
+                (Some(area), Some(view_bbox)) => contains_rect(area, view_bbox),
+                (None, None) => true,
+                _ => false,
+            };
+
+            if !is_up_to_date {
+                let area =
+                    view_bbox.map(|view_bbox| view_bbox.magnify(1.0 + 2.0 * margin.max(0.0)));
+                self.update_render_area(canvas, &*projection, &mut contents, area);
+                drop(contents);
+                self.check_memory_budget();
+            }
+        }
+
+        let mut lod = lod.lock();
+        // Bundles are packed again after the render cache is cleared
+        lod.pack(canvas);
 
         canvas.draw_bundles(
             &lod.bundles(),
             RenderOptions {
                 antialias: self.options.use_antialiasing,
+                screen_ref_order: self.options.screen_ref_order,
+                ..Default::default()
             },
         );
     }
 
+    /// Renders the features that are inside the new render area of the level of detail, and removes the renders of
+    /// the features outside of it. If the `area` is `None`, all the features are rendered.
+    fn update_render_area<Proj: Projection<InPoint = P, OutPoint = Point3d> + ?Sized>(
+        &self,
+        canvas: &dyn Canvas,
+        projection: &Proj,
+        lod: &mut FeatureRenderStore,
+        area: Option<Rect>,
+    ) {
+        lod.set_render_area(area);
+        let admitted = self.select_dense_features(projection, lod);
+        self.sync_renders(canvas, projection, lod, admitted.as_ref());
+
+        lod.pack(canvas);
+    }
+
+    /// Renders the features that must be drawn in the level of detail but are not rendered yet, and removes the
+    /// renders of the features that must not be drawn anymore. Features that are not in `admitted` set of the
+    /// density limit are not drawn.
+    fn sync_renders<Proj: Projection<InPoint = P, OutPoint = Point3d> + ?Sized>(
+        &self,
+        canvas: &dyn Canvas,
+        projection: &Proj,
+        lod: &mut FeatureRenderStore,
+        admitted: Option<&HashSet<usize>>,
+    ) {
+        for (container, feature_entry) in self.features.iter_entries() {
+            if feature_entry.is_hidden() {
+                continue;
+            }
+
+            let should_render = self.should_render(feature_entry, lod)
+                && admitted.is_none_or(|admitted| admitted.contains(&container.index()));
+            match feature_entry.render_index(lod.id()) {
+                Some(render_index) if !should_render => {
+                    lod.remove_render(render_index);
+                    feature_entry.clear_render_index(lod.id());
+                }
+                None if should_render => {
+                    lod.init_bundle(|| canvas.create_bundle());
+                    self.render_feature(feature_entry, projection, lod);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Returns false if the feature is outside of the render area of the level of detail with view culling enabled.
+    /// If the level of detail has no render area, all the features are rendered.
+    fn should_render(&self, feature_entry: &FeatureEntry<F>, lod: &FeatureRenderStore) -> bool {
+        if self.options.view_culling_margin.is_none() {
+            return true;
+        }
+
+        match (lod.render_area(), feature_entry.extent()) {
+            (Some(area), Some(extent)) => extent.intersects(area),
+            (Some(_), None) => false,
+            (None, _) => true,
+        }
+    }
+
     fn update_feature_renders<Proj: Projection<InPoint = P, OutPoint = Point3d> + ?Sized>(
         &self,
         canvas: &dyn Canvas,
@@ -311,6 +1101,7 @@ where
 
         for lod in &self.lods {
             let mut lod = lod.contents.lock();
+            let admitted = self.select_dense_features(&*projection, &lod);
 
             for update in updates {
                 lod.init_bundle(|| canvas.create_bundle());
@@ -322,11 +1113,29 @@ where
                             continue;
                         };
 
-                        if let Some(render_index) = feature_entry.render_index(lod.id()) {
-                            lod.remove_render(render_index);
-                        }
+                        self.rerender_feature(feature_entry, &*projection, &mut lod);
+                    }
+                    FeatureUpdate::Move { feature_index } => {
+                        let Some(feature_entry) = self.features.get_entry(*feature_index) else {
+                            log::warn!("Feature {feature_index} is not present in the store");
+                            continue;
+                        };
 
-                        self.render_feature(feature_entry, &*projection, &mut lod);
+                        let is_moved =
+                            feature_entry
+                                .render_index(lod.id())
+                                .is_some_and(|render_index| {
+                                    self.should_render(feature_entry, &lod)
+                                        && self.move_feature_render(
+                                            feature_entry,
+                                            &*projection,
+                                            render_index,
+                                            &mut lod,
+                                        )
+                                });
+                        if !is_moved {
+                            self.rerender_feature(feature_entry, &*projection, &mut lod);
+                        }
                     }
                     FeatureUpdate::UpdateStyle { feature_index } => {
                         let Some(feature_entry) = self.features.get_entry(*feature_index) else {
@@ -335,22 +1144,56 @@ where
                         };
 
                         if let Some(render_index) = feature_entry.render_index(lod.id()) {
-                            self.update_feature(
-                                feature_entry.feature(),
+                            if !self.update_feature(
+                                feature_entry,
                                 &*projection,
                                 render_index,
                                 &mut lod,
-                            );
+                            ) {
+                                self.rerender_feature(feature_entry, &*projection, &mut lod);
+                            }
                         }
                     }
                     _ => {}
                 }
             }
 
+            if admitted.is_some() {
+                self.sync_renders(canvas, &*projection, &mut lod, admitted.as_ref());
+            }
+
             lod.pack(canvas);
         }
     }
 
+    /// Returns the indices of the features to draw in the level of detail, if the density limit applies to it.
+    fn select_dense_features<Proj: Projection<InPoint = P, OutPoint = Point3d> + ?Sized>(
+        &self,
+        projection: &Proj,
+        lod: &FeatureRenderStore,
+    ) -> Option<HashSet<usize>> {
+        let (limit, priority) = self.density.as_ref()?;
+        if !limit.applies_to(lod.min_resolution()) {
+            return None;
+        }
+
+        let candidates = self
+            .features
+            .iter_entries()
+            .filter(|(_, entry)| !entry.is_hidden())
+            .filter_map(|(container, entry)| {
+                let projected: Geom<Point3d> = entry.feature().geometry().project(projection)?;
+                let anchor = density::anchor_point(&projected)?;
+                Some((
+                    container.index(),
+                    anchor,
+                    priority.priority(entry.feature()),
+                ))
+            });
+
+        Some(limit.select(candidates, lod.min_resolution()))
+    }
+
     fn render_feature<Proj: Projection<InPoint = P, OutPoint = Point3d> + ?Sized>(
         &self,
         feature_entry: &FeatureEntry<F>,
@@ -365,25 +1208,106 @@ where
         let primitives = self
             .symbol
             .render(feature, &projected, lod.min_resolution());
-        let index = lod.add_primitives(primitives);
+        feature_entry.set_hit_regions(hit_regions(&primitives));
+        let index = lod.add_primitives(self.apply_options(primitives));
         feature_entry.set_render_index(index, lod.id());
     }
 
+    /// Removes the render of the feature and renders it again if it is inside the render area.
+    fn rerender_feature<Proj: Projection<InPoint = P, OutPoint = Point3d> + ?Sized>(
+        &self,
+        feature_entry: &FeatureEntry<F>,
+        projection: &Proj,
+        lod: &mut FeatureRenderStore,
+    ) {
+        if let Some(render_index) = feature_entry.render_index(lod.id()) {
+            lod.remove_render(render_index);
+            feature_entry.clear_render_index(lod.id());
+        }
+
+        if self.should_render(feature_entry, lod) {
+            self.render_feature(feature_entry, projection, lod);
+        }
+    }
+
+    /// Moves the point symbols of the feature to its new position. Returns false if the render of the feature
+    /// cannot be moved and must be rendered again.
+    fn move_feature_render<Proj: Projection<InPoint = P, OutPoint = Point3d> + ?Sized>(
+        &self,
+        feature_entry: &FeatureEntry<F>,
+        projection: &Proj,
+        render_index: usize,
+        lod: &mut FeatureRenderStore,
+    ) -> bool {
+        let feature = feature_entry.feature();
+        let Some(projected): Option<Geom<Point3d>> = feature.geometry().project(projection) else {
+            return false;
+        };
+
+        let primitives = self
+            .symbol
+            .render(feature, &projected, lod.min_resolution());
+        feature_entry.set_hit_regions(hit_regions(&primitives));
+        lod.move_renders(render_index, self.apply_options(primitives))
+    }
+
+    /// Updates the style of the feature render in place. Returns false if the render of the feature cannot be
+    /// updated and must be rendered again.
     fn update_feature<Proj: Projection<InPoint = P, OutPoint = Point3d> + ?Sized>(
         &self,
-        feature: &F,
+        feature_entry: &FeatureEntry<F>,
         projection: &Proj,
         render_index: usize,
         lod: &mut FeatureRenderStore,
-    ) {
+    ) -> bool {
+        let feature = feature_entry.feature();
         let Some(projected): Option<Geom<Point3d>> = feature.geometry().project(projection) else {
-            return;
+            return false;
         };
 
         let primitives = self
             .symbol
             .render(feature, &projected, lod.min_resolution());
-        lod.update_renders(render_index, primitives);
+        feature_entry.set_hit_regions(hit_regions(&primitives));
+        lod.update_renders(render_index, self.apply_options(primitives))
+    }
+
+    /// Applies the layer-wide options (lighting and crisp lines) to the primitives created by the symbol.
+    fn apply_options<'a>(
+        &self,
+        mut primitives: Vec<RenderPrimitive<'a, f64, Point3d, Contour<Point3d>, Polygon<Point3d>>>,
+    ) -> Vec<RenderPrimitive<'a, f64, Point3d, Contour<Point3d>, Polygon<Point3d>>> {
+        if self.options.lighting.is_none() && !self.options.crisp_lines {
+            return primitives;
+        }
+
+        for primitive in &mut primitives {
+            match primitive {
+                RenderPrimitive::Polygon(polygon, paint) => {
+                    if let Some(lighting) = &self.options.lighting {
+                        paint.color = lighting.shade_polygon(paint.color, &**polygon);
+                    }
+                }
+                RenderPrimitive::ColoredPolygon(polygon, paint, colors) => {
+                    let Some(lighting) = &self.options.lighting else {
+                        continue;
+                    };
+                    if let Some(normal) = polygon_normal(&**polygon) {
+                        paint.color = lighting.shade(paint.color, &normal);
+                        for color in colors.to_mut() {
+                            *color = lighting.shade(*color, &normal);
+                        }
+                    }
+                }
+                RenderPrimitive::Contour(_, paint)
+                | RenderPrimitive::ColoredContour(_, paint, _) => {
+                    paint.crisp |= self.options.crisp_lines;
+                }
+                _ => {}
+            }
+        }
+
+        primitives
     }
 }
 
@@ -416,13 +1340,36 @@ where
         let Some(projection) = self.get_projection(view.crs()) else {
             return;
         };
-        self.render_with_projection(view, canvas, &projection);
+
+        let view_bbox = match self.options.view_culling_margin {
+            Some(_) => {
+                // Updates the projected extents of the features used to select the features to render
+                self.extent_projected(view.crs());
+                view.get_bbox()
+            }
+            None => None,
+        };
+
+        self.render_with_projection(view, canvas, &projection, view_bbox);
     }
 
     fn prepare(&self, _view: &MapView) {
         // do nothing
     }
 
+    fn clear_render_cache(&self) {
+        self.clear_packed_bundles();
+    }
+
+    fn extent(&self, crs: &Crs) -> Option<Rect> {
+        // Features of the backend that are not loaded yet are included too
+        let extent = self.extent_projected(crs);
+        match (extent, self.backend_extent_projected(crs)) {
+            (Some(extent), Some(backend_extent)) => Some(extent.merge(backend_extent)),
+            (extent, backend_extent) => extent.or(backend_extent),
+        }
+    }
+
     fn set_messenger(&mut self, messenger: Box<dyn Messenger>) {
         *self.messenger.write() = Some(messenger);
     }
@@ -475,13 +1422,17 @@ where
         let Some(projection) = self.get_projection(view.crs()) else {
             return;
         };
-        self.render_with_projection(view, canvas, projection);
+        self.render_with_projection(view, canvas, projection, None);
     }
 
     fn prepare(&self, _view: &MapView) {
         // do nothing
     }
 
+    fn clear_render_cache(&self) {
+        self.clear_packed_bundles();
+    }
+
     fn set_messenger(&mut self, messenger: Box<dyn Messenger>) {
         *self.messenger.write() = Some(messenger);
     }
@@ -523,13 +1474,17 @@ where
         }
 
         let projection = self.get_projection();
-        self.render_with_projection(view, canvas, &projection);
+        self.render_with_projection(view, canvas, &projection, None);
     }
 
     fn prepare(&self, _view: &MapView) {
         // do nothing
     }
 
+    fn clear_render_cache(&self) {
+        self.clear_packed_bundles();
+    }
+
     fn set_messenger(&mut self, messenger: Box<dyn Messenger>) {
         *self.messenger.write() = Some(messenger);
     }
@@ -542,3 +1497,92 @@ where
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use galileo_types::cartesian::Size;
+
+    use super::*;
+    use crate::layer::feature_layer::symbol::CirclePointSymbol;
+    use crate::render::render_bundle::tessellating::TessellatingRenderBundle;
+    use crate::render::render_bundle::{RenderBundle, RenderBundleType};
+    use crate::render::PackedBundle;
+    use crate::Color;
+
+    /// Canvas that counts drawn bundles with primitives in them.
+    #[derive(Default)]
+    struct TestCanvas {
+        drawn: AtomicUsize,
+    }
+
+    struct TestPackedBundle {
+        is_empty: bool,
+    }
+
+    impl PackedBundle for TestPackedBundle {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    impl Canvas for TestCanvas {
+        fn size(&self) -> Size {
+            Size::new(100.0, 100.0)
+        }
+
+        fn create_bundle(&self) -> RenderBundle {
+            RenderBundle(RenderBundleType::Tessellating(
+                TessellatingRenderBundle::new(),
+            ))
+        }
+
+        fn pack_bundle(&self, bundle: &RenderBundle) -> Box<dyn PackedBundle> {
+            Box::new(TestPackedBundle {
+                is_empty: bundle.is_empty(),
+            })
+        }
+
+        fn draw_bundles(&mut self, bundles: &[&dyn PackedBundle], _options: RenderOptions) {
+            let drawn = bundles
+                .iter()
+                .filter_map(|bundle| bundle.as_any().downcast_ref::<TestPackedBundle>())
+                .filter(|bundle| !bundle.is_empty)
+                .count();
+            self.drawn.fetch_add(drawn, Ordering::Relaxed);
+        }
+
+        fn draw_bundles_with_opacity(
+            &mut self,
+            bundles: &[(&dyn PackedBundle, f32)],
+            options: RenderOptions,
+        ) {
+            let bundles: Vec<_> = bundles.iter().map(|(bundle, _)| *bundle).collect();
+            self.draw_bundles(&bundles, options);
+        }
+    }
+
+    #[test]
+    fn culling_without_render_area_renders_all_features() {
+        let layer = FeatureLayer::<_, _, _, CartesianSpace2d>::new(
+            vec![Point2d::new(0.0, 0.0), Point2d::new(1000.0, 1000.0)],
+            CirclePointSymbol::new(Color::BLACK, 1.0),
+            Crs::EPSG3857,
+        )
+        .with_options(FeatureLayerOptions {
+            view_culling_margin: Some(0.5),
+            ..Default::default()
+        });
+
+        let view =
+            MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0).with_size(Size::new(100.0, 100.0));
+        let mut canvas = TestCanvas::default();
+        layer.render(&view, &mut canvas);
+
+        assert_eq!(canvas.drawn.load(Ordering::Relaxed), 1);
+        for (_, entry) in layer.features.iter_entries() {
+            assert!(entry.render_index(0).is_some());
+        }
+    }
+}
@@ -0,0 +1,309 @@
+//! [`Properties`] give generic access to the attributes of a feature.
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
+use std::hash::BuildHasher;
+
+use galileo_mvt::{MvtFeature, MvtValue};
+
+/// Value of a single feature property.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyValue<'a> {
+    /// Property is set, but has no value.
+    Null,
+    /// Boolean value.
+    Bool(bool),
+    /// Signed integer value.
+    Int(i64),
+    /// Unsigned integer value.
+    UInt(u64),
+    /// Floating point value.
+    Float(f64),
+    /// String value.
+    String(Cow<'a, str>),
+}
+
+impl PropertyValue<'_> {
+    /// Returns true if the value is [`PropertyValue::Null`].
+    pub fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+
+    /// Returns the value as a number, if it is numeric.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Int(v) => Some(*v as f64),
+            Self::UInt(v) => Some(*v as f64),
+            Self::Float(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as a string slice, if it is a string.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as a boolean, if it is a boolean.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Converts the value into an owned value not bound to the lifetime of the feature.
+    pub fn into_owned(self) -> PropertyValue<'static> {
+        match self {
+            Self::Null => PropertyValue::Null,
+            Self::Bool(v) => PropertyValue::Bool(v),
+            Self::Int(v) => PropertyValue::Int(v),
+            Self::UInt(v) => PropertyValue::UInt(v),
+            Self::Float(v) => PropertyValue::Float(v),
+            Self::String(v) => PropertyValue::String(Cow::Owned(v.into_owned())),
+        }
+    }
+}
+
+impl Display for PropertyValue<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Null => Ok(()),
+            Self::Bool(v) => write!(f, "{v}"),
+            Self::Int(v) => write!(f, "{v}"),
+            Self::UInt(v) => write!(f, "{v}"),
+            Self::Float(v) => write!(f, "{v}"),
+            Self::String(v) => write!(f, "{v}"),
+        }
+    }
+}
+
+macro_rules! impl_from_value {
+    ($t:ty, $variant:ident) => {
+        impl From<&$t> for PropertyValue<'_> {
+            fn from(value: &$t) -> Self {
+                Self::$variant((*value).into())
+            }
+        }
+    };
+}
+
+impl_from_value!(bool, Bool);
+impl_from_value!(i32, Int);
+impl_from_value!(i64, Int);
+impl_from_value!(u32, UInt);
+impl_from_value!(u64, UInt);
+impl_from_value!(f32, Float);
+impl_from_value!(f64, Float);
+
+impl<'a> From<&'a String> for PropertyValue<'a> {
+    fn from(value: &'a String) -> Self {
+        Self::String(Cow::Borrowed(value))
+    }
+}
+
+impl<'a> From<&'a str> for PropertyValue<'a> {
+    fn from(value: &'a str) -> Self {
+        Self::String(Cow::Borrowed(value))
+    }
+}
+
+impl<'a> From<&'a PropertyValue<'_>> for PropertyValue<'a> {
+    fn from(value: &'a PropertyValue<'_>) -> Self {
+        match value {
+            PropertyValue::String(v) => Self::String(Cow::Borrowed(v)),
+            PropertyValue::Null => Self::Null,
+            PropertyValue::Bool(v) => Self::Bool(*v),
+            PropertyValue::Int(v) => Self::Int(*v),
+            PropertyValue::UInt(v) => Self::UInt(*v),
+            PropertyValue::Float(v) => Self::Float(*v),
+        }
+    }
+}
+
+impl<'a> From<&'a MvtValue> for PropertyValue<'a> {
+    fn from(value: &'a MvtValue) -> Self {
+        match value {
+            MvtValue::String(v) => Self::String(Cow::Borrowed(v)),
+            MvtValue::Float(v) => Self::Float(*v as f64),
+            MvtValue::Double(v) => Self::Float(*v),
+            MvtValue::Int64(v) => Self::Int(*v),
+            MvtValue::Uint64(v) => Self::UInt(*v),
+            MvtValue::Bool(v) => Self::Bool(*v),
+            MvtValue::Unknown => Self::Null,
+        }
+    }
+}
+
+/// Key-value access to the attributes of a feature.
+///
+/// Implementing this trait for a feature type allows generic UI components (attribute tables, popups, inspectors) to
+/// display feature attributes without knowing the concrete type of the feature.
+pub trait Properties {
+    /// Returns the value of the property with the given key, or `None` if the feature does not have such property.
+    fn property(&self, key: &str) -> Option<PropertyValue<'_>>;
+
+    /// Iterates over all properties of the feature as `(key, value)` pairs.
+    fn iter_properties(&self) -> Box<dyn Iterator<Item = (&str, PropertyValue<'_>)> + '_>;
+}
+
+impl<V, S> Properties for HashMap<String, V, S>
+where
+    for<'a> &'a V: Into<PropertyValue<'a>>,
+    S: BuildHasher,
+{
+    fn property(&self, key: &str) -> Option<PropertyValue<'_>> {
+        self.get(key).map(|v| v.into())
+    }
+
+    fn iter_properties(&self) -> Box<dyn Iterator<Item = (&str, PropertyValue<'_>)> + '_> {
+        Box::new(self.iter().map(|(k, v)| (k.as_str(), v.into())))
+    }
+}
+
+impl<V> Properties for BTreeMap<String, V>
+where
+    for<'a> &'a V: Into<PropertyValue<'a>>,
+{
+    fn property(&self, key: &str) -> Option<PropertyValue<'_>> {
+        self.get(key).map(|v| v.into())
+    }
+
+    fn iter_properties(&self) -> Box<dyn Iterator<Item = (&str, PropertyValue<'_>)> + '_> {
+        Box::new(self.iter().map(|(k, v)| (k.as_str(), v.into())))
+    }
+}
+
+impl Properties for MvtFeature {
+    fn property(&self, key: &str) -> Option<PropertyValue<'_>> {
+        self.properties.property(key)
+    }
+
+    fn iter_properties(&self) -> Box<dyn Iterator<Item = (&str, PropertyValue<'_>)> + '_> {
+        self.properties.iter_properties()
+    }
+}
+
+#[cfg(feature = "serde_json")]
+mod json {
+    use serde_json::{Map, Value};
+
+    use super::*;
+
+    impl<'a> From<&'a Value> for PropertyValue<'a> {
+        fn from(value: &'a Value) -> Self {
+            match value {
+                Value::Null => Self::Null,
+                Value::Bool(v) => Self::Bool(*v),
+                Value::Number(v) => {
+                    if let Some(v) = v.as_i64() {
+                        Self::Int(v)
+                    } else if let Some(v) = v.as_u64() {
+                        Self::UInt(v)
+                    } else {
+                        Self::Float(v.as_f64().unwrap_or(f64::NAN))
+                    }
+                }
+                Value::String(v) => Self::String(Cow::Borrowed(v)),
+                // Nested values are displayed as their JSON representation.
+                Value::Array(_) | Value::Object(_) => Self::String(Cow::Owned(value.to_string())),
+            }
+        }
+    }
+
+    impl Properties for Map<String, Value> {
+        fn property(&self, key: &str) -> Option<PropertyValue<'_>> {
+            self.get(key).map(|v| v.into())
+        }
+
+        fn iter_properties(&self) -> Box<dyn Iterator<Item = (&str, PropertyValue<'_>)> + '_> {
+            Box::new(self.iter().map(|(k, v)| (k.as_str(), v.into())))
+        }
+    }
+
+    /// Only JSON objects have properties. For any other JSON value the property list is empty.
+    impl Properties for Value {
+        fn property(&self, key: &str) -> Option<PropertyValue<'_>> {
+            self.as_object().and_then(|object| object.property(key))
+        }
+
+        fn iter_properties(&self) -> Box<dyn Iterator<Item = (&str, PropertyValue<'_>)> + '_> {
+            match self.as_object() {
+                Some(object) => object.iter_properties(),
+                None => Box::new(std::iter::empty()),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "geojson")]
+impl Properties for geojson::Feature {
+    fn property(&self, key: &str) -> Option<PropertyValue<'_>> {
+        self.properties.as_ref()?.property(key)
+    }
+
+    fn iter_properties(&self) -> Box<dyn Iterator<Item = (&str, PropertyValue<'_>)> + '_> {
+        match &self.properties {
+            Some(properties) => properties.iter_properties(),
+            None => Box::new(std::iter::empty()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_map_properties() {
+        let mut map = HashMap::new();
+        map.insert("name".to_string(), "Galileo".to_string());
+
+        assert_eq!(
+            map.property("name"),
+            Some(PropertyValue::String("Galileo".into()))
+        );
+        assert_eq!(map.property("other"), None);
+        assert_eq!(map.iter_properties().count(), 1);
+    }
+
+    #[test]
+    fn numeric_properties() {
+        let mut map = BTreeMap::new();
+        map.insert("a".to_string(), 1.5f64);
+        map.insert("b".to_string(), 2.0f64);
+
+        let values: Vec<_> = map
+            .iter_properties()
+            .map(|(key, value)| (key.to_string(), value.as_f64()))
+            .collect();
+        assert_eq!(
+            values,
+            vec![("a".to_string(), Some(1.5)), ("b".to_string(), Some(2.0))]
+        );
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn json_properties() {
+        let value = serde_json::json!({"name": "Pisa", "population": 90000, "nested": {"a": 1}});
+
+        assert_eq!(
+            value.property("name"),
+            Some(PropertyValue::String("Pisa".into()))
+        );
+        assert_eq!(
+            value.property("population"),
+            Some(PropertyValue::Int(90000))
+        );
+        assert_eq!(
+            value.property("nested").map(|v| v.to_string()),
+            Some("{\"a\":1}".to_string())
+        );
+        assert_eq!(serde_json::json!(42).iter_properties().count(), 0);
+    }
+}
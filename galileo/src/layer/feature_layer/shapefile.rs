@@ -0,0 +1,409 @@
+//! Loading of features from [ESRI Shapefiles](https://en.wikipedia.org/wiki/Shapefile).
+//!
+//! A shapefile consists of several files with the same name: `.shp` with geometries, `.dbf` with attributes of the
+//! features, and optional `.prj` with the coordinate system of the data. [`read_shapefile`] reads all of them and
+//! returns features that can be added to a [`FeatureLayer`](super::FeatureLayer):
+//!
+//! ```no_run
+//! use galileo::layer::feature_layer::shapefile::read_shapefile;
+//! use galileo::layer::FeatureLayer;
+//! # use galileo::layer::feature_layer::symbol::SimplePolygonSymbol;
+//! # use galileo::Color;
+//! # let symbol = SimplePolygonSymbol::new(Color::BLUE);
+//!
+//! let data = read_shapefile("data/parcels.shp").expect("failed to read shapefile");
+//! let crs = data.crs.clone().expect("unknown CRS of the shapefile");
+//! let layer = FeatureLayer::new(data.features, symbol, crs);
+//! ```
+//!
+//! Coordinates of the features are read as projected coordinates. If the data is in geographic coordinates (which
+//! is the case when [`ShapefileData::crs`] is [`Crs::WGS84`]), convert it with [`ShapefileData::into_geographic`]
+//! before creating a layer.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::Path;
+
+use galileo_types::cartesian::Point2d;
+use galileo_types::contour::Contour as _;
+use galileo_types::geo::impls::GeoPoint2d;
+use galileo_types::geo::{Crs, Datum, NewGeoPoint, ProjectionType};
+use galileo_types::geometry::Geom;
+use galileo_types::geometry_type::GeometryType;
+use galileo_types::impls::{
+    ClosedContour, Contour, MultiContour, MultiPoint, MultiPolygon, Polygon,
+};
+use galileo_types::{MultiContour as _, MultiPoint as _};
+use shapefile::dbase::FieldValue;
+use shapefile::{PolygonRing, Shape};
+
+use crate::error::GalileoError;
+use crate::layer::feature_layer::{Feature, Properties, PropertyValue};
+
+/// Features read from a shapefile.
+#[derive(Debug, Clone)]
+pub struct ShapefileData<P = Point2d> {
+    /// Coordinate system of the data, read from the `.prj` file.
+    ///
+    /// `None` if there is no `.prj` file or the coordinate system is not supported.
+    pub crs: Option<Crs>,
+    /// Features of the shapefile. Shapes without geometry are skipped.
+    pub features: Vec<ShapefileFeature<P>>,
+}
+
+impl ShapefileData<Point2d> {
+    /// Converts the data into geographic coordinates, treating `x` coordinate as longitude and `y` as latitude.
+    pub fn into_geographic(self) -> ShapefileData<GeoPoint2d> {
+        ShapefileData {
+            crs: self.crs,
+            features: self
+                .features
+                .into_iter()
+                .map(|feature| ShapefileFeature {
+                    geometry: cast_geom(&feature.geometry, |p| GeoPoint2d::latlon(p.y, p.x)),
+                    attributes: feature.attributes,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A single feature of a shapefile: a shape with its attributes record.
+#[derive(Debug, Clone)]
+pub struct ShapefileFeature<P = Point2d> {
+    /// Geometry of the feature. `M` and `Z` values are ignored.
+    pub geometry: Geom<P>,
+    /// Attributes of the feature from the `.dbf` file.
+    pub attributes: HashMap<String, PropertyValue<'static>>,
+}
+
+impl<P: GeometryType> Feature for ShapefileFeature<P> {
+    type Geom = Geom<P>;
+
+    fn geometry(&self) -> &Self::Geom {
+        &self.geometry
+    }
+}
+
+impl<P> Properties for ShapefileFeature<P> {
+    fn property(&self, key: &str) -> Option<PropertyValue<'_>> {
+        self.attributes.get(key).cloned()
+    }
+
+    fn iter_properties(&self) -> Box<dyn Iterator<Item = (&str, PropertyValue<'_>)> + '_> {
+        Box::new(
+            self.attributes
+                .iter()
+                .map(|(key, value)| (key.as_str(), value.clone())),
+        )
+    }
+}
+
+/// Reads the shapefile with its attributes and coordinate system.
+///
+/// The `path` is the path to the `.shp` file. The `.dbf` file must be in the same directory, and `.prj` file is
+/// read if it exists.
+pub fn read_shapefile(path: impl AsRef<Path>) -> Result<ShapefileData, GalileoError> {
+    let path = path.as_ref();
+    let mut reader = shapefile::Reader::from_path(path).map_err(shapefile_error)?;
+
+    let mut features = vec![];
+    for item in reader.iter_shapes_and_records() {
+        let (shape, record) = item.map_err(shapefile_error)?;
+        let Some(geometry) = convert_shape(shape) else {
+            continue;
+        };
+
+        let attributes = record
+            .into_iter()
+            .map(|(name, value)| (name, convert_value(value)))
+            .collect();
+
+        features.push(ShapefileFeature {
+            geometry,
+            attributes,
+        });
+    }
+
+    let crs = match std::fs::read_to_string(path.with_extension("prj")) {
+        Ok(wkt) => parse_prj(&wkt),
+        Err(_) => None,
+    };
+
+    Ok(ShapefileData { crs, features })
+}
+
+fn shapefile_error(err: shapefile::Error) -> GalileoError {
+    GalileoError::Generic(format!("failed to read shapefile: {err}"))
+}
+
+fn convert_shape(shape: Shape) -> Option<Geom<Point2d>> {
+    let point = |x: f64, y: f64| Point2d::new(x, y);
+
+    let geometry = match shape {
+        Shape::NullShape => return None,
+        Shape::Point(p) => Geom::Point(point(p.x, p.y)),
+        Shape::PointM(p) => Geom::Point(point(p.x, p.y)),
+        Shape::PointZ(p) => Geom::Point(point(p.x, p.y)),
+        Shape::Multipoint(v) => multi_point(v.points().iter().map(|p| point(p.x, p.y))),
+        Shape::MultipointM(v) => multi_point(v.points().iter().map(|p| point(p.x, p.y))),
+        Shape::MultipointZ(v) => multi_point(v.points().iter().map(|p| point(p.x, p.y))),
+        Shape::Polyline(v) => lines(
+            v.parts()
+                .iter()
+                .map(|part| part.iter().map(|p| point(p.x, p.y)).collect()),
+        ),
+        Shape::PolylineM(v) => lines(
+            v.parts()
+                .iter()
+                .map(|part| part.iter().map(|p| point(p.x, p.y)).collect()),
+        ),
+        Shape::PolylineZ(v) => lines(
+            v.parts()
+                .iter()
+                .map(|part| part.iter().map(|p| point(p.x, p.y)).collect()),
+        ),
+        Shape::Polygon(v) => polygons(v.rings().iter().map(|ring| {
+            (
+                matches!(ring, PolygonRing::Outer(_)),
+                ring.points().iter().map(|p| point(p.x, p.y)).collect(),
+            )
+        })),
+        Shape::PolygonM(v) => polygons(v.rings().iter().map(|ring| {
+            (
+                matches!(ring, PolygonRing::Outer(_)),
+                ring.points().iter().map(|p| point(p.x, p.y)).collect(),
+            )
+        })),
+        Shape::PolygonZ(v) => polygons(v.rings().iter().map(|ring| {
+            (
+                matches!(ring, PolygonRing::Outer(_)),
+                ring.points().iter().map(|p| point(p.x, p.y)).collect(),
+            )
+        })),
+        Shape::Multipatch(_) => {
+            log::debug!("Multipatch shapes are not supported, skipping");
+            return None;
+        }
+    };
+
+    Some(geometry)
+}
+
+fn multi_point(points: impl Iterator<Item = Point2d>) -> Geom<Point2d> {
+    Geom::MultiPoint(MultiPoint::from(points.collect::<Vec<_>>()))
+}
+
+fn lines(parts: impl Iterator<Item = Vec<Point2d>>) -> Geom<Point2d> {
+    let mut contours: Vec<_> = parts.map(Contour::open).collect();
+    if contours.len() == 1 {
+        if let Some(contour) = contours.pop() {
+            return Geom::Contour(contour);
+        }
+    }
+
+    Geom::MultiContour(MultiContour::from(contours))
+}
+
+/// Builds polygons from the rings of a shape. Each outer ring starts a new polygon, and inner rings belong to the
+/// last outer ring before them.
+fn polygons(rings: impl Iterator<Item = (bool, Vec<Point2d>)>) -> Geom<Point2d> {
+    let mut parts: Vec<(ClosedContour<Point2d>, Vec<ClosedContour<Point2d>>)> = vec![];
+    for (is_outer, mut points) in rings {
+        // Rings in shapefiles repeat the first point at the end
+        if points.len() > 1 && points.first() == points.last() {
+            points.pop();
+        }
+
+        let contour = ClosedContour::new(points);
+        match parts.last_mut() {
+            Some((_, inner)) if !is_outer => inner.push(contour),
+            _ => parts.push((contour, vec![])),
+        }
+    }
+
+    let mut polygons: Vec<_> = parts
+        .into_iter()
+        .map(|(outer, inner)| Polygon::new(outer, inner))
+        .collect();
+    if polygons.len() == 1 {
+        if let Some(polygon) = polygons.pop() {
+            return Geom::Polygon(polygon);
+        }
+    }
+
+    Geom::MultiPolygon(MultiPolygon::from(polygons))
+}
+
+fn cast_geom<P, T>(geom: &Geom<P>, cast: impl Fn(&P) -> T + Copy) -> Geom<T> {
+    let contour = |contour: &Contour<P>| {
+        Contour::new(
+            contour.iter_points().map(cast).collect(),
+            contour.is_closed(),
+        )
+    };
+
+    match geom {
+        Geom::Point(p) => Geom::Point(cast(p)),
+        Geom::MultiPoint(v) => Geom::MultiPoint(MultiPoint::from(
+            v.iter_points().map(cast).collect::<Vec<_>>(),
+        )),
+        Geom::Contour(v) => Geom::Contour(contour(v)),
+        Geom::MultiContour(v) => Geom::MultiContour(MultiContour::from(
+            v.contours().map(contour).collect::<Vec<_>>(),
+        )),
+        Geom::Polygon(v) => Geom::Polygon(v.cast_points(cast)),
+        Geom::MultiPolygon(v) => Geom::MultiPolygon(MultiPolygon::from(
+            v.parts()
+                .iter()
+                .map(|polygon| polygon.cast_points(cast))
+                .collect::<Vec<_>>(),
+        )),
+    }
+}
+
+fn convert_value(value: FieldValue) -> PropertyValue<'static> {
+    match value {
+        FieldValue::Character(Some(v)) | FieldValue::Memo(v) => {
+            PropertyValue::String(Cow::Owned(v))
+        }
+        FieldValue::Numeric(Some(v)) | FieldValue::Double(v) | FieldValue::Currency(v) => {
+            PropertyValue::Float(v)
+        }
+        FieldValue::Float(Some(v)) => PropertyValue::Float(v as f64),
+        FieldValue::Integer(v) => PropertyValue::Int(v as i64),
+        FieldValue::Logical(Some(v)) => PropertyValue::Bool(v),
+        FieldValue::Date(Some(date)) => PropertyValue::String(Cow::Owned(format!(
+            "{:04}-{:02}-{:02}",
+            date.year(),
+            date.month(),
+            date.day()
+        ))),
+        FieldValue::DateTime(date_time) => {
+            let date = date_time.date();
+            let time = date_time.time();
+            PropertyValue::String(Cow::Owned(format!(
+                "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+                date.year(),
+                date.month(),
+                date.day(),
+                time.hours(),
+                time.minutes(),
+                time.seconds()
+            )))
+        }
+        FieldValue::Character(None)
+        | FieldValue::Numeric(None)
+        | FieldValue::Float(None)
+        | FieldValue::Logical(None)
+        | FieldValue::Date(None) => PropertyValue::Null,
+    }
+}
+
+/// Recognizes the coordinate system from the WKT definition in a `.prj` file.
+///
+/// Only the coordinate systems that Galileo can project into are recognized: WGS84 geographic coordinates, Web
+/// Mercator and WGS84 UTM zones.
+fn parse_prj(wkt: &str) -> Option<Crs> {
+    let wkt = wkt.to_ascii_lowercase().replace(['_', ' '], "");
+
+    if !wkt.starts_with("projcs") {
+        return (wkt.starts_with("geogcs") && is_wgs84(&wkt)).then_some(Crs::WGS84);
+    }
+
+    if wkt.contains("pseudo-mercator")
+        || wkt.contains("mercatorauxiliarysphere")
+        || wkt.contains("popularvisualisation")
+    {
+        return Some(Crs::EPSG3857);
+    }
+
+    if !is_wgs84(&wkt) {
+        return None;
+    }
+
+    // UTM zones are named like `WGS_1984_UTM_Zone_33N` or `WGS 84 / UTM zone 33N`
+    let zone_start = wkt.find("utmzone")? + "utmzone".len();
+    let zone_definition = &wkt[zone_start..];
+    let digits = zone_definition
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .count();
+    let zone: u8 = zone_definition[..digits].parse().ok()?;
+    if !(1..=60).contains(&zone) {
+        return None;
+    }
+
+    let definition = match zone_definition[digits..].chars().next() {
+        Some('n') => format!("utm zone={zone}"),
+        Some('s') => format!("utm zone={zone} south"),
+        _ => return None,
+    };
+
+    Some(Crs::new(Datum::WGS84, ProjectionType::Other(definition)))
+}
+
+fn is_wgs84(wkt: &str) -> bool {
+    wkt.contains("wgs1984") || wkt.contains("wgs84")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prj_parsing() {
+        let geographic = r#"GEOGCS["GCS_WGS_1984",DATUM["D_WGS_1984",SPHEROID["WGS_1984",6378137.0,298.257223563]],PRIMEM["Greenwich",0.0],UNIT["Degree",0.0174532925199433]]"#;
+        assert_eq!(parse_prj(geographic), Some(Crs::WGS84));
+
+        let mercator = r#"PROJCS["WGS_1984_Web_Mercator_Auxiliary_Sphere",GEOGCS["GCS_WGS_1984",DATUM["D_WGS_1984",SPHEROID["WGS_1984",6378137.0,298.257223563]]],PROJECTION["Mercator_Auxiliary_Sphere"]]"#;
+        assert_eq!(parse_prj(mercator), Some(Crs::EPSG3857));
+
+        let utm = r#"PROJCS["WGS_1984_UTM_Zone_33S",GEOGCS["GCS_WGS_1984",DATUM["D_WGS_1984",SPHEROID["WGS_1984",6378137.0,298.257223563]]],PROJECTION["Transverse_Mercator"]]"#;
+        assert_eq!(
+            parse_prj(utm),
+            Some(Crs::new(
+                Datum::WGS84,
+                ProjectionType::Other("utm zone=33 south".into())
+            ))
+        );
+
+        let other = r#"PROJCS["NAD_1983_StatePlane_California_III_FIPS_0403",GEOGCS["GCS_North_American_1983",DATUM["D_North_American_1983",SPHEROID["GRS_1980",6378137.0,298.257222101]]]]"#;
+        assert_eq!(parse_prj(other), None);
+    }
+
+    #[test]
+    fn polygon_rings() {
+        let square = |offset: f64, size: f64| {
+            vec![
+                Point2d::new(offset, offset),
+                Point2d::new(offset, offset + size),
+                Point2d::new(offset + size, offset + size),
+                Point2d::new(offset + size, offset),
+                Point2d::new(offset, offset),
+            ]
+        };
+
+        let Geom::Polygon(polygon) =
+            polygons([(true, square(0.0, 10.0)), (false, square(2.0, 1.0))].into_iter())
+        else {
+            panic!("expected a polygon");
+        };
+        assert_eq!(polygon.outer_contour.points.len(), 4);
+        assert_eq!(polygon.inner_contours.len(), 1);
+
+        let Geom::MultiPolygon(multi_polygon) = polygons(
+            [
+                (true, square(0.0, 10.0)),
+                (false, square(2.0, 1.0)),
+                (true, square(20.0, 1.0)),
+            ]
+            .into_iter(),
+        ) else {
+            panic!("expected a multipolygon");
+        };
+        assert_eq!(multi_polygon.parts().len(), 2);
+        assert_eq!(multi_polygon.parts()[0].inner_contours.len(), 1);
+        assert!(multi_polygon.parts()[1].inner_contours.is_empty());
+    }
+}
@@ -0,0 +1,310 @@
+//! Fanning out overlapping point features ("spiderfying") so that each of them can be seen and selected.
+
+use std::time::Duration;
+
+use galileo_types::cartesian::{Point2d, Point3d};
+use nalgebra::Vector2;
+use web_time::Instant;
+
+use crate::layer::{AnnotationLayer, CoordinateSpace};
+use crate::render::point_paint::PointPaint;
+use crate::view::MapView;
+use crate::Color;
+
+/// Up to this number of points are placed on a circle, more points are placed on a spiral.
+const MAX_CIRCLE_COUNT: usize = 8;
+/// Distance between the points on the circle in pixels.
+const CIRCLE_SEPARATION: f64 = 25.0;
+const MIN_CIRCLE_RADIUS: f64 = 20.0;
+/// Distance between the points on the spiral in pixels.
+const SPIRAL_SEPARATION: f64 = 28.0;
+const SPIRAL_START_LENGTH: f64 = 11.0;
+const SPIRAL_LENGTH_FACTOR: f64 = 5.0;
+
+const DEFAULT_ANIMATION_DURATION: Duration = Duration::from_millis(200);
+
+/// Result of a click handled by [`Spiderfy::handle_click`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpiderfyClick<Id> {
+    /// One of the fanned out points was clicked.
+    Selected(Id),
+    /// The click was outside of the fanned out points, and they are collapsing back.
+    Collapsed,
+}
+
+/// Overlapping points fanned out around their common position on a circle (or a spiral if there are many of them),
+/// with leader lines connecting them to the original position.
+///
+/// This is used when the points at the same place cannot be separated by zooming in. The application creates a
+/// `Spiderfy` when such a group of points is clicked, draws it every frame while it exists
+/// (see [`Spiderfy::draw`]) and passes next clicks to [`Spiderfy::handle_click`]. The points move out from the center
+/// with an animation, and move back when the user clicks outside of them.
+///
+/// ```no_run
+/// use galileo::galileo_types::cartesian::Point2d;
+/// use galileo::layer::feature_layer::spiderfy::{Spiderfy, SpiderfyClick};
+/// use galileo::layer::AnnotationLayer;
+/// use galileo::render::point_paint::PointPaint;
+/// use galileo::{Color, MapView};
+///
+/// # let view = MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0);
+/// let mut spiderfy = Spiderfy::new(Point2d::new(0.0, 0.0), vec![1, 2, 3]);
+///
+/// // on every frame
+/// let mut layer = AnnotationLayer::new();
+/// spiderfy.draw(&mut layer, &view, Color::BLACK, &PointPaint::circle(Color::RED, 10.0));
+///
+/// // on click
+/// match spiderfy.handle_click(&view, Point2d::new(10.0, 10.0)) {
+///     SpiderfyClick::Selected(id) => println!("Selected {id}"),
+///     SpiderfyClick::Collapsed => {}
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Spiderfy<Id> {
+    center: Point2d,
+    items: Vec<(Id, Vector2<f64>)>,
+    animation_duration: Duration,
+    started_at: Instant,
+    collapsed_at: Option<Instant>,
+    hit_tolerance: f64,
+}
+
+impl<Id: Clone> Spiderfy<Id> {
+    /// Fans out the points with the given ids around the `center` given in map coordinates.
+    pub fn new(center: Point2d, ids: impl IntoIterator<Item = Id>) -> Self {
+        let ids: Vec<Id> = ids.into_iter().collect();
+        let offsets = spider_offsets(ids.len());
+
+        Self {
+            center,
+            items: ids.into_iter().zip(offsets).collect(),
+            animation_duration: DEFAULT_ANIMATION_DURATION,
+            started_at: Instant::now(),
+            collapsed_at: None,
+            hit_tolerance: CIRCLE_SEPARATION / 2.0,
+        }
+    }
+
+    /// Sets the duration of expanding and collapsing animations. Default value is 200 ms.
+    pub fn with_animation_duration(mut self, duration: Duration) -> Self {
+        self.animation_duration = duration;
+        self
+    }
+
+    /// Sets the maximum distance in pixels from a fanned out point at which a click selects this point. Default value
+    /// is 12.5 pixels.
+    pub fn with_hit_tolerance(mut self, tolerance: f64) -> Self {
+        self.hit_tolerance = tolerance;
+        self
+    }
+
+    /// Common position of the points in map coordinates.
+    pub fn center(&self) -> Point2d {
+        self.center
+    }
+
+    /// Returns true while the points are moving, so the map should be redrawn.
+    pub fn is_animating(&self) -> bool {
+        self.is_animating_at(Instant::now())
+    }
+
+    /// Returns true if the points have collapsed back to the center, and the `Spiderfy` can be dropped.
+    pub fn is_collapsed(&self) -> bool {
+        self.collapsed_at.is_some() && self.progress_at(Instant::now()) == 0.0
+    }
+
+    /// Starts collapsing the points back to the center.
+    pub fn collapse(&mut self) {
+        if self.collapsed_at.is_none() {
+            self.collapse_at(Instant::now());
+        }
+    }
+
+    /// Screen positions of the fanned out points with the given view.
+    pub fn positions(&self, view: &MapView) -> Vec<(Id, Point2d)> {
+        self.positions_at(view, Instant::now())
+    }
+
+    /// Handles a click at the given screen position. If a fanned out point is clicked, returns its id. Otherwise
+    /// starts collapsing the points.
+    pub fn handle_click(&mut self, view: &MapView, screen_point: Point2d) -> SpiderfyClick<Id> {
+        let now = Instant::now();
+        if self.collapsed_at.is_none() {
+            if let Some(id) = self.hit_test(view, screen_point, now) {
+                return SpiderfyClick::Selected(id);
+            }
+
+            self.collapse_at(now);
+        }
+
+        SpiderfyClick::Collapsed
+    }
+
+    /// Draws the leader lines and the fanned out points into the annotation layer.
+    pub fn draw(
+        &self,
+        layer: &mut AnnotationLayer,
+        view: &MapView,
+        line_color: Color,
+        point_paint: &PointPaint<'static>,
+    ) {
+        let Some(center) = self.center_on_screen(view) else {
+            return;
+        };
+
+        for (_, position) in self.positions(view) {
+            layer.draw_polyline(
+                CoordinateSpace::Screen,
+                vec![center, position],
+                line_color,
+                1.5,
+            );
+            layer.draw_point(CoordinateSpace::Screen, position, point_paint.clone());
+        }
+    }
+
+    fn center_on_screen(&self, view: &MapView) -> Option<Point2d> {
+        view.map_to_screen(&Point3d::new(self.center.x, self.center.y, 0.0))
+    }
+
+    fn positions_at(&self, view: &MapView, now: Instant) -> Vec<(Id, Point2d)> {
+        let Some(center) = self.center_on_screen(view) else {
+            return vec![];
+        };
+
+        let progress = ease(self.progress_at(now));
+        self.items
+            .iter()
+            .map(|(id, offset)| (id.clone(), center + offset * progress))
+            .collect()
+    }
+
+    fn hit_test(&self, view: &MapView, screen_point: Point2d, now: Instant) -> Option<Id> {
+        self.positions_at(view, now)
+            .into_iter()
+            .map(|(id, position)| (id, (position - screen_point).norm()))
+            .filter(|(_, distance)| *distance <= self.hit_tolerance)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(id, _)| id)
+    }
+
+    fn collapse_at(&mut self, now: Instant) {
+        // Collapsing from a partially expanded state starts from the current position
+        let remaining = self.animation_duration.mul_f64(1.0 - self.progress_at(now));
+        self.collapsed_at = Some(now.checked_sub(remaining).unwrap_or(now));
+    }
+
+    fn is_animating_at(&self, now: Instant) -> bool {
+        let progress = self.progress_at(now);
+        match self.collapsed_at {
+            Some(_) => progress > 0.0,
+            None => progress < 1.0,
+        }
+    }
+
+    /// Expansion of the points from `0.0` (at the center) to `1.0` (fully expanded).
+    fn progress_at(&self, now: Instant) -> f64 {
+        let fraction = |since: Instant| {
+            if self.animation_duration.is_zero() {
+                1.0
+            } else {
+                (now.saturating_duration_since(since).as_secs_f64()
+                    / self.animation_duration.as_secs_f64())
+                .min(1.0)
+            }
+        };
+
+        match self.collapsed_at {
+            Some(collapsed_at) => 1.0 - fraction(collapsed_at),
+            None => fraction(self.started_at),
+        }
+    }
+}
+
+fn ease(t: f64) -> f64 {
+    1.0 - (1.0 - t) * (1.0 - t)
+}
+
+/// Offsets in pixels of the fanned out points from their center. Up to 8 points are placed on a circle, more points
+/// are placed on a spiral.
+pub fn spider_offsets(count: usize) -> Vec<Vector2<f64>> {
+    if count <= MAX_CIRCLE_COUNT {
+        let circumference = CIRCLE_SEPARATION * (2 + count) as f64;
+        let radius = (circumference / std::f64::consts::TAU).max(MIN_CIRCLE_RADIUS);
+        let step = std::f64::consts::TAU / count.max(1) as f64;
+
+        (0..count)
+            .map(|index| {
+                let angle = index as f64 * step;
+                Vector2::new(radius * angle.cos(), radius * angle.sin())
+            })
+            .collect()
+    } else {
+        let mut length = SPIRAL_START_LENGTH;
+        let mut angle = 0.0;
+
+        (0..count)
+            .map(|index| {
+                angle += SPIRAL_SEPARATION / length + index as f64 * 0.0005;
+                let offset = Vector2::new(length * angle.cos(), length * angle.sin());
+                length += std::f64::consts::TAU * SPIRAL_LENGTH_FACTOR / angle;
+                offset
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use galileo_types::cartesian::Size;
+
+    use super::*;
+
+    #[test]
+    fn offsets_are_separated() {
+        for count in [1, 2, 8, 9, 50] {
+            let offsets = spider_offsets(count);
+            assert_eq!(offsets.len(), count);
+
+            for (i, a) in offsets.iter().enumerate() {
+                assert!(a.norm() >= SPIRAL_START_LENGTH);
+                for b in &offsets[i + 1..] {
+                    assert!((a - b).norm() > 15.0, "{count} points are too close");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn expands_and_collapses() {
+        let view =
+            MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0).with_size(Size::new(200.0, 200.0));
+        let mut spiderfy = Spiderfy::new(Point2d::new(0.0, 0.0), vec!["a", "b"]);
+        let start = spiderfy.started_at;
+        let expanded = start + DEFAULT_ANIMATION_DURATION;
+
+        assert!(spiderfy.is_animating_at(start));
+        assert!(!spiderfy.is_animating_at(expanded));
+
+        let positions = spiderfy.positions_at(&view, start);
+        assert!((positions[0].1 - Point2d::new(100.0, 100.0)).norm() < 1e-6);
+
+        let positions = spiderfy.positions_at(&view, expanded);
+        assert_eq!(
+            spiderfy.hit_test(&view, positions[1].1, expanded),
+            Some("b")
+        );
+        assert_eq!(
+            spiderfy.hit_test(&view, Point2d::new(100.0, 100.0), expanded),
+            None
+        );
+
+        spiderfy.collapse_at(expanded);
+        assert!(spiderfy.is_animating_at(expanded));
+        assert_eq!(
+            spiderfy.progress_at(expanded + DEFAULT_ANIMATION_DURATION),
+            0.0
+        );
+    }
+}
@@ -0,0 +1,164 @@
+use galileo_types::cartesian::CartesianPoint3d;
+use galileo_types::geometry::Geom;
+use galileo_types::impls::{Contour, Polygon};
+use galileo_types::{Contour as _, MultiContour};
+use num_traits::AsPrimitive;
+
+use crate::animation::Interpolate;
+use crate::layer::feature_layer::symbol::Symbol;
+use crate::render::render_bundle::RenderPrimitive;
+use crate::render::{LineCap, LinePaint, ResolutionScale};
+use crate::Color;
+
+/// Renders a contour as a line with the color changing gradually from the start of the line to its end.
+///
+/// This is intended for arcs created with [`GreatCircleContour`](galileo_types::geo::GreatCircleContour), e.g. to
+/// show the direction of flight routes. The gradient goes along the whole geometry, so an arc split at the
+/// antimeridian into several contours of a multi-contour is colored as a single line. The gaps between the contours
+/// are not counted in the length of the line.
+#[derive(Debug, Copy, Clone)]
+pub struct ArcSymbol {
+    /// Color of the line at its start.
+    pub start_color: Color,
+    /// Color of the line at its end.
+    pub end_color: Color,
+    /// Width of the line in pixels.
+    pub width: f64,
+    /// Multiplier of the line width that changes with the resolution of the map.
+    pub width_scale: Option<ResolutionScale>,
+}
+
+impl ArcSymbol {
+    /// Creates a new instance.
+    pub fn new(start_color: Color, end_color: Color, width: f64) -> Self {
+        Self {
+            start_color,
+            end_color,
+            width,
+            width_scale: None,
+        }
+    }
+
+    /// Sets the multiplier of the line width that changes with the resolution of the map, so that the width changes
+    /// smoothly while zooming.
+    pub fn with_width_scale(mut self, width_scale: ResolutionScale) -> Self {
+        self.width_scale = Some(width_scale);
+        self
+    }
+
+    fn render_contours<'a, N, P>(
+        &self,
+        contours: &[&Contour<P>],
+    ) -> Vec<RenderPrimitive<'a, N, P, Contour<P>, Polygon<P>>>
+    where
+        N: AsPrimitive<f32>,
+        P: CartesianPoint3d<Num = N> + Clone,
+    {
+        let paint = LinePaint {
+            color: self.start_color,
+            width: self.width,
+            offset: 0.0,
+            line_cap: LineCap::Butt,
+            width_scale: self.width_scale,
+            crisp: false,
+        };
+
+        // Distance along the whole geometry to every point
+        let mut distances: Vec<Vec<f64>> = vec![];
+        let mut total = 0.0;
+        for contour in contours {
+            let mut prev: Option<(f64, f64)> = None;
+            let mut contour_distances = vec![];
+            for point in contour.iter_points() {
+                let xy = (point.x().as_() as f64, point.y().as_() as f64);
+                if let Some(prev) = prev {
+                    total += (xy.0 - prev.0).hypot(xy.1 - prev.1);
+                }
+                prev = Some(xy);
+                contour_distances.push(total);
+            }
+            distances.push(contour_distances);
+        }
+
+        contours
+            .iter()
+            .zip(distances)
+            .map(|(contour, distances)| {
+                let colors = distances
+                    .into_iter()
+                    .map(|distance| {
+                        let k = if total > 0.0 { distance / total } else { 0.0 };
+                        self.start_color.interpolate(&self.end_color, k)
+                    })
+                    .collect();
+                RenderPrimitive::new_contour_with_vertex_colors((*contour).clone(), paint, colors)
+            })
+            .collect()
+    }
+}
+
+impl<F> Symbol<F> for ArcSymbol {
+    fn render<'a, N, P>(
+        &self,
+        _feature: &F,
+        geometry: &'a Geom<P>,
+        _min_resolution: f64,
+    ) -> Vec<RenderPrimitive<'a, N, P, Contour<P>, Polygon<P>>>
+    where
+        N: AsPrimitive<f32>,
+        P: CartesianPoint3d<Num = N> + Clone,
+    {
+        match geometry {
+            Geom::Contour(contour) => self.render_contours(&[contour]),
+            Geom::MultiContour(contours) => {
+                self.render_contours(&contours.contours().collect::<Vec<_>>())
+            }
+            _ => vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use galileo_types::cartesian::Point3d;
+
+    use super::*;
+
+    #[test]
+    fn gradient_along_multi_contour() {
+        let geometry = Geom::MultiContour(
+            vec![
+                Contour::open(vec![
+                    Point3d::new(0.0, 0.0, 0.0),
+                    Point3d::new(1.0, 0.0, 0.0),
+                ]),
+                Contour::open(vec![
+                    Point3d::new(3.0, 0.0, 0.0),
+                    Point3d::new(4.0, 0.0, 0.0),
+                ]),
+            ]
+            .into(),
+        );
+
+        let symbol = ArcSymbol::new(Color::BLACK, Color::WHITE, 2.0);
+        let primitives = symbol.render(&(), &geometry, 1.0);
+        assert_eq!(primitives.len(), 2);
+
+        let colors: Vec<Color> = primitives
+            .iter()
+            .flat_map(|primitive| match primitive {
+                RenderPrimitive::ColoredContour(_, _, colors) => colors.to_vec(),
+                _ => panic!("unexpected primitive"),
+            })
+            .collect();
+        assert_eq!(
+            colors,
+            vec![
+                Color::BLACK,
+                Color::rgba(128, 128, 128, 255),
+                Color::rgba(128, 128, 128, 255),
+                Color::WHITE
+            ]
+        );
+    }
+}
@@ -6,7 +6,7 @@ use num_traits::AsPrimitive;
 
 use crate::layer::feature_layer::symbol::Symbol;
 use crate::render::render_bundle::RenderPrimitive;
-use crate::render::{LineCap, LinePaint};
+use crate::render::{LineCap, LinePaint, ResolutionScale};
 use crate::Color;
 
 /// Renders a contour as a line of fixed width.
@@ -16,12 +16,25 @@ pub struct SimpleContourSymbol {
     pub color: Color,
     /// Width of the line in pixels.
     pub width: f64,
+    /// Multiplier of the line width that changes with the resolution of the map.
+    pub width_scale: Option<ResolutionScale>,
 }
 
 impl SimpleContourSymbol {
     /// Creates a new instance.
     pub fn new(color: Color, width: f64) -> Self {
-        Self { color, width }
+        Self {
+            color,
+            width,
+            width_scale: None,
+        }
+    }
+
+    /// Sets the multiplier of the line width that changes with the resolution of the map, so that the width changes
+    /// smoothly while zooming.
+    pub fn with_width_scale(mut self, width_scale: ResolutionScale) -> Self {
+        self.width_scale = Some(width_scale);
+        self
     }
 }
 
@@ -41,6 +54,8 @@ impl<F> Symbol<F> for SimpleContourSymbol {
             width: self.width,
             offset: 0.0,
             line_cap: LineCap::Butt,
+            width_scale: self.width_scale,
+            crisp: false,
         };
 
         match geometry {
@@ -0,0 +1,351 @@
+use std::collections::HashMap;
+
+use galileo_types::cartesian::CartesianPoint3d;
+use galileo_types::geometry::Geom;
+use galileo_types::impls::{Contour, Polygon};
+use galileo_types::{MultiContour, MultiPoint, MultiPolygon};
+use num_traits::AsPrimitive;
+use serde::{Deserialize, Serialize};
+use strfmt::strfmt;
+
+use crate::layer::feature_layer::symbol::Symbol;
+use crate::layer::feature_layer::{Properties, PropertyValue};
+use crate::layer::vector_tile_layer::style::{
+    VectorTileDefaultSymbol, VectorTileLabelSymbol, VectorTileSymbol,
+};
+use crate::render::point_paint::PointPaint;
+use crate::render::render_bundle::RenderPrimitive;
+use crate::render::{LinePaint, PolygonPaint};
+
+/// Data-driven symbol, that selects the way a feature is drawn based on the feature properties.
+///
+/// The style model is the same as the one used by [`VectorTileStyle`](crate::layer::vector_tile_layer::style::VectorTileStyle):
+/// a list of rules is checked in sequence, and the symbol of the first rule with all filters passing is used to
+/// render the feature. If no rule applies to the feature or the rule symbol is not compatible with the feature
+/// geometry, the default symbol is used.
+///
+/// Since the symbol is deserializable, it can be loaded at runtime, e.g. from a JSON file:
+///
+/// ```json
+/// {
+///   "rules": [
+///     {
+///       "filters": [{ "op": "eq", "property": "kind", "value": "park" }],
+///       "symbol": { "polygon": { "fill_color": "#00ff0080" } }
+///     }
+///   ],
+///   "default_symbol": {
+///     "point": { "size": 5.0, "color": "#ff0000ff" }
+///   }
+/// }
+/// ```
+///
+/// <div class="warning">This exact type is experimental and is likely to change in near future.</div>
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct JsonSymbol {
+    /// Rules for features to be drawn.
+    #[serde(default)]
+    pub rules: Vec<JsonSymbolRule>,
+    /// Symbol used for features, for which no rule applies.
+    #[serde(default)]
+    pub default_symbol: VectorTileDefaultSymbol,
+}
+
+/// A rule of [`JsonSymbol`].
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct JsonSymbolRule {
+    /// Filters that all must pass for the rule to be applied. A rule without filters applies to all features.
+    #[serde(default)]
+    pub filters: Vec<PropertyFilter>,
+    /// Symbol to draw a feature with.
+    #[serde(default)]
+    pub symbol: VectorTileSymbol,
+}
+
+/// Condition on a property of a feature.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum PropertyFilter {
+    /// String representation of the property is equal to the value.
+    Eq {
+        /// Property name.
+        property: String,
+        /// Expected value.
+        value: String,
+    },
+    /// Property is not set, or its string representation is not equal to the value.
+    NotEq {
+        /// Property name.
+        property: String,
+        /// Value the property must not be equal to.
+        value: String,
+    },
+    /// String representation of the property is equal to one of the values.
+    In {
+        /// Property name.
+        property: String,
+        /// List of allowed values.
+        values: Vec<String>,
+    },
+    /// Property is a number greater than the value.
+    Gt {
+        /// Property name.
+        property: String,
+        /// Value to compare to.
+        value: f64,
+    },
+    /// Property is a number less than the value.
+    Lt {
+        /// Property name.
+        property: String,
+        /// Value to compare to.
+        value: f64,
+    },
+    /// Property is set and is not null.
+    Exists {
+        /// Property name.
+        property: String,
+    },
+}
+
+impl PropertyFilter {
+    /// Returns true if the feature passes the filter.
+    pub fn check(&self, feature: &(impl Properties + ?Sized)) -> bool {
+        match self {
+            Self::Eq { property, value } => {
+                Self::string_value(feature, property).is_some_and(|v| v == *value)
+            }
+            Self::NotEq { property, value } => {
+                Self::string_value(feature, property).as_deref() != Some(value.as_str())
+            }
+            Self::In { property, values } => {
+                Self::string_value(feature, property).is_some_and(|v| values.contains(&v))
+            }
+            Self::Gt { property, value } => {
+                Self::numeric_value(feature, property).is_some_and(|v| v > *value)
+            }
+            Self::Lt { property, value } => {
+                Self::numeric_value(feature, property).is_some_and(|v| v < *value)
+            }
+            Self::Exists { property } => feature
+                .property(property)
+                .is_some_and(|value| !value.is_null()),
+        }
+    }
+
+    fn string_value(feature: &(impl Properties + ?Sized), property: &str) -> Option<String> {
+        feature.property(property).map(|v| v.to_string())
+    }
+
+    fn numeric_value(feature: &(impl Properties + ?Sized), property: &str) -> Option<f64> {
+        match feature.property(property)? {
+            PropertyValue::String(v) => v.parse().ok(),
+            v => v.as_f64(),
+        }
+    }
+}
+
+impl JsonSymbol {
+    /// Parses the symbol from a JSON string.
+    #[cfg(feature = "serde_json")]
+    pub fn from_json(json: &str) -> Result<Self, crate::error::GalileoError> {
+        serde_json::from_str(json)
+            .map_err(|err| crate::error::GalileoError::Generic(err.to_string()))
+    }
+
+    /// Returns the first rule that applies to the feature.
+    pub fn get_rule(&self, feature: &(impl Properties + ?Sized)) -> Option<&JsonSymbolRule> {
+        self.rules
+            .iter()
+            .find(|rule| rule.filters.iter().all(|filter| filter.check(feature)))
+    }
+
+    fn point_paint(&self, feature: &(impl Properties + ?Sized)) -> Option<PointPaint<'static>> {
+        self.get_rule(feature)
+            .and_then(|rule| match &rule.symbol {
+                VectorTileSymbol::Point(symbol) => Some((*symbol).into()),
+                VectorTileSymbol::Label(symbol) => Self::format_label(symbol, feature),
+                _ => None,
+            })
+            .or_else(|| {
+                self.default_symbol
+                    .point
+                    .map(|symbol| symbol.into())
+                    .or_else(|| {
+                        self.default_symbol
+                            .label
+                            .as_ref()
+                            .and_then(|symbol| Self::format_label(symbol, feature))
+                    })
+            })
+    }
+
+    fn line_paint(&self, feature: &(impl Properties + ?Sized)) -> Option<LinePaint> {
+        self.get_rule(feature)
+            .and_then(|rule| rule.symbol.line())
+            .or(self.default_symbol.line.as_ref())
+            .map(|symbol| (*symbol).into())
+    }
+
+    fn polygon_paint(&self, feature: &(impl Properties + ?Sized)) -> Option<PolygonPaint> {
+        self.get_rule(feature)
+            .and_then(|rule| rule.symbol.polygon())
+            .or(self.default_symbol.polygon.as_ref())
+            .map(|symbol| (*symbol).into())
+    }
+
+    fn format_label(
+        label_symbol: &VectorTileLabelSymbol,
+        feature: &(impl Properties + ?Sized),
+    ) -> Option<PointPaint<'static>> {
+        let properties: HashMap<String, String> = feature
+            .iter_properties()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        let text = strfmt(&label_symbol.pattern, &properties).ok()?;
+        Some(PointPaint::label_owned(
+            text,
+            label_symbol.text_style.clone(),
+        ))
+    }
+}
+
+impl<F: Properties> Symbol<F> for JsonSymbol {
+    fn render<'a, N, P>(
+        &self,
+        feature: &F,
+        geometry: &'a Geom<P>,
+        _min_resolution: f64,
+    ) -> Vec<RenderPrimitive<'a, N, P, Contour<P>, Polygon<P>>>
+    where
+        N: AsPrimitive<f32>,
+        P: CartesianPoint3d<Num = N> + Clone,
+    {
+        match geometry {
+            Geom::Point(point) => self
+                .point_paint(feature)
+                .map(|paint| vec![RenderPrimitive::new_point(point.clone(), paint)])
+                .unwrap_or_default(),
+            Geom::MultiPoint(points) => match self.point_paint(feature) {
+                Some(paint) => points
+                    .iter_points()
+                    .map(|point| RenderPrimitive::new_point(point.clone(), paint.clone()))
+                    .collect(),
+                None => vec![],
+            },
+            Geom::Contour(contour) => self
+                .line_paint(feature)
+                .map(|paint| vec![RenderPrimitive::new_contour_ref(contour, paint)])
+                .unwrap_or_default(),
+            Geom::MultiContour(contours) => match self.line_paint(feature) {
+                Some(paint) => contours
+                    .contours()
+                    .map(|contour| RenderPrimitive::new_contour_ref(contour, paint))
+                    .collect(),
+                None => vec![],
+            },
+            Geom::Polygon(polygon) => self
+                .polygon_paint(feature)
+                .map(|paint| vec![RenderPrimitive::new_polygon_ref(polygon, paint)])
+                .unwrap_or_default(),
+            Geom::MultiPolygon(polygons) => match self.polygon_paint(feature) {
+                Some(paint) => polygons
+                    .polygons()
+                    .map(|polygon| RenderPrimitive::new_polygon_ref(polygon, paint))
+                    .collect(),
+                None => vec![],
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::layer::vector_tile_layer::style::VectorTilePolygonSymbol;
+    use crate::Color;
+
+    fn feature(kind: &str, area: f64) -> BTreeMap<String, PropertyValue<'static>> {
+        let mut properties = BTreeMap::new();
+        properties.insert(
+            "kind".to_string(),
+            PropertyValue::String(kind.to_string().into()),
+        );
+        properties.insert("area".to_string(), PropertyValue::Float(area));
+        properties
+    }
+
+    #[test]
+    fn filters() {
+        let park = feature("park", 100.0);
+
+        let eq = PropertyFilter::Eq {
+            property: "kind".into(),
+            value: "park".into(),
+        };
+        let gt = PropertyFilter::Gt {
+            property: "area".into(),
+            value: 500.0,
+        };
+        let exists = PropertyFilter::Exists {
+            property: "name".into(),
+        };
+
+        assert!(eq.check(&park));
+        assert!(!gt.check(&park));
+        assert!(gt.check(&feature("park", 1000.0)));
+        assert!(!exists.check(&park));
+    }
+
+    #[test]
+    fn selects_first_matching_rule() {
+        let symbol = JsonSymbol {
+            rules: vec![
+                JsonSymbolRule {
+                    filters: vec![PropertyFilter::In {
+                        property: "kind".into(),
+                        values: vec!["forest".into(), "park".into()],
+                    }],
+                    symbol: VectorTileSymbol::Polygon(VectorTilePolygonSymbol {
+                        fill_color: Color::GREEN,
+                        palette_color: None,
+                        hatching: None,
+                    }),
+                },
+                JsonSymbolRule {
+                    filters: vec![],
+                    symbol: VectorTileSymbol::None,
+                },
+            ],
+            default_symbol: Default::default(),
+        };
+
+        assert_eq!(
+            symbol.polygon_paint(&feature("park", 1.0)).map(|p| p.color),
+            Some(Color::GREEN)
+        );
+        assert!(symbol.polygon_paint(&feature("road", 1.0)).is_none());
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn from_json() {
+        let symbol = JsonSymbol::from_json(
+            r##"{
+                "rules": [{
+                    "filters": [{"op": "not_eq", "property": "kind", "value": "road"}],
+                    "symbol": {"line": {"width": 2.0, "stroke_color": "#0000ffff"}}
+                }]
+            }"##,
+        )
+        .expect("failed to parse symbol");
+
+        assert_eq!(
+            symbol.line_paint(&feature("river", 1.0)).map(|p| p.width),
+            Some(2.0)
+        );
+        assert!(symbol.line_paint(&feature("road", 1.0)).is_none());
+    }
+}
@@ -0,0 +1,158 @@
+use galileo_types::cartesian::CartesianPoint3d;
+use galileo_types::contour::Contour as _;
+use galileo_types::geometry::Geom;
+use galileo_types::impls::{Contour, Polygon};
+use galileo_types::{MultiContour, MultiPoint};
+use num_traits::AsPrimitive;
+
+use crate::layer::feature_layer::symbol::Symbol;
+use crate::render::point_paint::{LabelBackground, PointPaint};
+use crate::render::render_bundle::RenderPrimitive;
+use crate::render::text::TextStyle;
+
+/// Renders text labels of features, optionally with a background, e.g. road number shields.
+///
+/// Points are labeled at their positions, and lines are labeled at the vertex closest to the middle of their length.
+/// Features for which the text function returns `None` are not rendered.
+pub struct LabelSymbol<F> {
+    text_fn: LabelTextFn<F>,
+    style: TextStyle,
+    background: Option<LabelBackground>,
+}
+
+/// Function returning the text of the label of a feature.
+type LabelTextFn<F> = Box<dyn Fn(&F) -> Option<String> + Send + Sync>;
+
+impl<F> LabelSymbol<F> {
+    /// Creates a new symbol that draws the text returned by `text_fn` with the given style.
+    pub fn new(
+        style: TextStyle,
+        text_fn: impl Fn(&F) -> Option<String> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            text_fn: Box::new(text_fn),
+            style,
+            background: None,
+        }
+    }
+
+    /// Sets the background drawn behind the text of the labels.
+    pub fn with_background(mut self, background: LabelBackground) -> Self {
+        self.background = Some(background);
+        self
+    }
+
+    fn paint(&self, feature: &F) -> Option<PointPaint<'static>> {
+        let text = (self.text_fn)(feature)?;
+        let paint = PointPaint::label_owned(text, self.style.clone());
+        Some(match &self.background {
+            Some(background) => paint.with_background(background.clone()),
+            None => paint,
+        })
+    }
+}
+
+impl<F> Symbol<F> for LabelSymbol<F> {
+    fn render<'a, N, P>(
+        &self,
+        feature: &F,
+        geometry: &'a Geom<P>,
+        _min_resolution: f64,
+    ) -> Vec<RenderPrimitive<'a, N, P, Contour<P>, Polygon<P>>>
+    where
+        N: AsPrimitive<f32>,
+        P: CartesianPoint3d<Num = N> + Clone,
+    {
+        let Some(paint) = self.paint(feature) else {
+            return vec![];
+        };
+
+        let positions: Vec<&P> = match geometry {
+            Geom::Point(point) => vec![point],
+            Geom::MultiPoint(points) => points.iter_points().collect(),
+            Geom::Contour(contour) => middle_vertex(contour).into_iter().collect(),
+            Geom::MultiContour(contours) => contours.contours().filter_map(middle_vertex).collect(),
+            _ => vec![],
+        };
+
+        positions
+            .into_iter()
+            .map(|position| RenderPrimitive::new_point(position.clone(), paint.clone()))
+            .collect()
+    }
+}
+
+/// Returns the vertex of the contour closest to the middle of its length.
+fn middle_vertex<N, P>(contour: &Contour<P>) -> Option<&P>
+where
+    N: AsPrimitive<f32>,
+    P: CartesianPoint3d<Num = N>,
+{
+    let vertices: Vec<&P> = contour.iter_points_closing().collect();
+    let points: Vec<[f64; 2]> = vertices
+        .iter()
+        .map(|p| [p.x().as_() as f64, p.y().as_() as f64])
+        .collect();
+    let (segment, fraction) = line_middle(&points)?;
+    let index = if fraction < 0.5 { segment } else { segment + 1 };
+
+    vertices.get(index).or(vertices.last()).copied()
+}
+
+/// Finds the middle of the line by its length. Returns the index of the segment that contains the middle point, and
+/// the position of the middle point on that segment as a fraction of the segment length.
+fn line_middle(points: &[[f64; 2]]) -> Option<(usize, f64)> {
+    if points.is_empty() {
+        return None;
+    }
+
+    let lengths: Vec<f64> = points
+        .windows(2)
+        .map(|segment| {
+            let dx = segment[1][0] - segment[0][0];
+            let dy = segment[1][1] - segment[0][1];
+            (dx * dx + dy * dy).sqrt()
+        })
+        .collect();
+
+    let mut remaining = lengths.iter().sum::<f64>() / 2.0;
+    for (index, length) in lengths.iter().enumerate() {
+        if remaining <= *length {
+            let fraction = if *length > 0.0 {
+                remaining / length
+            } else {
+                0.0
+            };
+            return Some((index, fraction));
+        }
+
+        remaining -= length;
+    }
+
+    Some((lengths.len().saturating_sub(1), 0.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use galileo_types::cartesian::Point3d;
+
+    use super::*;
+
+    #[test]
+    fn middle_of_line() {
+        assert_eq!(line_middle(&[]), None);
+        assert_eq!(line_middle(&[[1.0, 1.0]]), Some((0, 0.0)));
+        assert_eq!(
+            line_middle(&[[0.0, 0.0], [2.0, 0.0], [2.0, 6.0]]),
+            Some((1, 1.0 / 3.0))
+        );
+
+        let contour = Contour::open(vec![
+            Point3d::new(0.0, 0.0, 0.0),
+            Point3d::new(1.0, 0.0, 0.0),
+            Point3d::new(9.0, 0.0, 0.0),
+            Point3d::new(12.0, 0.0, 0.0),
+        ]);
+        assert_eq!(middle_vertex(&contour), Some(&Point3d::new(9.0, 0.0, 0.0)));
+    }
+}
@@ -5,17 +5,25 @@
 use num_traits::AsPrimitive;
 
 mod arbitrary;
+mod arc;
 mod contour;
+mod json;
+mod label;
 mod point;
 mod polygon;
+mod sprite;
 
 pub use arbitrary::ArbitraryGeometrySymbol;
+pub use arc::ArcSymbol;
 pub use contour::SimpleContourSymbol;
 use galileo_types::cartesian::CartesianPoint3d;
 use galileo_types::geometry::Geom;
 use galileo_types::impls::{Contour, Polygon};
-pub use point::{CirclePointSymbol, ImagePointSymbol};
+pub use json::{JsonSymbol, JsonSymbolRule, PropertyFilter};
+pub use label::LabelSymbol;
+pub use point::{CirclePointSymbol, DataDrivenPointSymbol, ImagePointSymbol, SizeUnits};
 pub use polygon::SimplePolygonSymbol;
+pub use sprite::{SpriteAtlas, SpriteInfo, SpriteSymbol};
 
 use crate::render::render_bundle::RenderPrimitive;
 
@@ -13,10 +13,32 @@ use num_traits::AsPrimitive;
 use crate::decoded_image::DecodedImage;
 use crate::error::GalileoError;
 use crate::layer::feature_layer::symbol::Symbol;
-use crate::render::point_paint::PointPaint;
+use crate::layer::feature_layer::Properties;
+use crate::render::point_paint::{PointPaint, RotationAlignment};
 use crate::render::render_bundle::RenderPrimitive;
 use crate::Color;
 
+/// Units the size of a point symbol is specified in.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum SizeUnits {
+    /// Size is given in screen pixels and does not depend on map resolution.
+    #[default]
+    Pixels,
+    /// Size is given in map units (e.g. meters for Web Mercator), so the symbol is scaled together with the map.
+    ///
+    /// The size on the screen is calculated for the resolution of the level of detail the feature is rendered at.
+    MapUnits,
+}
+
+impl SizeUnits {
+    fn to_pixels(self, size: f64, resolution: f64) -> f32 {
+        match self {
+            SizeUnits::Pixels => size as f32,
+            SizeUnits::MapUnits => (size / resolution) as f32,
+        }
+    }
+}
+
 /// Renders a point as a circle of fixes size.
 #[derive(Debug, Copy, Clone)]
 pub struct CirclePointSymbol {
@@ -24,12 +46,70 @@ pub struct CirclePointSymbol {
     pub color: Color,
     /// Diameter of the circle.
     pub size: f64,
+    /// Units of the `size` value.
+    pub size_units: SizeUnits,
+    /// Color of the outline.
+    pub stroke_color: Color,
+    /// Width of the outline in pixels. If set to `0.0`, no outline is drawn.
+    pub stroke_width: f64,
+    /// Opacity of the symbol in the range `[0.0, 1.0]`. Applied on top of the alpha channel of the colors.
+    pub opacity: f32,
+    /// Offset of the circle center from the point in pixels. Positive `y` values move the circle towards the top of
+    /// the screen.
+    pub offset: Vector2<f32>,
 }
 
 impl CirclePointSymbol {
     /// Create a new instance.
     pub fn new(color: Color, size: f64) -> Self {
-        Self { color, size }
+        Self {
+            color,
+            size,
+            size_units: SizeUnits::Pixels,
+            stroke_color: Default::default(),
+            stroke_width: 0.0,
+            opacity: 1.0,
+            offset: Vector2::default(),
+        }
+    }
+
+    /// Creates a new instance from a copy of the current, but with the given size units.
+    pub fn with_size_units(&self, size_units: SizeUnits) -> Self {
+        Self {
+            size_units,
+            ..*self
+        }
+    }
+
+    /// Creates a new instance from a copy of the current, but with the given outline.
+    pub fn with_outline(&self, stroke_color: Color, stroke_width: f64) -> Self {
+        Self {
+            stroke_color,
+            stroke_width,
+            ..*self
+        }
+    }
+
+    /// Creates a new instance from a copy of the current, but with the given opacity.
+    pub fn with_opacity(&self, opacity: f32) -> Self {
+        Self { opacity, ..*self }
+    }
+
+    /// Creates a new instance from a copy of the current, but with the given offset in pixels.
+    pub fn with_offset(&self, offset: Vector2<f32>) -> Self {
+        Self { offset, ..*self }
+    }
+
+    fn paint(&self, min_resolution: f64) -> PointPaint<'static> {
+        let mut paint = PointPaint::circle(
+            self.color,
+            self.size_units.to_pixels(self.size, min_resolution),
+        );
+        if self.stroke_width > 0.0 {
+            paint = paint.with_outline(self.stroke_color, self.stroke_width as f32);
+        }
+
+        paint.with_opacity(self.opacity).with_offset(self.offset)
     }
 }
 
@@ -38,13 +118,13 @@ impl<F> Symbol<F> for CirclePointSymbol {
         &self,
         _feature: &F,
         geometry: &'a Geom<P>,
-        _min_resolution: f64,
+        min_resolution: f64,
     ) -> Vec<RenderPrimitive<'a, N, P, Contour<P>, Polygon<P>>>
     where
         N: AsPrimitive<f32>,
         P: CartesianPoint3d<Num = N> + Clone,
     {
-        let paint = PointPaint::circle(self.color, self.size as f32);
+        let paint = self.paint(min_resolution);
         match geometry {
             Geom::Point(point) => vec![RenderPrimitive::new_point(point.clone(), paint)],
             Geom::MultiPoint(points) => points
@@ -58,10 +138,23 @@ impl<F> Symbol<F> for CirclePointSymbol {
 
 /// Symbol that renders a point with an image. The image size is fixed on the screen and does not depend on map
 /// resolution.
+///
+/// The image can be configured with builder methods:
+///
+/// ```ignore
+/// let symbol = ImagePointSymbol::from_path("pin.png", Vector2::new(0.5, 1.0), 1.0)?
+///     .with_rotation(std::f32::consts::FRAC_PI_4)
+///     .with_opacity(0.8);
+/// ```
 pub struct ImagePointSymbol {
     image: Arc<DecodedImage>,
+    anchor: Vector2<f32>,
     offset: Vector2<f32>,
     scale: f32,
+    scale_units: SizeUnits,
+    rotation: f32,
+    rotation_alignment: RotationAlignment,
+    opacity: f32,
 }
 
 impl ImagePointSymbol {
@@ -79,8 +172,13 @@ impl ImagePointSymbol {
                 Vec::from(image.to_rgba8().deref()),
                 Size::new(image.width(), image.height()),
             )?),
-            offset,
+            anchor: offset,
+            offset: Vector2::default(),
             scale,
+            scale_units: SizeUnits::Pixels,
+            rotation: 0.0,
+            rotation_alignment: RotationAlignment::Screen,
+            opacity: 1.0,
         })
     }
 
@@ -97,24 +195,82 @@ impl ImagePointSymbol {
                 Vec::from(image.as_bytes()),
                 Size::new(image.width(), image.height()),
             )?),
-            offset,
+            anchor: offset,
+            offset: Vector2::default(),
             scale,
+            scale_units: SizeUnits::Pixels,
+            rotation: 0.0,
+            rotation_alignment: RotationAlignment::Screen,
+            opacity: 1.0,
         })
     }
 }
 
+impl ImagePointSymbol {
+    /// Sets the anchor point of the image as a portion of image size, e.g. `[0.5, 1.0]` places the center-bottom
+    /// point of the image at the point position.
+    pub fn with_anchor(mut self, anchor: Vector2<f32>) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    /// Sets the offset of the image from the point position in pixels. Positive `y` values move the image towards
+    /// the top of the screen.
+    pub fn with_offset(mut self, offset: Vector2<f32>) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Sets the units of the image scale.
+    ///
+    /// With [`SizeUnits::Pixels`] scale of `1.0` draws one image pixel per screen pixel. With
+    /// [`SizeUnits::MapUnits`] scale specifies how many map units a single image pixel covers.
+    pub fn with_scale_units(mut self, scale_units: SizeUnits) -> Self {
+        self.scale_units = scale_units;
+        self
+    }
+
+    /// Sets rotation of the image around its anchor point in radians (counterclockwise).
+    pub fn with_rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Sets whether the rotation of the image is relative to the screen (default) or to the map.
+    ///
+    /// With [`RotationAlignment::Map`] the image is rotated together with the map, e.g. a wind direction arrow keeps
+    /// pointing in the same geographic direction.
+    pub fn with_rotation_alignment(mut self, alignment: RotationAlignment) -> Self {
+        self.rotation_alignment = alignment;
+        self
+    }
+
+    /// Sets opacity of the image in the range `[0.0, 1.0]`.
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity;
+        self
+    }
+}
+
 impl<F> Symbol<F> for ImagePointSymbol {
     fn render<'a, N, P>(
         &self,
         _feature: &F,
         geometry: &'a Geom<P>,
-        _min_resolution: f64,
+        min_resolution: f64,
     ) -> Vec<RenderPrimitive<'a, N, P, Contour<P>, Polygon<P>>>
     where
         N: AsPrimitive<f32>,
         P: CartesianPoint3d<Num = N> + Clone,
     {
-        let paint = PointPaint::image(self.image.clone(), self.offset, self.scale);
+        let scale = self
+            .scale_units
+            .to_pixels(self.scale as f64, min_resolution);
+        let paint = PointPaint::image(self.image.clone(), self.anchor, scale)
+            .with_offset(self.offset)
+            .with_rotation(self.rotation)
+            .with_rotation_alignment(self.rotation_alignment)
+            .with_opacity(self.opacity);
 
         match geometry {
             Geom::Point(point) => vec![RenderPrimitive::new_point(point.clone(), paint)],
@@ -127,9 +283,96 @@ impl<F> Symbol<F> for ImagePointSymbol {
     }
 }
 
+/// Wraps a point symbol, rotating and scaling its markers by the values of the feature properties.
+///
+/// Useful for wind direction arrows, vehicle headings and other markers, the orientation or size of which comes from
+/// data. Rotation and scale of the rendered points can be changed without tessellating them again, so after
+/// changing the properties of a feature, it is enough to update it with
+/// [`FeatureContainerMut::edit_style`](crate::layer::feature_layer::FeatureContainerMut::edit_style).
+///
+/// ```ignore
+/// let symbol = DataDrivenPointSymbol::new(arrow_symbol)
+///     .with_rotation_property("heading")
+///     .with_scale_property("speed_factor");
+/// ```
+#[derive(Debug, Clone)]
+pub struct DataDrivenPointSymbol<S> {
+    inner: S,
+    rotation_property: Option<String>,
+    scale_property: Option<String>,
+}
+
+impl<S> DataDrivenPointSymbol<S> {
+    /// Creates a new symbol that renders points with the `inner` symbol without changing them.
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            rotation_property: None,
+            scale_property: None,
+        }
+    }
+
+    /// Sets the name of the numeric property that contains the rotation of the marker in degrees clockwise, like
+    /// compass headings. The rotation is added to the rotation set by the inner symbol.
+    pub fn with_rotation_property(mut self, property: impl Into<String>) -> Self {
+        self.rotation_property = Some(property.into());
+        self
+    }
+
+    /// Sets the name of the numeric property that contains the scale factor of the marker. The size set by the inner
+    /// symbol is multiplied by the factor.
+    pub fn with_scale_property(mut self, property: impl Into<String>) -> Self {
+        self.scale_property = Some(property.into());
+        self
+    }
+
+    /// The wrapped symbol.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    fn numeric_property(feature: &impl Properties, property: &Option<String>) -> Option<f32> {
+        let value = feature.property(property.as_deref()?)?.as_f64()?;
+        value.is_finite().then_some(value as f32)
+    }
+}
+
+impl<F: Properties, S: Symbol<F>> Symbol<F> for DataDrivenPointSymbol<S> {
+    fn render<'a, N, P>(
+        &self,
+        feature: &F,
+        geometry: &'a Geom<P>,
+        min_resolution: f64,
+    ) -> Vec<RenderPrimitive<'a, N, P, Contour<P>, Polygon<P>>>
+    where
+        N: AsPrimitive<f32>,
+        P: CartesianPoint3d<Num = N> + Clone,
+    {
+        let rotation = Self::numeric_property(feature, &self.rotation_property)
+            .map(|degrees| -degrees.to_radians());
+        let scale = Self::numeric_property(feature, &self.scale_property);
+
+        let mut primitives = self.inner.render(feature, geometry, min_resolution);
+        for primitive in &mut primitives {
+            if let RenderPrimitive::Point(_, paint) = primitive {
+                let paint = paint.to_mut();
+                if let Some(rotation) = rotation {
+                    paint.rotation += rotation;
+                }
+                if let Some(scale) = scale {
+                    paint.scale *= scale;
+                }
+            }
+        }
+
+        primitives
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::render::point_paint::PointShape;
 
     #[test]
     fn image_symbol_from_file() {
@@ -143,4 +386,58 @@ mod tests {
         assert_eq!(symbol.image.height(), 99);
         assert_eq!(symbol.image.size(), 62 * 99 * 4);
     }
+
+    #[test]
+    fn data_driven_rotation_and_scale() {
+        use std::collections::HashMap;
+
+        use galileo_types::cartesian::Point3d;
+
+        let symbol = DataDrivenPointSymbol::new(CirclePointSymbol::new(Color::RED, 10.0))
+            .with_rotation_property("heading")
+            .with_scale_property("scale");
+        let feature = HashMap::from([("heading".to_string(), 90.0), ("scale".to_string(), 2.0)]);
+        let geometry = Geom::Point(Point3d::new(0.0, 0.0, 0.0));
+
+        let primitives: Vec<RenderPrimitive<f64, Point3d, Contour<Point3d>, Polygon<Point3d>>> =
+            symbol.render(&feature, &geometry, 1.0);
+        let [RenderPrimitive::Point(_, paint)] = &primitives[..] else {
+            panic!("unexpected primitives");
+        };
+        assert!((paint.rotation + std::f32::consts::FRAC_PI_2).abs() < 1e-6);
+        assert_eq!(paint.scale, 2.0);
+
+        let without_properties: HashMap<String, f64> = HashMap::new();
+        let primitives: Vec<RenderPrimitive<f64, Point3d, Contour<Point3d>, Polygon<Point3d>>> =
+            symbol.render(&without_properties, &geometry, 1.0);
+        let [RenderPrimitive::Point(_, paint)] = &primitives[..] else {
+            panic!("unexpected primitives");
+        };
+        assert_eq!(paint.rotation, 0.0);
+        assert_eq!(paint.scale, 1.0);
+    }
+
+    #[test]
+    fn size_units_to_pixels() {
+        assert_eq!(SizeUnits::Pixels.to_pixels(10.0, 5.0), 10.0);
+        assert_eq!(SizeUnits::MapUnits.to_pixels(10.0, 5.0), 2.0);
+    }
+
+    #[test]
+    fn circle_symbol_builder() {
+        let symbol = CirclePointSymbol::new(Color::RED, 100.0)
+            .with_size_units(SizeUnits::MapUnits)
+            .with_outline(Color::BLACK, 2.0)
+            .with_opacity(0.5);
+
+        let PointShape::Circle {
+            radius, outline, ..
+        } = symbol.paint(10.0).shape
+        else {
+            panic!("unexpected shape");
+        };
+
+        assert_eq!(radius, 5.0);
+        assert_eq!(outline.map(|v| v.width), Some(2.0));
+    }
 }
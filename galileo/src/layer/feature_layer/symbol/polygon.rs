@@ -6,7 +6,8 @@ use num_traits::AsPrimitive;
 
 use crate::layer::feature_layer::symbol::Symbol;
 use crate::render::render_bundle::RenderPrimitive;
-use crate::render::{LineCap, LinePaint, PolygonPaint};
+use crate::render::theme::Theme;
+use crate::render::{Hatching, LineCap, LinePaint, PolygonPaint};
 use crate::Color;
 
 /// Renders a polygon geometry as a filled polygon with an outline.
@@ -21,6 +22,10 @@ pub struct SimplePolygonSymbol {
     /// Offset of the outline in pixels. Positive offset will move outline outside of the polygon, negative offset
     /// will move the outline inside the polygon.
     pub stroke_offset: f64,
+    /// Index of the color in the palette of the current [`Theme`]. If set, it is used instead of the `fill_color`.
+    pub palette_color: Option<usize>,
+    /// Pattern drawn over the fill when [`Theme::show_patterns`] is enabled.
+    pub hatching: Option<Hatching>,
 }
 
 impl SimplePolygonSymbol {
@@ -31,6 +36,8 @@ impl SimplePolygonSymbol {
             stroke_color: Default::default(),
             stroke_width: 0.0,
             stroke_offset: 0.0,
+            palette_color: None,
+            hatching: None,
         }
     }
 
@@ -58,6 +65,23 @@ impl SimplePolygonSymbol {
         }
     }
 
+    /// Creates a new instance from a copy of the current, but with the fill color taken from the palette of the current
+    /// [`Theme`].
+    pub fn with_palette_color(&self, index: usize) -> Self {
+        Self {
+            palette_color: Some(index),
+            ..*self
+        }
+    }
+
+    /// Creates a new instance from a copy of the current, but with the given hatching pattern.
+    pub fn with_hatching(&self, hatching: Hatching) -> Self {
+        Self {
+            hatching: Some(hatching),
+            ..*self
+        }
+    }
+
     fn render_poly<'a, N, P>(
         &self,
         polygon: &'a galileo_types::impls::Polygon<P>,
@@ -66,11 +90,16 @@ impl SimplePolygonSymbol {
         N: AsPrimitive<f32>,
         P: CartesianPoint3d<Num = N> + Clone,
     {
+        let theme = Theme::current();
         let mut primitives = vec![];
         primitives.push(RenderPrimitive::new_polygon_ref(
             polygon,
             PolygonPaint {
-                color: self.fill_color,
+                color: self
+                    .palette_color
+                    .map(|index| theme.color(index))
+                    .unwrap_or(self.fill_color),
+                hatching: self.hatching.filter(|_| theme.show_patterns),
             },
         ));
 
@@ -79,6 +108,8 @@ impl SimplePolygonSymbol {
             width: self.stroke_width,
             offset: self.stroke_offset,
             line_cap: LineCap::Butt,
+            width_scale: None,
+            crisp: false,
         };
 
         for contour in polygon.iter_contours() {
@@ -0,0 +1,348 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use galileo_types::cartesian::{CartesianPoint3d, Size};
+use galileo_types::geometry::Geom;
+use galileo_types::impls::{Contour, Polygon};
+use galileo_types::MultiPoint;
+use nalgebra::Vector2;
+use num_traits::AsPrimitive;
+use serde::{Deserialize, Serialize};
+
+use crate::decoded_image::{DecodedImage, DecodedImageType};
+use crate::error::GalileoError;
+use crate::layer::feature_layer::symbol::Symbol;
+use crate::render::point_paint::PointPaint;
+use crate::render::render_bundle::RenderPrimitive;
+
+/// Position of a single sprite in an atlas image.
+///
+/// The structure has the same format as the entries of the sprite index files used by Mapbox and MapLibre styles, so
+/// such files can be loaded with [`SpriteAtlas::from_json`].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpriteInfo {
+    /// X coordinate of the left side of the sprite in the atlas in pixels.
+    pub x: u32,
+    /// Y coordinate of the top side of the sprite in the atlas in pixels.
+    pub y: u32,
+    /// Width of the sprite in pixels.
+    pub width: u32,
+    /// Height of the sprite in pixels.
+    pub height: u32,
+    /// Number of image pixels per screen pixel, e.g. `2.0` for `@2x` sprites.
+    #[serde(default = "default_pixel_ratio")]
+    pub pixel_ratio: f32,
+}
+
+fn default_pixel_ratio() -> f32 {
+    1.0
+}
+
+#[derive(Debug, Clone)]
+struct Sprite {
+    image: Arc<DecodedImage>,
+    pixel_ratio: f32,
+}
+
+/// Set of named images cut from a single atlas image.
+///
+/// The sprites are cut from the atlas once when it is loaded. All points drawn with the same sprite then share the
+/// same image, so a render bundle stores and uploads each sprite to the GPU only once, regardless of the number of
+/// features that use it.
+#[derive(Debug, Clone, Default)]
+pub struct SpriteAtlas {
+    sprites: HashMap<String, Sprite>,
+}
+
+impl SpriteAtlas {
+    /// Cuts the sprites with the given positions from the atlas image.
+    ///
+    /// Returns an error if any of the sprites lies outside of the image, or if the image is not a bitmap.
+    pub fn new(
+        image: &DecodedImage,
+        sprites: HashMap<String, SpriteInfo>,
+    ) -> Result<Self, GalileoError> {
+        #[allow(irrefutable_let_patterns)]
+        let DecodedImageType::Bitmap { bytes, dimensions } = &image.0
+        else {
+            return Err(GalileoError::Generic(
+                "sprites can only be cut from bitmap images".into(),
+            ));
+        };
+
+        let sprites = sprites
+            .into_iter()
+            .map(|(name, info)| {
+                let image = cut_sprite(bytes, *dimensions, &info).ok_or_else(|| {
+                    GalileoError::Generic(format!("sprite '{name}' is outside of the atlas image"))
+                })?;
+                let sprite = Sprite {
+                    image: Arc::new(image),
+                    pixel_ratio: info.pixel_ratio,
+                };
+                Ok((name, sprite))
+            })
+            .collect::<Result<_, GalileoError>>()?;
+
+        Ok(Self { sprites })
+    }
+
+    /// Cuts the sprites from the atlas image using a sprite index JSON in the Mapbox/MapLibre format, e.g.
+    /// `{"bus_stop": {"x": 0, "y": 0, "width": 24, "height": 24, "pixelRatio": 1}}`.
+    #[cfg(feature = "serde_json")]
+    pub fn from_json(image: &DecodedImage, json: &str) -> Result<Self, GalileoError> {
+        let sprites: HashMap<String, SpriteInfo> =
+            serde_json::from_str(json).map_err(|err| GalileoError::Generic(err.to_string()))?;
+        Self::new(image, sprites)
+    }
+
+    /// Returns the image of the sprite with the given name.
+    pub fn sprite(&self, name: &str) -> Option<&Arc<DecodedImage>> {
+        self.sprites.get(name).map(|sprite| &sprite.image)
+    }
+
+    /// Iterates over the names of the sprites in the atlas.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.sprites.keys().map(|name| name.as_str())
+    }
+
+    /// Number of sprites in the atlas.
+    pub fn len(&self) -> usize {
+        self.sprites.len()
+    }
+
+    /// Returns true if the atlas has no sprites.
+    pub fn is_empty(&self) -> bool {
+        self.sprites.is_empty()
+    }
+}
+
+fn cut_sprite(bytes: &[u8], atlas_size: Size<u32>, info: &SpriteInfo) -> Option<DecodedImage> {
+    if info.x.checked_add(info.width)? > atlas_size.width()
+        || info.y.checked_add(info.height)? > atlas_size.height()
+    {
+        return None;
+    }
+
+    let atlas_width = atlas_size.width() as usize;
+    let row_length = info.width as usize * 4;
+    let mut sprite = Vec::with_capacity(row_length * info.height as usize);
+    for row in info.y as usize..(info.y + info.height) as usize {
+        let start = (row * atlas_width + info.x as usize) * 4;
+        sprite.extend_from_slice(&bytes[start..start + row_length]);
+    }
+
+    DecodedImage::from_raw(sprite, Size::new(info.width, info.height)).ok()
+}
+
+/// Renders points with images from a [`SpriteAtlas`], choosing the sprite for every feature with a function.
+///
+/// ```ignore
+/// let atlas = Arc::new(SpriteAtlas::from_json(&atlas_image, &atlas_json)?);
+/// let symbol = SpriteSymbol::new(atlas)
+///     .with_sprite_fn(|feature: &Poi| feature.kind.as_str())
+///     .with_anchor(Vector2::new(0.5, 1.0));
+/// ```
+///
+/// Features for which the function returns a name that is not in the atlas are not rendered.
+pub struct SpriteSymbol<F> {
+    atlas: Arc<SpriteAtlas>,
+    sprite_fn: Option<SpriteFn<F>>,
+    anchor: Vector2<f32>,
+    offset: Vector2<f32>,
+    scale: f32,
+    opacity: f32,
+}
+
+/// Function returning the name of the sprite to draw a feature with.
+type SpriteFn<F> = Box<dyn Fn(&F) -> &str + Send + Sync>;
+
+impl<F> SpriteSymbol<F> {
+    /// Creates a new symbol with the given atlas. Nothing is rendered until the sprite function is set with
+    /// [`SpriteSymbol::with_sprite_fn`].
+    pub fn new(atlas: Arc<SpriteAtlas>) -> Self {
+        Self {
+            atlas,
+            sprite_fn: None,
+            anchor: Vector2::new(0.5, 0.5),
+            offset: Vector2::default(),
+            scale: 1.0,
+            opacity: 1.0,
+        }
+    }
+
+    /// Sets the function that returns the name of the sprite to draw the feature with.
+    pub fn with_sprite_fn(
+        mut self,
+        sprite_fn: impl Fn(&F) -> &str + Send + Sync + 'static,
+    ) -> Self {
+        self.sprite_fn = Some(Box::new(sprite_fn));
+        self
+    }
+
+    /// Sets the anchor point of the sprites as a portion of their size, e.g. `[0.5, 1.0]` places the center-bottom
+    /// point of the image at the point position. Default value is the center of the image.
+    pub fn with_anchor(mut self, anchor: Vector2<f32>) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    /// Sets the offset of the sprites from the point position in pixels. Positive `y` values move the image towards
+    /// the top of the screen.
+    pub fn with_offset(mut self, offset: Vector2<f32>) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Sets the scale of the sprites. With the scale of `1.0` sprites are drawn with their
+    /// [pixel ratio](SpriteInfo::pixel_ratio), i.e. one image pixel per screen pixel for `1x` sprites.
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Sets opacity of the sprites in the range `[0.0, 1.0]`.
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity;
+        self
+    }
+
+    /// The atlas the sprites are taken from.
+    pub fn atlas(&self) -> &Arc<SpriteAtlas> {
+        &self.atlas
+    }
+
+    fn paint(&self, feature: &F) -> Option<PointPaint<'static>> {
+        let name = (self.sprite_fn.as_ref()?)(feature);
+        let Some(sprite) = self.atlas.sprites.get(name) else {
+            log::debug!("Sprite '{name}' is not found in the atlas");
+            return None;
+        };
+
+        Some(
+            PointPaint::image(
+                sprite.image.clone(),
+                self.anchor,
+                self.scale / sprite.pixel_ratio,
+            )
+            .with_offset(self.offset)
+            .with_opacity(self.opacity),
+        )
+    }
+}
+
+impl<F> Symbol<F> for SpriteSymbol<F> {
+    fn render<'a, N, P>(
+        &self,
+        feature: &F,
+        geometry: &'a Geom<P>,
+        _min_resolution: f64,
+    ) -> Vec<RenderPrimitive<'a, N, P, Contour<P>, Polygon<P>>>
+    where
+        N: AsPrimitive<f32>,
+        P: CartesianPoint3d<Num = N> + Clone,
+    {
+        let Some(paint) = self.paint(feature) else {
+            return vec![];
+        };
+
+        match geometry {
+            Geom::Point(point) => vec![RenderPrimitive::new_point(point.clone(), paint)],
+            Geom::MultiPoint(points) => points
+                .iter_points()
+                .map(|point| RenderPrimitive::new_point(point.clone(), paint.clone()))
+                .collect(),
+            _ => vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use galileo_types::cartesian::Point3d;
+
+    use super::*;
+    use crate::render::point_paint::PointShape;
+
+    fn test_atlas() -> SpriteAtlas {
+        // 4x2 atlas with the left half filled with 1 and the right half with 2
+        let bytes: Vec<u8> = (0..8)
+            .flat_map(|i| if i % 4 < 2 { [1; 4] } else { [2; 4] })
+            .collect();
+        let image = DecodedImage::from_raw(bytes, Size::new(4, 2)).expect("valid image");
+        let sprite = |x, pixel_ratio| SpriteInfo {
+            x,
+            y: 0,
+            width: 2,
+            height: 2,
+            pixel_ratio,
+        };
+
+        SpriteAtlas::new(
+            &image,
+            HashMap::from([
+                ("bus".to_string(), sprite(0, 1.0)),
+                ("tram".to_string(), sprite(2, 2.0)),
+            ]),
+        )
+        .expect("valid atlas")
+    }
+
+    #[test]
+    fn cut_sprites_from_atlas() {
+        let atlas = test_atlas();
+        assert_eq!(atlas.len(), 2);
+
+        #[allow(irrefutable_let_patterns)]
+        let DecodedImageType::Bitmap { bytes, dimensions } = &atlas.sprite("tram").unwrap().0
+        else {
+            panic!("expected bitmap");
+        };
+        assert_eq!(*dimensions, Size::new(2, 2));
+        assert!(bytes.iter().all(|v| *v == 2));
+
+        let image = DecodedImage::from_raw(vec![0; 16], Size::new(2, 2)).expect("valid image");
+        let outside = SpriteInfo {
+            x: 1,
+            y: 0,
+            width: 2,
+            height: 2,
+            pixel_ratio: 1.0,
+        };
+        assert!(SpriteAtlas::new(&image, HashMap::from([("a".to_string(), outside)])).is_err());
+    }
+
+    #[test]
+    fn sprite_symbol_selects_sprite_by_feature() {
+        let atlas = Arc::new(test_atlas());
+        let symbol =
+            SpriteSymbol::new(atlas.clone()).with_sprite_fn(|feature: &String| feature.as_str());
+        let geometry = Geom::Point(Point3d::new(0.0, 0.0, 0.0));
+
+        let render = |feature: &str| {
+            let primitives: Vec<RenderPrimitive<f64, Point3d, Contour<Point3d>, Polygon<Point3d>>> =
+                symbol.render(&feature.to_string(), &geometry, 1.0);
+            primitives
+        };
+
+        let primitives = render("tram");
+        let [RenderPrimitive::Point(_, paint)] = &primitives[..] else {
+            panic!("unexpected primitives");
+        };
+        let PointShape::Image {
+            image,
+            width,
+            height,
+            ..
+        } = &paint.shape
+        else {
+            panic!("expected image");
+        };
+        assert!(Arc::ptr_eq(image, atlas.sprite("tram").unwrap()));
+        // 2x2 pixels sprite with pixel ratio 2 is drawn as 1x1 logical pixels
+        assert_eq!((*width, *height), (1.0, 1.0));
+
+        assert!(render("unknown").is_empty());
+    }
+}
@@ -0,0 +1,192 @@
+//! Element tree of XML documents, used to read GPX and KML files.
+
+use std::io::BufRead;
+
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::{Decoder, Reader, XmlVersion};
+
+use crate::error::GalileoError;
+
+/// XML element with its attributes, text and child elements. Namespace prefixes are dropped from the names of the
+/// elements and attributes.
+#[derive(Debug)]
+pub(super) struct XmlElement {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<XmlElement>,
+    text: String,
+}
+
+impl XmlElement {
+    /// Reads the root element of the document.
+    pub fn read(reader: impl BufRead) -> Result<Self, GalileoError> {
+        let mut reader = Reader::from_reader(reader);
+        let mut buf = vec![];
+        let mut stack: Vec<XmlElement> = vec![];
+
+        loop {
+            let element = match reader.read_event_into(&mut buf).map_err(xml_error)? {
+                Event::Start(start) => {
+                    stack.push(Self::new(&start, reader.decoder())?);
+                    None
+                }
+                Event::Empty(start) => Some(Self::new(&start, reader.decoder())?),
+                Event::End(_) => Some(
+                    stack
+                        .pop()
+                        .ok_or_else(|| xml_error("unexpected closing tag"))?,
+                ),
+                Event::Text(text) => {
+                    push_text(&mut stack, &text.decode().map_err(xml_error)?);
+                    None
+                }
+                Event::CData(cdata) => {
+                    push_text(&mut stack, &cdata.decode().map_err(xml_error)?);
+                    None
+                }
+                Event::GeneralRef(reference) => {
+                    let resolved = match reference.resolve_char_ref().map_err(xml_error)? {
+                        Some(char) => char.to_string(),
+                        None => {
+                            let name = reference.decode().map_err(xml_error)?;
+                            resolve_predefined_entity(&name)
+                                .ok_or_else(|| xml_error(format!("unknown entity &{name};")))?
+                                .to_string()
+                        }
+                    };
+                    push_text(&mut stack, &resolved);
+                    None
+                }
+                Event::Eof => return Err(xml_error("unexpected end of document")),
+                _ => None,
+            };
+
+            if let Some(element) = element {
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(element),
+                    None => return Ok(element),
+                }
+            }
+
+            buf.clear();
+        }
+    }
+
+    fn new(start: &BytesStart, decoder: Decoder) -> Result<Self, GalileoError> {
+        let attributes = start
+            .attributes()
+            .map(|attribute| {
+                let attribute = attribute.map_err(xml_error)?;
+                let value = attribute
+                    .decoded_and_normalized_value(XmlVersion::Implicit1_0, decoder)
+                    .map_err(xml_error)?;
+                Ok((
+                    String::from_utf8_lossy(attribute.key.local_name().as_ref()).into_owned(),
+                    value.into_owned(),
+                ))
+            })
+            .collect::<Result<_, GalileoError>>()?;
+
+        Ok(Self {
+            name: String::from_utf8_lossy(start.local_name().as_ref()).into_owned(),
+            attributes,
+            children: vec![],
+            text: String::new(),
+        })
+    }
+
+    /// Name of the element without the namespace prefix.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Value of the attribute with the given name.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(attribute, _)| attribute == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Text content of the element without leading and trailing whitespace.
+    pub fn text(&self) -> &str {
+        self.text.trim()
+    }
+
+    /// Child elements.
+    pub fn children(&self) -> &[XmlElement] {
+        &self.children
+    }
+
+    /// Child elements with the given name.
+    pub fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a XmlElement> {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+
+    /// The first child element with the given name.
+    pub fn child(&self, name: &str) -> Option<&XmlElement> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    /// Text of the first child element with the given name.
+    pub fn child_text(&self, name: &str) -> Option<&str> {
+        self.child(name).map(|child| child.text())
+    }
+
+    /// Elements with the given name at any depth below this element. Elements inside the found elements are not
+    /// searched.
+    pub fn descendants<'a>(&'a self, name: &str, found: &mut Vec<&'a XmlElement>) {
+        for child in &self.children {
+            if child.name == name {
+                found.push(child);
+            } else {
+                child.descendants(name, found);
+            }
+        }
+    }
+}
+
+fn push_text(stack: &mut [XmlElement], text: &str) {
+    if let Some(element) = stack.last_mut() {
+        element.text.push_str(text);
+    }
+}
+
+fn xml_error(err: impl std::fmt::Display) -> GalileoError {
+    GalileoError::Generic(format!("invalid XML: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_element_tree() {
+        let xml = r#"<?xml version="1.0"?>
+            <root xmlns:x="urn:x">
+                <x:item id="1">A &amp; B</x:item>
+                <item id="2"><![CDATA[<b>C</b>]]></item>
+                <group><item id="3"/></group>
+            </root>"#;
+        let root = XmlElement::read(xml.as_bytes()).expect("invalid XML");
+
+        assert_eq!(root.name(), "root");
+        assert_eq!(root.children().len(), 3);
+        assert_eq!(root.child_text("item"), Some("A & B"));
+
+        let items: Vec<_> = root.children_named("item").collect();
+        assert_eq!(items[1].attribute("id"), Some("2"));
+        assert_eq!(items[1].text(), "<b>C</b>");
+
+        let mut found = vec![];
+        root.descendants("item", &mut found);
+        assert_eq!(found.len(), 3);
+        assert_eq!(found[2].attribute("id"), Some("3"));
+    }
+
+    #[test]
+    fn unclosed_element() {
+        assert!(XmlElement::read("<root><item>".as_bytes()).is_err());
+    }
+}
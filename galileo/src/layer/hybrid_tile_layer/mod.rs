@@ -0,0 +1,419 @@
+//! [`HybridTileLayer`] draws a basemap either from vector or from raster tiles, falling back to raster tiles when
+//! vector tile processing is too expensive.
+
+use std::any::Any;
+use std::sync::Arc;
+
+use maybe_sync::{MaybeSend, MaybeSync};
+use parking_lot::Mutex;
+use web_time::{Duration, Instant};
+
+use crate::decoded_image::DecodedImage;
+use crate::layer::data_provider::{DataProvider, TileSourceStats};
+use crate::layer::{Layer, RasterTileLayer, VectorTileLayer};
+use crate::messenger::Messenger;
+use crate::render::Canvas;
+use crate::tile_scheme::TileIndex;
+use crate::view::MapView;
+
+/// Weight of the last frame in the average frame time.
+const FRAME_TIME_SMOOTHING: f64 = 0.1;
+/// Number of frames that must be rendered before the average frame time is trusted.
+const MIN_MEASURED_FRAMES: u32 = 30;
+
+/// Which of the paired layers [`HybridTileLayer`] draws.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HybridSource {
+    /// Raster tiles.
+    Raster,
+    /// Vector tiles.
+    Vector,
+}
+
+/// Strategy of selecting the source of [`HybridTileLayer`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum HybridMode {
+    /// Vector tiles are used, unless the map is zoomed out below
+    /// [`raster_below_zoom`](HybridTileLayer::set_raster_below_zoom) level, or rendering of vector tiles became
+    /// slower than the [limit](HybridTileLayer::set_frame_time_limit).
+    #[default]
+    Auto,
+    /// Always use raster tiles.
+    Raster,
+    /// Always use vector tiles.
+    Vector,
+}
+
+/// Layer that pairs a [`VectorTileLayer`] with a [`RasterTileLayer`] of the same basemap and draws one of them.
+///
+/// Vector tiles look better, but decoding and tessellating them can be too slow on some devices. This layer draws
+/// raster tiles instead of vector ones:
+/// * when the map is zoomed out below the level set by [`HybridTileLayer::set_raster_below_zoom`], since zoomed out
+///   vector tiles are usually the most expensive to process;
+/// * when the average time of rendering vector tiles exceeds the limit set by
+///   [`HybridTileLayer::set_frame_time_limit`]. Once this fallback is triggered, the layer keeps using raster tiles
+///   until [`HybridTileLayer::reset_frame_rate_fallback`] is called, so that it does not switch back and forth.
+///
+/// When the source is switched, both layers are drawn (raster tiles over the vector ones) for the
+/// [transition duration](HybridTileLayer::set_transition_duration), so the map is not blank while the tiles of the
+/// new source are loading.
+pub struct HybridTileLayer<Provider>
+where
+    Provider: DataProvider<TileIndex, DecodedImage, ()> + MaybeSync + MaybeSend,
+{
+    raster: RasterTileLayer<Provider>,
+    vector: VectorTileLayer,
+    mode: HybridMode,
+    raster_below_zoom: Option<u32>,
+    frame_time_limit: Option<Duration>,
+    transition_duration: Duration,
+    state: Mutex<HybridState>,
+}
+
+struct HybridState {
+    active: HybridSource,
+    previous: Option<(HybridSource, Instant)>,
+    frame_monitor: FrameMonitor,
+    frame_rate_fallback: bool,
+}
+
+impl<Provider> HybridTileLayer<Provider>
+where
+    Provider: DataProvider<TileIndex, DecodedImage, ()> + MaybeSync + MaybeSend,
+{
+    /// Creates a new layer from the pair of layers drawing the same basemap. By default, the layer is in
+    /// [`HybridMode::Auto`] mode without a zoom or frame time limits set, so it draws vector tiles until configured
+    /// otherwise.
+    pub fn new(raster: RasterTileLayer<Provider>, vector: VectorTileLayer) -> Self {
+        Self {
+            raster,
+            vector,
+            mode: HybridMode::default(),
+            raster_below_zoom: None,
+            frame_time_limit: None,
+            transition_duration: Duration::from_millis(500),
+            state: Mutex::new(HybridState {
+                active: HybridSource::Vector,
+                previous: None,
+                frame_monitor: FrameMonitor::default(),
+                frame_rate_fallback: false,
+            }),
+        }
+    }
+
+    /// Raster layer of the pair.
+    pub fn raster(&self) -> &RasterTileLayer<Provider> {
+        &self.raster
+    }
+
+    /// Mutable reference to the raster layer of the pair.
+    pub fn raster_mut(&mut self) -> &mut RasterTileLayer<Provider> {
+        &mut self.raster
+    }
+
+    /// Vector layer of the pair.
+    pub fn vector(&self) -> &VectorTileLayer {
+        &self.vector
+    }
+
+    /// Mutable reference to the vector layer of the pair.
+    pub fn vector_mut(&mut self) -> &mut VectorTileLayer {
+        &mut self.vector
+    }
+
+    /// Strategy of selecting the source.
+    pub fn mode(&self) -> HybridMode {
+        self.mode
+    }
+
+    /// Sets the strategy of selecting the source.
+    pub fn set_mode(&mut self, mode: HybridMode) {
+        self.mode = mode;
+    }
+
+    /// Sets the strategy of selecting the source.
+    pub fn with_mode(mut self, mode: HybridMode) -> Self {
+        self.set_mode(mode);
+        self
+    }
+
+    /// Sets the zoom level (z index of the vector layer tile schema), below which raster tiles are drawn in
+    /// [`HybridMode::Auto`] mode. `None` means vector tiles are drawn at all zoom levels.
+    pub fn set_raster_below_zoom(&mut self, zoom: Option<u32>) {
+        self.raster_below_zoom = zoom;
+    }
+
+    /// Sets the zoom level, below which raster tiles are drawn. See [`HybridTileLayer::set_raster_below_zoom`].
+    pub fn with_raster_below_zoom(mut self, zoom: u32) -> Self {
+        self.set_raster_below_zoom(Some(zoom));
+        self
+    }
+
+    /// Sets the maximum average frame time for drawing vector tiles in [`HybridMode::Auto`] mode. If the average
+    /// frame time exceeds this value, the layer switches to raster tiles. `None` disables the check.
+    ///
+    /// Frame time is the time the layer spends rendering vector tiles in a frame. Time between frames, e.g. when the
+    /// application is idle, is not counted. For example, to fall back to raster tiles when drawing vector tiles
+    /// alone does not fit into a frame at 20 FPS, set the limit to 50 ms.
+    pub fn set_frame_time_limit(&mut self, limit: Option<Duration>) {
+        self.frame_time_limit = limit;
+    }
+
+    /// Sets the maximum average frame time for drawing vector tiles. See [`HybridTileLayer::set_frame_time_limit`].
+    pub fn with_frame_time_limit(mut self, limit: Duration) -> Self {
+        self.set_frame_time_limit(Some(limit));
+        self
+    }
+
+    /// Sets the time during which both sources are drawn after switching.
+    ///
+    /// Default value is 500 ms.
+    pub fn set_transition_duration(&mut self, duration: Duration) {
+        self.transition_duration = duration;
+    }
+
+    /// Source drawn by the layer during the last render.
+    pub fn active_source(&self) -> HybridSource {
+        self.state.lock().active
+    }
+
+    /// Returns true if the layer fell back to raster tiles because of the low frame rate.
+    pub fn is_frame_rate_fallback_active(&self) -> bool {
+        self.state.lock().frame_rate_fallback
+    }
+
+    /// Switches the layer back to vector tiles after it fell back to raster tiles because of the low frame rate, and
+    /// restarts frame rate measurement.
+    pub fn reset_frame_rate_fallback(&self) {
+        let mut state = self.state.lock();
+        state.frame_rate_fallback = false;
+        state.frame_monitor = FrameMonitor::default();
+    }
+
+    fn target_source(&self, view: &MapView, frame_rate_fallback: bool) -> HybridSource {
+        let zoom = self
+            .vector
+            .tile_schema()
+            .select_lod(view.resolution())
+            .map(|lod| lod.z_index());
+        select_source(self.mode, zoom, self.raster_below_zoom, frame_rate_fallback)
+    }
+
+    fn layer(&self, source: HybridSource) -> &dyn Layer
+    where
+        Provider: 'static,
+    {
+        match source {
+            HybridSource::Raster => &self.raster,
+            HybridSource::Vector => &self.vector,
+        }
+    }
+}
+
+impl<Provider> Layer for HybridTileLayer<Provider>
+where
+    Provider: DataProvider<TileIndex, DecodedImage, ()> + MaybeSync + MaybeSend + 'static,
+{
+    fn render(&self, view: &MapView, canvas: &mut dyn Canvas) {
+        let now = Instant::now();
+        let (active, previous) = {
+            let mut state = self.state.lock();
+            if state.active == HybridSource::Vector
+                && self.mode == HybridMode::Auto
+                && !state.frame_rate_fallback
+            {
+                if let (Some(limit), Some(average)) =
+                    (self.frame_time_limit, state.frame_monitor.average())
+                {
+                    if average > limit {
+                        log::info!(
+                            "Average frame time {average:?} exceeds the limit {limit:?}, falling back to raster tiles"
+                        );
+                        state.frame_rate_fallback = true;
+                    }
+                }
+            }
+
+            let target = self.target_source(view, state.frame_rate_fallback);
+            if target != state.active {
+                state.previous = Some((state.active, now));
+                state.active = target;
+                // Frame time of one source says nothing about the other one
+                state.frame_monitor = FrameMonitor::default();
+            }
+
+            if let Some((_, switched_at)) = state.previous {
+                if now.duration_since(switched_at) > self.transition_duration {
+                    state.previous = None;
+                }
+            }
+
+            (state.active, state.previous.map(|(source, _)| source))
+        };
+
+        if previous.is_some_and(|previous| previous != active) {
+            // Vector layer draws opaque background, so it must be drawn below the raster tiles, which fade in as
+            // they are loaded
+            self.vector.render(view, canvas);
+            self.raster.render(view, canvas);
+        } else if active == HybridSource::Vector {
+            // Only the time spent on rendering is measured, so that idle time between frames does not count
+            self.state.lock().frame_monitor.start_frame(Instant::now());
+            self.vector.render(view, canvas);
+            self.state.lock().frame_monitor.end_frame(Instant::now());
+        } else {
+            self.layer(active).render(view, canvas);
+        }
+    }
+
+    fn prepare(&self, view: &MapView) {
+        let frame_rate_fallback = self.state.lock().frame_rate_fallback;
+        self.layer(self.target_source(view, frame_rate_fallback))
+            .prepare(view);
+    }
+
+    fn set_messenger(&mut self, messenger: Box<dyn Messenger>) {
+        let messenger: Arc<dyn Messenger> = Arc::from(messenger);
+        self.raster.set_messenger(Box::new(messenger.clone()));
+        self.vector.set_messenger(Box::new(messenger));
+    }
+
+    fn clear_render_cache(&self) {
+        self.raster.clear_render_cache();
+        self.vector.clear_render_cache();
+    }
+
+    fn tile_source_stats(&self) -> Option<TileSourceStats> {
+        match (self.raster.source_stats(), self.vector.source_stats()) {
+            (Some(raster), Some(vector)) => Some(raster + vector),
+            (raster, vector) => raster.or(vector),
+        }
+    }
+
+    fn is_ready(&self, view: &MapView) -> bool {
+        let frame_rate_fallback = self.state.lock().frame_rate_fallback;
+        self.layer(self.target_source(view, frame_rate_fallback))
+            .is_ready(view)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+fn select_source(
+    mode: HybridMode,
+    zoom: Option<u32>,
+    raster_below_zoom: Option<u32>,
+    frame_rate_fallback: bool,
+) -> HybridSource {
+    match mode {
+        HybridMode::Raster => HybridSource::Raster,
+        HybridMode::Vector => HybridSource::Vector,
+        HybridMode::Auto => {
+            let zoomed_out = match (zoom, raster_below_zoom) {
+                (Some(zoom), Some(limit)) => zoom < limit,
+                _ => false,
+            };
+
+            if zoomed_out || frame_rate_fallback {
+                HybridSource::Raster
+            } else {
+                HybridSource::Vector
+            }
+        }
+    }
+}
+
+/// Calculates moving average of the time it takes to render a frame.
+#[derive(Debug, Default)]
+struct FrameMonitor {
+    frame_start: Option<Instant>,
+    average: Option<Duration>,
+    measured_frames: u32,
+}
+
+impl FrameMonitor {
+    /// Marks the start of rendering of a frame.
+    fn start_frame(&mut self, now: Instant) {
+        self.frame_start = Some(now);
+    }
+
+    /// Marks the end of rendering of the frame started with [`FrameMonitor::start_frame`].
+    fn end_frame(&mut self, now: Instant) {
+        let Some(frame_start) = self.frame_start.take() else {
+            return;
+        };
+
+        let frame_time = now.saturating_duration_since(frame_start);
+        self.measured_frames += 1;
+        self.average = Some(match self.average {
+            None => frame_time,
+            Some(average) => {
+                average.mul_f64(1.0 - FRAME_TIME_SMOOTHING)
+                    + frame_time.mul_f64(FRAME_TIME_SMOOTHING)
+            }
+        });
+    }
+
+    /// Average frame time, if enough frames were measured.
+    fn average(&self) -> Option<Duration> {
+        if self.measured_frames >= MIN_MEASURED_FRAMES {
+            self.average
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn source_selection() {
+        assert_eq!(
+            select_source(HybridMode::Auto, Some(5), Some(8), false),
+            HybridSource::Raster
+        );
+        assert_eq!(
+            select_source(HybridMode::Auto, Some(10), Some(8), false),
+            HybridSource::Vector
+        );
+        assert_eq!(
+            select_source(HybridMode::Auto, Some(10), None, true),
+            HybridSource::Raster
+        );
+        assert_eq!(
+            select_source(HybridMode::Vector, Some(5), Some(8), true),
+            HybridSource::Vector
+        );
+        assert_eq!(
+            select_source(HybridMode::Raster, Some(10), None, false),
+            HybridSource::Raster
+        );
+    }
+
+    #[test]
+    fn frame_monitor_ignores_idle_time() {
+        let mut monitor = FrameMonitor::default();
+        let mut now = Instant::now();
+        for _ in 0..MIN_MEASURED_FRAMES {
+            assert_eq!(monitor.average(), None);
+
+            // Fast frames with long pauses between them
+            monitor.start_frame(now);
+            now += Duration::from_millis(5);
+            monitor.end_frame(now);
+            now += Duration::from_secs(10);
+        }
+
+        let average = monitor.average().expect("enough frames are measured");
+        assert!((average.as_secs_f64() - 0.005).abs() < 1e-6);
+        assert!(average < Duration::from_millis(50));
+    }
+}
@@ -0,0 +1,195 @@
+//! [`ImageLayer`] draws a single georeferenced image.
+
+use std::any::Any;
+
+use galileo_types::cartesian::{Point2d, Rect, Size};
+use galileo_types::geo::Crs;
+use parking_lot::Mutex;
+
+use crate::decoded_image::DecodedImage;
+use crate::error::GalileoError;
+use crate::layer::Layer;
+use crate::messenger::Messenger;
+use crate::render::{Canvas, ImagePaint, PackedBundle, RenderOptions};
+use crate::view::MapView;
+
+/// Layer that draws a single image placed at a fixed location on the map, e.g. a scanned map or an aerial photo
+/// georeferenced with a [world file](https://en.wikipedia.org/wiki/World_file).
+///
+/// The image is drawn only if the CRS of the map view is the same as the CRS of the layer. The image is not
+/// reprojected.
+///
+/// ```no_run
+/// use galileo::decoded_image::DecodedImage;
+/// use galileo::galileo_types::geo::Crs;
+/// use galileo::layer::ImageLayer;
+///
+/// let image = DecodedImage::decode(&std::fs::read("map.png").expect("failed to read image"))
+///     .expect("failed to decode image");
+/// let world_file = std::fs::read_to_string("map.pgw").expect("failed to read world file");
+/// let layer =
+///     ImageLayer::from_world_file(image, &world_file, Crs::EPSG3857).expect("invalid world file");
+/// ```
+pub struct ImageLayer {
+    image: DecodedImage,
+    vertices: [Point2d; 4],
+    crs: Crs,
+    opacity: u8,
+    packed: Mutex<Option<Box<dyn PackedBundle>>>,
+}
+
+impl ImageLayer {
+    /// Creates a new layer that draws the image stretched over the `bbox` in the given CRS.
+    pub fn new(image: DecodedImage, bbox: Rect, crs: Crs) -> Self {
+        Self::from_quadrangle(image, bbox.into_quadrangle(), crs)
+    }
+
+    /// Creates a new layer that draws the image over the quadrangle with the given vertices. The vertices are the
+    /// positions of the bottom left, top left, top right and bottom right corners of the image.
+    pub fn from_quadrangle(image: DecodedImage, vertices: [Point2d; 4], crs: Crs) -> Self {
+        Self {
+            image,
+            vertices,
+            crs,
+            opacity: 255,
+            packed: Mutex::new(None),
+        }
+    }
+
+    /// Creates a new layer that draws the image at the position set by the contents of its world file. Rotation
+    /// terms of the world file are supported.
+    ///
+    /// World files do not specify the CRS, so it must be provided by the caller.
+    pub fn from_world_file(
+        image: DecodedImage,
+        world_file: &str,
+        crs: Crs,
+    ) -> Result<Self, GalileoError> {
+        let size = Size::new(image.width(), image.height());
+        let vertices = world_file_quadrangle(world_file, size)?;
+        Ok(Self::from_quadrangle(image, vertices, crs))
+    }
+
+    /// Sets the opacity of the image.
+    pub fn with_opacity(mut self, opacity: u8) -> Self {
+        self.opacity = opacity;
+        self
+    }
+
+    /// CRS of the layer.
+    pub fn crs(&self) -> &Crs {
+        &self.crs
+    }
+
+    /// Bounding rectangle of the image in the CRS of the layer.
+    pub fn bbox(&self) -> Rect {
+        Rect::from_points(self.vertices.iter()).expect("image always has vertices")
+    }
+}
+
+impl Layer for ImageLayer {
+    fn render(&self, view: &MapView, canvas: &mut dyn Canvas) {
+        if *view.crs() != self.crs {
+            return;
+        }
+
+        let mut packed = self.packed.lock();
+        // The image is kept, so that it can be packed again after the render cache is cleared
+        let packed = packed.get_or_insert_with(|| {
+            let mut bundle = canvas.create_bundle();
+            bundle.add_image(
+                self.image.clone(),
+                self.vertices,
+                ImagePaint {
+                    opacity: self.opacity,
+                },
+            );
+            canvas.pack_bundle(&bundle)
+        });
+
+        canvas.draw_bundles(&[&**packed], RenderOptions::default());
+    }
+
+    fn prepare(&self, _view: &MapView) {
+        // do nothing
+    }
+
+    fn clear_render_cache(&self) {
+        *self.packed.lock() = None;
+    }
+
+    fn set_messenger(&mut self, _messenger: Box<dyn Messenger>) {
+        // do nothing
+    }
+
+    fn extent(&self, crs: &Crs) -> Option<Rect> {
+        (*crs == self.crs).then(|| self.bbox())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Returns the positions of the corners of the image with the given size, georeferenced with the world file.
+///
+/// A world file contains 6 lines with the parameters of the affine transformation from the pixel coordinates to the
+/// map coordinates: the pixel size in `x` direction, two rotation terms, the pixel size in `y` direction (usually
+/// negative), and the map coordinates of the center of the top left pixel.
+fn world_file_quadrangle(world_file: &str, size: Size<u32>) -> Result<[Point2d; 4], GalileoError> {
+    let parameters = world_file
+        .split_whitespace()
+        .map(|value| value.parse::<f64>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| GalileoError::Generic(format!("invalid world file: {err}")))?;
+    let &[a, d, b, e, c, f] = parameters.as_slice() else {
+        return Err(GalileoError::Generic(format!(
+            "invalid world file: expected 6 parameters, got {}",
+            parameters.len()
+        )));
+    };
+
+    // Edges of the image are half a pixel away from the centers of the border pixels
+    let (left, top) = (-0.5, -0.5);
+    let (right, bottom) = (size.width() as f64 - 0.5, size.height() as f64 - 0.5);
+    let to_map =
+        |column: f64, row: f64| Point2d::new(a * column + b * row + c, d * column + e * row + f);
+
+    Ok([
+        to_map(left, bottom),
+        to_map(left, top),
+        to_map(right, top),
+        to_map(right, bottom),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn world_file_corners() {
+        let world_file = "10.0\n0.0\n0.0\n-10.0\n1005.0\n1995.0\n";
+        let vertices =
+            world_file_quadrangle(world_file, Size::new(20, 10)).expect("invalid world file");
+        assert_eq!(
+            vertices,
+            [
+                Point2d::new(1000.0, 1900.0),
+                Point2d::new(1000.0, 2000.0),
+                Point2d::new(1200.0, 2000.0),
+                Point2d::new(1200.0, 1900.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_world_file() {
+        assert!(world_file_quadrangle("1.0 0.0 0.0 -1.0", Size::new(1, 1)).is_err());
+        assert!(world_file_quadrangle("1.0 0.0 0.0 -1.0 a 0.0", Size::new(1, 1)).is_err());
+    }
+}
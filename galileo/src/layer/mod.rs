@@ -3,29 +3,54 @@
 use std::any::Any;
 use std::sync::Arc;
 
+use galileo_types::cartesian::Rect;
+use galileo_types::geo::Crs;
 use maybe_sync::{MaybeSend, MaybeSync};
 use parking_lot::RwLock;
 
+use crate::layer::data_provider::TileSourceStats;
+use crate::map::LayerTransform;
 use crate::messenger::Messenger;
 use crate::render::Canvas;
 use crate::view::MapView;
 
+mod annotation_layer;
 pub mod data_provider;
+mod day_night_layer;
 pub mod feature_layer;
+pub mod hybrid_tile_layer;
+mod image_layer;
+mod raster_switch_layer;
 mod raster_tile_layer;
 pub mod vector_tile_layer;
+mod wms_layer;
 
+pub use annotation_layer::{AnnotationLayer, CoordinateSpace};
+pub use day_night_layer::DayNightLayer;
 pub use feature_layer::FeatureLayer;
-pub use raster_tile_layer::RasterTileLayer;
-pub use vector_tile_layer::VectorTileLayer;
+pub use hybrid_tile_layer::HybridTileLayer;
+pub use image_layer::ImageLayer;
+pub use raster_switch_layer::{RasterSwitchLayer, SwitchSource};
+pub use raster_tile_layer::{Basemap, RasterTileLayer};
+pub use vector_tile_layer::{VectorBasemap, VectorTileLayer};
+pub use wms_layer::{WmsLayer, WmsVersion};
 
 /// Layers specify a data source and the way the data should be rendered to the map.
 ///
-/// There are currently 3 types of layers:
+/// There are currently 9 types of layers:
 /// * [`RasterTileLayer`] - downloads prerendered tiles from an Internet source and draws them as is.
 /// * [`VectorTileLayer`] - downloads vector tiles (in MVT format) from an Internet source and draws them using the
 ///   provided stylesheet.
 /// * [`FeatureLayer`] - draws custom set of geographic objects with the given [`feature_layer::Symbol`];
+/// * [`HybridTileLayer`] - draws a basemap from vector tiles, falling back to raster tiles of the same basemap when
+///   vector tiles are too expensive to process.
+/// * [`AnnotationLayer`] - draws transient graphics (debug overlays, cursors, selection boxes) added with an
+///   immediate-mode drawing API.
+/// * [`DayNightLayer`] - shades the night side of the Earth and marks the position of the sun for the given time.
+/// * [`ImageLayer`] - draws a single georeferenced image, e.g. a raster with a world file.
+/// * [`WmsLayer`] - requests map images for the current view from an OGC WMS server.
+/// * [`RasterSwitchLayer`] - draws one of two raster tile layers depending on the zoom level, cross-fading between
+///   them at the switch.
 pub trait Layer: MaybeSend + MaybeSync {
     /// Renders the layer to the given canvas.
     fn render(&self, view: &MapView, canvas: &mut dyn Canvas);
@@ -35,6 +60,35 @@ pub trait Layer: MaybeSend + MaybeSync {
     /// Sets the messenger for the layer. Messenger is used to notify the application when the layer thinks it should
     /// be updated on the screen.
     fn set_messenger(&mut self, messenger: Box<dyn Messenger>);
+    /// Statistics of the requests made by the tile source of the layer. Layers that do not load data from a
+    /// tile source return `None`.
+    fn tile_source_stats(&self) -> Option<TileSourceStats> {
+        None
+    }
+    /// Returns `true` if all the data needed to render the layer with the given `view` has been loaded (or failed to
+    /// load), so that rendering the layer now gives the final image. Layers that do not load data asynchronously are
+    /// always ready.
+    fn is_ready(&self, _view: &MapView) -> bool {
+        true
+    }
+    /// Bounding rectangle of the area the layer draws at, in the given CRS. Returns `None` if the extent is unknown,
+    /// unlimited or cannot be calculated in the CRS.
+    ///
+    /// The map does not render and prepare the layers, which extent does not intersect the visible area (extended
+    /// by a margin of a few hundred pixels for the symbols drawn beyond the geometries, e.g. point icons and labels),
+    /// so a layer must not report an extent smaller than the area it draws at. The extents of all layers are
+    /// aggregated by [`Map::layers_extent`](crate::Map::layers_extent), e.g. to fit the view to the data of the map.
+    fn extent(&self, _crs: &Crs) -> Option<Rect> {
+        None
+    }
+    /// Drops the data the layer has prepared for the GPU (packed bundles and textures), so that it is prepared again
+    /// at the next render.
+    ///
+    /// Packed data of one graphics device cannot be drawn with another one, so this method must be called when the
+    /// map is moved to a renderer with a different device (see
+    /// [`Map::clear_render_cache`](crate::Map::clear_render_cache)). The default implementation does nothing, which
+    /// is correct for layers that pack their bundles every frame.
+    fn clear_render_cache(&self) {}
     /// A map stores layers as trait objects. This method can be used to convert the trait object into the concrete type.
     fn as_any(&self) -> &dyn Any;
     /// A map stores layers as trait objects. This method can be used to convert the trait object into the concrete type.
@@ -54,6 +108,22 @@ impl<T: Layer + 'static> Layer for Arc<RwLock<T>> {
         self.write().set_messenger(messenger)
     }
 
+    fn tile_source_stats(&self) -> Option<TileSourceStats> {
+        self.read().tile_source_stats()
+    }
+
+    fn is_ready(&self, view: &MapView) -> bool {
+        self.read().is_ready(view)
+    }
+
+    fn extent(&self, crs: &Crs) -> Option<Rect> {
+        self.read().extent(crs)
+    }
+
+    fn clear_render_cache(&self) {
+        self.read().clear_render_cache()
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -63,6 +133,27 @@ impl<T: Layer + 'static> Layer for Arc<RwLock<T>> {
     }
 }
 
+/// Margin in pixels added around the visible area of the map when checking if a layer draws anything in it.
+const CULLING_MARGIN_PX: f64 = 256.0;
+
+/// Returns false if the [extent](Layer::extent) of the layer does not intersect the visible area of the `view`, so
+/// the layer does not need to be rendered or prepared.
+pub(crate) fn is_in_view(
+    layer: &dyn Layer,
+    view: &MapView,
+    transform: Option<&LayerTransform>,
+) -> bool {
+    if transform.is_some_and(|transform| !transform.is_identity()) {
+        return true;
+    }
+
+    let (Some(extent), Some(bbox)) = (layer.extent(view.crs()), view.get_bbox()) else {
+        return true;
+    };
+
+    extent.intersects(bbox.shrink(-CULLING_MARGIN_PX * view.resolution()))
+}
+
 /// Used for doc-tests
 #[cfg(feature = "_tests")]
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
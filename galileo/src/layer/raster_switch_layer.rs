@@ -0,0 +1,281 @@
+//! [`RasterSwitchLayer`] draws one of two raster tile sources depending on the zoom level of the map.
+
+use std::any::Any;
+use std::sync::Arc;
+
+use maybe_sync::{MaybeSend, MaybeSync};
+use parking_lot::Mutex;
+use web_time::{Duration, Instant};
+
+use crate::decoded_image::DecodedImage;
+use crate::layer::data_provider::{DataProvider, TileSourceStats};
+use crate::layer::{Layer, RasterTileLayer};
+use crate::messenger::Messenger;
+use crate::render::Canvas;
+use crate::tile_scheme::TileIndex;
+use crate::view::MapView;
+
+/// Which of the sources [`RasterSwitchLayer`] draws.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SwitchSource {
+    /// Source drawn when the map is zoomed out.
+    Low,
+    /// Source drawn when the map is zoomed in.
+    High,
+}
+
+/// Layer that draws one of two raster tile layers depending on the resolution of the map view, e.g. a stylized
+/// basemap when the map is zoomed out and aerial imagery when it is zoomed in.
+///
+/// The `high` layer is drawn when the resolution of the view is less than the
+/// [switch resolution](RasterSwitchLayer::set_switch_resolution), and the `low` layer otherwise.
+///
+/// To make the switch seamless:
+/// * when the resolution of the view is close to the switch resolution (see
+///   [`RasterSwitchLayer::set_prefetch_factor`]), the tiles of the other layer are loaded in advance, so they are
+///   ready when the map is zoomed over the boundary;
+/// * after the switch, both layers are drawn for the [transition duration](RasterSwitchLayer::set_transition_duration),
+///   so the tiles of the new layer fade in over the tiles of the previous one.
+///
+/// ```no_run
+/// use galileo::layer::{Basemap, RasterSwitchLayer};
+/// use galileo::MapBuilder;
+///
+/// let basemap = MapBuilder::create_basemap_layer(&Basemap::OpenStreetMap);
+/// let imagery = MapBuilder::create_basemap_layer(&Basemap::EsriWorldImagery);
+///
+/// // Aerial imagery is drawn at zoom levels above 13
+/// let resolution = basemap.tile_schema().lod_resolution(13).expect("invalid tile schema");
+/// let layer = RasterSwitchLayer::new(basemap, imagery, resolution);
+/// ```
+pub struct RasterSwitchLayer<Low, High>
+where
+    Low: DataProvider<TileIndex, DecodedImage, ()> + MaybeSync + MaybeSend,
+    High: DataProvider<TileIndex, DecodedImage, ()> + MaybeSync + MaybeSend,
+{
+    low: RasterTileLayer<Low>,
+    high: RasterTileLayer<High>,
+    switch_resolution: f64,
+    prefetch_factor: f64,
+    transition_duration: Duration,
+    state: Mutex<SwitchState>,
+}
+
+struct SwitchState {
+    active: Option<SwitchSource>,
+    previous: Option<(SwitchSource, Instant)>,
+}
+
+impl<Low, High> RasterSwitchLayer<Low, High>
+where
+    Low: DataProvider<TileIndex, DecodedImage, ()> + MaybeSync + MaybeSend,
+    High: DataProvider<TileIndex, DecodedImage, ()> + MaybeSync + MaybeSend,
+{
+    /// Creates a new layer that draws the `high` layer at the resolutions less than `switch_resolution`, and the
+    /// `low` layer otherwise.
+    pub fn new(
+        low: RasterTileLayer<Low>,
+        high: RasterTileLayer<High>,
+        switch_resolution: f64,
+    ) -> Self {
+        Self {
+            low,
+            high,
+            switch_resolution,
+            prefetch_factor: 2.0,
+            transition_duration: Duration::from_millis(500),
+            state: Mutex::new(SwitchState {
+                active: None,
+                previous: None,
+            }),
+        }
+    }
+
+    /// Layer drawn when the map is zoomed out.
+    pub fn low(&self) -> &RasterTileLayer<Low> {
+        &self.low
+    }
+
+    /// Mutable reference to the layer drawn when the map is zoomed out.
+    pub fn low_mut(&mut self) -> &mut RasterTileLayer<Low> {
+        &mut self.low
+    }
+
+    /// Layer drawn when the map is zoomed in.
+    pub fn high(&self) -> &RasterTileLayer<High> {
+        &self.high
+    }
+
+    /// Mutable reference to the layer drawn when the map is zoomed in.
+    pub fn high_mut(&mut self) -> &mut RasterTileLayer<High> {
+        &mut self.high
+    }
+
+    /// Resolution, below which the `high` layer is drawn.
+    pub fn switch_resolution(&self) -> f64 {
+        self.switch_resolution
+    }
+
+    /// Sets the resolution, below which the `high` layer is drawn.
+    pub fn set_switch_resolution(&mut self, resolution: f64) {
+        self.switch_resolution = resolution;
+    }
+
+    /// Sets how close to the switch resolution the tiles of the other layer are loaded in advance. The tiles are
+    /// loaded when the resolution of the view differs from the switch resolution less than `factor` times.
+    ///
+    /// Default value is 2, i.e. the tiles of the other layer are loaded one zoom level before the switch. Values not
+    /// greater than 1 disable loading in advance.
+    pub fn set_prefetch_factor(&mut self, factor: f64) {
+        self.prefetch_factor = factor;
+    }
+
+    /// Sets how close to the switch resolution the tiles of the other layer are loaded in advance. See
+    /// [`RasterSwitchLayer::set_prefetch_factor`].
+    pub fn with_prefetch_factor(mut self, factor: f64) -> Self {
+        self.set_prefetch_factor(factor);
+        self
+    }
+
+    /// Sets the time during which both layers are drawn after switching.
+    ///
+    /// Default value is 500 ms.
+    pub fn set_transition_duration(&mut self, duration: Duration) {
+        self.transition_duration = duration;
+    }
+
+    /// Sets the time during which both layers are drawn after switching.
+    pub fn with_transition_duration(mut self, duration: Duration) -> Self {
+        self.set_transition_duration(duration);
+        self
+    }
+
+    /// Source drawn by the layer during the last render.
+    pub fn active_source(&self) -> Option<SwitchSource> {
+        self.state.lock().active
+    }
+
+    fn layer(&self, source: SwitchSource) -> &dyn Layer
+    where
+        Low: 'static,
+        High: 'static,
+    {
+        match source {
+            SwitchSource::Low => &self.low,
+            SwitchSource::High => &self.high,
+        }
+    }
+}
+
+impl<Low, High> Layer for RasterSwitchLayer<Low, High>
+where
+    Low: DataProvider<TileIndex, DecodedImage, ()> + MaybeSync + MaybeSend + 'static,
+    High: DataProvider<TileIndex, DecodedImage, ()> + MaybeSync + MaybeSend + 'static,
+{
+    fn render(&self, view: &MapView, canvas: &mut dyn Canvas) {
+        let now = Instant::now();
+        let (active, previous) = {
+            let mut state = self.state.lock();
+            let target = select_source(view.resolution(), self.switch_resolution);
+            if let Some(active) = state.active.filter(|active| *active != target) {
+                state.previous = Some((active, now));
+            }
+            state.active = Some(target);
+
+            if let Some((_, switched_at)) = state.previous {
+                if now.duration_since(switched_at) > self.transition_duration {
+                    state.previous = None;
+                }
+            }
+
+            (target, state.previous.map(|(source, _)| source))
+        };
+
+        // Tiles of the new layer fade in over the tiles of the previous one
+        if let Some(previous) = previous.filter(|previous| *previous != active) {
+            self.layer(previous).render(view, canvas);
+        }
+        self.layer(active).render(view, canvas);
+    }
+
+    fn prepare(&self, view: &MapView) {
+        let resolution = view.resolution();
+        let active = select_source(resolution, self.switch_resolution);
+        self.layer(active).prepare(view);
+
+        if is_near_switch(resolution, self.switch_resolution, self.prefetch_factor) {
+            // Tiles of the other layer are loaded for the view right beyond the switch resolution
+            let (other, other_resolution) = match active {
+                SwitchSource::Low => (SwitchSource::High, self.switch_resolution * 0.99),
+                SwitchSource::High => (SwitchSource::Low, self.switch_resolution),
+            };
+            self.layer(other)
+                .prepare(&view.with_resolution(other_resolution));
+        }
+    }
+
+    fn set_messenger(&mut self, messenger: Box<dyn Messenger>) {
+        let messenger: Arc<dyn Messenger> = Arc::from(messenger);
+        self.low.set_messenger(Box::new(messenger.clone()));
+        self.high.set_messenger(Box::new(messenger));
+    }
+
+    fn clear_render_cache(&self) {
+        self.low.clear_render_cache();
+        self.high.clear_render_cache();
+    }
+
+    fn tile_source_stats(&self) -> Option<TileSourceStats> {
+        match (self.low.source_stats(), self.high.source_stats()) {
+            (Some(low), Some(high)) => Some(low + high),
+            (low, high) => low.or(high),
+        }
+    }
+
+    fn is_ready(&self, view: &MapView) -> bool {
+        self.layer(select_source(view.resolution(), self.switch_resolution))
+            .is_ready(view)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+fn select_source(resolution: f64, switch_resolution: f64) -> SwitchSource {
+    if resolution < switch_resolution {
+        SwitchSource::High
+    } else {
+        SwitchSource::Low
+    }
+}
+
+fn is_near_switch(resolution: f64, switch_resolution: f64, prefetch_factor: f64) -> bool {
+    resolution < switch_resolution * prefetch_factor
+        && resolution * prefetch_factor > switch_resolution
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn source_selection() {
+        assert_eq!(select_source(5.0, 10.0), SwitchSource::High);
+        assert_eq!(select_source(10.0, 10.0), SwitchSource::Low);
+        assert_eq!(select_source(20.0, 10.0), SwitchSource::Low);
+    }
+
+    #[test]
+    fn prefetch_near_switch() {
+        assert!(is_near_switch(15.0, 10.0, 2.0));
+        assert!(is_near_switch(6.0, 10.0, 2.0));
+        assert!(!is_near_switch(25.0, 10.0, 2.0));
+        assert!(!is_near_switch(4.0, 10.0, 2.0));
+        assert!(!is_near_switch(10.5, 10.0, 1.0));
+    }
+}
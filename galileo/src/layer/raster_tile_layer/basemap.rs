@@ -0,0 +1,180 @@
+use crate::layer::data_provider::UrlSource;
+use crate::tile_scheme::{TileIndex, TileSchema};
+
+/// Well-known raster tile basemaps.
+///
+/// A preset knows the URL template of the tile service, the tile schema and the attribution that must be displayed
+/// on the map when the tiles are used. A layer with the preset can be created with
+/// [`MapBuilder::create_basemap_layer`](crate::MapBuilder::create_basemap_layer).
+///
+/// Note that every service has its own terms of use. Most of them do not allow heavy usage without a commercial
+/// agreement (e.g. the [OSM tile usage policy](https://operations.osmfoundation.org/policies/tiles/)), so check the
+/// terms before using a preset in production.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Basemap {
+    /// Standard OpenStreetMap style.
+    OpenStreetMap,
+    /// OpenTopoMap topographic map based on OpenStreetMap and SRTM data.
+    OpenTopoMap,
+    /// CARTO Positron light basemap.
+    CartoLight,
+    /// CARTO Dark Matter basemap.
+    CartoDark,
+    /// CARTO Voyager basemap.
+    CartoVoyager,
+    /// Esri World Imagery satellite and aerial imagery.
+    EsriWorldImagery,
+    /// Esri World Topographic map.
+    EsriWorldTopo,
+    /// Stamen Toner high contrast map hosted by Stadia Maps. Requires a Stadia Maps API key.
+    StamenToner {
+        /// Stadia Maps API key.
+        api_key: String,
+    },
+    /// Stamen Terrain map hosted by Stadia Maps. Requires a Stadia Maps API key.
+    StamenTerrain {
+        /// Stadia Maps API key.
+        api_key: String,
+    },
+    /// Stamen Watercolor map hosted by Stadia Maps. Requires a Stadia Maps API key.
+    StamenWatercolor {
+        /// Stadia Maps API key.
+        api_key: String,
+    },
+}
+
+/// Subdomains used by the services that distribute requests between several hosts.
+const SUBDOMAINS: [&str; 4] = ["a", "b", "c", "d"];
+
+impl Basemap {
+    /// Returns the URL of the tile with the given index.
+    pub fn tile_url(&self, index: TileIndex) -> String {
+        let TileIndex { x, y, z, .. } = index;
+        let subdomain = SUBDOMAINS[(x + y).rem_euclid(SUBDOMAINS.len() as i32) as usize];
+
+        match self {
+            Self::OpenStreetMap => format!("https://tile.openstreetmap.org/{z}/{x}/{y}.png"),
+            Self::OpenTopoMap => {
+                // OpenTopoMap has only 3 subdomains
+                let subdomain = SUBDOMAINS[(x + y).rem_euclid(3) as usize];
+                format!("https://{subdomain}.tile.opentopomap.org/{z}/{x}/{y}.png")
+            }
+            Self::CartoLight => {
+                format!("https://{subdomain}.basemaps.cartocdn.com/light_all/{z}/{x}/{y}.png")
+            }
+            Self::CartoDark => {
+                format!("https://{subdomain}.basemaps.cartocdn.com/dark_all/{z}/{x}/{y}.png")
+            }
+            Self::CartoVoyager => format!(
+                "https://{subdomain}.basemaps.cartocdn.com/rastertiles/voyager/{z}/{x}/{y}.png"
+            ),
+            Self::EsriWorldImagery => format!(
+                "https://server.arcgisonline.com/ArcGIS/rest/services/World_Imagery/MapServer/tile/{z}/{y}/{x}"
+            ),
+            Self::EsriWorldTopo => format!(
+                "https://server.arcgisonline.com/ArcGIS/rest/services/World_Topo_Map/MapServer/tile/{z}/{y}/{x}"
+            ),
+            Self::StamenToner { api_key } => format!(
+                "https://tiles.stadiamaps.com/tiles/stamen_toner/{z}/{x}/{y}.png?api_key={api_key}"
+            ),
+            Self::StamenTerrain { api_key } => format!(
+                "https://tiles.stadiamaps.com/tiles/stamen_terrain/{z}/{x}/{y}.png?api_key={api_key}"
+            ),
+            Self::StamenWatercolor { api_key } => format!(
+                "https://tiles.stadiamaps.com/tiles/stamen_watercolor/{z}/{x}/{y}.jpg?api_key={api_key}"
+            ),
+        }
+    }
+
+    /// Returns the URL source that can be used to create a tile provider for the basemap.
+    pub fn url_source(&self) -> impl UrlSource<TileIndex> + 'static {
+        let basemap = self.clone();
+        move |index: &TileIndex| basemap.tile_url(*index)
+    }
+
+    /// Attribution text that must be displayed on the map when the basemap is used.
+    pub fn attribution(&self) -> &'static str {
+        match self {
+            Self::OpenStreetMap => "© OpenStreetMap contributors",
+            Self::OpenTopoMap => {
+                "© OpenStreetMap contributors, SRTM | Map style: © OpenTopoMap (CC-BY-SA)"
+            }
+            Self::CartoLight | Self::CartoDark | Self::CartoVoyager => {
+                "© OpenStreetMap contributors © CARTO"
+            }
+            Self::EsriWorldImagery => {
+                "Tiles © Esri — Source: Esri, Maxar, Earthstar Geographics, and the GIS User Community"
+            }
+            Self::EsriWorldTopo => {
+                "Tiles © Esri — Esri, HERE, Garmin, FAO, NOAA, USGS, © OpenStreetMap contributors, and the GIS User \
+                 Community"
+            }
+            Self::StamenToner { .. }
+            | Self::StamenTerrain { .. }
+            | Self::StamenWatercolor { .. } => {
+                "© Stadia Maps © Stamen Design © OpenMapTiles © OpenStreetMap contributors"
+            }
+        }
+    }
+
+    /// Maximum zoom level provided by the service.
+    pub fn max_zoom(&self) -> u32 {
+        match self {
+            Self::OpenStreetMap => 19,
+            Self::OpenTopoMap => 17,
+            Self::CartoLight | Self::CartoDark | Self::CartoVoyager => 20,
+            Self::EsriWorldImagery | Self::EsriWorldTopo => 19,
+            Self::StamenToner { .. } | Self::StamenTerrain { .. } => 20,
+            Self::StamenWatercolor { .. } => 16,
+        }
+    }
+
+    /// Returns true if the service requires an API key to access the tiles.
+    pub fn requires_api_key(&self) -> bool {
+        matches!(
+            self,
+            Self::StamenToner { .. } | Self::StamenTerrain { .. } | Self::StamenWatercolor { .. }
+        )
+    }
+
+    /// Tile schema of the basemap.
+    pub fn tile_schema(&self) -> TileSchema {
+        TileSchema::web(self.max_zoom() + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tile_urls() {
+        let index = TileIndex::new(3, 5, 4);
+        assert_eq!(
+            Basemap::OpenStreetMap.tile_url(index),
+            "https://tile.openstreetmap.org/4/3/5.png"
+        );
+        assert_eq!(
+            Basemap::EsriWorldImagery.tile_url(index),
+            "https://server.arcgisonline.com/ArcGIS/rest/services/World_Imagery/MapServer/tile/4/5/3"
+        );
+        assert_eq!(
+            Basemap::CartoDark.tile_url(index),
+            "https://a.basemaps.cartocdn.com/dark_all/4/3/5.png"
+        );
+
+        let toner = Basemap::StamenToner {
+            api_key: "key".into(),
+        };
+        assert!(toner.requires_api_key());
+        assert!(toner.tile_url(index).ends_with("/4/3/5.png?api_key=key"));
+
+        let source = Basemap::OpenTopoMap.url_source();
+        assert_eq!(source(&index), "https://c.tile.opentopomap.org/4/3/5.png");
+        assert_eq!(
+            Basemap::OpenTopoMap.tile_schema().lod_resolution(17),
+            TileSchema::web(18).lod_resolution(17)
+        );
+    }
+}
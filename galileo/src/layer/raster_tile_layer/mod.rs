@@ -2,44 +2,83 @@ use std::any::Any;
 use std::collections::HashSet;
 use std::sync::Arc;
 
-use galileo_types::cartesian::Size;
+use galileo_types::cartesian::{Rect, Size};
 use maybe_sync::{MaybeSend, MaybeSync};
 use parking_lot::Mutex;
 use quick_cache::sync::Cache;
-use web_time::{Duration, SystemTime};
+use web_time::{Duration, Instant, SystemTime};
 
 use super::Layer;
+use crate::async_runtime::CancellationToken;
 use crate::decoded_image::DecodedImage;
-use crate::layer::data_provider::DataProvider;
+use crate::layer::data_provider::{DataProvider, TileSourceStats};
 use crate::messenger::Messenger;
-use crate::render::{Canvas, ImagePaint, PackedBundle, RenderOptions};
+use crate::render::{BlendMode, Canvas, ImagePaint, PackedBundle, RenderOptions};
 use crate::tile_scheme::{TileIndex, TileSchema};
 use crate::view::MapView;
 
+mod basemap;
+mod reprojection;
+
+pub use basemap::Basemap;
+use reprojection::{TileKey, TileWarp};
+
 /// Raster tile layers load prerender tile sets using [`Provider`](DataProvider) and render them to the map.
+///
+/// If the CRS of the map view differs from the CRS of the tile schema (e.g. a Web Mercator basemap displayed in a
+/// national grid like EPSG:2056), the tiles are reprojected into the view CRS on the fly after they are loaded. The
+/// reprojected tiles are stored in the same cache as the other tiles, keyed by the tile index (including its level of
+/// detail) and the target CRS, so each tile is reprojected only once until it is evicted from the cache.
+///
+/// For frequently updated sources (e.g. weather radar or traffic) the layer can re-fetch visible tiles after a
+/// maximum age (see [`RasterTileLayer::set_tile_max_age`]), or on demand with [`RasterTileLayer::refresh_visible`].
+/// Expired tiles stay on the screen until their new versions are loaded.
 pub struct RasterTileLayer<Provider>
 where
     Provider: DataProvider<TileIndex, DecodedImage, ()> + MaybeSync + MaybeSend,
 {
     tile_provider: Arc<Provider>,
     tile_scheme: TileSchema,
+    tile_pixel_ratio: f64,
     fade_in_duration: Duration,
-    tiles: Arc<Cache<TileIndex, Arc<TileState>>>,
+    blend_mode: BlendMode,
+    tiles: Arc<Cache<TileKey, Arc<TileState>>>,
+    warps: Mutex<Vec<Arc<TileWarp>>>,
     prev_drawn_tiles: Mutex<Vec<TileIndex>>,
     messenger: Option<Arc<dyn Messenger>>,
+    cancellation: CancellationToken,
+    tile_max_age: Option<Duration>,
+    /// Tiles loaded before this moment are expired.
+    refresh_requested_at: Mutex<Option<Instant>>,
+    /// Expired tiles that are being loaded again.
+    refreshing: Arc<Mutex<HashSet<TileKey>>>,
+    /// Moment at which the layer is woken up to re-fetch the next expiring tile.
+    wakeup_at: Arc<Mutex<Option<Instant>>>,
 }
 
 enum TileState {
     Loading,
-    Loaded(Mutex<DecodedImage>),
+    Loaded(Mutex<DecodedImage>, TileLoad),
+    /// The image is reprojected into the view CRS. The rectangle is the bounding box of the image in the view CRS.
+    Warped(Mutex<DecodedImage>, Rect, TileLoad),
     Rendered(Box<Mutex<RenderedTile>>),
     Error,
 }
 
+/// Information about loading of a tile image.
+#[derive(Debug, Clone, Copy)]
+struct TileLoad {
+    loaded_at: Instant,
+    /// The image replaces an expired tile that is already drawn, so it is shown without fading in.
+    is_refresh: bool,
+}
+
 struct RenderedTile {
     packed_bundle: Box<dyn PackedBundle>,
     first_drawn: SystemTime,
     opacity: f32,
+    /// Moment the tile image was loaded, or the last failed attempt to refresh it.
+    loaded_at: Instant,
 }
 
 impl RenderedTile {
@@ -61,10 +100,18 @@ where
         Self {
             tile_provider: Arc::new(tile_provider),
             tile_scheme,
+            tile_pixel_ratio: 1.0,
             prev_drawn_tiles: Mutex::new(vec![]),
             fade_in_duration: Duration::from_millis(300),
+            blend_mode: BlendMode::Normal,
             tiles: Arc::new(Cache::new(5000)),
+            warps: Mutex::new(vec![]),
             messenger,
+            cancellation: CancellationToken::new(),
+            tile_max_age: None,
+            refresh_requested_at: Mutex::new(None),
+            refreshing: Arc::new(Mutex::new(HashSet::new())),
+            wakeup_at: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -73,17 +120,240 @@ where
         self.fade_in_duration = duration;
     }
 
-    fn get_tiles_to_draw(&self, view: &MapView) -> Vec<(TileIndex, Arc<TileState>)> {
+    /// Sets the way the tiles are combined with the layers below. E.g. a hillshade layer can be drawn over a light
+    /// basemap with [`BlendMode::Multiply`].
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) {
+        self.blend_mode = blend_mode;
+    }
+
+    /// Sets the way the tiles are combined with the layers below. See [`RasterTileLayer::set_blend_mode`].
+    pub fn with_blend_mode(mut self, blend_mode: BlendMode) -> Self {
+        self.set_blend_mode(blend_mode);
+        self
+    }
+
+    /// The way the tiles are combined with the layers below.
+    pub fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    /// Sets the pixel ratio of the tile images.
+    ///
+    /// Many tile sources provide HiDPI (`@2x`) tiles, which have twice as many pixels in each
+    /// dimension as the tile schema specifies (e.g. 512x512 images for a 256 pixel tile schema).
+    /// With the pixel ratio of `2.0` the layer selects tiles one level of detail lower than it
+    /// would for normal tiles, so the screen is covered by the same number of image pixels without
+    /// loading four times as many tiles.
+    ///
+    /// Default value is `1.0`.
+    pub fn set_tile_pixel_ratio(&mut self, pixel_ratio: f64) {
+        if pixel_ratio.is_finite() && pixel_ratio > 0.0 {
+            self.tile_pixel_ratio = pixel_ratio;
+        } else {
+            log::warn!("Invalid tile pixel ratio {pixel_ratio} is ignored");
+        }
+    }
+
+    /// Sets the pixel ratio of the tile images. See [`RasterTileLayer::set_tile_pixel_ratio`].
+    pub fn with_tile_pixel_ratio(mut self, pixel_ratio: f64) -> Self {
+        self.set_tile_pixel_ratio(pixel_ratio);
+        self
+    }
+
+    /// Pixel ratio of the tile images.
+    pub fn tile_pixel_ratio(&self) -> f64 {
+        self.tile_pixel_ratio
+    }
+
+    /// Sets the maximum age of the tiles. Visible tiles older than `max_age` are loaded again from the tile provider,
+    /// even if the map is not moved. The old tile is drawn until the new one is loaded, after which it is replaced
+    /// without fading in. `None` (default) keeps the tiles until they are evicted from the cache.
+    ///
+    /// Note that providers with a persistent cache (e.g. [`UrlImageProvider::new_cached`]) will return the cached
+    /// version of the tile, so live sources should use providers without persistent cache.
+    ///
+    /// [`UrlImageProvider::new_cached`]: crate::layer::data_provider::UrlImageProvider::new_cached
+    pub fn set_tile_max_age(&mut self, max_age: Option<Duration>) {
+        self.tile_max_age = max_age;
+    }
+
+    /// Sets the maximum age of the tiles. See [`RasterTileLayer::set_tile_max_age`].
+    pub fn with_tile_max_age(mut self, max_age: Duration) -> Self {
+        self.set_tile_max_age(Some(max_age));
+        self
+    }
+
+    /// Maximum age of the tiles.
+    pub fn tile_max_age(&self) -> Option<Duration> {
+        self.tile_max_age
+    }
+
+    /// Loads all the visible tiles again, e.g. when the application knows that the source data is updated. The tiles
+    /// are replaced on the screen as soon as their new versions are loaded. Tiles that are not visible now are loaded
+    /// again when they become visible.
+    pub fn refresh_visible(&self) {
+        *self.refresh_requested_at.lock() = Some(Instant::now());
+        if let Some(messenger) = &self.messenger {
+            messenger.request_redraw();
+        }
+    }
+
+    /// Returns true if the tile loaded at `loaded_at` must be loaded again.
+    fn is_expired(&self, loaded_at: Instant, now: Instant) -> bool {
+        self.tile_max_age
+            .is_some_and(|max_age| now.duration_since(loaded_at) >= max_age)
+            || self
+                .refresh_requested_at
+                .lock()
+                .is_some_and(|requested_at| loaded_at < requested_at)
+    }
+
+    /// Starts loading of the new version of the tile, if it is expired. Returns the moment the tile expires otherwise.
+    fn check_expiry(
+        &self,
+        index: TileIndex,
+        key: TileKey,
+        warp: Option<(Arc<TileWarp>, Rect)>,
+        now: Instant,
+    ) -> Option<Instant>
+    where
+        Provider: 'static,
+    {
+        let tile = self.tiles.get(&key)?;
+        let TileState::Rendered(rendered) = &*tile else {
+            return None;
+        };
+
+        let loaded_at = rendered.lock().loaded_at;
+        if self.is_expired(loaded_at, now) {
+            self.refresh_tile(index, key, warp);
+            None
+        } else {
+            self.tile_max_age.map(|max_age| loaded_at + max_age)
+        }
+    }
+
+    /// Loads the tile again, keeping the current version in the cache until the new one is loaded.
+    fn refresh_tile(&self, index: TileIndex, key: TileKey, warp: Option<(Arc<TileWarp>, Rect)>)
+    where
+        Provider: 'static,
+    {
+        if !self.refreshing.lock().insert(key) {
+            return;
+        }
+
+        let tile_provider = self.tile_provider.clone();
+        let tiles = self.tiles.clone();
+        let messenger = self.messenger.clone();
+        let refreshing = self.refreshing.clone();
+        crate::async_runtime::spawn_cancellable(&self.cancellation, async move {
+            match Self::fetch_tile(index, &tile_provider, warp, true).await {
+                Some(state) => {
+                    tiles.insert(key, Arc::new(state));
+                    if let Some(messenger) = messenger {
+                        messenger.request_redraw();
+                    }
+                }
+                None => {
+                    // Keep the old tile, and try again after the max age
+                    if let Some(tile) = tiles.get(&key) {
+                        if let TileState::Rendered(rendered) = &*tile {
+                            rendered.lock().loaded_at = Instant::now();
+                        }
+                    }
+                }
+            }
+
+            refreshing.lock().remove(&key);
+        });
+    }
+
+    /// Requests redraw of the map when the next tile expires, so that it is loaded again even if the map is idle.
+    fn schedule_wakeup(&self, at: Instant) {
+        {
+            let mut wakeup_at = self.wakeup_at.lock();
+            if wakeup_at.is_some_and(|scheduled| scheduled <= at) {
+                return;
+            }
+            *wakeup_at = Some(at);
+        }
+
+        let wakeup_at = self.wakeup_at.clone();
+        let messenger = self.messenger.clone();
+        crate::async_runtime::spawn_cancellable(&self.cancellation, async move {
+            crate::async_runtime::sleep(at.saturating_duration_since(Instant::now())).await;
+
+            let mut scheduled = wakeup_at.lock();
+            if *scheduled == Some(at) {
+                *scheduled = None;
+                drop(scheduled);
+
+                if let Some(messenger) = messenger {
+                    messenger.request_redraw();
+                }
+            }
+        });
+    }
+
+    /// Returns the reprojection from the tile schema CRS into the CRS of the view, or `None` if the CRSs are the
+    /// same.
+    fn get_warp(&self, view: &MapView) -> Option<Arc<TileWarp>> {
+        if *view.crs() == self.tile_scheme.crs {
+            return None;
+        }
+
+        let mut warps = self.warps.lock();
+        if let Some(warp) = warps.iter().find(|warp| warp.target_crs() == view.crs()) {
+            return Some(warp.clone());
+        }
+
+        let warp = Arc::new(TileWarp::new(
+            self.tile_scheme.crs.clone(),
+            view.crs().clone(),
+            warps.len() as u32,
+        ));
+        warps.push(warp.clone());
+        Some(warp)
+    }
+
+    fn tile_key(index: TileIndex, warp: Option<&TileWarp>) -> TileKey {
+        match warp {
+            Some(warp) => warp.key(index),
+            None => TileKey {
+                index,
+                target_crs: None,
+            },
+        }
+    }
+
+    fn iter_tiles(
+        &self,
+        view: &MapView,
+        warp: Option<&TileWarp>,
+    ) -> Option<impl Iterator<Item = TileIndex>> {
+        let (bbox, resolution) = match warp {
+            Some(warp) => warp.source_view(view)?,
+            None => (view.get_bbox()?, view.resolution()),
+        };
+
+        self.tile_scheme
+            .iter_tiles_over_bbox(resolution * self.tile_pixel_ratio, bbox)
+    }
+
+    fn get_tiles_to_draw(
+        &self,
+        view: &MapView,
+        warp: Option<&TileWarp>,
+    ) -> Vec<(TileIndex, Arc<TileState>)> {
         let mut tiles = vec![];
-        let Some(tile_iter) = self.tile_scheme.iter_tiles(view) else {
+        let Some(tile_iter) = self.iter_tiles(view, warp) else {
             return vec![];
         };
+        let get_tile = |index: &TileIndex| self.tiles.get(&Self::tile_key(*index, warp));
 
         let mut to_substitute = vec![];
         for index in tile_iter {
-            self.tiles.get(&index);
-
-            match self.tiles.get(&index) {
+            match get_tile(&index) {
                 None => to_substitute.push(index),
                 Some(tile_state) => match &*tile_state.clone() {
                     TileState::Rendered(tile) => {
@@ -93,7 +363,7 @@ where
 
                         tiles.push((index, tile_state));
                     }
-                    TileState::Loaded(_) => {
+                    TileState::Loaded(..) | TileState::Warped(..) => {
                         to_substitute.push(index);
                         tiles.push((index, tile_state));
                     }
@@ -115,7 +385,7 @@ where
                     // todo: this will not work correctly if a tile is substituted by more then 1 tile
                     next_level = substitute_index;
 
-                    if let Some(tile) = self.tiles.get(&substitute_index) {
+                    if let Some(tile) = get_tile(&substitute_index) {
                         if matches!(*tile, TileState::Rendered(_))
                             && !substitute_indices.contains(&substitute_index)
                         {
@@ -123,11 +393,8 @@ where
                             substitute_indices.insert(substitute_index);
                         }
 
-                        if let Some(TileState::Rendered(rendered)) = self
-                            .tiles
-                            .get(&substitute_index)
-                            .as_ref()
-                            .map(|v| v.as_ref())
+                        if let Some(TileState::Rendered(rendered)) =
+                            get_tile(&substitute_index).as_ref().map(|v| v.as_ref())
                         {
                             if !rendered.lock().is_opaque() {
                                 need_more = true;
@@ -154,7 +421,7 @@ where
                     };
                     if !substitute_indices.contains(prev) && prev_bbox.intersects(required_bbox) {
                         substitute_indices.insert(*prev);
-                        let Some(tile) = self.tiles.get(prev) else {
+                        let Some(tile) = get_tile(prev) else {
                             continue;
                         };
                         substitute_tiles.push((*prev, tile));
@@ -169,7 +436,12 @@ where
         substitute_tiles
     }
 
-    fn prepare_tile_renders(&self, tiles: &[(TileIndex, Arc<TileState>)], canvas: &mut dyn Canvas) {
+    fn prepare_tile_renders(
+        &self,
+        tiles: &[(TileIndex, Arc<TileState>)],
+        warp: Option<&TileWarp>,
+        canvas: &mut dyn Canvas,
+    ) {
         let mut requires_redraw = false;
 
         let now = SystemTime::now();
@@ -198,7 +470,8 @@ where
                         requires_redraw = true;
                     }
                 }
-                TileState::Loaded(decoded_image) => {
+                TileState::Loaded(decoded_image, load)
+                | TileState::Warped(decoded_image, _, load) => {
                     let mut bundle = canvas.create_bundle();
                     let mut decoded_image = decoded_image.lock();
 
@@ -208,15 +481,21 @@ where
                             .expect("empty image is always ok"),
                     );
 
-                    let opacity = if self.fade_in_duration.is_zero() {
+                    let opacity = if self.fade_in_duration.is_zero() || load.is_refresh {
                         1.0
                     } else {
                         0.0
                     };
 
-                    let Some(tile_bbox) = self.tile_scheme.tile_bbox(*index) else {
-                        log::warn!("Failed to get bbox for tile {index:?}");
-                        continue;
+                    let tile_bbox = match &**tile {
+                        TileState::Warped(_, bbox, _) => *bbox,
+                        _ => {
+                            let Some(tile_bbox) = self.tile_scheme.tile_bbox(*index) else {
+                                log::warn!("Failed to get bbox for tile {index:?}");
+                                continue;
+                            };
+                            tile_bbox
+                        }
                     };
 
                     bundle.add_image(
@@ -226,11 +505,12 @@ where
                     );
                     let packed = canvas.pack_bundle(&bundle);
                     self.tiles.insert(
-                        *index,
+                        Self::tile_key(*index, warp),
                         Arc::new(TileState::Rendered(Box::new(Mutex::new(RenderedTile {
                             packed_bundle: packed,
                             first_drawn: now,
                             opacity,
+                            loaded_at: load.loaded_at,
                         })))),
                     );
 
@@ -250,46 +530,85 @@ where
     async fn load_tile(
         index: TileIndex,
         tile_provider: Arc<Provider>,
-        tiles: &Cache<TileIndex, Arc<TileState>>,
+        tiles: &Cache<TileKey, Arc<TileState>>,
+        warp: Option<(Arc<TileWarp>, Rect)>,
         messenger: Option<Arc<dyn Messenger>>,
     ) {
-        match tiles.get_value_or_guard_async(&index).await {
+        let key = Self::tile_key(index, warp.as_ref().map(|(warp, _)| &**warp));
+        match tiles.get_value_or_guard_async(&key).await {
             Ok(_) => {}
             Err(guard) => {
                 let _ = guard.insert(Arc::new(TileState::Loading));
-                let load_result = tile_provider.load(&index, ()).await;
 
-                match load_result {
-                    Ok(decoded_image) => {
-                        if let Some(v) = tiles.get(&index) {
+                match Self::fetch_tile(index, &tile_provider, warp, false).await {
+                    Some(state) => {
+                        if let Some(v) = tiles.get(&key) {
                             if matches!(*v, TileState::Rendered(_)) {
                                 log::error!("This should not happen to {index:?}");
                             }
                         }
 
-                        tiles.insert(
-                            index,
-                            Arc::new(TileState::Loaded(Mutex::new(decoded_image))),
-                        );
+                        tiles.insert(key, Arc::new(state));
 
                         if let Some(messenger) = messenger {
                             messenger.request_redraw();
                         }
                     }
-                    Err(_) => tiles.insert(index, Arc::new(TileState::Error)),
+                    None => tiles.insert(key, Arc::new(TileState::Error)),
                 }
             }
         }
     }
 
+    /// Loads the tile image and reprojects it if needed. Returns `None` if the tile cannot be loaded.
+    async fn fetch_tile(
+        index: TileIndex,
+        tile_provider: &Provider,
+        warp: Option<(Arc<TileWarp>, Rect)>,
+        is_refresh: bool,
+    ) -> Option<TileState> {
+        let decoded_image = tile_provider.load(&index, ()).await.ok()?;
+        let load = TileLoad {
+            loaded_at: Instant::now(),
+            is_refresh,
+        };
+
+        match warp {
+            Some((warp, source_bbox)) => {
+                let (warped, bbox) = warp.warp(&decoded_image, source_bbox)?;
+                Some(TileState::Warped(Mutex::new(warped), bbox, load))
+            }
+            None => Some(TileState::Loaded(Mutex::new(decoded_image), load)),
+        }
+    }
+
+    /// Returns the reprojection parameters for loading of the tile, or `Err` if the tile cannot be reprojected.
+    fn tile_warp(
+        &self,
+        index: TileIndex,
+        warp: Option<&Arc<TileWarp>>,
+    ) -> Result<Option<(Arc<TileWarp>, Rect)>, ()> {
+        match warp {
+            Some(warp) => {
+                let source_bbox = self.tile_scheme.tile_bbox(index).ok_or(())?;
+                Ok(Some((warp.clone(), source_bbox)))
+            }
+            None => Ok(None),
+        }
+    }
+
     /// Preload tiles for the given `view`.
     pub async fn load_tiles(&self, view: &MapView) {
-        if let Some(iter) = self.tile_scheme.iter_tiles(view) {
+        let warp = self.get_warp(view);
+        if let Some(iter) = self.iter_tiles(view, warp.as_deref()) {
             for index in iter {
+                let Ok(tile_warp) = self.tile_warp(index, warp.as_ref()) else {
+                    continue;
+                };
                 let tile_provider = self.tile_provider.clone();
                 let tiles = self.tiles.clone();
                 let messenger = self.messenger.clone();
-                Self::load_tile(index, tile_provider, &tiles, messenger).await;
+                Self::load_tile(index, tile_provider, &tiles, tile_warp, messenger).await;
             }
         }
     }
@@ -298,6 +617,21 @@ where
     pub fn tile_schema(&self) -> &TileSchema {
         &self.tile_scheme
     }
+
+    /// Statistics of the requests made by the tile provider, if the provider collects them.
+    pub fn source_stats(&self) -> Option<TileSourceStats> {
+        self.tile_provider.stats()
+    }
+}
+
+impl<Provider> Drop for RasterTileLayer<Provider>
+where
+    Provider: DataProvider<TileIndex, DecodedImage, ()> + MaybeSync + MaybeSend,
+{
+    fn drop(&mut self) {
+        // Tiles that are still loading are not needed anymore
+        self.cancellation.cancel();
+    }
 }
 
 impl<Provider> Layer for RasterTileLayer<Provider>
@@ -305,12 +639,14 @@ where
     Provider: DataProvider<TileIndex, DecodedImage, ()> + MaybeSync + MaybeSend + 'static,
 {
     fn render(&self, view: &MapView, canvas: &mut dyn Canvas) {
-        let tiles = self.get_tiles_to_draw(view);
-        self.prepare_tile_renders(&tiles, canvas);
+        let warp = self.get_warp(view);
+        let warp = warp.as_deref();
+        let tiles = self.get_tiles_to_draw(view, warp);
+        self.prepare_tile_renders(&tiles, warp, canvas);
 
         let updated_tiles: Vec<_> = tiles
             .iter()
-            .filter_map(|(index, _)| self.tiles.get(index))
+            .filter_map(|(index, _)| self.tiles.get(&Self::tile_key(*index, warp)))
             .collect();
         let mut to_draw = Vec::new();
         for tile in &updated_tiles {
@@ -324,28 +660,69 @@ where
                 .iter()
                 .map(|guard| (&*guard.packed_bundle, guard.opacity))
                 .collect::<Vec<_>>(),
-            RenderOptions::default(),
+            RenderOptions {
+                blend_mode: self.blend_mode,
+                ..Default::default()
+            },
         );
         *self.prev_drawn_tiles.lock() = tiles.iter().map(|(index, _)| *index).collect();
     }
 
     fn prepare(&self, view: &MapView) {
-        if let Some(iter) = self.tile_scheme.iter_tiles(view) {
+        let warp = self.get_warp(view);
+        let now = Instant::now();
+        let mut next_expiry: Option<Instant> = None;
+        if let Some(iter) = self.iter_tiles(view, warp.as_deref()) {
             for index in iter {
+                let Ok(tile_warp) = self.tile_warp(index, warp.as_ref()) else {
+                    continue;
+                };
+
+                let key = Self::tile_key(index, warp.as_deref());
+                if let Some(expiry) = self.check_expiry(index, key, tile_warp.clone(), now) {
+                    next_expiry = Some(next_expiry.map_or(expiry, |next| next.min(expiry)));
+                }
+
                 let tile_provider = self.tile_provider.clone();
                 let tiles = self.tiles.clone();
                 let messenger = self.messenger.clone();
-                crate::async_runtime::spawn(async move {
-                    Self::load_tile(index, tile_provider, &tiles, messenger).await;
+                crate::async_runtime::spawn_cancellable(&self.cancellation, async move {
+                    Self::load_tile(index, tile_provider, &tiles, tile_warp, messenger).await;
                 });
             }
         }
+
+        if let Some(expiry) = next_expiry {
+            self.schedule_wakeup(expiry);
+        }
     }
 
     fn set_messenger(&mut self, messenger: Box<dyn Messenger>) {
         self.messenger = Some(Arc::from(messenger));
     }
 
+    fn clear_render_cache(&self) {
+        // Decoded images are consumed by packing, so the tiles are loaded again
+        self.tiles.clear();
+    }
+
+    fn tile_source_stats(&self) -> Option<TileSourceStats> {
+        self.source_stats()
+    }
+
+    fn is_ready(&self, view: &MapView) -> bool {
+        let warp = self.get_warp(view);
+        let Some(mut iter) = self.iter_tiles(view, warp.as_deref()) else {
+            return true;
+        };
+
+        iter.all(|index| {
+            self.tiles
+                .get(&Self::tile_key(index, warp.as_deref()))
+                .is_some_and(|tile| !matches!(*tile, TileState::Loading))
+        })
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -354,3 +731,24 @@ where
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::data_provider::UrlImageProvider;
+
+    #[test]
+    fn tiles_expire_by_age_and_refresh() {
+        let provider = UrlImageProvider::new(|_: &TileIndex| String::new());
+        let layer = RasterTileLayer::new(TileSchema::web(18), provider, None)
+            .with_tile_max_age(Duration::from_secs(60));
+
+        let loaded_at = Instant::now();
+        assert!(!layer.is_expired(loaded_at, loaded_at + Duration::from_secs(59)));
+        assert!(layer.is_expired(loaded_at, loaded_at + Duration::from_secs(60)));
+
+        layer.refresh_visible();
+        assert!(layer.is_expired(loaded_at, loaded_at));
+        assert!(!layer.is_expired(Instant::now(), Instant::now()));
+    }
+}
@@ -0,0 +1,263 @@
+use galileo_types::cartesian::{CartesianPoint2d, Point2d, Rect, Size};
+use galileo_types::geo::impls::GeoPoint2d;
+use galileo_types::geo::{Crs, Projection};
+
+use crate::decoded_image::{DecodedImage, DecodedImageType};
+use crate::tile_scheme::TileIndex;
+use crate::view::MapView;
+
+/// Number of cells of the grid in each dimension, at the nodes of which coordinates are transformed exactly. Pixels
+/// inside the cells are transformed by bilinear interpolation of the node coordinates.
+const GRID_CELLS: usize = 16;
+
+/// Number of points on each side of a rectangle used to calculate its bounding box in another CRS.
+const EDGE_SAMPLES: usize = 8;
+
+/// Key of a tile in the tile cache of a raster layer.
+///
+/// A tile loaded in the CRS of the tile schema can be reprojected into different view CRSs, so the reprojected images
+/// are stored separately for each target CRS. The level of detail of the tile is the `z` index of the tile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(super) struct TileKey {
+    pub(super) index: TileIndex,
+    /// Id of the CRS the tile is reprojected into, or `None` if the tile is drawn in the CRS of the tile schema.
+    pub(super) target_crs: Option<u32>,
+}
+
+/// Reprojects tile images from the CRS of the tile schema into the CRS of the map view.
+pub(super) struct TileWarp {
+    source_crs: Crs,
+    target_crs: Crs,
+    target_crs_id: u32,
+}
+
+/// Pair of projections converting coordinates from the source CRS to the target CRS through geographic coordinates.
+struct Transformation {
+    source: Box<dyn Projection<InPoint = GeoPoint2d, OutPoint = Point2d>>,
+    target: Box<dyn Projection<InPoint = GeoPoint2d, OutPoint = Point2d>>,
+}
+
+impl Transformation {
+    fn forward(&self, point: Point2d) -> Option<Point2d> {
+        self.target.project(&self.source.unproject(&point)?)
+    }
+
+    fn backward(&self, point: Point2d) -> Option<Point2d> {
+        self.source.project(&self.target.unproject(&point)?)
+    }
+}
+
+impl TileWarp {
+    pub(super) fn new(source_crs: Crs, target_crs: Crs, target_crs_id: u32) -> Self {
+        Self {
+            source_crs,
+            target_crs,
+            target_crs_id,
+        }
+    }
+
+    /// CRS the tiles are reprojected into.
+    pub(super) fn target_crs(&self) -> &Crs {
+        &self.target_crs
+    }
+
+    /// Cache key of the reprojected tile with the given index.
+    pub(super) fn key(&self, index: TileIndex) -> TileKey {
+        TileKey {
+            index,
+            target_crs: Some(self.target_crs_id),
+        }
+    }
+
+    fn transformation(&self) -> Option<Transformation> {
+        Some(Transformation {
+            source: self.source_crs.get_projection()?,
+            target: self.target_crs.get_projection()?,
+        })
+    }
+
+    /// Calculates the area and the resolution in the source CRS that correspond to the given view.
+    pub(super) fn source_view(&self, view: &MapView) -> Option<(Rect, f64)> {
+        let transformation = self.transformation()?;
+        let target_bbox = view.get_bbox()?;
+        let source_bbox = transform_bbox(target_bbox, |point| transformation.backward(point))?;
+
+        let scale = ((source_bbox.width() * source_bbox.height())
+            / (target_bbox.width() * target_bbox.height()))
+        .sqrt();
+        if !scale.is_finite() || scale <= 0.0 {
+            return None;
+        }
+
+        Some((source_bbox, view.resolution() * scale))
+    }
+
+    /// Reprojects the tile image with the given bounding box in the source CRS. Returns the reprojected image and its
+    /// bounding box in the target CRS.
+    ///
+    /// The reprojected image has the same size as the source image. Pixels that do not correspond to any pixel of the
+    /// source image are transparent.
+    pub(super) fn warp(
+        &self,
+        image: &DecodedImage,
+        source_bbox: Rect,
+    ) -> Option<(DecodedImage, Rect)> {
+        let (bytes, dimensions) = match &image.0 {
+            DecodedImageType::Bitmap { bytes, dimensions } => (bytes, *dimensions),
+            #[cfg(target_arch = "wasm32")]
+            _ => {
+                log::warn!("Only bitmap images can be reprojected");
+                return None;
+            }
+        };
+
+        let transformation = self.transformation()?;
+        let target_bbox = transform_bbox(source_bbox, |point| transformation.forward(point))?;
+        let bytes = warp_bitmap(bytes, dimensions, source_bbox, target_bbox, |point| {
+            transformation.backward(point)
+        });
+
+        Some((DecodedImage::from_raw(bytes, dimensions).ok()?, target_bbox))
+    }
+}
+
+/// Calculates the bounding box of the rectangle after transformation, using points along its sides.
+fn transform_bbox(rect: Rect, transform: impl Fn(Point2d) -> Option<Point2d>) -> Option<Rect> {
+    let mut points = Vec::with_capacity(EDGE_SAMPLES * 4);
+    for i in 0..EDGE_SAMPLES {
+        let t = i as f64 / EDGE_SAMPLES as f64;
+        let x = rect.x_min() + rect.width() * t;
+        let y = rect.y_min() + rect.height() * t;
+        points.push(Point2d::new(x, rect.y_min()));
+        points.push(Point2d::new(rect.x_max(), y));
+        points.push(Point2d::new(rect.x_max() - rect.width() * t, rect.y_max()));
+        points.push(Point2d::new(rect.x_min(), rect.y_max() - rect.height() * t));
+    }
+
+    let transformed: Vec<Point2d> = points.into_iter().filter_map(transform).collect();
+    Rect::from_points(transformed.iter())
+}
+
+/// Fills an RGBA image covering `target_bbox` with the pixels of the `source` image covering `source_bbox`, using
+/// nearest neighbour sampling. `backward` converts target coordinates into source coordinates.
+///
+/// The transformation is calculated exactly only at the nodes of a [`GRID_CELLS`] grid, and interpolated between them.
+fn warp_bitmap(
+    source: &[u8],
+    size: Size<u32>,
+    source_bbox: Rect,
+    target_bbox: Rect,
+    backward: impl Fn(Point2d) -> Option<Point2d>,
+) -> Vec<u8> {
+    let width = size.width() as usize;
+    let height = size.height() as usize;
+    let mut target = vec![0; source.len()];
+
+    let node_position = |col: usize, row: usize| {
+        Point2d::new(
+            target_bbox.x_min() + target_bbox.width() * col as f64 / GRID_CELLS as f64,
+            target_bbox.y_max() - target_bbox.height() * row as f64 / GRID_CELLS as f64,
+        )
+    };
+    let nodes: Vec<Option<Point2d>> = (0..=GRID_CELLS)
+        .flat_map(|row| (0..=GRID_CELLS).map(move |col| (col, row)))
+        .map(|(col, row)| backward(node_position(col, row)))
+        .collect();
+
+    for row in 0..height {
+        let v = (row as f64 + 0.5) / height as f64 * GRID_CELLS as f64;
+        let cell_row = (v as usize).min(GRID_CELLS - 1);
+        let fy = v - cell_row as f64;
+
+        for col in 0..width {
+            let u = (col as f64 + 0.5) / width as f64 * GRID_CELLS as f64;
+            let cell_col = (u as usize).min(GRID_CELLS - 1);
+            let fx = u - cell_col as f64;
+
+            let node = |c: usize, r: usize| nodes[r * (GRID_CELLS + 1) + c];
+            let (Some(p00), Some(p10), Some(p01), Some(p11)) = (
+                node(cell_col, cell_row),
+                node(cell_col + 1, cell_row),
+                node(cell_col, cell_row + 1),
+                node(cell_col + 1, cell_row + 1),
+            ) else {
+                continue;
+            };
+
+            let x = lerp(lerp(p00.x(), p10.x(), fx), lerp(p01.x(), p11.x(), fx), fy);
+            let y = lerp(lerp(p00.y(), p10.y(), fx), lerp(p01.y(), p11.y(), fx), fy);
+
+            let source_col = (x - source_bbox.x_min()) / source_bbox.width() * width as f64;
+            let source_row = (source_bbox.y_max() - y) / source_bbox.height() * height as f64;
+            if !(0.0..width as f64).contains(&source_col)
+                || !(0.0..height as f64).contains(&source_row)
+            {
+                continue;
+            }
+
+            let source_offset = (source_row as usize * width + source_col as usize) * 4;
+            let target_offset = (row * width + col) * 4;
+            target[target_offset..target_offset + 4]
+                .copy_from_slice(&source[source_offset..source_offset + 4]);
+        }
+    }
+
+    target
+}
+
+fn lerp(a: f64, b: f64, t: f64) -> f64 {
+    a + (b - a) * t
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn warp_bitmap_with_shift_and_flip() {
+        // 2x2 image with pixels 1, 2 in the top row and 3, 4 in the bottom row
+        let source: Vec<u8> = (1..=4).flat_map(|v| [v, v, v, 255]).collect();
+        let size = Size::new(2, 2);
+        let source_bbox = Rect::new(0.0, 0.0, 2.0, 2.0);
+
+        // Shift by 10 units to the right
+        let shifted = warp_bitmap(
+            &source,
+            size,
+            source_bbox,
+            Rect::new(10.0, 0.0, 12.0, 2.0),
+            |p| Some(Point2d::new(p.x() - 10.0, p.y())),
+        );
+        assert_eq!(shifted, source);
+
+        // Mirror vertically
+        let flipped = warp_bitmap(&source, size, source_bbox, source_bbox, |p| {
+            Some(Point2d::new(p.x(), 2.0 - p.y()))
+        });
+        let pixels: Vec<u8> = flipped.chunks(4).map(|p| p[0]).collect();
+        assert_eq!(pixels, vec![3, 4, 1, 2]);
+
+        // Points outside of the source image are transparent
+        let outside = warp_bitmap(&source, size, source_bbox, source_bbox, |p| {
+            Some(Point2d::new(p.x() + 1.0, p.y()))
+        });
+        let alpha: Vec<u8> = outside.chunks(4).map(|p| p[3]).collect();
+        assert_eq!(alpha, vec![255, 0, 255, 0]);
+    }
+
+    #[test]
+    fn transform_bbox_uses_edge_points() {
+        let rect = Rect::new(-1.0, -1.0, 1.0, 1.0);
+        // Rotation by 45 degrees makes the bbox larger by sqrt(2)
+        let angle = std::f64::consts::FRAC_PI_4;
+        let bbox = transform_bbox(rect, |p| {
+            Some(Point2d::new(
+                p.x() * angle.cos() - p.y() * angle.sin(),
+                p.x() * angle.sin() + p.y() * angle.cos(),
+            ))
+        })
+        .expect("bbox is valid");
+        assert!((bbox.x_max() - 2f64.sqrt()).abs() < 1e-9);
+        assert!((bbox.y_min() + 2f64.sqrt()).abs() < 1e-9);
+    }
+}
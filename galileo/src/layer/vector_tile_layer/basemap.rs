@@ -0,0 +1,337 @@
+use galileo_types::cartesian::{Point2d, Rect};
+use galileo_types::geo::Crs;
+
+use crate::layer::data_provider::UrlSource;
+use crate::layer::vector_tile_layer::style::{
+    LabelPriority, StyleRule, TileClipping, VectorTileDefaultSymbol, VectorTileLabelSymbol,
+    VectorTileLineSymbol, VectorTilePolygonSymbol, VectorTileStyle, VectorTileSymbol,
+};
+use crate::render::text::TextStyle;
+use crate::tile_scheme::{TileIndex, TileSchema, VerticalDirection};
+use crate::{Color, Lod};
+
+/// Name of the font used by the labels of the bundled basemap styles.
+pub const BASEMAP_FONT: &str = "Noto Sans";
+
+/// Well-known vector tile basemaps with bundled default styles.
+///
+/// A preset knows the URL template of the tile service, the tile schema, the attribution that must be displayed on
+/// the map when the tiles are used, and a light style for the schema of the tiles. A layer with the preset can be
+/// created with [`MapBuilder::create_vector_basemap_layer`](crate::MapBuilder::create_vector_basemap_layer).
+///
+/// The styles draw place labels with the [`BASEMAP_FONT`] font, which is not bundled with Galileo. Load it with
+/// [`FontService::load_fonts`](crate::render::text::font_service::FontService::load_fonts) for the labels to be
+/// displayed.
+///
+/// Note that every service has its own terms of use, so check them before using a preset in production.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum VectorBasemap {
+    /// OpenFreeMap tiles in the OpenMapTiles schema. The service is free and does not require an API key.
+    OpenFreeMap {
+        /// Version of the planet tileset, e.g. `20250101_001001_pt`. The current version is listed in the TileJSON at
+        /// `https://tiles.openfreemap.org/planet`.
+        version: String,
+    },
+    /// MapTiler tiles in the OpenMapTiles schema. Requires a MapTiler API key.
+    MapTiler {
+        /// MapTiler API key.
+        api_key: String,
+    },
+    /// Protomaps basemap tiles from the Protomaps API. Requires a Protomaps API key.
+    Protomaps {
+        /// Protomaps API key.
+        api_key: String,
+    },
+}
+
+impl VectorBasemap {
+    /// Returns the URL of the tile with the given index.
+    pub fn tile_url(&self, index: TileIndex) -> String {
+        let TileIndex { x, y, z, .. } = index;
+
+        match self {
+            Self::OpenFreeMap { version } => {
+                format!("https://tiles.openfreemap.org/planet/{version}/{z}/{x}/{y}.pbf")
+            }
+            Self::MapTiler { api_key } => format!(
+                "https://api.maptiler.com/tiles/v3-openmaptiles/{z}/{x}/{y}.pbf?key={api_key}"
+            ),
+            Self::Protomaps { api_key } => {
+                format!("https://api.protomaps.com/tiles/v4/{z}/{x}/{y}.mvt?key={api_key}")
+            }
+        }
+    }
+
+    /// Returns the URL source that can be used to create a tile provider for the basemap.
+    pub fn url_source(&self) -> impl UrlSource<TileIndex> + 'static {
+        let basemap = self.clone();
+        move |index: &TileIndex| basemap.tile_url(*index)
+    }
+
+    /// Attribution text that must be displayed on the map when the basemap is used.
+    pub fn attribution(&self) -> &'static str {
+        match self {
+            Self::OpenFreeMap { .. } => "OpenFreeMap © OpenMapTiles Data from OpenStreetMap",
+            Self::MapTiler { .. } => "© MapTiler © OpenStreetMap contributors",
+            Self::Protomaps { .. } => "Protomaps © OpenStreetMap",
+        }
+    }
+
+    /// Maximum zoom level provided by the service. The tiles of this level are used for all higher levels.
+    pub fn max_zoom(&self) -> u32 {
+        match self {
+            Self::OpenFreeMap { .. } | Self::MapTiler { .. } => 14,
+            Self::Protomaps { .. } => 15,
+        }
+    }
+
+    /// Returns true if the service requires an API key to access the tiles.
+    pub fn requires_api_key(&self) -> bool {
+        matches!(self, Self::MapTiler { .. } | Self::Protomaps { .. })
+    }
+
+    /// Tile schema of the basemap.
+    ///
+    /// Vector tiles are displayed as 512 pixel tiles, so that the features are not too dense on the screen.
+    pub fn tile_schema(&self) -> TileSchema {
+        const ORIGIN: Point2d = Point2d::new(-20037508.342787, 20037508.342787);
+        const TOP_RESOLUTION: f64 = 156543.03392800014 / 2.0;
+
+        let mut lods = vec![Lod::new(TOP_RESOLUTION, 0).expect("invalid const parameters")];
+        for z in 1..=self.max_zoom() {
+            lods.push(
+                Lod::new(lods[(z - 1) as usize].resolution() / 2.0, z)
+                    .expect("invalid const parameters"),
+            );
+        }
+
+        TileSchema {
+            origin: ORIGIN,
+            bounds: Rect::new(
+                -20037508.342787,
+                -20037508.342787,
+                20037508.342787,
+                20037508.342787,
+            ),
+            lods: lods.into_iter().collect(),
+            tile_width: 512,
+            tile_height: 512,
+            y_direction: VerticalDirection::TopToBottom,
+            crs: Crs::EPSG3857,
+        }
+    }
+
+    /// Default light style for the tiles of the basemap.
+    pub fn style(&self) -> VectorTileStyle {
+        match self {
+            Self::OpenFreeMap { .. } | Self::MapTiler { .. } => openmaptiles_style(),
+            Self::Protomaps { .. } => protomaps_style(),
+        }
+    }
+}
+
+const LAND: Color = Color::rgba(0xf8, 0xf4, 0xf0, 0xff);
+const WATER: Color = Color::rgba(0xa0, 0xc8, 0xf0, 0xff);
+const PARK: Color = Color::rgba(0xd8, 0xe8, 0xc8, 0xff);
+const FOREST: Color = Color::rgba(0xc8, 0xdf, 0xb8, 0xff);
+const RESIDENTIAL: Color = Color::rgba(0xee, 0xea, 0xe4, 0xff);
+const BUILDING: Color = Color::rgba(0xdf, 0xd8, 0xd0, 0xff);
+const BOUNDARY: Color = Color::rgba(0x9e, 0x9c, 0xab, 0xff);
+const MAJOR_ROAD: Color = Color::rgba(0xfc, 0xd6, 0xa4, 0xff);
+const ROAD: Color = Color::rgba(0xff, 0xff, 0xff, 0xff);
+const PATH: Color = Color::rgba(0xd0, 0xc8, 0xc0, 0xff);
+const LABEL: Color = Color::rgba(0x33, 0x33, 0x33, 0xff);
+
+fn openmaptiles_style() -> VectorTileStyle {
+    VectorTileStyle {
+        rules: vec![
+            polygon("water", &[], WATER),
+            polygon("park", &[], PARK),
+            polygon("landcover", &[("class", "wood")], FOREST),
+            polygon("landcover", &[("class", "grass")], PARK),
+            polygon("landuse", &[("class", "residential")], RESIDENTIAL),
+            polygon("building", &[], BUILDING),
+            line("boundary", &[("admin_level", "2")], BOUNDARY, 1.5),
+            line("boundary", &[("admin_level", "4")], BOUNDARY, 0.8),
+            line("waterway", &[], WATER, 1.0),
+            line("transportation", &[("class", "motorway")], MAJOR_ROAD, 3.0),
+            line("transportation", &[("class", "trunk")], MAJOR_ROAD, 2.5),
+            line("transportation", &[("class", "primary")], MAJOR_ROAD, 2.0),
+            line("transportation", &[("class", "secondary")], ROAD, 2.0),
+            line("transportation", &[("class", "tertiary")], ROAD, 1.5),
+            line("transportation", &[("class", "minor")], ROAD, 1.0),
+            line("transportation", &[("class", "path")], PATH, 0.5),
+            label(
+                "place",
+                &[("class", "country")],
+                14.0,
+                Some(("rank", -1.0)),
+                1000.0,
+            ),
+            label(
+                "place",
+                &[("class", "city")],
+                13.0,
+                Some(("rank", -1.0)),
+                500.0,
+            ),
+            label(
+                "place",
+                &[("class", "town")],
+                11.0,
+                Some(("rank", -1.0)),
+                100.0,
+            ),
+            label("place", &[("class", "village")], 10.0, None, 0.0),
+        ],
+        default_symbol: VectorTileDefaultSymbol::default(),
+        background: LAND,
+        clipping: TileClipping::default(),
+        hidden_groups: Default::default(),
+    }
+}
+
+fn protomaps_style() -> VectorTileStyle {
+    VectorTileStyle {
+        rules: vec![
+            polygon("earth", &[], LAND),
+            polygon("water", &[], WATER),
+            polygon("landuse", &[("kind", "park")], PARK),
+            polygon("landuse", &[("kind", "forest")], FOREST),
+            polygon("landuse", &[("kind", "residential")], RESIDENTIAL),
+            polygon("landcover", &[("kind", "forest")], FOREST),
+            polygon("landcover", &[("kind", "grassland")], PARK),
+            polygon("buildings", &[], BUILDING),
+            line("boundaries", &[("kind", "country")], BOUNDARY, 1.5),
+            line("boundaries", &[("kind", "region")], BOUNDARY, 0.8),
+            line("roads", &[("kind", "highway")], MAJOR_ROAD, 3.0),
+            line("roads", &[("kind", "major_road")], MAJOR_ROAD, 2.0),
+            line("roads", &[("kind", "medium_road")], ROAD, 1.5),
+            line("roads", &[("kind", "minor_road")], ROAD, 1.0),
+            line("roads", &[("kind", "path")], PATH, 0.5),
+            label(
+                "places",
+                &[("kind", "country")],
+                14.0,
+                Some(("population_rank", 1.0)),
+                1000.0,
+            ),
+            label(
+                "places",
+                &[("kind", "locality")],
+                12.0,
+                Some(("population_rank", 1.0)),
+                0.0,
+            ),
+        ],
+        default_symbol: VectorTileDefaultSymbol::default(),
+        // The `earth` layer covers the land, so everything else is water
+        background: WATER,
+        clipping: TileClipping::default(),
+        hidden_groups: Default::default(),
+    }
+}
+
+fn rule(layer_name: &str, properties: &[(&str, &str)], symbol: VectorTileSymbol) -> StyleRule {
+    StyleRule {
+        layer_name: Some(layer_name.to_string()),
+        properties: properties
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect(),
+        symbol,
+        min_feature_size: None,
+        group: None,
+    }
+}
+
+fn polygon(layer_name: &str, properties: &[(&str, &str)], fill_color: Color) -> StyleRule {
+    rule(
+        layer_name,
+        properties,
+        VectorTileSymbol::Polygon(VectorTilePolygonSymbol {
+            fill_color,
+            palette_color: None,
+            hatching: None,
+        }),
+    )
+}
+
+fn line(layer_name: &str, properties: &[(&str, &str)], color: Color, width: f64) -> StyleRule {
+    rule(
+        layer_name,
+        properties,
+        VectorTileSymbol::Line(VectorTileLineSymbol {
+            width,
+            stroke_color: color,
+            width_scale: None,
+        }),
+    )
+}
+
+fn label(
+    layer_name: &str,
+    properties: &[(&str, &str)],
+    font_size: f32,
+    priority_property: Option<(&str, f64)>,
+    priority: f64,
+) -> StyleRule {
+    rule(
+        layer_name,
+        properties,
+        VectorTileSymbol::Label(VectorTileLabelSymbol {
+            pattern: "{name}".into(),
+            text_style: TextStyle {
+                font_name: BASEMAP_FONT.to_string(),
+                font_size,
+                font_color: LABEL,
+                horizontal_alignment: Default::default(),
+                vertical_alignment: Default::default(),
+                max_width: Some(100.0),
+                line_spacing: 1.0,
+                line_alignment: None,
+            },
+            priority: LabelPriority {
+                value: priority,
+                property: priority_property.map(|(property, _)| property.to_string()),
+                property_factor: priority_property.map_or(1.0, |(_, factor)| factor),
+            },
+            background: None,
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tile_urls() {
+        let index = TileIndex::new(1, 2, 3);
+        assert_eq!(
+            VectorBasemap::OpenFreeMap {
+                version: "20250101_001001_pt".into()
+            }
+            .tile_url(index),
+            "https://tiles.openfreemap.org/planet/20250101_001001_pt/3/1/2.pbf"
+        );
+        assert_eq!(
+            VectorBasemap::Protomaps {
+                api_key: "key".into()
+            }
+            .tile_url(index),
+            "https://api.protomaps.com/tiles/v4/3/1/2.mvt?key=key"
+        );
+    }
+
+    #[test]
+    fn tile_schema_covers_all_zoom_levels() {
+        let basemap = VectorBasemap::Protomaps {
+            api_key: "key".into(),
+        };
+        let schema = basemap.tile_schema();
+        assert!(schema.lod_resolution(basemap.max_zoom()).is_some());
+        assert!(schema.lod_resolution(basemap.max_zoom() + 1).is_none());
+    }
+}
@@ -5,24 +5,30 @@ use std::any::Any;
 use std::sync::Arc;
 use std::time::Duration;
 
-use galileo_mvt::{MvtFeature, MvtGeometry};
-use galileo_types::cartesian::{CartesianPoint2d, Point3d};
+pub use basemap::{VectorBasemap, BASEMAP_FONT};
+use galileo_mvt::{MvtFeature, MvtGeometry, MvtTile};
+use galileo_types::cartesian::{CartesianPoint2d, Point2d, Point3d, Rect};
+use galileo_types::geo::Crs;
 use galileo_types::geometry::CartesianGeometry2d;
 use galileo_types::impls::{ClosedContour, Polygon};
 use nalgebra::Point2;
 use parking_lot::Mutex;
 pub use vector_tile::VectorTile;
 
+use crate::layer::data_provider::TileSourceStats;
 use crate::layer::vector_tile_layer::style::VectorTileStyle;
-use crate::layer::vector_tile_layer::tile_provider::{VectorTileProvider, VtStyleId};
+use crate::layer::vector_tile_layer::tile_provider::{
+    VectorTileProvider, VtStyleId, VtTileMetrics,
+};
 use crate::layer::Layer;
 use crate::messenger::Messenger;
 use crate::render::render_bundle::RenderPrimitive;
-use crate::render::{Canvas, PackedBundle, PolygonPaint, RenderOptions};
+use crate::render::{Canvas, PackedBundle, PolygonPaint, RenderOptions, ScreenRefOrder};
 use crate::tile_scheme::{TileIndex, TileSchema};
 use crate::view::MapView;
 use crate::Color;
 
+mod basemap;
 pub mod style;
 pub mod tile_provider;
 mod vector_tile;
@@ -35,12 +41,16 @@ pub struct VectorTileLayer {
     style_id: VtStyleId,
     displayed_tiles: Mutex<Vec<DisplayedTile>>,
     prev_background: Mutex<Option<PreviousBackground>>,
+    data_bounds: Option<Rect>,
+    style_transition: Duration,
+    fade_in_duration: Duration,
 }
 
 #[derive(Debug, Copy, Clone)]
 struct PreviousBackground {
     color: Color,
     replaced_at: web_time::Instant,
+    duration: Duration,
 }
 
 #[derive(Clone)]
@@ -50,6 +60,7 @@ struct DisplayedTile {
     style_id: VtStyleId,
     opacity: f32,
     displayed_at: web_time::Instant,
+    fade_in: Duration,
 }
 
 impl DisplayedTile {
@@ -72,12 +83,19 @@ impl Layer for VectorTileLayer {
             .chain(displayed_tiles.iter().map(|v| (&*v.bundle, v.opacity)))
             .collect();
 
-        canvas.draw_bundles_with_opacity(&to_render, RenderOptions::default());
+        // Labels of a tile must not be covered by the polygons of the tiles drawn after it
+        canvas.draw_bundles_with_opacity(
+            &to_render,
+            RenderOptions {
+                screen_ref_order: ScreenRefOrder::AfterBundles,
+                ..Default::default()
+            },
+        );
     }
 
     fn prepare(&self, view: &MapView) {
         if let Some(iter) = self.tile_scheme.iter_tiles(view) {
-            for index in iter {
+            for index in iter.filter(|index| !self.is_empty_tile(*index)) {
                 self.tile_provider.load_tile(index, self.style_id);
             }
         }
@@ -87,6 +105,34 @@ impl Layer for VectorTileLayer {
         self.tile_provider.set_messenger(messenger);
     }
 
+    fn clear_render_cache(&self) {
+        self.displayed_tiles.lock().clear();
+        self.tile_provider.clear_packed_tiles();
+    }
+
+    fn tile_source_stats(&self) -> Option<TileSourceStats> {
+        self.source_stats()
+    }
+
+    fn extent(&self, crs: &Crs) -> Option<Rect> {
+        // Background of the tiles is drawn over the whole map
+        if crs != &self.tile_scheme.crs || !self.style().background.is_transparent() {
+            return None;
+        }
+
+        self.data_bounds
+    }
+
+    fn is_ready(&self, view: &MapView) -> bool {
+        let Some(mut iter) = self.tile_scheme.iter_tiles(view) else {
+            return true;
+        };
+
+        iter.all(|index| {
+            self.is_empty_tile(index) || self.tile_provider.is_tile_loaded(index, self.style_id)
+        })
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -104,6 +150,22 @@ impl VectorTileLayer {
             .unwrap_or_default()
     }
 
+    /// Tile schema of the layer.
+    pub fn tile_schema(&self) -> &TileSchema {
+        &self.tile_scheme
+    }
+
+    /// Statistics of the requests made by the tile loader of the layer, if the loader collects them.
+    pub fn source_stats(&self) -> Option<TileSourceStats> {
+        self.tile_provider.source_stats()
+    }
+
+    /// Feature counts of the loaded tile and the number of its features selected by each rule of the layer style.
+    /// Returns `None` if the tile is not loaded.
+    pub fn tile_metrics(&self, index: TileIndex) -> Option<VtTileMetrics> {
+        self.tile_provider.tile_metrics(index, self.style_id)
+    }
+
     /// Creates a new layer with the given url source.
     pub fn new(
         mut tile_provider: VectorTileProvider,
@@ -117,15 +179,58 @@ impl VectorTileLayer {
             style_id,
             displayed_tiles: Default::default(),
             prev_background: Default::default(),
+            data_bounds: None,
+            style_transition: DEFAULT_FADE_IN_TIME,
+            fade_in_duration: DEFAULT_FADE_IN_TIME,
         }
     }
 
+    /// Sets fade in duration for newly loaded tiles. Zero duration makes the tiles appear at full opacity right away.
+    pub fn set_fade_in_duration(&mut self, duration: Duration) {
+        self.fade_in_duration = duration;
+    }
+
+    /// Sets the area (in the tile schema CRS), outside of which the tile source has no data.
+    ///
+    /// Usually the bounds of the data set are given in the metadata of the source (e.g. TileJSON `bounds` or PMTiles
+    /// header). Tiles that do not intersect the bounds are never requested from the source. Such tiles are
+    /// considered empty, so they are drawn with only the background color of the style right away, without
+    /// waiting for the source to fail loading them.
+    pub fn set_data_bounds(&mut self, bounds: Option<Rect>) {
+        self.data_bounds = bounds;
+    }
+
+    /// Sets the area, outside of which the tile source has no data. See [`VectorTileLayer::set_data_bounds`].
+    pub fn with_data_bounds(mut self, bounds: Rect) -> Self {
+        self.set_data_bounds(Some(bounds));
+        self
+    }
+
+    /// The area, outside of which the tile source has no data.
+    pub fn data_bounds(&self) -> Option<Rect> {
+        self.data_bounds
+    }
+
+    /// Returns true if the tile is known to have no data, because it lies outside the data bounds.
+    fn is_empty_tile(&self, index: TileIndex) -> bool {
+        let Some(bounds) = self.data_bounds else {
+            return false;
+        };
+
+        self.tile_scheme
+            .tile_bbox(index)
+            .is_some_and(|tile_bbox| !tile_bbox.intersects(bounds))
+    }
+
     fn update_displayed_tiles(&self, view: &MapView, canvas: &dyn Canvas) {
         let Some(tile_iter) = self.tile_scheme.iter_tiles(view) else {
             return;
         };
 
-        let needed_indices: Vec<_> = tile_iter.collect();
+        // Empty tiles are covered by the background bundle, so they need neither loading nor substitution
+        let needed_indices: Vec<_> = tile_iter
+            .filter(|index| !self.is_empty_tile(*index))
+            .collect();
         self.tile_provider
             .pack_tiles(&needed_indices, self.style_id, canvas);
 
@@ -135,7 +240,15 @@ impl VectorTileLayer {
         let mut to_substitute = vec![];
 
         let now = web_time::Instant::now();
-        let fade_in_time = self.fade_in_time();
+        // While tiles of the previous style are still displayed, new tiles replace them with the style transition
+        let fade_in = if displayed_tiles
+            .iter()
+            .any(|displayed| displayed.style_id != self.style_id)
+        {
+            self.style_transition
+        } else {
+            self.fade_in_duration
+        };
         let mut requires_redraw = false;
 
         for index in &needed_indices {
@@ -145,9 +258,10 @@ impl VectorTileLayer {
             {
                 if !displayed.is_opaque() {
                     to_substitute.push(*index);
-                    displayed.opacity = ((now.duration_since(displayed.displayed_at)).as_secs_f64()
-                        / fade_in_time.as_secs_f64())
-                    .min(1.0) as f32;
+                    displayed.opacity = fade_in_opacity(
+                        now.duration_since(displayed.displayed_at),
+                        displayed.fade_in,
+                    );
                     requires_redraw = true;
                 }
 
@@ -156,15 +270,19 @@ impl VectorTileLayer {
                 match self.tile_provider.get_tile(*index, self.style_id) {
                     None => to_substitute.push(*index),
                     Some(bundle) => {
-                        needed_tiles.push(DisplayedTile {
+                        let displayed = DisplayedTile {
                             index: *index,
                             bundle,
                             style_id: self.style_id,
-                            opacity: 0.0,
+                            opacity: fade_in_opacity(Duration::ZERO, fade_in),
                             displayed_at: now,
-                        });
-                        to_substitute.push(*index);
-                        requires_redraw = true;
+                            fade_in,
+                        };
+                        if !displayed.is_opaque() {
+                            to_substitute.push(*index);
+                            requires_redraw = true;
+                        }
+                        needed_tiles.push(displayed);
                     }
                 }
             }
@@ -203,32 +321,115 @@ impl VectorTileLayer {
         }
     }
 
-    fn fade_in_time(&self) -> Duration {
-        Duration::from_millis(300)
-    }
-
     /// Change style of the layer and redraw it.
     pub fn update_style(&mut self, style: VectorTileStyle) {
+        self.update_style_with_transition(style, DEFAULT_FADE_IN_TIME);
+    }
+
+    /// Change style of the layer, cross-fading from the current style to the new one during the given `duration`.
+    ///
+    /// Already loaded vector tile data is reused, only the processing of the tiles with the new style is done in the
+    /// background. Tiles drawn with the current style stay on the map until the tiles with the new style are ready,
+    /// so switching e.g. between day and night styles does not blank the map.
+    pub fn update_style_with_transition(&mut self, style: VectorTileStyle, duration: Duration) {
         let new_style_id = self.tile_provider.add_style(style);
         if let Some(curr_style) = self.tile_provider.get_style(self.style_id) {
             *self.prev_background.lock() = Some(PreviousBackground {
                 color: curr_style.background,
                 replaced_at: web_time::Instant::now(),
+                duration,
             });
         }
         self.tile_provider.drop_style(self.style_id);
         self.style_id = new_style_id;
+        self.style_transition = duration;
+        self.tile_provider.request_redraw();
+    }
+
+    /// Shows or hides the features selected by the style rules of the [group](style::StyleRule::group) and redraws
+    /// the layer.
+    ///
+    /// Only the tiles that contain features of the group are prepared again, other tiles are reused as they are.
+    pub fn set_group_visible(&mut self, group: &str, visible: bool) {
+        let curr_style = self.style();
+        if curr_style.is_group_visible(group) == visible {
+            return;
+        }
+
+        let mut style = (*curr_style).clone();
+        style.set_group_visible(group, visible);
+
+        let new_style_id = self.tile_provider.add_style(style);
+        let reused = self
+            .tile_provider
+            .reuse_tiles(self.style_id, new_style_id, |tile| {
+                !Self::has_group_features(&curr_style, tile, group)
+            });
+
+        // Reused tiles look the same with the new style, so they are kept on the map without fading
+        for displayed in self.displayed_tiles.lock().iter_mut() {
+            if displayed.style_id == self.style_id && reused.contains(&displayed.index) {
+                displayed.style_id = new_style_id;
+            }
+        }
+
+        self.tile_provider.drop_style(self.style_id);
+        self.style_id = new_style_id;
+        self.tile_provider.request_redraw();
+    }
+
+    fn has_group_features(style: &VectorTileStyle, tile: &MvtTile, group: &str) -> bool {
+        tile.layers.iter().any(|layer| {
+            layer.features.iter().any(|feature| {
+                style
+                    .get_style_rule(&layer.name, feature)
+                    .is_some_and(|rule| rule.group.as_deref() == Some(group))
+            })
+        })
     }
 
     /// Returns features, visible in the layer at the given point with the given map view.
+    ///
+    /// Features within 2 pixels (at the resolution of the `view`) from the point are returned. To set the tolerance
+    /// explicitly, use [`VectorTileLayer::get_features_at_screen_point`].
     pub fn get_features_at(
         &self,
         point: &impl CartesianPoint2d<Num = f64>,
         view: &MapView,
+    ) -> Vec<(String, MvtFeature)> {
+        self.get_features_in_radius(point, view.resolution() * 2.0, view)
+    }
+
+    /// Returns features, visible in the layer within `tolerance_px` logical pixels from the given point on the
+    /// screen.
+    ///
+    /// The tolerance is converted into map units with [`MapView::screen_distance_to_map`], so it takes into account
+    /// the resolution, tilt and DPI scale factor of the view.
+    pub fn get_features_at_screen_point(
+        &self,
+        screen_point: Point2d,
+        view: &MapView,
+        tolerance_px: f64,
+    ) -> Vec<(String, MvtFeature)> {
+        let Some(point) = view.screen_to_map(screen_point) else {
+            return vec![];
+        };
+        let Some(tolerance) = view.screen_distance_to_map(screen_point, tolerance_px) else {
+            return vec![];
+        };
+
+        self.get_features_in_radius(&point, tolerance, view)
+    }
+
+    fn get_features_in_radius(
+        &self,
+        point: &impl CartesianPoint2d<Num = f64>,
+        tolerance: f64,
+        view: &MapView,
     ) -> Vec<(String, MvtFeature)> {
         let mut features = vec![];
         if let Some(iter) = self.tile_scheme.iter_tiles(view) {
-            for index in iter {
+            for index in iter.filter(|index| !self.is_empty_tile(*index)) {
                 let Some(tile_bbox) = self.tile_scheme.tile_bbox(index) else {
                     continue;
                 };
@@ -243,7 +444,7 @@ impl VectorTileLayer {
                     ((tile_bbox.y_max() - point.y()) / tile_resolution) as f32,
                 );
 
-                let tolerance = (view.resolution() / tile_resolution) as f32 * 2.0;
+                let tolerance = (tolerance / tile_resolution) as f32;
 
                 if let Some(mvt_tile) = self.tile_provider.get_mvt_tile(index) {
                     for layer in &mvt_tile.layers {
@@ -297,15 +498,16 @@ impl VectorTileLayer {
         let mut prev_background = self.prev_background.lock();
         let color = match *prev_background {
             Some(prev) => {
-                let k = web_time::Instant::now()
-                    .duration_since(prev.replaced_at)
-                    .as_secs_f32()
-                    / self.fade_in_time().as_secs_f32();
+                let k = fade_in_opacity(
+                    web_time::Instant::now().duration_since(prev.replaced_at),
+                    prev.duration,
+                );
 
                 if k >= 1.0 {
                     *prev_background = None;
                     style.background
                 } else {
+                    self.tile_provider.request_redraw();
                     prev.color.blend(
                         style
                             .background
@@ -319,7 +521,10 @@ impl VectorTileLayer {
         bundle.add(
             RenderPrimitive::<_, _, galileo_types::impls::Contour<_>, _>::new_polygon_ref(
                 &bounds,
-                PolygonPaint { color },
+                PolygonPaint {
+                    color,
+                    hatching: None,
+                },
             ),
             view.resolution(),
         );
@@ -328,6 +533,16 @@ impl VectorTileLayer {
     }
 }
 
+const DEFAULT_FADE_IN_TIME: Duration = Duration::from_millis(300);
+
+fn fade_in_opacity(elapsed: Duration, fade_in: Duration) -> f32 {
+    if fade_in.is_zero() {
+        return 1.0;
+    }
+
+    (elapsed.as_secs_f64() / fade_in.as_secs_f64()).min(1.0) as f32
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -353,9 +568,24 @@ mod tests {
             style_id,
             displayed_tiles: Default::default(),
             prev_background: Default::default(),
+            data_bounds: None,
+            style_transition: DEFAULT_FADE_IN_TIME,
+            fade_in_duration: DEFAULT_FADE_IN_TIME,
         }
     }
 
+    #[test]
+    fn tiles_outside_data_bounds_are_empty() {
+        let layer = test_layer().with_data_bounds(Rect::new(1000.0, 1000.0, 2000.0, 2000.0));
+
+        // Tiles of the first level split the world into four quadrants around the origin
+        assert!(!layer.is_empty_tile(TileIndex::new(0, 0, 0)));
+        assert!(!layer.is_empty_tile(TileIndex::new(1, 0, 1)));
+        assert!(layer.is_empty_tile(TileIndex::new(0, 0, 1)));
+        assert!(layer.is_empty_tile(TileIndex::new(1, 1, 1)));
+        assert!(!test_layer().is_empty_tile(TileIndex::new(1, 1, 1)));
+    }
+
     #[test]
     fn update_style_drops_previous_style() {
         let mut layer = test_layer();
@@ -367,4 +597,44 @@ mod tests {
         assert!(layer.tile_provider.get_style(new_style_id).is_some());
         assert!(layer.tile_provider.get_style(style_id).is_none());
     }
+
+    #[test]
+    fn set_group_visible() {
+        let mut layer = test_layer();
+        let style_id = layer.style_id;
+
+        layer.set_group_visible("poi", true);
+        assert_eq!(layer.style_id, style_id);
+
+        layer.set_group_visible("poi", false);
+        assert_ne!(layer.style_id, style_id);
+        assert!(!layer.style().is_group_visible("poi"));
+        assert!(layer.tile_provider.get_style(style_id).is_none());
+
+        layer.set_group_visible("poi", true);
+        assert!(layer.style().is_group_visible("poi"));
+    }
+
+    #[test]
+    fn style_transition_cross_fades_background() {
+        let mut layer = test_layer();
+        let style = VectorTileStyle {
+            background: Color::BLACK,
+            ..Default::default()
+        };
+        layer.update_style_with_transition(style, Duration::from_secs(2));
+
+        let prev_background = layer
+            .prev_background
+            .lock()
+            .expect("previous background is stored");
+        assert_eq!(prev_background.duration, Duration::from_secs(2));
+        assert_eq!(layer.style_transition, Duration::from_secs(2));
+
+        assert_eq!(
+            fade_in_opacity(Duration::from_secs(1), Duration::from_secs(2)),
+            0.5
+        );
+        assert_eq!(fade_in_opacity(Duration::from_secs(1), Duration::ZERO), 1.0);
+    }
 }
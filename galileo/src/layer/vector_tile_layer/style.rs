@@ -1,15 +1,21 @@
 //! See [`VectorTileStyle`].
 
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 use galileo_mvt::MvtFeature;
 use serde::{Deserialize, Serialize};
 
-use crate::render::point_paint::PointPaint;
+use crate::render::point_paint::{LabelBackground, PointPaint};
 use crate::render::text::TextStyle;
-use crate::render::{LineCap, LinePaint, PolygonPaint};
+use crate::render::theme::Theme;
+use crate::render::{Hatching, LineCap, LinePaint, PolygonPaint, ResolutionScale};
 use crate::Color;
 
+#[cfg(feature = "serde_json")]
+pub mod template;
+#[cfg(feature = "serde_json")]
+pub mod validation;
+
 /// Style of a vector tile layer. This specifies how each feature in a tile should be rendered.
 ///
 /// <div class="warning">This exact type is experimental and is likely to change in near future.</div>
@@ -24,6 +30,43 @@ pub struct VectorTileStyle {
 
     /// Background color of tiles.
     pub background: Color,
+
+    /// Handling of the parts of features that are outside of the tile boundaries.
+    #[serde(default)]
+    pub clipping: TileClipping,
+
+    /// Names of the [rule groups](StyleRule::group) that are hidden. Features selected by the rules of these groups
+    /// are not drawn.
+    #[serde(default)]
+    pub hidden_groups: HashSet<String>,
+}
+
+/// Specifies how the parts of the features in the tile buffer (outside of the tile boundaries) are handled.
+///
+/// Vector tiles usually contain features that extend a little beyond the tile, so that the lines and polygons at the
+/// tile boundaries are drawn without gaps. Since neighbouring tiles contain the same parts of these features, drawing
+/// them fully would draw thick or semi-transparent lines twice at the tile edges.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TileClipping {
+    /// Geometries are cut at this distance from the tile boundaries when the tile is decoded. The distance is given
+    /// as a portion of the tile size, e.g. `0.0` cuts the geometries exactly at the tile boundaries. `None` keeps the
+    /// geometries as they are stored in the tile.
+    ///
+    /// Cutting the geometries reduces the amount of data to be tessellated and rendered.
+    pub buffer: Option<f32>,
+    /// If set to true, the rendered tile is masked by the tile boundaries, so that nothing is drawn outside of them.
+    pub mask: bool,
+}
+
+impl Default for TileClipping {
+    /// Cuts the geometries at the distance of 1/64 of the tile size (default buffer of the most of tile generators)
+    /// and masks the tiles.
+    fn default() -> Self {
+        Self {
+            buffer: Some(1.0 / 64.0),
+            mask: true,
+        }
+    }
 }
 
 /// Default symbol of the vector tile.
@@ -45,7 +88,42 @@ pub struct VectorTileDefaultSymbol {
 impl VectorTileStyle {
     /// Get a rule for the given feature.
     pub fn get_style_rule(&self, layer_name: &str, feature: &MvtFeature) -> Option<&StyleRule> {
-        self.rules.iter().find(|&rule| {
+        self.get_style_rule_index(layer_name, feature)
+            .map(|index| &self.rules[index])
+    }
+
+    /// Names of all rule groups of the style.
+    pub fn groups(&self) -> BTreeSet<&str> {
+        self.rules
+            .iter()
+            .filter_map(|rule| rule.group.as_deref())
+            .collect()
+    }
+
+    /// Returns false if the group with the given name is hidden.
+    pub fn is_group_visible(&self, group: &str) -> bool {
+        !self.hidden_groups.contains(group)
+    }
+
+    /// Shows or hides the features selected by the rules of the group.
+    pub fn set_group_visible(&mut self, group: &str, visible: bool) {
+        if visible {
+            self.hidden_groups.remove(group);
+        } else {
+            self.hidden_groups.insert(group.to_string());
+        }
+    }
+
+    /// Returns false if the rule belongs to a hidden group.
+    pub fn is_rule_visible(&self, rule: &StyleRule) -> bool {
+        rule.group
+            .as_deref()
+            .is_none_or(|group| self.is_group_visible(group))
+    }
+
+    /// Get the index of the rule for the given feature in the [`rules`](Self::rules) list.
+    pub fn get_style_rule_index(&self, layer_name: &str, feature: &MvtFeature) -> Option<usize> {
+        self.rules.iter().position(|rule| {
             let layer_name_check_passed = match &rule.layer_name {
                 Some(name) => name == layer_name,
                 None => true,
@@ -71,6 +149,15 @@ pub struct StyleRule {
     /// Symbol to draw a feature with.
     #[serde(default)]
     pub symbol: VectorTileSymbol,
+    /// Minimum size of a line or polygon feature on the screen in pixels (at the zoom level of the tile). Features,
+    /// which bounding box is smaller than this value in both dimensions, are skipped when the tile is processed, which
+    /// saves tessellation of e.g. sub-pixel buildings at low zoom levels. If not set, all features are drawn.
+    #[serde(default)]
+    pub min_feature_size: Option<f32>,
+    /// Name of the group of the rule. All rules of a group can be shown or hidden at once with
+    /// [`VectorTileStyle::set_group_visible`], e.g. to toggle display of all points of interest.
+    #[serde(default)]
+    pub group: Option<String>,
 }
 
 /// Symbol of an object in a vector tile.
@@ -152,6 +239,16 @@ pub struct VectorTileLineSymbol {
     pub width: f64,
     /// Color of the line in pixels.
     pub stroke_color: Color,
+    /// Multiplier of the line width that changes with the resolution of the map.
+    #[serde(default)]
+    pub width_scale: Option<ResolutionScale>,
+}
+
+impl VectorTileLineSymbol {
+    /// Names of the serialized properties of the symbol, used by the style validation.
+    #[cfg(feature = "serde_json")]
+    pub(crate) const PROPERTIES: &'static [&'static str] =
+        &["width", "stroke_color", "width_scale"];
 }
 
 impl From<VectorTileLineSymbol> for LinePaint {
@@ -161,6 +258,8 @@ impl From<VectorTileLineSymbol> for LinePaint {
             width: value.width,
             offset: 0.0,
             line_cap: LineCap::Butt,
+            width_scale: value.width_scale,
+            crisp: false,
         }
     }
 }
@@ -170,12 +269,23 @@ impl From<VectorTileLineSymbol> for LinePaint {
 pub struct VectorTilePolygonSymbol {
     /// Color of the fill of polygon.
     pub fill_color: Color,
+    /// Index of the color in the palette of the current [`Theme`]. If set, it is used instead of the `fill_color`.
+    #[serde(default)]
+    pub palette_color: Option<usize>,
+    /// Pattern drawn over the fill when [`Theme::show_patterns`] is enabled.
+    #[serde(default)]
+    pub hatching: Option<Hatching>,
 }
 
 impl From<VectorTilePolygonSymbol> for PolygonPaint {
     fn from(value: VectorTilePolygonSymbol) -> Self {
+        let theme = Theme::current();
         Self {
-            color: value.fill_color,
+            color: value
+                .palette_color
+                .map(|index| theme.color(index))
+                .unwrap_or(value.fill_color),
+            hatching: value.hatching.filter(|_| theme.show_patterns),
         }
     }
 }
@@ -187,6 +297,68 @@ pub struct VectorTileLabelSymbol {
     pub pattern: String,
     /// Style of the text.
     pub text_style: TextStyle,
+    /// Priority of the label. Labels with higher priority are drawn over the labels with lower priority of the same
+    /// tile. See [`LabelPriority`] for details.
+    #[serde(default)]
+    pub priority: LabelPriority,
+    /// Background drawn behind the text, e.g. a road number shield.
+    #[serde(default)]
+    pub background: Option<LabelBackground>,
+}
+
+/// Priority of a text label, calculated from the properties of the feature.
+///
+/// For a feature the priority is `value + property_factor * properties[property]`. For example, to show city names
+/// over village names, the `population` property can be used with a positive factor, and the `rank` property
+/// (where smaller rank means more important place) can be used with a negative factor. Labels of the features that
+/// don't have a numeric value of the property get the base `value` as their priority.
+///
+/// Labels with equal priority are drawn in the order they appear in the tile.
+///
+/// The priority is applied per tile: labels are ordered only relative to the other labels of the same tile. Labels of
+/// different tiles are not compared, so a label near a tile border can be covered by a lower priority label of the
+/// neighbouring tile if that tile is drawn later.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LabelPriority {
+    /// Base priority of the label.
+    #[serde(default)]
+    pub value: f64,
+    /// Name of the feature property to add to the priority.
+    #[serde(default)]
+    pub property: Option<String>,
+    /// Multiplier of the property value.
+    #[serde(default = "default_property_factor")]
+    pub property_factor: f64,
+}
+
+fn default_property_factor() -> f64 {
+    1.0
+}
+
+impl Default for LabelPriority {
+    fn default() -> Self {
+        Self {
+            value: 0.0,
+            property: None,
+            property_factor: default_property_factor(),
+        }
+    }
+}
+
+impl LabelPriority {
+    /// Calculates the priority of the label of the given feature.
+    pub fn get(&self, feature: &MvtFeature) -> f64 {
+        let property_value = self
+            .property
+            .as_ref()
+            .and_then(|property| feature.properties.get(property))
+            .and_then(|value| value.as_f64());
+
+        match property_value {
+            Some(property_value) => self.value + self.property_factor * property_value,
+            None => self.value,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -208,12 +380,45 @@ mod tests {
         assert!(value.as_object().unwrap().get("polygon").is_none());
     }
 
+    #[test]
+    fn label_priority() {
+        let feature = MvtFeature {
+            id: None,
+            properties: HashMap::from([
+                ("rank".to_string(), galileo_mvt::MvtValue::Int64(3)),
+                (
+                    "name".to_string(),
+                    galileo_mvt::MvtValue::String("A".into()),
+                ),
+            ]),
+            geometry: galileo_mvt::MvtGeometry::Point(vec![]),
+        };
+
+        let priority = LabelPriority {
+            value: 10.0,
+            property: Some("rank".into()),
+            property_factor: -2.0,
+        };
+        assert_eq!(priority.get(&feature), 4.0);
+
+        let priority = LabelPriority {
+            property: Some("name".into()),
+            ..Default::default()
+        };
+        assert_eq!(priority.get(&feature), 0.0);
+
+        let priority: LabelPriority = serde_json::from_str(r#"{"property": "rank"}"#).unwrap();
+        assert_eq!(priority.get(&feature), 3.0);
+    }
+
     #[test]
     fn serialize_with_bincode() {
         let rule = StyleRule {
             layer_name: None,
             properties: HashMap::new(),
             symbol: VectorTileSymbol::None,
+            min_feature_size: Some(2.0),
+            group: Some("buildings".into()),
         };
 
         let serialized = bincode::serde::encode_to_vec(&rule, bincode::config::standard()).unwrap();
@@ -0,0 +1,293 @@
+//! Composition of [`VectorTileStyle`]s from a base style, patches and variables.
+//!
+//! Styles for different themes of the same map (e.g. light and dark) usually differ only in a handful of colors and
+//! sizes. A [`StyleTemplate`] lets such styles be written once:
+//!
+//! * the style document can define named values in the top-level `variables` object. Any string value of the form
+//!   `"$name"` anywhere in the document is replaced by the value of the variable `name`. Variables can reference other
+//!   variables. To use a string starting with `$` literally, double the dollar sign (`"$$name"`).
+//! * a patch is a JSON document that is merged into the template following the
+//!   [JSON merge patch](https://datatracker.ietf.org/doc/html/rfc7386) rules: objects are merged recursively, `null`
+//!   removes the property, and all other values (including arrays, e.g. the list of rules) replace the existing ones.
+//!
+//! So a theme can be expressed as a patch that changes only the `variables` of the base style:
+//!
+//! ```
+//! use galileo::layer::vector_tile_layer::style::template::StyleTemplate;
+//!
+//! let base = StyleTemplate::from_json(
+//!     r##"{
+//!         "variables": { "water": "#A0C8F0FF", "road": "#FFFFFFFF", "road_width": 2.0 },
+//!         "rules": [
+//!             { "layer_name": "water", "symbol": { "polygon": { "fill_color": "$water" } } },
+//!             { "layer_name": "road", "symbol": { "line": { "width": "$road_width", "stroke_color": "$road" } } }
+//!         ],
+//!         "default_symbol": {},
+//!         "background": "#F8F4F0FF"
+//!     }"##,
+//! )
+//! .expect("invalid style");
+//!
+//! let dark = base
+//!     .clone()
+//!     .with_patch(r##"{ "variables": { "water": "#1B2B3AFF", "road": "#505050FF" }, "background": "#202020FF" }"##)
+//!     .expect("invalid patch");
+//!
+//! let light_style = base.build().expect("invalid style");
+//! let dark_style = dark.build().expect("invalid style");
+//! ```
+//!
+//! Switching the theme at runtime is then done by building the style and passing it to
+//! [`VectorTileLayer::update_style`](crate::layer::VectorTileLayer::update_style).
+
+use serde_json::{Map, Value};
+
+use super::VectorTileStyle;
+use crate::error::GalileoError;
+
+const VARIABLES: &str = "variables";
+const VARIABLE_PREFIX: char = '$';
+
+/// Vector tile style document with variables that can be modified with patches before it is built into a
+/// [`VectorTileStyle`]. See [module documentation](self) for details.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StyleTemplate {
+    document: Map<String, Value>,
+}
+
+impl StyleTemplate {
+    /// Parses the template from a JSON string.
+    pub fn from_json(json: &str) -> Result<Self, GalileoError> {
+        Self::from_value(parse(json)?)
+    }
+
+    /// Creates a template from a parsed JSON document. The document must be an object.
+    pub fn from_value(value: Value) -> Result<Self, GalileoError> {
+        match value {
+            Value::Object(document) => Ok(Self { document }),
+            _ => Err(GalileoError::Generic(
+                "style document must be a JSON object".into(),
+            )),
+        }
+    }
+
+    /// Applies the JSON merge patch to the template.
+    pub fn with_patch(mut self, patch: &str) -> Result<Self, GalileoError> {
+        self.apply_patch(patch)?;
+        Ok(self)
+    }
+
+    /// Applies the JSON merge patch to the template.
+    pub fn apply_patch(&mut self, patch: &str) -> Result<(), GalileoError> {
+        match parse(patch)? {
+            Value::Object(patch) => {
+                merge_objects(&mut self.document, patch);
+                Ok(())
+            }
+            _ => Err(GalileoError::Generic(
+                "style patch must be a JSON object".into(),
+            )),
+        }
+    }
+
+    /// Returns the value of the variable.
+    pub fn variable(&self, name: &str) -> Option<&Value> {
+        self.variables()?.get(name)
+    }
+
+    /// Sets the value of the variable.
+    pub fn set_variable(&mut self, name: impl Into<String>, value: impl Into<Value>) {
+        let variables = self
+            .document
+            .entry(VARIABLES)
+            .or_insert_with(|| Value::Object(Map::new()));
+        if !variables.is_object() {
+            *variables = Value::Object(Map::new());
+        }
+
+        if let Value::Object(variables) = variables {
+            variables.insert(name.into(), value.into());
+        }
+    }
+
+    /// Sets the value of the variable.
+    pub fn with_variable(mut self, name: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.set_variable(name, value);
+        self
+    }
+
+    /// Returns the style document with all variable references replaced by their values and without the `variables`
+    /// object.
+    pub fn resolve(&self) -> Result<Value, GalileoError> {
+        let resolver = Resolver {
+            variables: self.variables(),
+        };
+
+        let mut document = self.document.clone();
+        document.remove(VARIABLES);
+
+        let mut document = Value::Object(document);
+        resolver.resolve(&mut document, &mut vec![])?;
+
+        Ok(document)
+    }
+
+    /// Resolves the variables and creates the style from the template.
+    pub fn build(&self) -> Result<VectorTileStyle, GalileoError> {
+        serde_json::from_value(self.resolve()?)
+            .map_err(|err| GalileoError::Generic(format!("invalid style: {err}")))
+    }
+
+    fn variables(&self) -> Option<&Map<String, Value>> {
+        self.document.get(VARIABLES)?.as_object()
+    }
+}
+
+fn parse(json: &str) -> Result<Value, GalileoError> {
+    serde_json::from_str(json).map_err(|err| GalileoError::Generic(format!("invalid JSON: {err}")))
+}
+
+fn merge_objects(target: &mut Map<String, Value>, patch: Map<String, Value>) {
+    for (key, value) in patch {
+        match value {
+            Value::Null => {
+                target.remove(&key);
+            }
+            Value::Object(patch) => match target.get_mut(&key) {
+                Some(Value::Object(target)) => merge_objects(target, patch),
+                _ => {
+                    let mut object = Map::new();
+                    merge_objects(&mut object, patch);
+                    target.insert(key, Value::Object(object));
+                }
+            },
+            value => {
+                target.insert(key, value);
+            }
+        }
+    }
+}
+
+struct Resolver<'a> {
+    variables: Option<&'a Map<String, Value>>,
+}
+
+impl Resolver<'_> {
+    /// `stack` contains the names of the variables being resolved, to detect cyclic references.
+    fn resolve(&self, value: &mut Value, stack: &mut Vec<String>) -> Result<(), GalileoError> {
+        match value {
+            Value::String(string) => {
+                if let Some(literal) = string.strip_prefix("$$") {
+                    *string = format!("{VARIABLE_PREFIX}{literal}");
+                } else if let Some(name) = string.strip_prefix(VARIABLE_PREFIX) {
+                    let resolved = self.variable(name, stack)?;
+                    *value = resolved;
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    self.resolve(item, stack)?;
+                }
+            }
+            Value::Object(object) => {
+                for item in object.values_mut() {
+                    self.resolve(item, stack)?;
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn variable(&self, name: &str, stack: &mut Vec<String>) -> Result<Value, GalileoError> {
+        if stack.iter().any(|resolving| resolving == name) {
+            return Err(GalileoError::Generic(format!(
+                "style variable `{name}` references itself"
+            )));
+        }
+
+        let mut value = self
+            .variables
+            .and_then(|variables| variables.get(name))
+            .cloned()
+            .ok_or_else(|| {
+                GalileoError::Generic(format!("style variable `{name}` is not defined"))
+            })?;
+
+        stack.push(name.to_string());
+        self.resolve(&mut value, stack)?;
+        stack.pop();
+
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::Color;
+
+    const BASE: &str = r##"{
+        "variables": { "water": "#0000FFFF", "casing": "$road", "road": "#FFFFFFFF", "width": 2.0 },
+        "rules": [
+            { "layer_name": "water", "symbol": { "polygon": { "fill_color": "$water" } } },
+            { "layer_name": "road", "symbol": { "line": { "width": "$width", "stroke_color": "$casing" } } },
+            { "layer_name": "$$literal", "symbol": "None" }
+        ],
+        "default_symbol": {},
+        "background": "#FFFFFFFF",
+        "clipping": { "buffer": 0.1, "mask": true }
+    }"##;
+
+    #[test]
+    fn resolves_variables() {
+        let style = StyleTemplate::from_json(BASE).unwrap().build().unwrap();
+
+        assert_eq!(
+            style.rules[0].symbol.polygon().unwrap().fill_color,
+            Color::BLUE
+        );
+        let line = style.rules[1].symbol.line().unwrap();
+        assert_eq!(line.width, 2.0);
+        assert_eq!(line.stroke_color, Color::WHITE);
+        assert_eq!(style.rules[2].layer_name.as_deref(), Some("$literal"));
+    }
+
+    #[test]
+    fn applies_patch() {
+        let dark = StyleTemplate::from_json(BASE)
+            .unwrap()
+            .with_patch(
+                r##"{ "variables": { "road": "#000000FF" }, "background": "#000000FF", "clipping": { "buffer": null } }"##,
+            )
+            .unwrap()
+            .with_variable("width", 3.0);
+
+        assert_eq!(dark.variable("water"), Some(&json!("#0000FFFF")));
+
+        let style = dark.build().unwrap();
+        assert_eq!(style.background, Color::BLACK);
+        assert_eq!(style.clipping.buffer, None);
+        assert!(style.clipping.mask);
+        let line = style.rules[1].symbol.line().unwrap();
+        assert_eq!(line.width, 3.0);
+        assert_eq!(line.stroke_color, Color::BLACK);
+    }
+
+    #[test]
+    fn invalid_variables() {
+        let undefined = StyleTemplate::from_json(r#"{ "background": "$missing" }"#).unwrap();
+        assert!(undefined.resolve().is_err());
+
+        let cyclic = StyleTemplate::from_json(
+            r#"{ "variables": { "a": "$b", "b": ["$a"] }, "background": "$a" }"#,
+        )
+        .unwrap();
+        assert!(cyclic.resolve().is_err());
+
+        assert!(StyleTemplate::from_json("[]").is_err());
+    }
+}
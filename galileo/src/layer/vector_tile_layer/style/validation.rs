@@ -0,0 +1,612 @@
+//! Validation of [`VectorTileStyle`] JSON documents.
+//!
+//! Deserialization of a style ignores unknown properties and replaces invalid colors with black, so a typo in a style
+//! file results in a silently wrong map. [`validate_style`] checks the style document and returns the list of
+//! problems found in it, with the location of each problem, so that style editors can show them to the user.
+
+use std::fmt::{Display, Formatter};
+
+use serde_json::{Map, Value};
+
+use super::template::StyleTemplate;
+use super::{VectorTileLineSymbol, VectorTileStyle};
+use crate::Color;
+
+/// Severity of a [`StyleDiagnostic`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Severity {
+    /// The style cannot be loaded.
+    Error,
+    /// The style can be loaded, but probably will not be rendered as intended.
+    Warning,
+}
+
+/// Kind of the problem found in a style.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiagnosticKind {
+    /// The document is not a valid JSON, or it does not match the structure of the style (e.g. a required property is
+    /// missing or has a wrong type).
+    InvalidStructure {
+        /// Description of the problem given by the parser.
+        message: String,
+    },
+    /// Property is not a part of the style and is ignored.
+    UnknownProperty {
+        /// Name of the property.
+        name: String,
+        /// Known property with the similar name.
+        suggestion: Option<String>,
+    },
+    /// Value is not a valid color. Such colors are drawn black.
+    InvalidColor {
+        /// The invalid value.
+        value: String,
+    },
+    /// Style rule references a layer that is not present in the vector tiles.
+    UnknownSourceLayer {
+        /// Name of the layer.
+        name: String,
+        /// Layer of the tiles with the similar name.
+        suggestion: Option<String>,
+    },
+}
+
+/// A problem found in a style by [`validate_style`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct StyleDiagnostic {
+    /// Severity of the problem.
+    pub severity: Severity,
+    /// Location of the problem in the document, e.g. `rules[2].symbol.line.stroke_color`. Empty for the problems of
+    /// the whole document.
+    pub path: String,
+    /// Kind of the problem.
+    pub kind: DiagnosticKind,
+}
+
+impl Display for StyleDiagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if !self.path.is_empty() {
+            write!(f, "{}: ", self.path)?;
+        }
+
+        match &self.kind {
+            DiagnosticKind::InvalidStructure { message } => write!(f, "{message}"),
+            DiagnosticKind::UnknownProperty { name, suggestion } => {
+                write!(f, "unknown property `{name}`")?;
+                write_suggestion(f, suggestion)
+            }
+            DiagnosticKind::InvalidColor { value } => write!(
+                f,
+                "invalid color `{value}`, expected `#RRGGBB` or `#RRGGBBAA`"
+            ),
+            DiagnosticKind::UnknownSourceLayer { name, suggestion } => {
+                write!(f, "layer `{name}` is not present in the tiles")?;
+                write_suggestion(f, suggestion)
+            }
+        }
+    }
+}
+
+fn write_suggestion(f: &mut Formatter<'_>, suggestion: &Option<String>) -> std::fmt::Result {
+    match suggestion {
+        Some(suggestion) => write!(f, ", did you mean `{suggestion}`?"),
+        None => Ok(()),
+    }
+}
+
+/// Checks the style JSON document and returns the list of found problems. Empty list means the style is valid.
+///
+/// If `source_layers` are given (e.g. from the `vector_layers` list of the TileJSON of the tile source), style rules
+/// are checked to reference only these layers.
+///
+/// Variables of [`StyleTemplate`] documents are substituted before the check.
+///
+/// ```
+/// use galileo::layer::vector_tile_layer::style::validation::validate_style;
+///
+/// let style = r##"{
+///     "rules": [{ "layer_name": "roads", "symbol": { "line": { "width": 1.0, "stroke_colour": "#FF0000" } } }],
+///     "default_symbol": {},
+///     "background": "#FFFFFF"
+/// }"##;
+/// let diagnostics = validate_style(style, Some(&["road", "water"]));
+/// for diagnostic in &diagnostics {
+///     println!("{diagnostic}");
+/// }
+/// // rules[0].layer_name: layer `roads` is not present in the tiles, did you mean `road`?
+/// // rules[0].symbol.line: unknown property `stroke_colour`, did you mean `stroke_color`?
+/// // missing field `stroke_color` at line 2 column ...
+/// assert_eq!(diagnostics.len(), 3);
+/// ```
+pub fn validate_style(json: &str, source_layers: Option<&[&str]>) -> Vec<StyleDiagnostic> {
+    let mut validator = Validator {
+        source_layers,
+        diagnostics: vec![],
+    };
+
+    let value = match serde_json::from_str::<Value>(json) {
+        Ok(value) => value,
+        Err(err) => {
+            validator.error(
+                "",
+                DiagnosticKind::InvalidStructure {
+                    message: err.to_string(),
+                },
+            );
+            return validator.diagnostics;
+        }
+    };
+
+    // Templates are checked after the variables are substituted
+    let has_variables = value.get("variables").is_some();
+    let value = if has_variables {
+        match StyleTemplate::from_value(value).and_then(|template| template.resolve()) {
+            Ok(value) => value,
+            Err(err) => {
+                validator.error(
+                    "",
+                    DiagnosticKind::InvalidStructure {
+                        message: err.to_string(),
+                    },
+                );
+                return validator.diagnostics;
+            }
+        }
+    } else {
+        value
+    };
+
+    validator.style(&value);
+
+    let result = if has_variables {
+        serde_json::from_value::<VectorTileStyle>(value).map(|_| ())
+    } else {
+        serde_json::from_str::<VectorTileStyle>(json).map(|_| ())
+    };
+    if let Err(err) = result {
+        validator.error(
+            "",
+            DiagnosticKind::InvalidStructure {
+                message: err.to_string(),
+            },
+        );
+    }
+
+    validator.diagnostics
+}
+
+struct Validator<'a> {
+    source_layers: Option<&'a [&'a str]>,
+    diagnostics: Vec<StyleDiagnostic>,
+}
+
+impl Validator<'_> {
+    fn error(&mut self, path: &str, kind: DiagnosticKind) {
+        self.push(Severity::Error, path, kind);
+    }
+
+    fn warning(&mut self, path: &str, kind: DiagnosticKind) {
+        self.push(Severity::Warning, path, kind);
+    }
+
+    fn push(&mut self, severity: Severity, path: &str, kind: DiagnosticKind) {
+        self.diagnostics.push(StyleDiagnostic {
+            severity,
+            path: path.to_string(),
+            kind,
+        });
+    }
+
+    /// Reports unknown properties of the object. Returns `None` if the value is not an object, leaving reporting of
+    /// type errors to the deserializer.
+    fn object<'v>(
+        &mut self,
+        value: &'v Value,
+        path: &str,
+        known: &[&str],
+    ) -> Option<&'v Map<String, Value>> {
+        let object = value.as_object()?;
+        for name in object.keys() {
+            if !known.contains(&name.as_str()) {
+                self.warning(
+                    path,
+                    DiagnosticKind::UnknownProperty {
+                        name: name.clone(),
+                        suggestion: suggest(name, known.iter().copied()),
+                    },
+                );
+            }
+        }
+
+        Some(object)
+    }
+
+    fn style(&mut self, value: &Value) {
+        let Some(style) = self.object(
+            value,
+            "",
+            &[
+                "rules",
+                "default_symbol",
+                "background",
+                "clipping",
+                "hidden_groups",
+            ],
+        ) else {
+            return;
+        };
+
+        if let Some(rules) = style.get("rules").and_then(|v| v.as_array()) {
+            for (index, rule) in rules.iter().enumerate() {
+                self.rule(rule, &format!("rules[{index}]"));
+            }
+        }
+
+        if let Some(default_symbol) = style.get("default_symbol") {
+            self.symbol(default_symbol, "default_symbol");
+        }
+
+        if let Some(background) = style.get("background") {
+            self.color(background, "background");
+        }
+
+        if let Some(clipping) = style.get("clipping") {
+            self.object(clipping, "clipping", &["buffer", "mask"]);
+        }
+    }
+
+    fn rule(&mut self, value: &Value, path: &str) {
+        let Some(rule) = self.object(
+            value,
+            path,
+            &[
+                "layer_name",
+                "properties",
+                "symbol",
+                "min_feature_size",
+                "group",
+            ],
+        ) else {
+            return;
+        };
+
+        if let (Some(layer_name), Some(source_layers)) = (
+            rule.get("layer_name").and_then(|v| v.as_str()),
+            self.source_layers,
+        ) {
+            if !source_layers.contains(&layer_name) {
+                self.warning(
+                    &format!("{path}.layer_name"),
+                    DiagnosticKind::UnknownSourceLayer {
+                        name: layer_name.to_string(),
+                        suggestion: suggest(layer_name, source_layers.iter().copied()),
+                    },
+                );
+            }
+        }
+
+        if let Some(symbol) = rule.get("symbol") {
+            self.symbol(symbol, &format!("{path}.symbol"));
+        }
+    }
+
+    fn symbol(&mut self, value: &Value, path: &str) {
+        let Some(symbol) = self.object(value, path, &["point", "line", "polygon", "label"]) else {
+            return;
+        };
+
+        // Symbols of the default symbol are optional
+        for (name, value) in symbol.iter().filter(|(_, value)| !value.is_null()) {
+            let path = format!("{path}.{name}");
+            match name.as_str() {
+                "point" => self.point_symbol(value, &path),
+                "line" => self.line_symbol(value, &path),
+                "polygon" => self.polygon_symbol(value, &path),
+                "label" => self.label_symbol(value, &path),
+                _ => {}
+            }
+        }
+    }
+
+    fn point_symbol(&mut self, value: &Value, path: &str) {
+        if let Some(symbol) = self.object(value, path, &["size", "color"]) {
+            self.color_property(symbol, path, "color");
+        }
+    }
+
+    fn line_symbol(&mut self, value: &Value, path: &str) {
+        if let Some(symbol) = self.object(value, path, VectorTileLineSymbol::PROPERTIES) {
+            self.color_property(symbol, path, "stroke_color");
+        }
+    }
+
+    fn polygon_symbol(&mut self, value: &Value, path: &str) {
+        let Some(symbol) = self.object(value, path, &["fill_color", "palette_color", "hatching"])
+        else {
+            return;
+        };
+
+        self.color_property(symbol, path, "fill_color");
+        if let Some(hatching) = symbol.get("hatching").filter(|v| !v.is_null()) {
+            let path = format!("{path}.hatching");
+            if let Some(hatching) =
+                self.object(hatching, &path, &["color", "width", "spacing", "angle"])
+            {
+                self.color_property(hatching, &path, "color");
+            }
+        }
+    }
+
+    fn label_symbol(&mut self, value: &Value, path: &str) {
+        let Some(symbol) = self.object(
+            value,
+            path,
+            &["pattern", "text_style", "priority", "background"],
+        ) else {
+            return;
+        };
+
+        if let Some(text_style) = symbol.get("text_style") {
+            let path = format!("{path}.text_style");
+            if let Some(text_style) = self.object(
+                text_style,
+                &path,
+                &[
+                    "font_name",
+                    "font_size",
+                    "font_color",
+                    "horizontal_alignment",
+                    "vertical_alignment",
+                ],
+            ) {
+                self.color_property(text_style, &path, "font_color");
+            }
+        }
+
+        if let Some(priority) = symbol.get("priority") {
+            self.object(
+                priority,
+                &format!("{path}.priority"),
+                &["value", "property", "property_factor"],
+            );
+        }
+
+        if let Some(background) = symbol.get("background").filter(|v| !v.is_null()) {
+            self.label_background(background, &format!("{path}.background"));
+        }
+    }
+
+    fn label_background(&mut self, value: &Value, path: &str) {
+        let Some(background) = self.object(value, path, &["rect", "nine_patch"]) else {
+            return;
+        };
+
+        for (name, value) in background {
+            let path = format!("{path}.{name}");
+            match name.as_str() {
+                "rect" => {
+                    if let Some(rect) = self.object(
+                        value,
+                        &path,
+                        &[
+                            "fill",
+                            "corner_radius",
+                            "stroke_color",
+                            "stroke_width",
+                            "padding",
+                        ],
+                    ) {
+                        self.color_property(rect, &path, "fill");
+                        if let Some(color) = rect.get("stroke_color").filter(|v| !v.is_null()) {
+                            self.color(color, &format!("{path}.stroke_color"));
+                        }
+                    }
+                }
+                "nine_patch" => {
+                    self.object(value, &path, &["image", "borders", "padding"]);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn color_property(&mut self, object: &Map<String, Value>, path: &str, name: &str) {
+        if let Some(value) = object.get(name) {
+            self.color(value, &format!("{path}.{name}"));
+        }
+    }
+
+    fn color(&mut self, value: &Value, path: &str) {
+        let is_valid = value
+            .as_str()
+            .is_some_and(|color| Color::try_from_hex(color).is_some());
+        if !is_valid {
+            let value = match value {
+                Value::String(value) => value.clone(),
+                other => other.to_string(),
+            };
+            self.warning(path, DiagnosticKind::InvalidColor { value });
+        }
+    }
+}
+
+/// Returns the candidate most similar to the given name, if it is similar enough to be a probable typo.
+fn suggest<'a>(name: &str, candidates: impl Iterator<Item = &'a str>) -> Option<String> {
+    let max_distance = (name.chars().count() / 3).max(1);
+    candidates
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate.to_string())
+}
+
+/// Levenshtein distance between the strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev_row: Vec<usize> = (0..=b.len()).collect();
+    let mut row = vec![0; b.len() + 1];
+
+    for (i, a_char) in a.chars().enumerate() {
+        row[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = prev_row[j] + usize::from(a_char != *b_char);
+            row[j + 1] = substitution.min(prev_row[j + 1] + 1).min(row[j] + 1);
+        }
+        std::mem::swap(&mut prev_row, &mut row);
+    }
+
+    prev_row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_style() {
+        let style = r##"{
+            "rules": [
+                {
+                    "layer_name": "water",
+                    "symbol": { "polygon": { "fill_color": "#0000FFFF" } }
+                },
+                {
+                    "layer_name": "place",
+                    "symbol": {
+                        "label": {
+                            "pattern": "{name}",
+                            "text_style": { "font_name": "Noto Sans", "font_size": 12.0 }
+                        }
+                    }
+                }
+            ],
+            "default_symbol": { "line": { "width": 1.0, "stroke_color": "#000000" } },
+            "background": "#FFFFFF"
+        }"##;
+
+        assert_eq!(validate_style(style, Some(&["water", "place"])), vec![]);
+    }
+
+    #[test]
+    fn template_variables() {
+        let style = r##"{
+            "variables": { "water": "#0000FFFF", "land": "green" },
+            "rules": [{ "layer_name": "water", "symbol": { "polygon": { "fill_color": "$water" } } }],
+            "default_symbol": {},
+            "background": "$land"
+        }"##;
+
+        let diagnostics = validate_style(style, None);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].path, "background");
+
+        let undefined = style.replace("$land", "$ground");
+        assert!(matches!(
+            validate_style(&undefined, None)[0].kind,
+            DiagnosticKind::InvalidStructure { .. }
+        ));
+    }
+
+    #[test]
+    fn line_width_scale() {
+        let style = r##"{
+            "rules": [],
+            "default_symbol": {
+                "line": {
+                    "width": 1.0,
+                    "stroke_color": "#000000",
+                    "width_scale": {
+                        "from_resolution": 100.0,
+                        "from_scale": 1.0,
+                        "to_resolution": 10.0,
+                        "to_scale": 4.0
+                    }
+                }
+            },
+            "background": "#FFFFFF"
+        }"##;
+
+        assert_eq!(validate_style(style, None), vec![]);
+    }
+
+    #[test]
+    fn diagnostics() {
+        let style = r##"{
+            "rules": [
+                {
+                    "layer_name": "watr",
+                    "symbol": { "polygon": { "fill_color": "blue", "opacity": 1.0 } }
+                }
+            ],
+            "default_symbol": {},
+            "background": "#FFFFFF",
+            "backgroud": "#000000"
+        }"##;
+
+        let diagnostics = validate_style(style, Some(&["water", "roads"]));
+        let expected = [
+            StyleDiagnostic {
+                severity: Severity::Warning,
+                path: "".into(),
+                kind: DiagnosticKind::UnknownProperty {
+                    name: "backgroud".into(),
+                    suggestion: Some("background".into()),
+                },
+            },
+            StyleDiagnostic {
+                severity: Severity::Warning,
+                path: "rules[0].layer_name".into(),
+                kind: DiagnosticKind::UnknownSourceLayer {
+                    name: "watr".into(),
+                    suggestion: Some("water".into()),
+                },
+            },
+            StyleDiagnostic {
+                severity: Severity::Warning,
+                path: "rules[0].symbol.polygon".into(),
+                kind: DiagnosticKind::UnknownProperty {
+                    name: "opacity".into(),
+                    suggestion: None,
+                },
+            },
+            StyleDiagnostic {
+                severity: Severity::Warning,
+                path: "rules[0].symbol.polygon.fill_color".into(),
+                kind: DiagnosticKind::InvalidColor {
+                    value: "blue".into(),
+                },
+            },
+        ];
+
+        assert_eq!(diagnostics.len(), expected.len());
+        for diagnostic in &expected {
+            assert!(
+                diagnostics.contains(diagnostic),
+                "{diagnostic} is not reported"
+            );
+        }
+
+        assert_eq!(
+            expected[1].to_string(),
+            "rules[0].layer_name: layer `watr` is not present in the tiles, did you mean `water`?"
+        );
+    }
+
+    #[test]
+    fn invalid_structure() {
+        let diagnostics = validate_style(r#"{"rules": []"#, None);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+
+        let diagnostics = validate_style(r#"{"rules": [], "default_symbol": {}}"#, None);
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0].to_string().contains("background"));
+    }
+
+    #[test]
+    fn edit_distances() {
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("color", "colour"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+}
@@ -0,0 +1,230 @@
+//! Clipping of the vector tile geometries by a rectangle.
+
+use galileo_mvt::Point;
+use galileo_types::cartesian::Rect;
+
+/// Cuts the line by the rectangle, returning the parts of the line inside it.
+pub(super) fn clip_line<'a>(
+    points: impl IntoIterator<Item = &'a Point>,
+    rect: &Rect<f32>,
+) -> Vec<Vec<Point>> {
+    let mut parts = vec![];
+    let mut current: Vec<Point> = vec![];
+    let mut iterator = points.into_iter();
+    let Some(mut prev) = iterator.next().copied() else {
+        return parts;
+    };
+
+    for &point in iterator {
+        match clip_segment(prev, point, rect) {
+            Some((from, to)) => {
+                if current.last() != Some(&from) {
+                    flush_part(&mut current, &mut parts);
+                    current.push(from);
+                }
+                current.push(to);
+            }
+            None => flush_part(&mut current, &mut parts),
+        }
+
+        prev = point;
+    }
+
+    flush_part(&mut current, &mut parts);
+    parts
+}
+
+fn flush_part(current: &mut Vec<Point>, parts: &mut Vec<Vec<Point>>) {
+    let part = std::mem::take(current);
+    if part.len() > 1 {
+        parts.push(part);
+    }
+}
+
+/// Liang-Barsky clipping of a single segment. End points of the segment that are inside the rectangle are returned
+/// unchanged, so that consequent segments of a line can be joined by comparing their end points.
+fn clip_segment(a: Point, b: Point, rect: &Rect<f32>) -> Option<(Point, Point)> {
+    let d = b - a;
+    let mut t0 = 0.0f32;
+    let mut t1 = 1.0f32;
+
+    for (p, q) in [
+        (-d.x, a.x - rect.x_min()),
+        (d.x, rect.x_max() - a.x),
+        (-d.y, a.y - rect.y_min()),
+        (d.y, rect.y_max() - a.y),
+    ] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+        } else {
+            let r = q / p;
+            if p < 0.0 {
+                if r > t1 {
+                    return None;
+                }
+                t0 = t0.max(r);
+            } else {
+                if r < t0 {
+                    return None;
+                }
+                t1 = t1.min(r);
+            }
+        }
+    }
+
+    let at = |t: f32| {
+        if t <= 0.0 {
+            a
+        } else if t >= 1.0 {
+            b
+        } else {
+            a + d * t
+        }
+    };
+
+    Some((at(t0), at(t1)))
+}
+
+/// Clips a closed contour by the rectangle using Sutherland-Hodgman algorithm. The result is a single contour, parts
+/// of which may go along the sides of the rectangle.
+///
+/// Returns an empty vector if the contour is outside of the rectangle.
+pub(super) fn clip_ring<'a>(
+    points: impl IntoIterator<Item = &'a Point>,
+    rect: &Rect<f32>,
+) -> Vec<Point> {
+    let mut output: Vec<Point> = points.into_iter().copied().collect();
+    for edge in [
+        Edge::XMin(rect.x_min()),
+        Edge::XMax(rect.x_max()),
+        Edge::YMin(rect.y_min()),
+        Edge::YMax(rect.y_max()),
+    ] {
+        let input = std::mem::take(&mut output);
+        let Some(mut prev) = input.last().copied() else {
+            break;
+        };
+
+        for &point in &input {
+            match (edge.is_inside(prev), edge.is_inside(point)) {
+                (true, true) => output.push(point),
+                (false, true) => {
+                    output.push(edge.intersection(prev, point));
+                    output.push(point);
+                }
+                (true, false) => output.push(edge.intersection(prev, point)),
+                (false, false) => {}
+            }
+
+            prev = point;
+        }
+    }
+
+    if output.len() < 3 {
+        output.clear();
+    }
+
+    output
+}
+
+#[derive(Clone, Copy)]
+enum Edge {
+    XMin(f32),
+    XMax(f32),
+    YMin(f32),
+    YMax(f32),
+}
+
+impl Edge {
+    fn is_inside(&self, point: Point) -> bool {
+        match *self {
+            Edge::XMin(v) => point.x >= v,
+            Edge::XMax(v) => point.x <= v,
+            Edge::YMin(v) => point.y >= v,
+            Edge::YMax(v) => point.y <= v,
+        }
+    }
+
+    fn intersection(&self, a: Point, b: Point) -> Point {
+        let t = match *self {
+            Edge::XMin(v) | Edge::XMax(v) => (v - a.x) / (b.x - a.x),
+            Edge::YMin(v) | Edge::YMax(v) => (v - a.y) / (b.y - a.y),
+        };
+
+        a + (b - a) * t
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect() -> Rect<f32> {
+        Rect::new(0.0, 0.0, 1.0, 1.0)
+    }
+
+    #[test]
+    fn line_crossing_the_rect() {
+        let points = [
+            Point::new(-1.0, 0.5),
+            Point::new(0.5, 0.5),
+            Point::new(0.5, 2.0),
+            Point::new(0.8, 2.0),
+            Point::new(0.8, 0.2),
+        ];
+
+        let parts = clip_line(&points, &rect());
+        assert_eq!(
+            parts,
+            vec![
+                vec![
+                    Point::new(0.0, 0.5),
+                    Point::new(0.5, 0.5),
+                    Point::new(0.5, 1.0)
+                ],
+                vec![Point::new(0.8, 1.0), Point::new(0.8, 0.2)],
+            ]
+        );
+    }
+
+    #[test]
+    fn line_outside_the_rect() {
+        let points = [Point::new(-1.0, -1.0), Point::new(2.0, -1.0)];
+        assert!(clip_line(&points, &rect()).is_empty());
+    }
+
+    #[test]
+    fn ring_clipping() {
+        // Square partially outside the rect
+        let square = [
+            Point::new(0.5, 0.5),
+            Point::new(0.5, 1.5),
+            Point::new(1.5, 1.5),
+            Point::new(1.5, 0.5),
+        ];
+        let clipped = clip_ring(&square, &rect());
+        assert_eq!(clipped.len(), 4);
+        assert!(clipped
+            .iter()
+            .all(|p| p.x >= 0.5 && p.x <= 1.0 && p.y >= 0.5 && p.y <= 1.0));
+
+        // Ring containing the whole rect is converted into the rect
+        let large = [
+            Point::new(-1.0, -1.0),
+            Point::new(-1.0, 2.0),
+            Point::new(2.0, 2.0),
+            Point::new(2.0, -1.0),
+        ];
+        let clipped = clip_ring(&large, &rect());
+        assert_eq!(clipped.len(), 4);
+
+        let outside = [
+            Point::new(2.0, 2.0),
+            Point::new(2.0, 3.0),
+            Point::new(3.0, 3.0),
+        ];
+        assert!(clip_ring(&outside, &rect()).is_empty());
+    }
+}
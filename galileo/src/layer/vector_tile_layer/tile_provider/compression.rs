@@ -0,0 +1,162 @@
+//! Decompression of the tile data.
+//!
+//! Tile servers and tile archives (e.g. MBTiles and PMTiles) often store vector tiles compressed. When the tiles are
+//! requested by a browser, the `Content-Encoding` header makes the browser decompress them, but native HTTP clients
+//! and tile archives return the compressed bytes as is. The loaders decompress such tiles before decoding them, so the
+//! same tile source works on every platform.
+//!
+//! Each compression format is supported behind its own feature: `gzip`, `zstd` and `brotli`.
+
+#[cfg(any(feature = "gzip", feature = "zstd", feature = "brotli"))]
+use std::io::Read;
+
+use bytes::Bytes;
+
+use crate::error::GalileoError;
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Compression format of tile data.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Compression {
+    /// Data is not compressed.
+    None,
+    /// Gzip compression. Requires `gzip` feature.
+    Gzip,
+    /// Zstandard compression. Requires `zstd` feature.
+    Zstd,
+    /// Brotli compression. Requires `brotli` feature.
+    ///
+    /// Brotli streams do not have a signature, so this compression is never detected from the data and must be set
+    /// explicitly.
+    Brotli,
+}
+
+impl Compression {
+    /// Detects compression of the data by its first bytes.
+    pub fn detect(data: &[u8]) -> Self {
+        if data.starts_with(&GZIP_MAGIC) {
+            Self::Gzip
+        } else if data.starts_with(&ZSTD_MAGIC) {
+            Self::Zstd
+        } else {
+            Self::None
+        }
+    }
+
+    /// Decompresses the data. Returns the data as is for [`Compression::None`].
+    pub fn decompress(&self, data: Bytes) -> Result<Bytes, GalileoError> {
+        match self {
+            Self::None => Ok(data),
+            Self::Gzip => decompress_gzip(&data),
+            Self::Zstd => decompress_zstd(&data),
+            Self::Brotli => decompress_brotli(&data),
+        }
+    }
+}
+
+/// Decompresses the data if it is compressed with gzip or zstd. Other data is returned as is.
+pub fn decompress(data: Bytes) -> Result<Bytes, GalileoError> {
+    Compression::detect(&data).decompress(data)
+}
+
+#[cfg(any(feature = "gzip", feature = "zstd", feature = "brotli"))]
+fn read_all(mut reader: impl Read, capacity: usize) -> Result<Bytes, GalileoError> {
+    let mut decompressed = Vec::with_capacity(capacity);
+    reader
+        .read_to_end(&mut decompressed)
+        .map_err(|err| GalileoError::Generic(format!("failed to decompress tile data: {err}")))?;
+
+    Ok(decompressed.into())
+}
+
+#[cfg(feature = "gzip")]
+fn decompress_gzip(data: &[u8]) -> Result<Bytes, GalileoError> {
+    read_all(flate2::read::MultiGzDecoder::new(data), data.len() * 4)
+}
+
+#[cfg(not(feature = "gzip"))]
+fn decompress_gzip(_data: &[u8]) -> Result<Bytes, GalileoError> {
+    Err(not_enabled("gzip"))
+}
+
+#[cfg(feature = "zstd")]
+fn decompress_zstd(mut data: &[u8]) -> Result<Bytes, GalileoError> {
+    let capacity = data.len() * 4;
+    let decoder = ruzstd::decoding::StreamingDecoder::new(&mut data)
+        .map_err(|err| GalileoError::Generic(format!("invalid zstd data: {err}")))?;
+    read_all(decoder, capacity)
+}
+
+#[cfg(not(feature = "zstd"))]
+fn decompress_zstd(_data: &[u8]) -> Result<Bytes, GalileoError> {
+    Err(not_enabled("zstd"))
+}
+
+#[cfg(feature = "brotli")]
+fn decompress_brotli(data: &[u8]) -> Result<Bytes, GalileoError> {
+    const BUFFER_SIZE: usize = 4096;
+    read_all(
+        brotli_decompressor::Decompressor::new(data, BUFFER_SIZE),
+        data.len() * 4,
+    )
+}
+
+#[cfg(not(feature = "brotli"))]
+fn decompress_brotli(_data: &[u8]) -> Result<Bytes, GalileoError> {
+    Err(not_enabled("brotli"))
+}
+
+#[cfg(not(all(feature = "gzip", feature = "zstd", feature = "brotli")))]
+fn not_enabled(format: &str) -> GalileoError {
+    GalileoError::Generic(format!(
+        "tile data is compressed with {format}, but `{format}` feature is not enabled"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_compression() {
+        assert_eq!(
+            Compression::detect(&[0x1f, 0x8b, 0x08, 0x00]),
+            Compression::Gzip
+        );
+        assert_eq!(
+            Compression::detect(&[0x28, 0xb5, 0x2f, 0xfd, 0x00]),
+            Compression::Zstd
+        );
+        // Start of an MVT tile: field 3 (layers), wire type 2
+        assert_eq!(Compression::detect(&[0x1a, 0x10]), Compression::None);
+        assert_eq!(Compression::detect(&[]), Compression::None);
+    }
+
+    #[test]
+    fn uncompressed_data_is_not_changed() {
+        let data = Bytes::from_static(&[0x1a, 0x02, 0x03, 0x04]);
+        assert_eq!(decompress(data.clone()).unwrap(), data);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn gzip_round_trip() {
+        use std::io::Write;
+
+        let data = b"vector tile data".repeat(10);
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&data).unwrap();
+        let compressed = Bytes::from(encoder.finish().unwrap());
+
+        assert_eq!(Compression::detect(&compressed), Compression::Gzip);
+        assert_eq!(decompress(compressed).unwrap(), Bytes::from(data));
+    }
+
+    #[cfg(not(feature = "gzip"))]
+    #[test]
+    fn gzip_without_feature_is_error() {
+        assert!(decompress(Bytes::from_static(&[0x1f, 0x8b, 0x08, 0x00])).is_err());
+    }
+}
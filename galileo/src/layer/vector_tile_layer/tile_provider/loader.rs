@@ -1,13 +1,19 @@
 //! Vector tile loader stuff.
 
+use std::collections::HashMap;
+
 use bytes::Bytes;
 use galileo_mvt::MvtTile;
 use maybe_sync::{MaybeSend, MaybeSync};
 
+use super::compression::Compression;
 use crate::error::GalileoError;
-use crate::layer::data_provider::{PersistentCacheController, UrlSource};
+use crate::layer::data_provider::{
+    PersistentCacheController, TileSourceStats, TileSourceStatsCollector, UrlSource,
+};
 use crate::platform::{PlatformService, PlatformServiceImpl};
 use crate::tile_scheme::TileIndex;
+use crate::trace::{instrument, span};
 
 /// Error that can occur when trying to load a vector tile.
 pub enum TileLoadError {
@@ -25,6 +31,11 @@ pub enum TileLoadError {
 pub trait VectorTileLoader: MaybeSend + MaybeSync {
     /// Load tile with the given index.
     async fn load(&self, index: TileIndex) -> Result<MvtTile, TileLoadError>;
+
+    /// Statistics of the requests made by the loader, if the loader collects them.
+    fn stats(&self) -> Option<TileSourceStats> {
+        None
+    }
 }
 
 /// Load the tile from the Web.
@@ -35,6 +46,8 @@ where
     platform_service: PlatformServiceImpl,
     cache: Cache,
     url_source: Box<dyn UrlSource<TileIndex>>,
+    stats: TileSourceStatsCollector,
+    compression: Option<Compression>,
 }
 
 impl<Cache> WebVtLoader<Cache>
@@ -51,23 +64,44 @@ where
             platform_service,
             cache,
             url_source: Box::new(url_source),
+            stats: TileSourceStatsCollector::new(),
+            compression: None,
         }
     }
 
+    /// Sets the compression of the tiles returned by the server.
+    ///
+    /// By default the compression is detected from the data of every tile, which works for gzip and zstd. Brotli
+    /// compressed tiles cannot be detected, so [`Compression::Brotli`] must be set explicitly for them.
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    /// Collector of the request statistics of the loader.
+    pub fn stats_collector(&self) -> &TileSourceStatsCollector {
+        &self.stats
+    }
+
     async fn load_raw(&self, url: &str) -> Result<Bytes, TileLoadError> {
         if let Some(data) = self.cache.get(url) {
             log::trace!("Cache hit for url {url}");
+            self.stats.record_cache_hit();
             return Ok(data);
         }
 
-        let bytes = self
-            .platform_service
-            .load_bytes_from_url(url)
-            .await
-            .map_err(|err| match err {
-                GalileoError::NotFound => TileLoadError::DoesNotExist,
-                _ => TileLoadError::Network,
-            })?;
+        let start = web_time::Instant::now();
+        let bytes = match self.platform_service.load_bytes_from_url(url).await {
+            Ok(bytes) => bytes,
+            Err(err) => {
+                self.stats.record_error(start.elapsed());
+                return Err(match err {
+                    GalileoError::NotFound => TileLoadError::DoesNotExist,
+                    _ => TileLoadError::Network,
+                });
+            }
+        };
+        self.stats.record_download(bytes.len(), start.elapsed());
 
         log::info!("Loaded tile from url: {url}");
 
@@ -89,14 +123,68 @@ where
         let url = (self.url_source)(&index);
 
         log::trace!("Loading tile {index:?} from url {url}");
-        let bytes = self.load_raw(&url).await?;
+        let bytes = instrument!(self.load_raw(&url), "vt_tile_load", index = ?index).await?;
 
         log::trace!("Tile {index:?} loaded. Byte size: {}", bytes.len());
 
+        span!("vt_tile_decode", index = ?index, size = bytes.len());
+        let compression = self
+            .compression
+            .unwrap_or_else(|| Compression::detect(&bytes));
+        let bytes = decompress(compression, bytes)?;
         let mvt = MvtTile::decode(bytes, false).map_err(|_| TileLoadError::Decoding)?;
 
         log::trace!("Tile {index:?} successfully decoded");
 
         Ok(mvt)
     }
+
+    fn stats(&self) -> Option<TileSourceStats> {
+        Some(self.stats.snapshot())
+    }
+}
+
+/// Loader that takes the tiles from memory instead of downloading them, e.g. to render tiles bundled with an
+/// application or test data without network access.
+///
+/// Tiles compressed with gzip or zstd are decompressed before decoding, see [`Compression`]. Tiles that are not in the
+/// loader are reported as not existing.
+#[derive(Debug, Clone, Default)]
+pub struct StaticVtLoader {
+    tiles: HashMap<TileIndex, Bytes>,
+}
+
+impl StaticVtLoader {
+    /// Creates a new loader with the given encoded MVT tiles.
+    pub fn new(tiles: HashMap<TileIndex, Bytes>) -> Self {
+        Self { tiles }
+    }
+
+    /// Adds an encoded MVT tile to the loader, replacing the previous tile with the same index.
+    pub fn insert(&mut self, index: TileIndex, tile: Bytes) {
+        self.tiles.insert(index, tile);
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+impl VectorTileLoader for StaticVtLoader {
+    async fn load(&self, index: TileIndex) -> Result<MvtTile, TileLoadError> {
+        let bytes = self
+            .tiles
+            .get(&index)
+            .ok_or(TileLoadError::DoesNotExist)?
+            .clone();
+
+        span!("vt_tile_decode", index = ?index, size = bytes.len());
+        let bytes = decompress(Compression::detect(&bytes), bytes)?;
+        MvtTile::decode(bytes, false).map_err(|_| TileLoadError::Decoding)
+    }
+}
+
+fn decompress(compression: Compression, bytes: Bytes) -> Result<Bytes, TileLoadError> {
+    compression.decompress(bytes).map_err(|err| {
+        log::warn!("Failed to decompress tile: {err}");
+        TileLoadError::Decoding
+    })
 }
@@ -0,0 +1,136 @@
+//! Feature counts of vector tiles used to debug vector tile styles.
+
+use galileo_mvt::MvtTile;
+
+use crate::layer::vector_tile_layer::style::VectorTileStyle;
+
+/// Feature counts of a vector tile and the number of features selected by each rule of a style.
+///
+/// When a style shows nothing in some area, the metrics tell whether the tile has no features there at all or the
+/// rules of the style do not select them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VtTileMetrics {
+    /// Feature counts of the source layers of the tile, in the order of the layers in the tile.
+    pub layers: Vec<VtLayerMetrics>,
+    /// Number of features selected by each rule of the style. The counts are in the same order as
+    /// [`VectorTileStyle::rules`].
+    pub rule_matches: Vec<usize>,
+}
+
+/// Feature counts of a single source layer of a vector tile.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VtLayerMetrics {
+    /// Name of the layer.
+    pub name: String,
+    /// Number of features in the layer.
+    pub feature_count: usize,
+    /// Number of features of the layer that are not selected by any rule of the style. These features are drawn with
+    /// the default symbol of the style, if it is set.
+    pub unmatched_count: usize,
+}
+
+impl VtTileMetrics {
+    /// Counts the features of the tile and the features selected by the rules of the style.
+    pub fn new(tile: &MvtTile, style: &VectorTileStyle) -> Self {
+        let mut rule_matches = vec![0; style.rules.len()];
+        let layers = tile
+            .layers
+            .iter()
+            .map(|layer| {
+                let mut unmatched_count = 0;
+                for feature in &layer.features {
+                    match style.get_style_rule_index(&layer.name, feature) {
+                        Some(index) => rule_matches[index] += 1,
+                        None => unmatched_count += 1,
+                    }
+                }
+
+                VtLayerMetrics {
+                    name: layer.name.clone(),
+                    feature_count: layer.features.len(),
+                    unmatched_count,
+                }
+            })
+            .collect();
+
+        Self {
+            layers,
+            rule_matches,
+        }
+    }
+
+    /// Total number of features in the tile.
+    pub fn feature_count(&self) -> usize {
+        self.layers.iter().map(|layer| layer.feature_count).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use galileo_mvt::{MvtFeature, MvtGeometry, MvtLayer, MvtValue};
+
+    use super::*;
+    use crate::layer::vector_tile_layer::style::StyleRule;
+
+    fn layer(name: &str, kinds: &[&str]) -> MvtLayer {
+        MvtLayer {
+            name: name.into(),
+            features: kinds
+                .iter()
+                .map(|kind| MvtFeature {
+                    id: None,
+                    properties: HashMap::from([(
+                        "kind".into(),
+                        MvtValue::String(kind.to_string()),
+                    )]),
+                    geometry: MvtGeometry::Point(vec![]),
+                })
+                .collect(),
+            properties: vec![],
+            size: 4096,
+        }
+    }
+
+    #[test]
+    fn counts_rule_matches() {
+        let tile = MvtTile {
+            layers: vec![
+                layer("road", &["primary", "primary", "service"]),
+                layer("water", &["lake"]),
+            ],
+        };
+        let style = VectorTileStyle {
+            rules: vec![
+                StyleRule {
+                    layer_name: Some("road".into()),
+                    properties: HashMap::from([("kind".into(), "primary".into())]),
+                    ..Default::default()
+                },
+                StyleRule {
+                    layer_name: Some("road".into()),
+                    ..Default::default()
+                },
+                StyleRule {
+                    layer_name: Some("building".into()),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        let metrics = VtTileMetrics::new(&tile, &style);
+        assert_eq!(metrics.feature_count(), 4);
+        assert_eq!(metrics.rule_matches, vec![2, 1, 0]);
+        assert_eq!(
+            metrics.layers[1],
+            VtLayerMetrics {
+                name: "water".into(),
+                feature_count: 1,
+                unmatched_count: 1,
+            }
+        );
+        assert_eq!(metrics.layers[0].unmatched_count, 0);
+    }
+}
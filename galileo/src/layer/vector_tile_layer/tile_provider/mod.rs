@@ -8,16 +8,22 @@ use loader::VectorTileLoader;
 use parking_lot::RwLock;
 use processor::VectorTileProcessor;
 
+use crate::async_runtime::CancellationToken;
+use crate::layer::data_provider::TileSourceStats;
 use crate::layer::vector_tile_layer::style::VectorTileStyle;
 use crate::messenger::Messenger;
 use crate::render::{Canvas, PackedBundle};
 use crate::tile_scheme::TileIndex;
 
+mod clipping;
+pub mod compression;
 pub mod loader;
+mod metrics;
 pub mod processor;
 mod tile_store;
 mod vt_processor;
 
+pub use metrics::{VtLayerMetrics, VtTileMetrics};
 pub use vt_processor::{VectorTileDecodeContext, VtProcessor};
 
 use crate::layer::vector_tile_layer::tile_provider::tile_store::{
@@ -36,11 +42,15 @@ impl VtStyleId {
 }
 
 /// Provider of vector tiles for a vector tile layer.
+///
+/// Tile loading tasks started by a provider are aborted when the provider is dropped. Clones of
+/// the provider share the tile store, but each clone owns the tasks it started.
 pub struct VectorTileProvider {
     tiles: Arc<RwLock<TileStore>>,
     loader: Arc<dyn VectorTileLoader>,
     processor: Arc<dyn VectorTileProcessor>,
     messenger: Option<Arc<dyn Messenger>>,
+    cancellation: CancellationToken,
 }
 
 impl Clone for VectorTileProvider {
@@ -50,10 +60,17 @@ impl Clone for VectorTileProvider {
             loader: self.loader.clone(),
             processor: self.processor.clone(),
             messenger: self.messenger.clone(),
+            cancellation: CancellationToken::new(),
         }
     }
 }
 
+impl Drop for VectorTileProvider {
+    fn drop(&mut self) {
+        self.cancellation.cancel();
+    }
+}
+
 impl VectorTileProvider {
     /// Create a new instance of the provider.
     pub fn new(loader: Arc<dyn VectorTileLoader>, processor: Arc<dyn VectorTileProcessor>) -> Self {
@@ -62,6 +79,7 @@ impl VectorTileProvider {
             loader,
             processor,
             messenger: None,
+            cancellation: CancellationToken::new(),
         }
     }
 
@@ -83,6 +101,18 @@ impl VectorTileProvider {
         self.processor.drop_style(style_id);
     }
 
+    /// Reuses the tiles prepared with the `from` style for the `to` style, if `reuse` returns true for the data of the
+    /// tile. Other tiles are prepared with the `to` style when they are requested. Returns the indices of the reused
+    /// tiles.
+    pub fn reuse_tiles(
+        &self,
+        from: VtStyleId,
+        to: VtStyleId,
+        reuse: impl Fn(&MvtTile) -> bool,
+    ) -> Vec<TileIndex> {
+        self.tiles.write().copy_tiles(from, to, reuse)
+    }
+
     /// Load and pre-render the tile with given index using given style.
     ///
     /// A style with given id must first be registered in the provider.
@@ -103,7 +133,7 @@ impl VectorTileProvider {
         let data_provider = self.loader.clone();
         let messenger = self.messenger.clone();
 
-        crate::async_runtime::spawn(async move {
+        crate::async_runtime::spawn_cancellable(&self.cancellation, async move {
             let cell = {
                 let mut store = tile_store.write();
                 if store.contains(index, style_id) {
@@ -112,6 +142,12 @@ impl VectorTileProvider {
 
                 store.start_loading_tile(index, style_id)
             };
+            let mut guard = LoadingGuard {
+                tiles: tile_store.clone(),
+                index,
+                style_id,
+                finished: false,
+            };
 
             let tile_state = cell
                 .get_or_init(|| async { Self::download(index, data_provider).await })
@@ -126,6 +162,7 @@ impl VectorTileProvider {
             tile_store
                 .write()
                 .store_tile(index, style_id, cell, tile_state);
+            guard.finished = true;
 
             if let Some(messenger) = messenger {
                 messenger.request_redraw();
@@ -152,6 +189,11 @@ impl VectorTileProvider {
         }
     }
 
+    /// Drops all the tiles packed into GPU memory. The tiles are loaded and packed again when requested.
+    pub fn clear_packed_tiles(&self) {
+        self.tiles.write().remove_packed();
+    }
+
     /// Return render bundle for given tile.
     ///
     /// The tile must be packed before calling this method.
@@ -159,11 +201,31 @@ impl VectorTileProvider {
         self.tiles.read().get_packed(index, style_id)
     }
 
+    /// Returns true if loading and pre-rendering of the tile with the given style is finished, successfully or not.
+    pub fn is_tile_loaded(&self, index: TileIndex, style_id: VtStyleId) -> bool {
+        self.tiles.read().is_loaded(index, style_id)
+    }
+
     /// Returns raw tile data for the given index.
     pub fn get_mvt_tile(&self, index: TileIndex) -> Option<Arc<MvtTile>> {
         self.tiles.read().get_mvt_tile(index)
     }
 
+    /// Feature counts of the loaded tile and the number of its features selected by each rule of the style.
+    ///
+    /// Returns `None` if the tile is not loaded or the style is not registered. The features are counted before the
+    /// [feature transform](processor::VtFeatureTransform) of the processor is applied.
+    pub fn tile_metrics(&self, index: TileIndex, style_id: VtStyleId) -> Option<VtTileMetrics> {
+        let tile = self.get_mvt_tile(index)?;
+        let style = self.get_style(style_id)?;
+        Some(VtTileMetrics::new(&tile, &style))
+    }
+
+    /// Statistics of the requests made by the tile loader, if the loader collects them.
+    pub fn source_stats(&self) -> Option<TileSourceStats> {
+        self.loader.stats()
+    }
+
     /// Set messenger to use to notify about tile updates.
     pub fn set_messenger(&mut self, messenger: Box<dyn Messenger>) {
         self.messenger = Some(messenger.into());
@@ -205,6 +267,23 @@ impl VectorTileProvider {
     }
 }
 
+/// Removes the loading placeholder from the tile store if the loading task is aborted before the
+/// tile is stored.
+struct LoadingGuard {
+    tiles: Arc<RwLock<TileStore>>,
+    index: TileIndex,
+    style_id: VtStyleId,
+    finished: bool,
+}
+
+impl Drop for LoadingGuard {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.tiles.write().abort_loading(self.index, self.style_id);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
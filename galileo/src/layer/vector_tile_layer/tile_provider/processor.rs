@@ -2,7 +2,7 @@
 
 use std::sync::Arc;
 
-use galileo_mvt::MvtTile;
+use galileo_mvt::{MvtFeature, MvtTile};
 use maybe_sync::{MaybeSend, MaybeSync};
 use serde::{Deserialize, Serialize};
 
@@ -46,3 +46,102 @@ pub trait VectorTileProcessor: MaybeSend + MaybeSync {
         style_id: VtStyleId,
     ) -> Result<RenderBundle, TileProcessingError>;
 }
+
+/// User hook that modifies the features of vector tiles before they are rendered, e.g. to drop tiny rings, merge
+/// line segments or add computed properties used by the style rules.
+///
+/// The transform is applied by the processor to a copy of the tile, so it only affects rendering. Features returned
+/// by [`VectorTileProvider::get_mvt_tile`](super::VectorTileProvider::get_mvt_tile) are not modified.
+///
+/// The trait is implemented for closures:
+///
+/// ```
+/// use galileo::layer::vector_tile_layer::tile_provider::processor::VtFeatureTransform;
+/// use galileo_mvt::{MvtFeature, MvtValue};
+/// use galileo::tile_scheme::TileIndex;
+///
+/// let transform = |_index: TileIndex, layer_name: &str, feature: &mut MvtFeature| {
+///     if layer_name == "poi" {
+///         feature.properties.insert("visible".into(), MvtValue::Bool(true));
+///     }
+///     layer_name != "housenumber"
+/// };
+///
+/// fn assert_transform(_: impl VtFeatureTransform) {}
+/// assert_transform(transform);
+/// ```
+pub trait VtFeatureTransform: MaybeSend + MaybeSync {
+    /// Modifies the feature of the layer `layer_name` of the tile `index` in place. Returns `false` if the feature
+    /// must be dropped from the tile.
+    fn transform(&self, index: TileIndex, layer_name: &str, feature: &mut MvtFeature) -> bool;
+}
+
+impl<T> VtFeatureTransform for T
+where
+    T: Fn(TileIndex, &str, &mut MvtFeature) -> bool + MaybeSend + MaybeSync,
+{
+    fn transform(&self, index: TileIndex, layer_name: &str, feature: &mut MvtFeature) -> bool {
+        self(index, layer_name, feature)
+    }
+}
+
+/// Returns a copy of the tile with the transform applied to all its features.
+pub(crate) fn transform_tile(
+    tile: &MvtTile,
+    index: TileIndex,
+    transform: &dyn VtFeatureTransform,
+) -> MvtTile {
+    let mut tile = tile.clone();
+    for layer in &mut tile.layers {
+        let layer_name = &layer.name;
+        layer
+            .features
+            .retain_mut(|feature| transform.transform(index, layer_name, feature));
+    }
+
+    tile
+}
+
+#[cfg(test)]
+mod tests {
+    use galileo_mvt::{MvtGeometry, MvtLayer, MvtValue};
+
+    use super::*;
+
+    fn feature(id: u64) -> MvtFeature {
+        MvtFeature {
+            id: Some(id),
+            properties: Default::default(),
+            geometry: MvtGeometry::Point(vec![]),
+        }
+    }
+
+    #[test]
+    fn transform_tile_features() {
+        let tile = MvtTile {
+            layers: vec![MvtLayer {
+                name: "poi".into(),
+                features: vec![feature(1), feature(2), feature(3)],
+                properties: vec![],
+                size: 4096,
+            }],
+        };
+
+        let transform = |_: TileIndex, layer_name: &str, feature: &mut MvtFeature| {
+            feature
+                .properties
+                .insert("layer".into(), MvtValue::String(layer_name.into()));
+            feature.id != Some(2)
+        };
+        let transformed = transform_tile(&tile, TileIndex::new(0, 0, 0), &transform);
+
+        let features = &transformed.layers[0].features;
+        assert_eq!(features.len(), 2);
+        assert_eq!(features[1].id, Some(3));
+        assert!(matches!(
+            features[0].properties.get("layer"),
+            Some(MvtValue::String(name)) if name == "poi"
+        ));
+        assert_eq!(tile.layers[0].features.len(), 3);
+    }
+}
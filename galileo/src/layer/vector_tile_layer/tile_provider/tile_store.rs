@@ -126,6 +126,13 @@ impl TileStore {
         self.processed.peek(&(tile_index, style_id)).is_some()
     }
 
+    /// Returns true if the tile is in the store and its loading has finished, successfully or not.
+    pub fn is_loaded(&self, tile_index: TileIndex, style_id: VtStyleId) -> bool {
+        self.processed
+            .peek(&(tile_index, style_id))
+            .is_some_and(|entry| !matches!(entry.prepared_tile, PreparedTileState::Loading))
+    }
+
     pub fn start_loading_tile(
         &mut self,
         index: TileIndex,
@@ -148,6 +155,17 @@ impl TileStore {
         tile_cell
     }
 
+    /// Removes the placeholder of a tile which loading was aborted, so that it can be requested again.
+    pub fn abort_loading(&mut self, index: TileIndex, style_id: VtStyleId) {
+        let is_loading = self
+            .processed
+            .peek(&(index, style_id))
+            .is_some_and(|entry| matches!(entry.prepared_tile, PreparedTileState::Loading));
+        if is_loading {
+            self.processed.remove(&(index, style_id));
+        }
+    }
+
     pub fn store_tile(
         &mut self,
         tile_index: TileIndex,
@@ -163,6 +181,46 @@ impl TileStore {
         self.insert_entry(tile_index, style_id, entry);
     }
 
+    /// Stores the prepared tiles of the `from` style also for the `to` style, if `reuse` returns true for the data
+    /// of the tile. Returns the indices of the copied tiles.
+    pub fn copy_tiles(
+        &mut self,
+        from: VtStyleId,
+        to: VtStyleId,
+        reuse: impl Fn(&MvtTile) -> bool,
+    ) -> Vec<TileIndex> {
+        let to_copy: Vec<_> = self
+            .processed
+            .iter()
+            .filter(|((_, style_id), entry)| {
+                *style_id == from
+                    && !matches!(entry.prepared_tile, PreparedTileState::Loading)
+                    && match entry.mvt_tile.get() {
+                        Some(MvtTileState::Loaded(tile)) => reuse(tile),
+                        Some(MvtTileState::Error()) => true,
+                        None => false,
+                    }
+            })
+            .map(|((index, _), entry)| {
+                (
+                    *index,
+                    TileStoreEntry {
+                        mvt_tile: entry.mvt_tile.clone(),
+                        prepared_tile: entry.prepared_tile.clone(),
+                    },
+                )
+            })
+            .collect();
+
+        to_copy
+            .into_iter()
+            .map(|(index, entry)| {
+                self.insert_entry(index, to, entry);
+                index
+            })
+            .collect()
+    }
+
     pub fn get_prepared(
         &self,
         index: TileIndex,
@@ -191,6 +249,20 @@ impl TileStore {
         })
     }
 
+    /// Removes all the packed tiles, so that they are loaded and packed again when requested.
+    pub fn remove_packed(&mut self) {
+        let packed: Vec<_> = self
+            .processed
+            .iter()
+            .filter(|(_, entry)| matches!(entry.prepared_tile, PreparedTileState::Packed(_)))
+            .map(|(key, _)| *key)
+            .collect();
+
+        for key in packed {
+            self.processed.remove(&key);
+        }
+    }
+
     pub fn get_mvt_tile(&self, index: TileIndex) -> Option<Arc<MvtTile>> {
         match self
             .mvt_tiles
@@ -256,6 +328,60 @@ mod tests {
         );
     }
 
+    #[test]
+    fn abort_loading_removes_only_loading_tiles() {
+        let mut store = TileStore::with_capacity(1_000_000);
+        let index = TileIndex::new(0, 0, 0);
+        let loading_style = VtStyleId::next_id();
+        let loaded_style = VtStyleId::next_id();
+
+        store.start_loading_tile(index, loading_style);
+        let cell = store.start_loading_tile(index, loaded_style);
+        store.store_tile(index, loaded_style, cell, tile_with_size(100));
+
+        store.abort_loading(index, loading_style);
+        store.abort_loading(index, loaded_style);
+
+        assert!(!store.contains(index, loading_style));
+        assert!(store.contains(index, loaded_style));
+    }
+
+    #[test]
+    fn copy_tiles_to_another_style() {
+        let mut store = TileStore::with_capacity(1_000_000);
+        let from = VtStyleId::next_id();
+        let to = VtStyleId::next_id();
+
+        let loaded_cell = |layer_name: &str| {
+            let tile = MvtTile {
+                layers: vec![galileo_mvt::MvtLayer {
+                    name: layer_name.into(),
+                    features: vec![],
+                    properties: vec![],
+                    size: 4096,
+                }],
+            };
+            Arc::new(OnceCell::new_with(Some(MvtTileState::Loaded(Arc::new(
+                tile,
+            )))))
+        };
+
+        let reused = TileIndex::new(0, 0, 1);
+        store.store_tile(reused, from, loaded_cell("water"), tile_with_size(100));
+        let rejected = TileIndex::new(1, 0, 1);
+        store.store_tile(rejected, from, loaded_cell("poi"), tile_with_size(100));
+        let loading = TileIndex::new(0, 1, 1);
+        store.start_loading_tile(loading, from);
+
+        let copied = store.copy_tiles(from, to, |tile| tile.layers[0].name != "poi");
+
+        assert_eq!(copied, vec![reused]);
+        assert!(store.get_prepared(reused, to).is_some());
+        assert!(!store.contains(rejected, to));
+        assert!(!store.contains(loading, to));
+        assert!(store.contains(rejected, from));
+    }
+
     #[test]
     fn evicts_old_tiles() {
         const CAPACITY: u64 = 1_000_000;
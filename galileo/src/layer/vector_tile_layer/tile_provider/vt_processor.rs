@@ -1,11 +1,12 @@
 use bytes::Bytes;
-use galileo_mvt::{MvtFeature, MvtGeometry, MvtTile};
+use galileo_mvt::{MvtFeature, MvtGeometry, MvtTile, Point as MvtPoint};
 use galileo_types::cartesian::{CartesianPoint2d, Point3d, Rect};
 use galileo_types::impls::{ClosedContour, Polygon};
 use galileo_types::Contour;
 use num_traits::ToPrimitive;
 use strfmt::strfmt;
 
+use super::clipping::{clip_line, clip_ring};
 use crate::error::GalileoError;
 use crate::layer::data_provider::DataProcessor;
 use crate::layer::vector_tile_layer::style::{VectorTileLabelSymbol, VectorTileStyle};
@@ -13,6 +14,7 @@ use crate::render::point_paint::PointPaint;
 use crate::render::render_bundle::{RenderBundle, RenderPrimitive};
 use crate::render::{LinePaint, PolygonPaint};
 use crate::tile_scheme::TileIndex;
+use crate::trace::span;
 use crate::TileSchema;
 
 /// Data processor that decodes vector tiles.
@@ -71,6 +73,8 @@ impl VtProcessor {
         style: &VectorTileStyle,
         tile_scheme: &TileSchema,
     ) -> Result<(), GalileoError> {
+        span!("vt_tile_tessellate", index = ?index);
+
         let bbox = tile_scheme
             .tile_bbox(index)
             .ok_or_else(|| GalileoError::Generic("cannot get tile bbox".into()))?;
@@ -78,6 +82,7 @@ impl VtProcessor {
             GalileoError::Generic(format!("cannot get lod resolution for lod {}", index.z))
         })?;
         let tile_resolution = lod_resolution * tile_scheme.tile_width() as f64;
+        let tile_size = tile_scheme.tile_width() as f64;
 
         let bounds = Polygon::new(
             ClosedContour::new(vec![
@@ -88,29 +93,80 @@ impl VtProcessor {
             ]),
             vec![],
         );
-        bundle.clip_area(&bounds);
+        if style.clipping.mask {
+            bundle.clip_area(&bounds);
+        }
+
+        // Points in the tile are normalized to `[0.0, 1.0]` range
+        let clip_rect = style
+            .clipping
+            .buffer
+            .map(|buffer| Rect::new(-buffer, -buffer, 1.0 + buffer, 1.0 + buffer));
+
+        // Labels are added after all other features, so that the labels with higher priority are drawn on top
+        let mut labels = vec![];
 
         for layer in mvt_tile.layers.iter().rev() {
             for feature in &layer.features {
+                if style
+                    .get_style_rule(&layer.name, feature)
+                    .is_some_and(|rule| !style.is_rule_visible(rule))
+                {
+                    continue;
+                }
+
                 match &feature.geometry {
                     MvtGeometry::Point(points) => {
-                        let Some(paint) = Self::get_point_symbol(style, &layer.name, feature)
+                        let Some((paint, label_priority)) =
+                            Self::get_point_symbol(style, &layer.name, feature)
                         else {
                             continue;
                         };
 
                         for point in points {
-                            bundle.add(RenderPrimitive::<_, _, galileo_types::impls::Contour<_>, Polygon<_>>::new_point_ref(&Self::transform_point(point, bbox, tile_resolution), &paint), lod_resolution);
+                            if clip_rect.is_some_and(|rect| !rect.contains(point)) {
+                                continue;
+                            }
+
+                            let position = Self::transform_point(point, bbox, tile_resolution);
+                            match label_priority {
+                                Some(priority) => labels.push((priority, position, paint.clone())),
+                                None => {
+                                    bundle.add(
+                                        RenderPrimitive::<
+                                            _,
+                                            _,
+                                            galileo_types::impls::Contour<_>,
+                                            Polygon<_>,
+                                        >::new_point_ref(
+                                            &position, &paint
+                                        ),
+                                        lod_resolution,
+                                    );
+                                }
+                            }
                         }
                     }
                     MvtGeometry::LineString(contours) => {
                         if let Some(paint) = Self::get_line_symbol(style, &layer.name, feature) {
-                            for contour in contours {
+                            if Self::is_below_min_size(
+                                style,
+                                &layer.name,
+                                feature,
+                                contours.iter().flat_map(|contour| contour.iter_points()),
+                                tile_size,
+                            ) {
+                                continue;
+                            }
+
+                            for part in contours
+                                .iter()
+                                .flat_map(|contour| Self::clip_contour(contour, clip_rect))
+                            {
                                 bundle.add(
                                     RenderPrimitive::<_, _, _, Polygon<_>>::new_contour_ref(
                                         &galileo_types::impls::Contour::new(
-                                            contour
-                                                .iter_points()
+                                            part.iter()
                                                 .map(|p| {
                                                     Self::transform_point(p, bbox, tile_resolution)
                                                 })
@@ -126,7 +182,32 @@ impl VtProcessor {
                     }
                     MvtGeometry::Polygon(polygons) => {
                         if let Some(paint) = Self::get_polygon_symbol(style, &layer.name, feature) {
+                            if Self::is_below_min_size(
+                                style,
+                                &layer.name,
+                                feature,
+                                polygons
+                                    .iter()
+                                    .flat_map(|polygon| polygon.outer_contour.iter_points()),
+                                tile_size,
+                            ) {
+                                continue;
+                            }
+
                             for polygon in polygons {
+                                let clipped;
+                                let polygon = match clip_rect {
+                                    Some(rect) => {
+                                        let Some(polygon) = Self::clip_polygon(polygon, &rect)
+                                        else {
+                                            continue;
+                                        };
+                                        clipped = polygon;
+                                        &clipped
+                                    }
+                                    None => polygon,
+                                };
+
                                 bundle.add(
                                     RenderPrimitive::<_, _, galileo_types::impls::Contour<_>, _>::new_polygon_ref(
                                         &polygon.cast_points(|p| {
@@ -143,21 +224,32 @@ impl VtProcessor {
             }
         }
 
+        labels.sort_by(|(a, ..), (b, ..)| a.total_cmp(b));
+        for (_, position, paint) in &labels {
+            bundle.add(
+                RenderPrimitive::<_, _, galileo_types::impls::Contour<_>, Polygon<_>>::new_point_ref(
+                    position, paint,
+                ),
+                lod_resolution,
+            );
+        }
+
         Ok(())
     }
 
+    /// Returns the paint for the point feature and, if the feature is drawn as a label, the priority of the label.
     fn get_point_symbol<'a>(
         style: &'a VectorTileStyle,
         layer_name: &str,
         feature: &MvtFeature,
-    ) -> Option<PointPaint<'a>> {
+    ) -> Option<(PointPaint<'a>, Option<f64>)> {
         style
             .get_style_rule(layer_name, feature)
             .and_then(|rule| {
                 rule.symbol
                     .point()
                     .copied()
-                    .map(|symbol| symbol.into())
+                    .map(|symbol| (symbol.into(), None))
                     .or_else(|| {
                         rule.symbol
                             .label()
@@ -168,7 +260,7 @@ impl VtProcessor {
                 style
                     .default_symbol
                     .point
-                    .map(|symbol| symbol.into())
+                    .map(|symbol| (symbol.into(), None))
                     .or_else(|| {
                         style
                             .default_symbol
@@ -182,12 +274,14 @@ impl VtProcessor {
     fn format_label<'a>(
         label_symbol: &VectorTileLabelSymbol,
         feature: &MvtFeature,
-    ) -> Option<PointPaint<'a>> {
+    ) -> Option<(PointPaint<'a>, Option<f64>)> {
         let text = strfmt(&label_symbol.pattern, &feature.properties).ok()?;
-        Some(PointPaint::label_owned(
-            text,
-            label_symbol.text_style.clone(),
-        ))
+        let mut paint = PointPaint::label_owned(text, label_symbol.text_style.clone());
+        if let Some(background) = &label_symbol.background {
+            paint = paint.with_background(background.clone());
+        }
+
+        Some((paint, Some(label_symbol.priority.get(feature))))
     }
 
     fn get_line_symbol(
@@ -214,6 +308,61 @@ impl VtProcessor {
             .map(|symbol| symbol.into())
     }
 
+    /// Returns true if the feature with the given points is smaller on the screen than the minimum feature size set
+    /// by the style rule of the feature. Points are given in the normalized tile coordinates, and `tile_size` is the
+    /// size of the tile in pixels.
+    fn is_below_min_size<'a>(
+        style: &VectorTileStyle,
+        layer_name: &str,
+        feature: &MvtFeature,
+        points: impl IntoIterator<Item = &'a MvtPoint>,
+        tile_size: f64,
+    ) -> bool {
+        let Some(min_size) = style
+            .get_style_rule(layer_name, feature)
+            .and_then(|rule| rule.min_feature_size)
+        else {
+            return false;
+        };
+
+        let Some(extent) = Rect::from_points(points) else {
+            return true;
+        };
+
+        let size = extent.width().max(extent.height()) as f64 * tile_size;
+        size < min_size as f64
+    }
+
+    fn clip_contour(
+        contour: &galileo_types::impls::Contour<MvtPoint>,
+        clip_rect: Option<Rect<f32>>,
+    ) -> Vec<Vec<MvtPoint>> {
+        match clip_rect {
+            Some(rect) => clip_line(contour.iter_points_closing(), &rect),
+            None => vec![contour.iter_points_closing().copied().collect()],
+        }
+    }
+
+    fn clip_polygon(polygon: &Polygon<MvtPoint>, rect: &Rect<f32>) -> Option<Polygon<MvtPoint>> {
+        let outer_contour = clip_ring(polygon.outer_contour.iter_points(), rect);
+        if outer_contour.is_empty() {
+            return None;
+        }
+
+        let inner_contours = polygon
+            .inner_contours
+            .iter()
+            .map(|contour| clip_ring(contour.iter_points(), rect))
+            .filter(|points| !points.is_empty())
+            .map(ClosedContour::new)
+            .collect();
+
+        Some(Polygon::new(
+            ClosedContour::new(outer_contour),
+            inner_contours,
+        ))
+    }
+
     fn transform_point<Num: num_traits::Float + ToPrimitive>(
         p_in: &impl CartesianPoint2d<Num = Num>,
         tile_bbox: Rect,
@@ -224,3 +373,46 @@ impl VtProcessor {
         Point3d::new(x, y, 0.0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::layer::vector_tile_layer::style::{StyleRule, VectorTileSymbol};
+
+    #[test]
+    fn min_feature_size() {
+        let style = VectorTileStyle {
+            rules: vec![StyleRule {
+                layer_name: Some("building".into()),
+                symbol: VectorTileSymbol::None,
+                min_feature_size: Some(2.0),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let feature = MvtFeature {
+            id: None,
+            properties: HashMap::new(),
+            geometry: MvtGeometry::Point(vec![]),
+        };
+
+        // 1 pixel building in a 256px tile
+        let small = [
+            MvtPoint::new(0.5, 0.5),
+            MvtPoint::new(0.5 + 1.0 / 256.0, 0.5),
+        ];
+        assert!(VtProcessor::is_below_min_size(
+            &style, "building", &feature, &small, 256.0
+        ));
+        // Same building is 2 pixels wide in a 512px tile
+        assert!(!VtProcessor::is_below_min_size(
+            &style, "building", &feature, &small, 512.0
+        ));
+        // No limit is set for other layers
+        assert!(!VtProcessor::is_below_min_size(
+            &style, "road", &feature, &small, 256.0
+        ));
+    }
+}
@@ -0,0 +1,460 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use galileo_types::cartesian::{Rect, Size};
+use galileo_types::geo::Crs;
+use parking_lot::Mutex;
+use web_time::Duration;
+
+use crate::async_runtime::CancellationToken;
+use crate::decoded_image::DecodedImage;
+use crate::layer::Layer;
+use crate::messenger::Messenger;
+use crate::platform::{PlatformService, PlatformServiceImpl};
+use crate::render::{BlendMode, Canvas, ImagePaint, PackedBundle, RenderOptions};
+use crate::view::MapView;
+
+const DEFAULT_REQUEST_DELAY: Duration = Duration::from_millis(200);
+const DEFAULT_MAX_IMAGE_SIZE: u32 = 4096;
+
+/// Version of the WMS protocol used by [`WmsLayer`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum WmsVersion {
+    /// Version 1.1.1. The CRS of the request is set with the `SRS` parameter.
+    V1_1_1,
+    /// Version 1.3.0. The CRS of the request is set with the `CRS` parameter.
+    #[default]
+    V1_3_0,
+}
+
+impl WmsVersion {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::V1_1_1 => "1.1.1",
+            Self::V1_3_0 => "1.3.0",
+        }
+    }
+
+    fn crs_parameter(&self) -> &'static str {
+        match self {
+            Self::V1_1_1 => "SRS",
+            Self::V1_3_0 => "CRS",
+        }
+    }
+}
+
+/// Layer that draws map images requested from an [OGC WMS](https://www.ogc.org/standard/wms/) server.
+///
+/// Unlike [`RasterTileLayer`](super::RasterTileLayer), this layer does not use a tile pyramid. Every time the view
+/// changes, it requests a single image covering the whole view with a `GetMap` request. The previous image stays on
+/// the screen (moved and scaled together with the map) until the new one is loaded. To not flood the server with
+/// requests while the map is moved, an image is requested only after the view stays the same for
+/// [a short delay](WmsLayer::with_request_delay), and the requests for the previous views are cancelled.
+///
+/// Images are requested in the CRS of the layer (Web Mercator by default, see [`WmsLayer::with_crs`]). The layer is
+/// not drawn if the CRS of the map view is different. Note that WMS 1.3.0 uses the axis order defined by the CRS,
+/// so for the CRSs with northing as the first axis the 1.1.1 version should be used.
+///
+/// ```no_run
+/// use galileo::layer::WmsLayer;
+///
+/// let layer = WmsLayer::new("https://ows.terrestris.de/osm/service", ["OSM-WMS"])
+///     .with_format("image/jpeg")
+///     .with_transparent(false);
+/// ```
+pub struct WmsLayer {
+    url: String,
+    layers: Vec<String>,
+    styles: Vec<String>,
+    crs: Crs,
+    crs_code: String,
+    format: String,
+    version: WmsVersion,
+    transparent: bool,
+    parameters: Vec<(String, String)>,
+    request_delay: Duration,
+    max_image_size: u32,
+    blend_mode: BlendMode,
+    platform_service: Arc<PlatformServiceImpl>,
+    image: Arc<Mutex<Option<WmsImage>>>,
+    /// The last requested image.
+    requested: Mutex<Option<WmsRequest>>,
+    /// The last request that was completed, successfully or not.
+    finished: Arc<Mutex<Option<WmsRequest>>>,
+    request_cancellation: Mutex<CancellationToken>,
+    messenger: Option<Arc<dyn Messenger>>,
+}
+
+/// Parameters of a `GetMap` request that depend on the map view.
+#[derive(Debug, Clone, PartialEq)]
+struct WmsRequest {
+    bbox: Rect,
+    size: Size<u32>,
+}
+
+struct WmsImage {
+    bbox: Rect,
+    state: ImageState,
+}
+
+enum ImageState {
+    Loaded(DecodedImage),
+    Rendered(Box<dyn PackedBundle>),
+}
+
+impl WmsLayer {
+    /// Creates a new layer requesting the given `layers` from the WMS service at `url`.
+    ///
+    /// The `url` is the address of the service without the `GetMap` request parameters. It can contain additional
+    /// query parameters required by the service (e.g. an access key), which are kept in every request.
+    pub fn new(
+        url: impl Into<String>,
+        layers: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        Self {
+            url: url.into(),
+            layers: layers.into_iter().map(Into::into).collect(),
+            styles: vec![],
+            crs: Crs::EPSG3857,
+            crs_code: "EPSG:3857".to_string(),
+            format: "image/png".to_string(),
+            version: WmsVersion::default(),
+            transparent: true,
+            parameters: vec![],
+            request_delay: DEFAULT_REQUEST_DELAY,
+            max_image_size: DEFAULT_MAX_IMAGE_SIZE,
+            blend_mode: BlendMode::Normal,
+            platform_service: Arc::new(PlatformServiceImpl::new()),
+            image: Arc::new(Mutex::new(None)),
+            requested: Mutex::new(None),
+            finished: Arc::new(Mutex::new(None)),
+            request_cancellation: Mutex::new(CancellationToken::new()),
+            messenger: None,
+        }
+    }
+
+    /// Sets the styles of the layers, in the same order as the layers. By default the server uses the default style
+    /// of every layer.
+    pub fn with_styles(mut self, styles: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.styles = styles.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets the CRS of the requested images and its identifier in the WMS service (e.g. `EPSG:3857`). The layer is
+    /// drawn only if the map view has the same CRS.
+    pub fn with_crs(mut self, crs: Crs, code: impl Into<String>) -> Self {
+        self.crs = crs;
+        self.crs_code = code.into();
+        self
+    }
+
+    /// Sets the MIME type of the requested images. Default value is `image/png`.
+    pub fn with_format(mut self, format: impl Into<String>) -> Self {
+        self.format = format.into();
+        self
+    }
+
+    /// Sets the version of the WMS protocol. Default is [`WmsVersion::V1_3_0`].
+    pub fn with_version(mut self, version: WmsVersion) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Sets if the server should return images with transparent background. Default value is `true`.
+    pub fn with_transparent(mut self, transparent: bool) -> Self {
+        self.transparent = transparent;
+        self
+    }
+
+    /// Adds a vendor-specific parameter to the `GetMap` requests (e.g. `TIME` or `DPI`).
+    pub fn with_parameter(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.parameters.push((name.into(), value.into()));
+        self
+    }
+
+    /// Sets the time the view must stay the same before a new image is requested. Default value is 200 ms.
+    pub fn with_request_delay(mut self, delay: Duration) -> Self {
+        self.request_delay = delay;
+        self
+    }
+
+    /// Sets the maximum width and height of the requested images in pixels. When the view is larger, a smaller image
+    /// is requested and stretched over the view. Default value is 4096, which is the limit of many WMS servers.
+    pub fn with_max_image_size(mut self, max_image_size: u32) -> Self {
+        self.max_image_size = max_image_size;
+        self
+    }
+
+    /// Sets the way the image is combined with the layers below.
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) {
+        self.blend_mode = blend_mode;
+    }
+
+    /// Sets the way the image is combined with the layers below. See [`WmsLayer::set_blend_mode`].
+    pub fn with_blend_mode(mut self, blend_mode: BlendMode) -> Self {
+        self.set_blend_mode(blend_mode);
+        self
+    }
+
+    /// The way the image is combined with the layers below.
+    pub fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    /// Requests the image for the current view again, e.g. when the application knows that the source data is
+    /// updated. The current image is drawn until the new one is loaded.
+    pub fn refresh(&self) {
+        *self.requested.lock() = None;
+        if let Some(messenger) = &self.messenger {
+            messenger.request_redraw();
+        }
+    }
+
+    /// Returns the `GetMap` request for the given view, or `None` if the layer cannot be drawn with the view.
+    fn request_for(&self, view: &MapView) -> Option<WmsRequest> {
+        if *view.crs() != self.crs {
+            return None;
+        }
+
+        let bbox = view.get_bbox()?;
+        let width = bbox.width() / view.resolution();
+        let height = bbox.height() / view.resolution();
+        let scale = (self.max_image_size as f64 / width.max(height)).min(1.0);
+        let size = Size::new(
+            (width * scale).round() as u32,
+            (height * scale).round() as u32,
+        );
+        if size.width() == 0 || size.height() == 0 {
+            return None;
+        }
+
+        Some(WmsRequest { bbox, size })
+    }
+
+    fn get_map_url(&self, request: &WmsRequest) -> String {
+        let bbox = request.bbox;
+        let parameters = [
+            ("SERVICE", "WMS".to_string()),
+            ("VERSION", self.version.as_str().to_string()),
+            ("REQUEST", "GetMap".to_string()),
+            ("LAYERS", self.layers.join(",")),
+            ("STYLES", self.styles.join(",")),
+            (self.version.crs_parameter(), self.crs_code.clone()),
+            (
+                "BBOX",
+                format!(
+                    "{},{},{},{}",
+                    bbox.x_min(),
+                    bbox.y_min(),
+                    bbox.x_max(),
+                    bbox.y_max()
+                ),
+            ),
+            ("WIDTH", request.size.width().to_string()),
+            ("HEIGHT", request.size.height().to_string()),
+            ("FORMAT", self.format.clone()),
+            (
+                "TRANSPARENT",
+                if self.transparent { "TRUE" } else { "FALSE" }.to_string(),
+            ),
+        ];
+
+        let mut url = self.url.clone();
+        if !url.contains('?') {
+            url.push('?');
+        } else if !url.ends_with(['?', '&']) {
+            url.push('&');
+        }
+
+        let query: Vec<String> = parameters
+            .iter()
+            .map(|(name, value)| (*name, value.as_str()))
+            .chain(
+                self.parameters
+                    .iter()
+                    .map(|(name, value)| (name.as_str(), value.as_str())),
+            )
+            .map(|(name, value)| format!("{}={}", encode(name), encode(value)))
+            .collect();
+        url.push_str(&query.join("&"));
+        url
+    }
+}
+
+/// Percent-encodes a query parameter. Commas and colons are left as is for readability, as they are used in most of
+/// the WMS parameter values and are accepted by the servers.
+fn encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b',' | b':' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+
+    encoded
+}
+
+impl Drop for WmsLayer {
+    fn drop(&mut self) {
+        self.request_cancellation.lock().cancel();
+    }
+}
+
+impl Layer for WmsLayer {
+    fn render(&self, view: &MapView, canvas: &mut dyn Canvas) {
+        if *view.crs() != self.crs {
+            return;
+        }
+
+        let mut image = self.image.lock();
+        let Some(image) = &mut *image else {
+            return;
+        };
+
+        if let ImageState::Loaded(decoded) = &mut image.state {
+            let owned = std::mem::replace(
+                decoded,
+                DecodedImage::from_raw(vec![], Size::new(0, 0)).expect("empty image is always ok"),
+            );
+
+            let mut bundle = canvas.create_bundle();
+            bundle.add_image(
+                owned,
+                image.bbox.into_quadrangle(),
+                ImagePaint { opacity: 255 },
+            );
+            image.state = ImageState::Rendered(canvas.pack_bundle(&bundle));
+        }
+
+        if let ImageState::Rendered(packed) = &image.state {
+            canvas.draw_bundles(
+                &[&**packed],
+                RenderOptions {
+                    blend_mode: self.blend_mode,
+                    ..Default::default()
+                },
+            );
+        }
+    }
+
+    fn prepare(&self, view: &MapView) {
+        let Some(request) = self.request_for(view) else {
+            return;
+        };
+
+        {
+            let mut requested = self.requested.lock();
+            if requested.as_ref() == Some(&request) {
+                return;
+            }
+            *requested = Some(request.clone());
+        }
+
+        // The image for the previous view is not needed anymore
+        let cancellation = CancellationToken::new();
+        std::mem::replace(&mut *self.request_cancellation.lock(), cancellation.clone()).cancel();
+
+        let url = self.get_map_url(&request);
+        let delay = self.request_delay;
+        let platform_service = self.platform_service.clone();
+        let image = self.image.clone();
+        let finished = self.finished.clone();
+        let messenger = self.messenger.clone();
+        crate::async_runtime::spawn_cancellable(&cancellation, async move {
+            if !delay.is_zero() {
+                crate::async_runtime::sleep(delay).await;
+            }
+
+            log::debug!("Requesting WMS image {url}");
+            match platform_service.load_image_url(&url).await {
+                Ok(decoded) => {
+                    *image.lock() = Some(WmsImage {
+                        bbox: request.bbox,
+                        state: ImageState::Loaded(decoded),
+                    });
+                }
+                Err(err) => log::warn!("Failed to load WMS image {url}: {err}"),
+            }
+
+            *finished.lock() = Some(request);
+            if let Some(messenger) = messenger {
+                messenger.request_redraw();
+            }
+        });
+    }
+
+    fn set_messenger(&mut self, messenger: Box<dyn Messenger>) {
+        self.messenger = Some(Arc::from(messenger));
+    }
+
+    fn clear_render_cache(&self) {
+        // The decoded image is consumed by packing, so the image is requested again
+        *self.image.lock() = None;
+        *self.requested.lock() = None;
+        *self.finished.lock() = None;
+    }
+
+    fn is_ready(&self, view: &MapView) -> bool {
+        let Some(request) = self.request_for(view) else {
+            return true;
+        };
+
+        self.finished.lock().as_ref() == Some(&request)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use galileo_types::cartesian::Point2d;
+
+    use super::*;
+
+    #[test]
+    fn get_map_url() {
+        let layer = WmsLayer::new("https://example.com/wms?key=abc", ["roads", "rivers"])
+            .with_styles(["", "blue lines"])
+            .with_parameter("TIME", "2024-01-01T00:00:00Z");
+        let request = WmsRequest {
+            bbox: Rect::new(-1000.0, -500.0, 1000.0, 500.0),
+            size: Size::new(200, 100),
+        };
+
+        assert_eq!(
+            layer.get_map_url(&request),
+            "https://example.com/wms?key=abc&SERVICE=WMS&VERSION=1.3.0&REQUEST=GetMap\
+            &LAYERS=roads,rivers&STYLES=,blue%20lines&CRS=EPSG:3857&BBOX=-1000,-500,1000,500\
+            &WIDTH=200&HEIGHT=100&FORMAT=image%2Fpng&TRANSPARENT=TRUE&TIME=2024-01-01T00:00:00Z"
+        );
+    }
+
+    #[test]
+    fn request_for_view() {
+        let layer = WmsLayer::new("https://example.com/wms", ["roads"])
+            .with_version(WmsVersion::V1_1_1)
+            .with_max_image_size(100);
+        let view = MapView::new_projected(&Point2d::new(0.0, 0.0), 10.0)
+            .with_size(Size::new(200.0, 100.0));
+
+        let request = layer.request_for(&view).unwrap();
+        assert_eq!(request.size, Size::new(100, 50));
+        assert!((request.bbox.width() - 2000.0).abs() < 1e-6);
+        assert!(layer
+            .get_map_url(&request)
+            .contains("?SERVICE=WMS&VERSION=1.1.1"));
+        assert!(layer.get_map_url(&request).contains("&SRS=EPSG:3857&"));
+
+        let view = MapView::new_projected_with_crs(&Point2d::new(0.0, 0.0), 10.0, Crs::WGS84);
+        assert!(layer.request_for(&view).is_none());
+    }
+}
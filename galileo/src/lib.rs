@@ -3,7 +3,23 @@
 //!
 //! # Quick start
 //!
-//! TODO
+//! A window with a vector basemap and labels can be opened with [`MapBuilder`] (requires `winit` and `wgpu`
+//! features):
+//!
+//! ```ignore
+//! use galileo::layer::VectorBasemap;
+//! use galileo::MapBuilder;
+//!
+//! MapBuilder::default()
+//!     .with_vector_basemap(VectorBasemap::Protomaps { api_key: "<your key>".into() })
+//!     .build()
+//!     .await
+//!     .run();
+//! ```
+//!
+//! The labels of the basemap are drawn with the
+//! [`BASEMAP_FONT`](layer::vector_tile_layer::BASEMAP_FONT), which must be loaded by the application. Raster
+//! basemaps are available with [`MapBuilder::with_basemap`].
 //!
 //! # Main components of Galileo
 //!
@@ -26,18 +42,23 @@
 //!   some intermediate representation, more convenient to deal with, and some
 //! * [`controls`](control) that actually change state of the map or layers based on the user input.
 
+pub mod animation;
 pub(crate) mod async_runtime;
 mod color;
 pub mod control;
 pub mod decoded_image;
 pub mod error;
+pub mod file_drop;
 pub mod layer;
 mod lod;
 mod map;
 mod messenger;
 pub mod platform;
 pub mod render;
+pub mod routing;
+pub mod sdk;
 pub mod tile_scheme;
+mod trace;
 mod view;
 
 #[cfg(test)]
@@ -48,14 +69,20 @@ pub mod winit;
 
 #[cfg(all(feature = "winit", feature = "wgpu"))]
 mod galileo_map;
+#[cfg(all(feature = "winit", feature = "wgpu", not(target_arch = "wasm32")))]
+mod multi_window_map;
+#[cfg(not(target_arch = "wasm32"))]
+pub use async_runtime::block_on;
 pub use color::Color;
 #[cfg(all(feature = "winit", feature = "wgpu"))]
 pub use galileo_map::{GalileoMap, MapBuilder};
+#[cfg(all(feature = "winit", feature = "wgpu", not(target_arch = "wasm32")))]
+pub use multi_window_map::{MapWindow, MultiWindowMap, WindowOpener};
 // Reexport galileo_types
 pub use galileo_types;
 pub use layer::feature_layer::symbol;
 pub use lod::Lod;
-pub use map::{LayerCollection, Map};
-pub use messenger::{DummyMessenger, Messenger};
+pub use map::{LayerCollection, LayerTransform, Map, ViewRelation, ViewSync};
+pub use messenger::{ChannelMessenger, DummyMessenger, MapEvent, MapEventReceiver, Messenger};
 pub use tile_scheme::TileSchema;
 pub use view::MapView;
@@ -1,6 +1,14 @@
 use std::ops::{Index, IndexMut, RangeBounds};
 
-use crate::layer::Layer;
+use galileo_types::geometry::Geometry;
+use maybe_sync::{MaybeSend, MaybeSync};
+
+use crate::decoded_image::DecodedImage;
+use crate::layer::data_provider::DataProvider;
+use crate::layer::feature_layer::Feature;
+use crate::layer::{FeatureLayer, Layer, RasterTileLayer, VectorTileLayer};
+use crate::map::LayerTransform;
+use crate::tile_scheme::TileIndex;
 
 /// Collection of layers with some meta-information.
 ///
@@ -38,6 +46,7 @@ pub struct LayerCollection(Vec<LayerEntry>);
 struct LayerEntry {
     layer: Box<dyn Layer>,
     is_hidden: bool,
+    transform: Option<LayerTransform>,
 }
 
 impl LayerCollection {
@@ -215,6 +224,11 @@ impl LayerCollection {
         self.0.push(layer.into())
     }
 
+    /// Adds a boxed layer to the end of the collection.
+    pub fn push_boxed(&mut self, layer: Box<dyn Layer>) {
+        self.0.push(layer.into())
+    }
+
     /// Removes the last layer from the collection and returns it. Returns `None` if the collection
     /// is empty.
     ///
@@ -418,6 +432,113 @@ impl LayerCollection {
         self.0.iter_mut().map(|entry| &mut entry.layer)
     }
 
+    /// Iterates over all layers of the type `T` in the collection, skipping layers of other types.
+    ///
+    /// Layers that are stored in the collection behind a shared reference (e.g. `Arc<RwLock<T>>`) have a different
+    /// type and are not returned by this method.
+    ///
+    /// ```
+    /// use galileo::LayerCollection;
+    /// use galileo::layer::TestLayer;
+    ///
+    /// let collection = LayerCollection::from(vec![
+    ///     TestLayer("Layer A"),
+    ///     TestLayer("Layer B"),
+    /// ]);
+    ///
+    /// let names: Vec<_> = collection.layers_of_type::<TestLayer>().map(|layer| layer.0).collect();
+    /// assert_eq!(names, vec!["Layer A", "Layer B"]);
+    /// ```
+    pub fn layers_of_type<T: Layer + 'static>(&self) -> impl Iterator<Item = &T> + '_ {
+        self.0
+            .iter()
+            .filter_map(|entry| entry.layer.as_any().downcast_ref())
+    }
+
+    /// Iterates over mutable references to all layers of the type `T` in the collection, skipping layers of other
+    /// types.
+    ///
+    /// See also [`LayerCollection::layers_of_type`].
+    pub fn layers_of_type_mut<T: Layer + 'static>(&mut self) -> impl Iterator<Item = &mut T> + '_ {
+        self.0
+            .iter_mut()
+            .filter_map(|entry| entry.layer.as_any_mut().downcast_mut())
+    }
+
+    /// Iterates over all [`VectorTileLayer`]s in the collection.
+    pub fn vector_tile_layers(&self) -> impl Iterator<Item = &VectorTileLayer> + '_ {
+        self.layers_of_type()
+    }
+
+    /// Iterates over mutable references to all [`VectorTileLayer`]s in the collection.
+    pub fn vector_tile_layers_mut(&mut self) -> impl Iterator<Item = &mut VectorTileLayer> + '_ {
+        self.layers_of_type_mut()
+    }
+
+    /// Iterates over all [`RasterTileLayer`]s with the given tile provider type in the collection.
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use galileo::layer::data_provider::{FileCacheController, UrlImageProvider};
+    /// use galileo::tile_scheme::TileIndex;
+    /// use galileo::LayerCollection;
+    ///
+    /// fn disable_fade_in(layers: &mut LayerCollection) {
+    ///     for layer in layers.raster_tile_layers_mut::<UrlImageProvider<TileIndex, FileCacheController>>() {
+    ///         layer.set_fade_in_duration(Duration::ZERO);
+    ///     }
+    /// }
+    /// ```
+    pub fn raster_tile_layers<Provider>(
+        &self,
+    ) -> impl Iterator<Item = &RasterTileLayer<Provider>> + '_
+    where
+        Provider: DataProvider<TileIndex, DecodedImage, ()> + MaybeSync + MaybeSend + 'static,
+    {
+        self.layers_of_type()
+    }
+
+    /// Iterates over mutable references to all [`RasterTileLayer`]s with the given tile provider type in the
+    /// collection.
+    pub fn raster_tile_layers_mut<Provider>(
+        &mut self,
+    ) -> impl Iterator<Item = &mut RasterTileLayer<Provider>> + '_
+    where
+        Provider: DataProvider<TileIndex, DecodedImage, ()> + MaybeSync + MaybeSend + 'static,
+    {
+        self.layers_of_type_mut()
+    }
+
+    /// Iterates over all [`FeatureLayer`]s with the given feature, symbol and space types in the collection.
+    pub fn feature_layers<F, S, Space>(
+        &self,
+    ) -> impl Iterator<Item = &FeatureLayer<<F::Geom as Geometry>::Point, F, S, Space>> + '_
+    where
+        F: Feature + 'static,
+        <F::Geom as Geometry>::Point: 'static,
+        S: 'static,
+        Space: 'static,
+        FeatureLayer<<F::Geom as Geometry>::Point, F, S, Space>: Layer,
+    {
+        self.layers_of_type()
+    }
+
+    /// Iterates over mutable references to all [`FeatureLayer`]s with the given feature, symbol and space types in
+    /// the collection.
+    pub fn feature_layers_mut<F, S, Space>(
+        &mut self,
+    ) -> impl Iterator<Item = &mut FeatureLayer<<F::Geom as Geometry>::Point, F, S, Space>> + '_
+    where
+        F: Feature + 'static,
+        <F::Geom as Geometry>::Point: 'static,
+        S: 'static,
+        Space: 'static,
+        FeatureLayer<<F::Geom as Geometry>::Point, F, S, Space>: Layer,
+    {
+        self.layers_of_type_mut()
+    }
+
     /// Sets the layer at `index` as invisible. The hidden layer can be later shown with
     /// [`LayerCollection::show`].
     ///
@@ -557,6 +678,64 @@ impl LayerCollection {
             .filter(|entry| !entry.is_hidden)
             .map(|entry| &*entry.layer)
     }
+
+    /// Sets the transform applied to the layer at `index` when it is rendered. `None` removes the transform.
+    ///
+    /// See [`LayerTransform`] for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use galileo::{LayerCollection, LayerTransform};
+    /// use galileo::layer::TestLayer;
+    /// use nalgebra::Vector2;
+    ///
+    /// let mut collection = LayerCollection::from(vec![
+    ///     TestLayer("Layer A"),
+    ///     TestLayer("Layer B"),
+    /// ]);
+    ///
+    /// let transform = LayerTransform::new(Vector2::new(10.0, 0.0), 0.0, 1.0);
+    /// collection.set_transform(1, Some(transform));
+    /// assert_eq!(collection.transform(0), None);
+    /// assert_eq!(collection.transform(1), Some(&transform));
+    /// ```
+    pub fn set_transform(&mut self, index: usize, transform: Option<LayerTransform>) {
+        self.0[index].transform = transform;
+    }
+
+    /// Returns the transform of the layer at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn transform(&self, index: usize) -> Option<&LayerTransform> {
+        self.0[index].transform.as_ref()
+    }
+
+    /// Returns a mutable reference to the transform of the layer at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn transform_mut(&mut self, index: usize) -> Option<&mut LayerTransform> {
+        self.0[index].transform.as_mut()
+    }
+
+    /// Iterates over all visible layers together with their indices and transforms.
+    pub(crate) fn iter_visible_with_transforms(
+        &self,
+    ) -> impl Iterator<Item = (usize, &dyn Layer, Option<&LayerTransform>)> + '_ {
+        self.0
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| !entry.is_hidden)
+            .map(|(index, entry)| (index, &*entry.layer, entry.transform.as_ref()))
+    }
 }
 
 impl Index<usize> for LayerCollection {
@@ -584,6 +763,7 @@ impl<T: Layer + 'static> From<T> for LayerEntry {
         Self {
             layer: Box::new(value),
             is_hidden: false,
+            transform: None,
         }
     }
 }
@@ -593,6 +773,7 @@ impl From<Box<dyn Layer>> for LayerEntry {
         Self {
             layer: value,
             is_hidden: false,
+            transform: None,
         }
     }
 }
@@ -0,0 +1,155 @@
+use galileo_types::cartesian::Point2d;
+use nalgebra::{Matrix4, Rotation2, Vector2};
+
+/// Adjustment of the position of a layer relative to the other layers of the map, applied at render time.
+///
+/// The transform moves, rotates and uniformly scales the layer contents in the projected coordinates of the map. It
+/// is useful e.g. to georeference a scanned plan by manually aligning it with the basemap. A point `p` of the layer
+/// is drawn at `scale * R(rotation) * p + translation`.
+///
+/// The transform only changes how the layer is displayed. The layer itself (e.g. selection of tiles to load) still
+/// works with the untransformed view, and coordinates of the layer features stay the same. To convert a point on the
+/// map into the layer coordinates, use [`LayerTransform::inverse_apply`].
+///
+/// The transform of a layer is set with [`LayerCollection::set_transform`](crate::LayerCollection::set_transform),
+/// and can be adjusted interactively with
+/// [`LayerTransformController`](crate::control::LayerTransformController).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LayerTransform {
+    translation: Vector2<f64>,
+    rotation: f64,
+    scale: f64,
+}
+
+impl Default for LayerTransform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl LayerTransform {
+    /// Transform that does not change the layer.
+    pub fn identity() -> Self {
+        Self {
+            translation: Vector2::zeros(),
+            rotation: 0.0,
+            scale: 1.0,
+        }
+    }
+
+    /// Creates a transform from its components. Rotation is given in radians counterclockwise.
+    pub fn new(translation: Vector2<f64>, rotation: f64, scale: f64) -> Self {
+        Self {
+            translation,
+            rotation,
+            scale,
+        }
+    }
+
+    /// Translation of the layer in map units.
+    pub fn translation(&self) -> Vector2<f64> {
+        self.translation
+    }
+
+    /// Rotation of the layer around the origin of the coordinates in radians counterclockwise.
+    pub fn rotation(&self) -> f64 {
+        self.rotation
+    }
+
+    /// Scale factor of the layer.
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Returns true if the transform does not change the layer.
+    pub fn is_identity(&self) -> bool {
+        *self == Self::identity()
+    }
+
+    /// Moves the layer by the given vector in map units.
+    pub fn translate(&mut self, delta: Vector2<f64>) {
+        self.translation += delta;
+    }
+
+    /// Rotates the layer by the `angle` (in radians counterclockwise) around the given point on the map.
+    pub fn rotate_around(&mut self, center: Point2d, angle: f64) {
+        let rotation = Rotation2::new(angle);
+        self.rotation += angle;
+        self.translation = rotation * (self.translation - center.coords) + center.coords;
+    }
+
+    /// Scales the layer by the `factor` keeping the given point on the map in place.
+    pub fn scale_around(&mut self, center: Point2d, factor: f64) {
+        self.scale *= factor;
+        self.translation = (self.translation - center.coords) * factor + center.coords;
+    }
+
+    /// Converts a point in the layer coordinates into the point on the map where it is drawn.
+    pub fn apply(&self, point: Point2d) -> Point2d {
+        Rotation2::new(self.rotation) * point * self.scale + self.translation
+    }
+
+    /// Converts a point on the map into the layer coordinates. Returns `None` if the scale of the transform is zero.
+    pub fn inverse_apply(&self, point: Point2d) -> Option<Point2d> {
+        if self.scale == 0.0 {
+            return None;
+        }
+
+        Some(Rotation2::new(-self.rotation) * (point - self.translation) / self.scale)
+    }
+
+    /// Transformation matrix to be applied to the 3d coordinates of the layer before the view transformation.
+    pub fn to_matrix(&self) -> Matrix4<f64> {
+        let (sin, cos) = self.rotation.sin_cos();
+        let s = self.scale;
+        Matrix4::new(
+            s * cos,
+            -s * sin,
+            0.0,
+            self.translation.x,
+            s * sin,
+            s * cos,
+            0.0,
+            self.translation.y,
+            0.0,
+            0.0,
+            1.0,
+            0.0,
+            0.0,
+            0.0,
+            0.0,
+            1.0,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+    use nalgebra::Vector4;
+
+    use super::*;
+
+    #[test]
+    fn rotate_and_scale_around_point() {
+        let center = Point2d::new(10.0, 10.0);
+        let mut transform = LayerTransform::identity();
+        transform.translate(Vector2::new(5.0, 0.0));
+        transform.rotate_around(center, std::f64::consts::FRAC_PI_2);
+        transform.scale_around(center, 2.0);
+
+        // The point drawn at the center stays in place
+        let at_center = transform.inverse_apply(center).expect("invalid transform");
+        assert_abs_diff_eq!(at_center.x, 5.0, epsilon = 1e-9);
+        assert_abs_diff_eq!(at_center.y, 10.0, epsilon = 1e-9);
+
+        let point = Point2d::new(6.0, 10.0);
+        let applied = transform.apply(point);
+        assert_abs_diff_eq!(applied.x, 10.0, epsilon = 1e-9);
+        assert_abs_diff_eq!(applied.y, 12.0, epsilon = 1e-9);
+
+        let matrix_applied = transform.to_matrix() * Vector4::new(point.x, point.y, 0.0, 1.0);
+        assert_abs_diff_eq!(matrix_applied.x, applied.x, epsilon = 1e-9);
+        assert_abs_diff_eq!(matrix_applied.y, applied.y, epsilon = 1e-9);
+    }
+}
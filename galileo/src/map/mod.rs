@@ -1,16 +1,25 @@
 use std::time::Duration;
 
-use galileo_types::cartesian::Size;
-use web_time::SystemTime;
+use galileo_types::cartesian::{Point2d, Rect, Size};
+use maybe_sync::{MaybeSend, MaybeSync};
+use web_time::{Instant, SystemTime};
 
-use crate::layer::Layer;
+use crate::animation::{Animation, AnimationId, AnimationSet, AnimationTask, Easing, Interpolate};
+use crate::layer::data_provider::TileSourceStats;
+use crate::layer::{is_in_view, Layer};
 use crate::messenger::Messenger;
+use crate::render::horizon::HorizonOptions;
 use crate::view::MapView;
 
 mod layer_collection;
+mod layer_transform;
+mod view_sync;
 pub use layer_collection::LayerCollection;
+pub use layer_transform::LayerTransform;
+pub use view_sync::{ViewRelation, ViewSync};
 
 const FRAME_DURATION: Duration = Duration::from_millis(16);
+const ROTATION_RESET_DURATION: Duration = Duration::from_millis(300);
 
 /// Map specifies a set of layers, and the view that should be rendered.
 pub struct Map {
@@ -18,6 +27,9 @@ pub struct Map {
     layers: LayerCollection,
     messenger: Option<Box<dyn Messenger>>,
     animation: Option<AnimationParameters>,
+    property_animations: AnimationSet,
+    paused_at: Option<Instant>,
+    horizon: Option<HorizonOptions>,
 }
 
 struct AnimationParameters {
@@ -25,6 +37,7 @@ struct AnimationParameters {
     end_view: MapView,
     start_time: SystemTime,
     duration: Duration,
+    easing: Easing,
 }
 
 impl Map {
@@ -39,6 +52,9 @@ impl Map {
             layers: layers.into(),
             messenger,
             animation: None,
+            property_animations: AnimationSet::new(),
+            paused_at: None,
+            horizon: None,
         }
     }
 
@@ -57,31 +73,112 @@ impl Map {
         &mut self.layers
     }
 
+    /// Statistics of the requests made by the tile sources of all the map's layers (including hidden ones), summed up.
+    ///
+    /// Statistics of individual layers can be obtained with [`Layer::tile_source_stats`].
+    pub fn tile_source_stats(&self) -> TileSourceStats {
+        self.layers
+            .iter()
+            .filter_map(|layer| layer.tile_source_stats())
+            .sum()
+    }
+
+    /// Sky and haze drawn at the horizon when the map is tilted. See [`HorizonOptions`].
+    pub fn horizon(&self) -> Option<&HorizonOptions> {
+        self.horizon.as_ref()
+    }
+
+    /// Sets the sky and haze drawn at the horizon of the map, or disables them with `None`. Maps without horizon
+    /// options show the renderer background above the horizon.
+    pub fn set_horizon(&mut self, horizon: Option<HorizonOptions>) {
+        self.horizon = horizon;
+        self.redraw();
+    }
+
     /// Changes the view of the map to the given one.
     pub fn set_view(&mut self, view: MapView) {
+        self.update_view(view);
+        self.redraw();
+    }
+
+    fn update_view(&mut self, view: MapView) {
+        let rotation_changed = view.rotation_x() != self.view.rotation_x()
+            || view.rotation_z() != self.view.rotation_z();
         self.view = view;
-        if let Some(messenger) = &self.messenger {
-            messenger.request_redraw();
+
+        if rotation_changed {
+            if let Some(messenger) = &self.messenger {
+                messenger.rotation_changed(self.view.rotation_x(), self.view.rotation_z());
+            }
         }
     }
 
     /// Calls [`Layer::prepare`] method on all the layers with the current map view. Used to preload layer data before
-    /// the map is rendered.
+    /// the map is rendered. Layers with the [extent](Layer::extent) outside of the view are skipped.
+    ///
+    /// Does nothing while the map is paused.
     pub fn load_layers(&self) {
-        for layer in self.layers.iter_visible() {
-            layer.prepare(&self.view);
+        if self.is_paused() {
+            return;
+        }
+
+        for (_, layer, transform) in self.layers.iter_visible_with_transforms() {
+            if is_in_view(layer, &self.view, transform) {
+                layer.prepare(&self.view);
+            }
         }
     }
 
-    /// Request redraw of the map.
+    /// Returns the bounding rectangle of the [extents](Layer::extent) of all visible layers in the CRS of the map
+    /// view, e.g. to fit the view to all the data of the map. Layers with unknown extent are not taken into account.
+    /// Returns `None` if no layer has known extent.
+    pub fn layers_extent(&self) -> Option<Rect> {
+        self.layers
+            .iter_visible()
+            .filter_map(|layer| layer.extent(self.view.crs()))
+            .collect()
+    }
+
+    /// Drops the data all the layers have prepared for the GPU and requests redraw of the map. Must be called when the
+    /// map is moved to a renderer with a different graphics device. See [`Layer::clear_render_cache`].
+    pub fn clear_render_cache(&self) {
+        for layer in self.layers.iter() {
+            layer.clear_render_cache();
+        }
+
+        self.redraw();
+    }
+
+    /// Request redraw of the map. Redraw is not requested while the map is paused.
     pub fn redraw(&self) {
+        if self.is_paused() {
+            return;
+        }
+
         if let Some(messenger) = &self.messenger {
             messenger.request_redraw()
         }
     }
 
-    /// Update the view of the map before the rendering in case [`Map::animate_to`] was called.
+    /// Update the view of the map before the rendering in case [`Map::animate_to`] was called, and advance all the
+    /// animations added with [`Map::add_animation`].
+    ///
+    /// Animations are frozen while the map is paused.
     pub fn animate(&mut self) {
+        if self.is_paused() {
+            return;
+        }
+
+        self.animate_view();
+
+        if !self.property_animations.is_empty() {
+            self.property_animations
+                .advance(Instant::now(), &mut self.layers);
+            self.redraw();
+        }
+    }
+
+    fn animate_view(&mut self) {
         let Some(animation) = &self.animation else {
             return;
         };
@@ -98,9 +195,10 @@ impl Map {
                 .animation
                 .take()
                 .expect("the value was removed unexpectedly");
-            self.view = animation.end_view;
+            self.update_view(animation.end_view);
         } else {
-            self.view = animation.start_view.interpolate(&animation.end_view, k);
+            let k = animation.easing.apply(k);
+            self.update_view(animation.start_view.interpolate(&animation.end_view, k));
         }
 
         self.redraw();
@@ -115,20 +213,154 @@ impl Map {
     }
 
     /// Request a gradual change of the map view to the specified view.
+    ///
+    /// The view is changed with constant speed. Use [`Map::animate_to_with_easing`] to change the view with a
+    /// different motion.
     pub fn animate_to(&mut self, target: MapView, duration: Duration) {
+        self.animate_to_with_easing(target, duration, Easing::Linear);
+    }
+
+    /// Request a gradual change of the map view to the specified view, with the speed of the change defined by the
+    /// `easing` function.
+    pub fn animate_to_with_easing(&mut self, target: MapView, duration: Duration, easing: Easing) {
         self.animation = Some(AnimationParameters {
             start_view: self.view.clone(),
             end_view: target,
             start_time: SystemTime::now() - FRAME_DURATION,
             duration,
+            easing,
         });
     }
 
+    /// Starts the animation of a value. On every frame until the animation is finished, `apply` function is called
+    /// with the map layers and the current value, so that it can update the animated property.
+    ///
+    /// If the animated property belongs to a layer not stored in the map, it can be updated through a shared
+    /// reference captured by `apply` (e.g. `Arc<RwLock<FeatureLayer>>`).
+    ///
+    /// Returns the identifier that can be used to stop the animation with [`Map::remove_animation`].
+    pub fn add_animation<T, F>(&mut self, animation: Animation<T>, apply: F) -> AnimationId
+    where
+        T: Interpolate + MaybeSend + MaybeSync + 'static,
+        F: FnMut(&mut LayerCollection, T) + MaybeSend + MaybeSync + 'static,
+    {
+        let id = self.property_animations.add(Box::new(AnimationTask::new(
+            animation,
+            apply,
+            Instant::now(),
+        )));
+        self.redraw();
+        id
+    }
+
+    /// Stops the animation. The animated property keeps its current value.
+    ///
+    /// Returns false if the animation is already finished or was removed before.
+    pub fn remove_animation(&mut self, id: AnimationId) -> bool {
+        self.property_animations.remove(id)
+    }
+
+    /// Pauses or resumes the map.
+    ///
+    /// Use this when the map is not visible, e.g. when the application window is minimized or the browser tab is
+    /// hidden. While the map is paused, layers do not load new data, animations are frozen and the map does not request
+    /// redraws. When the map is resumed, animations continue from where they were paused, layer data for the current
+    /// view is loaded and the map is redrawn.
+    pub fn set_paused(&mut self, paused: bool) {
+        match (paused, self.paused_at) {
+            (true, None) => self.paused_at = Some(Instant::now()),
+            (false, Some(paused_at)) => {
+                self.paused_at = None;
+
+                let paused_for = paused_at.elapsed();
+                if let Some(animation) = &mut self.animation {
+                    animation.start_time += paused_for;
+                }
+                self.property_animations.delay(paused_for);
+
+                self.load_layers();
+                self.redraw();
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns true if the map is paused with [`Map::set_paused`].
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    /// Returns true if the view animation (see [`Map::animate_to`]) or any of the animations added with
+    /// [`Map::add_animation`] is in progress, so the map must be updated with [`Map::animate`] and redrawn at every
+    /// frame. Animations of a paused map are frozen, so this method returns false while the map is paused.
+    pub fn is_animating(&self) -> bool {
+        !self.is_paused() && (self.animation.is_some() || !self.property_animations.is_empty())
+    }
+
+    /// Zooms the map around the given screen point, multiplying the resolution by `zoom`.
+    ///
+    /// If an animation is in progress, the zoom is applied to the target view of the animation, so consequent calls
+    /// accumulate the same way as consequent mouse wheel scrolls do. If `duration` is zero, the view is changed
+    /// immediately.
+    pub fn zoom_around(&mut self, screen_point: Point2d, zoom: f64, duration: Duration) {
+        let target = self.target_view().zoom_around(screen_point, zoom);
+        if duration.is_zero() {
+            self.animation = None;
+            self.set_view(target);
+        } else {
+            self.animate_to(target, duration);
+            self.redraw();
+        }
+    }
+
+    /// Changes the resolution of the map so that the scale at the center of the map becomes `1:scale`.
+    ///
+    /// See [`MapView::scale`] for details on how the scale is calculated. If the scale of the current view cannot be
+    /// calculated (e.g. the map has zero size), the view is not changed.
+    pub fn set_scale(&mut self, scale: f64) {
+        if let Some(view) = self.view.with_scale(scale) {
+            self.animation = None;
+            self.set_view(view);
+        }
+    }
+
+    /// Moves the map by the given number of pixels, as if it was dragged by the pointer.
+    ///
+    /// If an animation of the view is in progress, it is stopped and the current view is moved, so the map is not
+    /// moved back by the next animation frame. See [`MapView::pan_by_pixels`].
+    pub fn pan_by_pixels(&mut self, dx: f64, dy: f64) {
+        self.animation = None;
+        self.set_view(self.view.pan_by_pixels(dx, dy));
+    }
+
+    /// Rotates the map so that the north is at the top of the screen. Tilt of the map is not changed.
+    ///
+    /// If `animated` is true, the map is rotated gradually, taking the shortest direction.
+    pub fn reset_rotation(&mut self, animated: bool) {
+        let target = self.target_view().with_rotation_z(0.0);
+        if animated {
+            // Normalizing the current angle does not change the view, but makes the animation go the shortest way
+            self.view = self
+                .view
+                .with_rotation_z(normalize_angle(self.view.rotation_z()));
+            self.animate_to(target, ROTATION_RESET_DURATION);
+            self.redraw();
+        } else {
+            self.animation = None;
+            self.set_view(target);
+        }
+    }
+
     /// Set the size of the map.
     pub fn set_size(&mut self, new_size: Size) {
         self.view = self.view.with_size(new_size);
     }
 
+    /// Set the DPI scale factor of the screen the map is displayed on. See [`MapView::dpi_scale_factor`].
+    pub fn set_dpi_scale_factor(&mut self, dpi_scale_factor: f64) {
+        self.view = self.view.with_dpi_scale_factor(dpi_scale_factor);
+    }
+
     /// Sets the new event messenger for the map.
     pub fn set_messenger(&mut self, messenger: Option<impl Messenger + 'static>) {
         let messenger: Option<Box<dyn Messenger>> = if let Some(m) = messenger {
@@ -139,4 +371,150 @@ impl Map {
 
         self.messenger = messenger;
     }
+
+    /// Sets the messenger for the map and all its layers, so that the application is notified of any change that
+    /// requires redrawing the map.
+    ///
+    /// Layers added to the map after this call must be given a messenger with [`Layer::set_messenger`] separately.
+    pub fn install_messenger(&mut self, messenger: impl Messenger + Clone + 'static) {
+        for layer in self.layers.iter_mut() {
+            layer.set_messenger(Box::new(messenger.clone()));
+        }
+
+        self.set_messenger(Some(messenger));
+    }
+}
+
+/// Converts the angle in radians into the `[-PI, PI)` range.
+pub(crate) fn normalize_angle(angle: f64) -> f64 {
+    use std::f64::consts::PI;
+    (angle + PI).rem_euclid(2.0 * PI) - PI
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use approx::assert_abs_diff_eq;
+    use parking_lot::Mutex;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct RedrawCounter(Mutex<usize>);
+
+    impl Messenger for RedrawCounter {
+        fn request_redraw(&self) {
+            *self.0.lock() += 1;
+        }
+    }
+
+    #[derive(Default)]
+    struct RotationRecorder {
+        rotations: Mutex<Vec<f64>>,
+    }
+
+    impl Messenger for RotationRecorder {
+        fn request_redraw(&self) {}
+
+        fn rotation_changed(&self, _rotation_x: f64, rotation_z: f64) {
+            self.rotations.lock().push(rotation_z);
+        }
+    }
+
+    #[test]
+    fn normalize_angle_range() {
+        use std::f64::consts::PI;
+        assert_abs_diff_eq!(normalize_angle(0.5), 0.5, epsilon = 1e-9);
+        assert_abs_diff_eq!(normalize_angle(2.0 * PI - 0.5), -0.5, epsilon = 1e-9);
+        assert_abs_diff_eq!(normalize_angle(-4.0 * PI + 0.5), 0.5, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn rotation_change_is_reported() {
+        let recorder = Arc::new(RotationRecorder::default());
+        let view = MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0);
+        let mut map = Map::new(view.clone(), vec![], None);
+        map.set_messenger(Some(recorder.clone()));
+
+        map.set_view(view.with_rotation_z(1.0));
+        map.pan_by_pixels(10.0, 10.0);
+        map.reset_rotation(false);
+
+        assert_eq!(*recorder.rotations.lock(), vec![1.0, 0.0]);
+        assert_eq!(map.view().rotation_z(), 0.0);
+    }
+
+    #[test]
+    fn paused_map_does_not_redraw() {
+        let counter = Arc::new(RedrawCounter::default());
+        let view = MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0);
+        let mut map = Map::new(view.clone(), vec![], None);
+        map.set_messenger(Some(counter.clone()));
+
+        map.set_paused(true);
+        assert!(map.is_paused());
+        map.set_view(view.with_rotation_z(1.0));
+        map.animate_to(view.clone(), Duration::from_millis(100));
+        map.animate();
+        map.redraw();
+        assert_eq!(*counter.0.lock(), 0);
+        assert_eq!(map.view().rotation_z(), 1.0);
+
+        map.set_paused(false);
+        assert!(!map.is_paused());
+        assert_eq!(*counter.0.lock(), 1);
+    }
+
+    struct ExtentLayer {
+        extent: Rect,
+        prepared: Mutex<usize>,
+    }
+
+    impl Layer for ExtentLayer {
+        fn render(&self, _view: &MapView, _canvas: &mut dyn crate::render::Canvas) {}
+
+        fn prepare(&self, _view: &MapView) {
+            *self.prepared.lock() += 1;
+        }
+
+        fn set_messenger(&mut self, _messenger: Box<dyn Messenger>) {}
+
+        fn extent(&self, _crs: &galileo_types::geo::Crs) -> Option<Rect> {
+            Some(self.extent)
+        }
+
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+            self
+        }
+    }
+
+    #[test]
+    fn layers_outside_view_are_not_prepared() {
+        let near = Arc::new(parking_lot::RwLock::new(ExtentLayer {
+            extent: Rect::new(40.0, 40.0, 60.0, 60.0),
+            prepared: Mutex::new(0),
+        }));
+        let far = Arc::new(parking_lot::RwLock::new(ExtentLayer {
+            extent: Rect::new(10_000.0, 10_000.0, 10_100.0, 10_100.0),
+            prepared: Mutex::new(0),
+        }));
+
+        let view =
+            MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0).with_size(Size::new(100.0, 100.0));
+        let layers: Vec<Box<dyn Layer>> = vec![Box::new(near.clone()), Box::new(far.clone())];
+        let map = Map::new(view, layers, None);
+
+        map.load_layers();
+        assert_eq!(*near.read().prepared.lock(), 1);
+        assert_eq!(*far.read().prepared.lock(), 0);
+        assert_eq!(
+            map.layers_extent(),
+            Some(Rect::new(40.0, 40.0, 10_100.0, 10_100.0))
+        );
+    }
 }
@@ -0,0 +1,310 @@
+use std::sync::Arc;
+
+use nalgebra::Vector2;
+use parking_lot::RwLock;
+
+use crate::map::Map;
+use crate::view::MapView;
+
+/// Relation between the view of a synchronized map and the common view of a [`ViewSync`] group.
+///
+/// The view of the map is the common view moved by `offset` (in projected units of the map CRS) with the resolution
+/// multiplied by `resolution_factor`. Rotation and tilt of the views are always the same.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ViewRelation {
+    offset: Vector2<f64>,
+    resolution_factor: f64,
+}
+
+impl Default for ViewRelation {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl ViewRelation {
+    /// Relation that makes the map show exactly the common view.
+    pub fn identity() -> Self {
+        Self {
+            offset: Vector2::zeros(),
+            resolution_factor: 1.0,
+        }
+    }
+
+    /// Creates a new relation from its components.
+    pub fn new(offset: Vector2<f64>, resolution_factor: f64) -> Self {
+        Self {
+            offset,
+            resolution_factor,
+        }
+    }
+
+    /// Offset of the center of the map view from the center of the common view in projected units.
+    pub fn offset(&self) -> Vector2<f64> {
+        self.offset
+    }
+
+    /// Ratio of the map view resolution to the resolution of the common view.
+    pub fn resolution_factor(&self) -> f64 {
+        self.resolution_factor
+    }
+
+    /// Converts the common view into the view of the map.
+    pub fn apply(&self, view: &MapView) -> MapView {
+        view.translate(-self.offset)
+            .with_resolution(view.resolution() * self.resolution_factor)
+    }
+
+    /// Converts the view of the map into the common view.
+    pub fn inverse_apply(&self, view: &MapView) -> MapView {
+        view.translate(self.offset)
+            .with_resolution(view.resolution() / self.resolution_factor)
+    }
+}
+
+struct SyncedMap {
+    map: Arc<RwLock<Map>>,
+    relation: ViewRelation,
+    last_view: Option<MapView>,
+}
+
+/// Links the views of several [maps](Map), so that panning, zooming or rotating one of them changes the others
+/// accordingly.
+///
+/// This is useful to compare two maps side by side, e.g. imagery of the same area made at different times. Each map
+/// can be shifted or zoomed relative to the others with a [`ViewRelation`].
+///
+/// The group does not track the maps by itself: [`ViewSync::sync`] must be called after the views of the maps might
+/// have changed, e.g. after user input is processed or once per frame. It finds the map which view was changed since
+/// the previous call and sets the views of all other maps to match it. Only size and DPI scale factor of the maps
+/// are not synchronized.
+///
+/// ```no_run
+/// use std::sync::Arc;
+///
+/// use galileo::{Map, MapView, ViewRelation, ViewSync};
+/// use galileo::galileo_types::geo::impls::GeoPoint2d;
+/// use galileo::galileo_types::geo::NewGeoPoint;
+/// use parking_lot::RwLock;
+///
+/// let view = MapView::new(&GeoPoint2d::latlon(52.0, 13.0), 10.0);
+/// let before = Arc::new(RwLock::new(Map::new(view.clone(), vec![], None)));
+/// let after = Arc::new(RwLock::new(Map::new(view, vec![], None)));
+///
+/// let mut sync = ViewSync::new();
+/// sync.add_map(before.clone(), ViewRelation::identity());
+/// sync.add_map(after.clone(), ViewRelation::identity());
+///
+/// before.write().pan_by_pixels(10.0, 0.0);
+/// sync.sync();
+/// ```
+#[derive(Default)]
+pub struct ViewSync {
+    maps: Vec<SyncedMap>,
+}
+
+impl ViewSync {
+    /// Creates an empty group.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the map to the group.
+    ///
+    /// The view of the new map is immediately set to follow the maps that are already in the group. If the group is
+    /// empty, the view of the map becomes the common view of the group.
+    pub fn add_map(&mut self, map: Arc<RwLock<Map>>, relation: ViewRelation) {
+        let common_view = self.maps.first().map(|synced| {
+            let view = synced.map.read().view().clone();
+            synced.relation.inverse_apply(&view)
+        });
+
+        let last_view = {
+            let mut map = map.write();
+            if let Some(common_view) = common_view {
+                let view = related_view(&common_view, &relation, map.view());
+                map.set_view(view);
+            }
+            map.view().clone()
+        };
+
+        self.maps.push(SyncedMap {
+            map,
+            relation,
+            last_view: Some(last_view),
+        });
+    }
+
+    /// Removes the map from the group. Returns false if the map is not in the group.
+    pub fn remove_map(&mut self, map: &Arc<RwLock<Map>>) -> bool {
+        let count = self.maps.len();
+        self.maps.retain(|synced| !Arc::ptr_eq(&synced.map, map));
+        self.maps.len() != count
+    }
+
+    /// Changes the relation of the map to the common view of the group. Returns false if the map is not in the group.
+    ///
+    /// The view of the map is immediately changed to follow the common view with the new relation, while the views of
+    /// other maps in the group stay the same.
+    pub fn set_relation(&mut self, map: &Arc<RwLock<Map>>, relation: ViewRelation) -> bool {
+        let Some(index) = self
+            .maps
+            .iter()
+            .position(|synced| Arc::ptr_eq(&synced.map, map))
+        else {
+            return false;
+        };
+
+        let common_view = self
+            .maps
+            .iter()
+            .enumerate()
+            .find(|(other_index, _)| *other_index != index)
+            .map(|(_, synced)| {
+                let view = synced.map.read().view().clone();
+                synced.relation.inverse_apply(&view)
+            });
+
+        let synced = &mut self.maps[index];
+        synced.relation = relation;
+        if let Some(common_view) = common_view {
+            let mut map = synced.map.write();
+            let view = related_view(&common_view, &relation, map.view());
+            map.set_view(view);
+            synced.last_view = Some(map.view().clone());
+        }
+
+        true
+    }
+
+    /// Number of maps in the group.
+    pub fn len(&self) -> usize {
+        self.maps.len()
+    }
+
+    /// Returns true if there are no maps in the group.
+    pub fn is_empty(&self) -> bool {
+        self.maps.is_empty()
+    }
+
+    /// Propagates the view of the changed map to all other maps of the group.
+    ///
+    /// If several maps were changed since the last call, the first one added to the group wins. Returns true if the
+    /// views of the maps were updated.
+    pub fn sync(&mut self) -> bool {
+        let source = self.maps.iter().enumerate().find_map(|(index, synced)| {
+            let view = synced.map.read().view().clone();
+            (synced.last_view.as_ref() != Some(&view)).then_some((index, view))
+        });
+
+        let Some((source_index, source_view)) = source else {
+            return false;
+        };
+
+        let common_view = self.maps[source_index].relation.inverse_apply(&source_view);
+        for (index, synced) in self.maps.iter_mut().enumerate() {
+            if index == source_index {
+                synced.last_view = Some(source_view.clone());
+                continue;
+            }
+
+            let mut map = synced.map.write();
+            let view = related_view(&common_view, &synced.relation, map.view());
+            map.set_view(view);
+            synced.last_view = Some(map.view().clone());
+        }
+
+        true
+    }
+}
+
+/// Returns the view of a map in the group for the given common view, keeping the size of the current map view.
+fn related_view(common_view: &MapView, relation: &ViewRelation, current: &MapView) -> MapView {
+    relation
+        .apply(common_view)
+        .with_size(current.size())
+        .with_dpi_scale_factor(current.dpi_scale_factor())
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+    use galileo_types::cartesian::{Point2d, Size};
+
+    use super::*;
+
+    fn map(position: Point2d) -> Arc<RwLock<Map>> {
+        let view = MapView::new_projected(&position, 1.0).with_size(Size::new(100.0, 100.0));
+        Arc::new(RwLock::new(Map::new(view, vec![], None)))
+    }
+
+    fn center(map: &Arc<RwLock<Map>>) -> Point2d {
+        map.read()
+            .view()
+            .screen_to_map(Point2d::new(50.0, 50.0))
+            .expect("valid view")
+    }
+
+    #[test]
+    fn views_are_synchronized() {
+        let first = map(Point2d::new(0.0, 0.0));
+        let second = map(Point2d::new(500.0, 500.0));
+
+        let mut sync = ViewSync::new();
+        sync.add_map(first.clone(), ViewRelation::identity());
+        sync.add_map(
+            second.clone(),
+            ViewRelation::new(Vector2::new(10.0, 0.0), 2.0),
+        );
+        assert!(!sync.sync());
+
+        // The second map follows the first one on adding
+        assert_abs_diff_eq!(center(&second), Point2d::new(10.0, 0.0), epsilon = 1e-9);
+        assert_abs_diff_eq!(second.read().view().resolution(), 2.0);
+
+        first.write().set_view(
+            MapView::new_projected(&Point2d::new(100.0, 100.0), 3.0)
+                .with_size(Size::new(100.0, 100.0))
+                .with_rotation_z(0.5),
+        );
+        assert!(sync.sync());
+        assert_abs_diff_eq!(center(&second), Point2d::new(110.0, 100.0), epsilon = 1e-9);
+        assert_abs_diff_eq!(second.read().view().resolution(), 6.0);
+        assert_abs_diff_eq!(second.read().view().rotation_z(), 0.5);
+        assert!(!sync.sync());
+
+        // Changes are propagated in the other direction as well
+        second.write().set_view(
+            MapView::new_projected(&Point2d::new(20.0, 0.0), 4.0)
+                .with_size(Size::new(100.0, 100.0)),
+        );
+        assert!(sync.sync());
+        assert_abs_diff_eq!(center(&first), Point2d::new(10.0, 0.0), epsilon = 1e-9);
+        assert_abs_diff_eq!(first.read().view().resolution(), 2.0);
+
+        assert!(sync.remove_map(&second));
+        assert!(!sync.remove_map(&second));
+        assert_eq!(sync.len(), 1);
+    }
+
+    #[test]
+    fn set_relation_moves_only_related_map() {
+        let first = map(Point2d::new(0.0, 0.0));
+        let second = map(Point2d::new(0.0, 0.0));
+
+        let mut sync = ViewSync::new();
+        sync.add_map(first.clone(), ViewRelation::identity());
+        sync.add_map(second.clone(), ViewRelation::identity());
+
+        assert!(sync.set_relation(&second, ViewRelation::new(Vector2::new(10.0, 0.0), 2.0)));
+        assert_abs_diff_eq!(center(&second), Point2d::new(10.0, 0.0), epsilon = 1e-9);
+        assert_abs_diff_eq!(second.read().view().resolution(), 2.0);
+
+        // The common view is not changed by the new relation
+        assert!(!sync.sync());
+        assert_abs_diff_eq!(center(&first), Point2d::new(0.0, 0.0), epsilon = 1e-9);
+        assert_abs_diff_eq!(first.read().view().resolution(), 1.0);
+
+        assert!(!sync.set_relation(&map(Point2d::new(0.0, 0.0)), ViewRelation::identity()));
+    }
+}
@@ -1,7 +1,31 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, RecvError, RecvTimeoutError, Sender, TryRecvError};
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+
 /// Messenger used to notify application when the map requires update.
 pub trait Messenger: Send + Sync {
     /// Notifies the application that the map requires an update.
     fn request_redraw(&self);
+
+    /// Notifies the application that the rotation of the map view was changed, e.g. to update a compass indicator.
+    ///
+    /// Called on every change of the rotation, including every frame of an animation. Angles are given in radians
+    /// (see [`MapView::rotation_x`](crate::MapView::rotation_x) and
+    /// [`MapView::rotation_z`](crate::MapView::rotation_z)). Default implementation does nothing.
+    fn rotation_changed(&self, _rotation_x: f64, _rotation_z: f64) {}
+}
+
+impl<T: Messenger + ?Sized> Messenger for Arc<T> {
+    fn request_redraw(&self) {
+        (**self).request_redraw()
+    }
+
+    fn rotation_changed(&self, rotation_x: f64, rotation_z: f64) {
+        (**self).rotation_changed(rotation_x, rotation_z)
+    }
 }
 
 /// Empty struct used for generic disambiguation.
@@ -11,3 +35,184 @@ impl Messenger for DummyMessenger {
         // do nothing
     }
 }
+
+/// Pair of messengers forwards all notifications to both of them, e.g. to redraw the window and to notify the
+/// application at the same time.
+impl<A: Messenger, B: Messenger> Messenger for (A, B) {
+    fn request_redraw(&self) {
+        self.0.request_redraw();
+        self.1.request_redraw();
+    }
+
+    fn rotation_changed(&self, rotation_x: f64, rotation_z: f64) {
+        self.0.rotation_changed(rotation_x, rotation_z);
+        self.1.rotation_changed(rotation_x, rotation_z);
+    }
+}
+
+/// Notification of the map sent by a [`ChannelMessenger`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MapEvent {
+    /// The map requires an update (see [`Messenger::request_redraw`]).
+    Redraw,
+    /// The rotation of the map view was changed (see [`Messenger::rotation_changed`]). The angles are the latest
+    /// values at the moment the event is received.
+    RotationChanged {
+        /// Rotation around *X* axis (tilt) in radians.
+        rotation_x: f64,
+        /// Rotation around *Z* axis in radians.
+        rotation_z: f64,
+    },
+}
+
+#[derive(Debug, Default)]
+struct PendingEvents {
+    redraw: AtomicBool,
+    rotation: Mutex<Option<(f64, f64)>>,
+}
+
+/// Messenger that sends notifications of the map and its layers as [`MapEvent`]s into a channel, for applications
+/// built around a message loop (e.g. an ECS or an Elm-style architecture).
+///
+/// The events are coalesced: while an event of some kind is waiting in the channel, new notifications of the same
+/// kind are not sent again, so a burst of redraw requests (e.g. many tiles loaded at once) results in a single
+/// [`MapEvent::Redraw`].
+///
+/// ```
+/// use galileo::{ChannelMessenger, MapEvent, Messenger};
+///
+/// let (messenger, receiver) = ChannelMessenger::channel();
+/// messenger.request_redraw();
+/// messenger.request_redraw();
+///
+/// assert_eq!(receiver.try_iter().collect::<Vec<_>>(), vec![MapEvent::Redraw]);
+/// ```
+///
+/// The messenger can be installed to the map and all its layers with
+/// [`Map::install_messenger`](crate::Map::install_messenger) or [`MapBuilder::with_messenger`](crate::MapBuilder).
+#[derive(Debug, Clone)]
+pub struct ChannelMessenger {
+    sender: Sender<MapEvent>,
+    pending: Arc<PendingEvents>,
+}
+
+/// Receiving side of a [`ChannelMessenger`].
+#[derive(Debug)]
+pub struct MapEventReceiver {
+    receiver: Receiver<MapEvent>,
+    pending: Arc<PendingEvents>,
+}
+
+impl ChannelMessenger {
+    /// Creates a new messenger and the receiver of its events.
+    pub fn channel() -> (Self, MapEventReceiver) {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let pending = Arc::new(PendingEvents::default());
+        (
+            Self {
+                sender,
+                pending: pending.clone(),
+            },
+            MapEventReceiver { receiver, pending },
+        )
+    }
+
+    fn send(&self, event: MapEvent) {
+        // The receiver being dropped means the application is not interested in the events anymore
+        let _ = self.sender.send(event);
+    }
+}
+
+impl Messenger for ChannelMessenger {
+    fn request_redraw(&self) {
+        if !self.pending.redraw.swap(true, Ordering::AcqRel) {
+            self.send(MapEvent::Redraw);
+        }
+    }
+
+    fn rotation_changed(&self, rotation_x: f64, rotation_z: f64) {
+        let is_pending = self
+            .pending
+            .rotation
+            .lock()
+            .replace((rotation_x, rotation_z))
+            .is_some();
+        if !is_pending {
+            self.send(MapEvent::RotationChanged {
+                rotation_x,
+                rotation_z,
+            });
+        }
+    }
+}
+
+impl MapEventReceiver {
+    /// Waits for the next event. Returns an error if all messengers are dropped.
+    pub fn recv(&self) -> Result<MapEvent, RecvError> {
+        self.receiver.recv().map(|event| self.take(event))
+    }
+
+    /// Waits for the next event for at most `timeout`.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<MapEvent, RecvTimeoutError> {
+        self.receiver
+            .recv_timeout(timeout)
+            .map(|event| self.take(event))
+    }
+
+    /// Returns the next event if there is one, without blocking.
+    pub fn try_recv(&self) -> Result<MapEvent, TryRecvError> {
+        self.receiver.try_recv().map(|event| self.take(event))
+    }
+
+    /// Iterates over the events waiting in the channel without blocking.
+    pub fn try_iter(&self) -> impl Iterator<Item = MapEvent> + '_ {
+        self.receiver.try_iter().map(|event| self.take(event))
+    }
+
+    /// Marks the event as received, so that the next notification of the same kind is sent again.
+    fn take(&self, event: MapEvent) -> MapEvent {
+        match event {
+            MapEvent::Redraw => {
+                self.pending.redraw.store(false, Ordering::Release);
+                event
+            }
+            MapEvent::RotationChanged { .. } => match self.pending.rotation.lock().take() {
+                Some((rotation_x, rotation_z)) => MapEvent::RotationChanged {
+                    rotation_x,
+                    rotation_z,
+                },
+                None => event,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn channel_messenger_coalesces_events() {
+        let (messenger, receiver) = ChannelMessenger::channel();
+        let layer_messenger: Box<dyn Messenger> = Box::new(messenger.clone());
+
+        messenger.request_redraw();
+        layer_messenger.request_redraw();
+        messenger.rotation_changed(0.0, 0.1);
+        messenger.rotation_changed(0.0, 0.2);
+
+        assert_eq!(receiver.try_recv(), Ok(MapEvent::Redraw));
+        assert_eq!(
+            receiver.try_recv(),
+            Ok(MapEvent::RotationChanged {
+                rotation_x: 0.0,
+                rotation_z: 0.2
+            })
+        );
+        assert!(receiver.try_recv().is_err());
+
+        // After the event is received, the next one is sent again
+        layer_messenger.request_redraw();
+        assert_eq!(receiver.try_recv(), Ok(MapEvent::Redraw));
+    }
+}
@@ -0,0 +1,384 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use galileo_types::cartesian::Size;
+use parking_lot::{Mutex, RwLock};
+use winit::application::ApplicationHandler;
+use winit::event::WindowEvent;
+use winit::event_loop::{ActiveEventLoop, EventLoop, EventLoopProxy};
+use winit::window::{Window, WindowAttributes, WindowId};
+
+use crate::control::{EventProcessor, MapController};
+use crate::map::Map;
+use crate::render::{WgpuContext, WgpuRenderer};
+use crate::winit::{WinitInputHandler, WinitMessenger};
+
+type SharedContext = Arc<futures_intrusive::sync::Mutex<Option<WgpuContext>>>;
+
+/// Description of a window showing a map, that can be opened with [`MultiWindowMap`].
+pub struct MapWindow {
+    map: Arc<RwLock<Map>>,
+    attributes: WindowAttributes,
+    event_processor: EventProcessor,
+}
+
+impl MapWindow {
+    /// Creates a window showing the given map with the default window attributes.
+    ///
+    /// The window handles user input with the default [`MapController`].
+    pub fn new(map: Arc<RwLock<Map>>) -> Self {
+        let mut event_processor = EventProcessor::default();
+        event_processor.add_named_handler(
+            EventProcessor::MAP_CONTROLLER,
+            MapController::default(),
+            EventProcessor::LOW_PRIORITY,
+        );
+
+        Self {
+            map,
+            attributes: Window::default_attributes(),
+            event_processor,
+        }
+    }
+
+    /// Sets attributes (title, size, position etc.) of the window.
+    pub fn with_attributes(mut self, attributes: WindowAttributes) -> Self {
+        self.attributes = attributes;
+        self
+    }
+
+    /// Sets the event processor that handles user input in the window.
+    pub fn with_event_processor(mut self, event_processor: EventProcessor) -> Self {
+        self.event_processor = event_processor;
+        self
+    }
+
+    /// Map shown in the window.
+    pub fn map(&self) -> &Arc<RwLock<Map>> {
+        &self.map
+    }
+}
+
+/// Handle that can be used to open new map windows of a running [`MultiWindowMap`], e.g. from an event handler.
+#[derive(Clone)]
+pub struct WindowOpener {
+    pending: Arc<Mutex<Vec<MapWindow>>>,
+    proxy: EventLoopProxy<()>,
+}
+
+impl WindowOpener {
+    /// Opens a new window. The window is created asynchronously by the event loop.
+    pub fn open(&self, window: MapWindow) {
+        self.pending.lock().push(window);
+        if self.proxy.send_event(()).is_err() {
+            log::warn!("Cannot open a map window: the event loop is closed");
+        }
+    }
+}
+
+struct OpenWindow {
+    window: Arc<Window>,
+    map: Arc<RwLock<Map>>,
+    backend: Arc<RwLock<Option<WgpuRenderer>>>,
+    event_processor: EventProcessor,
+    input_handler: WinitInputHandler,
+    is_minimized: bool,
+    is_occluded: bool,
+}
+
+impl OpenWindow {
+    /// Pauses the map while the window is not visible or there is no renderer to draw it with.
+    fn update_paused(&self) {
+        let is_hidden = self.is_minimized || self.is_occluded || self.backend.read().is_none();
+        let mut map = self.map.write();
+        if map.is_paused() != is_hidden {
+            map.set_paused(is_hidden);
+        }
+    }
+}
+
+/// Runs several windows, each showing its own [`Map`], on one event loop.
+///
+/// All windows are rendered with the same `wgpu` device, each to its own surface. Window events are routed to the
+/// map of the window they were sent to, so the maps are fully independent. Views of the maps can be linked with
+/// [`ViewSync`](crate::ViewSync) if needed.
+///
+/// New windows can be opened while the event loop is running with a [`WindowOpener`], e.g. to show a part of the
+/// application map in a detached window. The event loop exits when the last window is closed.
+///
+/// ```no_run
+/// use std::sync::Arc;
+///
+/// use galileo::{Map, MapView, MapWindow, MultiWindowMap};
+/// use galileo::galileo_types::geo::impls::GeoPoint2d;
+/// use galileo::galileo_types::geo::NewGeoPoint;
+/// use parking_lot::RwLock;
+///
+/// let view = MapView::new(&GeoPoint2d::latlon(52.0, 13.0), 10.0);
+/// let mut app = MultiWindowMap::new();
+/// app.open(MapWindow::new(Arc::new(RwLock::new(Map::new(view.clone(), vec![], None)))));
+/// app.open(MapWindow::new(Arc::new(RwLock::new(Map::new(view, vec![], None)))));
+/// app.run();
+/// ```
+pub struct MultiWindowMap {
+    windows: HashMap<WindowId, OpenWindow>,
+    pending: Arc<Mutex<Vec<MapWindow>>>,
+    context: SharedContext,
+    event_loop: Option<EventLoop<()>>,
+}
+
+impl Default for MultiWindowMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MultiWindowMap {
+    /// Creates a new instance with a default event loop.
+    pub fn new() -> Self {
+        Self::with_event_loop(EventLoop::new().expect("Failed to create event loop."))
+    }
+
+    /// Creates a new instance running on the given event loop.
+    pub fn with_event_loop(event_loop: EventLoop<()>) -> Self {
+        event_loop.set_control_flow(winit::event_loop::ControlFlow::Wait);
+        Self {
+            windows: HashMap::new(),
+            pending: Default::default(),
+            context: Arc::new(futures_intrusive::sync::Mutex::new(None, false)),
+            event_loop: Some(event_loop),
+        }
+    }
+
+    /// Opens a new window when the event loop is started.
+    pub fn open(&mut self, window: MapWindow) {
+        self.pending.lock().push(window);
+    }
+
+    /// Returns a handle to open new windows while the event loop is running.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called after the event loop is started.
+    pub fn window_opener(&self) -> WindowOpener {
+        let event_loop = self
+            .event_loop
+            .as_ref()
+            .expect("window opener must be created before the event loop is started");
+        WindowOpener {
+            pending: self.pending.clone(),
+            proxy: event_loop.create_proxy(),
+        }
+    }
+
+    /// Returns the map shown in the window with the given id.
+    pub fn map(&self, window_id: WindowId) -> Option<&Arc<RwLock<Map>>> {
+        self.windows.get(&window_id).map(|window| &window.map)
+    }
+
+    /// Ids of all open windows.
+    pub fn window_ids(&self) -> impl Iterator<Item = WindowId> + '_ {
+        self.windows.keys().copied()
+    }
+
+    /// Runs the main event loop.
+    pub fn run(&mut self) {
+        let event_loop = self.event_loop.take().expect("event loop is not created");
+        event_loop.run_app(self).expect("failed to run application");
+    }
+
+    fn open_pending(&mut self, event_loop: &ActiveEventLoop) {
+        let pending = std::mem::take(&mut *self.pending.lock());
+        for MapWindow {
+            map,
+            attributes,
+            event_processor,
+        } in pending
+        {
+            let window = match event_loop.create_window(attributes) {
+                Ok(window) => Arc::new(window),
+                Err(err) => {
+                    log::error!("Failed to create a map window: {err}");
+                    continue;
+                }
+            };
+
+            let messenger = WinitMessenger::new(window.clone());
+            map.write().install_messenger(messenger);
+
+            let open_window = OpenWindow {
+                window: window.clone(),
+                map,
+                backend: Arc::new(RwLock::new(None)),
+                event_processor,
+                input_handler: WinitInputHandler::default(),
+                is_minimized: false,
+                is_occluded: false,
+            };
+            open_window.update_paused();
+            self.init_renderer(&open_window);
+            self.windows.insert(window.id(), open_window);
+        }
+    }
+
+    /// Creates the renderer for the window. The first renderer creates the device, all others share it.
+    fn init_renderer(&self, open_window: &OpenWindow) {
+        let window = open_window.window.clone();
+        let backend = open_window.backend.clone();
+        let map = open_window.map.clone();
+        let context = self.context.clone();
+
+        crate::async_runtime::spawn(async move {
+            let size = window.inner_size();
+            let renderer_size = Size::new(size.width, size.height);
+
+            // Lock is held during device creation so that all windows wait for the first device
+            let mut context = context.lock().await;
+            let renderer = match &*context {
+                Some(context) => WgpuRenderer::new_with_context_and_window(
+                    context,
+                    window.clone(),
+                    renderer_size,
+                ),
+                None => {
+                    let renderer =
+                        WgpuRenderer::new_with_window(window.clone(), renderer_size).await;
+                    *context = renderer
+                        .as_ref()
+                        .and_then(|renderer| renderer.context().cloned());
+                    renderer
+                }
+            };
+            drop(context);
+
+            let Some(mut renderer) = renderer else {
+                log::error!("Failed to init renderer for window {:?}", window.id());
+                return;
+            };
+
+            let new_size = window.inner_size();
+            if new_size != size {
+                renderer.resize(Size::new(new_size.width, new_size.height));
+            }
+
+            *backend.write() = Some(renderer);
+            let mut map = map.write();
+            map.set_size(Size::new(new_size.width as f64, new_size.height as f64));
+            map.set_dpi_scale_factor(window.scale_factor());
+            map.set_paused(new_size.width == 0 || new_size.height == 0);
+            drop(map);
+            window.request_redraw();
+        });
+    }
+}
+
+impl ApplicationHandler for MultiWindowMap {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        // Renderers are dropped when the application is suspended
+        for open_window in self.windows.values() {
+            if open_window.backend.read().is_none() {
+                self.init_renderer(open_window);
+            }
+        }
+
+        self.open_pending(event_loop);
+    }
+
+    fn user_event(&mut self, event_loop: &ActiveEventLoop, _event: ()) {
+        self.open_pending(event_loop);
+    }
+
+    fn suspended(&mut self, _event_loop: &ActiveEventLoop) {
+        for open_window in self.windows.values() {
+            *open_window.backend.write() = None;
+            open_window.map.write().set_paused(true);
+        }
+    }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        let mut next_update_in = None;
+        for open_window in self.windows.values_mut() {
+            let mut map = open_window.map.write();
+            open_window.event_processor.update(&mut map);
+            map.animate();
+
+            if let Some(delay) = open_window.event_processor.next_update_in() {
+                next_update_in = Some(next_update_in.map_or(delay, |d: Duration| d.min(delay)));
+            }
+        }
+
+        // Wake up the event loop when a pending gesture (e.g. long press) in any of the windows is due
+        let control_flow = match next_update_in {
+            Some(delay) => {
+                winit::event_loop::ControlFlow::WaitUntil(web_time::Instant::now() + delay)
+            }
+            None => winit::event_loop::ControlFlow::Wait,
+        };
+        event_loop.set_control_flow(control_flow);
+    }
+
+    fn window_event(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        window_id: WindowId,
+        event: WindowEvent,
+    ) {
+        if let WindowEvent::CloseRequested = event {
+            self.windows.remove(&window_id);
+            if self.windows.is_empty() && self.pending.lock().is_empty() {
+                event_loop.exit();
+            }
+            return;
+        }
+
+        let Some(open_window) = self.windows.get_mut(&window_id) else {
+            return;
+        };
+
+        match event {
+            WindowEvent::Resized(size) => {
+                // Nothing can be rendered to a zero size window, so the map is paused until the window is restored,
+                // keeping the last non-zero size
+                open_window.is_minimized = size.width == 0 || size.height == 0;
+                if !open_window.is_minimized {
+                    if let Some(backend) = open_window.backend.write().as_mut() {
+                        backend.resize(Size::new(size.width, size.height));
+                        open_window
+                            .map
+                            .write()
+                            .set_size(Size::new(size.width as f64, size.height as f64));
+                    }
+                }
+
+                open_window.update_paused();
+            }
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                open_window.map.write().set_dpi_scale_factor(scale_factor);
+            }
+            WindowEvent::Occluded(occluded) => {
+                open_window.is_occluded = occluded;
+                open_window.update_paused();
+            }
+            WindowEvent::RedrawRequested => {
+                if let Some(backend) = open_window.backend.read().as_ref() {
+                    let map = open_window.map.read();
+                    if map.is_paused() {
+                        return;
+                    }
+
+                    map.load_layers();
+                    if let Err(err) = backend.render(&map) {
+                        log::error!("Render error: {err:?}");
+                    }
+                }
+            }
+            other => {
+                if let Some(raw_event) = open_window.input_handler.process_user_input(&other, 1.0) {
+                    let mut map = open_window.map.write();
+                    open_window.event_processor.handle(raw_event, &mut map);
+                }
+            }
+        }
+    }
+}
@@ -4,11 +4,12 @@ use std::sync::Arc;
 
 use galileo_types::geo::impls::GeoPoint2d;
 
+use crate::control::InputFilter;
 use crate::layer::data_provider::{FileCacheController, UrlImageProvider, UrlSource};
 use crate::layer::vector_tile_layer::style::VectorTileStyle;
 use crate::layer::vector_tile_layer::tile_provider::loader::WebVtLoader;
 use crate::layer::vector_tile_layer::tile_provider::VectorTileProvider;
-use crate::layer::{RasterTileLayer, VectorTileLayer};
+use crate::layer::{Basemap, RasterTileLayer, VectorBasemap, VectorTileLayer};
 use crate::platform::native::vt_processor::ThreadVtProcessor;
 use crate::platform::{PlatformService, PlatformServiceImpl};
 use crate::render::render_bundle::tessellating::TessellatingRenderBundle;
@@ -28,6 +29,10 @@ impl MapBuilder {
             window: None,
             event_loop: None,
             size: None,
+            messenger: None,
+            input_filter: InputFilter::default(),
+            file_drop_handler: None,
+            horizon: None,
         }
     }
 
@@ -47,6 +52,13 @@ impl MapBuilder {
         RasterTileLayer::new(tile_scheme, tile_provider, None)
     }
 
+    /// Create a new raster tile layer showing one of the well-known basemaps.
+    pub fn create_basemap_layer(
+        basemap: &Basemap,
+    ) -> RasterTileLayer<UrlImageProvider<TileIndex, FileCacheController>> {
+        Self::create_raster_tile_layer(basemap.url_source(), basemap.tile_schema())
+    }
+
     /// Add a new raster layer to the layer list.
     pub fn with_raster_tiles(
         mut self,
@@ -70,6 +82,11 @@ impl MapBuilder {
         VectorTileLayer::new(tile_provider, style, tile_schema)
     }
 
+    /// Create a new vector tile layer showing one of the well-known vector basemaps with its default style.
+    pub fn create_vector_basemap_layer(basemap: &VectorBasemap) -> VectorTileLayer {
+        Self::create_vector_tile_layer(basemap.url_source(), basemap.tile_schema(), basemap.style())
+    }
+
     /// Returns a vector tile provider.
     pub fn create_vector_tile_provider(
         tile_source: impl UrlSource<TileIndex> + 'static,
@@ -9,7 +9,7 @@ use parking_lot::RwLock;
 
 use crate::layer::vector_tile_layer::style::VectorTileStyle;
 use crate::layer::vector_tile_layer::tile_provider::processor::{
-    TileProcessingError, VectorTileProcessor,
+    transform_tile, TileProcessingError, VectorTileProcessor, VtFeatureTransform,
 };
 use crate::layer::vector_tile_layer::tile_provider::{VtProcessor, VtStyleId};
 use crate::render::render_bundle::RenderBundle;
@@ -21,6 +21,7 @@ pub struct ThreadVtProcessor {
     tile_schema: TileSchema,
     empty_bundle: RenderBundle,
     styles: RwLock<HashMap<VtStyleId, Arc<VectorTileStyle>>>,
+    feature_transform: Option<Arc<dyn VtFeatureTransform>>,
 }
 
 impl ThreadVtProcessor {
@@ -30,8 +31,15 @@ impl ThreadVtProcessor {
             tile_schema,
             empty_bundle,
             styles: Default::default(),
+            feature_transform: None,
         }
     }
+
+    /// Sets the hook that modifies the features of every tile before the tile is rendered.
+    pub fn with_feature_transform(mut self, transform: impl VtFeatureTransform + 'static) -> Self {
+        self.feature_transform = Some(Arc::new(transform));
+        self
+    }
 }
 
 #[async_trait::async_trait]
@@ -65,14 +73,19 @@ impl VectorTileProcessor for ThreadVtProcessor {
 
         let mut bundle = self.empty_bundle.clone();
         let tile_schema = self.tile_schema.clone();
+        let feature_transform = self.feature_transform.clone();
 
         static COUNTER: AtomicUsize = AtomicUsize::new(0);
 
-        tokio::task::spawn_blocking(move || {
+        crate::async_runtime::spawn_blocking(move || {
             log::debug!(
                 "Added worker: {}",
                 COUNTER.fetch_add(1, Ordering::Relaxed) + 1
             );
+            let tile = match feature_transform {
+                Some(transform) => Arc::new(transform_tile(&tile, index, transform.as_ref())),
+                None => tile,
+            };
             let result = match VtProcessor::prepare(&tile, &mut bundle, index, &style, &tile_schema)
             {
                 Ok(()) => Ok(bundle),
@@ -0,0 +1,85 @@
+//! Heading of the device from the compass of the browser.
+
+use js_sys::Reflect;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{DeviceOrientationEvent, Event, Window};
+
+use crate::error::GalileoError;
+
+const ABSOLUTE_EVENT: &str = "deviceorientationabsolute";
+const RELATIVE_EVENT: &str = "deviceorientation";
+
+/// Listens to the orientation events of the browser and reports the compass heading of the device, e.g. to be fed
+/// into a [`HeadingController`](crate::control::HeadingController).
+///
+/// The listener is removed when the value is dropped. The heading is not corrected for the orientation of the
+/// screen, so the application should lock the screen in the portrait orientation or correct the heading itself.
+///
+/// Note that some browsers (e.g. Safari on iOS) only send orientation events after the user gives a permission,
+/// which must be requested by the application from a user gesture handler.
+pub struct DeviceHeading {
+    window: Window,
+    event_name: &'static str,
+    callback: Closure<dyn FnMut(Event)>,
+}
+
+impl DeviceHeading {
+    /// Starts listening to the orientation events. `on_heading` is called with the heading in radians clockwise
+    /// from the north every time the browser reports a new absolute orientation of the device.
+    pub fn listen(mut on_heading: impl FnMut(f64) + 'static) -> Result<Self, GalileoError> {
+        let window =
+            web_sys::window().ok_or_else(|| GalileoError::Wasm(Some("no window".into())))?;
+
+        // Chrome reports the compass heading only with the absolute events, other browsers with the normal ones
+        let event_name =
+            if Reflect::has(&window, &JsValue::from_str("ondeviceorientationabsolute"))? {
+                ABSOLUTE_EVENT
+            } else {
+                RELATIVE_EVENT
+            };
+
+        let callback: Closure<dyn FnMut(Event)> = Closure::new(move |event: Event| {
+            if let Some(heading) = compass_heading(&event) {
+                on_heading(heading.to_radians());
+            }
+        });
+        window.add_event_listener_with_callback(event_name, callback.as_ref().unchecked_ref())?;
+
+        Ok(Self {
+            window,
+            event_name,
+            callback,
+        })
+    }
+}
+
+impl Drop for DeviceHeading {
+    fn drop(&mut self) {
+        if let Err(err) = self.window.remove_event_listener_with_callback(
+            self.event_name,
+            self.callback.as_ref().unchecked_ref(),
+        ) {
+            log::warn!("Failed to remove device orientation listener: {err:?}");
+        }
+    }
+}
+
+/// Compass heading in degrees from the orientation event, if the event has an absolute orientation.
+fn compass_heading(event: &Event) -> Option<f64> {
+    // Safari reports the heading in a non-standard property
+    if let Some(heading) = Reflect::get(event, &JsValue::from_str("webkitCompassHeading"))
+        .ok()
+        .and_then(|value| value.as_f64())
+    {
+        return Some(heading);
+    }
+
+    let event = event.dyn_ref::<DeviceOrientationEvent>()?;
+    if !event.absolute() && event.type_() != ABSOLUTE_EVENT {
+        return None;
+    }
+
+    // Alpha is the rotation of the device counterclockwise around the vertical axis
+    event.alpha().map(|alpha| (360.0 - alpha).rem_euclid(360.0))
+}
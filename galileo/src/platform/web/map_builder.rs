@@ -8,14 +8,14 @@ use parking_lot::RwLock;
 use wasm_bindgen::prelude::wasm_bindgen;
 use winit::event_loop::{ControlFlow, EventLoop};
 
-use crate::control::{EventProcessor, MapController};
+use crate::control::{EventProcessor, InputFilter, MapController};
 use crate::galileo_map::{GalileoMap, MapBuilder};
 use crate::layer::data_provider::dummy::DummyCacheController;
 use crate::layer::data_provider::{UrlImageProvider, UrlSource};
 use crate::layer::vector_tile_layer::style::VectorTileStyle;
 use crate::layer::vector_tile_layer::tile_provider::loader::WebVtLoader;
 use crate::layer::vector_tile_layer::tile_provider::VectorTileProvider;
-use crate::layer::{RasterTileLayer, VectorTileLayer};
+use crate::layer::{Basemap, RasterTileLayer, VectorBasemap, VectorTileLayer};
 use crate::platform::web::vt_processor::WebWorkerVtProcessor;
 use crate::platform::web::web_workers::WebWorkerService;
 use crate::platform::{PlatformService, PlatformServiceImpl};
@@ -33,6 +33,11 @@ impl MapBuilder {
         RasterTileLayer::new(tile_scheme, tile_provider, None)
     }
 
+    /// Creates a raster tile layer showing one of the well-known basemaps.
+    pub fn create_basemap_layer(basemap: &Basemap) -> RasterTileLayer<UrlImageProvider<TileIndex>> {
+        Self::create_raster_tile_layer(basemap.url_source(), basemap.tile_schema())
+    }
+
     /// Create a new vector tile layer.
     pub fn create_vector_tile_layer(
         tile_source: impl UrlSource<TileIndex> + 'static,
@@ -43,6 +48,11 @@ impl MapBuilder {
         VectorTileLayer::new(tile_provider, style, tile_schema)
     }
 
+    /// Create a new vector tile layer showing one of the well-known vector basemaps with its default style.
+    pub fn create_vector_basemap_layer(basemap: &VectorBasemap) -> VectorTileLayer {
+        Self::create_vector_tile_layer(basemap.url_source(), basemap.tile_schema(), basemap.style())
+    }
+
     /// Create a new vector tile provider.
     pub fn create_vector_tile_provider(
         tile_source: impl UrlSource<TileIndex> + 'static,
@@ -78,6 +88,10 @@ impl MapBuilder {
             window: None,
             event_loop: None,
             size: None,
+            messenger: None,
+            input_filter: InputFilter::default(),
+            file_drop_handler: None,
+            horizon: None,
             dom_container: None,
         }
     }
@@ -101,11 +115,16 @@ impl MapBuilder {
         for handler in self.event_handlers.drain(..) {
             event_processor.add_handler(handler);
         }
-        event_processor.add_handler(MapController::default());
+        event_processor.add_named_handler(
+            EventProcessor::MAP_CONTROLLER,
+            MapController::default(),
+            EventProcessor::LOW_PRIORITY,
+        );
 
         let width = container.offset_width() as u32;
         let height = container.offset_height() as u32;
         let size = Size::new(width, height);
+        let app_messenger = self.messenger.clone();
 
         GalileoMap {
             window: None,
@@ -115,6 +134,9 @@ impl MapBuilder {
             input_handler,
             event_loop: Some(event_loop),
             init_size: size,
+            is_minimized: false,
+            is_occluded: false,
+            app_messenger,
             dom_container: Some(container),
         }
     }
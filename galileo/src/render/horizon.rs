@@ -0,0 +1,34 @@
+//! Sky and haze drawn at the horizon of a tilted map.
+//!
+//! When the map is tilted strongly enough, the horizon line becomes visible and the part of the screen above it is
+//! filled only with the background color. Setting [`HorizonOptions`] for a map with
+//! [`Map::set_horizon`](crate::Map::set_horizon) fills this area with the sky and blends the far edge of the map
+//! into a haze, which makes the transition between the map and the sky look natural.
+//!
+//! Horizon options belong to the map rather than to the renderer, so several maps drawn by the same renderer can
+//! each have their own sky, or none at all.
+
+use crate::Color;
+
+/// Appearance of the sky and the haze at the horizon.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct HorizonOptions {
+    /// Color of the sky far above the horizon.
+    pub sky_color: Color,
+    /// Color of the haze at the horizon line. Above the horizon the haze fades into the sky, and below it the haze
+    /// fades out over the map.
+    pub haze_color: Color,
+    /// Height of the haze gradient on each side of the horizon line in logical pixels.
+    pub haze_height: f64,
+}
+
+impl Default for HorizonOptions {
+    /// Light blue sky with a whitish haze.
+    fn default() -> Self {
+        Self {
+            sky_color: Color::rgba(135, 185, 235, 255),
+            haze_color: Color::rgba(225, 235, 245, 255),
+            haze_height: 80.0,
+        }
+    }
+}
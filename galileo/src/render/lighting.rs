@@ -0,0 +1,248 @@
+//! Simple lighting model for 3D content.
+//!
+//! Lighting is calculated per polygon: the color of a polygon is shaded according to the angle between the
+//! polygon's normal and the direction to the light source. This makes the faces of 3D objects (e.g. walls and roofs
+//! of [extruded](extrude) buildings) distinguishable even when they have the same fill color.
+//!
+//! Lighting is enabled for a feature layer with
+//! [`FeatureLayerOptions::lighting`](crate::layer::feature_layer::FeatureLayerOptions::lighting).
+//!
+//! Shading is done on the CPU when the features are tessellated, not in the shaders. So every polygon is flat-shaded
+//! with a single brightness value, and the colors are not updated when the light source changes until the features
+//! are rendered again.
+
+use galileo_types::cartesian::{CartesianPoint2d, CartesianPoint3d, Point3d};
+use galileo_types::contour::Contour;
+use galileo_types::impls::{ClosedContour, Polygon as PolygonImpl};
+use galileo_types::Polygon;
+use nalgebra::Vector3;
+
+use crate::Color;
+
+/// Single directional light source (like the sun) with an ambient light term.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Lighting {
+    /// Direction to the light source in the horizontal plane in radians, measured clockwise from the north
+    /// (positive *y* direction).
+    pub azimuth: f64,
+    /// Angle of the light source above the horizon in radians.
+    pub elevation: f64,
+    /// Portion of light that illuminates all surfaces equally, regardless of their orientation, in the range
+    /// `[0.0, 1.0]`. Surfaces facing away from the light source are drawn with this brightness.
+    pub ambient: f32,
+}
+
+impl Default for Lighting {
+    /// Light from the north-west at 45 degrees above the horizon, which is the conventional light direction in
+    /// cartography.
+    fn default() -> Self {
+        Self {
+            azimuth: 315f64.to_radians(),
+            elevation: 45f64.to_radians(),
+            ambient: 0.4,
+        }
+    }
+}
+
+impl Lighting {
+    /// Unit vector pointing from the surface towards the light source.
+    pub fn direction(&self) -> Vector3<f64> {
+        let horizontal = self.elevation.cos();
+        Vector3::new(
+            horizontal * self.azimuth.sin(),
+            horizontal * self.azimuth.cos(),
+            self.elevation.sin(),
+        )
+    }
+
+    /// Brightness of a surface with the given normal in the range `[ambient, 1.0]`.
+    pub fn brightness(&self, normal: &Vector3<f64>) -> f32 {
+        let ambient = self.ambient.clamp(0.0, 1.0);
+        let Some(normal) = normal.try_normalize(f64::EPSILON) else {
+            return 1.0;
+        };
+
+        let diffuse = normal.dot(&self.direction()).max(0.0) as f32;
+        ambient + (1.0 - ambient) * diffuse
+    }
+
+    /// Shades the color of a surface with the given normal. Alpha channel is not changed.
+    pub fn shade(&self, color: Color, normal: &Vector3<f64>) -> Color {
+        let brightness = self.brightness(normal);
+        let apply = |channel: u8| (channel as f32 * brightness).round() as u8;
+        Color::rgba(
+            apply(color.r()),
+            apply(color.g()),
+            apply(color.b()),
+            color.a(),
+        )
+    }
+
+    /// Shades the color of the polygon according to its [normal](polygon_normal).
+    pub fn shade_polygon<P, Poly>(&self, color: Color, polygon: &Poly) -> Color
+    where
+        P: CartesianPoint3d<Num = f64>,
+        Poly: Polygon,
+        Poly::Contour: Contour<Point = P>,
+    {
+        match polygon_normal(polygon) {
+            Some(normal) => self.shade(color, &normal),
+            None => color,
+        }
+    }
+}
+
+/// Calculates the normal of a planar polygon using its outer contour.
+///
+/// The direction of the normal is defined by the order of the points of the contour: for a contour going
+/// counterclockwise when looked at from the outside, the normal points outside. Horizontal polygons always get the
+/// normal pointing up, regardless of the order of points.
+///
+/// Returns `None` if the polygon is degenerate (all points lie on a single line).
+pub fn polygon_normal<P, Poly>(polygon: &Poly) -> Option<Vector3<f64>>
+where
+    P: CartesianPoint3d<Num = f64>,
+    Poly: Polygon,
+    Poly::Contour: Contour<Point = P>,
+{
+    // Newell's method works for non-convex polygons and is robust to collinear points
+    let mut normal = Vector3::zeros();
+    for segment in polygon.outer_contour().iter_segments() {
+        let (a, b) = (segment.0, segment.1);
+        normal.x += (a.y() - b.y()) * (a.z() + b.z());
+        normal.y += (a.z() - b.z()) * (a.x() + b.x());
+        normal.z += (a.x() - b.x()) * (a.y() + b.y());
+    }
+
+    let mut normal = normal.try_normalize(f64::EPSILON)?;
+    if normal.z < -1e-6 {
+        normal = -normal;
+    }
+
+    Some(normal)
+}
+
+/// Creates a 3D shape from the 2D contour by lifting it from the `base` to the `base + height` elevation.
+///
+/// Returns the polygons of the walls and the roof. The points of every polygon are ordered so that
+/// [`polygon_normal`] points outside of the shape, which is needed for walls facing away from the light source to be
+/// shaded correctly.
+pub fn extrude<P>(contour: &ClosedContour<P>, base: f64, height: f64) -> Vec<PolygonImpl<Point3d>>
+where
+    P: CartesianPoint2d<Num = f64>,
+{
+    let mut points: Vec<_> = contour.iter_points().map(|p| (p.x(), p.y())).collect();
+    if points.len() < 3 {
+        return vec![];
+    }
+
+    // Make the contour counterclockwise, so that the normals of the walls point outside
+    let double_area: f64 = (0..points.len())
+        .map(|i| {
+            let (x1, y1) = points[i];
+            let (x2, y2) = points[(i + 1) % points.len()];
+            x1 * y2 - x2 * y1
+        })
+        .sum();
+    if double_area < 0.0 {
+        points.reverse();
+    }
+
+    let top = base + height;
+    let mut polygons: Vec<_> = (0..points.len())
+        .map(|i| {
+            let (x1, y1) = points[i];
+            let (x2, y2) = points[(i + 1) % points.len()];
+            PolygonImpl::new(
+                ClosedContour::new(vec![
+                    Point3d::new(x1, y1, base),
+                    Point3d::new(x2, y2, base),
+                    Point3d::new(x2, y2, top),
+                    Point3d::new(x1, y1, top),
+                ]),
+                vec![],
+            )
+        })
+        .collect();
+
+    polygons.push(PolygonImpl::new(
+        ClosedContour::new(
+            points
+                .into_iter()
+                .map(|(x, y)| Point3d::new(x, y, top))
+                .collect(),
+        ),
+        vec![],
+    ));
+
+    polygons
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+    use galileo_types::cartesian::Point2d;
+
+    use super::*;
+
+    fn square() -> ClosedContour<Point2d> {
+        // Clockwise
+        ClosedContour::new(vec![
+            Point2d::new(0.0, 0.0),
+            Point2d::new(0.0, 1.0),
+            Point2d::new(1.0, 1.0),
+            Point2d::new(1.0, 0.0),
+        ])
+    }
+
+    #[test]
+    fn extruded_normals_point_outside() {
+        let polygons = extrude(&square(), 0.0, 10.0);
+        assert_eq!(polygons.len(), 5);
+
+        let normals: Vec<_> = polygons
+            .iter()
+            .map(|p| polygon_normal(p).expect("degenerate polygon"))
+            .collect();
+
+        // Roof
+        assert_abs_diff_eq!(normals[4].z, 1.0, epsilon = 1e-9);
+
+        // Walls point away from the center of the square
+        for (polygon, normal) in polygons.iter().zip(&normals).take(4) {
+            let p = polygon
+                .outer_contour()
+                .iter_points()
+                .next()
+                .expect("no points");
+            let q = polygon
+                .outer_contour()
+                .iter_points()
+                .nth(1)
+                .expect("no points");
+            let wall_center = Vector3::new((p.x + q.x) / 2.0 - 0.5, (p.y + q.y) / 2.0 - 0.5, 0.0);
+            assert!(normal.dot(&wall_center) > 0.0);
+            assert_abs_diff_eq!(normal.z, 0.0, epsilon = 1e-9);
+        }
+    }
+
+    #[test]
+    fn brightness() {
+        let lighting = Lighting {
+            azimuth: 0.0,
+            elevation: 0.0,
+            ambient: 0.2,
+        };
+
+        // Facing the light
+        assert_abs_diff_eq!(lighting.brightness(&Vector3::new(0.0, 1.0, 0.0)), 1.0);
+        // Facing away from the light
+        assert_abs_diff_eq!(lighting.brightness(&Vector3::new(0.0, -1.0, 0.0)), 0.2);
+
+        let shaded = lighting.shade(
+            Color::rgba(100, 100, 100, 50),
+            &Vector3::new(0.0, -1.0, 0.0),
+        );
+        assert_eq!(shaded, Color::rgba(20, 20, 20, 50));
+    }
+}
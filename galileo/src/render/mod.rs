@@ -16,11 +16,30 @@ use crate::Color;
 #[cfg(feature = "wgpu")]
 mod wgpu;
 #[cfg(feature = "wgpu")]
-pub use wgpu::WgpuRenderer;
+pub use wgpu::{LayerGpuTime, WgpuContext, WgpuRenderer};
 
+#[cfg(all(feature = "wgpu", not(target_arch = "wasm32")))]
+mod tile_renderer;
+#[cfg(all(feature = "wgpu", not(target_arch = "wasm32")))]
+pub use tile_renderer::TileRenderer;
+#[cfg(all(feature = "wgpu", not(target_arch = "wasm32")))]
+mod offscreen;
+#[cfg(all(feature = "wgpu", not(target_arch = "wasm32"), feature = "image"))]
+pub use offscreen::ImageEncoding;
+#[cfg(all(feature = "wgpu", not(target_arch = "wasm32")))]
+pub use offscreen::{OffscreenRenderer, PooledRenderer, RenderedImage};
+#[cfg(all(feature = "wgpu", not(target_arch = "wasm32")))]
+mod style_snapshot;
+#[cfg(all(feature = "wgpu", not(target_arch = "wasm32")))]
+pub use style_snapshot::StyleSnapshotRenderer;
+
+pub mod horizon;
+pub mod lighting;
 pub mod point_paint;
+pub mod post_processing;
 pub mod render_bundle;
 pub mod text;
+pub mod theme;
 
 /// Id of a rendering primitive
 #[derive(Debug, Copy, Clone, PartialEq, Hash)]
@@ -33,7 +52,9 @@ pub struct PrimitiveId(usize);
 ///    tessellation are done when a rendering primitive is added to the bundle. So to prevent frame rate drops, this can
 ///    be done in background threads or worker processes.
 /// 2. When a bundle is ready to be drawn, it must be packed with [`Canvas::pack_bundle`] method. This moves data to
-///    GPU buffers. Packed bundles cannot be modified and must be recreated in case the source `RenderBundle` changes.
+///    GPU buffers. Packed bundles must be recreated in case the primitives of the source `RenderBundle` are added or
+///    removed. Changes made to the existing primitives with [`RenderBundle::update`] or [`RenderBundle::move_point`]
+///    can be written into the packed bundle with the cheaper [`Canvas::update_packed_bundle`] method.
 /// 3. [`PackedBundle`]s can then be rendered by calling [`Canvas::draw_bundles`] method.
 ///
 /// A layer may choose to store `RenderBundles` and `PackedBundles` between redraws to skip the expensive preparation
@@ -45,6 +66,16 @@ pub trait Canvas {
     fn create_bundle(&self) -> RenderBundle;
     /// Packs a bundle to make it ready for be rendered with [`Canvas::draw_bundles`] method.
     fn pack_bundle(&self, bundle: &RenderBundle) -> Box<dyn PackedBundle>;
+    /// Writes the changes of the primitives of the `bundle` into the `packed` bundle created from it earlier, without
+    /// allocating new GPU buffers.
+    ///
+    /// Only changes that keep the layout of the bundle (see [`RenderBundle::update`] and
+    /// [`RenderBundle::move_point`]) can be applied this way. Returns `false` if the packed bundle cannot be updated,
+    /// e.g. because primitives were added to or removed from the bundle, in which case the bundle must be packed
+    /// again with [`Canvas::pack_bundle`]. The default implementation never updates packed bundles.
+    fn update_packed_bundle(&self, _packed: &dyn PackedBundle, _bundle: &RenderBundle) -> bool {
+        false
+    }
     /// Render the bundles.
     fn draw_bundles(&mut self, bundles: &[&dyn PackedBundle], options: RenderOptions);
     /// Render bundles applying the specified opacity to each of them.
@@ -66,19 +97,84 @@ pub trait PackedBundle: MaybeSend + MaybeSync {
 pub struct RenderOptions {
     /// If set to true, the primitives will be drawn using antialiasing (multisampling).
     pub antialias: bool,
+    /// The way images are combined with the content already drawn below them.
+    pub blend_mode: BlendMode,
+    /// Order of the screen referenced primitives relative to the map geometry of the drawn bundles.
+    pub screen_ref_order: ScreenRefOrder,
 }
 
 impl Default for RenderOptions {
     fn default() -> Self {
-        Self { antialias: true }
+        Self {
+            antialias: true,
+            blend_mode: BlendMode::Normal,
+            screen_ref_order: ScreenRefOrder::WithBundle,
+        }
     }
 }
 
+/// Order in which screen referenced primitives (markers, labels and other points with the size set in pixels) are
+/// drawn relative to the geometry in map coordinates (lines, polygons and images).
+///
+/// Screen referenced primitives are always drawn in the position of their layer in the layer stack: they cover the
+/// layers below and are covered by the layers above, e.g. by an overlay with UI elements. This option only controls
+/// the order between the bundles drawn by a single [`Canvas::draw_bundles`] call.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ScreenRefOrder {
+    /// Screen referenced primitives of a bundle are drawn right after the map geometry of the same bundle, so they
+    /// can be covered by the geometry of the next bundles.
+    #[default]
+    WithBundle,
+    /// Screen referenced primitives of all bundles are drawn after the map geometry of all bundles. This keeps e.g.
+    /// the labels of a tile from being covered by the polygons of the neighbouring tiles.
+    AfterBundles,
+}
+
+/// The way an image layer is combined with the layers below it.
+///
+/// Non-normal modes are useful to draw e.g. a hillshade layer together with a basemap: with
+/// [`BlendMode::Multiply`] the light areas of the hillshade keep the basemap unchanged and the dark areas darken it.
+/// These modes only change the colors of the content below the image, so over transparent areas the image is not
+/// visible.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BlendMode {
+    /// Image is drawn over the content below it, taking into account its transparency.
+    #[default]
+    Normal,
+    /// Colors of the image and the content below are multiplied, which always results in a darker color.
+    Multiply,
+    /// Inverted colors of the image and the content below are multiplied, which always results in a lighter color.
+    Screen,
+}
+
 /// Parameters to draw a polygon primitive with.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct PolygonPaint {
     /// Fill color of the polygon.
     pub color: Color,
+    /// Pattern of lines drawn over the fill.
+    #[serde(default)]
+    pub hatching: Option<Hatching>,
+}
+
+/// Pattern of parallel lines drawn over the fill of a polygon.
+///
+/// Patterns make areas distinguishable without relying on the color only, e.g. for color-blind users (see
+/// [`Theme::show_patterns`](theme::Theme::show_patterns)). Line width keeps its size on the screen, while the spacing
+/// between the lines is calculated for the resolution the polygon is added to the render bundle with, and scales
+/// together with the map. The lines are aligned to the map coordinates grid, so patterns of adjacent polygons
+/// (e.g. in neighbouring vector tiles) are continuous.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Hatching {
+    /// Color of the lines.
+    pub color: Color,
+    /// Width of the lines in pixels.
+    pub width: f64,
+    /// Distance between the lines in pixels.
+    pub spacing: f64,
+    /// Direction of the lines in radians counterclockwise from the horizontal axis.
+    #[serde(default)]
+    pub angle: f64,
 }
 
 /// Parameter to draw a line primitive with.
@@ -93,6 +189,87 @@ pub struct LinePaint {
     pub offset: f64,
     /// Type of the cap of the line.
     pub line_cap: LineCap,
+    /// Multiplier of the width and offset of the line that changes with the resolution of the map view.
+    #[serde(default)]
+    pub width_scale: Option<ResolutionScale>,
+    /// If set to true, the line is aligned to the pixel grid of the screen, so that thin horizontal and vertical lines
+    /// (e.g. grids and graticules) are drawn sharp instead of being smeared over two rows of pixels. The width of such
+    /// lines should be a whole number of pixels.
+    ///
+    /// Lines in other directions are not affected much, but can move by up to half a pixel.
+    #[serde(default)]
+    pub crisp: bool,
+}
+
+/// Multiplier of a size in pixels (e.g. line width) that changes continuously with the resolution of the map view.
+///
+/// The multiplier is set for two resolution stops. Between the stops, the logarithm of the multiplier changes
+/// linearly with the logarithm of the resolution (i.e. the zoom level), and beyond the stops the multiplier stays
+/// equal to the value at the closest stop. For example, a multiplier that is 1.0 at resolution 8.0 and 4.0 at
+/// resolution 2.0 doubles with every zoom level in.
+///
+/// The multiplier is evaluated by the GPU for every frame, so the size changes smoothly when the map is zoomed
+/// without the need to tessellate the render bundles for every resolution.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ResolutionScale {
+    /// Resolution of the first stop.
+    pub from_resolution: f64,
+    /// Multiplier at the first stop. Must be positive.
+    pub from_scale: f64,
+    /// Resolution of the second stop.
+    pub to_resolution: f64,
+    /// Multiplier at the second stop. Must be positive.
+    pub to_scale: f64,
+}
+
+impl ResolutionScale {
+    /// Creates a new instance.
+    pub fn new(from_resolution: f64, from_scale: f64, to_resolution: f64, to_scale: f64) -> Self {
+        Self {
+            from_resolution,
+            from_scale,
+            to_resolution,
+            to_scale,
+        }
+    }
+
+    /// Multiplier that makes the size behave as if it was set in map units between the given resolutions: the size
+    /// is unchanged at `base_resolution`, and doubles every time the resolution halves.
+    pub fn map_units(base_resolution: f64, min_resolution: f64, max_resolution: f64) -> Self {
+        Self::new(
+            min_resolution,
+            base_resolution / min_resolution,
+            max_resolution,
+            base_resolution / max_resolution,
+        )
+    }
+
+    /// Value of the multiplier at the given resolution.
+    pub fn scale_at(&self, resolution: f64) -> f64 {
+        let [from_resolution, from_scale, to_resolution, to_scale] =
+            self.to_log_parameters().map(|v| v as f64);
+        let range = to_resolution - from_resolution;
+        let t = if range == 0.0 {
+            0.0
+        } else {
+            ((resolution.log2() - from_resolution) / range).clamp(0.0, 1.0)
+        };
+
+        (from_scale + (to_scale - from_scale) * t).exp2()
+    }
+
+    /// Base 2 logarithms of the stop parameters, as they are stored in the vertices. Zero parameters correspond to
+    /// the constant multiplier 1.0.
+    pub(crate) fn to_log_parameters(self) -> [f32; 4] {
+        // Zero or negative values cannot be interpolated in logarithmic space
+        let log = |v: f64| v.max(f64::MIN_POSITIVE).log2() as f32;
+        [
+            log(self.from_resolution),
+            log(self.from_scale),
+            log(self.to_resolution),
+            log(self.to_scale),
+        ]
+    }
 }
 
 /// Cap (end point) style of the line.
@@ -121,3 +298,30 @@ pub struct ImagePaint {
     /// opacity and this value represented in percents.
     pub opacity: u8,
 }
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use super::*;
+
+    #[test]
+    fn resolution_scale() {
+        let scale = ResolutionScale::new(8.0, 1.0, 2.0, 4.0);
+        assert_abs_diff_eq!(scale.scale_at(16.0), 1.0, epsilon = 1e-5);
+        assert_abs_diff_eq!(scale.scale_at(8.0), 1.0, epsilon = 1e-5);
+        assert_abs_diff_eq!(scale.scale_at(4.0), 2.0, epsilon = 1e-5);
+        assert_abs_diff_eq!(scale.scale_at(2.0), 4.0, epsilon = 1e-5);
+        assert_abs_diff_eq!(scale.scale_at(1.0), 4.0, epsilon = 1e-5);
+
+        let map_units = ResolutionScale::map_units(10.0, 1.0, 100.0);
+        assert_abs_diff_eq!(map_units.scale_at(5.0), 2.0, epsilon = 1e-5);
+        assert_abs_diff_eq!(map_units.scale_at(1000.0), 0.1, epsilon = 1e-5);
+
+        // Default parameters of the vertices give constant scale
+        assert_eq!(
+            ResolutionScale::new(1.0, 1.0, 1.0, 1.0).to_log_parameters(),
+            [0.0; 4]
+        );
+    }
+}
@@ -0,0 +1,284 @@
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+use galileo_types::cartesian::Size;
+use parking_lot::Mutex;
+use wgpu::{Device, Queue};
+
+use crate::error::GalileoError;
+use crate::render::WgpuRenderer;
+use crate::Map;
+
+const DEFAULT_MAX_IDLE: usize = 4;
+
+/// Renders maps into images off-screen, reusing the render targets between renders.
+///
+/// Creating the textures of a render target is relatively expensive, so servers rendering many images (e.g. static
+/// map images or tiles) should not create a new [`WgpuRenderer`] for every request. This renderer keeps a pool of
+/// texture-backed renderers sharing one GPU device. A renderer of the requested size is taken from the pool for every
+/// render and returned to the pool after the image is read back.
+///
+/// ```ignore
+/// let renderer = OffscreenRenderer::new().await.expect("no graphics adapter");
+///
+/// let image = renderer.render(&map).await?;
+/// let png = image.encode(ImageEncoding::Png)?;
+/// ```
+pub struct OffscreenRenderer {
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+    idle: Mutex<Vec<WgpuRenderer>>,
+    max_idle: usize,
+}
+
+impl OffscreenRenderer {
+    /// Creates a new renderer with its own GPU device.
+    ///
+    /// Returns `None` if a device adapter cannot be acquired.
+    pub async fn new() -> Option<Self> {
+        let renderer = WgpuRenderer::new().await?;
+        Some(Self::with_device(
+            renderer.device().clone(),
+            renderer.queue().clone(),
+        ))
+    }
+
+    /// Creates a new renderer using the given device and queue, e.g. from the [`WgpuContext`](super::WgpuContext) of
+    /// the application windows.
+    pub fn with_device(device: Arc<Device>, queue: Arc<Queue>) -> Self {
+        Self {
+            device,
+            queue,
+            idle: Mutex::new(vec![]),
+            max_idle: DEFAULT_MAX_IDLE,
+        }
+    }
+
+    /// Sets the maximum number of unused render targets kept in the pool. Default value is 4.
+    pub fn with_max_idle(mut self, max_idle: usize) -> Self {
+        self.max_idle = max_idle;
+        self.idle.get_mut().truncate(max_idle);
+        self
+    }
+
+    /// Takes a renderer with a render target of the given size from the pool, or creates a new one if the pool is
+    /// empty. The renderer is returned to the pool when the guard is dropped.
+    ///
+    /// Settings of the renderer (e.g. the background or post-processing effects) are kept when it is returned to the
+    /// pool, so renderers with custom settings should be reset before they are dropped.
+    pub fn acquire(&self, size: Size<u32>) -> PooledRenderer<'_> {
+        let renderer = {
+            let mut idle = self.idle.lock();
+            let position = idle
+                .iter()
+                .position(|renderer| renderer.size().cast::<u32>() == size)
+                .or_else(|| idle.len().checked_sub(1));
+            position.map(|position| idle.swap_remove(position))
+        };
+
+        let renderer = match renderer {
+            Some(mut renderer) => {
+                renderer.resize(size);
+                renderer
+            }
+            None => WgpuRenderer::new_with_device_and_texture(
+                self.device.clone(),
+                self.queue.clone(),
+                size,
+            ),
+        };
+
+        PooledRenderer {
+            renderer: Some(renderer),
+            pool: self,
+        }
+    }
+
+    /// Renders the map with the size of its view and reads the image back from the GPU.
+    pub async fn render(&self, map: &Map) -> Result<RenderedImage, GalileoError> {
+        let view_size = map.view().size();
+        let size = Size::new(
+            view_size.width().round() as u32,
+            view_size.height().round() as u32,
+        );
+        if size.width() == 0 || size.height() == 0 {
+            return Err(GalileoError::Generic(
+                "cannot render a map with empty size".into(),
+            ));
+        }
+
+        let renderer = self.acquire(size);
+        renderer
+            .render(map)
+            .map_err(|err| GalileoError::Generic(format!("failed to render map: {err}")))?;
+        let bytes = renderer
+            .get_image()
+            .await
+            .map_err(|err| GalileoError::Generic(format!("failed to read map image: {err}")))?;
+
+        Ok(RenderedImage {
+            width: size.width(),
+            height: size.height(),
+            bytes,
+        })
+    }
+
+    /// Number of unused render targets in the pool.
+    pub fn idle_count(&self) -> usize {
+        self.idle.lock().len()
+    }
+
+    fn release(&self, renderer: WgpuRenderer) {
+        let mut idle = self.idle.lock();
+        if idle.len() < self.max_idle {
+            idle.push(renderer);
+        }
+    }
+}
+
+/// Renderer taken from an [`OffscreenRenderer`] pool. Returns to the pool when dropped.
+pub struct PooledRenderer<'a> {
+    renderer: Option<WgpuRenderer>,
+    pool: &'a OffscreenRenderer,
+}
+
+impl Deref for PooledRenderer<'_> {
+    type Target = WgpuRenderer;
+
+    fn deref(&self) -> &Self::Target {
+        self.renderer
+            .as_ref()
+            .expect("renderer is taken only on drop")
+    }
+}
+
+impl DerefMut for PooledRenderer<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.renderer
+            .as_mut()
+            .expect("renderer is taken only on drop")
+    }
+}
+
+impl Drop for PooledRenderer<'_> {
+    fn drop(&mut self) {
+        if let Some(renderer) = self.renderer.take() {
+            self.pool.release(renderer);
+        }
+    }
+}
+
+/// Image rendered by an [`OffscreenRenderer`].
+#[derive(Debug, Clone)]
+pub struct RenderedImage {
+    width: u32,
+    height: u32,
+    bytes: Vec<u8>,
+}
+
+impl RenderedImage {
+    /// Width of the image in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Height of the image in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Raw RGBA bytes of the image, row by row from the top.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Consumes the image, returning its raw RGBA bytes.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    /// Encodes the image into the given format.
+    #[cfg(feature = "image")]
+    pub fn encode(&self, encoding: ImageEncoding) -> Result<Vec<u8>, GalileoError> {
+        let image = image::RgbaImage::from_raw(self.width, self.height, self.bytes.clone())
+            .ok_or_else(|| GalileoError::Generic("invalid size of the rendered image".into()))?;
+
+        let mut bytes = vec![];
+        let mut writer = std::io::Cursor::new(&mut bytes);
+        let result = match encoding {
+            ImageEncoding::Png => image.write_to(&mut writer, image::ImageOutputFormat::Png),
+            // JPEG does not support transparency
+            ImageEncoding::Jpeg { quality } => image::DynamicImage::ImageRgba8(image)
+                .to_rgb8()
+                .write_to(&mut writer, image::ImageOutputFormat::Jpeg(quality)),
+            #[cfg(feature = "webp-encoder")]
+            ImageEncoding::WebP => image.write_to(&mut writer, image::ImageOutputFormat::WebP),
+        };
+        result.map_err(|err| GalileoError::Generic(format!("failed to encode image: {err}")))?;
+
+        Ok(bytes)
+    }
+}
+
+/// Format to encode a [`RenderedImage`] into.
+#[cfg(feature = "image")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageEncoding {
+    /// Lossless PNG.
+    Png,
+    /// JPEG with the given quality in the range `1..=100`. Transparency is lost.
+    Jpeg {
+        /// Quality of the encoding.
+        quality: u8,
+    },
+    /// WebP. Requires the `webp-encoder` feature.
+    #[cfg(feature = "webp-encoder")]
+    WebP,
+}
+
+#[cfg(test)]
+mod tests {
+    use galileo_types::cartesian::Point2d;
+
+    use super::*;
+    use crate::MapView;
+
+    #[test]
+    fn render_width_not_aligned_to_copy_rows() {
+        let Some(renderer) = tokio_test::block_on(OffscreenRenderer::new()) else {
+            log::warn!("No graphics adapter available, skipping the test");
+            return;
+        };
+
+        let view =
+            MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0).with_size(Size::new(100.0, 30.0));
+        let map = Map::new(view, vec![], None);
+        let image = tokio_test::block_on(renderer.render(&map)).expect("failed to render");
+
+        assert_eq!(image.width(), 100);
+        assert_eq!(image.height(), 30);
+        assert_eq!(image.bytes().len(), 100 * 30 * 4);
+        // Map without layers is filled with the background color
+        let first_pixel = &image.bytes()[..4];
+        assert!(image.bytes().chunks(4).all(|pixel| pixel == first_pixel));
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn encode_rendered_image() {
+        let image = RenderedImage {
+            width: 2,
+            height: 1,
+            bytes: vec![255, 0, 0, 255, 0, 0, 255, 128],
+        };
+
+        let png = image.encode(ImageEncoding::Png).expect("valid image");
+        let decoded = image::load_from_memory(&png).expect("valid png").to_rgba8();
+        assert_eq!(decoded.as_raw(), image.bytes());
+
+        let jpeg = image
+            .encode(ImageEncoding::Jpeg { quality: 90 })
+            .expect("valid image");
+        assert_eq!(&jpeg[..2], &[0xFF, 0xD8]);
+    }
+}
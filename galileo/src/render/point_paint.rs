@@ -3,13 +3,16 @@
 use std::borrow::Cow;
 use std::sync::Arc;
 
+use galileo_types::cartesian::Rect;
+use galileo_types::contour::Contour;
 use galileo_types::impls::ClosedContour;
 use nalgebra::{Point2, Vector2};
 use serde::{Deserialize, Serialize};
 
 use crate::decoded_image::DecodedImage;
+use crate::render::render_bundle::tessellating::image_corner_offsets;
 use crate::render::text::TextStyle;
-use crate::render::{LineCap, LinePaint};
+use crate::render::{LineCap, LinePaint, ResolutionScale};
 use crate::Color;
 
 /// Specifies the way a point should be drawn to the map.
@@ -17,6 +20,16 @@ use crate::Color;
 pub struct PointPaint<'a> {
     pub(crate) shape: PointShape<'a>,
     pub(crate) offset: Vector2<f32>,
+    #[serde(default)]
+    pub(crate) rotation: f32,
+    #[serde(default = "default_scale")]
+    pub(crate) scale: f32,
+    #[serde(default)]
+    pub(crate) resolution_scale: Option<ResolutionScale>,
+}
+
+fn default_scale() -> f32 {
+    1.0
 }
 
 impl<'a> PointPaint<'a> {
@@ -24,6 +37,9 @@ impl<'a> PointPaint<'a> {
     pub fn circle(color: Color, diameter: f32) -> Self {
         Self {
             offset: Vector2::default(),
+            rotation: 0.0,
+            scale: 1.0,
+            resolution_scale: None,
             shape: PointShape::Circle {
                 fill: color.into(),
                 radius: diameter / 2.0,
@@ -36,6 +52,9 @@ impl<'a> PointPaint<'a> {
     pub fn sector(color: Color, diameter: f32, start_angle: f32, end_angle: f32) -> Self {
         Self {
             offset: Vector2::default(),
+            rotation: 0.0,
+            scale: 1.0,
+            resolution_scale: None,
             shape: PointShape::Sector(SectorParameters {
                 fill: color.into(),
                 radius: diameter / 2.0,
@@ -50,6 +69,9 @@ impl<'a> PointPaint<'a> {
     pub fn square(color: Color, size: f32) -> Self {
         Self {
             offset: Vector2::default(),
+            rotation: 0.0,
+            scale: 1.0,
+            resolution_scale: None,
             shape: PointShape::Square {
                 fill: color,
                 size,
@@ -62,6 +84,9 @@ impl<'a> PointPaint<'a> {
     pub fn dot(color: Color) -> Self {
         Self {
             offset: Vector2::default(),
+            rotation: 0.0,
+            scale: 1.0,
+            resolution_scale: None,
             shape: PointShape::Dot { color },
         }
     }
@@ -70,6 +95,9 @@ impl<'a> PointPaint<'a> {
     pub fn shape(color: Color, contour: &'a ClosedContour<Point2<f32>>, scale: f32) -> Self {
         Self {
             offset: Vector2::default(),
+            rotation: 0.0,
+            scale: 1.0,
+            resolution_scale: None,
             shape: PointShape::FreeShape {
                 fill: color,
                 scale,
@@ -79,18 +107,23 @@ impl<'a> PointPaint<'a> {
         }
     }
 
-    /// Creates a paint that draws a point as an image of fixed pixel size. Offset is given as a portion of image size,
-    /// e.g. offset `[0.5, 1.0]` will create an image with anchor point at the center-bottom point of the image.
-    pub fn image(image: Arc<DecodedImage>, offset: Vector2<f32>, scale: f32) -> Self {
+    /// Creates a paint that draws a point as an image of fixed pixel size. Anchor is given as a portion of image size,
+    /// e.g. anchor `[0.5, 1.0]` will create an image with anchor point at the center-bottom point of the image.
+    pub fn image(image: Arc<DecodedImage>, anchor: Vector2<f32>, scale: f32) -> Self {
         let width = image.width() as f32 * scale;
         let height = image.height() as f32 * scale;
         Self {
-            offset,
+            offset: Vector2::default(),
+            rotation: 0.0,
+            scale: 1.0,
+            resolution_scale: None,
             shape: PointShape::Image {
                 image,
                 opacity: 255,
                 width,
                 height,
+                anchor,
+                rotation_alignment: RotationAlignment::Screen,
             },
         }
     }
@@ -99,9 +132,13 @@ impl<'a> PointPaint<'a> {
     pub fn label(text: &'a String, style: &'a TextStyle) -> Self {
         Self {
             offset: Vector2::new(0.0, 0.0),
+            rotation: 0.0,
+            scale: 1.0,
+            resolution_scale: None,
             shape: PointShape::Label {
                 text: Cow::Borrowed(text),
                 style: Cow::Borrowed(style),
+                background: None,
             },
         }
     }
@@ -110,9 +147,13 @@ impl<'a> PointPaint<'a> {
     pub fn label_owned(text: String, style: TextStyle) -> Self {
         Self {
             offset: Vector2::new(0.0, 0.0),
+            rotation: 0.0,
+            scale: 1.0,
+            resolution_scale: None,
             shape: PointShape::Label {
                 text: Cow::Owned(text),
                 style: Cow::Owned(style),
+                background: None,
             },
         }
     }
@@ -128,6 +169,8 @@ impl<'a> PointPaint<'a> {
                     width: width as f64,
                     offset: 0.0,
                     line_cap: LineCap::Round,
+                    width_scale: None,
+                    crisp: false,
                 })
             }
             _ => {}
@@ -136,6 +179,114 @@ impl<'a> PointPaint<'a> {
         self
     }
 
+    /// Multiplies the opacity of the paint by the given value in the range `[0.0, 1.0]`.
+    ///
+    /// Applies to all colors of the shape, including the outline. Has no effect on labels.
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        let opacity = opacity.clamp(0.0, 1.0);
+        let apply = |color: &mut Color| {
+            *color = color.with_alpha((color.a() as f32 * opacity).round() as u8)
+        };
+        let apply_outline = |outline: &mut Option<LinePaint>| {
+            if let Some(outline) = outline {
+                apply(&mut outline.color);
+            }
+        };
+
+        match &mut self.shape {
+            PointShape::Dot { color } => apply(color),
+            PointShape::Circle { fill, outline, .. } => {
+                apply(&mut fill.center_color);
+                apply(&mut fill.side_color);
+                apply_outline(outline);
+            }
+            PointShape::Sector(parameters) => {
+                apply(&mut parameters.fill.center_color);
+                apply(&mut parameters.fill.side_color);
+                apply_outline(&mut parameters.outline);
+            }
+            PointShape::Square { fill, outline, .. }
+            | PointShape::FreeShape { fill, outline, .. } => {
+                apply(fill);
+                apply_outline(outline);
+            }
+            PointShape::Image {
+                opacity: image_opacity,
+                ..
+            } => *image_opacity = (*image_opacity as f32 * opacity).round() as u8,
+            PointShape::Label { .. } => {}
+        }
+
+        self
+    }
+
+    /// Sets rotation of the paint in radians. Positive values rotate the object counterclockwise
+    /// around its anchor point (base point for the shapes other than images).
+    ///
+    /// Has no effect on dots. Rotation and scale of a point can be changed in a render bundle without tessellating
+    /// the shape again, so they are suitable for frequently updated values like vehicle headings.
+    pub fn with_rotation(mut self, angle: f32) -> Self {
+        self.rotation = angle;
+        self
+    }
+
+    /// Multiplies the size of the object by the given factor. Has no effect on dots.
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale *= scale;
+        self
+    }
+
+    /// Sets the multiplier of the size of the object that changes with the resolution of the map, so that the object
+    /// grows or shrinks smoothly while zooming.
+    ///
+    /// Applies to circles, sectors, squares and shapes, and has no effect on dots, images and labels. The
+    /// multiplier is applied on top of the scale set with [`PointPaint::with_scale`].
+    pub fn with_resolution_scale(mut self, scale: ResolutionScale) -> Self {
+        self.resolution_scale = Some(scale);
+        self
+    }
+
+    /// Sets the point of the object that is placed at the base point, as a portion of the object size. E.g.
+    /// `[0.5, 0.5]` places the center of the object at the base point, and `[0.5, 1.0]` places the center-bottom
+    /// point (useful for pin-like markers).
+    ///
+    /// Currently only applies to images.
+    pub fn with_anchor(mut self, value: Vector2<f32>) -> Self {
+        if let PointShape::Image { anchor, .. } = &mut self.shape {
+            *anchor = value;
+        }
+
+        self
+    }
+
+    /// Sets whether the rotation of the paint is relative to the screen or to the map.
+    ///
+    /// Currently only applies to images, other shapes are always rotated relative to the screen.
+    pub fn with_rotation_alignment(mut self, alignment: RotationAlignment) -> Self {
+        if let PointShape::Image {
+            rotation_alignment, ..
+        } = &mut self.shape
+        {
+            *rotation_alignment = alignment;
+        }
+
+        self
+    }
+
+    /// Sets the background of a label, e.g. a road number shield, which is stretched to the size of the text.
+    ///
+    /// Labels with a background are aligned relative to the base point according to the
+    /// [`horizontal_alignment`](TextStyle::horizontal_alignment) and
+    /// [`vertical_alignment`](TextStyle::vertical_alignment) of the text style, and the text is centered in the
+    /// background. Has no effect on other shapes.
+    pub fn with_background(mut self, value: LabelBackground) -> Self {
+        if let PointShape::Label { background, .. } = &mut self.shape {
+            *background = Some(value);
+        }
+
+        self
+    }
+
     /// Sets offset of the paint.
     ///
     /// Offset is the distance in pixels from the base point the object will be drawn at. E.g.
@@ -147,6 +298,94 @@ impl<'a> PointPaint<'a> {
         self.offset = offset;
         self
     }
+
+    /// Bounding rectangle of the rendered object in pixels relative to the base point, including the offset.
+    ///
+    /// The `y` axis of the rectangle is directed towards the top of the screen. For images with
+    /// [`RotationAlignment::Map`] the rectangle is given in the coordinate system rotated together with the map.
+    ///
+    /// Returns `None` for dots and labels, the size of which is not known before they are rendered.
+    pub fn screen_bounds(&self) -> Option<Rect<f32>> {
+        let outline_width = |outline: &Option<LinePaint>| outline.map(|v| v.width as f32 / 2.0);
+        let bounds = match &self.shape {
+            PointShape::Dot { .. } | PointShape::Label { .. } => return None,
+            PointShape::Circle {
+                radius, outline, ..
+            } => {
+                let r = radius + outline_width(outline).unwrap_or(0.0);
+                Rect::new(-r, -r, r, r)
+            }
+            PointShape::Sector(SectorParameters {
+                radius, outline, ..
+            }) => {
+                let r = radius + outline_width(outline).unwrap_or(0.0);
+                Rect::new(-r, -r, r, r)
+            }
+            PointShape::Square { size, outline, .. } => {
+                let half = size / 2.0 + outline_width(outline).unwrap_or(0.0);
+                Rect::new(-half, -half, half, half)
+            }
+            PointShape::FreeShape {
+                scale,
+                outline,
+                shape,
+                ..
+            } => {
+                let bounds = Rect::from_points(shape.iter_points())?;
+                let outline = outline_width(outline).unwrap_or(0.0);
+                Rect::new(
+                    bounds.x_min() * scale - outline,
+                    bounds.y_min() * scale - outline,
+                    bounds.x_max() * scale + outline,
+                    bounds.y_max() * scale + outline,
+                )
+            }
+            PointShape::Image {
+                width,
+                height,
+                anchor,
+                ..
+            } => {
+                let corners = image_corner_offsets(
+                    *width * self.scale,
+                    *height * self.scale,
+                    *anchor,
+                    self.rotation,
+                    self.offset,
+                )
+                .map(|[x, y]| Point2::new(x, y));
+                return Rect::from_points(corners.iter());
+            }
+        };
+
+        let (sin, cos) = self.rotation.sin_cos();
+        let corners = [
+            (bounds.x_min(), bounds.y_min()),
+            (bounds.x_min(), bounds.y_max()),
+            (bounds.x_max(), bounds.y_min()),
+            (bounds.x_max(), bounds.y_max()),
+        ]
+        .map(|(x, y)| {
+            let (x, y) = (x * self.scale, y * self.scale);
+            Point2::new(
+                x * cos - y * sin + self.offset.x,
+                x * sin + y * cos + self.offset.y,
+            )
+        });
+
+        Rect::from_points(corners.iter())
+    }
+
+    /// Returns true if the object is rotated together with the map.
+    pub(crate) fn is_map_aligned(&self) -> bool {
+        matches!(
+            self.shape,
+            PointShape::Image {
+                rotation_alignment: RotationAlignment::Map,
+                ..
+            }
+        )
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -177,11 +416,126 @@ pub(crate) enum PointShape<'a> {
         opacity: u8,
         width: f32,
         height: f32,
+        #[serde(default)]
+        anchor: Vector2<f32>,
+        #[serde(default)]
+        rotation_alignment: RotationAlignment,
     },
     Label {
         text: Cow<'a, String>,
         style: Cow<'a, TextStyle>,
+        #[serde(default)]
+        background: Option<LabelBackground>,
+    },
+}
+
+/// Background of a text label that is stretched to the size of the text, e.g. a road number shield.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum LabelBackground {
+    /// Rectangle with optionally rounded corners.
+    #[serde(rename = "rect")]
+    Rect {
+        /// Fill color of the rectangle.
+        fill: Color,
+        /// Radius of the corners in pixels.
+        #[serde(default)]
+        corner_radius: f32,
+        /// Color of the outline. If not set, the rectangle is drawn without outline.
+        #[serde(default)]
+        stroke_color: Option<Color>,
+        /// Width of the outline in pixels.
+        #[serde(default)]
+        stroke_width: f32,
+        /// Distance between the text and the sides of the rectangle in pixels along `x` and `y` axes.
+        #[serde(default)]
+        padding: Vector2<f32>,
     },
+    /// Image scaled as a nine-patch: the corners of the image keep their size, the sides are stretched along one
+    /// axis and the center is stretched along both axes.
+    #[serde(rename = "nine_patch")]
+    NinePatch {
+        /// Background image.
+        image: Arc<DecodedImage>,
+        /// Sizes of the non-stretched borders of the image in pixels: left, top, right and bottom.
+        borders: [f32; 4],
+        /// Distance between the text and the sides of the image in pixels along `x` and `y` axes.
+        #[serde(default)]
+        padding: Vector2<f32>,
+    },
+}
+
+impl LabelBackground {
+    /// Creates a rectangle background with the given fill color and corner radius.
+    pub fn rect(fill: Color, corner_radius: f32) -> Self {
+        Self::Rect {
+            fill,
+            corner_radius,
+            stroke_color: None,
+            stroke_width: 0.0,
+            padding: Vector2::default(),
+        }
+    }
+
+    /// Creates a nine-patch image background. See [`LabelBackground::NinePatch`].
+    pub fn nine_patch(image: Arc<DecodedImage>, borders: [f32; 4]) -> Self {
+        Self::NinePatch {
+            image,
+            borders,
+            padding: Vector2::default(),
+        }
+    }
+
+    /// Sets the outline of the rectangle background. Has no effect on image backgrounds.
+    pub fn with_outline(mut self, color: Color, width: f32) -> Self {
+        if let Self::Rect {
+            stroke_color,
+            stroke_width,
+            ..
+        } = &mut self
+        {
+            *stroke_color = Some(color);
+            *stroke_width = width;
+        }
+
+        self
+    }
+
+    /// Sets the distance between the text and the sides of the background in pixels.
+    pub fn with_padding(mut self, value: Vector2<f32>) -> Self {
+        match &mut self {
+            Self::Rect { padding, .. } | Self::NinePatch { padding, .. } => *padding = value,
+        }
+
+        self
+    }
+
+    pub(crate) fn padding(&self) -> Vector2<f32> {
+        match self {
+            Self::Rect { padding, .. } | Self::NinePatch { padding, .. } => *padding,
+        }
+    }
+
+    /// Minimum size of the background, at which its corners are not overlapping.
+    pub(crate) fn min_size(&self) -> Vector2<f32> {
+        match self {
+            Self::Rect { corner_radius, .. } => {
+                Vector2::new(corner_radius * 2.0, corner_radius * 2.0)
+            }
+            Self::NinePatch { borders, .. } => {
+                Vector2::new(borders[0] + borders[2], borders[1] + borders[3])
+            }
+        }
+    }
+}
+
+/// Defines what the rotation of a screen-referenced object (e.g. a marker image) is relative to.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RotationAlignment {
+    /// The object keeps its orientation relative to the screen when the map is rotated.
+    #[default]
+    Screen,
+    /// The object is rotated together with the map, e.g. an arrow pointing north keeps pointing north.
+    Map,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -219,4 +573,74 @@ mod tests {
         assert_eq!(fill.center_color, color);
         assert_eq!(fill.side_color, color);
     }
+
+    #[test]
+    fn opacity_applies_to_fill_and_outline() {
+        let paint = PointPaint::circle(Color::RED, 10.0)
+            .with_outline(Color::BLUE.with_alpha(100), 1.0)
+            .with_opacity(0.5);
+        let PointShape::Circle { fill, outline, .. } = paint.shape else {
+            panic!("unexpected shape");
+        };
+
+        assert_eq!(fill.center_color.a(), 128);
+        assert_eq!(outline.map(|v| v.color.a()), Some(50));
+    }
+
+    #[test]
+    fn image_anchor_and_offset() {
+        let image = Arc::new(
+            DecodedImage::from_raw(vec![0; 4], galileo_types::cartesian::Size::new(1, 1))
+                .expect("invalid image"),
+        );
+        let paint = PointPaint::image(image, Vector2::new(0.5, 0.5), 1.0)
+            .with_anchor(Vector2::new(0.5, 1.0))
+            .with_offset(Vector2::new(0.0, 10.0))
+            .with_rotation_alignment(RotationAlignment::Map);
+
+        assert_eq!(paint.offset, Vector2::new(0.0, 10.0));
+        let PointShape::Image {
+            anchor,
+            rotation_alignment,
+            ..
+        } = paint.shape
+        else {
+            panic!("unexpected shape");
+        };
+        assert_eq!(anchor, Vector2::new(0.5, 1.0));
+        assert_eq!(rotation_alignment, RotationAlignment::Map);
+    }
+
+    #[test]
+    fn screen_bounds() {
+        let circle = PointPaint::circle(Color::RED, 10.0)
+            .with_outline(Color::BLUE, 2.0)
+            .with_offset(Vector2::new(10.0, 0.0));
+        assert_eq!(
+            circle.screen_bounds(),
+            Some(Rect::new(4.0, -6.0, 16.0, 6.0))
+        );
+
+        let image = Arc::new(
+            DecodedImage::from_raw(vec![0; 4], galileo_types::cartesian::Size::new(1, 1))
+                .expect("invalid image"),
+        );
+        let pin = PointPaint::image(image, Vector2::new(0.5, 1.0), 32.0);
+        assert_eq!(pin.screen_bounds(), Some(Rect::new(-16.0, 0.0, 16.0, 32.0)));
+
+        assert_eq!(PointPaint::dot(Color::RED).screen_bounds(), None);
+    }
+
+    #[test]
+    fn rotated_and_scaled_bounds() {
+        let square = PointPaint::square(Color::RED, 2.0)
+            .with_rotation(std::f32::consts::FRAC_PI_4)
+            .with_scale(2.0)
+            .with_offset(Vector2::new(10.0, 0.0));
+        let bounds = square.screen_bounds().expect("no bounds");
+        let half_diagonal = 2.0 * std::f32::consts::SQRT_2;
+        assert!((bounds.x_min() - (10.0 - half_diagonal)).abs() < 1e-5);
+        assert!((bounds.x_max() - (10.0 + half_diagonal)).abs() < 1e-5);
+        assert!((bounds.y_max() - half_diagonal).abs() < 1e-5);
+    }
 }
@@ -0,0 +1,150 @@
+//! Full-screen effects applied to the map image after all layers are rendered.
+//!
+//! Effects are set with [`WgpuRenderer::set_post_effects`](crate::render::WgpuRenderer::set_post_effects) and are
+//! applied in the order they are given.
+
+/// A post processing effect.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PostEffect {
+    /// Changes brightness of the image.
+    ColorAdjustment {
+        /// Exposure correction in stops. `1.0` doubles brightness of the image, `-1.0` halves it.
+        exposure: f32,
+        /// Gamma correction value. Values greater than `1.0` make dark areas of the image lighter.
+        gamma: f32,
+    },
+    /// Darkens the corners of the image.
+    Vignette {
+        /// Darkening factor at the image corners in the range `[0.0, 1.0]`.
+        intensity: f32,
+        /// Distance from the image center (as a portion of the distance to the corners) at which darkening starts.
+        radius: f32,
+    },
+    /// Inverts lightness of the image, while preserving the hue of the colors. Useful for dark themes of the
+    /// applications.
+    NightMode {
+        /// Portion of the inverted image mixed into the result in the range `[0.0, 1.0]`.
+        intensity: f32,
+    },
+    /// Effect with a custom WGSL fragment shader.
+    Custom(CustomPostEffect),
+}
+
+impl PostEffect {
+    /// Default exposure and gamma correction. Does not change the image.
+    pub fn color_adjustment() -> Self {
+        Self::ColorAdjustment {
+            exposure: 0.0,
+            gamma: 1.0,
+        }
+    }
+
+    /// Night mode with full intensity.
+    pub fn night_mode() -> Self {
+        Self::NightMode { intensity: 1.0 }
+    }
+
+    pub(crate) fn parameters(&self) -> [f32; 8] {
+        match self {
+            Self::ColorAdjustment { exposure, gamma } => [
+                *exposure,
+                gamma.max(f32::EPSILON),
+                0.0,
+                0.0,
+                0.0,
+                0.0,
+                0.0,
+                0.0,
+            ],
+            Self::Vignette { intensity, radius } => {
+                [*intensity, *radius, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]
+            }
+            Self::NightMode { intensity } => [*intensity, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+            Self::Custom(effect) => effect.parameters,
+        }
+    }
+}
+
+/// Post processing effect defined by a WGSL fragment shader.
+///
+/// The shader source must define `fs_main` fragment entry point. The following declarations are prepended to the
+/// source and can be used by the shader:
+///
+/// ```wgsl
+/// struct VertexOutput {
+///     @builtin(position) clip_position: vec4<f32>,
+///     // Texture coordinates of the pixel in the source image
+///     @location(0) tex_coord: vec2<f32>,
+/// };
+///
+/// struct EffectParameters {
+///     a: vec4<f32>,
+///     b: vec4<f32>,
+/// }
+///
+/// // Image of the map rendered by the previous stage
+/// @group(0) @binding(0)
+/// var t_source: texture_2d<f32>;
+/// @group(0) @binding(1)
+/// var s_source: sampler;
+/// // Values set by `CustomPostEffect::with_parameters`
+/// @group(0) @binding(2)
+/// var<uniform> params: EffectParameters;
+/// ```
+///
+/// For example, a grayscale effect:
+///
+/// ```wgsl
+/// @fragment
+/// fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+///     let color = textureSample(t_source, s_source, in.tex_coord);
+///     let gray = dot(color.rgb, vec3<f32>(0.2126, 0.7152, 0.0722));
+///     return vec4<f32>(vec3<f32>(gray), color.a);
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomPostEffect {
+    source: String,
+    parameters: [f32; 8],
+}
+
+impl CustomPostEffect {
+    /// Creates a new effect with the given WGSL source.
+    pub fn new(source: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+            parameters: [0.0; 8],
+        }
+    }
+
+    /// Sets the values available to the shader as `params.a` and `params.b` vectors.
+    pub fn with_parameters(mut self, parameters: [f32; 8]) -> Self {
+        self.parameters = parameters;
+        self
+    }
+
+    /// WGSL source of the fragment shader.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn effect_parameters() {
+        let gamma = PostEffect::ColorAdjustment {
+            exposure: 1.0,
+            gamma: 0.0,
+        };
+        assert_eq!(gamma.parameters()[0], 1.0);
+        assert!(gamma.parameters()[1] > 0.0);
+
+        let custom = PostEffect::Custom(
+            CustomPostEffect::new("").with_parameters([1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0]),
+        );
+        assert_eq!(custom.parameters()[7], 8.0);
+    }
+}
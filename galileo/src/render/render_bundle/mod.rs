@@ -4,7 +4,8 @@ use std::borrow::Cow;
 
 use galileo_types::cartesian::{CartesianPoint3d, Point2d};
 use galileo_types::contour::Contour;
-use galileo_types::Polygon;
+use galileo_types::{impls, Polygon};
+use nalgebra::Vector2;
 use num_traits::AsPrimitive;
 
 use crate::decoded_image::DecodedImage;
@@ -13,6 +14,7 @@ use crate::render::point_paint::PointPaint;
 use crate::render::render_bundle::tessellating::TessellatingRenderBundle;
 use crate::render::{ImagePaint, LinePaint, PolygonPaint, PrimitiveId};
 use crate::view::MapView;
+use crate::Color;
 
 pub(crate) mod tessellating;
 
@@ -33,6 +35,16 @@ impl RenderBundle {
         }
     }
 
+    /// Returns the amount of memory used by the data of this bundle, split by the type of the data.
+    ///
+    /// Unlike [`RenderBundle::approx_buffer_size`], this value is always calculated from the
+    /// actual contents of the bundle.
+    pub fn memory_usage(&self) -> BundleMemoryUsage {
+        match &self.0 {
+            RenderBundleType::Tessellating(inner) => inner.memory_usage(),
+        }
+    }
+
     /// Sets the value for `approx_buffer_size`.
     ///
     /// This can be useful for better memory management when used buffers size cannot be calculated
@@ -46,6 +58,29 @@ impl RenderBundle {
         }
     }
 
+    /// Maximum distance in screen pixels between curves (e.g. circles of point symbols, round line caps and joins) and
+    /// their tessellated approximation.
+    pub fn tessellation_tolerance(&self) -> f32 {
+        match &self.0 {
+            RenderBundleType::Tessellating(inner) => inner.tessellation_tolerance(),
+        }
+    }
+
+    /// Sets the maximum distance in screen pixels between curves and their tessellated approximation. Smaller values
+    /// make curves smoother at the cost of a larger number of vertices. Only applies to the primitives added after
+    /// the value is changed.
+    ///
+    /// Curves are approximated based on their size on the screen: sizes of point symbols are set in pixels, and lines
+    /// are tessellated in pixels at the `min_resolution` given to [`RenderBundle::add`]. So small circles get only a
+    /// few segments, while large ones get as many as needed to look smooth.
+    ///
+    /// Default value is `0.1`.
+    pub fn set_tessellation_tolerance(&mut self, tolerance: f32) {
+        match &mut self.0 {
+            RenderBundleType::Tessellating(inner) => inner.set_tessellation_tolerance(tolerance),
+        }
+    }
+
     /// Set the clip area for drawing. Only primitives inside the clipped area will be displayed after rendering.
     pub fn clip_area<N, P, Poly>(&mut self, polygon: &Poly)
     where
@@ -90,6 +125,67 @@ impl RenderBundle {
         }
     }
 
+    /// Adds a point primitive drawn at the given offset (in screen pixels) from the `anchor` point.
+    ///
+    /// The offset is added to the offset of the `paint` itself, and does not depend on the map resolution. This can be
+    /// used to draw several markers around a single feature (e.g. status dots around a pin) without creating
+    /// additional geometries for them:
+    ///
+    /// ```no_run
+    /// # use galileo::render::render_bundle::RenderBundle;
+    /// # use galileo::render::point_paint::PointPaint;
+    /// # use galileo::Color;
+    /// # use galileo_types::cartesian::Point3d;
+    /// # use nalgebra::Vector2;
+    /// # fn f(bundle: &mut RenderBundle, anchor: Point3d) {
+    /// bundle.add_offset_point(&anchor, Vector2::new(0.0, 0.0), PointPaint::circle(Color::BLUE, 20.0), 1.0);
+    /// bundle.add_offset_point(&anchor, Vector2::new(10.0, 10.0), PointPaint::circle(Color::RED, 6.0), 1.0);
+    /// # }
+    /// ```
+    ///
+    /// Note that [`PointPaint::dot`] is drawn in map coordinates, so the offset should not be used with it.
+    pub fn add_offset_point<N, P>(
+        &mut self,
+        anchor: &P,
+        offset: Vector2<f32>,
+        paint: PointPaint,
+        min_resolution: f64,
+    ) -> PrimitiveId
+    where
+        N: AsPrimitive<f32>,
+        P: CartesianPoint3d<Num = N> + Clone,
+    {
+        let paint_offset = paint.offset;
+        let paint = paint.with_offset(paint_offset + offset);
+        self.add(
+            RenderPrimitive::<N, P, impls::Contour<P>, impls::Polygon<P>>::Point(
+                Cow::Borrowed(anchor),
+                Cow::Owned(paint),
+            ),
+            min_resolution,
+        )
+    }
+
+    /// Adds several point primitives placed around the `anchor` point. Every item of the iterator is a pair of the
+    /// offset in screen pixels and the paint of the primitive. See [`RenderBundle::add_offset_point`] for details.
+    ///
+    /// Returns ids of the added primitives in the same order as the items were given.
+    pub fn add_offset_points<'a, N, P>(
+        &mut self,
+        anchor: &P,
+        points: impl IntoIterator<Item = (Vector2<f32>, PointPaint<'a>)>,
+        min_resolution: f64,
+    ) -> Vec<PrimitiveId>
+    where
+        N: AsPrimitive<f32>,
+        P: CartesianPoint3d<Num = N> + Clone,
+    {
+        points
+            .into_iter()
+            .map(|(offset, paint)| self.add_offset_point(anchor, offset, paint, min_resolution))
+            .collect()
+    }
+
     /// Removes the primitive from the bundle.
     pub fn remove(&mut self, primitive_id: PrimitiveId) -> Result<(), GalileoError> {
         match &mut self.0 {
@@ -123,6 +219,25 @@ impl RenderBundle {
         }
     }
 
+    /// Moves a point primitive (e.g. a marker) to the new `anchor` point without tessellating it again.
+    ///
+    /// Combined with [`RenderBundle::update`] to change the rotation of the point and
+    /// [`Canvas::update_packed_bundle`](crate::render::Canvas::update_packed_bundle) to write the changes into the GPU buffers, this allows to animate markers cheaply. Dot points and primitives
+    /// other than points cannot be moved, so an error is returned for them.
+    pub fn move_point<N, P>(
+        &mut self,
+        primitive_id: PrimitiveId,
+        anchor: &P,
+    ) -> Result<(), GalileoError>
+    where
+        N: AsPrimitive<f32>,
+        P: CartesianPoint3d<Num = N>,
+    {
+        match &mut self.0 {
+            RenderBundleType::Tessellating(inner) => inner.move_point(primitive_id, anchor),
+        }
+    }
+
     /// Returns true if the bundle has not primitives added.
     pub fn is_empty(&self) -> bool {
         match &self.0 {
@@ -145,6 +260,51 @@ impl RenderBundle {
     }
 }
 
+/// Memory used by a [`RenderBundle`] in bytes.
+///
+/// The values give the size of the data that is moved to the GPU when the bundle is packed, so
+/// they can be used to estimate GPU memory consumption of a layer.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct BundleMemoryUsage {
+    /// Size of vertex buffers.
+    pub vertices: usize,
+    /// Size of index buffers.
+    pub indices: usize,
+    /// Size of image bitmaps.
+    pub images: usize,
+}
+
+impl BundleMemoryUsage {
+    /// Total size of all buffers.
+    pub fn total(&self) -> usize {
+        self.vertices + self.indices + self.images
+    }
+}
+
+impl std::ops::Add for BundleMemoryUsage {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self {
+            vertices: self.vertices + rhs.vertices,
+            indices: self.indices + rhs.indices,
+            images: self.images + rhs.images,
+        }
+    }
+}
+
+impl std::ops::AddAssign for BundleMemoryUsage {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl std::iter::Sum for BundleMemoryUsage {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), |acc, v| acc + v)
+    }
+}
+
 /// Rendering primitive.
 pub enum RenderPrimitive<'a, N, P, C, Poly>
 where
@@ -160,6 +320,12 @@ where
     Contour(Cow<'a, C>, LinePaint),
     /// Polygon primitive
     Polygon(Cow<'a, Poly>, PolygonPaint),
+    /// Contour (line) primitive with a color for every point of the contour. See
+    /// [`RenderPrimitive::new_contour_with_vertex_colors`].
+    ColoredContour(Cow<'a, C>, LinePaint, Cow<'a, [Color]>),
+    /// Polygon primitive with a color for every point of the polygon. See
+    /// [`RenderPrimitive::new_polygon_with_vertex_colors`].
+    ColoredPolygon(Cow<'a, Poly>, PolygonPaint, Cow<'a, [Color]>),
 }
 
 impl<'a, N, P, C, Poly> RenderPrimitive<'a, N, P, C, Poly>
@@ -199,4 +365,31 @@ where
     pub fn new_polygon_ref(polygon: &'a Poly, paint: PolygonPaint) -> Self {
         Self::Polygon(Cow::Borrowed(polygon), paint)
     }
+
+    /// Creates a new contour primitive with a color for every point of the contour. The colors are interpolated
+    /// between the points, e.g. to show a value changing along a route.
+    ///
+    /// The color of the `paint` is used for the points without a color in the `colors` slice.
+    pub fn new_contour_with_vertex_colors(
+        contour: C,
+        paint: LinePaint,
+        colors: Vec<Color>,
+    ) -> Self {
+        Self::ColoredContour(Cow::Owned(contour), paint, Cow::Owned(colors))
+    }
+
+    /// Creates a new polygon primitive with a color for every point of the polygon. The colors are interpolated over
+    /// the area of the polygon.
+    ///
+    /// The colors are given in the order the points are iterated by
+    /// [`Polygon::iter_contours`](galileo_types::Polygon::iter_contours): first the points of the outer contour, then
+    /// the points of the inner contours. The color of the `paint` is used for the points without a color in the
+    /// `colors` slice. The hatching of the `paint` is drawn with its own color.
+    pub fn new_polygon_with_vertex_colors(
+        polygon: Poly,
+        paint: PolygonPaint,
+        colors: Vec<Color>,
+    ) -> Self {
+        Self::ColoredPolygon(Cow::Owned(polygon), paint, Cow::Owned(colors))
+    }
 }
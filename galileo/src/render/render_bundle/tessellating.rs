@@ -1,9 +1,10 @@
 use std::borrow::Borrow;
+use std::f32::consts::{FRAC_PI_2, PI};
 use std::mem::size_of;
 use std::ops::Range;
 use std::sync::Arc;
 
-use galileo_types::cartesian::{CartesianPoint2d, CartesianPoint3d, Point2d, Point3d};
+use galileo_types::cartesian::{CartesianPoint2d, CartesianPoint3d, Point2d, Point3d, Rect};
 use galileo_types::contour::Contour;
 use galileo_types::impls::ClosedContour;
 use galileo_types::Polygon;
@@ -22,10 +23,14 @@ use serde::{Deserialize, Serialize};
 
 use crate::decoded_image::DecodedImage;
 use crate::error::GalileoError;
-use crate::render::point_paint::{CircleFill, PointPaint, PointShape, SectorParameters};
-use crate::render::render_bundle::RenderPrimitive;
-use crate::render::text::{FontService, TextShaping, TextStyle};
-use crate::render::{ImagePaint, LinePaint, PolygonPaint, PrimitiveId};
+use crate::render::point_paint::{
+    CircleFill, LabelBackground, PointPaint, PointShape, RotationAlignment, SectorParameters,
+};
+use crate::render::render_bundle::{BundleMemoryUsage, RenderPrimitive};
+use crate::render::text::{
+    FontService, HorizontalAlignment, TextMetrics, TextShaping, TextStyle, VerticalAlignment,
+};
+use crate::render::{Hatching, ImagePaint, LineCap, LinePaint, PolygonPaint, PrimitiveId};
 use crate::view::MapView;
 use crate::Color;
 
@@ -42,8 +47,13 @@ pub(crate) struct TessellatingRenderBundle {
     vacant_image_ids: Vec<usize>,
     vacant_image_store_ids: Vec<usize>,
     buffer_size: usize,
+    tolerance: f32,
 }
 
+/// Default maximum distance in pixels between a curve and its tessellated approximation.
+pub(crate) const DEFAULT_TESSELLATION_TOLERANCE: f32 = 0.1;
+const MIN_TESSELLATION_TOLERANCE: f32 = 0.001;
+
 #[derive(Debug, Clone)]
 pub(crate) enum ImageStoreInfo {
     Vacant,
@@ -62,18 +72,54 @@ pub(crate) type ScreenRefTessellation = VertexBuffers<ScreenRefVertex, u32>;
 #[repr(C)]
 pub(crate) struct ScreenRefVertex {
     position: [f32; 3],
+    /// Position of the vertex relative to the base point in pixels, before rotation and scaling.
     normal: [f32; 2],
     color: [u8; 4],
+    /// Offset in pixels added to the normal after rotation and scaling.
+    offset: [f32; 2],
+    /// Rotation in radians counterclockwise.
+    rotation: f32,
+    scale: f32,
+    /// Logarithmic stops of the resolution dependent scale, see
+    /// [`ResolutionScale::to_log_parameters`](crate::render::ResolutionScale::to_log_parameters).
+    resolution_scale: [f32; 4],
+}
+
+impl ScreenRefVertex {
+    fn new(position: [f32; 3], normal: [f32; 2], color: [u8; 4], offset: Vector2<f32>) -> Self {
+        Self {
+            position,
+            normal,
+            color,
+            offset: offset.into(),
+            rotation: 0.0,
+            scale: 1.0,
+            resolution_scale: [0.0; 4],
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) enum PrimitiveInfo {
     None,
     Vacant,
-    MapRef { vertex_range: Range<usize> },
-    ScreenRef { vertex_range: Range<usize> },
-    Dot { point_index: usize },
-    Image { image_index: usize },
+    MapRef {
+        vertex_range: Range<usize>,
+    },
+    ScreenRef {
+        vertex_range: Range<usize>,
+    },
+    Dot {
+        point_index: usize,
+    },
+    Image {
+        image_index: usize,
+    },
+    /// Screen referenced vertices drawn together with a set of images, e.g. a label with an image background.
+    Composite {
+        vertex_range: Range<usize>,
+        image_indices: Vec<usize>,
+    },
 }
 
 impl Default for TessellatingRenderBundle {
@@ -96,9 +142,18 @@ impl TessellatingRenderBundle {
             vacant_image_ids: vec![],
             vacant_image_store_ids: vec![],
             buffer_size: 0,
+            tolerance: DEFAULT_TESSELLATION_TOLERANCE,
         }
     }
 
+    pub fn tessellation_tolerance(&self) -> f32 {
+        self.tolerance
+    }
+
+    pub fn set_tessellation_tolerance(&mut self, tolerance: f32) {
+        self.tolerance = tolerance.max(MIN_TESSELLATION_TOLERANCE);
+    }
+
     pub fn approx_buffer_size(&self) -> usize {
         self.buffer_size
     }
@@ -107,6 +162,47 @@ impl TessellatingRenderBundle {
         self.buffer_size = size;
     }
 
+    pub fn memory_usage(&self) -> BundleMemoryUsage {
+        let clip_vertices = self
+            .clip_area
+            .as_ref()
+            .map(|clip| clip.vertices.len())
+            .unwrap_or_default();
+        let clip_indices = self
+            .clip_area
+            .as_ref()
+            .map(|clip| clip.indices.len())
+            .unwrap_or_default();
+        let image_count = self
+            .images
+            .iter()
+            .filter(|info| matches!(info, ImageInfo::Image(_)))
+            .count();
+
+        let vertices = (self.poly_tessellation.vertices.len() + clip_vertices)
+            * size_of::<PolyVertex>()
+            + self.screen_ref.vertices.len() * size_of::<ScreenRefVertex>()
+            + self.points.len() * size_of::<PointInstance>()
+            + image_count * 4 * size_of::<ImageVertex>();
+        let indices =
+            (self.poly_tessellation.indices.len() + self.screen_ref.indices.len() + clip_indices)
+                * size_of::<u32>();
+        let images = self
+            .image_store
+            .iter()
+            .map(|stored| match stored {
+                ImageStoreInfo::Vacant => 0,
+                ImageStoreInfo::Image(image) => image.size(),
+            })
+            .sum();
+
+        BundleMemoryUsage {
+            vertices,
+            indices,
+            images,
+        }
+    }
+
     pub fn clip_area<N, P, Poly>(&mut self, polygon: &Poly)
     where
         N: AsPrimitive<f32>,
@@ -119,7 +215,9 @@ impl TessellatingRenderBundle {
             polygon,
             PolygonPaint {
                 color: Color::BLACK,
+                hatching: None,
             },
+            &[],
             &mut tessellation,
         );
 
@@ -146,24 +244,28 @@ impl TessellatingRenderBundle {
                 opacity,
                 tex_coords: [0.0, 1.0],
                 offset: [0.0, 0.0],
+                map_aligned: 0.0,
             },
             ImageVertex {
                 position: [vertices[1].x() as f32, vertices[1].y() as f32],
                 opacity,
                 tex_coords: [0.0, 0.0],
                 offset: [0.0, 0.0],
+                map_aligned: 0.0,
             },
             ImageVertex {
                 position: [vertices[3].x() as f32, vertices[3].y() as f32],
                 opacity,
                 tex_coords: [1.0, 1.0],
                 offset: [0.0, 0.0],
+                map_aligned: 0.0,
             },
             ImageVertex {
                 position: [vertices[2].x() as f32, vertices[2].y() as f32],
                 opacity,
                 tex_coords: [1.0, 0.0],
                 offset: [0.0, 0.0],
+                map_aligned: 0.0,
             },
         ];
 
@@ -180,49 +282,32 @@ impl TessellatingRenderBundle {
         position: &P,
         image: Arc<DecodedImage>,
         opacity: u8,
-        width: f32,
-        height: f32,
-        offset: Vector2<f32>,
+        corner_offsets: [[f32; 2]; 4],
+        rotation_alignment: RotationAlignment,
     ) -> PrimitiveInfo
     where
         N: AsPrimitive<f32>,
         P: CartesianPoint3d<Num = N>,
     {
         let opacity = opacity as f32 / 255.0;
+        let map_aligned = match rotation_alignment {
+            RotationAlignment::Screen => 0.0,
+            RotationAlignment::Map => 1.0,
+        };
 
         self.buffer_size += image.size() + size_of::<ImageVertex>() * 4;
 
         let position = [position.x().as_(), position.y().as_()];
-        let offset_x = -offset[0] * width;
-        let offset_y = offset[1] * height;
+        let tex_coords = [[0.0, 1.0], [0.0, 0.0], [1.0, 1.0], [1.0, 0.0]];
 
         let index = self.add_image_to_store(image);
-        let vertices = [
-            ImageVertex {
-                position,
-                opacity,
-                tex_coords: [0.0, 1.0],
-                offset: [offset_x, offset_y - height],
-            },
-            ImageVertex {
-                position,
-                opacity,
-                tex_coords: [0.0, 0.0],
-                offset: [offset_x, offset_y],
-            },
-            ImageVertex {
-                position,
-                opacity,
-                tex_coords: [1.0, 1.0],
-                offset: [offset_x + width, offset_y - height],
-            },
-            ImageVertex {
-                position,
-                opacity,
-                tex_coords: [1.0, 0.0],
-                offset: [offset_x + width, offset_y],
-            },
-        ];
+        let vertices = std::array::from_fn(|i| ImageVertex {
+            position,
+            opacity,
+            tex_coords: tex_coords[i],
+            offset: corner_offsets[i],
+            map_aligned,
+        });
 
         let image_index = self.add_image_info(index, vertices);
 
@@ -289,10 +374,16 @@ impl TessellatingRenderBundle {
         match primitive {
             RenderPrimitive::Point(point, paint) => self.add_point::<N, P>(point.borrow(), &paint),
             RenderPrimitive::Contour(contour, paint) => {
-                self.add_line::<N, P, C>(contour.borrow(), paint, min_resolution)
+                self.add_line::<N, P, C>(contour.borrow(), paint, &[], min_resolution)
             }
             RenderPrimitive::Polygon(polygon, paint) => {
-                self.add_polygon::<N, P, Poly>(polygon.borrow(), paint, min_resolution)
+                self.add_polygon::<N, P, Poly>(polygon.borrow(), paint, &[], min_resolution)
+            }
+            RenderPrimitive::ColoredContour(contour, paint, colors) => {
+                self.add_line::<N, P, C>(contour.borrow(), paint, &colors, min_resolution)
+            }
+            RenderPrimitive::ColoredPolygon(polygon, paint, colors) => {
+                self.add_polygon::<N, P, Poly>(polygon.borrow(), paint, &colors, min_resolution)
             }
         }
     }
@@ -321,8 +412,17 @@ impl TessellatingRenderBundle {
             PrimitiveInfo::MapRef { vertex_range } => {
                 self.update_map_ref(vertex_range.clone(), primitive)
             }
-            PrimitiveInfo::Vacant => Ok(()),
-            _ => todo!(),
+            PrimitiveInfo::ScreenRef { vertex_range } => {
+                self.update_screen_ref(vertex_range.clone(), primitive)
+            }
+            PrimitiveInfo::Image { image_index } => self.update_image(*image_index, primitive),
+            PrimitiveInfo::Vacant | PrimitiveInfo::None => Ok(()),
+            PrimitiveInfo::Dot { .. } => Err(GalileoError::Generic(
+                "updating of dot primitives is not supported".into(),
+            )),
+            PrimitiveInfo::Composite { .. } => Err(GalileoError::Generic(
+                "updating of composite primitives is not supported".into(),
+            )),
         }
     }
 
@@ -340,6 +440,15 @@ impl TessellatingRenderBundle {
             PrimitiveInfo::ScreenRef { vertex_range } => self.remove_screen_ref(vertex_range),
             PrimitiveInfo::Dot { point_index } => self.remove_dot(point_index),
             PrimitiveInfo::Image { image_index } => self.remove_image(image_index),
+            PrimitiveInfo::Composite {
+                vertex_range,
+                image_indices,
+            } => {
+                self.remove_screen_ref(vertex_range)?;
+                image_indices
+                    .into_iter()
+                    .try_for_each(|image_index| self.remove_image(image_index))
+            }
             PrimitiveInfo::Vacant => Ok(()),
             PrimitiveInfo::None => Ok(()),
         }
@@ -429,6 +538,10 @@ impl TessellatingRenderBundle {
             match info {
                 PrimitiveInfo::ScreenRef {
                     ref mut vertex_range,
+                }
+                | PrimitiveInfo::Composite {
+                    ref mut vertex_range,
+                    ..
                 } if vertex_range.start >= range.end => {
                     vertex_range.start -= len;
                     vertex_range.end -= len;
@@ -511,13 +624,20 @@ impl TessellatingRenderBundle {
                 opacity,
                 width,
                 height,
+                anchor,
+                rotation_alignment,
             } => self.add_image_point(
                 point,
                 image.clone(),
                 *opacity,
-                *width,
-                *height,
-                paint.offset,
+                image_corner_offsets(
+                    *width * paint.scale,
+                    *height * paint.scale,
+                    *anchor,
+                    paint.rotation,
+                    paint.offset,
+                ),
+                *rotation_alignment,
             ),
             PointShape::Circle {
                 fill,
@@ -556,16 +676,213 @@ impl TessellatingRenderBundle {
                     vertex_range: start_index..self.screen_ref.vertices.len(),
                 }
             }
-            PointShape::Label { text, style } => self.add_label(point, text, style, paint.offset),
+            PointShape::Label {
+                text,
+                style,
+                background,
+            } => self.add_label(point, text, style, background.as_ref(), paint.offset),
         };
 
+        if let PrimitiveInfo::ScreenRef { vertex_range } = &info {
+            self.set_screen_ref_transform(vertex_range.clone(), paint);
+        }
+
         self.add_primitive_info(info)
     }
 
+    /// Sets offset, rotation and scale of the point from the paint to the already tessellated vertices.
+    ///
+    /// Labels are not transformed, since the offset is applied to them while shaping the text.
+    fn set_screen_ref_transform(&mut self, range: Range<usize>, paint: &PointPaint) {
+        if matches!(paint.shape, PointShape::Label { .. }) {
+            return;
+        }
+
+        let Some(vertices) = self.screen_ref.vertices.get_mut(range) else {
+            return;
+        };
+
+        let resolution_scale = paint
+            .resolution_scale
+            .map(|scale| scale.to_log_parameters())
+            .unwrap_or_default();
+
+        for vertex in vertices {
+            vertex.offset = paint.offset.into();
+            vertex.rotation = paint.rotation;
+            vertex.scale = paint.scale;
+            vertex.resolution_scale = resolution_scale;
+        }
+    }
+
+    /// Updates the screen-referenced point in place. The point is tessellated again and its vertices (including
+    /// colors) replace the old ones. Returns an error if the new shape has a different topology, in which case the
+    /// primitive must be removed and added again.
+    fn update_screen_ref<N, P, C, Poly>(
+        &mut self,
+        range: Range<usize>,
+        primitive: RenderPrimitive<N, P, C, Poly>,
+    ) -> Result<(), GalileoError>
+    where
+        N: AsPrimitive<f32>,
+        P: CartesianPoint3d<Num = N> + Clone,
+        C: Contour<Point = P> + Clone,
+        Poly: Polygon + Clone,
+        Poly::Contour: Contour<Point = P>,
+    {
+        let RenderPrimitive::Point(point, paint) = primitive else {
+            return Err(GalileoError::Generic(
+                "screen referenced primitive can only be updated with a point".into(),
+            ));
+        };
+
+        let mut updated = Self::new();
+        updated.tolerance = self.tolerance;
+        updated.add_point::<N, P>(point.borrow(), &paint);
+
+        let old_indices = self
+            .screen_ref
+            .indices
+            .iter()
+            .filter(|&&index| range.contains(&(index as usize)))
+            .map(|&index| index as usize - range.start);
+        let new_indices = updated
+            .screen_ref
+            .indices
+            .iter()
+            .map(|&index| index as usize);
+        if updated.screen_ref.vertices.len() != range.len() || !old_indices.eq(new_indices) {
+            return Err(GalileoError::Generic(
+                "shape of the screen referenced primitive has changed".into(),
+            ));
+        }
+
+        let Some(vertices) = self.screen_ref.vertices.get_mut(range) else {
+            return Err(GalileoError::Generic("invalid vertex range".into()));
+        };
+        vertices.copy_from_slice(&updated.screen_ref.vertices);
+
+        Ok(())
+    }
+
+    fn update_image<N, P, C, Poly>(
+        &mut self,
+        image_index: usize,
+        primitive: RenderPrimitive<N, P, C, Poly>,
+    ) -> Result<(), GalileoError>
+    where
+        N: AsPrimitive<f32>,
+        P: CartesianPoint3d<Num = N> + Clone,
+        C: Contour<Point = P> + Clone,
+        Poly: Polygon + Clone,
+        Poly::Contour: Contour<Point = P>,
+    {
+        let RenderPrimitive::Point(_, paint) = primitive else {
+            return Err(GalileoError::Generic(
+                "image primitive can only be updated with a point".into(),
+            ));
+        };
+        let PointShape::Image {
+            opacity,
+            width,
+            height,
+            anchor,
+            ..
+        } = &paint.shape
+        else {
+            return Err(GalileoError::Generic(
+                "image primitive can only be updated with an image paint".into(),
+            ));
+        };
+        let Some(ImageInfo::Image((_, vertices))) = self.images.get_mut(image_index) else {
+            return Err(GalileoError::Generic("invalid image id".into()));
+        };
+
+        let corner_offsets = image_corner_offsets(
+            *width * paint.scale,
+            *height * paint.scale,
+            *anchor,
+            paint.rotation,
+            paint.offset,
+        );
+        for (vertex, offset) in vertices.iter_mut().zip(corner_offsets) {
+            vertex.offset = offset;
+            vertex.opacity = *opacity as f32 / 255.0;
+        }
+
+        Ok(())
+    }
+
+    /// Moves a point primitive to the new `anchor` without tessellating it again. Shape, offset and rotation of the
+    /// point stay the same.
+    pub fn move_point<N, P>(
+        &mut self,
+        primitive_id: PrimitiveId,
+        anchor: &P,
+    ) -> Result<(), GalileoError>
+    where
+        N: AsPrimitive<f32>,
+        P: CartesianPoint3d<Num = N>,
+    {
+        let Some(info) = self.primitives.get(primitive_id.0).cloned() else {
+            return Err(GalileoError::Generic(
+                "no primitive with the given id".into(),
+            ));
+        };
+
+        let position = [anchor.x().as_(), anchor.y().as_(), anchor.z().as_()];
+        match info {
+            PrimitiveInfo::ScreenRef { vertex_range } => {
+                self.move_screen_ref(vertex_range, position);
+                Ok(())
+            }
+            PrimitiveInfo::Image { image_index } => self.move_image(image_index, position),
+            PrimitiveInfo::Composite {
+                vertex_range,
+                image_indices,
+            } => {
+                self.move_screen_ref(vertex_range, position);
+                image_indices
+                    .into_iter()
+                    .try_for_each(|image_index| self.move_image(image_index, position))
+            }
+            PrimitiveInfo::Vacant | PrimitiveInfo::None => Ok(()),
+            PrimitiveInfo::Dot { .. } => Err(GalileoError::Generic(
+                "moving of dot primitives is not supported".into(),
+            )),
+            PrimitiveInfo::MapRef { .. } => Err(GalileoError::Generic(
+                "only point primitives can be moved".into(),
+            )),
+        }
+    }
+
+    fn move_screen_ref(&mut self, range: Range<usize>, position: [f32; 3]) {
+        if let Some(vertices) = self.screen_ref.vertices.get_mut(range) {
+            for vertex in vertices {
+                vertex.position = position;
+            }
+        }
+    }
+
+    fn move_image(&mut self, image_index: usize, position: [f32; 3]) -> Result<(), GalileoError> {
+        let Some(ImageInfo::Image((_, vertices))) = self.images.get_mut(image_index) else {
+            return Err(GalileoError::Generic("invalid image id".into()));
+        };
+
+        for vertex in vertices {
+            vertex.position = [position[0], position[1]];
+        }
+
+        Ok(())
+    }
+
+    /// Adds a line to the bundle. Points of the line take colors from `colors` in order, and the color of the `paint`
+    /// if there are more points than colors.
     pub fn add_line<N, P, C>(
         &mut self,
         line: &C,
         paint: LinePaint,
+        colors: &[Color],
         min_resolution: f64,
     ) -> PrimitiveId
     where
@@ -573,7 +890,7 @@ impl TessellatingRenderBundle {
         P: CartesianPoint3d<Num = N>,
         C: Contour<Point = P>,
     {
-        let range = self.add_line_lod(line, paint, min_resolution);
+        let range = self.add_line_lod(line, paint, colors, min_resolution);
 
         self.add_primitive_info(PrimitiveInfo::MapRef {
             vertex_range: range,
@@ -584,6 +901,7 @@ impl TessellatingRenderBundle {
         &mut self,
         line: &C,
         paint: LinePaint,
+        colors: &[Color],
         min_resolution: f64,
     ) -> Range<usize>
     where
@@ -592,8 +910,9 @@ impl TessellatingRenderBundle {
         C: Contour<Point = P>,
     {
         let tessellation = &mut self.poly_tessellation;
-        let mut path_builder = BuilderWithAttributes::new(1);
+        let mut path_builder = BuilderWithAttributes::new(VERTEX_ATTRIBUTES);
         let mut iterator = line.iter_points();
+        let mut colors = colors.iter();
 
         let Some(first_point) = iterator.next() else {
             return 0..0;
@@ -604,7 +923,7 @@ impl TessellatingRenderBundle {
                 first_point.x().as_() / min_resolution as f32,
                 first_point.y().as_() / min_resolution as f32,
             ),
-            &[first_point.z().as_()],
+            &vertex_attributes(first_point.z().as_(), colors.next(), paint.color),
         );
 
         for p in iterator {
@@ -613,7 +932,7 @@ impl TessellatingRenderBundle {
                     p.x().as_() / min_resolution as f32,
                     p.y().as_() / min_resolution as f32,
                 ),
-                &[p.z().as_()],
+                &vertex_attributes(p.z().as_(), colors.next(), paint.color),
             );
         }
 
@@ -623,8 +942,13 @@ impl TessellatingRenderBundle {
         let vertex_constructor = LineVertexConstructor {
             width: paint.width as f32,
             offset: paint.offset as f32,
-            color: paint.color.to_f32_array(),
             resolution: min_resolution as f32,
+            width_scale: paint
+                .width_scale
+                .map(|scale| scale.to_log_parameters())
+                .unwrap_or_default(),
+            crisp: paint.crisp,
+            color: paint.color.to_f32_array(),
             path: &path,
         };
 
@@ -638,7 +962,7 @@ impl TessellatingRenderBundle {
                 .with_line_cap(paint.line_cap.into())
                 .with_line_width(paint.width as f32)
                 .with_miter_limit(1.0)
-                .with_tolerance(0.1)
+                .with_tolerance(self.tolerance)
                 .with_line_join(LineJoin::Round),
             &mut BuffersBuilder::new(tessellation, vertex_constructor),
         ) {
@@ -654,10 +978,13 @@ impl TessellatingRenderBundle {
         start_index..end_index
     }
 
+    /// Adds a polygon to the bundle. Points of the polygon take colors from `colors` in order, and the color of the
+    /// `paint` if there are more points than colors.
     pub fn add_polygon<N, P, Poly>(
         &mut self,
         polygon: &Poly,
         paint: PolygonPaint,
+        colors: &[Color],
         min_resolution: f64,
     ) -> PrimitiveId
     where
@@ -666,7 +993,7 @@ impl TessellatingRenderBundle {
         Poly: Polygon,
         Poly::Contour: Contour<Point = P>,
     {
-        let vertex_range = self.add_polygon_lod(polygon, paint, min_resolution as f32);
+        let vertex_range = self.add_polygon_lod(polygon, paint, colors, min_resolution as f32);
         self.add_primitive_info(PrimitiveInfo::MapRef { vertex_range })
     }
 
@@ -710,10 +1037,18 @@ impl TessellatingRenderBundle {
         Poly: Polygon + Clone,
         Poly::Contour: Contour<Point = P>,
     {
-        let color = match primitive {
-            RenderPrimitive::Contour(_, LinePaint { color, .. })
-            | RenderPrimitive::Polygon(_, PolygonPaint { color }) => color,
-            _ => {
+        let (color, line_color) = match primitive {
+            RenderPrimitive::Contour(_, LinePaint { color, .. }) => (color, color),
+            RenderPrimitive::Polygon(_, PolygonPaint { color, hatching }) => (
+                color,
+                hatching.map(|hatching| hatching.color).unwrap_or(color),
+            ),
+            RenderPrimitive::ColoredContour(..) | RenderPrimitive::ColoredPolygon(..) => {
+                return Err(GalileoError::Generic(
+                    "updating of primitives with vertex colors is not supported".into(),
+                ));
+            }
+            RenderPrimitive::Point(..) => {
                 return Err(GalileoError::Generic(
                     "expected line or polygon primitive, but got a point".into(),
                 ));
@@ -721,7 +1056,12 @@ impl TessellatingRenderBundle {
         };
 
         for vertex in &mut self.poly_tessellation.vertices[range] {
-            vertex.color = color.to_f32_array();
+            // Fill vertices have zero normals, while the vertices of lines (including hatching) don't
+            vertex.color = if vertex.normal == [0.0, 0.0] {
+                color.to_f32_array()
+            } else {
+                line_color.to_f32_array()
+            };
         }
 
         Ok(())
@@ -731,7 +1071,8 @@ impl TessellatingRenderBundle {
         &mut self,
         polygon: &Poly,
         paint: PolygonPaint,
-        _min_resolution: f32,
+        colors: &[Color],
+        min_resolution: f32,
     ) -> Range<usize>
     where
         N: AsPrimitive<f32>,
@@ -743,13 +1084,39 @@ impl TessellatingRenderBundle {
         let start_index = lod.vertices.len();
         let start_index_count = lod.indices.len();
 
-        Self::tessellate_polygon(polygon, paint, lod);
+        Self::tessellate_polygon(polygon, paint, colors, lod);
 
         let end_index = lod.vertices.len();
 
         self.buffer_size += (end_index - start_index) * size_of::<PolyVertex>();
         self.buffer_size += (lod.indices.len() - start_index_count) * size_of::<u32>();
 
+        let Some(hatching) = paint.hatching else {
+            return start_index..end_index;
+        };
+
+        // Hatching lines are stored right after the fill, so that the primitive is still a single range of vertices
+        let line_paint = LinePaint {
+            color: hatching.color,
+            width: hatching.width,
+            offset: 0.0,
+            line_cap: LineCap::Butt,
+            width_scale: None,
+            crisp: false,
+        };
+        let mut end_index = end_index;
+        for segment in hatch_segments(polygon, &hatching, min_resolution) {
+            let range = self.add_line_lod(
+                &galileo_types::impls::Contour::open(segment.to_vec()),
+                line_paint,
+                &[],
+                min_resolution as f64,
+            );
+            if !range.is_empty() {
+                end_index = range.end;
+            }
+        }
+
         start_index..end_index
     }
 
@@ -760,6 +1127,7 @@ impl TessellatingRenderBundle {
     fn tessellate_polygon<N, P, Poly>(
         polygon: &Poly,
         paint: PolygonPaint,
+        colors: &[Color],
         tessellation: &mut VertexBuffers<PolyVertex, u32>,
     ) where
         N: AsPrimitive<f32>,
@@ -767,21 +1135,25 @@ impl TessellatingRenderBundle {
         Poly: Polygon,
         Poly::Contour: Contour<Point = P>,
     {
-        let mut path_builder = BuilderWithAttributes::new(1);
+        let mut path_builder = BuilderWithAttributes::new(VERTEX_ATTRIBUTES);
+        let mut colors = colors.iter();
         for contour in polygon.iter_contours() {
             let mut iterator = contour.iter_points();
 
             if let Some(first_point) = iterator.next() {
                 let _ = path_builder.begin(
                     point(first_point.x().as_(), first_point.y().as_()),
-                    &[first_point.z().as_()],
+                    &vertex_attributes(first_point.z().as_(), colors.next(), paint.color),
                 );
             } else {
                 return;
             }
 
             for p in iterator {
-                let _ = path_builder.line_to(point(p.x().as_(), p.y().as_()), &[p.z().as_()]);
+                let _ = path_builder.line_to(
+                    point(p.x().as_(), p.y().as_()),
+                    &vertex_attributes(p.z().as_(), colors.next(), paint.color),
+                );
             }
 
             path_builder.end(true);
@@ -794,7 +1166,7 @@ impl TessellatingRenderBundle {
         };
         let mut tesselator = FillTessellator::new();
 
-        if let Err(err) = tesselator.tessellate(
+        if let Err(err) = tesselator.tessellate_path(
             &path,
             &FillOptions::DEFAULT,
             &mut BuffersBuilder::new(tessellation, vertex_constructor),
@@ -831,7 +1203,9 @@ impl TessellatingRenderBundle {
 
             if let Err(err) = StrokeTessellator::new().tessellate(
                 &path,
-                &StrokeOptions::DEFAULT.with_line_width(outline.width as f32 * 2.0),
+                &StrokeOptions::DEFAULT
+                    .with_line_width(outline.width as f32 * 2.0)
+                    .with_tolerance(self.tolerance),
                 &mut BuffersBuilder::new(&mut self.screen_ref, vertex_constructor),
             ) {
                 log::warn!("Shape tessellation failed: {err:?}");
@@ -848,7 +1222,7 @@ impl TessellatingRenderBundle {
 
             if let Err(err) = FillTessellator::new().tessellate(
                 &path,
-                &FillOptions::DEFAULT,
+                &FillOptions::DEFAULT.with_tolerance(self.tolerance),
                 &mut BuffersBuilder::new(&mut self.screen_ref, vertex_constructor),
             ) {
                 log::warn!("Shape tessellation failed: {err:?}");
@@ -906,15 +1280,16 @@ impl TessellatingRenderBundle {
             .abs()
             .min(std::f32::consts::PI * 2.0);
 
-        let center = ScreenRefVertex {
-            position: [position.x().as_(), position.y().as_(), position.z().as_()],
-            normal: [offset.x, offset.y],
-            color: fill.center_color.to_u8_array(),
-        };
+        let center = ScreenRefVertex::new(
+            [position.x().as_(), position.y().as_(), position.z().as_()],
+            [0.0, 0.0],
+            fill.center_color.to_u8_array(),
+            offset,
+        );
 
         let is_full_circle = (dr - std::f32::consts::PI * 2.0).abs() < TOLERANCE;
 
-        let mut contour = get_circle_sector(radius, start_angle, end_angle);
+        let mut contour = get_circle_sector(radius, start_angle, end_angle, self.tolerance);
         let first_index = self.screen_ref.vertices.len() as u32;
 
         let start_vertex_count = self.screen_ref.vertices.len();
@@ -929,11 +1304,12 @@ impl TessellatingRenderBundle {
                 indices.push(vertices.len() as u32 + first_index);
             }
 
-            vertices.push(ScreenRefVertex {
-                position: [position.x().as_(), position.y().as_(), position.z().as_()],
-                normal: (point + offset).coords.into(),
-                color: fill.side_color.to_u8_array(),
-            });
+            vertices.push(ScreenRefVertex::new(
+                [position.x().as_(), position.y().as_(), position.z().as_()],
+                point.coords.into(),
+                fill.side_color.to_u8_array(),
+                offset,
+            ));
         }
 
         if is_full_circle {
@@ -1023,58 +1399,327 @@ impl TessellatingRenderBundle {
         position: &P,
         text: &str,
         style: &TextStyle,
+        background: Option<&LabelBackground>,
         offset: Vector2<f32>,
     ) -> PrimitiveInfo
     where
         N: AsPrimitive<f32>,
         P: CartesianPoint3d<Num = N>,
     {
-        FontService::with(
-            |font_service| match font_service.shape(text, style, offset) {
-                Ok(TextShaping::Tessellation { glyphs, .. }) => {
-                    let indices_start = self.screen_ref.indices.len();
+        FontService::with(|font_service| {
+            let vertex_start = self.screen_ref.vertices.len();
+            let mut image_indices = vec![];
+            let mut text_offset = offset;
+
+            if let Some(background) = background {
+                let metrics = match font_service.measure(text, style) {
+                    Ok(metrics) => metrics,
+                    Err(err) => {
+                        log::error!("Error measuring text label: {err:?}");
+                        return PrimitiveInfo::None;
+                    }
+                };
+
+                let (text_origin, rect) = label_background_layout(&metrics, style, background);
+                text_offset += text_origin;
+
+                match background {
+                    LabelBackground::Rect {
+                        fill,
+                        corner_radius,
+                        stroke_color,
+                        stroke_width,
+                        ..
+                    } => {
+                        let outline = stroke_color.map(|color| LinePaint {
+                            color,
+                            width: *stroke_width as f64,
+                            offset: 0.0,
+                            line_cap: LineCap::Round,
+                            width_scale: None,
+                            crisp: false,
+                        });
+                        self.add_shape(
+                            position,
+                            *fill,
+                            1.0,
+                            outline,
+                            &rounded_rect_shape(rect, *corner_radius),
+                            offset,
+                        );
+                    }
+                    LabelBackground::NinePatch { image, borders, .. } => {
+                        image_indices =
+                            self.add_nine_patch(position, image.clone(), *borders, rect, offset);
+                    }
+                }
+            }
 
+            match font_service.shape(text, style, text_offset) {
+                Ok(TextShaping::Tessellation { glyphs, .. }) => {
                     for glyph in glyphs {
                         let vertices_start = self.screen_ref.vertices.len() as u32;
                         for vertex in glyph.vertices {
-                            self.screen_ref.vertices.push(ScreenRefVertex {
-                                position: [
-                                    position.x().as_(),
-                                    position.y().as_(),
-                                    position.z().as_(),
-                                ],
-                                normal: vertex,
-                                color: style.font_color.to_u8_array(),
-                            });
+                            self.screen_ref.vertices.push(ScreenRefVertex::new(
+                                [position.x().as_(), position.y().as_(), position.z().as_()],
+                                vertex,
+                                style.font_color.to_u8_array(),
+                                Vector2::zeros(),
+                            ));
                         }
                         for index in glyph.indices {
                             self.screen_ref.indices.push(index + vertices_start);
                         }
                     }
-
-                    PrimitiveInfo::ScreenRef {
-                        vertex_range: indices_start..self.screen_ref.indices.len(),
-                    }
                 }
-                Err(err) => {
-                    log::error!("Error shaping text label: {err:?}");
-                    PrimitiveInfo::None
+                Err(err) => log::error!("Error shaping text label: {err:?}"),
+                _ => log::error!("Not supported font type"),
+            }
+
+            let vertex_range = vertex_start..self.screen_ref.vertices.len();
+            if image_indices.is_empty() {
+                PrimitiveInfo::ScreenRef { vertex_range }
+            } else {
+                PrimitiveInfo::Composite {
+                    vertex_range,
+                    image_indices,
                 }
-                _ => {
-                    log::error!("Not supported font type");
-                    PrimitiveInfo::None
+            }
+        })
+    }
+
+    /// Adds the image stretched over the `rect` (in pixels relative to the position) as a nine-patch. Returns the
+    /// indices of the added image parts.
+    fn add_nine_patch<N, P>(
+        &mut self,
+        position: &P,
+        image: Arc<DecodedImage>,
+        borders: [f32; 4],
+        rect: Rect<f32>,
+        offset: Vector2<f32>,
+    ) -> Vec<usize>
+    where
+        N: AsPrimitive<f32>,
+        P: CartesianPoint3d<Num = N>,
+    {
+        let width = image.width() as f32;
+        let height = image.height() as f32;
+        let [left, top, right, bottom] = borders;
+
+        // Columns go from left to right, rows from top to bottom
+        let xs = [
+            rect.x_min(),
+            rect.x_min() + left,
+            rect.x_max() - right,
+            rect.x_max(),
+        ];
+        let us = [0.0, left / width, 1.0 - right / width, 1.0];
+        let ys = [
+            rect.y_max(),
+            rect.y_max() - top,
+            rect.y_min() + bottom,
+            rect.y_min(),
+        ];
+        let vs = [0.0, top / height, 1.0 - bottom / height, 1.0];
+
+        self.buffer_size += image.size();
+        let store_index = self.add_image_to_store(image);
+        let position = [position.x().as_(), position.y().as_()];
+
+        let mut image_indices = Vec::with_capacity(9);
+        for row in 0..3 {
+            for col in 0..3 {
+                if xs[col + 1] <= xs[col] || ys[row + 1] >= ys[row] {
+                    continue;
                 }
-            },
-        )
+
+                // Same order of corners as in `image_corner_offsets`
+                let corners = [
+                    (col, row + 1),
+                    (col, row),
+                    (col + 1, row + 1),
+                    (col + 1, row),
+                ];
+                let vertices = corners.map(|(c, r)| ImageVertex {
+                    position,
+                    opacity: 1.0,
+                    tex_coords: [us[c], vs[r]],
+                    offset: [xs[c] + offset.x, ys[r] + offset.y],
+                    map_aligned: 0.0,
+                });
+
+                self.buffer_size += size_of::<ImageVertex>() * 4;
+                image_indices.push(self.add_image_info(store_index, vertices));
+            }
+        }
+
+        image_indices
+    }
+}
+
+/// Calculates the position of the background of a label relative to the label base point, and the origin of the
+/// text (start of the baseline) inside the background.
+///
+/// The background is aligned according to the alignment of the text style, e.g. with the default center-bottom
+/// alignment the base point is at the middle of the bottom side of the background.
+fn label_background_layout(
+    metrics: &TextMetrics,
+    style: &TextStyle,
+    background: &LabelBackground,
+) -> (Vector2<f32>, Rect<f32>) {
+    let padding = background.padding();
+    let min_size = background.min_size();
+    let width = (metrics.width + padding.x * 2.0).max(min_size.x);
+    let height = (metrics.height + padding.y * 2.0).max(min_size.y);
+
+    let x_min = match style.horizontal_alignment {
+        HorizontalAlignment::Left => 0.0,
+        HorizontalAlignment::Center => -width / 2.0,
+        HorizontalAlignment::Right => -width,
+    };
+    let y_min = match style.vertical_alignment {
+        VerticalAlignment::Bottom => 0.0,
+        VerticalAlignment::Middle => -height / 2.0,
+        VerticalAlignment::Top => -height,
+    };
+
+    let text_origin = Vector2::new(
+        x_min + (width - metrics.width) / 2.0,
+        y_min + (height - metrics.height) / 2.0 + metrics.descent,
+    );
+
+    (
+        text_origin,
+        Rect::new(x_min, y_min, x_min + width, y_min + height),
+    )
+}
+
+/// Number of segments in each rounded corner of a label background.
+const CORNER_SEGMENTS: usize = 4;
+
+fn rounded_rect_shape(rect: Rect<f32>, corner_radius: f32) -> ClosedContour<Point2<f32>> {
+    let radius = corner_radius
+        .min(rect.width() / 2.0)
+        .min(rect.height() / 2.0)
+        .max(0.0);
+    if radius == 0.0 {
+        return ClosedContour::new(vec![
+            Point2::new(rect.x_min(), rect.y_min()),
+            Point2::new(rect.x_min(), rect.y_max()),
+            Point2::new(rect.x_max(), rect.y_max()),
+            Point2::new(rect.x_max(), rect.y_min()),
+        ]);
     }
+
+    // Centers of the corner arcs with the start angle of each arc, going clockwise from the bottom-left corner
+    let corners = [
+        (rect.x_min() + radius, rect.y_min() + radius, -FRAC_PI_2),
+        (rect.x_min() + radius, rect.y_max() - radius, PI),
+        (rect.x_max() - radius, rect.y_max() - radius, FRAC_PI_2),
+        (rect.x_max() - radius, rect.y_min() + radius, 0.0),
+    ];
+
+    let points = corners
+        .into_iter()
+        .flat_map(|(x, y, start_angle)| {
+            (0..=CORNER_SEGMENTS).map(move |i| {
+                let angle = start_angle - FRAC_PI_2 * i as f32 / CORNER_SEGMENTS as f32;
+                Point2::new(x + radius * angle.cos(), y + radius * angle.sin())
+            })
+        })
+        .collect();
+
+    ClosedContour::new(points)
 }
 
-fn get_circle_sector(radius: f32, start_angle: f32, end_angle: f32) -> Vec<Point2<f32>> {
-    const TOLERANCE: f32 = 0.1;
+/// Maximum number of hatching lines per polygon. Polygons that would need more lines (e.g. a polygon covering the
+/// whole world drawn at a street level resolution) are drawn without hatching.
+const MAX_HATCH_LINES: f32 = 10_000.0;
+
+/// Calculates the segments of the hatching lines inside the polygon (using even-odd rule for the holes).
+fn hatch_segments<N, P, Poly>(
+    polygon: &Poly,
+    hatching: &Hatching,
+    resolution: f32,
+) -> Vec<[Point3d; 2]>
+where
+    N: AsPrimitive<f32>,
+    P: CartesianPoint3d<Num = N>,
+    Poly: Polygon,
+    Poly::Contour: Contour<Point = P>,
+{
+    let spacing = hatching.spacing as f32 * resolution;
+    if spacing.is_nan() || spacing <= 0.0 {
+        return vec![];
+    }
+
+    // Lines go along `direction`, and `normal` is used to enumerate them
+    let (sin, cos) = (hatching.angle as f32).sin_cos();
+    let direction = Vector2::new(cos, sin);
+    let normal = Vector2::new(-sin, cos);
+
+    let edges: Vec<_> = polygon
+        .iter_contours()
+        .flat_map(|contour| contour.iter_segments())
+        .map(|segment| {
+            let to_vector = |p: &P| (Vector2::new(p.x().as_(), p.y().as_()), p.z().as_());
+            (to_vector(segment.0), to_vector(segment.1))
+        })
+        .collect();
+
+    let (min, max) = edges
+        .iter()
+        .map(|((p, _), _)| p.dot(&normal))
+        .fold((f32::MAX, f32::MIN), |(min, max), v| {
+            (min.min(v), max.max(v))
+        });
+    if min > max || (max - min) / spacing > MAX_HATCH_LINES {
+        return vec![];
+    }
 
+    let mut segments = vec![];
+    let mut line_index = (min / spacing).ceil();
+    while line_index * spacing <= max {
+        let offset = line_index * spacing;
+        line_index += 1.0;
+
+        let mut crossings: Vec<(f32, f32)> = edges
+            .iter()
+            .filter_map(|((a, za), (b, zb))| {
+                let (ta, tb) = (a.dot(&normal), b.dot(&normal));
+                if (ta <= offset) == (tb <= offset) {
+                    return None;
+                }
+
+                let k = (offset - ta) / (tb - ta);
+                let point = a + (b - a) * k;
+                Some((point.dot(&direction), za + (zb - za) * k))
+            })
+            .collect();
+        crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        for pair in crossings.chunks_exact(2) {
+            let to_point = |(along, z): (f32, f32)| {
+                let p = normal * offset + direction * along;
+                Point3d::new(p.x as f64, p.y as f64, z as f64)
+            };
+            segments.push([to_point(pair[0]), to_point(pair[1])]);
+        }
+    }
+
+    segments
+}
+
+/// Approximates the circle sector with a contour, the points of which are no further than `tolerance` from the arc.
+fn get_circle_sector(
+    radius: f32,
+    start_angle: f32,
+    end_angle: f32,
+    tolerance: f32,
+) -> Vec<Point2<f32>> {
     let mut contour = vec![];
 
-    if radius <= TOLERANCE {
+    if radius <= tolerance {
         return contour;
     }
 
@@ -1083,7 +1728,7 @@ fn get_circle_sector(radius: f32, start_angle: f32, end_angle: f32) -> Vec<Point
         .min(std::f32::consts::PI * 2.0);
 
     let circle_steps_count =
-        std::f32::consts::PI / ((radius - TOLERANCE) / (radius + TOLERANCE)).acos();
+        std::f32::consts::PI / ((radius - tolerance) / (radius + tolerance)).acos();
 
     let segment_steps_count =
         ((dr / std::f32::consts::PI * 2.0) * circle_steps_count).ceil() as usize;
@@ -1130,12 +1775,36 @@ fn build_contour_path(
     Some(())
 }
 
+/// Number of custom attributes of the path points used for tessellation of lines and polygons: the `z` coordinate
+/// and the RGBA color of the point.
+const VERTEX_ATTRIBUTES: usize = 5;
+
+fn vertex_attributes(
+    z: f32,
+    color: Option<&Color>,
+    default_color: Color,
+) -> [f32; VERTEX_ATTRIBUTES] {
+    let [r, g, b, a] = color.copied().unwrap_or(default_color).to_f32_array();
+    [z, r, g, b, a]
+}
+
+/// Returns the color of the vertex from the interpolated path attributes, or the `default` color if the path has no
+/// color attributes.
+fn attributes_color(attributes: &[f32], default: [f32; 4]) -> [f32; 4] {
+    match attributes {
+        [_, r, g, b, a, ..] => [*r, *g, *b, *a],
+        _ => default,
+    }
+}
+
 #[allow(dead_code)]
 struct LineVertexConstructor<'a> {
     width: f32,
     offset: f32,
-    color: [f32; 4],
     resolution: f32,
+    width_scale: [f32; 4],
+    crisp: bool,
+    color: [f32; 4],
     path: &'a Path,
 }
 
@@ -1172,15 +1841,18 @@ impl StrokeVertexConstructor<PolyVertex> for LineVertexConstructor<'_> {
             f32::MAX
         };
 
+        let attributes = vertex.interpolated_attributes();
         PolyVertex {
             position: [
                 position.x * self.resolution,
                 position.y * self.resolution,
-                vertex.interpolated_attributes()[0],
+                attributes[0],
             ],
-            color: self.color,
+            color: attributes_color(attributes, self.color),
             normal,
             norm_limit,
+            width_scale: self.width_scale,
+            crisp_width: if self.crisp { self.width } else { 0.0 },
         }
     }
 }
@@ -1190,12 +1862,15 @@ struct PolygonVertexConstructor {
 }
 
 impl FillVertexConstructor<PolyVertex> for PolygonVertexConstructor {
-    fn new_vertex(&mut self, vertex: FillVertex) -> PolyVertex {
+    fn new_vertex(&mut self, mut vertex: FillVertex) -> PolyVertex {
+        let color = attributes_color(vertex.interpolated_attributes(), self.color);
         PolyVertex {
             position: [vertex.position().x, vertex.position().y, 0.0],
-            color: self.color,
+            color,
             normal: Default::default(),
             norm_limit: 1.0,
+            width_scale: Default::default(),
+            crisp_width: 0.0,
         }
     }
 }
@@ -1208,11 +1883,12 @@ struct ScreenRefVertexConstructor {
 
 impl ScreenRefVertexConstructor {
     fn create_vertex(&self, position: lyon::math::Point) -> ScreenRefVertex {
-        ScreenRefVertex {
-            position: self.position,
-            normal: [position.x + self.offset.x, position.y + self.offset.y],
-            color: self.color,
-        }
+        ScreenRefVertex::new(
+            self.position,
+            [position.x, position.y],
+            self.color,
+            self.offset,
+        )
     }
 }
 
@@ -1235,6 +1911,11 @@ pub(crate) struct PolyVertex {
     pub color: [f32; 4],
     pub normal: [f32; 2],
     pub norm_limit: f32,
+    /// Logarithmic stops of the resolution dependent line width scale, see
+    /// [`ResolutionScale::to_log_parameters`](crate::render::ResolutionScale::to_log_parameters).
+    pub width_scale: [f32; 4],
+    /// Width of the line in pixels if it should be aligned to the pixel grid, or `0.0` otherwise.
+    pub crisp_width: f32,
 }
 
 #[repr(C)]
@@ -1244,6 +1925,30 @@ pub(crate) struct PointInstance {
     pub color: [u8; 4],
 }
 
+/// Calculates screen offsets of the corners of an image point (in the order of image vertices).
+///
+/// Corners are rotated around the anchor point, and then moved by the pixel `offset`.
+pub(crate) fn image_corner_offsets(
+    width: f32,
+    height: f32,
+    anchor: Vector2<f32>,
+    rotation: f32,
+    offset: Vector2<f32>,
+) -> [[f32; 2]; 4] {
+    let left = -anchor[0] * width;
+    let top = anchor[1] * height;
+
+    let (sin, cos) = rotation.sin_cos();
+    let transform = |x: f32, y: f32| [x * cos - y * sin + offset[0], x * sin + y * cos + offset[1]];
+
+    [
+        transform(left, top - height),
+        transform(left, top),
+        transform(left + width, top - height),
+        transform(left + width, top),
+    ]
+}
+
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 pub(crate) struct ImageVertex {
@@ -1251,6 +1956,8 @@ pub(crate) struct ImageVertex {
     pub opacity: f32,
     pub tex_coords: [f32; 2],
     pub offset: [f32; 2],
+    /// `1.0` if the offset must be rotated together with the map, `0.0` otherwise
+    pub map_aligned: f32,
 }
 
 #[cfg(target_arch = "wasm32")]
@@ -1262,6 +1969,32 @@ mod tests {
 
     type C = galileo_types::impls::Contour<Point3d>;
 
+    #[test]
+    fn image_corners_with_anchor_and_offset() {
+        let corners = image_corner_offsets(
+            10.0,
+            20.0,
+            Vector2::new(0.5, 1.0),
+            0.0,
+            Vector2::new(1.0, 2.0),
+        );
+        assert_eq!(
+            corners,
+            [[-4.0, 2.0], [-4.0, 22.0], [6.0, 2.0], [6.0, 22.0]]
+        );
+
+        // Rotation by 180 degrees around the center-bottom anchor flips the image down
+        let rotated = image_corner_offsets(
+            10.0,
+            20.0,
+            Vector2::new(0.5, 1.0),
+            std::f32::consts::PI,
+            Vector2::zeros(),
+        );
+        assert!((rotated[1][1] + 20.0).abs() < 1e-4);
+        assert!((rotated[1][0] - 5.0).abs() < 1e-4);
+    }
+
     #[test]
     fn remove_map_ref() {
         let mut bundle = TessellatingRenderBundle::new();
@@ -1273,8 +2006,12 @@ mod tests {
         ]);
         let paint1 = PolygonPaint {
             color: Color::BLACK,
+            hatching: None,
+        };
+        let paint2 = PolygonPaint {
+            color: Color::RED,
+            hatching: None,
         };
-        let paint2 = PolygonPaint { color: Color::RED };
 
         let _id0 = bundle.add(
             RenderPrimitive::<_, _, C, _>::new_polygon_ref(&polygon, paint1),
@@ -1311,4 +2048,421 @@ mod tests {
 
         assert_eq!(vertex_range.end, vertex_count);
     }
+
+    #[test]
+    fn hatching_segments_inside_polygon() {
+        // Square with a hole in the middle
+        let polygon = galileo_types::impls::Polygon::new(
+            ClosedContour::new(vec![
+                Point3d::new(0.0, 0.0, 0.0),
+                Point3d::new(0.0, 10.0, 0.0),
+                Point3d::new(10.0, 10.0, 0.0),
+                Point3d::new(10.0, 0.0, 0.0),
+            ]),
+            vec![ClosedContour::new(vec![
+                Point3d::new(4.0, 4.0, 0.0),
+                Point3d::new(4.0, 7.0, 0.0),
+                Point3d::new(6.0, 7.0, 0.0),
+                Point3d::new(6.0, 4.0, 0.0),
+            ])],
+        );
+        let hatching = Hatching {
+            color: Color::BLACK,
+            width: 1.0,
+            spacing: 3.0,
+            angle: 0.0,
+        };
+
+        // Horizontal lines at y = 0, 3, 6, 9; line at y = 6 crosses the hole and is split by it
+        let segments = hatch_segments(&polygon, &hatching, 1.0);
+        let lengths: Vec<_> = segments
+            .iter()
+            .map(|[a, b]| ((b.x - a.x) as f32, a.y as f32))
+            .collect();
+        assert_eq!(
+            lengths,
+            vec![
+                (10.0, 0.0),
+                (10.0, 3.0),
+                (4.0, 6.0),
+                (4.0, 6.0),
+                (10.0, 9.0)
+            ]
+        );
+
+        // Spacing is given in pixels
+        assert_eq!(hatch_segments(&polygon, &hatching, 2.0).len(), 3);
+    }
+
+    #[test]
+    fn memory_usage() {
+        let mut bundle = TessellatingRenderBundle::new();
+        assert_eq!(bundle.memory_usage().total(), 0);
+
+        let polygon = galileo_types::impls::Polygon::from(vec![
+            Point3d::new(0.0, 0.0, 0.0),
+            Point3d::new(1.0, 0.0, 0.0),
+            Point3d::new(1.0, 1.0, 0.0),
+        ]);
+        let id = bundle.add(
+            RenderPrimitive::<_, _, C, _>::new_polygon_ref(
+                &polygon,
+                PolygonPaint {
+                    color: Color::RED,
+                    hatching: None,
+                },
+            ),
+            1.0,
+        );
+
+        let usage = bundle.memory_usage();
+        assert_eq!(
+            usage.vertices,
+            bundle.poly_tessellation.vertices.len() * size_of::<PolyVertex>()
+        );
+        assert_eq!(
+            usage.indices,
+            bundle.poly_tessellation.indices.len() * size_of::<u32>()
+        );
+        assert_eq!(usage.images, 0);
+        assert_eq!(usage.total(), bundle.approx_buffer_size());
+
+        let image =
+            DecodedImage::from_raw(vec![0; 16], galileo_types::cartesian::Size::new(2, 2)).unwrap();
+        bundle.add_image(
+            image,
+            [
+                Point2d::new(0.0, 0.0),
+                Point2d::new(0.0, 1.0),
+                Point2d::new(1.0, 1.0),
+                Point2d::new(1.0, 0.0),
+            ],
+            ImagePaint { opacity: 255 },
+        );
+        assert_eq!(bundle.memory_usage().images, 16);
+
+        bundle.remove(id).unwrap();
+        assert_eq!(bundle.memory_usage().indices, 0);
+    }
+
+    #[test]
+    fn offset_points() {
+        use crate::render::render_bundle::{RenderBundle, RenderBundleType};
+
+        let mut bundle = RenderBundle(RenderBundleType::Tessellating(
+            TessellatingRenderBundle::new(),
+        ));
+        let anchor = Point3d::new(1.0, 2.0, 0.0);
+
+        let ids = bundle.add_offset_points(
+            &anchor,
+            [
+                (
+                    Vector2::new(5.0, 0.0),
+                    PointPaint::circle(Color::RED, 4.0).with_offset(Vector2::new(0.0, 3.0)),
+                ),
+                (
+                    Vector2::new(-5.0, 0.0),
+                    PointPaint::circle(Color::BLUE, 4.0),
+                ),
+            ],
+            1.0,
+        );
+        assert_eq!(ids.len(), 2);
+
+        let RenderBundleType::Tessellating(inner) = &bundle.0;
+        let offsets: Vec<_> = inner.screen_ref.vertices.iter().map(|v| v.offset).collect();
+        assert!(offsets.contains(&[5.0, 3.0]));
+        assert!(offsets.contains(&[-5.0, 0.0]));
+    }
+
+    #[test]
+    fn update_point_transform() {
+        let mut bundle = TessellatingRenderBundle::new();
+        let point = Point3d::new(1.0, 2.0, 0.0);
+        let id = bundle.add_point::<f64, _>(&point, &PointPaint::square(Color::RED, 4.0));
+        let vertex_count = bundle.screen_ref.vertices.len();
+        let normals: Vec<_> = bundle
+            .screen_ref
+            .vertices
+            .iter()
+            .map(|v| v.normal)
+            .collect();
+
+        let paint = PointPaint::square(Color::RED, 4.0)
+            .with_rotation(1.0)
+            .with_scale(2.0);
+        bundle
+            .update(
+                id,
+                RenderPrimitive::<
+                    f64,
+                    Point3d,
+                    galileo_types::impls::Contour<Point3d>,
+                    galileo_types::impls::Polygon<Point3d>,
+                >::new_point(point, paint),
+            )
+            .expect("failed to update point");
+
+        // The shape is not tessellated again
+        assert_eq!(bundle.screen_ref.vertices.len(), vertex_count);
+        for (vertex, normal) in bundle.screen_ref.vertices.iter().zip(normals) {
+            assert_eq!(vertex.normal, normal);
+            assert_eq!(vertex.rotation, 1.0);
+            assert_eq!(vertex.scale, 2.0);
+        }
+    }
+
+    #[test]
+    fn update_point_color() {
+        type Primitive<'a> = RenderPrimitive<
+            'a,
+            f64,
+            Point3d,
+            galileo_types::impls::Contour<Point3d>,
+            galileo_types::impls::Polygon<Point3d>,
+        >;
+
+        let mut bundle = TessellatingRenderBundle::new();
+        let point = Point3d::new(1.0, 2.0, 0.0);
+        let id = bundle.add_point::<f64, _>(&point, &PointPaint::circle(Color::RED, 4.0));
+        let vertex_count = bundle.screen_ref.vertices.len();
+
+        bundle
+            .update(
+                id,
+                Primitive::new_point(point, PointPaint::circle(Color::BLUE, 4.0)),
+            )
+            .expect("failed to update point");
+
+        assert_eq!(bundle.screen_ref.vertices.len(), vertex_count);
+        assert!(bundle
+            .screen_ref
+            .vertices
+            .iter()
+            .all(|v| v.color == Color::BLUE.to_u8_array()));
+
+        // A circle of a different size has a different number of vertices and cannot be updated in place
+        assert!(bundle
+            .update(
+                id,
+                Primitive::new_point(point, PointPaint::circle(Color::BLUE, 40.0)),
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn move_point() {
+        let mut bundle = TessellatingRenderBundle::new();
+        let square = bundle.add_point::<f64, _>(
+            &Point3d::new(1.0, 2.0, 0.0),
+            &PointPaint::square(Color::RED, 4.0).with_rotation(1.0),
+        );
+        let dot =
+            bundle.add_point::<f64, _>(&Point3d::new(1.0, 2.0, 0.0), &PointPaint::dot(Color::RED));
+        let vertex_count = bundle.screen_ref.vertices.len();
+
+        bundle
+            .move_point(square, &Point3d::new(5.0, 6.0, 1.0))
+            .expect("failed to move point");
+
+        assert_eq!(bundle.screen_ref.vertices.len(), vertex_count);
+        for vertex in &bundle.screen_ref.vertices {
+            assert_eq!(vertex.position, [5.0, 6.0, 1.0]);
+            assert_eq!(vertex.rotation, 1.0);
+        }
+
+        assert!(bundle
+            .move_point(dot, &Point3d::new(5.0, 6.0, 0.0))
+            .is_err());
+    }
+
+    #[test]
+    fn circle_segments_depend_on_tolerance() {
+        let count = |tolerance: f32, radius: f32| {
+            get_circle_sector(radius, 0.0, std::f32::consts::PI * 2.0, tolerance).len()
+        };
+
+        assert!(count(0.01, 10.0) > count(0.1, 10.0));
+        assert!(count(0.1, 100.0) > count(0.1, 10.0));
+        assert_eq!(count(0.1, 0.05), 0);
+
+        let mut bundle = TessellatingRenderBundle::new();
+        bundle.set_tessellation_tolerance(0.0);
+        assert!(bundle.tessellation_tolerance() > 0.0);
+    }
+
+    #[test]
+    fn vertex_colors() {
+        let mut bundle = TessellatingRenderBundle::new();
+        let polygon = galileo_types::impls::Polygon::from(vec![
+            Point3d::new(0.0, 0.0, 0.0),
+            Point3d::new(1.0, 0.0, 0.0),
+            Point3d::new(1.0, 1.0, 0.0),
+            Point3d::new(0.0, 1.0, 0.0),
+        ]);
+        let paint = PolygonPaint {
+            color: Color::BLACK,
+            hatching: None,
+        };
+        bundle.add(
+            RenderPrimitive::<_, _, C, _>::new_polygon_with_vertex_colors(
+                polygon,
+                paint,
+                vec![Color::RED, Color::RED, Color::BLUE],
+            ),
+            1.0,
+        );
+
+        let color_at = |bundle: &TessellatingRenderBundle, x: f32, y: f32| {
+            bundle
+                .poly_tessellation
+                .vertices
+                .iter()
+                .find(|v| v.position[0] == x && v.position[1] == y)
+                .map(|v| v.color)
+        };
+        assert_eq!(color_at(&bundle, 0.0, 0.0), Some(Color::RED.to_f32_array()));
+        assert_eq!(
+            color_at(&bundle, 1.0, 1.0),
+            Some(Color::BLUE.to_f32_array())
+        );
+        // The last point has no color in the list
+        assert_eq!(
+            color_at(&bundle, 0.0, 1.0),
+            Some(Color::BLACK.to_f32_array())
+        );
+
+        let mut bundle = TessellatingRenderBundle::new();
+        let line = C::open(vec![
+            Point3d::new(0.0, 0.0, 0.0),
+            Point3d::new(10.0, 0.0, 0.0),
+        ]);
+        let paint = LinePaint {
+            color: Color::BLACK,
+            width: 2.0,
+            offset: 0.0,
+            line_cap: LineCap::Butt,
+            width_scale: None,
+            crisp: false,
+        };
+        bundle.add(
+            RenderPrimitive::<_, _, _, galileo_types::impls::Polygon<_>>::new_contour_with_vertex_colors(
+                line,
+                paint,
+                vec![Color::RED, Color::BLUE],
+            ),
+            1.0,
+        );
+
+        let vertices = &bundle.poly_tessellation.vertices;
+        assert!(!vertices.is_empty());
+        for vertex in vertices {
+            let expected = if vertex.position[0] == 0.0 {
+                Color::RED
+            } else {
+                Color::BLUE
+            };
+            assert_eq!(vertex.color, expected.to_f32_array());
+        }
+    }
+
+    #[test]
+    fn crisp_line_vertices() {
+        let line = || {
+            C::open(vec![
+                Point3d::new(0.0, 0.0, 0.0),
+                Point3d::new(10.0, 0.0, 0.0),
+            ])
+        };
+        let paint = LinePaint {
+            color: Color::BLACK,
+            width: 1.0,
+            offset: 0.0,
+            line_cap: LineCap::Butt,
+            width_scale: None,
+            crisp: true,
+        };
+
+        let mut bundle = TessellatingRenderBundle::new();
+        bundle.add(
+            RenderPrimitive::<_, _, _, galileo_types::impls::Polygon<_>>::new_contour(
+                line(),
+                paint,
+            ),
+            1.0,
+        );
+        bundle.add(
+            RenderPrimitive::<_, _, _, galileo_types::impls::Polygon<_>>::new_contour(
+                line(),
+                LinePaint {
+                    crisp: false,
+                    ..paint
+                },
+            ),
+            1.0,
+        );
+
+        let vertices = &bundle.poly_tessellation.vertices;
+        let half = vertices.len() / 2;
+        assert!(vertices[..half].iter().all(|v| v.crisp_width == 1.0));
+        assert!(vertices[half..].iter().all(|v| v.crisp_width == 0.0));
+    }
+
+    #[test]
+    fn label_background_layout_with_alignment() {
+        let metrics = TextMetrics {
+            width: 20.0,
+            height: 10.0,
+            ascent: 8.0,
+            descent: 2.0,
+            line_height: 12.0,
+            line_count: 1,
+        };
+        let mut style = TextStyle {
+            font_name: "Noto Sans".into(),
+            font_size: 10.0,
+            font_color: Color::BLACK,
+            horizontal_alignment: HorizontalAlignment::Center,
+            vertical_alignment: VerticalAlignment::Middle,
+            max_width: None,
+            line_spacing: 1.0,
+            line_alignment: None,
+        };
+        let background =
+            LabelBackground::rect(Color::WHITE, 3.0).with_padding(Vector2::new(4.0, 2.0));
+
+        let (origin, rect) = label_background_layout(&metrics, &style, &background);
+        assert_eq!(rect, Rect::new(-14.0, -7.0, 14.0, 7.0));
+        assert_eq!(origin, Vector2::new(-10.0, -3.0));
+
+        style.horizontal_alignment = HorizontalAlignment::Left;
+        style.vertical_alignment = VerticalAlignment::Bottom;
+        let (origin, rect) = label_background_layout(&metrics, &style, &background);
+        assert_eq!(rect, Rect::new(0.0, 0.0, 28.0, 14.0));
+        assert_eq!(origin, Vector2::new(4.0, 4.0));
+
+        // Nine-patch is never smaller than its borders
+        let image = Arc::new(
+            DecodedImage::from_raw(vec![0; 4], galileo_types::cartesian::Size::new(1, 1))
+                .expect("invalid image"),
+        );
+        let background = LabelBackground::nine_patch(image, [20.0, 10.0, 20.0, 10.0]);
+        let (origin, rect) = label_background_layout(&metrics, &style, &background);
+        assert_eq!(rect, Rect::new(0.0, 0.0, 40.0, 20.0));
+        assert_eq!(origin, Vector2::new(10.0, 7.0));
+    }
+
+    #[test]
+    fn rounded_rect_stays_inside_rect() {
+        let rect = Rect::new(-10.0, -5.0, 10.0, 5.0);
+        let shape = rounded_rect_shape(rect, 20.0);
+        assert_eq!(shape.points.len(), (CORNER_SEGMENTS + 1) * 4);
+
+        let bounds = Rect::from_points(shape.points.iter()).expect("empty shape");
+        assert!((bounds.x_min() - rect.x_min()).abs() < 1e-5);
+        assert!((bounds.y_max() - rect.y_max()).abs() < 1e-5);
+
+        assert_eq!(rounded_rect_shape(rect, 0.0).points.len(), 4);
+    }
 }
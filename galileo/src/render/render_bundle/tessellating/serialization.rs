@@ -22,6 +22,7 @@ pub(crate) struct TessellatingRenderBundleBytes {
     pub vacant_image_store_ids: Vec<usize>,
     pub clip_area: Option<PolyVertexBuffersBytes>,
     pub bundle_size: usize,
+    pub tolerance: f32,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -121,6 +122,7 @@ impl TessellatingRenderBundle {
             vacant_image_store_ids: self.vacant_image_store_ids,
             clip_area: self.clip_area.map(|v| v.into()),
             bundle_size: self.buffer_size,
+            tolerance: self.tolerance,
         }
     }
 
@@ -164,6 +166,7 @@ impl TessellatingRenderBundle {
             vacant_image_store_ids: bundle.vacant_image_store_ids,
             clip_area: bundle.clip_area.map(|v| v.into_typed_unchecked()),
             buffer_size: bundle.bundle_size,
+            tolerance: bundle.tolerance,
             vacant_ids: vec![],
         }
     }
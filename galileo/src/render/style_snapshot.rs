@@ -0,0 +1,195 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+
+use crate::error::GalileoError;
+use crate::layer::vector_tile_layer::style::VectorTileStyle;
+use crate::layer::vector_tile_layer::tile_provider::loader::StaticVtLoader;
+use crate::layer::vector_tile_layer::tile_provider::VectorTileProvider;
+use crate::layer::VectorTileLayer;
+use crate::platform::native::vt_processor::ThreadVtProcessor;
+use crate::render::render_bundle::tessellating::TessellatingRenderBundle;
+use crate::render::render_bundle::{RenderBundle, RenderBundleType};
+use crate::render::text::font_service::FontService;
+use crate::render::TileRenderer;
+use crate::tile_scheme::{TileIndex, TileSchema};
+use crate::view::MapView;
+use crate::Map;
+
+/// Renders vector tiles with a style into images without network access, for regression testing of styles.
+///
+/// The tiles are given as encoded MVT bytes, and the output only depends on the tiles, the style and the fonts, so
+/// the images can be compared with reference images stored with the style:
+///
+/// ```ignore
+/// let renderer = StyleSnapshotRenderer::new(TileSchema::web(18), 512)
+///     .await
+///     .expect("no graphics adapter")
+///     .with_fonts([Bytes::from(include_bytes!("NotoSans-Regular.ttf").as_slice())])?;
+///
+/// let tiles = HashMap::from([(TileIndex::new(1, 1, 1), Bytes::from(std::fs::read("1-1-1.mvt")?))]);
+/// let image = renderer.render_tile(&tiles, style, TileIndex::new(1, 1, 1)).await?;
+/// ```
+///
+/// Tiles missing from the given set are rendered empty. The zoom level of the image is defined by the index of the
+/// rendered tile. If the image size is larger than the tile size of the schema, tiles of the next levels are used
+/// (e.g. 512px images of a 256px schema are rendered from the tiles of the level `z + 1`).
+pub struct StyleSnapshotRenderer {
+    renderer: TileRenderer,
+}
+
+impl StyleSnapshotRenderer {
+    /// Creates a new renderer producing images of `image_size x image_size` pixels for the tiles of the given schema.
+    ///
+    /// Returns `None` if no suitable graphics adapter is found.
+    pub async fn new(tile_schema: TileSchema, image_size: u32) -> Option<Self> {
+        let renderer = TileRenderer::new(tile_schema, image_size).await?;
+        Some(Self { renderer })
+    }
+
+    /// Replaces the fonts of the [`FontService`] with the given font files, so that labels are rendered with exactly
+    /// these fonts regardless of the fonts loaded by other parts of the application.
+    ///
+    /// <div class="warning">
+    ///
+    /// The font service is global: this call drops all fonts loaded before in the whole process, and the given fonts
+    /// are used by every map and renderer of the application, not only by this snapshot renderer. Use it only in
+    /// processes dedicated to rendering snapshots, e.g. in style regression tests.
+    ///
+    /// </div>
+    pub fn with_fonts(self, fonts: impl IntoIterator<Item = Bytes>) -> Result<Self, GalileoError> {
+        FontService::with_mut(|service| {
+            *service = FontService::default();
+            for font in fonts {
+                service
+                    .load_fonts(font)
+                    .map_err(|err| GalileoError::Generic(format!("failed to load font: {err}")))?;
+            }
+
+            Ok::<_, GalileoError>(())
+        })?;
+
+        Ok(self)
+    }
+
+    /// Tile schema of the rendered tiles.
+    pub fn tile_schema(&self) -> &TileSchema {
+        self.renderer.tile_schema()
+    }
+
+    /// Renders the tile with the given index from the `tiles` with the `style`, and returns the image as raw RGBA
+    /// bytes.
+    pub async fn render_tile(
+        &self,
+        tiles: &HashMap<TileIndex, Bytes>,
+        style: VectorTileStyle,
+        index: TileIndex,
+    ) -> Result<Vec<u8>, GalileoError> {
+        let mut map = self.create_map(tiles, style, index)?;
+        self.renderer.render_tile(&mut map, index).await
+    }
+
+    /// Renders the tile with the given index from the `tiles` with the `style`, and returns the image encoded as
+    /// PNG.
+    #[cfg(feature = "image")]
+    pub async fn render_tile_png(
+        &self,
+        tiles: &HashMap<TileIndex, Bytes>,
+        style: VectorTileStyle,
+        index: TileIndex,
+    ) -> Result<Vec<u8>, GalileoError> {
+        let mut map = self.create_map(tiles, style, index)?;
+        self.renderer.render_tile_png(&mut map, index).await
+    }
+
+    fn create_map(
+        &self,
+        tiles: &HashMap<TileIndex, Bytes>,
+        style: VectorTileStyle,
+        index: TileIndex,
+    ) -> Result<Map, GalileoError> {
+        let tile_schema = self.renderer.tile_schema().clone();
+        let view: MapView = self.renderer.tile_view(index).ok_or_else(|| {
+            GalileoError::Generic(format!("tile {index:?} is not valid for the tile schema"))
+        })?;
+
+        let processor = ThreadVtProcessor::new(
+            tile_schema.clone(),
+            RenderBundle(RenderBundleType::Tessellating(
+                TessellatingRenderBundle::new(),
+            )),
+        );
+        let provider = VectorTileProvider::new(
+            Arc::new(StaticVtLoader::new(tiles.clone())),
+            Arc::new(processor),
+        );
+
+        let mut layer = VectorTileLayer::new(provider, style, tile_schema);
+        layer.set_fade_in_duration(Duration::ZERO);
+
+        Ok(Map::new(view, vec![Box::new(layer)], None))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use geozero::mvt::{tile, Message, Tile};
+
+    use super::*;
+
+    /// Encodes a tile with a single `land` layer containing a square covering the whole tile.
+    fn land_tile() -> Bytes {
+        let extent = 4096;
+        let tile = Tile {
+            layers: vec![tile::Layer {
+                version: 2,
+                name: "land".into(),
+                features: vec![tile::Feature {
+                    id: Some(1),
+                    tags: vec![],
+                    r#type: Some(tile::GeomType::Polygon as i32),
+                    // MoveTo (0, 0), LineTo (4096, 0), (4096, 4096), (0, 4096), ClosePath
+                    geometry: vec![9, 0, 0, 26, 8192, 0, 0, 8192, 8191, 0, 15],
+                }],
+                keys: vec![],
+                values: vec![],
+                extent: Some(extent),
+            }],
+        };
+
+        Bytes::from(tile.encode_to_vec())
+    }
+
+    #[test]
+    fn render_tile_snapshot() {
+        let image_size = 64;
+        let Some(renderer) =
+            crate::block_on(StyleSnapshotRenderer::new(TileSchema::web(18), image_size))
+        else {
+            log::warn!("No graphics adapter available, skipping the test");
+            return;
+        };
+
+        let style: VectorTileStyle = serde_json::from_str(
+            r##"{
+                "rules": [{ "layer_name": "land", "symbol": { "polygon": { "fill_color": "#FF0000FF" } } }],
+                "default_symbol": {},
+                "background": "#0000FFFF"
+            }"##,
+        )
+        .expect("valid style");
+
+        let index = TileIndex::new(0, 0, 0);
+        let tiles = HashMap::from([(index, land_tile())]);
+        let image = crate::block_on(renderer.render_tile(&tiles, style, index))
+            .expect("failed to render tile");
+
+        assert_eq!(image.len(), (image_size * image_size * 4) as usize);
+        assert!(
+            image.chunks(4).all(|pixel| pixel == [255, 0, 0, 255]),
+            "the tile is not filled with the polygon color"
+        );
+    }
+}
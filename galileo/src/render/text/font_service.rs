@@ -10,7 +10,7 @@ use rustybuzz::ttf_parser::FaceParsingError;
 use thiserror::Error;
 
 use crate::render::text::rustybuzz::RustybuzzFontServiceProvider;
-use crate::render::text::{FontServiceProvider, TextShaping, TextStyle};
+use crate::render::text::{FontServiceProvider, TextMetrics, TextShaping, TextStyle};
 
 lazy_static! {
     static ref INSTANCE: Arc<RwLock<FontService>> = Arc::new(RwLock::new(FontService::default()));
@@ -67,6 +67,35 @@ impl FontService {
         self.provider.shape(text, style, offset)
     }
 
+    /// Measure the size of the given text with the given style.
+    ///
+    /// The result does not depend on the way the text is rendered (tessellated or rasterized), so it can be used to
+    /// lay out other elements around a label (e.g. a callout box) before the label is added to a render bundle.
+    ///
+    /// ```no_run
+    /// use galileo::render::text::font_service::FontService;
+    /// use galileo::render::text::TextStyle;
+    /// use galileo::Color;
+    ///
+    /// let style = TextStyle {
+    ///     font_name: "Noto Sans".to_string(),
+    ///     font_size: 16.0,
+    ///     font_color: Color::BLACK,
+    ///     horizontal_alignment: Default::default(),
+    ///     vertical_alignment: Default::default(),
+    ///     max_width: None,
+    ///     line_spacing: 1.0,
+    ///     line_alignment: None,
+    /// };
+    ///
+    /// let metrics = FontService::with(|service| service.measure("Label", &style))
+    ///     .expect("fonts are not loaded");
+    /// let box_size = (metrics.width + 8.0, metrics.height + 8.0);
+    /// ```
+    pub fn measure(&self, text: &str, style: &TextStyle) -> Result<TextMetrics, FontServiceError> {
+        self.provider.measure(text, style)
+    }
+
     /// Try parse input binary data to load fonts to the font service.
     pub fn load_fonts(&mut self, fonts_data: Bytes) -> Result<(), FontServiceError> {
         self.provider.load_fonts(fonts_data)
@@ -34,12 +34,36 @@ pub struct TextStyle {
     /// Alignment of label along vertical axis.
     #[serde(default)]
     pub vertical_alignment: VerticalAlignment,
+    /// Maximum width of a line of the label in pixels. Longer lines are wrapped at the spaces between words. Words
+    /// longer than the maximum width are not broken. If `None` (default), lines are only broken at `\n` characters.
+    #[serde(default)]
+    pub max_width: Option<f32>,
+    /// Distance between the baselines of the lines of the label as a multiple of the line height of the font.
+    /// Default value is `1.0`.
+    #[serde(default = "default_line_spacing")]
+    pub line_spacing: f32,
+    /// Alignment of the lines of a multi-line label relative to each other. If `None` (default), the lines are
+    /// aligned the same way as the label is aligned relative to its position
+    /// ([`horizontal_alignment`](TextStyle::horizontal_alignment)).
+    #[serde(default)]
+    pub line_alignment: Option<HorizontalAlignment>,
 }
 
 fn default_font_color() -> Color {
     Color::BLACK
 }
 
+fn default_line_spacing() -> f32 {
+    1.0
+}
+
+impl TextStyle {
+    /// Alignment of the lines of a multi-line label relative to each other.
+    pub fn effective_line_alignment(&self) -> HorizontalAlignment {
+        self.line_alignment.unwrap_or(self.horizontal_alignment)
+    }
+}
+
 /// Horizontal alignment.
 #[derive(Default, Debug, Copy, Clone, Serialize, Deserialize)]
 pub enum HorizontalAlignment {
@@ -84,6 +108,28 @@ pub struct TessellatedGlyph {
     pub indices: Vec<u32>,
 }
 
+/// Size of a text label as it would be rendered with a given [`TextStyle`], in pixels.
+///
+/// Lines of the text are separated by the `\n` character, and by wrapping to the
+/// [`max_width`](TextStyle::max_width) of the style. All values are positive numbers.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct TextMetrics {
+    /// Advance width of the widest line of the text.
+    pub width: f32,
+    /// Total height of the text: `line_height` for every line except the last one plus `ascent` and `descent` of
+    /// the last line.
+    pub height: f32,
+    /// Distance from the baseline to the top of the highest glyph of the font.
+    pub ascent: f32,
+    /// Distance from the baseline to the bottom of the lowest glyph of the font.
+    pub descent: f32,
+    /// Distance between the baselines of two consequent lines, including the
+    /// [`line_spacing`](TextStyle::line_spacing) of the style.
+    pub line_height: f32,
+    /// Number of lines in the text.
+    pub line_count: usize,
+}
+
 /// Data provider for font service.
 pub trait FontServiceProvider {
     /// Shape text label.
@@ -94,6 +140,122 @@ pub trait FontServiceProvider {
         offset: Vector2<f32>,
     ) -> Result<TextShaping, FontServiceError>;
 
+    /// Measure the size of the text label without shaping its glyphs.
+    fn measure(&self, text: &str, style: &TextStyle) -> Result<TextMetrics, FontServiceError>;
+
     /// Try to Load fonts from the given binary data.
     fn load_fonts(&mut self, fonts_data: Bytes) -> Result<(), FontServiceError>;
 }
+
+/// Single line of a laid out text label.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(not(feature = "rustybuzz"), allow(dead_code))]
+pub(crate) struct TextLine {
+    pub text: String,
+    pub width: f32,
+}
+
+/// Splits the text into lines at `\n` characters, and wraps the lines longer than `max_width` at spaces between the
+/// words. `measure` returns the width of a string in the same units as `max_width`.
+#[cfg_attr(not(feature = "rustybuzz"), allow(dead_code))]
+pub(crate) fn layout_lines(
+    text: &str,
+    max_width: Option<f32>,
+    measure: impl Fn(&str) -> f32,
+) -> Vec<TextLine> {
+    let mut lines = vec![];
+    for paragraph in text.split('\n') {
+        let width = measure(paragraph);
+        let Some(max_width) = max_width.filter(|max_width| width > *max_width) else {
+            lines.push(TextLine {
+                text: paragraph.to_string(),
+                width,
+            });
+            continue;
+        };
+
+        let mut current: Option<TextLine> = None;
+        for word in paragraph.split_whitespace() {
+            current = Some(match current.take() {
+                None => TextLine {
+                    text: word.to_string(),
+                    width: measure(word),
+                },
+                Some(line) => {
+                    let candidate = format!("{} {word}", line.text);
+                    let candidate_width = measure(&candidate);
+                    if candidate_width > max_width {
+                        lines.push(line);
+                        TextLine {
+                            text: word.to_string(),
+                            width: measure(word),
+                        }
+                    } else {
+                        TextLine {
+                            text: candidate,
+                            width: candidate_width,
+                        }
+                    }
+                }
+            });
+        }
+
+        lines.push(current.unwrap_or(TextLine {
+            text: String::new(),
+            width: 0.0,
+        }));
+    }
+
+    lines
+}
+
+/// Horizontal offset of a line with the given width inside a text block of the given width.
+#[cfg_attr(not(feature = "rustybuzz"), allow(dead_code))]
+pub(crate) fn line_offset(
+    alignment: HorizontalAlignment,
+    block_width: f32,
+    line_width: f32,
+) -> f32 {
+    match alignment {
+        HorizontalAlignment::Left => 0.0,
+        HorizontalAlignment::Center => (block_width - line_width) / 2.0,
+        HorizontalAlignment::Right => block_width - line_width,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str, max_width: Option<f32>) -> Vec<String> {
+        // Every character is 1 unit wide
+        layout_lines(text, max_width, |line| line.chars().count() as f32)
+            .into_iter()
+            .map(|line| line.text)
+            .collect()
+    }
+
+    #[test]
+    fn layout_lines_with_wrapping() {
+        assert_eq!(lines("one line", None), vec!["one line"]);
+        assert_eq!(lines("two\nlines", None), vec!["two", "lines"]);
+        assert_eq!(
+            lines("the quick brown fox", Some(10.0)),
+            vec!["the quick", "brown fox"]
+        );
+        assert_eq!(
+            lines("a verylongword b\n\nc", Some(5.0)),
+            vec!["a", "verylongword", "b", "", "c"]
+        );
+
+        let layout = layout_lines("ab cd", Some(3.0), |line| line.len() as f32);
+        assert_eq!(layout[1].width, 2.0);
+    }
+
+    #[test]
+    fn line_offset_by_alignment() {
+        assert_eq!(line_offset(HorizontalAlignment::Left, 10.0, 4.0), 0.0);
+        assert_eq!(line_offset(HorizontalAlignment::Center, 10.0, 4.0), 3.0);
+        assert_eq!(line_offset(HorizontalAlignment::Right, 10.0, 4.0), 6.0);
+    }
+}
@@ -6,10 +6,13 @@ use lyon::path::path::Builder;
 use lyon::path::Path;
 use nalgebra::Vector2;
 use rustybuzz::ttf_parser::{GlyphId, OutlineBuilder};
-use rustybuzz::{Face, UnicodeBuffer};
+use rustybuzz::{Face, GlyphBuffer, UnicodeBuffer};
 
 use crate::render::text::font_service::FontServiceError;
-use crate::render::text::{FontServiceProvider, TessellatedGlyph, TextShaping, TextStyle};
+use crate::render::text::{
+    layout_lines, line_offset, FontServiceProvider, TessellatedGlyph, TextLine, TextMetrics,
+    TextShaping, TextStyle,
+};
 
 #[derive(Default)]
 pub struct RustybuzzFontServiceProvider {
@@ -17,11 +20,40 @@ pub struct RustybuzzFontServiceProvider {
 }
 
 impl RustybuzzFontServiceProvider {
-    fn select_face(&self, _buffer: &UnicodeBuffer) -> Option<Face<'_>> {
-        // todo
+    fn select_face(&self) -> Option<Face<'_>> {
+        // todo: select face by the text and style
         let fonts_data = self.fonts_data.first()?;
         Face::from_slice(fonts_data, 0)
     }
+
+    fn shape_line(face: &Face, text: &str) -> GlyphBuffer {
+        let mut buffer = UnicodeBuffer::new();
+        buffer.push_str(text);
+        buffer.guess_segment_properties();
+
+        rustybuzz::shape(face, &[], buffer)
+    }
+
+    fn layout(face: &Face, text: &str, style: &TextStyle) -> Vec<TextLine> {
+        let scale = style.font_size / face.units_per_em() as f32;
+        layout_lines(text, style.max_width, |line| {
+            let width: i32 = Self::shape_line(face, line)
+                .glyph_positions()
+                .iter()
+                .map(|position| position.x_advance)
+                .sum();
+            width as f32 * scale
+        })
+    }
+
+    fn vertical_metrics(face: &Face, style: &TextStyle) -> (f32, f32, f32) {
+        let scale = style.font_size / face.units_per_em() as f32;
+        let ascent = face.ascender() as f32 * scale;
+        let descent = -face.descender() as f32 * scale;
+        let line_height = (ascent + descent + face.line_gap() as f32 * scale) * style.line_spacing;
+
+        (ascent, descent, line_height)
+    }
 }
 
 impl FontServiceProvider for RustybuzzFontServiceProvider {
@@ -31,35 +63,43 @@ impl FontServiceProvider for RustybuzzFontServiceProvider {
         style: &TextStyle,
         offset: Vector2<f32>,
     ) -> Result<TextShaping, FontServiceError> {
-        let mut buffer = UnicodeBuffer::new();
-        buffer.push_str(text);
-        buffer.guess_segment_properties();
-
-        let Some(face) = self.select_face(&buffer) else {
+        let Some(face) = self.select_face() else {
             return Err(FontServiceError::FontNotFound);
         };
 
         let units = face.units_per_em() as f32;
         let scale = style.font_size / units;
 
-        let glyph_buffer = rustybuzz::shape(&face, &[], buffer);
-        let mut tessellations = vec![];
+        let lines = Self::layout(&face, text, style);
+        let (_, _, line_height) = Self::vertical_metrics(&face, style);
+        let block_width = lines.iter().map(|line| line.width).fold(0.0, f32::max);
+        let alignment = style.effective_line_alignment();
 
-        let mut advance_x = 0;
-        let mut advance_y = 0;
-        for index in 0..glyph_buffer.len() {
-            let position = glyph_buffer.glyph_positions()[index];
-            let glyph_info = glyph_buffer.glyph_infos()[index];
-
-            let mut path_builder = GlyphPathBuilder::new(scale);
-            face.outline_glyph(GlyphId(glyph_info.glyph_id as u16), &mut path_builder);
-            tessellations.push(path_builder.tessellate(Vector2::new(
-                offset.x + (position.x_offset + advance_x) as f32 * scale,
-                offset.y + (position.y_offset + advance_y) as f32 * scale,
-            )));
-
-            advance_x += position.x_advance;
-            advance_y += position.y_advance;
+        let mut tessellations = vec![];
+        for (line_index, line) in lines.iter().enumerate() {
+            // Offset is the baseline of the last line, lines above it go up
+            let line_origin = Vector2::new(
+                offset.x + line_offset(alignment, block_width, line.width),
+                offset.y + (lines.len() - 1 - line_index) as f32 * line_height,
+            );
+
+            let glyph_buffer = Self::shape_line(&face, &line.text);
+            let mut advance_x = 0;
+            let mut advance_y = 0;
+            for index in 0..glyph_buffer.len() {
+                let position = glyph_buffer.glyph_positions()[index];
+                let glyph_info = glyph_buffer.glyph_infos()[index];
+
+                let mut path_builder = GlyphPathBuilder::new(scale);
+                face.outline_glyph(GlyphId(glyph_info.glyph_id as u16), &mut path_builder);
+                tessellations.push(path_builder.tessellate(Vector2::new(
+                    line_origin.x + (position.x_offset + advance_x) as f32 * scale,
+                    line_origin.y + (position.y_offset + advance_y) as f32 * scale,
+                )));
+
+                advance_x += position.x_advance;
+                advance_y += position.y_advance;
+            }
         }
 
         Ok(TextShaping::Tessellation {
@@ -67,6 +107,25 @@ impl FontServiceProvider for RustybuzzFontServiceProvider {
         })
     }
 
+    fn measure(&self, text: &str, style: &TextStyle) -> Result<TextMetrics, FontServiceError> {
+        let Some(face) = self.select_face() else {
+            return Err(FontServiceError::FontNotFound);
+        };
+
+        let lines = Self::layout(&face, text, style);
+        let (ascent, descent, line_height) = Self::vertical_metrics(&face, style);
+        let line_count = lines.len();
+
+        Ok(TextMetrics {
+            width: lines.iter().map(|line| line.width).fold(0.0, f32::max),
+            height: line_height * (line_count - 1) as f32 + ascent + descent,
+            ascent,
+            descent,
+            line_height,
+            line_count,
+        })
+    }
+
     fn load_fonts(&mut self, fonts_data: Bytes) -> Result<(), FontServiceError> {
         self.fonts_data.push(fonts_data);
         Ok(())
@@ -157,3 +216,28 @@ impl FillVertexConstructor<[f32; 2]> for GlyphVertexConstructor {
         ]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measure_without_fonts() {
+        let provider = RustybuzzFontServiceProvider::default();
+        let style = TextStyle {
+            font_name: "Noto Sans".to_string(),
+            font_size: 12.0,
+            font_color: crate::Color::BLACK,
+            horizontal_alignment: Default::default(),
+            vertical_alignment: Default::default(),
+            max_width: None,
+            line_spacing: 1.0,
+            line_alignment: None,
+        };
+
+        assert!(matches!(
+            provider.measure("text", &style),
+            Err(FontServiceError::FontNotFound)
+        ));
+    }
+}
@@ -0,0 +1,154 @@
+//! Color palettes and the global theme that symbols consult to render accessible maps.
+//!
+//! Symbols can refer to a color by its index in the [`Palette`] instead of a fixed color (see e.g.
+//! [`SimplePolygonSymbol::with_palette_color`](crate::layer::feature_layer::symbol::SimplePolygonSymbol::with_palette_color)).
+//! The actual color is then taken from the palette of the current [`Theme`] when the feature is rendered. Switching
+//! the theme to a color-blind-safe palette changes the colors of all such symbols at once.
+//!
+//! The theme is read when the layers create their render bundles, so after the theme is changed the layers must be
+//! re-rendered (e.g. with [`VectorTileLayer::update_style`](crate::layer::VectorTileLayer::update_style) or by
+//! recreating feature layers).
+
+use lazy_static::lazy_static;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+
+use crate::Color;
+
+lazy_static! {
+    static ref CURRENT_THEME: RwLock<Theme> = RwLock::new(Theme::default());
+}
+
+/// Set of categorical colors, used to distinguish different kinds of features on the map.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Palette {
+    /// General purpose palette (`category10` from D3). Some of its colors are hard to distinguish with color vision
+    /// deficiencies.
+    #[default]
+    Standard,
+    /// Palette by Masataka Okabe and Kei Ito, distinguishable with all common types of color blindness.
+    OkabeIto,
+    /// Bright qualitative palette by Paul Tol, color-blind safe.
+    TolBright,
+    /// Muted qualitative palette by Paul Tol, color-blind safe. Suitable for filling large areas.
+    TolMuted,
+}
+
+const STANDARD: [Color; 10] = [
+    Color::rgba(0x1f, 0x77, 0xb4, 255),
+    Color::rgba(0xff, 0x7f, 0x0e, 255),
+    Color::rgba(0x2c, 0xa0, 0x2c, 255),
+    Color::rgba(0xd6, 0x27, 0x28, 255),
+    Color::rgba(0x94, 0x67, 0xbd, 255),
+    Color::rgba(0x8c, 0x56, 0x4b, 255),
+    Color::rgba(0xe3, 0x77, 0xc2, 255),
+    Color::rgba(0x7f, 0x7f, 0x7f, 255),
+    Color::rgba(0xbc, 0xbd, 0x22, 255),
+    Color::rgba(0x17, 0xbe, 0xcf, 255),
+];
+
+const OKABE_ITO: [Color; 8] = [
+    Color::rgba(0xe6, 0x9f, 0x00, 255),
+    Color::rgba(0x56, 0xb4, 0xe9, 255),
+    Color::rgba(0x00, 0x9e, 0x73, 255),
+    Color::rgba(0xf0, 0xe4, 0x42, 255),
+    Color::rgba(0x00, 0x72, 0xb2, 255),
+    Color::rgba(0xd5, 0x5e, 0x00, 255),
+    Color::rgba(0xcc, 0x79, 0xa7, 255),
+    Color::rgba(0x00, 0x00, 0x00, 255),
+];
+
+const TOL_BRIGHT: [Color; 7] = [
+    Color::rgba(0x44, 0x77, 0xaa, 255),
+    Color::rgba(0xee, 0x66, 0x77, 255),
+    Color::rgba(0x22, 0x88, 0x33, 255),
+    Color::rgba(0xcc, 0xbb, 0x44, 255),
+    Color::rgba(0x66, 0xcc, 0xee, 255),
+    Color::rgba(0xaa, 0x33, 0x77, 255),
+    Color::rgba(0xbb, 0xbb, 0xbb, 255),
+];
+
+const TOL_MUTED: [Color; 9] = [
+    Color::rgba(0xcc, 0x66, 0x77, 255),
+    Color::rgba(0x33, 0x22, 0x88, 255),
+    Color::rgba(0xdd, 0xcc, 0x77, 255),
+    Color::rgba(0x11, 0x77, 0x33, 255),
+    Color::rgba(0x88, 0xcc, 0xee, 255),
+    Color::rgba(0x88, 0x22, 0x55, 255),
+    Color::rgba(0x44, 0xaa, 0x99, 255),
+    Color::rgba(0x99, 0x99, 0x33, 255),
+    Color::rgba(0xaa, 0x44, 0x99, 255),
+];
+
+impl Palette {
+    /// All colors of the palette.
+    pub fn colors(&self) -> &'static [Color] {
+        match self {
+            Palette::Standard => &STANDARD,
+            Palette::OkabeIto => &OKABE_ITO,
+            Palette::TolBright => &TOL_BRIGHT,
+            Palette::TolMuted => &TOL_MUTED,
+        }
+    }
+
+    /// Color with the given index. If the index is larger than the number of colors in the palette, the colors are
+    /// repeated.
+    pub fn color(&self, index: usize) -> Color {
+        let colors = self.colors();
+        colors[index % colors.len()]
+    }
+
+    /// Returns true if the colors of the palette can be distinguished by people with color vision deficiencies.
+    pub fn is_color_blind_safe(&self) -> bool {
+        !matches!(self, Palette::Standard)
+    }
+}
+
+/// Global settings that symbols consult when rendering features.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Theme {
+    /// Palette used for the symbols that refer to palette colors.
+    pub palette: Palette,
+    /// If set to true, polygon symbols draw their [hatching](super::Hatching) patterns over the fill. Patterns allow
+    /// distinguishing areas without relying on their color.
+    pub show_patterns: bool,
+}
+
+impl Theme {
+    /// Theme with a color-blind-safe palette and patterns enabled.
+    pub fn accessible() -> Self {
+        Self {
+            palette: Palette::OkabeIto,
+            show_patterns: true,
+        }
+    }
+
+    /// Returns the current global theme.
+    pub fn current() -> Self {
+        *CURRENT_THEME.read()
+    }
+
+    /// Sets the global theme.
+    pub fn set_current(theme: Theme) {
+        *CURRENT_THEME.write() = theme;
+    }
+
+    /// Color with the given index in the palette of the theme.
+    pub fn color(&self, index: usize) -> Color {
+        self.palette.color(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn palette_colors_repeat() {
+        let palette = Palette::TolBright;
+        assert_eq!(palette.color(0), palette.color(palette.colors().len()));
+        assert_ne!(palette.color(0), palette.color(1));
+        assert!(palette.is_color_blind_safe());
+        assert!(!Palette::Standard.is_color_blind_safe());
+    }
+}
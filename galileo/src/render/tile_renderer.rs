@@ -0,0 +1,212 @@
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::time::Duration;
+
+use galileo_types::cartesian::Size;
+use web_time::Instant;
+
+use crate::error::GalileoError;
+use crate::messenger::Messenger;
+use crate::render::WgpuRenderer;
+use crate::tile_scheme::{TileIndex, TileSchema};
+use crate::view::MapView;
+use crate::Map;
+
+const DEFAULT_LOAD_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Renders single tiles of a tile schema into images off-screen.
+///
+/// This can be used to run Galileo as the rendering core of a tile server: for each requested [`TileIndex`] the
+/// renderer sets up the map view covering exactly the area of the tile, waits until all visible layers of the map
+/// have loaded their data for this view and renders the map into a texture. GPU resources of the renderer are
+/// reused between tiles.
+///
+/// For the output to be deterministic, the fade in animations of the map layers must be turned off (e.g. with
+/// [`RasterTileLayer::set_fade_in_duration`](crate::layer::RasterTileLayer::set_fade_in_duration) or
+/// [`VectorTileLayer::set_fade_in_duration`](crate::layer::VectorTileLayer::set_fade_in_duration) set to zero).
+/// Otherwise newly loaded tiles are drawn semi-transparent.
+///
+/// To be notified when the layers load their data, the renderer sets its own messenger to all the layers of the
+/// rendered map, replacing the messengers set to them before.
+pub struct TileRenderer {
+    renderer: WgpuRenderer,
+    tile_schema: TileSchema,
+    tile_size: u32,
+    load_timeout: Duration,
+}
+
+impl TileRenderer {
+    /// Creates a new renderer producing images of `tile_size x tile_size` pixels for the tiles of the given schema.
+    ///
+    /// The size of the produced images does not have to be equal to the tile size of the schema, e.g. 512px tiles
+    /// can be rendered for a schema with 256px tiles to get high DPI images.
+    ///
+    /// Returns `None` if no suitable graphics adapter is found.
+    pub async fn new(tile_schema: TileSchema, tile_size: u32) -> Option<Self> {
+        let renderer = WgpuRenderer::new_with_texture_rt(Size::new(tile_size, tile_size)).await?;
+        Some(Self {
+            renderer,
+            tile_schema,
+            tile_size,
+            load_timeout: DEFAULT_LOAD_TIMEOUT,
+        })
+    }
+
+    /// Sets the maximum time to wait for the layers to load their data for a tile. Default value is 30 seconds.
+    pub fn with_load_timeout(mut self, timeout: Duration) -> Self {
+        self.load_timeout = timeout;
+        self
+    }
+
+    /// Tile schema of the rendered tiles.
+    pub fn tile_schema(&self) -> &TileSchema {
+        &self.tile_schema
+    }
+
+    /// Size of the rendered images in pixels.
+    pub fn tile_size(&self) -> u32 {
+        self.tile_size
+    }
+
+    /// Returns a mutable reference to the underlying renderer, e.g. to set post-processing effects.
+    pub fn renderer_mut(&mut self) -> &mut WgpuRenderer {
+        &mut self.renderer
+    }
+
+    /// Returns the map view covering exactly the area of the tile with the given index.
+    ///
+    /// Returns `None` if the index is not valid for the tile schema.
+    pub fn tile_view(&self, index: TileIndex) -> Option<MapView> {
+        tile_view(&self.tile_schema, index, self.tile_size)
+    }
+
+    /// Renders the tile with the given index and returns the image as raw RGBA bytes.
+    ///
+    /// The view of the `map` is changed to the view of the tile.
+    pub async fn render_tile(
+        &self,
+        map: &mut Map,
+        index: TileIndex,
+    ) -> Result<Vec<u8>, GalileoError> {
+        let view = self.tile_view(index).ok_or_else(|| {
+            GalileoError::Generic(format!("tile {index:?} is not valid for the tile schema"))
+        })?;
+
+        let (sender, layers_updated) = channel();
+        for layer in map.layers_mut().iter_mut() {
+            layer.set_messenger(Box::new(LayerUpdateNotifier(sender.clone())));
+        }
+
+        map.set_view(view);
+        map.load_layers();
+        self.wait_for_layers(map, layers_updated).await?;
+
+        self.renderer.render(map).map_err(|err| {
+            GalileoError::Generic(format!("failed to render tile {index:?}: {err}"))
+        })?;
+        self.renderer.get_image().await.map_err(|err| {
+            GalileoError::Generic(format!("failed to read tile {index:?} image: {err}"))
+        })
+    }
+
+    /// Renders the tile with the given index and returns the image encoded as PNG.
+    #[cfg(feature = "image")]
+    pub async fn render_tile_png(
+        &self,
+        map: &mut Map,
+        index: TileIndex,
+    ) -> Result<Vec<u8>, GalileoError> {
+        let bitmap = self.render_tile(map, index).await?;
+        let image = image::RgbaImage::from_raw(self.tile_size, self.tile_size, bitmap)
+            .ok_or_else(|| GalileoError::Generic("invalid size of the rendered image".into()))?;
+
+        let mut bytes = vec![];
+        image
+            .write_to(
+                &mut std::io::Cursor::new(&mut bytes),
+                image::ImageFormat::Png,
+            )
+            .map_err(|err| GalileoError::Generic(format!("failed to encode tile image: {err}")))?;
+
+        Ok(bytes)
+    }
+
+    /// Waits until all visible layers are ready to be rendered, checking them every time any of the layers notifies
+    /// about an update.
+    async fn wait_for_layers(
+        &self,
+        map: &Map,
+        mut layers_updated: Receiver<()>,
+    ) -> Result<(), GalileoError> {
+        let deadline = Instant::now() + self.load_timeout;
+        while !map
+            .layers()
+            .iter_visible()
+            .all(|layer| layer.is_ready(map.view()))
+        {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let (receiver, result) = crate::async_runtime::spawn_blocking(move || {
+                let result = layers_updated.recv_timeout(remaining);
+                // Updates of several layers at once are checked together
+                while layers_updated.try_recv().is_ok() {}
+                (layers_updated, result)
+            })
+            .await
+            .map_err(|err| GalileoError::Generic(err.to_string()))?;
+
+            layers_updated = receiver;
+            if result.is_err() {
+                return Err(GalileoError::Generic(format!(
+                    "layers did not load within {:?}",
+                    self.load_timeout
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Messenger that wakes up [`TileRenderer::wait_for_layers`] when a layer is updated.
+#[derive(Clone)]
+struct LayerUpdateNotifier(Sender<()>);
+
+impl Messenger for LayerUpdateNotifier {
+    fn request_redraw(&self) {
+        let _ = self.0.send(());
+    }
+}
+
+fn tile_view(tile_schema: &TileSchema, index: TileIndex, tile_size: u32) -> Option<MapView> {
+    let bbox = tile_schema.tile_bbox(index)?;
+    let resolution = bbox.width() / tile_size as f64;
+
+    Some(
+        MapView::new_projected_with_crs(&bbox.center(), resolution, tile_schema.crs.clone())
+            .with_size(Size::new(tile_size as f64, tile_size as f64)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tile_view_covers_tile() {
+        let schema = TileSchema::web(18);
+        let index = TileIndex::new(3, 2, 2);
+        let bbox = schema.tile_bbox(index).expect("valid index");
+
+        let view = tile_view(&schema, index, 512).expect("valid index");
+        let extent = view.get_bbox().expect("view has extent");
+        assert!((extent.x_min() - bbox.x_min()).abs() < 1e-6);
+        assert!((extent.x_max() - bbox.x_max()).abs() < 1e-6);
+        assert!((extent.y_min() - bbox.y_min()).abs() < 1e-6);
+        assert!((extent.y_max() - bbox.y_max()).abs() < 1e-6);
+
+        // 512px image of a 256px tile has twice the resolution of the tile level
+        let lod_resolution = schema.lod_resolution(2).expect("valid lod");
+        assert!((view.resolution() * 2.0 - lod_resolution).abs() < 1e-9);
+
+        assert!(tile_view(&schema, TileIndex::new(0, 0, 100), 512).is_none());
+    }
+}
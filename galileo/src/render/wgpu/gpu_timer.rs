@@ -0,0 +1,231 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use wgpu::{
+    Buffer, BufferDescriptor, BufferUsages, CommandEncoderDescriptor, Device, Features, MapMode,
+    QuerySet, QuerySetDescriptor, QueryType, Queue, QUERY_SIZE,
+};
+
+/// Maximum number of layers that are timed in one frame. Layers above this number are rendered without timing.
+const MAX_TIMED_LAYERS: u32 = 64;
+
+/// GPU time spent on rendering a single layer of the map.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LayerGpuTime {
+    /// Index of the layer in the [`LayerCollection`](crate::LayerCollection) of the map.
+    pub layer_index: usize,
+    /// Time between the start of the first and the end of the last draw call of the layer on the GPU.
+    pub duration: Duration,
+}
+
+impl LayerGpuTime {
+    /// Rendering time of the layer in milliseconds.
+    pub fn milliseconds(&self) -> f64 {
+        self.duration.as_secs_f64() * 1000.0
+    }
+}
+
+/// Measures GPU time of rendering map layers using timestamp queries.
+///
+/// Timestamps are written into separate command buffers submitted before and after the draw calls of each layer. The
+/// results are read back asynchronously, so they become available one or more frames after the frame is rendered.
+/// While the results of a frame are being read back, the next frames are rendered without timing.
+pub(super) struct GpuTimer {
+    query_set: QuerySet,
+    resolve_buffer: Buffer,
+    readback_buffer: Arc<Buffer>,
+    period: f32,
+    state: Arc<Mutex<TimerState>>,
+}
+
+#[derive(Default)]
+struct TimerState {
+    is_mapping: bool,
+    frame_layers: Vec<usize>,
+    results: Vec<LayerGpuTime>,
+}
+
+impl GpuTimer {
+    /// Device features required for timing.
+    pub(super) const FEATURES: Features =
+        Features::TIMESTAMP_QUERY.union(Features::TIMESTAMP_QUERY_INSIDE_ENCODERS);
+
+    /// Creates a new timer. Returns `None` if the device does not support timestamp queries.
+    pub(super) fn new(device: &Device, queue: &Queue) -> Option<Self> {
+        if !device.features().contains(Self::FEATURES) {
+            return None;
+        }
+
+        let count = MAX_TIMED_LAYERS * 2;
+        let query_set = device.create_query_set(&QuerySetDescriptor {
+            label: Some("Layer timing query set"),
+            ty: QueryType::Timestamp,
+            count,
+        });
+        let size = count as u64 * QUERY_SIZE as u64;
+        let resolve_buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("Layer timing resolve buffer"),
+            size,
+            usage: BufferUsages::QUERY_RESOLVE | BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback_buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("Layer timing readback buffer"),
+            size,
+            usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Some(Self {
+            query_set,
+            resolve_buffer,
+            readback_buffer: Arc::new(readback_buffer),
+            period: queue.get_timestamp_period(),
+            state: Default::default(),
+        })
+    }
+
+    /// Starts timing of a new frame. Returns `false` if the results of the previous frame are not read yet, in which
+    /// case the frame must not be timed.
+    pub(super) fn begin_frame(&self, device: &Device) -> bool {
+        // Give the pending readback a chance to complete
+        device.poll(wgpu::Maintain::Poll);
+
+        let mut state = self.state.lock();
+        if state.is_mapping {
+            return false;
+        }
+
+        state.frame_layers.clear();
+        true
+    }
+
+    /// Writes the timestamp before the draw calls of the layer with the given index. Returns the slot that must be
+    /// passed to [`GpuTimer::end_layer`], or `None` if no more layers can be timed in this frame.
+    pub(super) fn begin_layer(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        layer_index: usize,
+    ) -> Option<u32> {
+        let slot = {
+            let mut state = self.state.lock();
+            let slot = state.frame_layers.len() as u32;
+            if slot >= MAX_TIMED_LAYERS {
+                return None;
+            }
+
+            state.frame_layers.push(layer_index);
+            slot
+        };
+
+        self.write_timestamp(device, queue, slot * 2);
+        Some(slot)
+    }
+
+    /// Writes the timestamp after the draw calls of the layer.
+    pub(super) fn end_layer(&self, device: &Device, queue: &Queue, slot: u32) {
+        self.write_timestamp(device, queue, slot * 2 + 1);
+    }
+
+    fn write_timestamp(&self, device: &Device, queue: &Queue, query_index: u32) {
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("Layer timing encoder"),
+        });
+        encoder.write_timestamp(&self.query_set, query_index);
+        queue.submit(std::iter::once(encoder.finish()));
+    }
+
+    /// Resolves the timestamps of the frame and starts reading them back.
+    pub(super) fn end_frame(&self, device: &Device, queue: &Queue) {
+        let query_count = {
+            let mut state = self.state.lock();
+            if state.frame_layers.is_empty() {
+                state.results.clear();
+                return;
+            }
+
+            state.is_mapping = true;
+            state.frame_layers.len() as u32 * 2
+        };
+
+        let size = query_count as u64 * QUERY_SIZE as u64;
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("Layer timing resolve encoder"),
+        });
+        encoder.resolve_query_set(&self.query_set, 0..query_count, &self.resolve_buffer, 0);
+        encoder.copy_buffer_to_buffer(&self.resolve_buffer, 0, &self.readback_buffer, 0, size);
+        queue.submit(std::iter::once(encoder.finish()));
+
+        let buffer = self.readback_buffer.clone();
+        let state = self.state.clone();
+        let period = self.period;
+        self.readback_buffer
+            .slice(..size)
+            .map_async(MapMode::Read, move |result| {
+                let mut state = state.lock();
+                state.is_mapping = false;
+
+                if let Err(err) = result {
+                    log::warn!("Failed to read layer timings: {err}");
+                    return;
+                }
+
+                let timestamps: Vec<u64> = buffer
+                    .slice(..size)
+                    .get_mapped_range()
+                    .chunks_exact(QUERY_SIZE as usize)
+                    .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap_or_default()))
+                    .collect();
+                buffer.unmap();
+
+                state.results = layer_times(&state.frame_layers, &timestamps, period);
+            });
+    }
+
+    /// GPU times of the layers of the last frame which timings were read back.
+    pub(super) fn layer_times(&self) -> Vec<LayerGpuTime> {
+        self.state.lock().results.clone()
+    }
+}
+
+/// Converts pairs of begin/end timestamps (in ticks of `period` nanoseconds) into layer rendering times.
+fn layer_times(layers: &[usize], timestamps: &[u64], period: f32) -> Vec<LayerGpuTime> {
+    layers
+        .iter()
+        .zip(timestamps.chunks_exact(2))
+        .map(|(&layer_index, pair)| {
+            let ticks = pair[1].saturating_sub(pair[0]);
+            LayerGpuTime {
+                layer_index,
+                duration: Duration::from_nanos((ticks as f64 * period as f64) as u64),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timestamps_to_layer_times() {
+        let times = layer_times(&[0, 2], &[100, 1100, 1100, 1000], 2.0);
+        assert_eq!(
+            times,
+            vec![
+                LayerGpuTime {
+                    layer_index: 0,
+                    duration: Duration::from_nanos(2000),
+                },
+                // Timestamps are not guaranteed to be monotonic between command buffers
+                LayerGpuTime {
+                    layer_index: 2,
+                    duration: Duration::ZERO,
+                },
+            ]
+        );
+        assert_eq!(times[0].milliseconds(), 0.002);
+    }
+}
@@ -8,29 +8,38 @@ use lyon::tessellation::VertexBuffers;
 use nalgebra::{Rotation3, Vector3};
 use wgpu::util::DeviceExt;
 use wgpu::{
-    Adapter, Buffer, BufferAddress, BufferDescriptor, BufferUsages, Device, Extent3d,
-    ImageCopyBuffer, ImageCopyTexture, ImageDataLayout, Origin3d, Queue,
+    Adapter, Buffer, BufferAddress, BufferDescriptor, BufferUsages, CompositeAlphaMode, Device,
+    Extent3d, ImageCopyBuffer, ImageCopyTexture, ImageDataLayout, Origin3d, Queue,
     RenderPassDepthStencilAttachment, StoreOp, Surface, SurfaceConfiguration, SurfaceError,
     SurfaceTexture, Texture, TextureAspect, TextureDescriptor, TextureDimension, TextureFormat,
     TextureUsages, TextureView, TextureViewDescriptor, WasmNotSendSync,
 };
 
 use super::render_bundle::tessellating::{ImageInfo, ImageStoreInfo};
-use super::{Canvas, PackedBundle, RenderOptions};
+use super::{Canvas, PackedBundle, RenderOptions, ScreenRefOrder};
 use crate::error::GalileoError;
-use crate::layer::Layer;
-use crate::map::Map;
+use crate::layer::{is_in_view, Layer};
+use crate::map::{LayerTransform, Map};
+use crate::render::horizon::HorizonOptions;
+use crate::render::post_processing::PostEffect;
 use crate::render::render_bundle::tessellating::{
-    PointInstance, PolyVertex, TessellatingRenderBundle,
+    PointInstance, PolyVertex, TessellatingRenderBundle, DEFAULT_TESSELLATION_TOLERANCE,
 };
 use crate::render::render_bundle::{RenderBundle, RenderBundleType};
+use crate::render::wgpu::pipelines::horizon::HorizonUniform;
 use crate::render::wgpu::pipelines::image::WgpuImage;
+use crate::render::wgpu::pipelines::post_processing::PostProcessing;
 use crate::render::wgpu::pipelines::Pipelines;
+use crate::trace::span;
 use crate::view::MapView;
 use crate::Color;
 
+mod gpu_timer;
 mod pipelines;
 
+use gpu_timer::GpuTimer;
+pub use gpu_timer::LayerGpuTime;
+
 const DEFAULT_BACKGROUND: Color = Color::WHITE;
 const DEPTH_FORMAT: TextureFormat = TextureFormat::Depth24PlusStencil8;
 const TARGET_TEXTURE_FORMAT: TextureFormat = TextureFormat::Rgba8UnormSrgb;
@@ -41,6 +50,34 @@ pub struct WgpuRenderer {
     queue: Arc<Queue>,
     render_set: Option<RenderSet>,
     background: Color,
+    post_effects: Vec<PostEffect>,
+    post_processing: Option<PostProcessing>,
+    tessellation_tolerance: f32,
+    gpu_timer: Option<GpuTimer>,
+    context: Option<WgpuContext>,
+}
+
+/// Wgpu instance, adapter, device and queue shared by the renderers of several windows.
+///
+/// See [`WgpuRenderer::new_with_context_and_window`].
+#[derive(Clone)]
+pub struct WgpuContext {
+    instance: Arc<wgpu::Instance>,
+    adapter: Arc<Adapter>,
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+}
+
+impl WgpuContext {
+    /// Wgpu device used by the renderers.
+    pub fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
+
+    /// Wgpu queue used by the renderers.
+    pub fn queue(&self) -> &Arc<Queue> {
+        &self.queue
+    }
 }
 
 struct RenderSet {
@@ -90,6 +127,19 @@ impl RenderTarget {
         }
     }
 
+    /// Configures the surface again with the current parameters. This is needed when the surface is lost or outdated,
+    /// e.g. after the window was moved to a different display.
+    fn reconfigure(&self, device: &Device) {
+        if let RenderTarget::Surface { config, surface } = self {
+            log::info!(
+                "Reconfiguring surface with size {}x{}",
+                config.width,
+                config.height
+            );
+            surface.configure(device, config);
+        }
+    }
+
     fn size(&self) -> Size<u32> {
         match &self {
             RenderTarget::Surface { config, .. } => Size::new(config.width, config.height),
@@ -126,6 +176,11 @@ impl WgpuRenderer {
             queue: Arc::new(queue),
             render_set: None,
             background: DEFAULT_BACKGROUND,
+            post_effects: vec![],
+            tessellation_tolerance: DEFAULT_TESSELLATION_TOLERANCE,
+            post_processing: None,
+            gpu_timer: None,
+            context: None,
         })
     }
 
@@ -190,6 +245,8 @@ impl WgpuRenderer {
             }
             _ => self.render_set = Some(self.create_render_set(new_target)),
         }
+
+        self.update_post_processing();
     }
 
     fn create_render_set(&self, render_target: RenderTarget) -> RenderSet {
@@ -222,25 +279,113 @@ impl WgpuRenderer {
             + WasmNotSendSync
             + 'static,
     {
-        let (surface, adapter) = Self::get_window_surface(window).await?;
+        let (instance, surface, adapter) = Self::create_window_surface(window).await?;
         let (device, queue) = Self::create_device(&adapter).await;
 
         let config = Self::get_surface_configuration(&surface, &adapter, size);
         log::info!("Configuring surface with size {size:?}");
         surface.configure(&device, &config);
 
-        Some(Self::new_with_device_and_surface(
+        let mut renderer = Self::new_with_device_and_surface(
             Arc::new(device),
             Arc::new(surface),
             Arc::new(queue),
             config,
-        ))
+        );
+        renderer.context = Some(WgpuContext {
+            instance: Arc::new(instance),
+            adapter: Arc::new(adapter),
+            device: renderer.device.clone(),
+            queue: renderer.queue.clone(),
+        });
+
+        Some(renderer)
+    }
+
+    /// Creates a new wgpu renderer that renders the map to the given window using the device of an existing renderer.
+    ///
+    /// This allows rendering several windows with one device, so that GPU resources (e.g. textures and buffers of
+    /// render bundles) can be shared between the maps of the windows. The context can be obtained with
+    /// [`WgpuRenderer::context`] from a renderer created for another window. The given size must be equal to the window
+    /// size.
+    ///
+    /// Returns `None` if the surface for the window cannot be created or the adapter of the context cannot present to
+    /// it.
+    pub fn new_with_context_and_window<W>(
+        context: &WgpuContext,
+        window: Arc<W>,
+        size: Size<u32>,
+    ) -> Option<Self>
+    where
+        W: raw_window_handle::HasWindowHandle
+            + raw_window_handle::HasDisplayHandle
+            + WasmNotSendSync
+            + 'static,
+    {
+        let surface = match context.instance.create_surface(window) {
+            Ok(s) => s,
+            Err(err) => {
+                log::warn!("Failed to create a surface from window: {err:?}");
+                return None;
+            }
+        };
+
+        if !context.adapter.is_surface_supported(&surface) {
+            log::warn!("Adapter of the renderer context cannot present to the window surface");
+            return None;
+        }
+
+        let config = Self::get_surface_configuration(&surface, &context.adapter, size);
+        log::info!("Configuring surface with size {size:?}");
+        surface.configure(&context.device, &config);
+
+        let mut renderer = Self::new_with_device_and_surface(
+            context.device.clone(),
+            Arc::new(surface),
+            context.queue.clone(),
+            config,
+        );
+        renderer.context = Some(context.clone());
+
+        Some(renderer)
+    }
+
+    /// Returns the wgpu context of the renderer, which can be used to create renderers for other windows sharing the
+    /// same device.
+    ///
+    /// Only renderers created for a window with [`WgpuRenderer::new_with_window`] or
+    /// [`WgpuRenderer::new_with_context_and_window`] have a context.
+    pub fn context(&self) -> Option<&WgpuContext> {
+        self.context.as_ref()
+    }
+
+    /// Wgpu device used by the renderer.
+    pub fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
+
+    /// Wgpu queue used by the renderer.
+    pub fn queue(&self) -> &Arc<Queue> {
+        &self.queue
     }
 
     /// Creates a wgpu surface for the given window.
     ///
     /// Returns `None` if a device adapter cannot be acquired.
     pub async fn get_window_surface<W>(window: Arc<W>) -> Option<(Surface<'static>, Adapter)>
+    where
+        W: raw_window_handle::HasWindowHandle
+            + raw_window_handle::HasDisplayHandle
+            + WasmNotSendSync
+            + 'static,
+    {
+        let (_, surface, adapter) = Self::create_window_surface(window).await?;
+        Some((surface, adapter))
+    }
+
+    async fn create_window_surface<W>(
+        window: Arc<W>,
+    ) -> Option<(wgpu::Instance, Surface<'static>, Adapter)>
     where
         W: raw_window_handle::HasWindowHandle
             + raw_window_handle::HasDisplayHandle
@@ -265,7 +410,7 @@ impl WgpuRenderer {
                 force_fallback_adapter: false,
             })
             .await?;
-        Some((surface, adapter))
+        Some((instance, surface, adapter))
     }
 
     fn get_surface_configuration(
@@ -281,14 +426,26 @@ impl WgpuRenderer {
             .find(|f| f.is_srgb())
             .unwrap_or(surface_caps.formats[0]);
 
+        // The map image has premultiplied alpha, so with this mode a transparent background shows the content behind
+        // the window. For opaque backgrounds all alpha modes give the same result.
+        let alpha_mode = if surface_caps
+            .alpha_modes
+            .contains(&CompositeAlphaMode::PreMultiplied)
+        {
+            CompositeAlphaMode::PreMultiplied
+        } else {
+            surface_caps.alpha_modes[0]
+        };
+
+        // Surface cannot be configured with zero size, which windows have e.g. when minimized
         SurfaceConfiguration {
             usage: TextureUsages::RENDER_ATTACHMENT,
             format: surface_format,
-            width: size.width(),
-            height: size.height(),
+            width: size.width().max(1),
+            height: size.height().max(1),
             present_mode: surface_caps.present_modes[0],
             desired_maximum_frame_latency: 2,
-            alpha_mode: surface_caps.alpha_modes[0],
+            alpha_mode,
             view_formats: vec![],
         }
     }
@@ -306,6 +463,11 @@ impl WgpuRenderer {
             queue,
             render_set: None,
             background: DEFAULT_BACKGROUND,
+            post_effects: vec![],
+            tessellation_tolerance: DEFAULT_TESSELLATION_TOLERANCE,
+            post_processing: None,
+            gpu_timer: None,
+            context: None,
         };
         renderer.init_render_set(render_target);
 
@@ -324,6 +486,11 @@ impl WgpuRenderer {
             queue,
             render_set: None,
             background: DEFAULT_BACKGROUND,
+            post_effects: vec![],
+            tessellation_tolerance: DEFAULT_TESSELLATION_TOLERANCE,
+            post_processing: None,
+            gpu_timer: None,
+            context: None,
         };
 
         renderer.init_target_texture(size);
@@ -332,10 +499,96 @@ impl WgpuRenderer {
     }
 
     /// Set the background color for the map.
+    ///
+    /// The background can be (semi-)transparent, e.g. [`Color::TRANSPARENT`] to composite the map over other
+    /// content. The rendered image then has premultiplied alpha, which is the format expected by most compositors
+    /// (including `egui`). When rendering to a window surface, the surface must be configured with
+    /// [`CompositeAlphaMode::PreMultiplied`] alpha mode for the transparency to have effect. Renderers created for a
+    /// window with [`WgpuRenderer::new_with_window`] select this mode if the surface supports it.
     pub fn set_background(&mut self, color: Color) {
         self.background = color;
     }
 
+    /// Background color of the map.
+    pub fn background(&self) -> Color {
+        self.background
+    }
+
+    /// Sets the maximum distance in screen pixels between curves and their tessellated approximation for the
+    /// bundles created by the renderer. See [`RenderBundle::set_tessellation_tolerance`].
+    ///
+    /// The tolerance is set in physical pixels, so symbols that are scaled by the
+    /// [DPI scale factor](MapView::dpi_scale_factor) of the screen are tessellated with more segments. Bundles
+    /// that were already created keep their tolerance.
+    pub fn set_tessellation_tolerance(&mut self, tolerance: f32) {
+        self.tessellation_tolerance = tolerance;
+    }
+
+    /// Tessellation tolerance of the bundles created by the renderer.
+    pub fn tessellation_tolerance(&self) -> f32 {
+        self.tessellation_tolerance
+    }
+
+    /// Sets full-screen effects applied to the map image after all layers are rendered. Effects are applied in the
+    /// given order. Setting an empty list disables post processing.
+    pub fn set_post_effects(&mut self, effects: Vec<PostEffect>) {
+        self.post_effects = effects;
+        self.update_post_processing();
+    }
+
+    /// Post processing effects applied to the map image.
+    pub fn post_effects(&self) -> &[PostEffect] {
+        &self.post_effects
+    }
+
+    /// Enables or disables measuring of GPU time spent on rendering each layer of the map.
+    ///
+    /// Timing requires [timestamp queries](wgpu::Features::TIMESTAMP_QUERY_INSIDE_ENCODERS) support by the device.
+    /// Renderers that create their own device request the feature when it is available. Returns `false` if timing
+    /// cannot be enabled for the device.
+    ///
+    /// The results are available through [`WgpuRenderer::layer_gpu_times`].
+    pub fn set_gpu_timing(&mut self, enabled: bool) -> bool {
+        if !enabled {
+            self.gpu_timer = None;
+            return true;
+        }
+
+        if self.gpu_timer.is_none() {
+            self.gpu_timer = GpuTimer::new(&self.device, &self.queue);
+        }
+
+        self.gpu_timer.is_some()
+    }
+
+    /// Returns `true` if GPU timing of layers is enabled.
+    pub fn gpu_timing(&self) -> bool {
+        self.gpu_timer.is_some()
+    }
+
+    /// GPU time spent on rendering each visible layer of the map in a recent frame.
+    ///
+    /// The timings are read back from the GPU asynchronously, so they lag one or more frames behind. Returns an empty
+    /// list if GPU timing is not enabled with [`WgpuRenderer::set_gpu_timing`].
+    pub fn layer_gpu_times(&self) -> Vec<LayerGpuTime> {
+        self.gpu_timer
+            .as_ref()
+            .map(|timer| timer.layer_times())
+            .unwrap_or_default()
+    }
+
+    fn update_post_processing(&mut self) {
+        self.post_processing = match &self.render_set {
+            Some(render_set) if !self.post_effects.is_empty() => Some(PostProcessing::create(
+                &self.device,
+                render_set.render_target.format(),
+                render_set.render_target.size(),
+                &self.post_effects,
+            )),
+            _ => None,
+        };
+    }
+
     /// Returns `true` if the renderer can be used to draw to.
     pub fn initialized(&self) -> bool {
         self.render_set.is_some()
@@ -362,7 +615,9 @@ impl WgpuRenderer {
         adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
-                    required_features: wgpu::Features::empty(),
+                    // Timestamp queries are only used if GPU timing is enabled, but they can only be requested
+                    // when the device is created
+                    required_features: adapter.features() & GpuTimer::FEATURES,
                     required_limits: if cfg!(any(target_arch = "wasm32", target_os = "android")) {
                         wgpu::Limits {
                             max_texture_dimension_2d: 4096,
@@ -497,6 +752,8 @@ impl WgpuRenderer {
             render_set.stencil_view_multisample =
                 Self::create_stencil_texture(&self.device, new_size, 4);
             render_set.stencil_view = Self::create_stencil_texture(&self.device, new_size, 1);
+
+            self.update_post_processing();
         }
     }
 
@@ -526,7 +783,9 @@ impl WgpuRenderer {
         };
 
         let size = render_set.render_target.size();
-        let buffer_size = (size.width() * size.height() * size_of::<u32>() as u32) as BufferAddress;
+        let row_size = size_of::<u32>() as u32 * size.width();
+        let padded_row_size = padded_bytes_per_row(size.width());
+        let buffer_size = (padded_row_size * size.height()) as BufferAddress;
         let buffer_desc = BufferDescriptor {
             size: buffer_size,
             usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
@@ -551,7 +810,7 @@ impl WgpuRenderer {
                 buffer: &buffer,
                 layout: ImageDataLayout {
                     offset: 0,
-                    bytes_per_row: Some(size_of::<u32>() as u32 * size.width()),
+                    bytes_per_row: Some(padded_row_size),
                     rows_per_image: Some(size.height()),
                 },
             },
@@ -586,48 +845,77 @@ impl WgpuRenderer {
             }
         }
 
+        // Rows of the buffer are padded to the copy alignment, which must be stripped from the image
         let data = buffer_slice.get_mapped_range();
-        Ok(data.to_vec())
+        Ok(data
+            .chunks_exact(padded_row_size as usize)
+            .flat_map(|row| &row[..row_size as usize])
+            .copied()
+            .collect())
     }
 
     /// Renders the map to the given texture.
     pub fn render_to_texture_view(&self, map: &Map, view: &TextureView) {
-        if let Some(render_set) = &self.render_set {
+        let Some(render_set) = &self.render_set else {
+            return;
+        };
+
+        span!("render_frame");
+
+        // With post processing enabled layers are drawn to an intermediate texture first
+        let layers_target = match &self.post_processing {
+            Some(post_processing) => post_processing.source_view(),
+            None => view,
+        };
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Render Encoder"),
+            });
+
+        {
+            // Layers are blended into the target so that it keeps premultiplied alpha, so the clear color must be
+            // premultiplied too
+            let background = self.background.to_premultiplied_f32_array();
+            let _ = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Render Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &render_set.multisampling_view,
+                    resolve_target: Some(layers_target),
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color {
+                            r: background[0] as f64,
+                            g: background[1] as f64,
+                            b: background[2] as f64,
+                            a: background[3] as f64,
+                        }),
+                        store: StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+        }
+
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        self.render_map(map, layers_target);
+
+        if let Some(horizon) = map.horizon() {
+            self.render_horizon(render_set, horizon, map.view(), layers_target);
+        }
+
+        if let Some(post_processing) = &self.post_processing {
             let mut encoder = self
                 .device
                 .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                    label: Some("Render Encoder"),
-                });
-
-            {
-                let background = self.background.to_f32_array();
-                let _ = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                    label: Some("Render Pass"),
-                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                        view: &render_set.multisampling_view,
-                        resolve_target: Some(view),
-                        ops: wgpu::Operations {
-                            load: wgpu::LoadOp::Clear(wgpu::Color {
-                                r: background[0] as f64,
-                                g: background[1] as f64,
-                                b: background[2] as f64,
-                                a: background[3] as f64,
-                            }),
-                            store: StoreOp::Store,
-                        },
-                    })],
-                    depth_stencil_attachment: None,
-                    timestamp_writes: None,
-                    occlusion_query_set: None,
+                    label: Some("Post processing encoder"),
                 });
-            }
-
+            post_processing.apply(&mut encoder, view);
             self.queue.submit(std::iter::once(encoder.finish()));
-        } else {
-            return;
         }
-
-        self.render_map(map, view);
     }
 
     /// Renders the map.
@@ -636,7 +924,18 @@ impl WgpuRenderer {
             return Ok(());
         };
 
-        let texture = render_set.render_target.texture()?;
+        if map.view().size().is_zero() {
+            return Ok(());
+        }
+
+        let texture = match render_set.render_target.texture() {
+            Ok(texture) => texture,
+            Err(SurfaceError::Lost | SurfaceError::Outdated) => {
+                render_set.render_target.reconfigure(&self.device);
+                render_set.render_target.texture()?
+            }
+            Err(err) => return Err(err),
+        };
         let view = texture.view();
 
         self.render_to_texture_view(map, &view);
@@ -646,18 +945,79 @@ impl WgpuRenderer {
         Ok(())
     }
 
+    fn render_horizon(
+        &self,
+        render_set: &RenderSet,
+        horizon: &HorizonOptions,
+        view: &MapView,
+        texture_view: &TextureView,
+    ) {
+        let Some(horizon_y) = view.horizon_screen_y() else {
+            return;
+        };
+
+        let haze_height = horizon.haze_height * view.dpi_scale_factor();
+        if horizon_y + haze_height <= 0.0 {
+            // Horizon is too far above the screen for the haze to be visible
+            return;
+        }
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Horizon encoder"),
+            });
+        render_set.pipelines.horizon_pipeline().render(
+            &self.queue,
+            &mut encoder,
+            texture_view,
+            HorizonUniform {
+                sky_color: horizon.sky_color.to_premultiplied_f32_array(),
+                haze_color: horizon.haze_color.to_premultiplied_f32_array(),
+                params: [horizon_y as f32, haze_height as f32, 0.0, 0.0],
+            },
+        );
+        self.queue.submit(std::iter::once(encoder.finish()));
+    }
+
     fn render_map(&self, map: &Map, texture_view: &TextureView) {
         let view = map.view();
-        for layer in map.layers().iter_visible() {
-            self.render_layer(layer, view, texture_view);
+        let timer = self
+            .gpu_timer
+            .as_ref()
+            .filter(|timer| timer.begin_frame(&self.device));
+
+        for (index, layer, transform) in map.layers().iter_visible_with_transforms() {
+            if !is_in_view(layer, view, transform) {
+                continue;
+            }
+
+            span!("render_layer", index);
+            let slot = timer.and_then(|timer| timer.begin_layer(&self.device, &self.queue, index));
+            self.render_layer(layer, view, transform, texture_view);
+            if let (Some(timer), Some(slot)) = (timer, slot) {
+                timer.end_layer(&self.device, &self.queue, slot);
+            }
+        }
+
+        if let Some(timer) = timer {
+            timer.end_frame(&self.device, &self.queue);
         }
     }
 
-    fn render_layer(&self, layer: &dyn Layer, view: &MapView, texture_view: &TextureView) {
+    fn render_layer(
+        &self,
+        layer: &dyn Layer,
+        view: &MapView,
+        transform: Option<&LayerTransform>,
+        texture_view: &TextureView,
+    ) {
         let Some(render_set) = &self.render_set else {
             return;
         };
-        let Some(mut canvas) = WgpuCanvas::new(self, render_set, texture_view, view.clone()) else {
+        let Some(mut canvas) =
+            WgpuCanvas::new(self, render_set, texture_view, view.clone(), transform)
+        else {
             log::warn!("Layer cannot be rendered to the map view.");
             return;
         };
@@ -676,12 +1036,19 @@ impl WgpuRenderer {
     }
 
     fn create_bundle(&self) -> RenderBundle {
-        RenderBundle(RenderBundleType::Tessellating(
-            TessellatingRenderBundle::new(),
-        ))
+        let mut bundle = TessellatingRenderBundle::new();
+        bundle.set_tessellation_tolerance(self.tessellation_tolerance);
+        RenderBundle(RenderBundleType::Tessellating(bundle))
     }
 }
 
+/// Size of a row of an image with the given width in a buffer, padded to the alignment required for copying
+/// textures into buffers.
+fn padded_bytes_per_row(width: u32) -> u32 {
+    let row_size = size_of::<u32>() as u32 * width;
+    row_size.div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT) * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT
+}
+
 #[allow(dead_code)]
 struct WgpuCanvas<'a> {
     renderer: &'a WgpuRenderer,
@@ -695,6 +1062,7 @@ impl<'a> WgpuCanvas<'a> {
         render_set: &'a RenderSet,
         view: &'a TextureView,
         map_view: MapView,
+        layer_transform: Option<&LayerTransform>,
     ) -> Option<Self> {
         let rotation_mtx = Rotation3::new(Vector3::new(
             map_view.rotation_x(),
@@ -702,18 +1070,31 @@ impl<'a> WgpuCanvas<'a> {
             -map_view.rotation_z(),
         ))
         .to_homogeneous();
+
+        // Layer transform is applied to the layer coordinates before the view transform. Scale of the transform
+        // changes the size of the map units, so resolution is adjusted to keep the pixel sizes (e.g. line widths)
+        // unchanged.
+        let mut view_proj = map_view.map_to_scene_transform()?;
+        let mut resolution = map_view.resolution();
+        let mut rotation_z = map_view.rotation_z();
+        if let Some(transform) = layer_transform {
+            view_proj *= transform.to_matrix();
+            resolution /= transform.scale();
+            rotation_z += transform.rotation();
+        }
+
         renderer.queue.write_buffer(
             render_set.pipelines.map_view_buffer(),
             0,
             bytemuck::cast_slice(&[ViewUniform {
-                view_proj: map_view.map_to_scene_mtx()?,
+                view_proj: view_proj.cast::<f32>().data.0,
                 view_rotation: rotation_mtx.cast::<f32>().data.0,
                 inv_screen_size: [
                     1.0 / renderer.size().width() as f32,
                     1.0 / renderer.size().height() as f32,
                 ],
-                resolution: map_view.resolution() as f32,
-                _padding: [0.0; 1],
+                resolution: resolution as f32,
+                rotation_z: rotation_z as f32,
             }]),
         );
 
@@ -735,6 +1116,7 @@ impl Canvas for WgpuCanvas<'_> {
     }
 
     fn pack_bundle(&self, bundle: &RenderBundle) -> Box<dyn PackedBundle> {
+        span!("pack_bundle");
         match bundle {
             RenderBundle(RenderBundleType::Tessellating(inner)) => {
                 Box::new(WgpuPackedBundle::new(inner, self.renderer, self.render_set))
@@ -742,6 +1124,19 @@ impl Canvas for WgpuCanvas<'_> {
         }
     }
 
+    fn update_packed_bundle(&self, packed: &dyn PackedBundle, bundle: &RenderBundle) -> bool {
+        span!("update_packed_bundle");
+        let Some(packed) = packed.as_any().downcast_ref::<WgpuPackedBundle>() else {
+            return false;
+        };
+
+        match bundle {
+            RenderBundle(RenderBundleType::Tessellating(inner)) => {
+                packed.update(inner, &self.renderer.queue)
+            }
+        }
+    }
+
     fn draw_bundles(&mut self, bundles: &[&dyn PackedBundle], options: RenderOptions) {
         let with_opacity: Vec<_> = bundles.iter().map(|bundle| (*bundle, 1.0)).collect();
         self.draw_bundles_with_opacity(&with_opacity, options);
@@ -811,11 +1206,30 @@ impl Canvas for WgpuCanvas<'_> {
                     });
             render_pass.set_vertex_buffer(1, display_buffer.slice(..));
 
-            for (index, (bundle, _)) in bundles.iter().enumerate() {
-                if let Some(cast) = bundle.as_any().downcast_ref::<WgpuPackedBundle>() {
-                    self.render_set
-                        .pipelines
-                        .render(&mut render_pass, cast, options, index as u32);
+            let packed: Vec<(u32, &WgpuPackedBundle)> = bundles
+                .iter()
+                .enumerate()
+                .filter_map(|(index, (bundle, _))| {
+                    Some((
+                        index as u32,
+                        bundle.as_any().downcast_ref::<WgpuPackedBundle>()?,
+                    ))
+                })
+                .collect();
+            let pipelines = &self.render_set.pipelines;
+            match options.screen_ref_order {
+                ScreenRefOrder::WithBundle => {
+                    for (index, bundle) in &packed {
+                        pipelines.render(&mut render_pass, bundle, options, *index);
+                    }
+                }
+                ScreenRefOrder::AfterBundles => {
+                    for (index, bundle) in &packed {
+                        pipelines.render_map_ref(&mut render_pass, bundle, options, *index);
+                    }
+                    for (index, bundle) in &packed {
+                        pipelines.render_screen_ref(&mut render_pass, bundle, options, *index);
+                    }
                 }
             }
         }
@@ -886,7 +1300,7 @@ impl WgpuPackedBundle {
                 .device
                 .create_buffer_init(&wgpu::util::BufferInitDescriptor {
                     label: None,
-                    usage: wgpu::BufferUsages::VERTEX,
+                    usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
                     contents: bytemuck::cast_slice(&screen_ref.vertices),
                 });
 
@@ -907,7 +1321,7 @@ impl WgpuPackedBundle {
                     .device
                     .create_buffer_init(&wgpu::util::BufferInitDescriptor {
                         label: None,
-                        usage: wgpu::BufferUsages::VERTEX,
+                        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
                         contents: bytemuck::cast_slice(points),
                     });
             let count = points.len();
@@ -917,38 +1331,23 @@ impl WgpuPackedBundle {
             })
         };
 
-        let textures: Vec<_> = image_store
-            .iter()
-            .map(|stored| match stored {
-                ImageStoreInfo::Vacant => None,
-                ImageStoreInfo::Image(decoded_image) => {
-                    Some(render_set.pipelines.image_pipeline().create_image_texture(
-                        &renderer.device,
-                        &renderer.queue,
-                        decoded_image,
-                    ))
-                }
-            })
-            .collect();
-
-        let mut image_buffers = vec![];
-        for image_info in images {
-            if let ImageInfo::Image((image_index, vertices)) = image_info {
-                let image = render_set.pipelines.image_pipeline().create_image(
-                    &renderer.device,
-                    textures
-                        .get(*image_index)
-                        .expect("texture at index must exist")
-                        .clone()
-                        .expect("image texture must not be None")
-                        .clone(),
-                    vertices,
-                );
-                image_buffers.push(image);
-            } else {
-                // ignore vacant image slots
-            }
-        }
+        let image_buffers = render_set.pipelines.image_pipeline().create_images(
+            &renderer.device,
+            &renderer.queue,
+            images
+                .iter()
+                .enumerate()
+                .filter_map(|(index, image_info)| match image_info {
+                    ImageInfo::Image((image_index, vertices)) => {
+                        match image_store.get(*image_index) {
+                            Some(ImageStoreInfo::Image(image)) => Some((index, image, vertices)),
+                            _ => None,
+                        }
+                    }
+                    // ignore vacant image slots
+                    ImageInfo::Vacant => None,
+                }),
+        );
 
         Self {
             clip_area_buffers,
@@ -959,6 +1358,65 @@ impl WgpuPackedBundle {
         }
     }
 
+    /// Writes the vertices of the `bundle` into the existing buffers. Returns false if the bundle has a different
+    /// set of primitives than the one the buffers were created from.
+    fn update(&self, bundle: &TessellatingRenderBundle, queue: &Queue) -> bool {
+        let TessellatingRenderBundle {
+            poly_tessellation,
+            points,
+            screen_ref,
+            images,
+            image_store,
+            ..
+        } = bundle;
+
+        let poly_bytes: &[u8] = bytemuck::cast_slice(&poly_tessellation.vertices);
+        let screen_ref_bytes: &[u8] = bytemuck::cast_slice(&screen_ref.vertices);
+        let point_bytes: &[u8] = bytemuck::cast_slice(points);
+
+        let poly_matches = self.map_ref_buffers.vertex.size() == poly_bytes.len() as u64
+            && self.map_ref_buffers.index_count as usize == poly_tessellation.indices.len();
+        let screen_ref_matches = match &self.screen_ref_buffers {
+            Some(buffers) => {
+                buffers.vertex.size() == screen_ref_bytes.len() as u64
+                    && buffers.index_count as usize == screen_ref.indices.len()
+            }
+            None => screen_ref.vertices.is_empty(),
+        };
+        let dots_match = match &self.dot_buffers {
+            Some(buffers) => buffers.point_count as usize == points.len(),
+            None => points.is_empty(),
+        };
+        let image_count = images
+            .iter()
+            .filter(|info| matches!(info, ImageInfo::Image(_)))
+            .count();
+        let images_match = self
+            .image_buffers
+            .iter()
+            .map(|image| image.image_count())
+            .sum::<usize>()
+            == image_count;
+
+        if !(poly_matches && screen_ref_matches && dots_match && images_match) {
+            return false;
+        }
+
+        if !poly_bytes.is_empty() {
+            queue.write_buffer(&self.map_ref_buffers.vertex, 0, poly_bytes);
+        }
+        if let Some(buffers) = &self.screen_ref_buffers {
+            queue.write_buffer(&buffers.vertex, 0, screen_ref_bytes);
+        }
+        if let Some(buffers) = &self.dot_buffers {
+            queue.write_buffer(&buffers.buffer, 0, point_bytes);
+        }
+
+        self.image_buffers
+            .iter()
+            .all(|image| image.update(queue, images, image_store))
+    }
+
     fn write_poly_buffers(
         tessellation: &VertexBuffers<PolyVertex, u32>,
         renderer: &WgpuRenderer,
@@ -1003,7 +1461,7 @@ struct ViewUniform {
     view_rotation: [[f32; 4]; 4],
     inv_screen_size: [f32; 2],
     resolution: f32,
-    _padding: [f32; 1],
+    rotation_z: f32,
 }
 
 impl PointInstance {
@@ -1054,6 +1512,23 @@ impl PolyVertex {
                     shader_location: 3,
                     format: wgpu::VertexFormat::Float32,
                 },
+                wgpu::VertexAttribute {
+                    offset: (size_of::<[f32; 3]>()
+                        + size_of::<[f32; 4]>()
+                        + size_of::<[f32; 2]>()
+                        + size_of::<f32>()) as wgpu::BufferAddress,
+                    shader_location: 4,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: (size_of::<[f32; 3]>()
+                        + size_of::<[f32; 4]>()
+                        + size_of::<[f32; 2]>()
+                        + size_of::<f32>()
+                        + size_of::<[f32; 4]>()) as wgpu::BufferAddress,
+                    shader_location: 5,
+                    format: wgpu::VertexFormat::Float32,
+                },
             ],
         }
     }
@@ -1078,3 +1553,15 @@ impl DisplayInstance {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn padded_rows() {
+        assert_eq!(padded_bytes_per_row(64), 256);
+        assert_eq!(padded_bytes_per_row(100), 512);
+        assert_eq!(padded_bytes_per_row(1), 256);
+    }
+}
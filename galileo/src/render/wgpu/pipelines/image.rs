@@ -1,5 +1,7 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
+use parking_lot::Mutex;
 use wgpu::util::{DeviceExt, TextureDataOrder};
 use wgpu::{
     BindGroup, BindGroupLayout, Device, Queue, RenderPass, RenderPipeline,
@@ -7,23 +9,113 @@ use wgpu::{
 };
 
 use crate::decoded_image::{DecodedImage, DecodedImageType};
-use crate::render::render_bundle::tessellating::ImageVertex;
+use crate::render::render_bundle::tessellating::{ImageInfo, ImageStoreInfo, ImageVertex};
 use crate::render::wgpu::pipelines::default_targets;
+use crate::render::wgpu::pipelines::image_atlas::ImageAtlas;
 use crate::render::wgpu::{pipelines, DisplayInstance};
-use crate::render::RenderOptions;
+use crate::render::{BlendMode, RenderOptions};
 
-const INDICES: &[u16] = &[1, 0, 2, 1, 2, 3];
+const QUAD_INDICES: [u32; 6] = [1, 0, 2, 1, 2, 3];
 
+/// A batch of images that use the same texture and are drawn with a single draw call.
 pub struct WgpuImage {
     pub texture_bind_group: Arc<BindGroup>,
     pub vertex_buffer: wgpu::Buffer,
+    pub index_buffer: wgpu::Buffer,
+    pub index_count: u32,
+    // Space in the image atlas is reused only after all images in it are dropped, so the images are kept alive for as
+    // long as they can be drawn.
+    sources: Vec<ImageSource>,
+}
+
+/// Image of the render bundle drawn as a quad of a [`WgpuImage`] batch.
+struct ImageSource {
+    /// Index of the image in the list of images of the bundle.
+    index: usize,
+    image: Arc<DecodedImage>,
+    /// Texture coordinates of the quad corners, mapped into the atlas region of the image.
+    tex_coords: [[f32; 2]; 4],
+}
+
+impl WgpuImage {
+    /// Writes the positions, offsets and opacity of the bundle images into the vertex buffer. Returns false if the
+    /// images of the bundle are not the ones the batch was created from.
+    pub fn update(
+        &self,
+        queue: &Queue,
+        images: &[ImageInfo],
+        image_store: &[ImageStoreInfo],
+    ) -> bool {
+        let mut vertices = Vec::with_capacity(self.sources.len() * 4);
+        for source in &self.sources {
+            let Some(ImageInfo::Image((store_index, source_vertices))) = images.get(source.index)
+            else {
+                return false;
+            };
+            match image_store.get(*store_index) {
+                Some(ImageStoreInfo::Image(image)) if Arc::ptr_eq(image, &source.image) => {}
+                _ => return false,
+            }
+
+            vertices.extend(source_vertices.iter().zip(source.tex_coords).map(
+                |(vertex, tex_coords)| ImageVertex {
+                    tex_coords,
+                    ..*vertex
+                },
+            ));
+        }
+
+        queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&vertices));
+        true
+    }
+
+    /// Number of images drawn by the batch.
+    pub fn image_count(&self) -> usize {
+        self.sources.len()
+    }
 }
 
 pub struct ImagePipeline {
-    wgpu_pipeline: RenderPipeline,
-    index_buffer: wgpu::Buffer,
+    normal: BlendPipelines,
+    multiply: BlendPipelines,
+    screen: BlendPipelines,
     texture_bind_group_layout: BindGroupLayout,
-    pub wgpu_pipeline_antialias: RenderPipeline,
+    atlas: Mutex<ImageAtlas>,
+}
+
+struct BlendPipelines {
+    wgpu_pipeline: RenderPipeline,
+    wgpu_pipeline_antialias: RenderPipeline,
+}
+
+impl BlendPipelines {
+    fn create(device: &Device, mut desc: RenderPipelineDescriptor) -> Self {
+        let wgpu_pipeline = device.create_render_pipeline(&desc);
+        desc.multisample.count = 4;
+        let wgpu_pipeline_antialias = device.create_render_pipeline(&desc);
+
+        Self {
+            wgpu_pipeline,
+            wgpu_pipeline_antialias,
+        }
+    }
+}
+
+/// Blend state for the modes that only change the color of the target, keeping its alpha. Source color is expected
+/// to be premultiplied.
+fn color_blend_state(color_src_factor: wgpu::BlendFactor) -> wgpu::BlendState {
+    wgpu::BlendState {
+        color: wgpu::BlendComponent {
+            src_factor: color_src_factor,
+            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+            operation: wgpu::BlendOperation::Add,
+        },
+        alpha: wgpu::BlendComponent {
+            src_factor: wgpu::BlendFactor::Zero,
+            dst_factor: wgpu::BlendFactor::One,
+            operation: wgpu::BlendOperation::Add,
+        },
+    }
 }
 
 impl ImagePipeline {
@@ -65,30 +157,41 @@ impl ImagePipeline {
         });
 
         let targets = default_targets(format);
+        let normal = BlendPipelines::create(
+            device,
+            pipelines::default_pipeline_descriptor(&layout, &shader, &targets, &buffers, false),
+        );
 
-        let mut desc = RenderPipelineDescriptor {
-            ..pipelines::default_pipeline_descriptor(&layout, &shader, &targets, &buffers, false)
-        };
+        // Shaders output premultiplied color `c * a`, so the blending results in:
+        // * multiply: `dst * c * a + dst * (1 - a)`,
+        // * screen (alpha output is set to 0): `(1 - dst) * c * a + dst = c * a + dst - dst * c * a`.
+        let blended_pipelines = |entry_point: &'static str, src_factor: wgpu::BlendFactor| {
+            let mut targets = default_targets(format);
+            if let Some(target) = &mut targets[0] {
+                target.blend = Some(color_blend_state(src_factor));
+            }
 
-        let wgpu_pipeline = device.create_render_pipeline(&desc);
-        desc.multisample.count = 4;
-        let wgpu_pipeline_antialias = device.create_render_pipeline(&desc);
+            let mut desc =
+                pipelines::default_pipeline_descriptor(&layout, &shader, &targets, &buffers, false);
+            if let Some(fragment) = &mut desc.fragment {
+                fragment.entry_point = Some(entry_point);
+            }
 
-        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Image index buffer"),
-            contents: bytemuck::cast_slice(INDICES),
-            usage: wgpu::BufferUsages::INDEX,
-        });
+            BlendPipelines::create(device, desc)
+        };
+        let multiply = blended_pipelines("fs_multiply", wgpu::BlendFactor::Dst);
+        let screen = blended_pipelines("fs_screen", wgpu::BlendFactor::OneMinusDst);
 
         Self {
-            wgpu_pipeline,
-            wgpu_pipeline_antialias,
+            normal,
+            multiply,
+            screen,
             texture_bind_group_layout,
-            index_buffer,
+            atlas: Mutex::new(ImageAtlas::default()),
         }
     }
 
-    pub fn create_image_texture(
+    fn create_image_texture(
         &self,
         device: &Device,
         queue: &Queue,
@@ -184,22 +287,66 @@ impl ImagePipeline {
         Arc::new(texture_bind_group)
     }
 
-    pub fn create_image(
+    /// Creates GPU buffers for the given images, keeping their order.
+    ///
+    /// Small images are packed into the shared image atlas, and consecutive images that end up in the same texture are
+    /// merged into a single batch. Larger images get a texture of their own, shared between all the usages of the
+    /// same image in the list.
+    pub fn create_images<'a>(
         &self,
         device: &Device,
-        texture: Arc<BindGroup>,
-        vertices: &[ImageVertex; 4],
-    ) -> WgpuImage {
-        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Image vertex buffer"),
-            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
-            contents: bytemuck::cast_slice(vertices),
-        });
+        queue: &Queue,
+        images: impl IntoIterator<Item = (usize, &'a Arc<DecodedImage>, &'a [ImageVertex; 4])>,
+    ) -> Vec<WgpuImage> {
+        let mut atlas = self.atlas.lock();
+        let mut own_textures: HashMap<*const DecodedImage, Arc<BindGroup>> = HashMap::new();
 
-        WgpuImage {
-            texture_bind_group: texture,
-            vertex_buffer,
+        let mut batches: Vec<ImageBatch> = vec![];
+        for (index, image, vertices) in images {
+            let mut vertices = *vertices;
+            let bind_group =
+                match atlas.get_or_insert(device, queue, &self.texture_bind_group_layout, image) {
+                    Some(region) => {
+                        for vertex in &mut vertices {
+                            vertex.tex_coords = region.map_tex_coords(vertex.tex_coords);
+                        }
+                        region.bind_group
+                    }
+                    None => own_textures
+                        .entry(Arc::as_ptr(image))
+                        .or_insert_with(|| self.create_image_texture(device, queue, image))
+                        .clone(),
+                };
+
+            let batch = match batches.last_mut() {
+                Some(batch) if Arc::ptr_eq(&batch.bind_group, &bind_group) => batch,
+                _ => {
+                    batches.push(ImageBatch {
+                        bind_group,
+                        vertices: vec![],
+                        indices: vec![],
+                        sources: vec![],
+                    });
+                    batches.last_mut().expect("batch was just added")
+                }
+            };
+
+            let first_index = batch.vertices.len() as u32;
+            batch
+                .indices
+                .extend(QUAD_INDICES.iter().map(|index| first_index + index));
+            batch.vertices.extend_from_slice(&vertices);
+            batch.sources.push(ImageSource {
+                index,
+                image: image.clone(),
+                tex_coords: vertices.map(|vertex| vertex.tex_coords),
+            });
         }
+
+        batches
+            .into_iter()
+            .map(|batch| batch.into_buffers(device))
+            .collect()
     }
 
     pub fn render<'a>(
@@ -209,17 +356,53 @@ impl ImagePipeline {
         render_options: RenderOptions,
         bundle_index: u32,
     ) {
+        let pipelines = match render_options.blend_mode {
+            BlendMode::Normal => &self.normal,
+            BlendMode::Multiply => &self.multiply,
+            BlendMode::Screen => &self.screen,
+        };
+
         if render_options.antialias {
-            render_pass.set_pipeline(&self.wgpu_pipeline_antialias);
+            render_pass.set_pipeline(&pipelines.wgpu_pipeline_antialias);
         } else {
-            render_pass.set_pipeline(&self.wgpu_pipeline);
+            render_pass.set_pipeline(&pipelines.wgpu_pipeline);
         }
 
         let bind_group: &BindGroup = &buffers.texture_bind_group;
         render_pass.set_bind_group(1, bind_group, &[]);
         render_pass.set_vertex_buffer(0, buffers.vertex_buffer.slice(..));
-        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-        render_pass.draw_indexed(0..INDICES.len() as u32, 0, bundle_index..(bundle_index + 1));
+        render_pass.set_index_buffer(buffers.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        render_pass.draw_indexed(0..buffers.index_count, 0, bundle_index..(bundle_index + 1));
+    }
+}
+
+struct ImageBatch {
+    bind_group: Arc<BindGroup>,
+    vertices: Vec<ImageVertex>,
+    indices: Vec<u32>,
+    sources: Vec<ImageSource>,
+}
+
+impl ImageBatch {
+    fn into_buffers(self, device: &Device) -> WgpuImage {
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Image vertex buffer"),
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            contents: bytemuck::cast_slice(&self.vertices),
+        });
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Image index buffer"),
+            usage: wgpu::BufferUsages::INDEX,
+            contents: bytemuck::cast_slice(&self.indices),
+        });
+
+        WgpuImage {
+            texture_bind_group: self.bind_group,
+            vertex_buffer,
+            index_buffer,
+            index_count: self.indices.len() as u32,
+            sources: self.sources,
+        }
     }
 }
 
@@ -253,6 +436,15 @@ impl ImageVertex {
                     shader_location: 3,
                     format: wgpu::VertexFormat::Float32x2,
                 },
+                wgpu::VertexAttribute {
+                    offset: (std::mem::size_of::<[f32; 2]>()
+                        + std::mem::size_of::<f32>()
+                        + std::mem::size_of::<[f32; 2]>()
+                        + std::mem::size_of::<[f32; 2]>())
+                        as wgpu::BufferAddress,
+                    shader_location: 4,
+                    format: wgpu::VertexFormat::Float32,
+                },
             ],
         }
     }
@@ -0,0 +1,375 @@
+//! Packing of small images into shared textures.
+//!
+//! Every texture requires a separate bind group and a separate draw call, which becomes the bottleneck when a map
+//! contains hundreds of small icons. Images that are small enough are instead packed into large atlas textures shared
+//! between all bundles of the renderer, so images of one bundle can be drawn with a few draw calls.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Weak};
+
+use wgpu::{BindGroup, BindGroupLayout, Device, Queue, Sampler, Texture, TextureFormat};
+
+use crate::decoded_image::{DecodedImage, DecodedImageType};
+
+/// Size of a single atlas texture in pixels. This is the minimum texture size guaranteed to be supported by WebGL 2.
+const ATLAS_SIZE: u32 = 2048;
+/// Images which width or height is larger than this value (e.g. raster tiles) are not packed into the atlas.
+const MAX_ATLAS_IMAGE_SIZE: u32 = 128;
+/// Empty space left between the images in the atlas, so that linear filtering does not mix the neighbouring images.
+const PADDING: u32 = 1;
+
+/// Place of an image in an atlas texture.
+pub struct AtlasRegion {
+    /// Bind group of the atlas texture.
+    pub bind_group: Arc<BindGroup>,
+    /// Texture coordinates of the top left corner of the image.
+    pub uv_min: [f32; 2],
+    /// Texture coordinates of the bottom right corner of the image.
+    pub uv_max: [f32; 2],
+}
+
+impl AtlasRegion {
+    /// Converts texture coordinates of the image into the texture coordinates of the atlas.
+    pub fn map_tex_coords(&self, tex_coords: [f32; 2]) -> [f32; 2] {
+        [
+            self.uv_min[0] + tex_coords[0] * (self.uv_max[0] - self.uv_min[0]),
+            self.uv_min[1] + tex_coords[1] * (self.uv_max[1] - self.uv_min[1]),
+        ]
+    }
+}
+
+/// Set of atlas textures shared by all the images rendered by a renderer.
+///
+/// Images are identified by their `Arc` pointer, so the same image used by many features or layers is stored only
+/// once. The atlas does not keep the images alive: space of an atlas page is reclaimed when all the images stored in
+/// it are dropped, so the users of the regions must hold the images for as long as they draw them.
+#[derive(Default)]
+pub struct ImageAtlas {
+    pages: Vec<AtlasPage>,
+    regions: HashMap<usize, StoredImage>,
+    sampler: Option<Sampler>,
+}
+
+struct StoredImage {
+    image: Weak<DecodedImage>,
+    page: usize,
+    position: (u32, u32),
+}
+
+struct AtlasPage {
+    texture: Texture,
+    bind_group: Arc<BindGroup>,
+    allocator: ShelfAllocator,
+    images: Vec<Weak<DecodedImage>>,
+}
+
+impl ImageAtlas {
+    /// Returns true if the image is small enough to be stored in the atlas.
+    pub fn fits(image: &DecodedImage) -> bool {
+        image.width() > 0
+            && image.height() > 0
+            && image.width() <= MAX_ATLAS_IMAGE_SIZE
+            && image.height() <= MAX_ATLAS_IMAGE_SIZE
+    }
+
+    /// Returns the region of the atlas with the given image, uploading the image to the atlas if it is not there yet.
+    ///
+    /// Returns `None` if the image is too large for the atlas.
+    pub fn get_or_insert(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        layout: &BindGroupLayout,
+        image: &Arc<DecodedImage>,
+    ) -> Option<AtlasRegion> {
+        if !Self::fits(image) {
+            return None;
+        }
+
+        let key = Arc::as_ptr(image) as usize;
+        if let Some(stored) = self.regions.get(&key) {
+            // Pointer can be reused by another image after the stored one is dropped
+            if stored
+                .image
+                .upgrade()
+                .is_some_and(|stored| Arc::ptr_eq(&stored, image))
+            {
+                return Some(self.region(stored.page, stored.position, image));
+            }
+        }
+
+        let (page, position) = self.allocate(device, queue, layout, image.width(), image.height());
+        Self::write_image(queue, &self.pages[page].texture, position, image);
+        self.pages[page].images.push(Arc::downgrade(image));
+        self.regions.insert(
+            key,
+            StoredImage {
+                image: Arc::downgrade(image),
+                page,
+                position,
+            },
+        );
+
+        Some(self.region(page, position, image))
+    }
+
+    fn region(&self, page: usize, position: (u32, u32), image: &DecodedImage) -> AtlasRegion {
+        let size = ATLAS_SIZE as f32;
+        AtlasRegion {
+            bind_group: self.pages[page].bind_group.clone(),
+            uv_min: [position.0 as f32 / size, position.1 as f32 / size],
+            uv_max: [
+                (position.0 + image.width()) as f32 / size,
+                (position.1 + image.height()) as f32 / size,
+            ],
+        }
+    }
+
+    fn allocate(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        layout: &BindGroupLayout,
+        width: u32,
+        height: u32,
+    ) -> (usize, (u32, u32)) {
+        for (index, page) in self.pages.iter_mut().enumerate() {
+            if let Some(position) = page.allocator.allocate(width, height) {
+                return (index, position);
+            }
+        }
+
+        // All pages are full, so reuse the pages which images are not used anymore before creating a new one
+        for index in 0..self.pages.len() {
+            let page = &mut self.pages[index];
+            if page.images.iter().all(|image| image.strong_count() == 0) {
+                page.images.clear();
+                page.allocator = ShelfAllocator::new(ATLAS_SIZE);
+                Self::clear_texture(device, queue, &page.texture);
+                self.regions.retain(|_, stored| stored.page != index);
+
+                if let Some(position) = page.allocator.allocate(width, height) {
+                    return (index, position);
+                }
+            }
+        }
+
+        let mut page = self.create_page(device, layout);
+        let position = page
+            .allocator
+            .allocate(width, height)
+            .expect("image must fit into an empty atlas page");
+        self.pages.push(page);
+
+        (self.pages.len() - 1, position)
+    }
+
+    fn create_page(&mut self, device: &Device, layout: &BindGroupLayout) -> AtlasPage {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            size: wgpu::Extent3d {
+                width: ATLAS_SIZE,
+                height: ATLAS_SIZE,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_DST
+                | wgpu::TextureUsages::RENDER_ATTACHMENT,
+            label: Some("Image atlas"),
+            view_formats: &[],
+        });
+
+        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = self.sampler.get_or_insert_with(|| {
+            device.create_sampler(&wgpu::SamplerDescriptor {
+                address_mode_u: wgpu::AddressMode::ClampToEdge,
+                address_mode_v: wgpu::AddressMode::ClampToEdge,
+                address_mode_w: wgpu::AddressMode::ClampToEdge,
+                mag_filter: wgpu::FilterMode::Linear,
+                min_filter: wgpu::FilterMode::Linear,
+                mipmap_filter: wgpu::FilterMode::Nearest,
+                ..Default::default()
+            })
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&texture_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+            ],
+            label: Some("atlas_bind_group"),
+        });
+
+        AtlasPage {
+            texture,
+            bind_group: Arc::new(bind_group),
+            allocator: ShelfAllocator::new(ATLAS_SIZE),
+            images: vec![],
+        }
+    }
+
+    /// Makes the texture fully transparent, so that the padding around new images does not contain old images.
+    fn clear_texture(device: &Device, queue: &Queue, texture: &Texture) {
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Image atlas clear encoder"),
+        });
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Image atlas clear pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        queue.submit(std::iter::once(encoder.finish()));
+    }
+
+    fn write_image(queue: &Queue, texture: &Texture, position: (u32, u32), image: &DecodedImage) {
+        let size = wgpu::Extent3d {
+            width: image.width(),
+            height: image.height(),
+            depth_or_array_layers: 1,
+        };
+        let origin = wgpu::Origin3d {
+            x: position.0,
+            y: position.1,
+            z: 0,
+        };
+
+        match &image.0 {
+            DecodedImageType::Bitmap { bytes, .. } => queue.write_texture(
+                wgpu::ImageCopyTexture {
+                    texture,
+                    mip_level: 0,
+                    origin,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                bytes,
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(4 * image.width()),
+                    rows_per_image: Some(image.height()),
+                },
+                size,
+            ),
+            #[cfg(target_arch = "wasm32")]
+            DecodedImageType::JsImageBitmap(bitmap) => {
+                use wgpu::{ExternalImageSource, ImageCopyExternalImage, Origin2d};
+
+                let source = ImageCopyExternalImage {
+                    source: ExternalImageSource::ImageBitmap(bitmap.clone()),
+                    origin: Origin2d::ZERO,
+                    flip_y: false,
+                };
+                queue.copy_external_image_to_texture(
+                    &source,
+                    wgpu::ImageCopyTexture {
+                        texture,
+                        mip_level: 0,
+                        origin,
+                        aspect: wgpu::TextureAspect::All,
+                    }
+                    .to_tagged(wgpu::PredefinedColorSpace::Srgb, false),
+                    size,
+                );
+            }
+        }
+    }
+}
+
+/// Allocates rectangles in a square area, placing them in rows (shelves) of similar height.
+struct ShelfAllocator {
+    size: u32,
+    shelves: Vec<Shelf>,
+}
+
+struct Shelf {
+    y: u32,
+    height: u32,
+    next_x: u32,
+}
+
+impl ShelfAllocator {
+    fn new(size: u32) -> Self {
+        Self {
+            size,
+            shelves: vec![],
+        }
+    }
+
+    /// Returns position of the top left corner of the allocated rectangle, or `None` if there is no space left.
+    fn allocate(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+        let width = width + PADDING;
+        let height = height + PADDING;
+        if width > self.size || height > self.size {
+            return None;
+        }
+
+        // Use the lowest shelf that can fit the rectangle to waste as little space as possible
+        let shelf = self
+            .shelves
+            .iter_mut()
+            .filter(|shelf| shelf.height >= height && shelf.next_x + width <= self.size)
+            .min_by_key(|shelf| shelf.height);
+        if let Some(shelf) = shelf {
+            let position = (shelf.next_x, shelf.y);
+            shelf.next_x += width;
+            return Some(position);
+        }
+
+        let y = self
+            .shelves
+            .last()
+            .map(|shelf| shelf.y + shelf.height)
+            .unwrap_or(0);
+        if y + height > self.size {
+            return None;
+        }
+
+        self.shelves.push(Shelf {
+            y,
+            height,
+            next_x: width,
+        });
+
+        Some((0, y))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shelf_allocation() {
+        let mut allocator = ShelfAllocator::new(64);
+
+        assert_eq!(allocator.allocate(31, 15), Some((0, 0)));
+        assert_eq!(allocator.allocate(31, 15), Some((32, 0)));
+        // First shelf is full
+        assert_eq!(allocator.allocate(15, 15), Some((0, 16)));
+        // Fits into the shelf of the same height
+        assert_eq!(allocator.allocate(7, 7), Some((16, 16)));
+        // Too high for existing shelves
+        assert_eq!(allocator.allocate(31, 31), Some((0, 32)));
+        assert_eq!(allocator.allocate(63, 1), None);
+        assert_eq!(allocator.allocate(100, 1), None);
+    }
+}
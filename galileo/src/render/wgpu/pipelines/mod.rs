@@ -8,6 +8,7 @@ use wgpu::{
 
 use crate::render::wgpu::pipelines::clip::ClipPipeline;
 use crate::render::wgpu::pipelines::dot::DotPipeline;
+use crate::render::wgpu::pipelines::horizon::HorizonPipeline;
 use crate::render::wgpu::pipelines::image::ImagePipeline;
 use crate::render::wgpu::pipelines::map_ref::MapRefPipeline;
 use crate::render::wgpu::pipelines::screen_ref::ScreenRefPipeline;
@@ -16,8 +17,11 @@ use crate::render::RenderOptions;
 
 mod clip;
 mod dot;
+pub mod horizon;
 pub mod image;
+mod image_atlas;
 mod map_ref;
+pub mod post_processing;
 mod screen_ref;
 
 pub struct Pipelines {
@@ -29,6 +33,7 @@ pub struct Pipelines {
     map_ref: MapRefPipeline,
     clip: ClipPipeline,
     dot: DotPipeline,
+    horizon: HorizonPipeline,
 }
 
 impl Pipelines {
@@ -72,6 +77,7 @@ impl Pipelines {
             screen_ref: ScreenRefPipeline::create(device, format, &map_view_bind_group_layout),
             clip: ClipPipeline::create(device, format, &map_view_bind_group_layout),
             dot: DotPipeline::create(device, format, &map_view_bind_group_layout),
+            horizon: HorizonPipeline::create(device, format),
         }
     }
 
@@ -81,6 +87,18 @@ impl Pipelines {
         bundle: &'a WgpuPackedBundle,
         render_options: RenderOptions,
         bundle_index: u32,
+    ) {
+        self.render_map_ref(render_pass, bundle, render_options, bundle_index);
+        self.render_screen_ref(render_pass, bundle, render_options, bundle_index);
+    }
+
+    /// Renders the primitives of the bundle drawn in map coordinates: lines, polygons and images.
+    pub fn render_map_ref<'a>(
+        &'a self,
+        render_pass: &mut RenderPass<'a>,
+        bundle: &'a WgpuPackedBundle,
+        render_options: RenderOptions,
+        bundle_index: u32,
     ) {
         self.set_bindings(render_pass);
 
@@ -105,6 +123,17 @@ impl Pipelines {
         if let Some(clip) = &bundle.clip_area_buffers {
             self.clip.unclip(clip, render_pass, render_options);
         }
+    }
+
+    /// Renders the screen referenced primitives of the bundle: markers, labels and dots.
+    pub fn render_screen_ref<'a>(
+        &'a self,
+        render_pass: &mut RenderPass<'a>,
+        bundle: &'a WgpuPackedBundle,
+        render_options: RenderOptions,
+        bundle_index: u32,
+    ) {
+        self.set_bindings(render_pass);
 
         if let Some(screen_ref_buffers) = &bundle.screen_ref_buffers {
             self.screen_ref.render(
@@ -129,6 +158,10 @@ impl Pipelines {
         &self.image
     }
 
+    pub fn horizon_pipeline(&self) -> &HorizonPipeline {
+        &self.horizon
+    }
+
     fn set_bindings<'a>(&'a self, render_pass: &mut RenderPass<'a>) {
         render_pass.set_bind_group(0, &self.map_view_binding, &[]);
     }
@@ -137,6 +170,9 @@ impl Pipelines {
 fn default_targets(format: TextureFormat) -> [Option<wgpu::ColorTargetState>; 1] {
     [Some(wgpu::ColorTargetState {
         format,
+        // Color is blended with straight alpha, while alpha channel uses "over" operation. Given that the target is
+        // cleared with a premultiplied color, this keeps the image in premultiplied alpha, which allows to composite
+        // it over other content when the background is transparent.
         blend: Some(wgpu::BlendState::ALPHA_BLENDING),
         write_mask: wgpu::ColorWrites::ALL,
     })]
@@ -0,0 +1,272 @@
+use std::borrow::Cow;
+
+use galileo_types::cartesian::Size;
+use wgpu::util::DeviceExt;
+use wgpu::{
+    BindGroup, BindGroupLayout, Buffer, CommandEncoder, Device, RenderPipeline, Sampler, StoreOp,
+    Texture, TextureFormat, TextureView,
+};
+
+use crate::render::post_processing::PostEffect;
+
+const COMMON_SHADER: &str = include_str!("./shaders/post_common.wgsl");
+const EFFECTS_SHADER: &str = include_str!("./shaders/post_effects.wgsl");
+
+/// Post processing stage of the renderer.
+///
+/// Layers are rendered into an intermediate texture, which is then passed through the effects. Effects ping-pong
+/// between two intermediate textures, and the last effect writes into the render target.
+pub struct PostProcessing {
+    textures: [(Texture, TextureView); 2],
+    passes: Vec<EffectPass>,
+}
+
+struct EffectPass {
+    pipeline: RenderPipeline,
+    // Bind groups reading from the first and the second intermediate textures
+    bind_groups: [BindGroup; 2],
+    _parameters: Buffer,
+}
+
+impl PostProcessing {
+    pub fn create(
+        device: &Device,
+        format: TextureFormat,
+        size: Size<u32>,
+        effects: &[PostEffect],
+    ) -> Self {
+        let textures = [
+            Self::create_texture(device, format, size),
+            Self::create_texture(device, format, size),
+        ];
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+            label: Some("Post processing bind group layout"),
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let built_in_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Post processing shader"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Owned(format!(
+                "{COMMON_SHADER}\n{EFFECTS_SHADER}"
+            ))),
+        });
+
+        let passes = effects
+            .iter()
+            .map(|effect| {
+                let custom_shader;
+                let (module, entry_point) = match effect {
+                    PostEffect::ColorAdjustment { .. } => (&built_in_shader, "fs_color_adjustment"),
+                    PostEffect::Vignette { .. } => (&built_in_shader, "fs_vignette"),
+                    PostEffect::NightMode { .. } => (&built_in_shader, "fs_night_mode"),
+                    PostEffect::Custom(custom) => {
+                        custom_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                            label: Some("Custom post processing shader"),
+                            source: wgpu::ShaderSource::Wgsl(Cow::Owned(format!(
+                                "{COMMON_SHADER}\n{}",
+                                custom.source()
+                            ))),
+                        });
+                        (&custom_shader, "fs_main")
+                    }
+                };
+
+                let pipeline = Self::create_pipeline(device, format, &layout, module, entry_point);
+                let parameters = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Post processing parameters buffer"),
+                    contents: bytemuck::cast_slice(&effect.parameters()),
+                    usage: wgpu::BufferUsages::UNIFORM,
+                });
+                let bind_groups = [
+                    Self::create_bind_group(
+                        device,
+                        &bind_group_layout,
+                        &textures[0].1,
+                        &sampler,
+                        &parameters,
+                    ),
+                    Self::create_bind_group(
+                        device,
+                        &bind_group_layout,
+                        &textures[1].1,
+                        &sampler,
+                        &parameters,
+                    ),
+                ];
+
+                EffectPass {
+                    pipeline,
+                    bind_groups,
+                    _parameters: parameters,
+                }
+            })
+            .collect();
+
+        Self { textures, passes }
+    }
+
+    /// View of the texture the layers must be rendered to.
+    pub fn source_view(&self) -> &TextureView {
+        &self.textures[0].1
+    }
+
+    /// Applies all the effects to the image in the source texture and writes the result into the `target`.
+    pub fn apply(&self, encoder: &mut CommandEncoder, target: &TextureView) {
+        for (index, pass) in self.passes.iter().enumerate() {
+            let is_last = index == self.passes.len() - 1;
+            let output = if is_last {
+                target
+            } else {
+                &self.textures[(index + 1) % 2].1
+            };
+
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Post processing pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: output,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            render_pass.set_pipeline(&pass.pipeline);
+            render_pass.set_bind_group(0, &pass.bind_groups[index % 2], &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+    }
+
+    fn create_texture(
+        device: &Device,
+        format: TextureFormat,
+        size: Size<u32>,
+    ) -> (Texture, TextureView) {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Post processing texture"),
+            size: wgpu::Extent3d {
+                width: size.width(),
+                height: size.height(),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&Default::default());
+
+        (texture, view)
+    }
+
+    fn create_bind_group(
+        device: &Device,
+        layout: &BindGroupLayout,
+        texture_view: &TextureView,
+        sampler: &Sampler,
+        parameters: &Buffer,
+    ) -> BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(texture_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: parameters.as_entire_binding(),
+                },
+            ],
+            label: Some("Post processing bind group"),
+        })
+    }
+
+    fn create_pipeline(
+        device: &Device,
+        format: TextureFormat,
+        layout: &wgpu::PipelineLayout,
+        module: &wgpu::ShaderModule,
+        entry_point: &str,
+    ) -> RenderPipeline {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Post processing pipeline"),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module,
+                entry_point: Some("vs_main"),
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module,
+                entry_point: Some(entry_point),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: Default::default(),
+        })
+    }
+}
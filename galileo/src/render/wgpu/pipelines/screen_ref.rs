@@ -102,6 +102,38 @@ impl ScreenRefVertex {
                     shader_location: 2,
                     format: wgpu::VertexFormat::Uint8x4,
                 },
+                wgpu::VertexAttribute {
+                    offset: (size_of::<[f32; 3]>() + size_of::<[f32; 2]>() + size_of::<[u8; 4]>())
+                        as wgpu::BufferAddress,
+                    shader_location: 3,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                wgpu::VertexAttribute {
+                    offset: (size_of::<[f32; 3]>()
+                        + size_of::<[f32; 2]>()
+                        + size_of::<[u8; 4]>()
+                        + size_of::<[f32; 2]>()) as wgpu::BufferAddress,
+                    shader_location: 4,
+                    format: wgpu::VertexFormat::Float32,
+                },
+                wgpu::VertexAttribute {
+                    offset: (size_of::<[f32; 3]>()
+                        + size_of::<[f32; 2]>()
+                        + size_of::<[u8; 4]>()
+                        + size_of::<[f32; 2]>()
+                        + size_of::<f32>()) as wgpu::BufferAddress,
+                    shader_location: 5,
+                    format: wgpu::VertexFormat::Float32,
+                },
+                wgpu::VertexAttribute {
+                    offset: (size_of::<[f32; 3]>()
+                        + size_of::<[f32; 2]>()
+                        + size_of::<[u8; 4]>()
+                        + size_of::<[f32; 2]>()
+                        + size_of::<f32>() * 2) as wgpu::BufferAddress,
+                    shader_location: 6,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
             ],
         }
     }
@@ -0,0 +1,41 @@
+// Sky and haze at the horizon of a tilted map. Colors are premultiplied.
+
+struct HorizonUniform {
+    sky_color: vec4<f32>,
+    haze_color: vec4<f32>,
+    // x - screen position of the horizon line in pixels, y - height of the haze gradient in pixels
+    params: vec4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> horizon: HorizonUniform;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+};
+
+// Vertex shader draws a single triangle covering the whole screen
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let x = f32((index << 1u) & 2u);
+    let y = f32(index & 2u);
+
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(x * 2.0 - 1.0, 1.0 - y * 2.0, 0.0, 1.0);
+
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let haze_height = max(horizon.params.y, 1.0);
+    // Fragment position is in pixels with y going from top to bottom
+    let distance = in.clip_position.y - horizon.params.x;
+
+    if (distance < 0.0) {
+        return mix(horizon.haze_color, horizon.sky_color, smoothstep(0.0, haze_height, -distance));
+    }
+
+    return horizon.haze_color * (1.0 - smoothstep(0.0, haze_height, distance));
+}
@@ -5,6 +5,7 @@ struct ViewUniform {
     view_rotation: mat4x4<f32>,
     inv_screen_size: vec2<f32>,
     resolution: f32,
+    rotation_z: f32,
 }
 
 @group(0) @binding(0)
@@ -15,6 +16,7 @@ struct VertexInput {
     @location(1) opacity: f32,
     @location(2) tex_coord: vec2<f32>,
     @location(3) offset: vec2<f32>,
+    @location(4) map_aligned: f32,
     @location(10) bundle_opacity: f32,
 }
 
@@ -32,7 +34,14 @@ fn vs_main(
     out.tex_coord = model.tex_coord;
 
     var point_position = transform.view_proj * vec4<f32>(model.position, 0.0, 1.0);
-    var vertex_delta = vec4<f32>(model.offset * transform.inv_screen_size * point_position[3] * 2.0, 0.0, 0.0);
+    var offset = model.offset;
+    if (model.map_aligned > 0.5) {
+        let s = sin(transform.rotation_z);
+        let c = cos(transform.rotation_z);
+        offset = vec2<f32>(offset.x * c - offset.y * s, offset.x * s + offset.y * c);
+    }
+
+    var vertex_delta = vec4<f32>(offset * transform.inv_screen_size * point_position[3] * 2.0, 0.0, 0.0);
 
     out.clip_position = point_position + vertex_delta;
     out.opacity = model.opacity * model.bundle_opacity;
@@ -59,3 +68,27 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
 
     return color;
 }
+
+@fragment
+fn fs_multiply(in: VertexOutput) -> @location(0) vec4<f32> {
+    var color = textureSample(t_diffuse, s_diffuse, in.tex_coord);
+    let alpha = color[3] * in.opacity;
+
+    if alpha == 0.0 {
+        discard;
+    }
+
+    return vec4<f32>(color.rgb * alpha, alpha);
+}
+
+@fragment
+fn fs_screen(in: VertexOutput) -> @location(0) vec4<f32> {
+    var color = textureSample(t_diffuse, s_diffuse, in.tex_coord);
+    let alpha = color[3] * in.opacity;
+
+    if alpha == 0.0 {
+        discard;
+    }
+
+    return vec4<f32>(color.rgb * alpha, 0.0);
+}
@@ -15,6 +15,8 @@ struct VertexInput {
     @location(1) color: vec4<f32>,
     @location(2) norm: vec2<f32>,
     @location(3) norm_limit: f32,
+    @location(4) width_scale: vec4<f32>,
+    @location(5) crisp_width: f32,
 }
 
 struct VertexOutput {
@@ -33,20 +35,50 @@ fn vs_main(
     out.color = color;
 
     var vertex_position = transform.view_proj * vec4<f32>(model.position, 1.0);
-    var norm_length = sqrt(model.norm[0] * model.norm[0] + model.norm[1] * model.norm[1]) * transform.resolution;
+    let width_scale = resolution_scale(model.width_scale, transform.resolution);
+    if (model.crisp_width > 0.0 && vertex_position[3] > 0.0) {
+        vertex_position = snap_to_pixel(vertex_position, model.crisp_width * width_scale);
+    }
+
+    let model_norm = model.norm * width_scale;
+    var norm_length = sqrt(model_norm[0] * model_norm[0] + model_norm[1] * model_norm[1]) * transform.resolution;
 
     var norm_limit = 1.0;
     if (norm_length > model.norm_limit) {
         norm_limit = model.norm_limit / norm_length;
     }
 
-    var norm_scale = vec2<f32>(model.norm[0] * transform.inv_screen_size[0], model.norm[1] * transform.inv_screen_size[1]) * norm_limit;
+    var norm_scale = vec2<f32>(model_norm[0] * transform.inv_screen_size[0], model_norm[1] * transform.inv_screen_size[1]) * norm_limit;
     var norm = vec4<f32>(norm_scale * vertex_position[3] * 2.0, 0.0, 0.0) * transform.view_rotation;
     out.clip_position = vertex_position + norm;
 
     return out;
 }
 
+// Moves the position of the line center so that the line of the given width (in pixels) covers whole pixels: centers
+// of the lines of odd width are placed at the pixel centers, and of the lines of even width at the pixel edges.
+fn snap_to_pixel(position: vec4<f32>, width: f32) -> vec4<f32> {
+    let screen_size = 1.0 / transform.inv_screen_size;
+    let pixel = (position.xy / position.w * 0.5 + 0.5) * screen_size;
+    let shift = 0.5 * (round(max(width, 1.0)) % 2.0);
+    let snapped = floor(pixel - shift + 0.5) + shift;
+    let ndc = (snapped / screen_size - 0.5) * 2.0;
+
+    return vec4<f32>(ndc * position.w, position.z, position.w);
+}
+
+// Evaluates the resolution dependent scale. `stops` contains base 2 logarithms of the first stop resolution and
+// scale, and of the second stop resolution and scale. Zero stops give the constant scale of 1.0.
+fn resolution_scale(stops: vec4<f32>, resolution: f32) -> f32 {
+    let range = stops[2] - stops[0];
+    var t = 0.0;
+    if (range != 0.0) {
+        t = clamp((log2(resolution) - stops[0]) / range, 0.0, 1.0);
+    }
+
+    return exp2(mix(stops[1], stops[3], t));
+}
+
 
 // Fragment shader
 
@@ -0,0 +1,34 @@
+// Built-in post processing effects. `post_common.wgsl` must be prepended to this file.
+
+// params.a.x - exposure in stops, params.a.y - gamma
+@fragment
+fn fs_color_adjustment(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(t_source, s_source, in.tex_coord);
+    let exposed = color.rgb * exp2(params.a.x);
+    let corrected = pow(max(exposed, vec3<f32>(0.0)), vec3<f32>(1.0 / params.a.y));
+
+    return vec4<f32>(clamp(corrected, vec3<f32>(0.0), vec3<f32>(1.0)), color.a);
+}
+
+// params.a.x - intensity, params.a.y - radius at which darkening starts
+@fragment
+fn fs_vignette(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(t_source, s_source, in.tex_coord);
+    let distance = length(in.tex_coord - vec2<f32>(0.5, 0.5)) * 1.41421356;
+    let factor = 1.0 - params.a.x * smoothstep(params.a.y, 1.0, distance);
+
+    return vec4<f32>(color.rgb * factor, color.a);
+}
+
+// params.a.x - intensity
+//
+// Inverts the luminance of the color, keeping the differences between the channels (and so the hue)
+// unchanged.
+@fragment
+fn fs_night_mode(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(t_source, s_source, in.tex_coord);
+    let luminance = dot(color.rgb, vec3<f32>(0.2126, 0.7152, 0.0722));
+    let inverted = clamp(color.rgb + vec3<f32>(1.0 - 2.0 * luminance), vec3<f32>(0.0), vec3<f32>(1.0));
+
+    return vec4<f32>(mix(color.rgb, inverted, params.a.x), color.a);
+}
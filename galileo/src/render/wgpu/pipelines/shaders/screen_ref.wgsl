@@ -14,6 +14,10 @@ struct VertexInput {
     @location(0) position: vec3<f32>,
     @location(1) normal: vec2<f32>,
     @location(2) color: vec4<u32>,
+    @location(3) offset: vec2<f32>,
+    @location(4) rotation: f32,
+    @location(5) scale: f32,
+    @location(6) resolution_scale: vec4<f32>,
 }
 
 struct VertexOutput {
@@ -32,14 +36,31 @@ fn vs_main(
     color[3] = color[3] * bundle_opacity;
     out.color = color;
 
+    let s = sin(model.rotation);
+    let c = cos(model.rotation);
+    let normal = model.normal * model.scale * resolution_scale(model.resolution_scale, transform.resolution);
+    let screen_offset = vec2<f32>(normal.x * c - normal.y * s, normal.x * s + normal.y * c) + model.offset;
+
     var point_position = transform.view_proj * vec4<f32>(model.position, 1.0);
-    var vertex_delta = vec4<f32>(model.normal * transform.inv_screen_size * point_position[3] * 2.0, 0.0, 0.0);
+    var vertex_delta = vec4<f32>(screen_offset * transform.inv_screen_size * point_position[3] * 2.0, 0.0, 0.0);
 
     out.clip_position = point_position + vertex_delta;
 
     return out;
 }
 
+// Evaluates the resolution dependent scale. `stops` contains base 2 logarithms of the first stop resolution and
+// scale, and of the second stop resolution and scale. Zero stops give the constant scale of 1.0.
+fn resolution_scale(stops: vec4<f32>, resolution: f32) -> f32 {
+    let range = stops[2] - stops[0];
+    var t = 0.0;
+    if (range != 0.0) {
+        t = clamp((log2(resolution) - stops[0]) / range, 0.0, 1.0);
+    }
+
+    return exp2(mix(stops[1], stops[3], t));
+}
+
 
 // Fragment shader
 
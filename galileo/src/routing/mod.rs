@@ -0,0 +1,202 @@
+//! Routing services that snap routes between geographic points to the road network, and helpers to show the routes
+//! on the map.
+//!
+//! [`Router`] is the common interface of the services. Implementations for [OSRM](osrm::OsrmRouter) and
+//! [Valhalla](valhalla::ValhallaRouter) HTTP APIs are available with the `routing` feature.
+
+use std::time::Duration;
+
+use galileo_types::geo::impls::GeoPoint2d;
+use galileo_types::geo::Crs;
+use galileo_types::geometry::Geom;
+use galileo_types::geometry_type::GeoSpace2d;
+use galileo_types::impls::Contour;
+use maybe_sync::{MaybeSend, MaybeSync};
+
+use crate::error::GalileoError;
+use crate::layer::feature_layer::symbol::{
+    ArbitraryGeometrySymbol, CirclePointSymbol, SimpleContourSymbol, SimplePolygonSymbol,
+};
+use crate::layer::feature_layer::Feature;
+use crate::layer::FeatureLayer;
+use crate::Color;
+
+#[cfg(feature = "routing")]
+pub mod osrm;
+#[cfg(feature = "routing")]
+pub mod valhalla;
+
+/// Request for a route through a list of points.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteRequest {
+    points: Vec<GeoPoint2d>,
+}
+
+impl RouteRequest {
+    /// Creates a request for a route from the `origin` to the `destination`.
+    pub fn new(origin: GeoPoint2d, destination: GeoPoint2d) -> Self {
+        Self {
+            points: vec![origin, destination],
+        }
+    }
+
+    /// Adds an intermediate point the route must go through. Waypoints are visited in the order they are added.
+    pub fn with_waypoint(mut self, waypoint: GeoPoint2d) -> Self {
+        let destination_index = self.points.len() - 1;
+        self.points.insert(destination_index, waypoint);
+        self
+    }
+
+    /// All points of the route in the order they are visited: the origin, the waypoints and the destination.
+    pub fn points(&self) -> &[GeoPoint2d] {
+        &self.points
+    }
+}
+
+/// Route returned by a [`Router`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Route {
+    /// Line of the whole route.
+    pub geometry: Vec<GeoPoint2d>,
+    /// Length of the route in meters.
+    pub distance: f64,
+    /// Expected travel time.
+    pub duration: Duration,
+    /// Parts of the route between consecutive points of the request.
+    pub legs: Vec<RouteLeg>,
+}
+
+/// Part of a [`Route`] between two consecutive points of the request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteLeg {
+    /// Length of the leg in meters.
+    pub distance: f64,
+    /// Expected travel time.
+    pub duration: Duration,
+    /// Maneuvers the driver must make along the leg.
+    pub maneuvers: Vec<Maneuver>,
+}
+
+/// Maneuver along a route, e.g. a turn or a roundabout exit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Maneuver {
+    /// Position of the maneuver.
+    pub position: GeoPoint2d,
+    /// Type of the maneuver as returned by the routing service, e.g. `turn left`.
+    pub kind: String,
+    /// Human-readable instruction, if provided by the service.
+    pub instruction: Option<String>,
+}
+
+/// Service that builds routes along the road network.
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+pub trait Router: MaybeSend + MaybeSync {
+    /// Finds a route through the points of the request.
+    async fn route(&self, request: &RouteRequest) -> Result<Route, GalileoError>;
+}
+
+/// Feature of a [`route layer`](route_layer): either the line of the route or one of its maneuvers.
+#[derive(Debug, Clone)]
+pub struct RouteFeature {
+    geometry: Geom<GeoPoint2d>,
+    maneuver: Option<Maneuver>,
+}
+
+impl RouteFeature {
+    /// Maneuver represented by the feature, or `None` if the feature is the line of the route.
+    pub fn maneuver(&self) -> Option<&Maneuver> {
+        self.maneuver.as_ref()
+    }
+}
+
+impl Feature for RouteFeature {
+    type Geom = Geom<GeoPoint2d>;
+
+    fn geometry(&self) -> &Self::Geom {
+        &self.geometry
+    }
+}
+
+/// Converts the route into features: the line of the route followed by a point feature for every maneuver.
+pub fn route_features(route: &Route) -> Vec<RouteFeature> {
+    let line = RouteFeature {
+        geometry: Geom::Contour(Contour::open(route.geometry.clone())),
+        maneuver: None,
+    };
+
+    let maneuvers = route
+        .legs
+        .iter()
+        .flat_map(|leg| &leg.maneuvers)
+        .map(|maneuver| RouteFeature {
+            geometry: Geom::Point(maneuver.position),
+            maneuver: Some(maneuver.clone()),
+        });
+
+    std::iter::once(line).chain(maneuvers).collect()
+}
+
+/// Creates a feature layer showing the route as a line of the given color, with the maneuvers as white circles.
+///
+/// Features of the layer can be used to find the clicked maneuver with [`RouteFeature::maneuver`].
+pub fn route_layer(
+    route: &Route,
+    color: Color,
+) -> FeatureLayer<GeoPoint2d, RouteFeature, ArbitraryGeometrySymbol, GeoSpace2d> {
+    let symbol = ArbitraryGeometrySymbol::new(
+        CirclePointSymbol::new(Color::WHITE, 8.0),
+        SimpleContourSymbol::new(color, 5.0),
+        SimplePolygonSymbol::new(color),
+    );
+
+    FeatureLayer::new(route_features(route), symbol, Crs::WGS84)
+}
+
+#[cfg(test)]
+mod tests {
+    use galileo_types::latlon;
+
+    use super::*;
+
+    #[test]
+    fn route_request_points() {
+        let request = RouteRequest::new(latlon!(0.0, 0.0), latlon!(1.0, 1.0))
+            .with_waypoint(latlon!(0.5, 0.5))
+            .with_waypoint(latlon!(0.7, 0.7));
+
+        assert_eq!(
+            request.points(),
+            &[
+                latlon!(0.0, 0.0),
+                latlon!(0.5, 0.5),
+                latlon!(0.7, 0.7),
+                latlon!(1.0, 1.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn features_of_route() {
+        let maneuver = Maneuver {
+            position: latlon!(0.5, 0.5),
+            kind: "turn left".into(),
+            instruction: None,
+        };
+        let route = Route {
+            geometry: vec![latlon!(0.0, 0.0), latlon!(0.5, 0.5), latlon!(1.0, 0.5)],
+            distance: 100.0,
+            duration: Duration::from_secs(10),
+            legs: vec![RouteLeg {
+                distance: 100.0,
+                duration: Duration::from_secs(10),
+                maneuvers: vec![maneuver.clone()],
+            }],
+        };
+
+        let features = route_features(&route);
+        assert_eq!(features.len(), 2);
+        assert!(matches!(features[0].geometry(), Geom::Contour(_)));
+        assert_eq!(features[1].maneuver(), Some(&maneuver));
+    }
+}
@@ -0,0 +1,213 @@
+//! Router using the HTTP API of [OSRM](https://project-osrm.org/).
+
+use std::time::Duration;
+
+use galileo_types::geo::impls::GeoPoint2d;
+use galileo_types::geo::{GeoPoint, NewGeoPoint};
+use serde_json::Value;
+
+use crate::error::GalileoError;
+use crate::platform::{PlatformService, PlatformServiceImpl};
+use crate::routing::{Maneuver, Route, RouteLeg, RouteRequest, Router};
+
+/// Router using the HTTP API of an [OSRM](https://project-osrm.org/) server.
+///
+/// ```no_run
+/// use galileo::routing::osrm::OsrmRouter;
+/// use galileo::routing::{RouteRequest, Router};
+/// use galileo_types::latlon;
+///
+/// # async fn route() -> Result<(), galileo::error::GalileoError> {
+/// let router = OsrmRouter::new("https://router.project-osrm.org", "driving");
+/// let route = router
+///     .route(&RouteRequest::new(latlon!(52.517, 13.388), latlon!(52.529, 13.397)))
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct OsrmRouter {
+    base_url: String,
+    profile: String,
+    platform_service: PlatformServiceImpl,
+}
+
+impl OsrmRouter {
+    /// Creates a new router for the server at `base_url` with the given routing profile (e.g. `driving`).
+    pub fn new(base_url: impl Into<String>, profile: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            profile: profile.into(),
+            platform_service: PlatformServiceImpl::new(),
+        }
+    }
+
+    fn url(&self, request: &RouteRequest) -> String {
+        let coordinates: Vec<String> = request
+            .points()
+            .iter()
+            .map(|point| format!("{},{}", point.lon(), point.lat()))
+            .collect();
+
+        format!(
+            "{}/route/v1/{}/{}?overview=full&geometries=geojson&steps=true",
+            self.base_url,
+            self.profile,
+            coordinates.join(";")
+        )
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+impl Router for OsrmRouter {
+    async fn route(&self, request: &RouteRequest) -> Result<Route, GalileoError> {
+        let bytes = self
+            .platform_service
+            .load_bytes_from_url(&self.url(request))
+            .await?;
+        let response: Value = serde_json::from_slice(&bytes)
+            .map_err(|err| GalileoError::Generic(format!("invalid OSRM response: {err}")))?;
+
+        parse_response(&response)
+    }
+}
+
+fn parse_response(response: &Value) -> Result<Route, GalileoError> {
+    let code = response["code"].as_str().unwrap_or_default();
+    if code != "Ok" {
+        let message = response["message"].as_str().unwrap_or(code);
+        return Err(GalileoError::Generic(format!(
+            "OSRM failed to find a route: {message}"
+        )));
+    }
+
+    let route = &response["routes"][0];
+    let geometry = route["geometry"]["coordinates"]
+        .as_array()
+        .ok_or_else(|| invalid_response("route geometry"))?
+        .iter()
+        .map(parse_position)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let legs = route["legs"]
+        .as_array()
+        .ok_or_else(|| invalid_response("route legs"))?
+        .iter()
+        .map(parse_leg)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Route {
+        geometry,
+        distance: route["distance"].as_f64().unwrap_or_default(),
+        duration: parse_duration(&route["duration"]),
+        legs,
+    })
+}
+
+fn parse_leg(leg: &Value) -> Result<RouteLeg, GalileoError> {
+    let maneuvers = leg["steps"]
+        .as_array()
+        .map(|steps| {
+            steps
+                .iter()
+                .map(parse_maneuver)
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .transpose()?
+        .unwrap_or_default();
+
+    Ok(RouteLeg {
+        distance: leg["distance"].as_f64().unwrap_or_default(),
+        duration: parse_duration(&leg["duration"]),
+        maneuvers,
+    })
+}
+
+fn parse_maneuver(step: &Value) -> Result<Maneuver, GalileoError> {
+    let maneuver = &step["maneuver"];
+    let kind = match (maneuver["type"].as_str(), maneuver["modifier"].as_str()) {
+        (Some(kind), Some(modifier)) => format!("{kind} {modifier}"),
+        (Some(kind), None) => kind.to_string(),
+        _ => return Err(invalid_response("maneuver type")),
+    };
+
+    Ok(Maneuver {
+        position: parse_position(&maneuver["location"])?,
+        kind,
+        instruction: step["name"]
+            .as_str()
+            .filter(|name| !name.is_empty())
+            .map(String::from),
+    })
+}
+
+fn parse_position(value: &Value) -> Result<GeoPoint2d, GalileoError> {
+    match (value[0].as_f64(), value[1].as_f64()) {
+        (Some(lon), Some(lat)) => Ok(GeoPoint2d::latlon(lat, lon)),
+        _ => Err(invalid_response("position")),
+    }
+}
+
+fn parse_duration(value: &Value) -> Duration {
+    Duration::from_secs_f64(value.as_f64().unwrap_or_default().max(0.0))
+}
+
+fn invalid_response(field: &str) -> GalileoError {
+    GalileoError::Generic(format!("invalid OSRM response: missing {field}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use galileo_types::latlon;
+
+    use super::*;
+
+    #[test]
+    fn route_url() {
+        let router = OsrmRouter::new("https://osrm.example.com/", "driving");
+        let request = RouteRequest::new(latlon!(52.5, 13.3), latlon!(52.6, 13.4))
+            .with_waypoint(latlon!(52.55, 13.35));
+
+        assert_eq!(
+            router.url(&request),
+            "https://osrm.example.com/route/v1/driving/13.3,52.5;13.35,52.55;13.4,52.6?overview=full&geometries=geojson&steps=true"
+        );
+    }
+
+    #[test]
+    fn parse_route() {
+        let response = serde_json::json!({
+            "code": "Ok",
+            "routes": [{
+                "distance": 1200.5,
+                "duration": 90.0,
+                "geometry": {"type": "LineString", "coordinates": [[13.3, 52.5], [13.4, 52.6]]},
+                "legs": [{
+                    "distance": 1200.5,
+                    "duration": 90.0,
+                    "steps": [
+                        {"name": "Unter den Linden", "maneuver": {"type": "depart", "location": [13.3, 52.5]}},
+                        {"name": "", "maneuver": {"type": "turn", "modifier": "left", "location": [13.4, 52.6]}}
+                    ]
+                }]
+            }]
+        });
+
+        let route = parse_response(&response).expect("valid response");
+        assert_eq!(
+            route.geometry,
+            vec![latlon!(52.5, 13.3), latlon!(52.6, 13.4)]
+        );
+        assert_eq!(route.duration, Duration::from_secs(90));
+        assert_eq!(route.legs[0].maneuvers.len(), 2);
+        assert_eq!(route.legs[0].maneuvers[1].kind, "turn left");
+        assert_eq!(
+            route.legs[0].maneuvers[0].instruction.as_deref(),
+            Some("Unter den Linden")
+        );
+        assert_eq!(route.legs[0].maneuvers[1].instruction, None);
+
+        let error = serde_json::json!({"code": "NoRoute", "message": "Impossible route"});
+        assert!(parse_response(&error).is_err());
+    }
+}
@@ -0,0 +1,282 @@
+//! Router using the HTTP API of [Valhalla](https://valhalla.github.io/valhalla/).
+
+use std::time::Duration;
+
+use galileo_types::geo::impls::GeoPoint2d;
+use galileo_types::geo::{GeoPoint, NewGeoPoint};
+use serde_json::{json, Value};
+
+use crate::error::GalileoError;
+use crate::platform::{PlatformService, PlatformServiceImpl};
+use crate::routing::{Maneuver, Route, RouteLeg, RouteRequest, Router};
+
+/// Valhalla encodes shapes as polylines with 6 digits precision.
+const SHAPE_PRECISION: f64 = 1e6;
+
+/// Router using the HTTP API of a [Valhalla](https://valhalla.github.io/valhalla/) server.
+///
+/// ```no_run
+/// use galileo::routing::valhalla::ValhallaRouter;
+/// use galileo::routing::{RouteRequest, Router};
+/// use galileo_types::latlon;
+///
+/// # async fn route() -> Result<(), galileo::error::GalileoError> {
+/// let router = ValhallaRouter::new("https://valhalla.example.com", "auto");
+/// let route = router
+///     .route(&RouteRequest::new(latlon!(52.517, 13.388), latlon!(52.529, 13.397)))
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ValhallaRouter {
+    base_url: String,
+    costing: String,
+    platform_service: PlatformServiceImpl,
+}
+
+impl ValhallaRouter {
+    /// Creates a new router for the server at `base_url` with the given costing model (e.g. `auto` or `bicycle`).
+    pub fn new(base_url: impl Into<String>, costing: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            costing: costing.into(),
+            platform_service: PlatformServiceImpl::new(),
+        }
+    }
+
+    fn url(&self, request: &RouteRequest) -> String {
+        let locations: Vec<Value> = request
+            .points()
+            .iter()
+            .map(|point| json!({"lat": point.lat(), "lon": point.lon()}))
+            .collect();
+        let body = json!({
+            "locations": locations,
+            "costing": self.costing,
+            "units": "kilometers",
+        });
+
+        format!(
+            "{}/route?json={}",
+            self.base_url,
+            percent_encode(&body.to_string())
+        )
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+impl Router for ValhallaRouter {
+    async fn route(&self, request: &RouteRequest) -> Result<Route, GalileoError> {
+        let bytes = self
+            .platform_service
+            .load_bytes_from_url(&self.url(request))
+            .await?;
+        let response: Value = serde_json::from_slice(&bytes)
+            .map_err(|err| GalileoError::Generic(format!("invalid Valhalla response: {err}")))?;
+
+        parse_response(&response)
+    }
+}
+
+fn parse_response(response: &Value) -> Result<Route, GalileoError> {
+    if let Some(message) = response["error"].as_str() {
+        return Err(GalileoError::Generic(format!(
+            "Valhalla failed to find a route: {message}"
+        )));
+    }
+
+    let trip = &response["trip"];
+    let mut geometry = vec![];
+    let mut legs = vec![];
+    for leg in trip["legs"]
+        .as_array()
+        .ok_or_else(|| invalid_response("trip legs"))?
+    {
+        let shape = decode_polyline(
+            leg["shape"]
+                .as_str()
+                .ok_or_else(|| invalid_response("leg shape"))?,
+        )?;
+
+        let maneuvers = leg["maneuvers"]
+            .as_array()
+            .map(|maneuvers| {
+                maneuvers
+                    .iter()
+                    .map(|maneuver| parse_maneuver(maneuver, &shape))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .transpose()?
+            .unwrap_or_default();
+
+        legs.push(RouteLeg {
+            distance: summary_distance(&leg["summary"]),
+            duration: summary_duration(&leg["summary"]),
+            maneuvers,
+        });
+
+        // Consecutive legs share the point between them
+        let skip = usize::from(geometry.last().is_some() && geometry.last() == shape.first());
+        geometry.extend(shape.into_iter().skip(skip));
+    }
+
+    Ok(Route {
+        geometry,
+        distance: summary_distance(&trip["summary"]),
+        duration: summary_duration(&trip["summary"]),
+        legs,
+    })
+}
+
+fn parse_maneuver(maneuver: &Value, shape: &[GeoPoint2d]) -> Result<Maneuver, GalileoError> {
+    let index = maneuver["begin_shape_index"]
+        .as_u64()
+        .ok_or_else(|| invalid_response("maneuver shape index"))? as usize;
+    let position = *shape
+        .get(index)
+        .ok_or_else(|| invalid_response("maneuver position"))?;
+
+    Ok(Maneuver {
+        position,
+        kind: maneuver["type"]
+            .as_u64()
+            .map(|kind| kind.to_string())
+            .unwrap_or_default(),
+        instruction: maneuver["instruction"].as_str().map(String::from),
+    })
+}
+
+fn summary_distance(summary: &Value) -> f64 {
+    summary["length"].as_f64().unwrap_or_default() * 1000.0
+}
+
+fn summary_duration(summary: &Value) -> Duration {
+    Duration::from_secs_f64(summary["time"].as_f64().unwrap_or_default().max(0.0))
+}
+
+/// Decodes a shape in the [encoded polyline format](https://developers.google.com/maps/documentation/utilities/polylinealgorithm).
+fn decode_polyline(encoded: &str) -> Result<Vec<GeoPoint2d>, GalileoError> {
+    let mut bytes = encoded.bytes();
+    let mut next_value = || -> Result<Option<i64>, GalileoError> {
+        let mut result = 0i64;
+        let mut shift = 0;
+        loop {
+            let Some(byte) = bytes.next() else {
+                return if shift == 0 {
+                    Ok(None)
+                } else {
+                    Err(invalid_response("valid shape"))
+                };
+            };
+
+            let chunk = i64::from(byte) - 63;
+            if !(0..64).contains(&chunk) || shift > 60 {
+                return Err(invalid_response("valid shape"));
+            }
+
+            result |= (chunk & 0x1f) << shift;
+            shift += 5;
+            if chunk < 0x20 {
+                break;
+            }
+        }
+
+        Ok(Some(if result & 1 == 1 {
+            !(result >> 1)
+        } else {
+            result >> 1
+        }))
+    };
+
+    let mut points = vec![];
+    let (mut lat, mut lon) = (0i64, 0i64);
+    while let Some(lat_delta) = next_value()? {
+        let lon_delta = next_value()?.ok_or_else(|| invalid_response("valid shape"))?;
+        lat += lat_delta;
+        lon += lon_delta;
+        points.push(GeoPoint2d::latlon(
+            lat as f64 / SHAPE_PRECISION,
+            lon as f64 / SHAPE_PRECISION,
+        ));
+    }
+
+    Ok(points)
+}
+
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+fn invalid_response(field: &str) -> GalileoError {
+    GalileoError::Generic(format!("invalid Valhalla response: missing {field}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use galileo_types::latlon;
+
+    use super::*;
+
+    #[test]
+    fn decode_shape() {
+        // Example from the polyline algorithm documentation, encoded with precision 6
+        let points = decode_polyline("_izlhA~rlgdF_{geC~ywl@_kwzCn`{nI").expect("valid shape");
+        assert_eq!(
+            points,
+            vec![
+                latlon!(38.5, -120.2),
+                latlon!(40.7, -120.95),
+                latlon!(43.252, -126.453)
+            ]
+        );
+
+        assert!(decode_polyline("_izlhA").is_err());
+    }
+
+    #[test]
+    fn parse_route() {
+        let response = serde_json::json!({
+            "trip": {
+                "summary": {"length": 1.5, "time": 120.0},
+                "legs": [{
+                    "shape": "_izlhA~rlgdF_{geC~ywl@_kwzCn`{nI",
+                    "summary": {"length": 1.5, "time": 120.0},
+                    "maneuvers": [
+                        {"type": 1, "instruction": "Drive north.", "begin_shape_index": 0},
+                        {"type": 4, "instruction": "You have arrived.", "begin_shape_index": 2}
+                    ]
+                }]
+            }
+        });
+
+        let route = parse_response(&response).expect("valid response");
+        assert_eq!(route.geometry.len(), 3);
+        assert_eq!(route.distance, 1500.0);
+        assert_eq!(route.duration, Duration::from_secs(120));
+        assert_eq!(
+            route.legs[0].maneuvers[1].position,
+            latlon!(43.252, -126.453)
+        );
+        assert_eq!(
+            route.legs[0].maneuvers[0].instruction.as_deref(),
+            Some("Drive north.")
+        );
+    }
+
+    #[test]
+    fn encode_url() {
+        let router = ValhallaRouter::new("https://valhalla.example.com/", "auto");
+        let url = router.url(&RouteRequest::new(latlon!(1.0, 2.0), latlon!(3.0, 4.0)));
+        assert!(url.starts_with("https://valhalla.example.com/route?json=%7B"));
+        assert!(!url.contains('"'));
+    }
+}
@@ -0,0 +1,46 @@
+//! Stable API for implementing custom layers in separate crates.
+//!
+//! Galileo is still young and its internals change often. This module collects the minimal set of types that a
+//! third-party layer (e.g. a heatmap or a terrain layer) needs, and these types are kept compatible across versions:
+//!
+//! * a breaking change to anything reexported here is made only together with the increase of [`SDK_VERSION`] and is
+//!   mentioned in the release notes;
+//! * new items (e.g. new types or trait methods with default implementations) can be added in any release.
+//!
+//! Everything outside of this module (concrete layers, data providers, renderer implementations) may change with any
+//! release, so layer crates should import only from `galileo::sdk` and do not need default features of Galileo:
+//!
+//! ```toml
+//! [dependencies]
+//! galileo = { version = "0.1", default-features = false }
+//! ```
+//!
+//! A layer implements the [`Layer`] trait. Data for the layer is loaded asynchronously in [`Layer::prepare`], and when
+//! it is ready the layer asks the map to redraw itself with the [`Messenger`]. In [`Layer::render`] the layer adds its
+//! primitives into a [`RenderBundle`] created by the [`Canvas`], packs the bundle and draws it. Packing is expensive,
+//! so layers with data that does not change every frame should keep the packed bundles between render calls. Layers
+//! that load data in tiles can use [`TileSchema`] to find the tiles needed for the current [`MapView`].
+//!
+//! See `custom_layer` example for a complete layer implemented only with this module.
+
+pub use galileo_types;
+pub use maybe_sync::{MaybeSend, MaybeSync};
+
+pub use crate::decoded_image::DecodedImage;
+pub use crate::error::GalileoError;
+pub use crate::layer::data_provider::TileSourceStats;
+pub use crate::layer::Layer;
+pub use crate::render::point_paint::PointPaint;
+pub use crate::render::render_bundle::{RenderBundle, RenderPrimitive};
+pub use crate::render::{
+    BlendMode, Canvas, ImagePaint, LineCap, LinePaint, PackedBundle, PolygonPaint, PrimitiveId,
+    RenderOptions, ResolutionScale, ScreenRefOrder,
+};
+pub use crate::tile_scheme::{TileIndex, TileSchema, VerticalDirection};
+pub use crate::{Color, Lod, MapView, Messenger};
+
+/// Version of the layer SDK. It is increased with every breaking change to the API of this module.
+///
+/// Layer crates can check it in a const assertion to fail the build with a clear message instead of a number of
+/// compilation errors when used with an incompatible version of Galileo.
+pub const SDK_VERSION: u32 = 1;
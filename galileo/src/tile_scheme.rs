@@ -109,16 +109,30 @@ impl TileSchema {
 
     /// Iterate over tile indices that should be displayed for the given map view.
     pub fn iter_tiles(&self, view: &MapView) -> Option<impl Iterator<Item = TileIndex>> {
+        self.iter_tiles_with_pixel_ratio(view, 1.0)
+    }
+
+    /// Iterate over tile indices that should be displayed for the given map view, if tile images
+    /// have `pixel_ratio` image pixels per one tile schema pixel.
+    ///
+    /// For example, `@2x` tiles have 512x512 pixel images for a tile schema with 256 pixel tiles.
+    /// Such tiles are sharp when displayed at twice the resolution of the tile level, so the
+    /// level of detail is selected for `view.resolution() * pixel_ratio`.
+    pub fn iter_tiles_with_pixel_ratio(
+        &self,
+        view: &MapView,
+        pixel_ratio: f64,
+    ) -> Option<impl Iterator<Item = TileIndex>> {
         if *view.crs() != self.crs {
             return None;
         }
 
-        let resolution = view.resolution();
+        let resolution = view.resolution() * pixel_ratio;
         let bounding_box = view.get_bbox()?;
         self.iter_tiles_over_bbox(resolution, bounding_box)
     }
 
-    fn iter_tiles_over_bbox(
+    pub(crate) fn iter_tiles_over_bbox(
         &self,
         resolution: f64,
         bounding_box: Rect,
@@ -433,6 +447,29 @@ mod tests {
         assert_eq!(schema.iter_tiles(&view).unwrap().count(), 16);
     }
 
+    #[test]
+    fn iter_tiles_with_pixel_ratio() {
+        let schema = simple_schema();
+        let bbox = Rect::new(0.0, 0.0, 2048.0, 2048.0);
+
+        let view = get_view(4.0, bbox);
+        assert_eq!(
+            schema
+                .iter_tiles_with_pixel_ratio(&view, 2.0)
+                .unwrap()
+                .count(),
+            1
+        );
+        for tile in schema.iter_tiles_with_pixel_ratio(&view, 2.0).unwrap() {
+            assert_eq!(tile.z, 0);
+        }
+
+        let view = get_view(2.0, bbox);
+        for tile in schema.iter_tiles_with_pixel_ratio(&view, 2.0).unwrap() {
+            assert_eq!(tile.z, 1);
+        }
+    }
+
     #[test]
     fn lod_over() {
         let schema = simple_schema();
@@ -0,0 +1,26 @@
+//! Helpers for instrumenting the crate with `tracing` spans. Without the `tracing` feature the macros expand to
+//! nothing, so the instrumentation has no cost.
+
+/// Enters a `tracing` span with the given name and fields for the rest of the current scope.
+///
+/// Must not be used in async code, where the span would stay entered across `.await` points. Use [`instrument!`]
+/// there instead.
+macro_rules! span {
+    ($($span:tt)+) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!($($span)+).entered();
+    };
+}
+
+/// Wraps the future so that a `tracing` span with the given name and fields is entered every time it is polled.
+macro_rules! instrument {
+    ($future:expr, $($span:tt)+) => {{
+        #[cfg(feature = "tracing")]
+        let future = tracing::Instrument::instrument($future, tracing::info_span!($($span)+));
+        #[cfg(not(feature = "tracing"))]
+        let future = $future;
+        future
+    }};
+}
+
+pub(crate) use {instrument, span};
@@ -1,11 +1,15 @@
-use galileo_types::cartesian::{CartesianPoint2d, Point2d, Rect, Size};
+use galileo_types::cartesian::{CartesianPoint2d, CartesianPoint3d, Point2d, Rect, Size};
 use galileo_types::geo::impls::GeoPoint2d;
-use galileo_types::geo::{Crs, GeoPoint};
+use galileo_types::geo::{Crs, Datum, GeoPoint};
 use nalgebra::{
     Matrix4, OMatrix, Perspective3, Point2, Point3, Rotation3, Scale3, Translation3, Vector2,
-    Vector3, U4,
+    Vector3, Vector4, U4,
 };
 
+/// Size of a logical pixel in meters used to calculate the map scale. This is the "standardized rendering pixel size"
+/// defined by the OGC WMTS specification.
+const PIXEL_SIZE_M: f64 = 0.00028;
+
 /// Map view specifies the area of the map that should be drawn. In other words, it sets the position of "camera" that
 /// looks at the map.
 ///
@@ -18,13 +22,14 @@ use nalgebra::{
 ///   drawn.
 ///
 /// The view can also specify rotation along *x* (tilt) and *z* (rotation) axis.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct MapView {
     projected_position: Option<Point3<f64>>,
     resolution: f64,
     rotation_x: f64,
     rotation_z: f64,
     size: Size,
+    dpi_scale_factor: f64,
     crs: Crs,
 }
 
@@ -46,6 +51,7 @@ impl MapView {
             rotation_z: 0.0,
             rotation_x: 0.0,
             size: Default::default(),
+            dpi_scale_factor: 1.0,
             crs,
         }
     }
@@ -67,6 +73,7 @@ impl MapView {
             rotation_z: 0.0,
             rotation_x: 0.0,
             size: Default::default(),
+            dpi_scale_factor: 1.0,
             crs,
         }
     }
@@ -129,6 +136,82 @@ impl MapView {
         }
     }
 
+    /// Number of physical pixels in a logical pixel of the screen (e.g. `2.0` for HiDPI displays).
+    ///
+    /// The size of the view is set in physical pixels. The scale factor is used to convert values given in logical
+    /// pixels, e.g. hit-test tolerance.
+    pub fn dpi_scale_factor(&self) -> f64 {
+        self.dpi_scale_factor
+    }
+
+    /// Creates a new view, same as the current one, but with the given DPI scale factor.
+    pub fn with_dpi_scale_factor(&self, dpi_scale_factor: f64) -> Self {
+        Self {
+            dpi_scale_factor,
+            crs: self.crs.clone(),
+            ..*self
+        }
+    }
+
+    /// Converts a distance in logical pixels around the given screen point into map units.
+    ///
+    /// Unlike multiplying the distance by the [resolution](Self::resolution), this takes into account the DPI scale
+    /// factor and the tilt of the map (resolution of a tilted map is different at different points of the screen).
+    /// If the distance is different along different axes, the largest value is returned.
+    ///
+    /// Returns `None` if the screen point is outside of the map.
+    pub fn screen_distance_to_map(&self, screen_point: Point2d, distance_px: f64) -> Option<f64> {
+        let distance = distance_px * self.dpi_scale_factor;
+        let center = self.screen_to_map(screen_point)?;
+
+        [
+            Point2d::new(screen_point.x + distance, screen_point.y),
+            Point2d::new(screen_point.x, screen_point.y + distance),
+        ]
+        .into_iter()
+        .map(|p| {
+            self.screen_to_map(p)
+                .map(|p| (p - center).norm())
+                .unwrap_or(f64::INFINITY)
+        })
+        .reduce(f64::max)
+    }
+
+    /// Cartographic scale denominator at the center of the view, i.e. the `N` in the `1:N` scale notation.
+    ///
+    /// The scale is calculated from the ground distance covered by a logical pixel at the center of the map, so it
+    /// takes into account the distortion of the CRS at the given latitude (e.g. in Web Mercator the scale at 60°
+    /// latitude is twice smaller than at the equator for the same resolution). The size of a logical pixel is taken
+    /// to be 0.28 mm as defined by the OGC standards.
+    ///
+    /// Returns `None` if the center of the view cannot be projected into geographic coordinates.
+    pub fn scale(&self) -> Option<f64> {
+        let center = Point2d::new(self.size.half_width(), self.size.half_height());
+        let offset = self.dpi_scale_factor;
+
+        let center_geo = self.screen_to_map_geo(center)?;
+        let horizontal = self.screen_to_map_geo(Point2d::new(center.x + offset, center.y))?;
+        let vertical = self.screen_to_map_geo(Point2d::new(center.x, center.y + offset))?;
+
+        // CRS can have different distortion along different axes, so the average is used
+        let pixel_ground_size = (ground_distance(&center_geo, &horizontal)
+            + ground_distance(&center_geo, &vertical))
+            / 2.0;
+
+        let scale = pixel_ground_size / PIXEL_SIZE_M;
+        (scale.is_finite() && scale > 0.0).then_some(scale)
+    }
+
+    /// Creates a new view, same as the current one, but with the resolution set so that the
+    /// [scale](Self::scale) at the center of the view equals `1:scale`. Since the distortion of the projection changes
+    /// slightly with the resolution, the resulting scale can differ from the requested one by a tiny fraction.
+    ///
+    /// Returns `None` if the scale of the current view cannot be calculated.
+    pub fn with_scale(&self, scale: f64) -> Option<Self> {
+        let current_scale = self.scale()?;
+        Some(self.with_resolution(self.resolution * scale / current_scale))
+    }
+
     /// Returns bounding rectangle of the view (in projected coordinates).
     pub fn get_bbox(&self) -> Option<Rect> {
         let points = [
@@ -244,6 +327,56 @@ impl MapView {
         }
     }
 
+    /// Distance from the camera to the center point of the map in map units.
+    pub fn camera_distance(&self) -> f64 {
+        self.size.half_height() * self.resolution
+    }
+
+    /// Position of the camera in map coordinates.
+    ///
+    /// The camera looks at the center point of the map from the [`MapView::camera_distance`]. When the map is tilted,
+    /// the camera moves away from the center towards the bottom side of the screen and goes down closer to the map.
+    pub fn camera_position(&self) -> Option<Point3<f64>> {
+        let position = self.projected_position?;
+        let distance = self.camera_distance();
+        let offset = Rotation3::new(Vector3::new(0.0, 0.0, -self.rotation_z))
+            * Vector3::new(
+                0.0,
+                -distance * self.rotation_x.sin(),
+                distance * self.rotation_x.cos(),
+            );
+
+        Some(position + offset)
+    }
+
+    /// Maximum tilt (rotation around *X* axis) at which the camera stays at least `min_height` map units above the
+    /// ground with the given `elevation`.
+    ///
+    /// Returns `0.0` if the camera is too low even when looking straight down, and `None` if the view has no position.
+    pub fn max_rotation_x_for_height(&self, elevation: f64, min_height: f64) -> Option<f64> {
+        let position = self.projected_position?;
+        let distance = self.camera_distance();
+        if distance <= 0.0 {
+            return Some(0.0);
+        }
+
+        let cos = (elevation + min_height - position.z) / distance;
+        Some(cos.clamp(0.0, 1.0).acos())
+    }
+
+    /// Vertical screen position of the horizon line in pixels from the top of the view.
+    ///
+    /// The value is negative if the horizon is above the visible area. Returns `None` if the map is not tilted, so it
+    /// has no horizon.
+    pub fn horizon_screen_y(&self) -> Option<f64> {
+        if self.rotation_x <= 0.0 || self.size.is_zero() {
+            return None;
+        }
+
+        // The field of view is 90 degrees, so the screen edge is at 45 degrees from the view direction
+        Some(self.size.half_height() * (1.0 - 1.0 / self.rotation_x.tan()))
+    }
+
     /// Projects the given screen point into map coordinates at the 0 elevation.
     ///
     /// Returns `None` if the point is outside of map (this can be possible, if the map is tilted and the point is
@@ -280,6 +413,25 @@ impl MapView {
         Some(Point2::new(transformed.x, transformed.y))
     }
 
+    /// Projects the given point in map coordinates onto the screen. This is the inverse of
+    /// [`MapView::screen_to_map`].
+    ///
+    /// Returns `None` if the view cannot be rendered, or the point is behind the camera.
+    pub fn map_to_screen(&self, point: &impl CartesianPoint3d<Num = f64>) -> Option<Point2d> {
+        let transform = self.map_to_scene_transform()?;
+        let projected = transform * Vector4::new(point.x(), point.y(), point.z(), 1.0);
+        if projected.w <= 0.0 {
+            return None;
+        }
+
+        let x = projected.x / projected.w;
+        let y = projected.y / projected.w;
+        Some(Point2d::new(
+            (x + 1.0) * self.size.half_width(),
+            (1.0 - y) * self.size.half_height(),
+        ))
+    }
+
     /// Projects the given screen point into map coordinates at the 0 elevation, and then projects them into
     /// geographic coordinates.
     ///
@@ -334,6 +486,32 @@ impl MapView {
         }
     }
 
+    /// Creates a new view, same as the current one, but moved by the given number of pixels.
+    ///
+    /// The map content moves in the direction of the delta, as if the map was dragged by the pointer: positive `dx`
+    /// moves the map to the right, positive `dy` moves the map down.
+    pub fn pan_by_pixels(&self, dx: f64, dy: f64) -> Self {
+        let from = Point2d::new(self.size.half_width(), self.size.half_height());
+        let to = Point2d::new(from.x + dx, from.y + dy);
+
+        let Some(from_projected) = self.screen_to_map(from) else {
+            return self.clone();
+        };
+        let Some(to_projected) = self.screen_to_map(to) else {
+            return self.clone();
+        };
+
+        self.translate((to_projected - from_projected).xy())
+    }
+
+    /// Creates a new view, same as the current one, but with resolution multiplied by `zoom`, keeping the map point
+    /// under the `screen_point` at the same position on the screen.
+    ///
+    /// Values of `zoom` less than 1 zoom the map in, values more than 1 zoom the map out.
+    pub fn zoom_around(&self, screen_point: Point2d, zoom: f64) -> Self {
+        self.zoom(zoom, screen_point)
+    }
+
     pub(crate) fn zoom(&self, zoom: f64, base_point: Point2d) -> Self {
         let base_point = self.screen_to_map(base_point);
         let resolution = self.resolution * zoom;
@@ -366,15 +544,27 @@ impl MapView {
         Self {
             projected_position: Some(projected_position),
             resolution: self.resolution + (target.resolution - self.resolution) * k,
+            rotation_x: self.rotation_x + (target.rotation_x - self.rotation_x) * k,
+            rotation_z: self.rotation_z + (target.rotation_z - self.rotation_z) * k,
             crs: self.crs.clone(),
             ..*self
         }
     }
 }
 
+/// Great circle distance in meters between two points on the WGS84 ellipsoid approximated by a sphere.
+fn ground_distance(a: &impl GeoPoint<Num = f64>, b: &impl GeoPoint<Num = f64>) -> f64 {
+    let d_lat = b.lat_rad() - a.lat_rad();
+    let d_lon = b.lon_rad() - a.lon_rad();
+    let h = (d_lat / 2.0).sin().powi(2)
+        + a.lat_rad().cos() * b.lat_rad().cos() * (d_lon / 2.0).sin().powi(2);
+
+    2.0 * Datum::WGS84.semimajor() * h.sqrt().asin()
+}
+
 #[cfg(test)]
 mod tests {
-    use approx::assert_abs_diff_eq;
+    use approx::{assert_abs_diff_eq, assert_relative_eq};
 
     use super::*;
 
@@ -382,6 +572,67 @@ mod tests {
         MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0)
     }
 
+    #[test]
+    fn map_to_screen_is_inverse_of_screen_to_map() {
+        let view = MapView::new_projected(&Point2d::new(100.0, -50.0), 2.0)
+            .with_size(Size::new(200.0, 100.0))
+            .with_rotation(0.5, 1.0);
+
+        for screen_point in [
+            Point2d::new(100.0, 50.0),
+            Point2d::new(30.0, 70.0),
+            Point2d::new(180.0, 20.0),
+        ] {
+            let map_point = view.screen_to_map(screen_point).unwrap();
+            let projected = view
+                .map_to_screen(&Point3::new(map_point.x, map_point.y, 0.0))
+                .unwrap();
+            assert_abs_diff_eq!(projected.x, screen_point.x, epsilon = 1e-6);
+            assert_abs_diff_eq!(projected.y, screen_point.y, epsilon = 1e-6);
+        }
+    }
+
+    #[test]
+    fn screen_distance_to_map() {
+        let view = MapView::new_projected(&Point2d::new(0.0, 0.0), 2.0)
+            .with_size(Size::new(100.0, 100.0))
+            .with_rotation_z(1.0);
+        assert_abs_diff_eq!(
+            view.screen_distance_to_map(Point2d::new(50.0, 50.0), 5.0)
+                .unwrap(),
+            10.0,
+            epsilon = 1e-6
+        );
+
+        let hidpi = view.with_dpi_scale_factor(2.0);
+        assert_abs_diff_eq!(
+            hidpi
+                .screen_distance_to_map(Point2d::new(50.0, 50.0), 5.0)
+                .unwrap(),
+            20.0,
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    fn horizon_screen_y() {
+        let view = test_view().with_size(Size::new(100.0, 100.0));
+        assert_eq!(view.horizon_screen_y(), None);
+
+        let tilted = view.with_rotation_x(std::f64::consts::FRAC_PI_3);
+        let horizon_y = tilted.horizon_screen_y().unwrap();
+        assert!(horizon_y > 0.0 && horizon_y < 50.0);
+        assert!(tilted
+            .screen_to_map(Point2d::new(50.0, horizon_y - 1.0))
+            .is_none());
+        assert!(tilted
+            .screen_to_map(Point2d::new(50.0, horizon_y + 1.0))
+            .is_some());
+
+        let slightly_tilted = view.with_rotation_x(0.1);
+        assert!(slightly_tilted.horizon_screen_y().unwrap() < 0.0);
+    }
+
     #[test]
     fn screen_to_map_size() {
         let view = test_view().with_size(Size::new(100.0, 100.0));
@@ -480,6 +731,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn pan_by_pixels() {
+        let view = test_view()
+            .with_resolution(2.0)
+            .with_size(Size::new(100.0, 100.0));
+        let panned = view.pan_by_pixels(10.0, 20.0);
+
+        assert_abs_diff_eq!(
+            panned.screen_to_map(Point2d::new(60.0, 70.0)).unwrap(),
+            Point2d::new(0.0, 0.0),
+            epsilon = 0.0001,
+        );
+    }
+
+    #[test]
+    fn zoom_around_keeps_base_point() {
+        let view = test_view().with_size(Size::new(100.0, 100.0));
+        let base_point = Point2d::new(20.0, 30.0);
+        let map_point = view.screen_to_map(base_point).unwrap();
+
+        let zoomed = view.zoom_around(base_point, 0.5);
+        assert_abs_diff_eq!(zoomed.resolution(), 0.5);
+        assert_abs_diff_eq!(
+            zoomed.screen_to_map(base_point).unwrap(),
+            map_point,
+            epsilon = 0.0001,
+        );
+    }
+
     #[test]
     fn map_to_scene() {
         let view = test_view().with_size(Size::new(100.0, 100.0));
@@ -503,4 +783,55 @@ mod tests {
             epsilon = 0.01
         );
     }
+
+    #[test]
+    fn scale() {
+        use galileo_types::geo::NewGeoPoint;
+
+        let view =
+            MapView::new(&GeoPoint2d::latlon(0.0, 0.0), 10.0).with_size(Size::new(100.0, 100.0));
+        assert_abs_diff_eq!(view.scale().unwrap(), 10.0 / PIXEL_SIZE_M, epsilon = 1.0);
+
+        // Web Mercator stretches the map twice at 60 degrees latitude
+        let view = view.with_position(&GeoPoint2d::latlon(60.0, 0.0));
+        assert_abs_diff_eq!(view.scale().unwrap(), 5.0 / PIXEL_SIZE_M, epsilon = 1.0);
+
+        // Scale is given for logical pixels
+        let hidpi = view.with_dpi_scale_factor(2.0);
+        assert_abs_diff_eq!(hidpi.scale().unwrap(), 10.0 / PIXEL_SIZE_M, epsilon = 1.0);
+
+        let scaled = view.with_scale(50_000.0).unwrap();
+        // Distortion of the projection depends on the resolution, so the scale is only approximately equal
+        assert_relative_eq!(scaled.scale().unwrap(), 50_000.0, max_relative = 1e-5);
+        assert_abs_diff_eq!(
+            scaled.resolution(),
+            50_000.0 * PIXEL_SIZE_M * 2.0,
+            epsilon = 1e-2
+        );
+
+        assert!(test_view().scale().is_none());
+    }
+
+    #[test]
+    fn camera_position_and_height_limit() {
+        let view = MapView::new_projected(&Point2d::new(100.0, 100.0), 2.0)
+            .with_size(Size::new(100.0, 100.0));
+        assert_abs_diff_eq!(view.camera_distance(), 100.0);
+
+        let camera = view.camera_position().expect("view has position");
+        assert_eq!(camera, Point3::new(100.0, 100.0, 100.0));
+
+        // Tilted camera moves to the south, rotated map moves it to the west
+        let tilted = view.with_rotation(std::f64::consts::FRAC_PI_6, std::f64::consts::FRAC_PI_2);
+        let camera = tilted.camera_position().expect("view has position");
+        assert_abs_diff_eq!(camera.x, 50.0, epsilon = 1e-9);
+        assert_abs_diff_eq!(camera.y, 100.0, epsilon = 1e-9);
+        assert_abs_diff_eq!(camera.z, 100.0 * 3f64.sqrt() / 2.0, epsilon = 1e-9);
+
+        let max_tilt = view
+            .max_rotation_x_for_height(40.0, 10.0)
+            .expect("view has position");
+        assert_abs_diff_eq!(max_tilt, std::f64::consts::FRAC_PI_3, epsilon = 1e-9);
+        assert_eq!(view.max_rotation_x_for_height(100.0, 10.0), Some(0.0));
+    }
 }
@@ -6,20 +6,46 @@ use galileo_types::cartesian::Point2d;
 use winit::event::{ElementState, MouseScrollDelta, Touch, TouchPhase, WindowEvent};
 use winit::window::Window;
 
-use crate::control::{MouseButton, RawUserEvent, TouchEvent};
+use crate::control::{InputFilter, MouseButton, RawUserEvent, TouchEvent};
 use crate::messenger::Messenger;
 
 /// Converts `winit` events into `Galileo` [`RawUserEvent`]s.
+///
+/// Only the event classes enabled by the [`InputFilter`] are converted. Keyboard, IME and clipboard events are never
+/// consumed, so they can always be given to other parts of the application.
 #[derive(Debug, Default)]
-pub struct WinitInputHandler {}
+pub struct WinitInputHandler {
+    filter: InputFilter,
+}
 
 impl WinitInputHandler {
-    /// Convert `winit` event into `Galileo` event.
+    /// Creates a new handler that converts only the events accepted by the filter.
+    pub fn with_filter(filter: InputFilter) -> Self {
+        Self { filter }
+    }
+
+    /// Filter of the events consumed by the map.
+    pub fn filter(&self) -> InputFilter {
+        self.filter
+    }
+
+    /// Sets the filter of the events consumed by the map.
+    pub fn set_filter(&mut self, filter: InputFilter) {
+        self.filter = filter;
+    }
+
+    /// Convert `winit` event into `Galileo` event. Returns `None` if the event is not used by the map, in which case
+    /// the application can handle it in some other way.
     pub fn process_user_input(
         &mut self,
         winit_event: &WindowEvent,
         scale: f64,
     ) -> Option<RawUserEvent> {
+        self.convert(winit_event, scale)
+            .filter(|event| self.filter.accepts(event))
+    }
+
+    fn convert(&mut self, winit_event: &WindowEvent, scale: f64) -> Option<RawUserEvent> {
         match winit_event {
             WindowEvent::MouseInput { button, state, .. } => match state {
                 ElementState::Pressed => Some(RawUserEvent::ButtonPressed(button.into())),